@@ -17,6 +17,7 @@ impl OpenMlsProvider for OpenMlsRustPersistentCrypto {
     type CryptoProvider = RustCrypto;
     type RandProvider = RustCrypto;
     type KeyStoreProvider = PersistentKeyStore;
+    type ClockProvider = RustCrypto;
 
     fn crypto(&self) -> &Self::CryptoProvider {
         &self.crypto
@@ -29,6 +30,10 @@ impl OpenMlsProvider for OpenMlsRustPersistentCrypto {
     fn key_store(&self) -> &Self::KeyStoreProvider {
         &self.key_store
     }
+
+    fn clock(&self) -> &Self::ClockProvider {
+        &self.crypto
+    }
 }
 
 impl OpenMlsRustPersistentCrypto {
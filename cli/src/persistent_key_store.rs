@@ -1,4 +1,4 @@
-use openmls_traits::key_store::{MlsEntity, OpenMlsKeyStore};
+use openmls_traits::key_store::{unwrap_blob, BlobHeader, MlsEntity, OpenMlsKeyStore};
 use serde::{Deserialize, Serialize};
 use std::{
     collections::HashMap,
@@ -31,6 +31,7 @@ impl OpenMlsKeyStore for PersistentKeyStore {
     fn store<V: MlsEntity>(&self, k: &[u8], v: &V) -> Result<(), Self::Error> {
         let value =
             serde_json::to_vec(v).map_err(|_| PersistentKeyStoreError::SerializationError)?;
+        let value = BlobHeader::for_entity::<V>().wrap(&value);
         // We unwrap here, because this is the only function claiming a write
         // lock on `credential_bundles`. It only holds the lock very briefly and
         // should not panic during that period.
@@ -49,7 +50,7 @@ impl OpenMlsKeyStore for PersistentKeyStore {
         // hold the lock very briefly and should not panic during that period.
         let values = self.values.read().unwrap();
         if let Some(value) = values.get(k) {
-            serde_json::from_slice(value).ok()
+            serde_json::from_slice(unwrap_blob::<V>(value)?).ok()
         } else {
             None
         }
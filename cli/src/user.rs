@@ -581,7 +581,8 @@ impl User {
                 &self.identity.borrow().signer,
                 &[joiner_key_package.into()],
             )
-            .map_err(|e| format!("Failed to add member to group - {e}"))?;
+            .map_err(|e| format!("Failed to add member to group - {e}"))?
+            .into_parts();
 
         /* First, send the MlsMessage commit to the group.
         This must be done before the member invitation is locally committed.
@@ -635,7 +636,8 @@ impl User {
             .mls_group
             .borrow_mut()
             .remove_members(&self.crypto, &self.identity.borrow().signer, &[leaf_index])
-            .map_err(|e| format!("Failed to remove member from group - {e}"))?;
+            .map_err(|e| format!("Failed to remove member from group - {e}"))?
+            .into_parts();
 
         // First, send the MlsMessage remove commit to the group.
         log::trace!("Sending commit");
@@ -281,7 +281,8 @@ async fn test_group() {
     // locally.)
     let (_out_messages, welcome_msg, _group_info) = group
         .add_members(crypto, &signer_1, &[client2_key_package.into()])
-        .expect("Could not add member to group.");
+        .expect("Could not add member to group.")
+        .into_parts();
     group
         .merge_pending_commit(crypto)
         .expect("error merging pending commit");
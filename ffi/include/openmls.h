@@ -0,0 +1,314 @@
+#ifndef OPENMLS_H
+#define OPENMLS_H
+
+/* This file is generated with cbindgen. Do not edit it manually. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * The kind of a message processed with [`openmls_group_process_message()`].
+ */
+typedef enum OpenMlsMessageKind {
+  /**
+   * An application message. The plaintext is returned.
+   */
+  OPEN_MLS_MESSAGE_KIND_APPLICATION = 0,
+  /**
+   * A proposal. It has been stored and is committed by the next commit.
+   */
+  OPEN_MLS_MESSAGE_KIND_PROPOSAL = 1,
+  /**
+   * A commit. It has been merged and the group is in the next epoch.
+   */
+  OPEN_MLS_MESSAGE_KIND_COMMIT = 2,
+} OpenMlsMessageKind;
+
+/**
+ * The status code returned by every fallible function of the API.
+ *
+ * The values are stable. New status codes are only ever appended.
+ */
+typedef enum OpenMlsStatus {
+  /**
+   * The function was successful.
+   */
+  OPEN_MLS_STATUS_OK = 0,
+  /**
+   * A required pointer was null.
+   */
+  OPEN_MLS_STATUS_NULL_POINTER = 1,
+  /**
+   * An argument is invalid, e.g. an unsupported ciphersuite or an unknown
+   * member.
+   */
+  OPEN_MLS_STATUS_INVALID_ARGUMENT = 2,
+  /**
+   * The input bytes could not be decoded.
+   */
+  OPEN_MLS_STATUS_DECODING_ERROR = 3,
+  /**
+   * A value could not be encoded.
+   */
+  OPEN_MLS_STATUS_ENCODING_ERROR = 4,
+  /**
+   * The requested value was not found.
+   */
+  OPEN_MLS_STATUS_NOT_FOUND = 5,
+  /**
+   * A message, key package or welcome was rejected by the validation.
+   */
+  OPEN_MLS_STATUS_INVALID_MESSAGE = 6,
+  /**
+   * The operation is not possible in the current state of the group, e.g.
+   * because a commit is pending or the group is inactive.
+   */
+  OPEN_MLS_STATUS_GROUP_STATE_ERROR = 7,
+  /**
+   * Accessing the key store failed.
+   */
+  OPEN_MLS_STATUS_KEY_STORE_ERROR = 8,
+  /**
+   * A cryptographic operation failed.
+   */
+  OPEN_MLS_STATUS_CRYPTO_ERROR = 9,
+  /**
+   * An internal error occurred. This is a bug in OpenMLS.
+   */
+  OPEN_MLS_STATUS_LIBRARY_ERROR = 10,
+  /**
+   * A panic occurred. This is a bug in OpenMLS.
+   */
+  OPEN_MLS_STATUS_PANIC = 11,
+} OpenMlsStatus;
+
+/**
+ * The group handle (`OpenMlsGroup` in C).
+ */
+typedef struct OpenMlsGroup OpenMlsGroup;
+
+/**
+ * The identity handle (`OpenMlsIdentity` in C).
+ *
+ * A basic credential with its signature key pair. The identity is bound to a
+ * ciphersuite, which is used for its key packages and the groups it creates.
+ */
+typedef struct OpenMlsIdentity OpenMlsIdentity;
+
+/**
+ * The provider handle (`OpenMlsProvider` in C).
+ *
+ * It uses the RustCrypto crypto provider and an in-memory key store that can
+ * be persisted with [`openmls_provider_save()`].
+ */
+typedef struct OpenMlsProvider OpenMlsProvider;
+
+/**
+ * A byte string returned by the API.
+ *
+ * The buffer is owned by the caller and has to be freed with
+ * [`openmls_buffer_free()`].
+ */
+typedef struct OpenMlsBuffer {
+  /**
+   * Pointer to the bytes, or null if the buffer is empty.
+   */
+  uint8_t *data;
+  /**
+   * The number of bytes.
+   */
+  uintptr_t len;
+} OpenMlsBuffer;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Free a buffer returned by the API.
+ */
+void openmls_buffer_free(struct OpenMlsBuffer buffer);
+
+/**
+ * Get a description of the status code `status`.
+ *
+ * The returned string is statically allocated and must not be freed.
+ */
+const char *openmls_status_message(enum OpenMlsStatus status);
+
+/**
+ * Create a new group with the ID `group_id` and `identity` as the only member
+ * and write it to `out_group`.
+ */
+enum OpenMlsStatus openmls_group_new(const OpenMlsProvider *provider,
+                                     const OpenMlsIdentity *identity,
+                                     const uint8_t *group_id,
+                                     uintptr_t group_id_len,
+                                     OpenMlsGroup **out_group);
+
+/**
+ * Join a group with the `welcome` message and write it to `out_group`.
+ */
+enum OpenMlsStatus openmls_group_join(const OpenMlsProvider *provider,
+                                      const uint8_t *welcome,
+                                      uintptr_t welcome_len,
+                                      OpenMlsGroup **out_group);
+
+/**
+ * Add the member with the given `key_package` to the group. The commit is
+ * written to `out_commit` and the welcome for the new member to
+ * `out_welcome`.
+ */
+enum OpenMlsStatus openmls_group_add_member(OpenMlsGroup *group,
+                                            const OpenMlsProvider *provider,
+                                            const OpenMlsIdentity *identity,
+                                            const uint8_t *key_package,
+                                            uintptr_t key_package_len,
+                                            struct OpenMlsBuffer *out_commit,
+                                            struct OpenMlsBuffer *out_welcome);
+
+/**
+ * Remove the member at `leaf_index` from the group. The commit is written to
+ * `out_commit`.
+ */
+enum OpenMlsStatus openmls_group_remove_member(OpenMlsGroup *group,
+                                               const OpenMlsProvider *provider,
+                                               const OpenMlsIdentity *identity,
+                                               uint32_t leaf_index,
+                                               struct OpenMlsBuffer *out_commit);
+
+/**
+ * Merge the pending commit of the group.
+ */
+enum OpenMlsStatus openmls_group_merge_pending_commit(OpenMlsGroup *group,
+                                                      const OpenMlsProvider *provider);
+
+/**
+ * Encrypt the application message `plaintext` and write the resulting
+ * `MLSMessage` to `out_message`.
+ */
+enum OpenMlsStatus openmls_group_encrypt(OpenMlsGroup *group,
+                                         const OpenMlsProvider *provider,
+                                         const OpenMlsIdentity *identity,
+                                         const uint8_t *plaintext,
+                                         uintptr_t plaintext_len,
+                                         struct OpenMlsBuffer *out_message);
+
+/**
+ * Process the `MLSMessage` `message` and write its kind to `out_kind`.
+ */
+enum OpenMlsStatus openmls_group_process_message(OpenMlsGroup *group,
+                                                 const OpenMlsProvider *provider,
+                                                 const uint8_t *message,
+                                                 uintptr_t message_len,
+                                                 enum OpenMlsMessageKind *out_kind,
+                                                 struct OpenMlsBuffer *out_plaintext);
+
+/**
+ * Write the current epoch of the group to `out_epoch`.
+ */
+enum OpenMlsStatus openmls_group_epoch(const OpenMlsGroup *group, uint64_t *out_epoch);
+
+/**
+ * Write the leaf index of the own member to `out_leaf_index`. Other members
+ * use it to remove this member.
+ */
+enum OpenMlsStatus openmls_group_own_leaf_index(const OpenMlsGroup *group,
+                                                uint32_t *out_leaf_index);
+
+/**
+ * Write whether the own member is still part of the group to `out_active`.
+ */
+enum OpenMlsStatus openmls_group_is_active(const OpenMlsGroup *group, bool *out_active);
+
+/**
+ * Save the group in the provider.
+ */
+enum OpenMlsStatus openmls_group_save(OpenMlsGroup *group, const OpenMlsProvider *provider);
+
+/**
+ * Load the group with the ID `group_id` from the provider and write it to
+ * `out_group`.
+ */
+enum OpenMlsStatus openmls_group_load(const OpenMlsProvider *provider,
+                                      const uint8_t *group_id,
+                                      uintptr_t group_id_len,
+                                      OpenMlsGroup **out_group);
+
+/**
+ * Free a group. `group` may be null.
+ */
+void openmls_group_free(OpenMlsGroup *group);
+
+/**
+ * Create a new identity with a basic credential for `identity` and write it
+ * to `out_identity`. `ciphersuite` is the IANA value of the ciphersuite,
+ * e.g. `1` for `MLS_128_DHKEMX25519_AES128GCM_SHA256_Ed25519`.
+ */
+enum OpenMlsStatus openmls_identity_new(const OpenMlsProvider *provider,
+                                        uint16_t ciphersuite,
+                                        const uint8_t *identity,
+                                        uintptr_t identity_len,
+                                        OpenMlsIdentity **out_identity);
+
+/**
+ * Export the identity, including its private signature key, to
+ * `out_identity`. The application has to store it securely.
+ */
+enum OpenMlsStatus openmls_identity_save(const OpenMlsIdentity *identity,
+                                         struct OpenMlsBuffer *out_identity);
+
+/**
+ * Load an identity exported with [`openmls_identity_save()`] and write it to
+ * `out_identity`.
+ */
+enum OpenMlsStatus openmls_identity_load(const uint8_t *identity,
+                                         uintptr_t identity_len,
+                                         OpenMlsIdentity **out_identity);
+
+/**
+ * Free an identity. `identity` may be null.
+ */
+void openmls_identity_free(OpenMlsIdentity *identity);
+
+/**
+ * Create a new key package for `identity` and write it to `out_key_package`
+ * as `MLSMessage`. The private keys of the key package are stored in the
+ * provider.
+ */
+enum OpenMlsStatus openmls_key_package_new(const OpenMlsProvider *provider,
+                                           const OpenMlsIdentity *identity,
+                                           struct OpenMlsBuffer *out_key_package);
+
+/**
+ * Create a new provider with an empty key store.
+ */
+OpenMlsProvider *openmls_provider_new(void);
+
+/**
+ * Export the state of the provider, i.e., the contents of its key store, to
+ * `out_state`.
+ */
+enum OpenMlsStatus openmls_provider_save(const OpenMlsProvider *provider,
+                                         struct OpenMlsBuffer *out_state);
+
+/**
+ * Create a provider from a state exported with [`openmls_provider_save()`]
+ * and write it to `out_provider`.
+ */
+enum OpenMlsStatus openmls_provider_load(const uint8_t *state,
+                                         uintptr_t state_len,
+                                         OpenMlsProvider **out_provider);
+
+/**
+ * Free a provider. `provider` may be null.
+ */
+void openmls_provider_free(OpenMlsProvider *provider);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* OPENMLS_H */
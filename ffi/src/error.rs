@@ -0,0 +1,215 @@
+//! Status codes of the FFI.
+
+use std::ffi::{c_char, CStr};
+
+use openmls::{error::LibraryError, framing::errors::MlsMessageError, prelude::*};
+
+/// The status code returned by every fallible function of the API.
+///
+/// The values are stable. New status codes are only ever appended.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpenMlsStatus {
+    /// The function was successful.
+    Ok = 0,
+    /// A required pointer was null.
+    NullPointer = 1,
+    /// An argument is invalid, e.g. an unsupported ciphersuite or an unknown
+    /// member.
+    InvalidArgument = 2,
+    /// The input bytes could not be decoded.
+    DecodingError = 3,
+    /// A value could not be encoded.
+    EncodingError = 4,
+    /// The requested value was not found.
+    NotFound = 5,
+    /// A message, key package or welcome was rejected by the validation.
+    InvalidMessage = 6,
+    /// The operation is not possible in the current state of the group, e.g.
+    /// because a commit is pending or the group is inactive.
+    GroupStateError = 7,
+    /// Accessing the key store failed.
+    KeyStoreError = 8,
+    /// A cryptographic operation failed.
+    CryptoError = 9,
+    /// An internal error occurred. This is a bug in OpenMLS.
+    LibraryError = 10,
+    /// A panic occurred. This is a bug in OpenMLS.
+    Panic = 11,
+}
+
+impl OpenMlsStatus {
+    fn message(self) -> &'static CStr {
+        let message: &'static [u8] = match self {
+            Self::Ok => b"The function was successful.\0",
+            Self::NullPointer => b"A required pointer was null.\0",
+            Self::InvalidArgument => b"An argument is invalid.\0",
+            Self::DecodingError => b"The input bytes could not be decoded.\0",
+            Self::EncodingError => b"A value could not be encoded.\0",
+            Self::NotFound => b"The requested value was not found.\0",
+            Self::InvalidMessage => b"The input was rejected by the validation.\0",
+            Self::GroupStateError => {
+                b"The operation is not possible in the current state of the group.\0"
+            }
+            Self::KeyStoreError => b"Accessing the key store failed.\0",
+            Self::CryptoError => b"A cryptographic operation failed.\0",
+            Self::LibraryError => b"An internal error occurred.\0",
+            Self::Panic => b"A panic occurred.\0",
+        };
+        // The messages are nul terminated and don't contain interior nul bytes.
+        CStr::from_bytes_with_nul(message).unwrap_or_default()
+    }
+}
+
+/// Get a description of the status code `status`.
+///
+/// The returned string is statically allocated and must not be freed.
+#[no_mangle]
+pub extern "C" fn openmls_status_message(status: OpenMlsStatus) -> *const c_char {
+    status.message().as_ptr()
+}
+
+impl From<LibraryError> for OpenMlsStatus {
+    fn from(_: LibraryError) -> Self {
+        Self::LibraryError
+    }
+}
+
+impl From<tls_codec::Error> for OpenMlsStatus {
+    fn from(_: tls_codec::Error) -> Self {
+        Self::DecodingError
+    }
+}
+
+impl From<MlsMessageError> for OpenMlsStatus {
+    fn from(_: MlsMessageError) -> Self {
+        Self::EncodingError
+    }
+}
+
+impl From<MlsGroupStateError> for OpenMlsStatus {
+    fn from(error: MlsGroupStateError) -> Self {
+        match error {
+            MlsGroupStateError::LibraryError(_) => Self::LibraryError,
+            _ => Self::GroupStateError,
+        }
+    }
+}
+
+impl From<KeyPackageVerifyError> for OpenMlsStatus {
+    fn from(error: KeyPackageVerifyError) -> Self {
+        match error {
+            KeyPackageVerifyError::LibraryError(_) => Self::LibraryError,
+            _ => Self::InvalidMessage,
+        }
+    }
+}
+
+impl<KeyStoreError> From<KeyPackageNewError<KeyStoreError>> for OpenMlsStatus {
+    fn from(error: KeyPackageNewError<KeyStoreError>) -> Self {
+        match error {
+            KeyPackageNewError::LibraryError(_) => Self::LibraryError,
+            KeyPackageNewError::CiphersuiteSignatureSchemeMismatch => Self::InvalidArgument,
+            KeyPackageNewError::KeyStoreError(_) => Self::KeyStoreError,
+            KeyPackageNewError::SignatureError(_) => Self::CryptoError,
+        }
+    }
+}
+
+impl<KeyStoreError> From<NewGroupError<KeyStoreError>> for OpenMlsStatus {
+    fn from(error: NewGroupError<KeyStoreError>) -> Self {
+        match error {
+            NewGroupError::LibraryError(_) => Self::LibraryError,
+            NewGroupError::KeyStoreError(_) => Self::KeyStoreError,
+            _ => Self::InvalidArgument,
+        }
+    }
+}
+
+impl<KeyStoreError> From<WelcomeError<KeyStoreError>> for OpenMlsStatus {
+    fn from(error: WelcomeError<KeyStoreError>) -> Self {
+        match error {
+            WelcomeError::LibraryError(_) => Self::LibraryError,
+            WelcomeError::KeyStoreError(_) => Self::KeyStoreError,
+            // The welcome is not for any of our key packages.
+            WelcomeError::NoMatchingKeyPackage | WelcomeError::NoMatchingEncryptionKey => {
+                Self::NotFound
+            }
+            _ => Self::InvalidMessage,
+        }
+    }
+}
+
+impl<KeyStoreError> From<CreateCommitError<KeyStoreError>> for OpenMlsStatus {
+    fn from(error: CreateCommitError<KeyStoreError>) -> Self {
+        match error {
+            CreateCommitError::LibraryError(_) => Self::LibraryError,
+            CreateCommitError::KeyStoreError(_) => Self::KeyStoreError,
+            CreateCommitError::KeyPackageGenerationError(error) => error.into(),
+            CreateCommitError::SignatureError(_) => Self::CryptoError,
+            _ => Self::InvalidArgument,
+        }
+    }
+}
+
+impl<KeyStoreError> From<AddMembersError<KeyStoreError>> for OpenMlsStatus {
+    fn from(error: AddMembersError<KeyStoreError>) -> Self {
+        match error {
+            AddMembersError::LibraryError(_) => Self::LibraryError,
+            AddMembersError::EmptyInput(_) => Self::InvalidArgument,
+            AddMembersError::CreateCommitError(error) => error.into(),
+            AddMembersError::GroupStateError(error) => error.into(),
+        }
+    }
+}
+
+impl<KeyStoreError> From<RemoveMembersError<KeyStoreError>> for OpenMlsStatus {
+    fn from(error: RemoveMembersError<KeyStoreError>) -> Self {
+        match error {
+            RemoveMembersError::LibraryError(_) => Self::LibraryError,
+            RemoveMembersError::EmptyInput(_) | RemoveMembersError::UnknownMember => {
+                Self::InvalidArgument
+            }
+            RemoveMembersError::CreateCommitError(error) => error.into(),
+            RemoveMembersError::GroupStateError(error) => error.into(),
+        }
+    }
+}
+
+impl<KeyStoreError> From<MergeCommitError<KeyStoreError>> for OpenMlsStatus {
+    fn from(error: MergeCommitError<KeyStoreError>) -> Self {
+        match error {
+            MergeCommitError::LibraryError(_) => Self::LibraryError,
+            MergeCommitError::KeyStoreError(_) => Self::KeyStoreError,
+            MergeCommitError::GroupStateError(error) => error.into(),
+        }
+    }
+}
+
+impl<KeyStoreError> From<MergePendingCommitError<KeyStoreError>> for OpenMlsStatus {
+    fn from(error: MergePendingCommitError<KeyStoreError>) -> Self {
+        match error {
+            MergePendingCommitError::MlsGroupStateError(error) => error.into(),
+            MergePendingCommitError::MergeCommitError(error) => error.into(),
+        }
+    }
+}
+
+impl From<CreateMessageError> for OpenMlsStatus {
+    fn from(error: CreateMessageError) -> Self {
+        match error {
+            CreateMessageError::LibraryError(_) => Self::LibraryError,
+            CreateMessageError::GroupStateError(error) => error.into(),
+        }
+    }
+}
+
+impl From<ProcessMessageError> for OpenMlsStatus {
+    fn from(error: ProcessMessageError) -> Self {
+        match error {
+            ProcessMessageError::LibraryError(_) => Self::LibraryError,
+            ProcessMessageError::GroupStateError(error) => error.into(),
+            _ => Self::InvalidMessage,
+        }
+    }
+}
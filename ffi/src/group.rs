@@ -0,0 +1,436 @@
+//! The group handle.
+
+use openmls::prelude::{config::CryptoConfig, *};
+use openmls_traits::OpenMlsProvider;
+use tls_codec::Deserialize;
+
+use crate::{
+    check_out, ffi_call, handle, handle_mut, input, write_out, Identity, OpenMlsBuffer,
+    OpenMlsStatus, Provider,
+};
+
+/// The group handle (`OpenMlsGroup` in C).
+#[derive(Debug)]
+pub struct Group {
+    group: MlsGroup,
+}
+
+/// The kind of a message processed with [`openmls_group_process_message()`].
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpenMlsMessageKind {
+    /// An application message. The plaintext is returned.
+    Application = 0,
+    /// A proposal. It has been stored and is committed by the next commit.
+    Proposal = 1,
+    /// A commit. It has been merged and the group is in the next epoch.
+    Commit = 2,
+}
+
+/// The configuration of groups created through the FFI. The ratchet tree is
+/// included in welcome messages, so that they can be processed on their own.
+fn group_config(ciphersuite: Ciphersuite) -> MlsGroupConfig {
+    MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .use_ratchet_tree_extension(true)
+        .build()
+}
+
+/// Decode an `MLSMessage`.
+fn decode_message(message: &[u8]) -> Result<MlsMessageInBody, OpenMlsStatus> {
+    Ok(MlsMessageIn::tls_deserialize_exact(message)?.extract())
+}
+
+fn into_group(group: MlsGroup) -> *mut Group {
+    Box::into_raw(Box::new(Group { group }))
+}
+
+/// Create a new group with the ID `group_id` and `identity` as the only member
+/// and write it to `out_group`.
+///
+/// The group has to be freed with [`openmls_group_free()`].
+///
+/// # Safety
+///
+/// `provider` and `identity` must be valid handles, `group_id` must be valid
+/// for reads of `group_id_len` bytes and `out_group` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn openmls_group_new(
+    provider: *const Provider,
+    identity: *const Identity,
+    group_id: *const u8,
+    group_id_len: usize,
+    out_group: *mut *mut Group,
+) -> OpenMlsStatus {
+    ffi_call(|| {
+        let provider = handle(provider)?;
+        let identity = handle(identity)?;
+        let group_id = input(group_id, group_id_len)?;
+        check_out(out_group)?;
+
+        let group = MlsGroup::new_with_group_id(
+            provider,
+            &identity.signer,
+            &group_config(identity.ciphersuite),
+            GroupId::from_slice(group_id),
+            identity.credential_with_key.clone(),
+        )?;
+        write_out(out_group, into_group(group));
+        Ok(())
+    })
+}
+
+/// Join a group with the `welcome` message and write it to `out_group`. The
+/// welcome must have been created for a key package created with
+/// [`openmls_key_package_new()`](crate::openmls_key_package_new()) and the same
+/// provider.
+///
+/// The group has to be freed with [`openmls_group_free()`].
+///
+/// # Safety
+///
+/// `provider` must be a valid provider handle, `welcome` must be valid for
+/// reads of `welcome_len` bytes and `out_group` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn openmls_group_join(
+    provider: *const Provider,
+    welcome: *const u8,
+    welcome_len: usize,
+    out_group: *mut *mut Group,
+) -> OpenMlsStatus {
+    ffi_call(|| {
+        let provider = handle(provider)?;
+        let welcome = input(welcome, welcome_len)?;
+        check_out(out_group)?;
+
+        let welcome = match decode_message(welcome)? {
+            MlsMessageInBody::Welcome(welcome) => welcome,
+            _ => return Err(OpenMlsStatus::InvalidArgument),
+        };
+        // The ciphersuite is taken from the welcome.
+        let config = MlsGroupConfig::builder()
+            .use_ratchet_tree_extension(true)
+            .build();
+        let group = MlsGroup::new_from_welcome(provider, &config, welcome, None)?;
+        write_out(out_group, into_group(group));
+        Ok(())
+    })
+}
+
+/// Add the member with the given `key_package` to the group. The commit is
+/// written to `out_commit` and the welcome for the new member to
+/// `out_welcome`.
+///
+/// The commit is pending until it is merged with
+/// [`openmls_group_merge_pending_commit()`], which should happen once the
+/// delivery service accepted it.
+///
+/// # Safety
+///
+/// `group`, `provider` and `identity` must be valid handles, `key_package`
+/// must be valid for reads of `key_package_len` bytes and `out_commit` and
+/// `out_welcome` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn openmls_group_add_member(
+    group: *mut Group,
+    provider: *const Provider,
+    identity: *const Identity,
+    key_package: *const u8,
+    key_package_len: usize,
+    out_commit: *mut OpenMlsBuffer,
+    out_welcome: *mut OpenMlsBuffer,
+) -> OpenMlsStatus {
+    ffi_call(|| {
+        let group = handle_mut(group)?;
+        let provider = handle(provider)?;
+        let identity = handle(identity)?;
+        let key_package = input(key_package, key_package_len)?;
+        check_out(out_commit)?;
+        check_out(out_welcome)?;
+
+        let key_package = match decode_message(key_package)? {
+            MlsMessageInBody::KeyPackage(key_package) => {
+                key_package.validate(provider.crypto(), ProtocolVersion::Mls10, provider.clock())?
+            }
+            _ => return Err(OpenMlsStatus::InvalidArgument),
+        };
+        let (commit, welcome, _group_info) = group
+            .group
+            .add_members(provider, &identity.signer, &[key_package])?
+            .into_parts();
+        let commit = commit.to_bytes()?;
+        let welcome = welcome.to_bytes()?;
+        write_out(out_commit, commit.into());
+        write_out(out_welcome, welcome.into());
+        Ok(())
+    })
+}
+
+/// Remove the member at `leaf_index` from the group. The commit is written to
+/// `out_commit`.
+///
+/// The commit is pending until it is merged with
+/// [`openmls_group_merge_pending_commit()`].
+///
+/// # Safety
+///
+/// `group`, `provider` and `identity` must be valid handles and `out_commit`
+/// must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn openmls_group_remove_member(
+    group: *mut Group,
+    provider: *const Provider,
+    identity: *const Identity,
+    leaf_index: u32,
+    out_commit: *mut OpenMlsBuffer,
+) -> OpenMlsStatus {
+    ffi_call(|| {
+        let group = handle_mut(group)?;
+        let provider = handle(provider)?;
+        let identity = handle(identity)?;
+        check_out(out_commit)?;
+
+        let (commit, _welcome, _group_info) = group
+            .group
+            .remove_members(
+                provider,
+                &identity.signer,
+                &[LeafNodeIndex::new(leaf_index)],
+            )?
+            .into_parts();
+        write_out(out_commit, commit.to_bytes()?.into());
+        Ok(())
+    })
+}
+
+/// Merge the pending commit of the group.
+///
+/// # Safety
+///
+/// `group` and `provider` must be valid handles.
+#[no_mangle]
+pub unsafe extern "C" fn openmls_group_merge_pending_commit(
+    group: *mut Group,
+    provider: *const Provider,
+) -> OpenMlsStatus {
+    ffi_call(|| {
+        let group = handle_mut(group)?;
+        let provider = handle(provider)?;
+        group.group.merge_pending_commit(provider)?;
+        Ok(())
+    })
+}
+
+/// Encrypt the application message `plaintext` and write the resulting
+/// `MLSMessage` to `out_message`.
+///
+/// # Safety
+///
+/// `group`, `provider` and `identity` must be valid handles, `plaintext`
+/// must be valid for reads of `plaintext_len` bytes and `out_message` must be
+/// valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn openmls_group_encrypt(
+    group: *mut Group,
+    provider: *const Provider,
+    identity: *const Identity,
+    plaintext: *const u8,
+    plaintext_len: usize,
+    out_message: *mut OpenMlsBuffer,
+) -> OpenMlsStatus {
+    ffi_call(|| {
+        let group = handle_mut(group)?;
+        let provider = handle(provider)?;
+        let identity = handle(identity)?;
+        let plaintext = input(plaintext, plaintext_len)?;
+        check_out(out_message)?;
+
+        let message = group
+            .group
+            .create_message(provider, &identity.signer, plaintext)?;
+        write_out(out_message, message.to_bytes()?.into());
+        Ok(())
+    })
+}
+
+/// Process the `MLSMessage` `message` and write its kind to `out_kind`.
+///
+/// * Application messages are decrypted and the plaintext is written to
+///   `out_plaintext`.
+/// * Proposals are stored in the group.
+/// * Commits are merged. If the commit removed the own member, the group
+///   becomes inactive (see [`openmls_group_is_active()`]).
+///
+/// `out_plaintext` is set to an empty buffer for proposals and commits.
+///
+/// # Safety
+///
+/// `group` and `provider` must be valid handles, `message` must be valid for
+/// reads of `message_len` bytes and `out_kind` and `out_plaintext` must be
+/// valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn openmls_group_process_message(
+    group: *mut Group,
+    provider: *const Provider,
+    message: *const u8,
+    message_len: usize,
+    out_kind: *mut OpenMlsMessageKind,
+    out_plaintext: *mut OpenMlsBuffer,
+) -> OpenMlsStatus {
+    ffi_call(|| {
+        let group = handle_mut(group)?;
+        let provider = handle(provider)?;
+        let message = input(message, message_len)?;
+        check_out(out_kind)?;
+        check_out(out_plaintext)?;
+
+        let message = match decode_message(message)? {
+            MlsMessageInBody::PublicMessage(message) => ProtocolMessage::from(message),
+            MlsMessageInBody::PrivateMessage(message) => ProtocolMessage::from(message),
+            _ => return Err(OpenMlsStatus::InvalidArgument),
+        };
+        let processed_message = group.group.process_message(provider, message)?;
+        let (kind, plaintext) = match processed_message.into_content() {
+            ProcessedMessageContent::ApplicationMessage(message) => {
+                (OpenMlsMessageKind::Application, message.into_bytes())
+            }
+            ProcessedMessageContent::ProposalMessage(proposal) => {
+                group.group.store_pending_proposal(*proposal);
+                (OpenMlsMessageKind::Proposal, Vec::new())
+            }
+            ProcessedMessageContent::ExternalJoinProposalMessage(proposal) => {
+                group.group.store_pending_proposal(*proposal);
+                (OpenMlsMessageKind::Proposal, Vec::new())
+            }
+            ProcessedMessageContent::StagedCommitMessage(staged_commit) => {
+                group.group.merge_staged_commit(provider, *staged_commit)?;
+                (OpenMlsMessageKind::Commit, Vec::new())
+            }
+        };
+        write_out(out_kind, kind);
+        write_out(out_plaintext, plaintext.into());
+        Ok(())
+    })
+}
+
+/// Write the current epoch of the group to `out_epoch`.
+///
+/// # Safety
+///
+/// `group` must be a valid group handle and `out_epoch` must be valid for
+/// writes.
+#[no_mangle]
+pub unsafe extern "C" fn openmls_group_epoch(
+    group: *const Group,
+    out_epoch: *mut u64,
+) -> OpenMlsStatus {
+    ffi_call(|| {
+        let group = handle(group)?;
+        check_out(out_epoch)?;
+        write_out(out_epoch, group.group.epoch().as_u64());
+        Ok(())
+    })
+}
+
+/// Write the leaf index of the own member to `out_leaf_index`. Other members
+/// use it to remove this member.
+///
+/// # Safety
+///
+/// `group` must be a valid group handle and `out_leaf_index` must be valid
+/// for writes.
+#[no_mangle]
+pub unsafe extern "C" fn openmls_group_own_leaf_index(
+    group: *const Group,
+    out_leaf_index: *mut u32,
+) -> OpenMlsStatus {
+    ffi_call(|| {
+        let group = handle(group)?;
+        check_out(out_leaf_index)?;
+        write_out(out_leaf_index, group.group.own_leaf_index().u32());
+        Ok(())
+    })
+}
+
+/// Write whether the own member is still part of the group to `out_active`.
+///
+/// # Safety
+///
+/// `group` must be a valid group handle and `out_active` must be valid for
+/// writes.
+#[no_mangle]
+pub unsafe extern "C" fn openmls_group_is_active(
+    group: *const Group,
+    out_active: *mut bool,
+) -> OpenMlsStatus {
+    ffi_call(|| {
+        let group = handle(group)?;
+        check_out(out_active)?;
+        write_out(out_active, group.group.is_active());
+        Ok(())
+    })
+}
+
+/// Save the group in the provider. Use
+/// [`openmls_provider_save()`](crate::openmls_provider_save()) to persist the
+/// provider afterwards.
+///
+/// # Safety
+///
+/// `group` and `provider` must be valid handles.
+#[no_mangle]
+pub unsafe extern "C" fn openmls_group_save(
+    group: *mut Group,
+    provider: *const Provider,
+) -> OpenMlsStatus {
+    ffi_call(|| {
+        let group = handle_mut(group)?;
+        let provider = handle(provider)?;
+        group
+            .group
+            .save(provider.key_store())
+            .map_err(|_| OpenMlsStatus::KeyStoreError)
+    })
+}
+
+/// Load the group with the ID `group_id` from the provider and write it to
+/// `out_group`. Returns [`OpenMlsStatus::NotFound`] if the group has not been
+/// saved with [`openmls_group_save()`].
+///
+/// The group has to be freed with [`openmls_group_free()`].
+///
+/// # Safety
+///
+/// `provider` must be a valid provider handle, `group_id` must be valid for
+/// reads of `group_id_len` bytes and `out_group` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn openmls_group_load(
+    provider: *const Provider,
+    group_id: *const u8,
+    group_id_len: usize,
+    out_group: *mut *mut Group,
+) -> OpenMlsStatus {
+    ffi_call(|| {
+        let provider = handle(provider)?;
+        let group_id = input(group_id, group_id_len)?;
+        check_out(out_group)?;
+
+        let group = MlsGroup::load(&GroupId::from_slice(group_id), provider.key_store())
+            .ok_or(OpenMlsStatus::NotFound)?;
+        write_out(out_group, into_group(group));
+        Ok(())
+    })
+}
+
+/// Free a group. `group` may be null.
+///
+/// # Safety
+///
+/// `group` must be null or a valid group handle that is not used after this
+/// call.
+#[no_mangle]
+pub unsafe extern "C" fn openmls_group_free(group: *mut Group) {
+    if !group.is_null() {
+        drop(Box::from_raw(group));
+    }
+}
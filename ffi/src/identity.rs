@@ -0,0 +1,163 @@
+//! The identity handle and key packages.
+
+use openmls::prelude::{config::CryptoConfig, *};
+use openmls_basic_credential::SignatureKeyPair;
+use openmls_traits::{crypto::OpenMlsCrypto, OpenMlsProvider};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    check_out, ffi_call, handle, input, write_out, OpenMlsBuffer, OpenMlsStatus, Provider,
+};
+
+/// The identity handle (`OpenMlsIdentity` in C).
+///
+/// A basic credential with its signature key pair. The identity is bound to a
+/// ciphersuite, which is used for its key packages and the groups it creates.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Identity {
+    pub(crate) ciphersuite: Ciphersuite,
+    pub(crate) credential_with_key: CredentialWithKey,
+    pub(crate) signer: SignatureKeyPair,
+}
+
+/// Create a new identity with a basic credential for `identity` and write it
+/// to `out_identity`. `ciphersuite` is the IANA value of the ciphersuite,
+/// e.g. `1` for `MLS_128_DHKEMX25519_AES128GCM_SHA256_Ed25519`.
+///
+/// The identity has to be freed with [`openmls_identity_free()`].
+///
+/// # Safety
+///
+/// `provider` must be a valid provider handle, `identity` must be valid for
+/// reads of `identity_len` bytes and `out_identity` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn openmls_identity_new(
+    provider: *const Provider,
+    ciphersuite: u16,
+    identity: *const u8,
+    identity_len: usize,
+    out_identity: *mut *mut Identity,
+) -> OpenMlsStatus {
+    ffi_call(|| {
+        let provider = handle(provider)?;
+        let identity = input(identity, identity_len)?;
+        check_out(out_identity)?;
+
+        let ciphersuite =
+            Ciphersuite::try_from(ciphersuite).map_err(|_| OpenMlsStatus::InvalidArgument)?;
+        if !provider
+            .crypto()
+            .supported_ciphersuites()
+            .contains(&ciphersuite)
+        {
+            return Err(OpenMlsStatus::InvalidArgument);
+        }
+
+        let credential = Credential::new(identity.to_vec(), CredentialType::Basic)
+            .map_err(|_| OpenMlsStatus::InvalidArgument)?;
+        let signer = SignatureKeyPair::new(ciphersuite.signature_algorithm())
+            .map_err(|_| OpenMlsStatus::CryptoError)?;
+        let credential_with_key = CredentialWithKey {
+            credential,
+            signature_key: signer.to_public_vec().into(),
+        };
+
+        let identity = Identity {
+            ciphersuite,
+            credential_with_key,
+            signer,
+        };
+        write_out(out_identity, Box::into_raw(Box::new(identity)));
+        Ok(())
+    })
+}
+
+/// Export the identity, including its private signature key, to
+/// `out_identity`. The application has to store it securely.
+///
+/// # Safety
+///
+/// `identity` must be a valid identity handle and `out_identity` must be
+/// valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn openmls_identity_save(
+    identity: *const Identity,
+    out_identity: *mut OpenMlsBuffer,
+) -> OpenMlsStatus {
+    ffi_call(|| {
+        let identity = handle(identity)?;
+        check_out(out_identity)?;
+        let bytes = serde_json::to_vec(identity).map_err(|_| OpenMlsStatus::EncodingError)?;
+        write_out(out_identity, bytes.into());
+        Ok(())
+    })
+}
+
+/// Load an identity exported with [`openmls_identity_save()`] and write it to
+/// `out_identity`.
+///
+/// The identity has to be freed with [`openmls_identity_free()`].
+///
+/// # Safety
+///
+/// `identity` must be valid for reads of `identity_len` bytes and
+/// `out_identity` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn openmls_identity_load(
+    identity: *const u8,
+    identity_len: usize,
+    out_identity: *mut *mut Identity,
+) -> OpenMlsStatus {
+    ffi_call(|| {
+        let identity = input(identity, identity_len)?;
+        check_out(out_identity)?;
+        let identity: Identity =
+            serde_json::from_slice(identity).map_err(|_| OpenMlsStatus::DecodingError)?;
+        write_out(out_identity, Box::into_raw(Box::new(identity)));
+        Ok(())
+    })
+}
+
+/// Free an identity. `identity` may be null.
+///
+/// # Safety
+///
+/// `identity` must be null or a valid identity handle that is not used after
+/// this call.
+#[no_mangle]
+pub unsafe extern "C" fn openmls_identity_free(identity: *mut Identity) {
+    if !identity.is_null() {
+        drop(Box::from_raw(identity));
+    }
+}
+
+/// Create a new key package for `identity` and write it to `out_key_package`
+/// as `MLSMessage`. The private keys of the key package are stored in the
+/// provider.
+///
+/// # Safety
+///
+/// `provider` and `identity` must be valid handles and `out_key_package` must
+/// be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn openmls_key_package_new(
+    provider: *const Provider,
+    identity: *const Identity,
+    out_key_package: *mut OpenMlsBuffer,
+) -> OpenMlsStatus {
+    ffi_call(|| {
+        let provider = handle(provider)?;
+        let identity = handle(identity)?;
+        check_out(out_key_package)?;
+
+        let key_package = KeyPackage::builder().build(
+            CryptoConfig::with_default_version(identity.ciphersuite),
+            provider,
+            &identity.signer,
+            identity.credential_with_key.clone(),
+        )?;
+        let key_package = MlsMessageOut::from(key_package).to_bytes()?;
+        write_out(out_key_package, key_package.into());
+        Ok(())
+    })
+}
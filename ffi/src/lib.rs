@@ -0,0 +1,138 @@
+//! # OpenMLS FFI
+//!
+//! A C API for the core flows of OpenMLS, e.g. for native iOS and Android apps.
+//!
+//! The API is built around three opaque handles:
+//!
+//! * [`Provider`] (`OpenMlsProvider` in C): the crypto provider and the key
+//!   store, which holds all key material and persisted groups.
+//! * [`Identity`] (`OpenMlsIdentity` in C): a basic credential with its
+//!   signature key pair and ciphersuite.
+//! * [`Group`] (`OpenMlsGroup` in C): an MLS group.
+//!
+//! Handles are created by the API and have to be freed with the corresponding
+//! `_free` function. Every fallible function returns an [`OpenMlsStatus`].
+//! Outputs are written to out parameters, which are only written if the
+//! function returns [`OpenMlsStatus::Ok`]. Byte strings are passed in as
+//! pointer and length and are returned as [`OpenMlsBuffer`], which has to be
+//! freed with [`openmls_buffer_free()`].
+//!
+//! MLS messages (key packages, commits, welcomes, application messages) are
+//! exchanged as TLS encoded `MLSMessage`s.
+//!
+//! Panics never cross the FFI boundary. They are reported as
+//! [`OpenMlsStatus::Panic`].
+
+use std::{
+    panic::{self, AssertUnwindSafe},
+    ptr, slice,
+};
+
+mod error;
+mod group;
+mod identity;
+mod provider;
+
+pub use error::*;
+pub use group::*;
+pub use identity::*;
+pub use provider::*;
+
+/// A byte string returned by the API.
+///
+/// The buffer is owned by the caller and has to be freed with
+/// [`openmls_buffer_free()`].
+#[repr(C)]
+#[derive(Debug)]
+pub struct OpenMlsBuffer {
+    /// Pointer to the bytes, or null if the buffer is empty.
+    pub data: *mut u8,
+    /// The number of bytes.
+    pub len: usize,
+}
+
+impl OpenMlsBuffer {
+    /// An empty buffer that doesn't need to be freed.
+    pub const fn empty() -> Self {
+        Self {
+            data: ptr::null_mut(),
+            len: 0,
+        }
+    }
+}
+
+impl From<Vec<u8>> for OpenMlsBuffer {
+    fn from(bytes: Vec<u8>) -> Self {
+        if bytes.is_empty() {
+            return Self::empty();
+        }
+        let len = bytes.len();
+        let data = Box::into_raw(bytes.into_boxed_slice()) as *mut u8;
+        Self { data, len }
+    }
+}
+
+/// Free a buffer returned by the API.
+///
+/// # Safety
+///
+/// `buffer` must have been returned by the API and must not be used after
+/// this call.
+#[no_mangle]
+pub unsafe extern "C" fn openmls_buffer_free(buffer: OpenMlsBuffer) {
+    if !buffer.data.is_null() {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(
+            buffer.data,
+            buffer.len,
+        )));
+    }
+}
+
+/// Run `f` and convert its result into an [`OpenMlsStatus`]. Panics are caught
+/// and reported as [`OpenMlsStatus::Panic`].
+fn ffi_call(f: impl FnOnce() -> Result<(), OpenMlsStatus>) -> OpenMlsStatus {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => OpenMlsStatus::Ok,
+        Ok(Err(status)) => status,
+        Err(_) => OpenMlsStatus::Panic,
+    }
+}
+
+/// Get the byte string of length `len` at `data`. `data` may only be null if
+/// `len` is 0.
+unsafe fn input<'a>(data: *const u8, len: usize) -> Result<&'a [u8], OpenMlsStatus> {
+    if data.is_null() {
+        if len == 0 {
+            Ok(&[])
+        } else {
+            Err(OpenMlsStatus::NullPointer)
+        }
+    } else {
+        Ok(slice::from_raw_parts(data, len))
+    }
+}
+
+/// Dereference a handle.
+unsafe fn handle<'a, T>(ptr: *const T) -> Result<&'a T, OpenMlsStatus> {
+    ptr.as_ref().ok_or(OpenMlsStatus::NullPointer)
+}
+
+/// Mutably dereference a handle.
+unsafe fn handle_mut<'a, T>(ptr: *mut T) -> Result<&'a mut T, OpenMlsStatus> {
+    ptr.as_mut().ok_or(OpenMlsStatus::NullPointer)
+}
+
+/// Make sure that the out parameter `out` is not null.
+fn check_out<T>(out: *mut T) -> Result<(), OpenMlsStatus> {
+    if out.is_null() {
+        Err(OpenMlsStatus::NullPointer)
+    } else {
+        Ok(())
+    }
+}
+
+/// Write `value` to the out parameter `out`, which has been checked with
+/// [`check_out()`].
+unsafe fn write_out<T>(out: *mut T, value: T) {
+    out.write(value)
+}
@@ -0,0 +1,190 @@
+//! The provider handle and its key store.
+
+use std::{collections::HashMap, sync::RwLock};
+
+use openmls_rust_crypto::RustCrypto;
+use openmls_traits::{
+    key_store::{unwrap_blob, BlobHeader, MlsEntity, OpenMlsKeyStore},
+    OpenMlsProvider,
+};
+use thiserror::Error;
+
+use crate::{check_out, ffi_call, handle, input, write_out, OpenMlsBuffer, OpenMlsStatus};
+
+/// An in-memory key store whose entire state can be exported and imported.
+#[derive(Debug, Default)]
+pub struct FfiKeyStore {
+    values: RwLock<HashMap<Vec<u8>, Vec<u8>>>,
+}
+
+impl OpenMlsKeyStore for FfiKeyStore {
+    /// The error type returned by the [`OpenMlsKeyStore`].
+    type Error = FfiKeyStoreError;
+
+    /// Store a value `v` that implements the [`MlsEntity`] trait for
+    /// serialization for ID `k`.
+    ///
+    /// Returns an error if storing fails.
+    fn store<V: MlsEntity>(&self, k: &[u8], v: &V) -> Result<(), Self::Error> {
+        let value = serde_json::to_vec(v).map_err(|_| FfiKeyStoreError::SerializationError)?;
+        let value = BlobHeader::for_entity::<V>().wrap(&value);
+        // We unwrap here, because the functions claiming a write lock only
+        // hold the lock very briefly and should not panic during that period.
+        self.values.write().unwrap().insert(k.to_vec(), value);
+        Ok(())
+    }
+
+    /// Read and return a value stored for ID `k` that implements the
+    /// [`MlsEntity`] trait for deserialization.
+    ///
+    /// Returns [`None`] if no value is stored for `k` or reading fails.
+    fn read<V: MlsEntity>(&self, k: &[u8]) -> Option<V> {
+        let values = self.values.read().unwrap();
+        values
+            .get(k)
+            .and_then(|value| serde_json::from_slice(unwrap_blob::<V>(value)?).ok())
+    }
+
+    /// Delete a value stored for ID `k`.
+    ///
+    /// Returns an error if deleting fails.
+    fn delete<V: MlsEntity>(&self, k: &[u8]) -> Result<(), Self::Error> {
+        self.values.write().unwrap().remove(k);
+        Ok(())
+    }
+}
+
+impl FfiKeyStore {
+    /// Export all values of the key store.
+    fn export(&self) -> Result<Vec<u8>, FfiKeyStoreError> {
+        let values = self.values.read().unwrap();
+        // JSON only supports string keys. The values are therefore exported as
+        // a list of key-value pairs.
+        let values: Vec<(&Vec<u8>, &Vec<u8>)> = values.iter().collect();
+        serde_json::to_vec(&values).map_err(|_| FfiKeyStoreError::SerializationError)
+    }
+
+    /// Create a key store from values exported with [`FfiKeyStore::export()`].
+    fn import(state: &[u8]) -> Result<Self, FfiKeyStoreError> {
+        let values: Vec<(Vec<u8>, Vec<u8>)> =
+            serde_json::from_slice(state).map_err(|_| FfiKeyStoreError::SerializationError)?;
+        Ok(Self {
+            values: RwLock::new(values.into_iter().collect()),
+        })
+    }
+}
+
+/// Errors thrown by the key store.
+#[derive(Error, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FfiKeyStoreError {
+    /// Error serializing a value.
+    #[error("Error serializing value.")]
+    SerializationError,
+}
+
+/// The provider handle (`OpenMlsProvider` in C).
+///
+/// It uses the RustCrypto crypto provider and an in-memory key store that can
+/// be persisted with [`openmls_provider_save()`].
+#[derive(Debug, Default)]
+pub struct Provider {
+    crypto: RustCrypto,
+    key_store: FfiKeyStore,
+}
+
+impl OpenMlsProvider for Provider {
+    type CryptoProvider = RustCrypto;
+    type RandProvider = RustCrypto;
+    type KeyStoreProvider = FfiKeyStore;
+    type ClockProvider = RustCrypto;
+
+    fn crypto(&self) -> &Self::CryptoProvider {
+        &self.crypto
+    }
+
+    fn rand(&self) -> &Self::RandProvider {
+        &self.crypto
+    }
+
+    fn key_store(&self) -> &Self::KeyStoreProvider {
+        &self.key_store
+    }
+
+    fn clock(&self) -> &Self::ClockProvider {
+        &self.crypto
+    }
+}
+
+/// Create a new provider with an empty key store.
+///
+/// The provider has to be freed with [`openmls_provider_free()`].
+#[no_mangle]
+pub extern "C" fn openmls_provider_new() -> *mut Provider {
+    Box::into_raw(Box::default())
+}
+
+/// Export the state of the provider, i.e., the contents of its key store, to
+/// `out_state`. This includes all groups saved with
+/// [`openmls_group_save()`](crate::openmls_group_save()).
+///
+/// # Safety
+///
+/// `provider` must be a valid provider handle and `out_state` must be valid
+/// for writes.
+#[no_mangle]
+pub unsafe extern "C" fn openmls_provider_save(
+    provider: *const Provider,
+    out_state: *mut OpenMlsBuffer,
+) -> OpenMlsStatus {
+    ffi_call(|| {
+        let provider = handle(provider)?;
+        check_out(out_state)?;
+        let state = provider
+            .key_store
+            .export()
+            .map_err(|_| OpenMlsStatus::EncodingError)?;
+        write_out(out_state, state.into());
+        Ok(())
+    })
+}
+
+/// Create a provider from a state exported with [`openmls_provider_save()`]
+/// and write it to `out_provider`.
+///
+/// The provider has to be freed with [`openmls_provider_free()`].
+///
+/// # Safety
+///
+/// `state` must be valid for reads of `state_len` bytes and `out_provider`
+/// must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn openmls_provider_load(
+    state: *const u8,
+    state_len: usize,
+    out_provider: *mut *mut Provider,
+) -> OpenMlsStatus {
+    ffi_call(|| {
+        let state = input(state, state_len)?;
+        check_out(out_provider)?;
+        let key_store = FfiKeyStore::import(state).map_err(|_| OpenMlsStatus::DecodingError)?;
+        let provider = Provider {
+            crypto: RustCrypto::default(),
+            key_store,
+        };
+        write_out(out_provider, Box::into_raw(Box::new(provider)));
+        Ok(())
+    })
+}
+
+/// Free a provider. `provider` may be null.
+///
+/// # Safety
+///
+/// `provider` must be null or a valid provider handle that is not used after
+/// this call.
+#[no_mangle]
+pub unsafe extern "C" fn openmls_provider_free(provider: *mut Provider) {
+    if !provider.is_null() {
+        drop(Box::from_raw(provider));
+    }
+}
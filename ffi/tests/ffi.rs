@@ -0,0 +1,313 @@
+//! Test the core flows through the C API.
+
+use std::{ffi::CStr, ptr, slice};
+
+use openmls_ffi::*;
+
+const CIPHERSUITE: u16 = 1;
+
+fn bytes(buffer: &OpenMlsBuffer) -> Vec<u8> {
+    if buffer.data.is_null() {
+        return Vec::new();
+    }
+    unsafe { slice::from_raw_parts(buffer.data, buffer.len) }.to_vec()
+}
+
+fn take(buffer: OpenMlsBuffer) -> Vec<u8> {
+    let result = bytes(&buffer);
+    unsafe { openmls_buffer_free(buffer) };
+    result
+}
+
+fn identity(provider: *const Provider, name: &[u8]) -> *mut Identity {
+    let mut identity = ptr::null_mut();
+    let status = unsafe {
+        openmls_identity_new(
+            provider,
+            CIPHERSUITE,
+            name.as_ptr(),
+            name.len(),
+            &mut identity,
+        )
+    };
+    assert_eq!(status, OpenMlsStatus::Ok);
+    identity
+}
+
+fn key_package(provider: *const Provider, identity: *const Identity) -> Vec<u8> {
+    let mut key_package = OpenMlsBuffer::empty();
+    let status = unsafe { openmls_key_package_new(provider, identity, &mut key_package) };
+    assert_eq!(status, OpenMlsStatus::Ok);
+    take(key_package)
+}
+
+fn process(
+    group: *mut Group,
+    provider: *const Provider,
+    message: &[u8],
+) -> (OpenMlsMessageKind, Vec<u8>) {
+    let mut kind = OpenMlsMessageKind::Proposal;
+    let mut plaintext = OpenMlsBuffer::empty();
+    let status = unsafe {
+        openmls_group_process_message(
+            group,
+            provider,
+            message.as_ptr(),
+            message.len(),
+            &mut kind,
+            &mut plaintext,
+        )
+    };
+    assert_eq!(status, OpenMlsStatus::Ok);
+    (kind, take(plaintext))
+}
+
+fn epoch(group: *const Group) -> u64 {
+    let mut epoch = 0;
+    assert_eq!(
+        unsafe { openmls_group_epoch(group, &mut epoch) },
+        OpenMlsStatus::Ok
+    );
+    epoch
+}
+
+#[test]
+fn core_flows() {
+    let alice_provider = openmls_provider_new();
+    let mut bob_provider = openmls_provider_new();
+    let alice = identity(alice_provider, b"Alice");
+    let bob = identity(bob_provider, b"Bob");
+
+    // === Alice creates a group and adds Bob ===
+    let group_id = b"Test Group";
+    let mut alice_group = ptr::null_mut();
+    let status = unsafe {
+        openmls_group_new(
+            alice_provider,
+            alice,
+            group_id.as_ptr(),
+            group_id.len(),
+            &mut alice_group,
+        )
+    };
+    assert_eq!(status, OpenMlsStatus::Ok);
+
+    let bob_key_package = key_package(bob_provider, bob);
+    let mut commit = OpenMlsBuffer::empty();
+    let mut welcome = OpenMlsBuffer::empty();
+    let status = unsafe {
+        openmls_group_add_member(
+            alice_group,
+            alice_provider,
+            alice,
+            bob_key_package.as_ptr(),
+            bob_key_package.len(),
+            &mut commit,
+            &mut welcome,
+        )
+    };
+    assert_eq!(status, OpenMlsStatus::Ok);
+    let _commit = take(commit);
+    let welcome = take(welcome);
+    assert_eq!(
+        unsafe { openmls_group_merge_pending_commit(alice_group, alice_provider) },
+        OpenMlsStatus::Ok
+    );
+    assert_eq!(epoch(alice_group), 1);
+
+    // === Bob joins ===
+    let mut bob_group = ptr::null_mut();
+    let status = unsafe {
+        openmls_group_join(
+            bob_provider,
+            welcome.as_ptr(),
+            welcome.len(),
+            &mut bob_group,
+        )
+    };
+    assert_eq!(status, OpenMlsStatus::Ok);
+    assert_eq!(epoch(bob_group), 1);
+
+    // === Alice sends a message to Bob ===
+    let mut message = OpenMlsBuffer::empty();
+    let status = unsafe {
+        openmls_group_encrypt(
+            alice_group,
+            alice_provider,
+            alice,
+            b"Hi".as_ptr(),
+            2,
+            &mut message,
+        )
+    };
+    assert_eq!(status, OpenMlsStatus::Ok);
+    let message = take(message);
+    assert_eq!(
+        process(bob_group, bob_provider, &message),
+        (OpenMlsMessageKind::Application, b"Hi".to_vec())
+    );
+
+    // === Bob persists his state and restarts ===
+    assert_eq!(
+        unsafe { openmls_group_save(bob_group, bob_provider) },
+        OpenMlsStatus::Ok
+    );
+    let mut provider_state = OpenMlsBuffer::empty();
+    assert_eq!(
+        unsafe { openmls_provider_save(bob_provider, &mut provider_state) },
+        OpenMlsStatus::Ok
+    );
+    let provider_state = take(provider_state);
+    let mut identity_state = OpenMlsBuffer::empty();
+    assert_eq!(
+        unsafe { openmls_identity_save(bob, &mut identity_state) },
+        OpenMlsStatus::Ok
+    );
+    let identity_state = take(identity_state);
+    unsafe {
+        openmls_group_free(bob_group);
+        openmls_identity_free(bob);
+        openmls_provider_free(bob_provider);
+    }
+
+    let status = unsafe {
+        openmls_provider_load(
+            provider_state.as_ptr(),
+            provider_state.len(),
+            &mut bob_provider,
+        )
+    };
+    assert_eq!(status, OpenMlsStatus::Ok);
+    let mut bob = ptr::null_mut();
+    let status =
+        unsafe { openmls_identity_load(identity_state.as_ptr(), identity_state.len(), &mut bob) };
+    assert_eq!(status, OpenMlsStatus::Ok);
+    let mut bob_group = ptr::null_mut();
+    let status = unsafe {
+        openmls_group_load(
+            bob_provider,
+            group_id.as_ptr(),
+            group_id.len(),
+            &mut bob_group,
+        )
+    };
+    assert_eq!(status, OpenMlsStatus::Ok);
+    let mut unknown_group = ptr::null_mut();
+    let status =
+        unsafe { openmls_group_load(bob_provider, b"Unknown".as_ptr(), 7, &mut unknown_group) };
+    assert_eq!(status, OpenMlsStatus::NotFound);
+    assert!(unknown_group.is_null());
+
+    // === Bob replies after the restart ===
+    let mut message = OpenMlsBuffer::empty();
+    let status = unsafe {
+        openmls_group_encrypt(
+            bob_group,
+            bob_provider,
+            bob,
+            b"Hello".as_ptr(),
+            5,
+            &mut message,
+        )
+    };
+    assert_eq!(status, OpenMlsStatus::Ok);
+    let message = take(message);
+    assert_eq!(
+        process(alice_group, alice_provider, &message),
+        (OpenMlsMessageKind::Application, b"Hello".to_vec())
+    );
+
+    // === Alice removes Bob ===
+    let mut bob_leaf_index = 0;
+    assert_eq!(
+        unsafe { openmls_group_own_leaf_index(bob_group, &mut bob_leaf_index) },
+        OpenMlsStatus::Ok
+    );
+    let mut commit = OpenMlsBuffer::empty();
+    let status = unsafe {
+        openmls_group_remove_member(
+            alice_group,
+            alice_provider,
+            alice,
+            bob_leaf_index,
+            &mut commit,
+        )
+    };
+    assert_eq!(status, OpenMlsStatus::Ok);
+    let commit = take(commit);
+    assert_eq!(
+        unsafe { openmls_group_merge_pending_commit(alice_group, alice_provider) },
+        OpenMlsStatus::Ok
+    );
+    assert_eq!(
+        process(bob_group, bob_provider, &commit),
+        (OpenMlsMessageKind::Commit, Vec::new())
+    );
+    let mut active = true;
+    assert_eq!(
+        unsafe { openmls_group_is_active(bob_group, &mut active) },
+        OpenMlsStatus::Ok
+    );
+    assert!(!active);
+    assert_eq!(epoch(alice_group), 2);
+
+    unsafe {
+        openmls_group_free(alice_group);
+        openmls_group_free(bob_group);
+        openmls_identity_free(alice);
+        openmls_identity_free(bob);
+        openmls_provider_free(alice_provider);
+        openmls_provider_free(bob_provider);
+    }
+}
+
+#[test]
+fn errors() {
+    let provider = openmls_provider_new();
+
+    // Null pointers
+    let mut out_identity = ptr::null_mut();
+    let status = unsafe {
+        openmls_identity_new(ptr::null(), CIPHERSUITE, ptr::null(), 0, &mut out_identity)
+    };
+    assert_eq!(status, OpenMlsStatus::NullPointer);
+    let status =
+        unsafe { openmls_identity_new(provider, CIPHERSUITE, ptr::null(), 1, &mut out_identity) };
+    assert_eq!(status, OpenMlsStatus::NullPointer);
+
+    // Unknown ciphersuites
+    let status =
+        unsafe { openmls_identity_new(provider, 0xffff, b"Alice".as_ptr(), 5, &mut out_identity) };
+    assert_eq!(status, OpenMlsStatus::InvalidArgument);
+
+    // Malformed messages
+    let mut group = ptr::null_mut();
+    let status = unsafe { openmls_group_join(provider, [1, 2, 3].as_ptr(), 3, &mut group) };
+    assert_eq!(status, OpenMlsStatus::DecodingError);
+
+    // A key package is not a welcome
+    let alice = identity(provider, b"Alice");
+    let key_package = key_package(provider, alice);
+    let status = unsafe {
+        openmls_group_join(
+            provider,
+            key_package.as_ptr(),
+            key_package.len(),
+            &mut group,
+        )
+    };
+    assert_eq!(status, OpenMlsStatus::InvalidArgument);
+    assert!(group.is_null());
+
+    // Every status has a description
+    let message = unsafe { CStr::from_ptr(openmls_status_message(OpenMlsStatus::NotFound)) };
+    assert_eq!(
+        message.to_str().unwrap(),
+        "The requested value was not found."
+    );
+
+    unsafe {
+        openmls_identity_free(alice);
+        openmls_provider_free(provider);
+    }
+}
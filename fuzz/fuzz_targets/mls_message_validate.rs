@@ -0,0 +1,10 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use openmls::{fuzz, prelude::OpenMlsProvider};
+use openmls_rust_crypto::OpenMlsRustCrypto;
+
+fuzz_target!(|data: &[u8]| {
+    let provider = OpenMlsRustCrypto::default();
+    let _ = fuzz::mls_message_in(provider.crypto(), data);
+});
@@ -0,0 +1,8 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use openmls::fuzz;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = fuzz::update_path_in(data);
+});
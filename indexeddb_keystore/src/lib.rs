@@ -0,0 +1,125 @@
+//! # OpenMLS IndexedDB Keystore
+//!
+//! An [`AsyncOpenMlsKeyStore`] that persists values in the browser's
+//! IndexedDB. Use it with the `AsyncMlsGroup` of OpenMLS.
+//!
+//! All values are kept in a single object store of the database. Keys are
+//! stored hex encoded and values as `Uint8Array`s.
+//!
+//! This crate is only available on `wasm32-unknown-unknown`.
+#![cfg(target_arch = "wasm32")]
+
+use async_trait::async_trait;
+use js_sys::Uint8Array;
+use openmls::prelude::AsyncOpenMlsKeyStore;
+use rexie::{ObjectStore, Rexie, TransactionMode};
+use thiserror::Error;
+use wasm_bindgen::JsValue;
+
+/// The name of the object store that holds the values.
+const OBJECT_STORE: &str = "openmls";
+
+/// A key store over IndexedDB.
+pub struct IndexedDbKeyStore {
+    database: Rexie,
+}
+
+impl IndexedDbKeyStore {
+    /// Open the IndexedDB database with the given `name`. The database is
+    /// created if it doesn't exist yet.
+    ///
+    /// Returns an error if the database can't be opened.
+    pub async fn open(name: &str) -> Result<Self, IndexedDbKeyStoreError> {
+        let database = Rexie::builder(name)
+            .version(1)
+            .add_object_store(ObjectStore::new(OBJECT_STORE))
+            .build()
+            .await?;
+        Ok(Self { database })
+    }
+
+    /// Close the database.
+    pub fn close(self) {
+        self.database.close()
+    }
+
+    /// Delete the IndexedDB database with the given `name`, including all
+    /// values stored in it.
+    ///
+    /// Returns an error if the database can't be deleted.
+    pub async fn delete_database(name: &str) -> Result<(), IndexedDbKeyStoreError> {
+        Ok(Rexie::delete(name).await?)
+    }
+}
+
+/// Encode the ID `k` as key of the object store.
+fn key(k: &[u8]) -> JsValue {
+    let key: String = k.iter().map(|byte| format!("{byte:02x}")).collect();
+    JsValue::from_str(&key)
+}
+
+#[async_trait(?Send)]
+impl AsyncOpenMlsKeyStore for IndexedDbKeyStore {
+    /// The error type returned by the [`IndexedDbKeyStore`].
+    type Error = IndexedDbKeyStoreError;
+
+    /// Store the value `v` for ID `k`. An existing value is overwritten.
+    ///
+    /// Returns an error if storing fails.
+    async fn store(&self, k: &[u8], v: &[u8]) -> Result<(), Self::Error> {
+        let transaction = self
+            .database
+            .transaction(&[OBJECT_STORE], TransactionMode::ReadWrite)?;
+        let store = transaction.store(OBJECT_STORE)?;
+        store
+            .put(&Uint8Array::from(v).into(), Some(&key(k)))
+            .await?;
+        transaction.done().await?;
+        Ok(())
+    }
+
+    /// Read the value stored for ID `k`.
+    ///
+    /// Returns [`None`] if no value is stored for `k` and an error if reading
+    /// fails.
+    async fn read(&self, k: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+        let transaction = self
+            .database
+            .transaction(&[OBJECT_STORE], TransactionMode::ReadOnly)?;
+        let store = transaction.store(OBJECT_STORE)?;
+        let value = store.get(&key(k)).await?;
+        transaction.done().await?;
+        if value.is_undefined() {
+            Ok(None)
+        } else {
+            Ok(Some(Uint8Array::new(&value).to_vec()))
+        }
+    }
+
+    /// Delete the value stored for ID `k`.
+    ///
+    /// Returns an error if deleting fails.
+    async fn delete(&self, k: &[u8]) -> Result<(), Self::Error> {
+        let transaction = self
+            .database
+            .transaction(&[OBJECT_STORE], TransactionMode::ReadWrite)?;
+        let store = transaction.store(OBJECT_STORE)?;
+        store.delete(&key(k)).await?;
+        transaction.done().await?;
+        Ok(())
+    }
+}
+
+/// Errors thrown by the key store.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum IndexedDbKeyStoreError {
+    /// Error accessing IndexedDB.
+    #[error("Error accessing IndexedDB: {0}")]
+    IndexedDbError(String),
+}
+
+impl From<rexie::Error> for IndexedDbKeyStoreError {
+    fn from(error: rexie::Error) -> Self {
+        Self::IndexedDbError(error.to_string())
+    }
+}
@@ -0,0 +1,135 @@
+//! Tests for the IndexedDB key store that run in the browser.
+//!
+//! Run them with `wasm-pack test --headless --chrome indexeddb_keystore`.
+#![cfg(target_arch = "wasm32")]
+
+use openmls::prelude::{config::CryptoConfig, test_utils::new_credential, *};
+use openmls_indexeddb_keystore::IndexedDbKeyStore;
+use openmls_rust_crypto::{OpenMlsRustCrypto, RustCrypto};
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+struct IndexedDbProvider {
+    crypto: RustCrypto,
+    key_store: IndexedDbKeyStore,
+}
+
+impl AsyncOpenMlsProvider for IndexedDbProvider {
+    type CryptoProvider = RustCrypto;
+    type RandProvider = RustCrypto;
+    type KeyStoreProvider = IndexedDbKeyStore;
+    type ClockProvider = SystemClock;
+
+    fn crypto(&self) -> &Self::CryptoProvider {
+        &self.crypto
+    }
+
+    fn rand(&self) -> &Self::RandProvider {
+        &self.crypto
+    }
+
+    fn key_store(&self) -> &Self::KeyStoreProvider {
+        &self.key_store
+    }
+
+    fn clock(&self) -> &Self::ClockProvider {
+        &SystemClock
+    }
+}
+
+#[wasm_bindgen_test]
+async fn key_store() {
+    let key_store = IndexedDbKeyStore::open("key_store").await.unwrap();
+
+    assert_eq!(key_store.read(b"id").await.unwrap(), None);
+    key_store.store(b"id", b"value").await.unwrap();
+    assert_eq!(
+        key_store.read(b"id").await.unwrap(),
+        Some(b"value".to_vec())
+    );
+    key_store.store(b"id", b"other value").await.unwrap();
+    assert_eq!(
+        key_store.read(b"id").await.unwrap(),
+        Some(b"other value".to_vec())
+    );
+    key_store.delete(b"id").await.unwrap();
+    assert_eq!(key_store.read(b"id").await.unwrap(), None);
+
+    key_store.close();
+    IndexedDbKeyStore::delete_database("key_store")
+        .await
+        .unwrap();
+}
+
+/// Create a group, persist it in IndexedDB and load it from a newly opened
+/// database.
+#[wasm_bindgen_test]
+async fn create_save_and_load_group() {
+    let ciphersuite = Ciphersuite::MLS_128_DHKEMX25519_AES128GCM_SHA256_Ed25519;
+    let (alice_credential, alice_signer) = new_credential(
+        &OpenMlsRustCrypto::default(),
+        b"Alice",
+        CredentialType::Basic,
+        ciphersuite.signature_algorithm(),
+    );
+    let mls_group_config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .build();
+    let group_id = GroupId::from_slice(b"Test Group");
+
+    let provider = IndexedDbProvider {
+        crypto: RustCrypto::default(),
+        key_store: IndexedDbKeyStore::open("group").await.unwrap(),
+    };
+    let mut alice_group = AsyncMlsGroup::new_with_group_id(
+        &provider,
+        &alice_signer,
+        &mls_group_config,
+        group_id.clone(),
+        alice_credential,
+    )
+    .await
+    .expect("error creating group");
+    alice_group
+        .self_update(&provider, &alice_signer)
+        .await
+        .expect("error updating");
+    alice_group
+        .merge_pending_commit(&provider)
+        .await
+        .expect("error merging commit");
+    alice_group
+        .save(&provider)
+        .await
+        .expect("error saving group");
+    provider.key_store.close();
+
+    // === The group is loaded from a newly opened database ===
+    let provider = IndexedDbProvider {
+        crypto: RustCrypto::default(),
+        key_store: IndexedDbKeyStore::open("group").await.unwrap(),
+    };
+    let mut loaded_group = AsyncMlsGroup::load(&provider, &group_id)
+        .await
+        .expect("error loading group")
+        .expect("group not found");
+    assert_eq!(loaded_group.epoch().as_u64(), 1);
+    assert_eq!(
+        loaded_group.export_ratchet_tree(),
+        alice_group.export_ratchet_tree()
+    );
+
+    // The encryption keys of the epoch are available after loading.
+    loaded_group
+        .self_update(&provider, &alice_signer)
+        .await
+        .expect("error updating");
+    loaded_group
+        .merge_pending_commit(&provider)
+        .await
+        .expect("error merging commit");
+
+    provider.key_store.close();
+    IndexedDbKeyStore::delete_database("group").await.unwrap();
+}
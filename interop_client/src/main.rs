@@ -1105,7 +1105,8 @@ impl MlsClient for MlsClientImpl {
                 &interop_group.crypto_provider,
                 &interop_group.signature_keys,
             )
-            .map_err(into_status)?;
+            .map_err(into_status)?
+            .into_parts();
 
         let commit = commit.to_bytes().unwrap();
 
@@ -1281,7 +1282,7 @@ impl MlsClient for MlsClientImpl {
 
         let group_info = group
             .export_group_info(
-                interop_group.crypto_provider.crypto(),
+                &interop_group.crypto_provider,
                 &interop_group.signature_keys,
                 !request.external_tree,
             )
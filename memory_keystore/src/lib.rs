@@ -1,11 +1,21 @@
-use openmls_traits::key_store::{MlsEntity, OpenMlsKeyStore};
+use openmls_traits::key_store::{unwrap_blob, BlobHeader, MlsEntity, OpenMlsKeyStore};
 use std::{collections::HashMap, sync::RwLock};
 
-#[derive(Debug, Default)]
+#[derive(Default)]
 pub struct MemoryKeyStore {
     values: RwLock<HashMap<Vec<u8>, Vec<u8>>>,
 }
 
+// The stored values contain private keys, only their number is printed.
+impl std::fmt::Debug for MemoryKeyStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let len = self.values.read().map(|values| values.len()).ok();
+        f.debug_struct("MemoryKeyStore")
+            .field("values", &len)
+            .finish()
+    }
+}
+
 impl OpenMlsKeyStore for MemoryKeyStore {
     /// The error type returned by the [`OpenMlsKeyStore`].
     type Error = MemoryKeyStoreError;
@@ -16,6 +26,7 @@ impl OpenMlsKeyStore for MemoryKeyStore {
     /// Returns an error if storing fails.
     fn store<V: MlsEntity>(&self, k: &[u8], v: &V) -> Result<(), Self::Error> {
         let value = serde_json::to_vec(v).map_err(|_| MemoryKeyStoreError::SerializationError)?;
+        let value = BlobHeader::for_entity::<V>().wrap(&value);
         // We unwrap here, because this is the only function claiming a write
         // lock on `credential_bundles`. It only holds the lock very briefly and
         // should not panic during that period.
@@ -34,7 +45,7 @@ impl OpenMlsKeyStore for MemoryKeyStore {
         // hold the lock very briefly and should not panic during that period.
         let values = self.values.read().unwrap();
         if let Some(value) = values.get(k) {
-            serde_json::from_slice(value).ok()
+            serde_json::from_slice(unwrap_blob::<V>(value)?).ok()
         } else {
             None
         }
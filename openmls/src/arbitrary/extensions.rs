@@ -0,0 +1,181 @@
+//! Arbitrary extensions.
+
+use proptest::{array::uniform32, collection::vec, prelude::*};
+
+use super::{ciphersuite, hpke_public_key, new_signer};
+use crate::{
+    credentials::{Credential, CredentialType},
+    extensions::*,
+    messages::proposals::ProposalType,
+};
+
+/// Returns a strategy for the extensions of a key package.
+pub fn key_package_extensions() -> impl Strategy<Value = Extensions> {
+    vec(
+        prop_oneof![
+            Just(Extension::LastResort(LastResortExtension::new())),
+            unknown_extension(),
+        ],
+        0..3,
+    )
+    .prop_map(unique)
+}
+
+/// Returns a strategy for the extensions of a leaf node.
+pub fn leaf_node_extensions() -> impl Strategy<Value = Extensions> {
+    vec(
+        prop_oneof![
+            any::<ApplicationIdExtension>().prop_map(Extension::ApplicationId),
+            unknown_extension(),
+        ],
+        0..3,
+    )
+    .prop_map(unique)
+}
+
+/// Returns a strategy for the extensions of a group context.
+pub fn group_context_extensions() -> impl Strategy<Value = Extensions> {
+    vec(
+        prop_oneof![
+            any::<RequiredCapabilitiesExtension>().prop_map(Extension::RequiredCapabilities),
+            vec(any::<ExternalSender>(), 0..3).prop_map(Extension::ExternalSenders),
+            unknown_extension(),
+        ],
+        0..3,
+    )
+    .prop_map(unique)
+}
+
+/// Returns a strategy for extensions of types that are unknown to OpenMLS.
+fn unknown_extension() -> impl Strategy<Value = Extension> {
+    (unknown_extension_type(), vec(any::<u8>(), 0..32)).prop_map(|(extension_type, data)| {
+        Extension::Unknown(extension_type, UnknownExtension(data))
+    })
+}
+
+fn unknown_extension_type() -> impl Strategy<Value = u16> {
+    any::<u16>().prop_filter("known extension type", |extension_type| {
+        matches!(
+            ExtensionType::from(*extension_type),
+            ExtensionType::Unknown(_)
+        )
+    })
+}
+
+/// Collects `extensions` into [`Extensions`]. Later extensions replace earlier
+/// ones of the same type.
+fn unique(extensions: Vec<Extension>) -> Extensions {
+    extensions
+        .into_iter()
+        .fold(Extensions::empty(), |mut extensions, extension| {
+            extensions.add_or_replace(extension);
+            extensions
+        })
+}
+
+impl Arbitrary for ExtensionType {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        prop_oneof![
+            Just(ExtensionType::ApplicationId),
+            Just(ExtensionType::RatchetTree),
+            Just(ExtensionType::RequiredCapabilities),
+            Just(ExtensionType::ExternalPub),
+            Just(ExtensionType::ExternalSenders),
+            Just(ExtensionType::LastResort),
+            unknown_extension_type().prop_map(ExtensionType::Unknown),
+        ]
+        .boxed()
+    }
+}
+
+impl Arbitrary for ApplicationIdExtension {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        vec(any::<u8>(), 0..32)
+            .prop_map(|id| ApplicationIdExtension::new(&id))
+            .boxed()
+    }
+}
+
+impl Arbitrary for RequiredCapabilitiesExtension {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        (
+            vec(any::<ExtensionType>(), 0..4),
+            vec(any::<ProposalType>(), 0..4),
+            vec(any::<CredentialType>(), 0..4),
+        )
+            .prop_map(|(extension_types, proposal_types, credential_types)| {
+                RequiredCapabilitiesExtension::new(
+                    &extension_types,
+                    &proposal_types,
+                    &credential_types,
+                )
+            })
+            .boxed()
+    }
+}
+
+impl Arbitrary for ExternalPubExtension {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        (ciphersuite(), uniform32(any::<u8>()))
+            .prop_map(|(ciphersuite, ikm)| {
+                ExternalPubExtension::new(hpke_public_key(ciphersuite, &ikm).into())
+            })
+            .boxed()
+    }
+}
+
+impl Arbitrary for ExternalSender {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        (ciphersuite(), any::<Credential>())
+            .prop_map(|(ciphersuite, credential)| {
+                let (credential_with_key, _signer) = new_signer(ciphersuite, credential);
+                ExternalSender::new(
+                    credential_with_key.signature_key,
+                    credential_with_key.credential,
+                )
+            })
+            .boxed()
+    }
+}
+
+/// Generates all extensions but [`RatchetTreeExtension`]s.
+impl Arbitrary for Extension {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        prop_oneof![
+            any::<ApplicationIdExtension>().prop_map(Extension::ApplicationId),
+            any::<RequiredCapabilitiesExtension>().prop_map(Extension::RequiredCapabilities),
+            any::<ExternalPubExtension>().prop_map(Extension::ExternalPub),
+            vec(any::<ExternalSender>(), 0..3).prop_map(Extension::ExternalSenders),
+            Just(Extension::LastResort(LastResortExtension::new())),
+            unknown_extension(),
+        ]
+        .boxed()
+    }
+}
+
+impl Arbitrary for Extensions {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        vec(any::<Extension>(), 0..4).prop_map(unique).boxed()
+    }
+}
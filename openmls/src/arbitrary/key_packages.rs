@@ -0,0 +1,77 @@
+//! Arbitrary key packages.
+
+use openmls_rust_crypto::OpenMlsRustCrypto;
+use openmls_traits::types::Ciphersuite;
+use proptest::prelude::*;
+
+use super::{ciphersuite, key_package_extensions, leaf_node_extensions, new_signer};
+use crate::{
+    credentials::Credential,
+    extensions::{ExtensionType, Extensions},
+    group::config::CryptoConfig,
+    key_packages::KeyPackage,
+    treesync::node::leaf_node::Capabilities,
+};
+
+/// Creates a key package for `ciphersuite` and `credential` and stores its
+/// private keys in the key store of `provider`.
+///
+/// All `extensions` and `leaf_node_extensions` are listed in the capabilities
+/// of the leaf node.
+pub(super) fn new_key_package(
+    provider: &OpenMlsRustCrypto,
+    ciphersuite: Ciphersuite,
+    credential: Credential,
+    extensions: Extensions,
+    leaf_node_extensions: Extensions,
+) -> KeyPackage {
+    let extension_types: Vec<ExtensionType> = extensions
+        .iter()
+        .chain(leaf_node_extensions.iter())
+        .map(|extension| extension.extension_type())
+        .collect();
+    let (credential_with_key, signer) = new_signer(ciphersuite, credential);
+    KeyPackage::builder()
+        .key_package_extensions(extensions)
+        .leaf_node_capabilities(Capabilities::new(
+            None,
+            None,
+            Some(&extension_types),
+            None,
+            None,
+        ))
+        .leaf_node_extensions(leaf_node_extensions)
+        .build(
+            CryptoConfig::with_default_version(ciphersuite),
+            provider,
+            &signer,
+            credential_with_key,
+        )
+        .expect("error creating key package")
+}
+
+impl Arbitrary for KeyPackage {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        (
+            ciphersuite(),
+            any::<Credential>(),
+            key_package_extensions(),
+            leaf_node_extensions(),
+        )
+            .prop_map(
+                |(ciphersuite, credential, extensions, leaf_node_extensions)| {
+                    new_key_package(
+                        &OpenMlsRustCrypto::default(),
+                        ciphersuite,
+                        credential,
+                        extensions,
+                        leaf_node_extensions,
+                    )
+                },
+            )
+            .boxed()
+    }
+}
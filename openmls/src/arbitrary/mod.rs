@@ -0,0 +1,157 @@
+//! # Arbitrary protocol values
+//!
+//! This module is only available with the `proptest` feature. It implements
+//! [`proptest`]'s [`Arbitrary`] trait for key packages, proposals, extensions
+//! and ratchet trees, so that they can be used in property-based tests, both
+//! in OpenMLS and in applications built on top of it.
+//!
+//! The generated values are valid, not just well-formed:
+//! - [`KeyPackage`](crate::key_packages::KeyPackage)s are created and signed
+//!   by OpenMLS and pass validation. All their extensions are listed in the
+//!   capabilities of their leaf node.
+//! - Ratchet trees are exported from groups that OpenMLS built with arbitrary
+//!   members, some of which are removed again. See [`ArbitraryRatchetTree`]
+//!   for the context needed to verify them.
+//! - Extensions and proposals are encoded to bytes that decode to the same
+//!   value. Keys in them are real keys of the chosen ciphersuite. Extensions
+//!   are only generated where they are allowed by the protocol, e.g.,
+//!   [`key_package_extensions()`] only contains key package extensions.
+//!
+//! Some values can't be generated without the state of a group and are
+//! therefore left out: [`Proposal`](crate::messages::proposals::Proposal)s
+//! don't include `Update` and `AppAck` proposals, and
+//! [`Extension`](crate::extensions::Extension)s don't include ratchet tree
+//! extensions.
+//!
+//! All cryptographic operations use [`OpenMlsRustCrypto`]. HPKE keys are
+//! derived from the randomness of the test runner, signature keys are not.
+//! Failing cases therefore shrink to the same structure, but not to the same
+//! bytes.
+//!
+//! ```ignore
+//! use openmls::prelude::*;
+//! use proptest::prelude::*;
+//!
+//! proptest! {
+//!     #[test]
+//!     fn key_packages_round_trip(key_package in any::<KeyPackage>()) {
+//!         let provider = OpenMlsRustCrypto::default();
+//!         let bytes = key_package.tls_serialize_detached().unwrap();
+//!         let decoded = KeyPackageIn::tls_deserialize_exact(bytes)
+//!             .unwrap()
+//!             .validate(provider.crypto(), ProtocolVersion::Mls10, provider.clock())
+//!             .unwrap();
+//!         prop_assert_eq!(decoded, key_package);
+//!     }
+//! }
+//! ```
+
+use openmls_basic_credential::SignatureKeyPair;
+use openmls_rust_crypto::OpenMlsRustCrypto;
+use openmls_traits::{crypto::OpenMlsCrypto, types::Ciphersuite, OpenMlsProvider};
+use proptest::{collection::vec, prelude::*, sample::select};
+
+use crate::{
+    binary_tree::{array_representation::MAX_TREE_SIZE, LeafNodeIndex},
+    credentials::{Credential, CredentialType, CredentialWithKey},
+    group::GroupId,
+};
+
+mod extensions;
+mod key_packages;
+mod proposals;
+mod tree;
+
+pub use extensions::{group_context_extensions, key_package_extensions, leaf_node_extensions};
+pub use tree::ArbitraryRatchetTree;
+
+/// Returns a strategy for the ciphersuites that are supported by
+/// [`OpenMlsRustCrypto`].
+pub fn ciphersuite() -> impl Strategy<Value = Ciphersuite> {
+    select(
+        OpenMlsRustCrypto::default()
+            .crypto()
+            .supported_ciphersuites(),
+    )
+}
+
+/// Returns a strategy for identifiers, such as group IDs or credential
+/// identities.
+fn identifier() -> impl Strategy<Value = Vec<u8>> {
+    vec(any::<u8>(), 1..32)
+}
+
+/// Creates a signature key pair for `ciphersuite` and binds it to
+/// `credential`.
+fn new_signer(
+    ciphersuite: Ciphersuite,
+    credential: Credential,
+) -> (CredentialWithKey, SignatureKeyPair) {
+    let signer = SignatureKeyPair::new(ciphersuite.signature_algorithm())
+        .expect("error creating signature keys");
+    let credential_with_key = CredentialWithKey {
+        credential,
+        signature_key: signer.public().into(),
+    };
+
+    (credential_with_key, signer)
+}
+
+/// Derives an HPKE public key for `ciphersuite` from `ikm`.
+fn hpke_public_key(ciphersuite: Ciphersuite, ikm: &[u8]) -> Vec<u8> {
+    OpenMlsRustCrypto::default()
+        .crypto()
+        .derive_hpke_keypair(ciphersuite.hpke_config(), ikm)
+        .public
+}
+
+impl Arbitrary for GroupId {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        identifier()
+            .prop_map(|group_id| GroupId::from_slice(&group_id))
+            .boxed()
+    }
+}
+
+impl Arbitrary for LeafNodeIndex {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        (0..MAX_TREE_SIZE / 2).prop_map(LeafNodeIndex::new).boxed()
+    }
+}
+
+impl Arbitrary for CredentialType {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        prop_oneof![
+            Just(CredentialType::Basic),
+            Just(CredentialType::X509),
+            any::<u16>().prop_map(CredentialType::from).prop_filter(
+                "known credential type",
+                |credential_type| matches!(credential_type, CredentialType::Unknown(_))
+            ),
+        ]
+        .boxed()
+    }
+}
+
+/// Generates basic credentials with arbitrary identities.
+impl Arbitrary for Credential {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        identifier()
+            .prop_map(|identity| {
+                Credential::new(identity, CredentialType::Basic).expect("error creating credential")
+            })
+            .boxed()
+    }
+}
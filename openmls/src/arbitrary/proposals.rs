@@ -0,0 +1,155 @@
+//! Arbitrary proposals.
+
+use proptest::{array::uniform32, prelude::*};
+
+use super::{ciphersuite, group_context_extensions, hpke_public_key, identifier};
+use crate::{
+    binary_tree::LeafNodeIndex,
+    group::{GroupEpoch, GroupId},
+    key_packages::KeyPackage,
+    messages::proposals::*,
+    schedule::psk::{PreSharedKeyId, ResumptionPskUsage},
+    versions::ProtocolVersion,
+};
+
+impl Arbitrary for ProposalType {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        prop_oneof![
+            Just(ProposalType::Add),
+            Just(ProposalType::Update),
+            Just(ProposalType::Remove),
+            Just(ProposalType::PreSharedKey),
+            Just(ProposalType::Reinit),
+            Just(ProposalType::ExternalInit),
+            Just(ProposalType::GroupContextExtensions),
+            Just(ProposalType::AppAck),
+            any::<u16>().prop_map(ProposalType::from).prop_filter(
+                "known proposal type",
+                |proposal_type| matches!(proposal_type, ProposalType::Unknown(_))
+            ),
+        ]
+        .boxed()
+    }
+}
+
+impl Arbitrary for AddProposal {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        any::<KeyPackage>()
+            .prop_map(|key_package| AddProposal { key_package })
+            .boxed()
+    }
+}
+
+impl Arbitrary for RemoveProposal {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        any::<LeafNodeIndex>()
+            .prop_map(|removed| RemoveProposal { removed })
+            .boxed()
+    }
+}
+
+/// Generates external PSKs and resumption PSKs for applications, i.e., the
+/// PSKs that may appear in proposals.
+impl Arbitrary for PreSharedKeyId {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        prop_oneof![
+            (identifier(), uniform32(any::<u8>())).prop_map(|(psk_id, psk_nonce)| {
+                PreSharedKeyId::external(psk_id, psk_nonce.to_vec())
+            }),
+            (any::<GroupId>(), any::<u64>(), uniform32(any::<u8>())).prop_map(
+                |(psk_group_id, psk_epoch, psk_nonce)| {
+                    PreSharedKeyId::resumption(
+                        ResumptionPskUsage::Application,
+                        psk_group_id,
+                        GroupEpoch::from(psk_epoch),
+                        psk_nonce.to_vec(),
+                    )
+                }
+            ),
+        ]
+        .boxed()
+    }
+}
+
+impl Arbitrary for PreSharedKeyProposal {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        any::<PreSharedKeyId>()
+            .prop_map(PreSharedKeyProposal::new)
+            .boxed()
+    }
+}
+
+impl Arbitrary for ReInitProposal {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        (any::<GroupId>(), ciphersuite(), group_context_extensions())
+            .prop_map(|(group_id, ciphersuite, extensions)| ReInitProposal {
+                group_id,
+                version: ProtocolVersion::Mls10,
+                ciphersuite,
+                extensions,
+            })
+            .boxed()
+    }
+}
+
+/// Generates KEM outputs that are HPKE public keys of the chosen ciphersuite,
+/// as for the DHKEMs.
+impl Arbitrary for ExternalInitProposal {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        (ciphersuite(), uniform32(any::<u8>()))
+            .prop_map(|(ciphersuite, ikm)| {
+                ExternalInitProposal::from(hpke_public_key(ciphersuite, &ikm))
+            })
+            .boxed()
+    }
+}
+
+impl Arbitrary for GroupContextExtensionProposal {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        group_context_extensions()
+            .prop_map(GroupContextExtensionProposal::new)
+            .boxed()
+    }
+}
+
+/// Generates all proposals but `Update` and `AppAck` proposals.
+impl Arbitrary for Proposal {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        prop_oneof![
+            any::<AddProposal>().prop_map(Proposal::Add),
+            any::<RemoveProposal>().prop_map(Proposal::Remove),
+            any::<PreSharedKeyProposal>().prop_map(Proposal::PreSharedKey),
+            any::<ReInitProposal>().prop_map(Proposal::ReInit),
+            any::<ExternalInitProposal>().prop_map(Proposal::ExternalInit),
+            any::<GroupContextExtensionProposal>().prop_map(Proposal::GroupContextExtensions),
+        ]
+        .boxed()
+    }
+}
@@ -0,0 +1,168 @@
+//! Arbitrary ratchet trees.
+
+use openmls_rust_crypto::OpenMlsRustCrypto;
+use openmls_traits::types::Ciphersuite;
+use proptest::{collection::vec, prelude::*};
+
+use super::{ciphersuite, key_packages::new_key_package, leaf_node_extensions, new_signer};
+use crate::{
+    binary_tree::LeafNodeIndex,
+    credentials::Credential,
+    extensions::Extensions,
+    group::{config::CryptoConfig, GroupId, MlsGroup, MlsGroupConfig},
+    key_packages::KeyPackage,
+    treesync::RatchetTree,
+};
+
+/// The maximum number of members that are added to the group of an
+/// [`ArbitraryRatchetTree`] in addition to its creator.
+const MAX_ADDED_MEMBERS: usize = 8;
+
+/// A valid [`RatchetTree`] together with the ciphersuite and the ID of the
+/// group it was exported from.
+///
+/// The tree is exported from a group in which the creator added up to eight
+/// members, updated its own leaf and removed some of the added members again.
+/// The tree therefore has parent nodes and may have blank leaves and parent
+/// nodes.
+///
+/// Leaf nodes that were updated are bound to the group, so the ratchet tree
+/// can only be verified with [`ArbitraryRatchetTree::ciphersuite()`] and
+/// [`ArbitraryRatchetTree::group_id()`]. Use `any::<RatchetTree>()` if you
+/// only need the tree.
+#[derive(Debug, Clone)]
+pub struct ArbitraryRatchetTree {
+    ciphersuite: Ciphersuite,
+    group_id: GroupId,
+    ratchet_tree: RatchetTree,
+}
+
+impl ArbitraryRatchetTree {
+    /// Returns the ciphersuite of the group.
+    pub fn ciphersuite(&self) -> Ciphersuite {
+        self.ciphersuite
+    }
+
+    /// Returns the ID of the group.
+    pub fn group_id(&self) -> &GroupId {
+        &self.group_id
+    }
+
+    /// Returns the ratchet tree of the group.
+    pub fn ratchet_tree(&self) -> &RatchetTree {
+        &self.ratchet_tree
+    }
+
+    /// Returns the ratchet tree of the group and consumes the
+    /// [`ArbitraryRatchetTree`].
+    pub fn into_ratchet_tree(self) -> RatchetTree {
+        self.ratchet_tree
+    }
+
+    /// Builds the group and exports its ratchet tree. Each entry of `members`
+    /// is the credential and the leaf node extensions of a member that is
+    /// added, and whether the member is removed again.
+    fn new(
+        ciphersuite: Ciphersuite,
+        group_id: GroupId,
+        creator: Credential,
+        members: Vec<(Credential, Extensions, bool)>,
+    ) -> Self {
+        let provider = OpenMlsRustCrypto::default();
+        let (credential_with_key, signer) = new_signer(ciphersuite, creator);
+        let mls_group_config = MlsGroupConfig::builder()
+            .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+            .build();
+        let mut group = MlsGroup::new_with_group_id(
+            &provider,
+            &signer,
+            &mls_group_config,
+            group_id.clone(),
+            credential_with_key,
+        )
+        .expect("error creating group");
+
+        if !members.is_empty() {
+            let key_packages: Vec<KeyPackage> = members
+                .iter()
+                .map(|(credential, leaf_node_extensions, _)| {
+                    new_key_package(
+                        &provider,
+                        ciphersuite,
+                        credential.clone(),
+                        Extensions::empty(),
+                        leaf_node_extensions.clone(),
+                    )
+                })
+                .collect();
+            group
+                .add_members(&provider, &signer, &key_packages)
+                .expect("error adding members");
+            group
+                .merge_pending_commit(&provider)
+                .expect("error merging commit");
+        }
+
+        group
+            .self_update(&provider, &signer)
+            .expect("error updating");
+        group
+            .merge_pending_commit(&provider)
+            .expect("error merging commit");
+
+        // The creator is in the first leaf and the added members in the
+        // following ones.
+        let removed: Vec<LeafNodeIndex> = members
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, _, removed))| *removed)
+            .map(|(index, _)| LeafNodeIndex::new(index as u32 + 1))
+            .collect();
+        if !removed.is_empty() {
+            group
+                .remove_members(&provider, &signer, &removed)
+                .expect("error removing members");
+            group
+                .merge_pending_commit(&provider)
+                .expect("error merging commit");
+        }
+
+        Self {
+            ciphersuite,
+            group_id,
+            ratchet_tree: group.export_ratchet_tree(),
+        }
+    }
+}
+
+impl Arbitrary for ArbitraryRatchetTree {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        (
+            ciphersuite(),
+            any::<GroupId>(),
+            any::<Credential>(),
+            vec(
+                (any::<Credential>(), leaf_node_extensions(), any::<bool>()),
+                0..=MAX_ADDED_MEMBERS,
+            ),
+        )
+            .prop_map(|(ciphersuite, group_id, creator, members)| {
+                ArbitraryRatchetTree::new(ciphersuite, group_id, creator, members)
+            })
+            .boxed()
+    }
+}
+
+impl Arbitrary for RatchetTree {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        any::<ArbitraryRatchetTree>()
+            .prop_map(ArbitraryRatchetTree::into_ratchet_tree)
+            .boxed()
+    }
+}
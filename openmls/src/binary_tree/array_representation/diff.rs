@@ -67,9 +67,25 @@ impl<L: Clone + Debug + Default, P: Clone + Debug + Default> StagedAbDiff<L, P>
     }
 
     /// Return the projected size of the tree after a merge with the diff.
-    pub(super) fn tree_size(&self) -> TreeSize {
+    pub(crate) fn tree_size(&self) -> TreeSize {
         self.size
     }
+
+    /// Return the leaf at the given index if it was changed by the diff.
+    pub(crate) fn changed_leaf(&self, leaf_index: LeafNodeIndex) -> Option<&L> {
+        self.leaf_diff.get(&leaf_index)
+    }
+
+    /// Return the parent at the given index if it was changed by the diff.
+    pub(crate) fn changed_parent(&self, parent_index: ParentNodeIndex) -> Option<&P> {
+        self.parent_diff.get(&parent_index)
+    }
+
+    /// Return an iterator over the leaves changed by the diff, in order of
+    /// their indices.
+    pub(crate) fn changed_leaves(&self) -> impl Iterator<Item = (LeafNodeIndex, &L)> {
+        self.leaf_diff.iter().map(|(index, leaf)| (*index, leaf))
+    }
 }
 
 /// The [`AbDiff`] represents a set of differences (i.e. a "Diff") for an
@@ -26,6 +26,9 @@ pub(crate) use treemath::{
 #[cfg(any(feature = "test-utils", test))]
 pub(crate) use treemath::level;
 
+#[cfg(feature = "proptest")]
+pub(crate) use treemath::MAX_TREE_SIZE;
+
 mod treemath;
 
 // Tests
@@ -36,6 +36,7 @@ use super::Ciphersuite;
 
 const KEY_PACKAGE_REF_LABEL: &[u8; 28] = b"MLS 1.0 KeyPackage Reference";
 const PROPOSAL_REF_LABEL: &[u8; 26] = b"MLS 1.0 Proposal Reference";
+const MESSAGE_REF_LABEL: &[u8; 25] = b"MLS 1.0 Message Reference";
 
 /// A reference to an MLS object computed as a hash of the value.
 #[derive(
@@ -63,6 +64,10 @@ pub type KeyPackageRef = HashReference;
 /// This value uniquely identifies a proposal.
 pub type ProposalRef = HashReference;
 
+/// A reference to an MLS message.
+/// This value uniquely identifies a message, e.g., in receipts.
+pub type MessageRef = HashReference;
+
 #[derive(TlsSerialize, TlsSize)]
 struct HashReferenceInput<'a> {
     label: VLByteSlice<'a>,
@@ -78,6 +83,15 @@ pub fn make_proposal_ref(
     HashReference::new(value, ciphersuite, crypto, PROPOSAL_REF_LABEL)
 }
 
+/// Compute a new [`MessageRef`] value for a `value`.
+pub fn make_message_ref(
+    value: &[u8],
+    ciphersuite: Ciphersuite,
+    crypto: &impl OpenMlsCrypto,
+) -> Result<MessageRef, CryptoError> {
+    HashReference::new(value, ciphersuite, crypto, MESSAGE_REF_LABEL)
+}
+
 /// Compute a new [`KeyPackageRef`] value for a `value`.
 pub fn make_key_package_ref(
     value: &[u8],
@@ -36,7 +36,7 @@ impl Mac {
         })
     }
 
-    #[cfg(test)]
+    #[cfg(any(test, feature = "test-utils"))]
     pub(crate) fn flip_last_byte(&mut self) {
         let mut last_bits = self.mac_value.pop().expect("An unexpected error occurred.");
         last_bits ^= 0xff;
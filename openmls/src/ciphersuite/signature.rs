@@ -109,7 +109,7 @@ pub struct OpenMlsSignaturePublicKey {
     pub(in crate::ciphersuite) value: VLBytes,
 }
 
-#[cfg(test)]
+#[cfg(any(test, feature = "test-utils"))]
 impl Signature {
     pub(crate) fn modify(&mut self, value: &[u8]) {
         self.value = value.to_vec().into();
@@ -121,7 +121,7 @@ impl Signature {
 
 impl Signature {
     /// Get this signature as slice.
-    pub(super) fn value(&self) -> &[u8] {
+    pub(crate) fn value(&self) -> &[u8] {
         self.value.as_slice()
     }
 }
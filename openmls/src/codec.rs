@@ -0,0 +1,290 @@
+//! # Position-aware decoding
+//!
+//! The [`tls_codec::Error`]s returned when decoding a structure don't tell
+//! where decoding failed, which makes it hard to debug interoperability
+//! problems with other MLS implementations.
+//!
+//! [`DecodeWithPosition::tls_deserialize_with_position()`] decodes a structure
+//! from a byte slice like `tls_deserialize_exact()`, but returns a
+//! [`DecodeError`] that contains the byte offset at which decoding failed,
+//! the name of the decoded structure and, if the input ended too early, the
+//! number of bytes that were expected and available at that offset.
+//!
+//! [`DecodeWithPosition::tls_deserialize_with_config()`] additionally enforces
+//! the limits of a [`DecoderConfig`] on inputs from untrusted sources.
+//!
+//! ```
+//! use openmls::prelude::*;
+//!
+//! // A 2-byte protocol version that is cut off after the first byte.
+//! let error = ProtocolVersion::tls_deserialize_with_position(&[0x00]).unwrap_err();
+//! assert_eq!(error.offset(), 0);
+//! assert_eq!(error.expected(), Some(2));
+//! assert_eq!(error.available(), 1);
+//! ```
+
+use std::{
+    fmt::{self, Display},
+    io::{self, Read},
+};
+
+use tls_codec::Deserialize;
+
+/// An error that occurred while decoding a structure with
+/// [`DecodeWithPosition::tls_deserialize_with_position()`].
+#[derive(Debug, PartialEq, Clone)]
+pub struct DecodeError {
+    offset: usize,
+    structure: &'static str,
+    expected: Option<usize>,
+    available: usize,
+    error: tls_codec::Error,
+}
+
+impl DecodeError {
+    /// Returns the offset in bytes at which decoding failed. For trailing
+    /// data, this is the length of the decoded structure.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns the type name of the structure that was decoded.
+    pub fn structure(&self) -> &'static str {
+        self.structure
+    }
+
+    /// Returns the number of bytes that were expected at the offset if the
+    /// input ended too early.
+    pub fn expected(&self) -> Option<usize> {
+        self.expected
+    }
+
+    /// Returns the number of bytes that were available at the offset.
+    pub fn available(&self) -> usize {
+        self.available
+    }
+
+    /// Returns the error of the decoder.
+    pub fn error(&self) -> &tls_codec::Error {
+        &self.error
+    }
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self {
+            offset,
+            structure,
+            expected,
+            available,
+            error,
+        } = self;
+        write!(
+            f,
+            "Decoding {structure} failed at byte {offset} ({error:?})"
+        )?;
+        match expected {
+            Some(expected) => write!(f, ": expected {expected} bytes, {available} available."),
+            None => write!(f, ": {available} bytes available."),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// Limits that are enforced while decoding with
+/// [`DecodeWithPosition::tls_deserialize_with_config()`].
+///
+/// Length prefixes are controlled by whoever sent the encoded structure. The
+/// limits bound the size of the whole input and of every single read from it,
+/// i.e., the content of a byte vector. A read that exceeds the input or the
+/// limit fails before any bytes are copied. By default, nothing is limited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecoderConfig {
+    max_input_size: usize,
+    max_vector_length: usize,
+}
+
+impl Default for DecoderConfig {
+    fn default() -> Self {
+        Self {
+            max_input_size: usize::MAX,
+            max_vector_length: usize::MAX,
+        }
+    }
+}
+
+impl DecoderConfig {
+    /// Sets the maximum size of the input in bytes.
+    pub const fn with_max_input_size(mut self, max_input_size: usize) -> Self {
+        self.max_input_size = max_input_size;
+        self
+    }
+
+    /// Sets the maximum length of a single byte vector in bytes.
+    pub const fn with_max_vector_length(mut self, max_vector_length: usize) -> Self {
+        self.max_vector_length = max_vector_length;
+        self
+    }
+
+    /// Returns the maximum size of the input in bytes.
+    pub const fn max_input_size(&self) -> usize {
+        self.max_input_size
+    }
+
+    /// Returns the maximum length of a single byte vector in bytes.
+    pub const fn max_vector_length(&self) -> usize {
+        self.max_vector_length
+    }
+}
+
+/// Decoding with a [`DecodeError`] that tells where decoding failed. This
+/// trait is implemented for all types that implement
+/// [`tls_codec::Deserialize`].
+pub trait DecodeWithPosition: Sized {
+    /// Decode `Self` from `bytes`. All bytes have to be consumed.
+    fn tls_deserialize_with_position(bytes: &[u8]) -> Result<Self, DecodeError> {
+        Self::tls_deserialize_with_config(bytes, &DecoderConfig::default())
+    }
+
+    /// Decode `Self` from `bytes` within the limits of `config`. All bytes
+    /// have to be consumed. Exceeding a limit returns a [`DecodeError`] with
+    /// [`tls_codec::Error::InvalidVectorLength`] and the length that exceeded
+    /// the limit as [`DecodeError::expected()`].
+    fn tls_deserialize_with_config(
+        bytes: &[u8],
+        config: &DecoderConfig,
+    ) -> Result<Self, DecodeError>;
+}
+
+impl<T: Deserialize> DecodeWithPosition for T {
+    fn tls_deserialize_with_config(
+        bytes: &[u8],
+        config: &DecoderConfig,
+    ) -> Result<Self, DecodeError> {
+        let mut reader = PositionReader {
+            bytes,
+            offset: 0,
+            expected: None,
+            max_read: config.max_vector_length,
+            limit_exceeded: false,
+        };
+        let error = |reader: &PositionReader, error| DecodeError {
+            offset: reader.offset,
+            structure: std::any::type_name::<T>(),
+            expected: reader.expected,
+            available: reader.remaining(),
+            error,
+        };
+        if bytes.len() > config.max_input_size {
+            reader.expected = Some(bytes.len());
+            return Err(error(&reader, tls_codec::Error::InvalidVectorLength));
+        }
+        let value = T::tls_deserialize(&mut reader).map_err(|e| {
+            if reader.limit_exceeded {
+                error(&reader, tls_codec::Error::InvalidVectorLength)
+            } else {
+                error(&reader, e)
+            }
+        })?;
+        if reader.remaining() > 0 {
+            return Err(error(&reader, tls_codec::Error::TrailingData));
+        }
+        Ok(value)
+    }
+}
+
+/// A reader that keeps track of the number of bytes read and of the length
+/// of the read that exceeded the input or the maximum read length.
+struct PositionReader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+    expected: Option<usize>,
+    max_read: usize,
+    limit_exceeded: bool,
+}
+
+impl PositionReader<'_> {
+    fn remaining(&self) -> usize {
+        self.bytes.len() - self.offset
+    }
+}
+
+impl Read for PositionReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let length = buf.len().min(self.remaining());
+        buf[..length].copy_from_slice(&self.bytes[self.offset..self.offset + length]);
+        self.offset += length;
+        Ok(length)
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> io::Result<()> {
+        if buf.len() > self.max_read {
+            self.expected = Some(buf.len());
+            self.limit_exceeded = true;
+            return Err(io::ErrorKind::InvalidData.into());
+        }
+        if buf.len() > self.remaining() {
+            self.expected = Some(buf.len());
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        self.read(buf).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tls_codec::{Serialize, VLBytes};
+
+    use super::*;
+    use crate::versions::ProtocolVersion;
+
+    #[test]
+    fn decode_with_position() {
+        let bytes = VLBytes::new(vec![1, 2, 3, 4, 5])
+            .tls_serialize_detached()
+            .unwrap();
+        assert_eq!(
+            VLBytes::tls_deserialize_with_position(&bytes).unwrap(),
+            VLBytes::new(vec![1, 2, 3, 4, 5])
+        );
+
+        // The vector is cut off after the length and two bytes.
+        let error = VLBytes::tls_deserialize_with_position(&bytes[..3]).unwrap_err();
+        assert_eq!(error.offset(), 1);
+        assert_eq!(error.structure(), std::any::type_name::<VLBytes>());
+        assert_eq!(error.expected(), Some(5));
+        assert_eq!(error.available(), 2);
+
+        // Trailing data is reported after the decoded structure.
+        let mut bytes = ProtocolVersion::Mls10.tls_serialize_detached().unwrap();
+        bytes.extend_from_slice(&[0, 0, 0]);
+        let error = ProtocolVersion::tls_deserialize_with_position(&bytes).unwrap_err();
+        assert_eq!(error.error(), &tls_codec::Error::TrailingData);
+        assert_eq!(error.offset(), 2);
+        assert_eq!(error.expected(), None);
+        assert_eq!(error.available(), 3);
+        assert!(error.to_string().contains("ProtocolVersion"));
+    }
+
+    #[test]
+    fn decode_with_config() {
+        let bytes = VLBytes::new(vec![7; 100]).tls_serialize_detached().unwrap();
+        let config = DecoderConfig::default().with_max_vector_length(100);
+        assert!(VLBytes::tls_deserialize_with_config(&bytes, &config).is_ok());
+
+        // The vector is longer than allowed.
+        let config = DecoderConfig::default().with_max_vector_length(99);
+        let error = VLBytes::tls_deserialize_with_config(&bytes, &config).unwrap_err();
+        assert_eq!(error.error(), &tls_codec::Error::InvalidVectorLength);
+        assert_eq!(error.offset(), 2);
+        assert_eq!(error.expected(), Some(100));
+
+        // The input is longer than allowed.
+        let config = DecoderConfig::default().with_max_input_size(101);
+        let error = VLBytes::tls_deserialize_with_config(&bytes, &config).unwrap_err();
+        assert_eq!(error.error(), &tls_codec::Error::InvalidVectorLength);
+        assert_eq!(error.offset(), 0);
+        assert_eq!(error.expected(), Some(102));
+    }
+}
@@ -8,7 +8,8 @@ impl tls_codec::Size for Credential {
         self.credential_type.tls_serialized_len()
             + match &self.credential {
                 MlsCredentialType::Basic(c) => c.tls_serialized_len(),
-                MlsCredentialType::X509(_) => unimplemented!(),
+                // X509 credentials can't be encoded yet, see `tls_serialize`.
+                MlsCredentialType::X509(_) => 0,
             }
     }
 }
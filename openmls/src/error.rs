@@ -35,12 +35,44 @@
 //!
 //! All errors derive [`thiserror::Error`](https://docs.rs/thiserror/latest/thiserror/) as well as
 //! [`Debug`](`std::fmt::Debug`), [`PartialEq`](`std::cmp::PartialEq`), and [`Clone`](`std::clone::Clone`).
+//!
+//! ## Error codes
+//!
+//! The error enums are nested and change between releases. Applications that
+//! report errors across an FFI boundary or to a monitoring system can instead
+//! use the [`ErrorCode`] of an error, which is available for every public
+//! error through the [`StableErrorCode`] trait.
+//!
+//! Error codes are stable: the code of an error never changes and codes are
+//! never reused, even if the error enum is restructured. Errors that wrap
+//! another error, e.g., [`ProcessMessageError::ValidationError`](crate::group::ProcessMessageError::ValidationError),
+//! return the code of the wrapped error, so that the code always identifies
+//! the root cause.
+//!
+//! Every error code belongs to an [`ErrorCategory`], which tells an
+//! application how to react to the error, e.g., to retry with a fresh group
+//! state after a [`ErrorCategory::Protocol`] error.
+//!
+//! Every error code also has an [`ErrorSeverity`], which tells an application
+//! whether the group can still be used after the error:
+//!
+//! * [`ErrorSeverity::Recoverable`] errors leave the group state untouched.
+//!   This covers invalid input and wrong API use.
+//! * [`ErrorSeverity::RetrySafe`] errors leave the group state untouched, but
+//!   the operation failed because of the key store and can be retried.
+//! * After a [`ErrorSeverity::FatalForGroup`] error, the group must be
+//!   reloaded from storage or rejoined. This covers all
+//!   [`ErrorCategory::Internal`] errors, failures of the crypto provider,
+//!   key store failures while merging a commit and the use of a group the
+//!   client was removed from.
 
 use openmls_traits::types::CryptoError;
 use std::fmt::Display;
 use thiserror::Error;
 use tls_codec::Error as TlsCodecError;
 
+mod codes;
+
 /// Generic error type that indicates unrecoverable errors in the library.
 ///
 /// This error has 3 subtypes:
@@ -167,6 +199,169 @@ impl ErrorString {
     }
 }
 
+/// The category of an [`ErrorCode`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorCategory {
+    /// An incoming message, key package, welcome or other input is malformed
+    /// or invalid.
+    Validation,
+    /// A cryptographic operation failed, e.g., a decryption.
+    Crypto,
+    /// Accessing the key store failed or a value was not found in the key
+    /// store.
+    Storage,
+    /// The input is valid, but doesn't fit the current state of the group or
+    /// the capabilities of the client, e.g., a message for another epoch.
+    Protocol,
+    /// The API was used incorrectly, e.g., a commit was created while another
+    /// commit is pending.
+    Usage,
+    /// An internal error occurred. This is a bug in OpenMLS, see
+    /// [`LibraryError`].
+    Internal,
+}
+
+impl ErrorCategory {
+    /// Returns the category as a string, e.g., for use as a metric label.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCategory::Validation => "validation",
+            ErrorCategory::Crypto => "crypto",
+            ErrorCategory::Storage => "storage",
+            ErrorCategory::Protocol => "protocol",
+            ErrorCategory::Usage => "usage",
+            ErrorCategory::Internal => "internal",
+        }
+    }
+
+    /// The leading digit of the error codes of this category.
+    const fn digit(&self) -> u32 {
+        match self {
+            ErrorCategory::Validation => 1,
+            ErrorCategory::Crypto => 2,
+            ErrorCategory::Storage => 3,
+            ErrorCategory::Protocol => 4,
+            ErrorCategory::Usage => 5,
+            ErrorCategory::Internal => 9,
+        }
+    }
+
+    fn from_digit(digit: u32) -> Option<Self> {
+        match digit {
+            1 => Some(ErrorCategory::Validation),
+            2 => Some(ErrorCategory::Crypto),
+            3 => Some(ErrorCategory::Storage),
+            4 => Some(ErrorCategory::Protocol),
+            5 => Some(ErrorCategory::Usage),
+            9 => Some(ErrorCategory::Internal),
+            _ => None,
+        }
+    }
+}
+
+/// The effect of an error on the group it occurred in, see
+/// [`ErrorCode::severity()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorSeverity {
+    /// The input or the call was rejected and the group state is unchanged.
+    /// The group can be used as before.
+    ///
+    /// The only exception is the decryption key of a rejected private
+    /// message: it is deleted once it was derived, so that the message can't
+    /// be processed again.
+    Recoverable,
+    /// Accessing the key store failed. The group state is unchanged and the
+    /// operation can be retried once the key store is available again.
+    RetrySafe,
+    /// The group state in memory may be inconsistent with itself or with the
+    /// key store, or the client is no longer a member of the group. The group
+    /// must not be used any longer; it has to be reloaded from storage or
+    /// rejoined.
+    FatalForGroup,
+}
+
+impl ErrorSeverity {
+    /// Returns the severity as a string, e.g., for use as a metric label.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorSeverity::Recoverable => "recoverable",
+            ErrorSeverity::RetrySafe => "retry_safe",
+            ErrorSeverity::FatalForGroup => "fatal_for_group",
+        }
+    }
+}
+
+/// A stable numeric code that identifies an error.
+///
+/// The decimal representation of a code has six digits `CEEEVV`, where `C` is
+/// the [`ErrorCategory`], `EEE` identifies the error enum and `VV` the variant
+/// of the enum. For example, `403803` is the code of
+/// [`ValidationError::WrongEpoch`](crate::group::ValidationError::WrongEpoch).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ErrorCode(u32);
+
+impl ErrorCode {
+    const fn new(category: ErrorCategory, error: u32, variant: u32) -> Self {
+        Self(category.digit() * 100_000 + error * 100 + variant)
+    }
+
+    /// Parse an error code from its numeric value, e.g., a value that was
+    /// received across an FFI boundary.
+    ///
+    /// Returns `None` if `code` is not a well-formed error code. Note that
+    /// this doesn't check that an error with this code exists.
+    pub fn from_u32(code: u32) -> Option<Self> {
+        let error = (code / 100) % 1000;
+        let variant = code % 100;
+        (code < 1_000_000 && error != 0 && variant != 0)
+            .then(|| ErrorCategory::from_digit(code / 100_000))
+            .flatten()
+            .map(|_| Self(code))
+    }
+
+    /// Returns the numeric value of the code.
+    pub fn code(&self) -> u32 {
+        self.0
+    }
+
+    /// Returns the category of the code.
+    pub fn category(&self) -> ErrorCategory {
+        // Codes are only created with a valid category.
+        ErrorCategory::from_digit(self.0 / 100_000).unwrap_or(ErrorCategory::Internal)
+    }
+}
+
+impl From<ErrorCode> for u32 {
+    fn from(code: ErrorCode) -> Self {
+        code.0
+    }
+}
+
+impl Display for ErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:06}", self.0)
+    }
+}
+
+/// Errors that have a stable [`ErrorCode`]. This is implemented for all
+/// public errors of OpenMLS.
+pub trait StableErrorCode {
+    /// Returns the error code of the error.
+    fn error_code(&self) -> ErrorCode;
+
+    /// Returns the category of the error.
+    fn error_category(&self) -> ErrorCategory {
+        self.error_code().category()
+    }
+
+    /// Returns the effect of the error on the group.
+    fn error_severity(&self) -> ErrorSeverity {
+        self.error_code().severity()
+    }
+}
+
 /*
 
 Note to maintainers
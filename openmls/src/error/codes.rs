@@ -0,0 +1,1434 @@
+//! # Error code registry
+//!
+//! This module assigns the [`ErrorCode`]s of all public errors.
+//!
+//! Every error enum has a number in the registry below and every variant is
+//! numbered by its position in the enum, starting with 1. Variants that wrap
+//! another error return the code of the wrapped error, their number is
+//! unused. Enums that only wrap other errors don't have a number.
+//!
+//! To keep the codes stable:
+//!
+//! * New error enums are appended to the registry.
+//! * New variants are appended to their enum.
+//! * Removed enums and variants keep their number, it is never reused.
+//! * The category of a variant doesn't change.
+
+#[cfg(feature = "speculative")]
+use crate::group::SpeculativeKeyStoreError;
+#[cfg(feature = "async")]
+use crate::group::{AsyncGroupError, KeyStoreCacheError};
+#[cfg(test)]
+use crate::treesync::node::leaf_node::LeafNodeGenerationError;
+use crate::{
+    ciphersuite::signable::SignatureError,
+    credentials::errors::CredentialError,
+    extensions::errors::{RatchetTreeError as RatchetTreeExtensionError, *},
+    framing::errors::*,
+    group::{errors::*, public_group::errors::*},
+    inspect::errors::InspectError,
+    key_packages::errors::*,
+    messages::{group_info::GroupInfoError, GroupSecretsError},
+    metrics::SetMetricsError,
+    schedule::errors::PskError,
+    security_events::SetSecurityEventHandlerError,
+    tree::secret_tree::SecretTreeError,
+    treesync::{errors::*, RatchetTreeError},
+    versions::VersionError,
+};
+
+use super::{ErrorCategory::*, *};
+
+// === Registry ===
+
+const LIBRARY_ERROR: u32 = 1;
+const SIGNATURE_ERROR: u32 = 2;
+const CREDENTIAL_ERROR: u32 = 3;
+const EXTENSION_ERROR: u32 = 4;
+const CAPABILITIES_EXTENSION_ERROR: u32 = 5;
+const KEY_PACKAGE_ID_ERROR: u32 = 6;
+const PARENT_HASH_ERROR: u32 = 7;
+const RATCHET_TREE_EXTENSION_ERROR: u32 = 8;
+const CUSTOM_EXTENSION_ERROR: u32 = 9;
+const INVALID_EXTENSION_ERROR: u32 = 10;
+const MESSAGE_DECRYPTION_ERROR: u32 = 11;
+const SENDER_ERROR: u32 = 12;
+const MLS_MESSAGE_ERROR: u32 = 13;
+const KEY_PACKAGE_VERIFY_ERROR: u32 = 14;
+const KEY_PACKAGE_EXTENSION_SUPPORT_ERROR: u32 = 15;
+const KEY_PACKAGE_NEW_ERROR: u32 = 16;
+const SECRET_TREE_ERROR: u32 = 17;
+const NEW_GROUP_ERROR: u32 = 18;
+const EMPTY_INPUT_ERROR: u32 = 19;
+const MLS_GROUP_STATE_ERROR: u32 = 20;
+const PROCESS_MESSAGE_ERROR: u32 = 21;
+const PROPOSE_ADD_MEMBER_ERROR: u32 = 22;
+const PROPOSE_REMOVE_MEMBER_ERROR: u32 = 23;
+const REMOVE_MEMBERS_ERROR: u32 = 24;
+const SELF_UPDATE_ERROR: u32 = 25;
+const PROPOSE_SELF_UPDATE_ERROR: u32 = 26;
+const COMMIT_OPERATION_ERROR: u32 = 27;
+const STAGE_PREPARED_COMMIT_ERROR: u32 = 28;
+const AUDIT_LOG_ERROR: u32 = 29;
+const EXPORT_SECRET_ERROR: u32 = 30;
+const SHARED_MLS_GROUP_ERROR: u32 = 31;
+#[cfg(feature = "async")]
+const ASYNC_GROUP_ERROR: u32 = 32;
+#[cfg(feature = "async")]
+const KEY_STORE_CACHE_ERROR: u32 = 33;
+const WELCOME_ERROR: u32 = 34;
+const EXTERNAL_COMMIT_ERROR: u32 = 35;
+const STAGE_COMMIT_ERROR: u32 = 36;
+const CREATE_COMMIT_ERROR: u32 = 37;
+const VALIDATION_ERROR: u32 = 38;
+const PROPOSAL_VALIDATION_ERROR: u32 = 39;
+const EXTERNAL_COMMIT_VALIDATION_ERROR: u32 = 40;
+const MERGE_COMMIT_ERROR: u32 = 41;
+const CREATION_FROM_EXTERNAL_ERROR: u32 = 42;
+const PUBLIC_GROUP_BUILD_ERROR: u32 = 43;
+const PSK_ERROR: u32 = 44;
+const GROUP_INFO_ERROR: u32 = 45;
+const GROUP_SECRETS_ERROR: u32 = 46;
+const INSPECT_ERROR: u32 = 47;
+const SET_METRICS_ERROR: u32 = 48;
+const VERSION_ERROR: u32 = 49;
+#[cfg(test)]
+const LEAF_NODE_GENERATION_ERROR: u32 = 50;
+const PUBLIC_TREE_ERROR: u32 = 51;
+const APPLY_UPDATE_PATH_ERROR: u32 = 52;
+const LEAF_NODE_VALIDATION_ERROR: u32 = 53;
+const LIFETIME_ERROR: u32 = 54;
+const UPDATE_PATH_ERROR: u32 = 55;
+const RATCHET_TREE_ERROR: u32 = 56;
+const MEMORY_LIMIT_ERROR: u32 = 57;
+const SET_SECURITY_EVENT_HANDLER_ERROR: u32 = 58;
+const MEMBERSHIP_POLICY_ERROR: u32 = 59;
+const GROUP_INFO_VALIDATION_ERROR: u32 = 60;
+const KEY_PACKAGE_POOL_ERROR: u32 = 61;
+const SEQUENCING_ERROR: u32 = 62;
+const ABUSE_REPORT_ERROR: u32 = 63;
+const DEVICE_ERROR: u32 = 64;
+const HISTORY_ERROR: u32 = 65;
+const SUBGROUP_ERROR: u32 = 66;
+const GROUP_MERGE_ERROR: u32 = 67;
+const EXTERNAL_REMOVE_PROPOSAL_ERROR: u32 = 68;
+const CONTACT_PAIR_ERROR: u32 = 69;
+const WELCOME_DECLINE_ERROR: u32 = 70;
+const REISSUE_WELCOME_ERROR: u32 = 71;
+const RATCHET_EXPORT_ERROR: u32 = 72;
+const DISCARD_EPOCH_ERROR: u32 = 73;
+const SNAPSHOT_ERROR: u32 = 74;
+const COMMIT_LIMIT_ERROR: u32 = 75;
+#[cfg(feature = "speculative")]
+const SPECULATIVE_KEY_STORE_ERROR: u32 = 76;
+const API_MISUSE_ERROR: u32 = 77;
+
+// === Severity ===
+
+/// Errors that leave the group unusable, in addition to all [`Internal`]
+/// errors.
+const FATAL_FOR_GROUP: [ErrorCode; 3] = [
+    // The crypto provider failed in the middle of an operation.
+    ErrorCode::new(Crypto, LIBRARY_ERROR, 2),
+    // MlsGroupStateError::UseAfterEviction
+    ErrorCode::new(Usage, MLS_GROUP_STATE_ERROR, 2),
+    // MergeCommitError::KeyStoreError: the commit is merged in memory, but
+    // the key material of the new epoch may be missing from the key store.
+    ErrorCode::new(Storage, MERGE_COMMIT_ERROR, 2),
+];
+
+impl ErrorCode {
+    /// Returns the effect of the error with this code on the group.
+    pub fn severity(&self) -> ErrorSeverity {
+        if self.category() == Internal || FATAL_FOR_GROUP.contains(self) {
+            ErrorSeverity::FatalForGroup
+        } else if self.category() == Storage {
+            ErrorSeverity::RetrySafe
+        } else {
+            ErrorSeverity::Recoverable
+        }
+    }
+}
+
+// === Implementations ===
+
+impl StableErrorCode for LibraryError {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, LIBRARY_ERROR, variant);
+        match self.internal {
+            InternalLibraryError::MissingBoundsCheck(_) => code(Internal, 1),
+            InternalLibraryError::CryptoError(_) => code(Crypto, 2),
+            InternalLibraryError::Custom(_) => code(Internal, 3),
+        }
+    }
+}
+
+impl StableErrorCode for SignatureError {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, SIGNATURE_ERROR, variant);
+        match self {
+            SignatureError::VerificationError => code(Validation, 1),
+            SignatureError::SigningError => code(Crypto, 2),
+        }
+    }
+}
+
+impl StableErrorCode for CredentialError {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, CREDENTIAL_ERROR, variant);
+        match self {
+            CredentialError::LibraryError(e) => e.error_code(),
+            CredentialError::UnsupportedCredentialType => code(Validation, 2),
+            CredentialError::InvalidSignature => code(Validation, 3),
+        }
+    }
+}
+
+impl StableErrorCode for ExtensionError {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, EXTENSION_ERROR, variant);
+        match self {
+            ExtensionError::UnsupportedProposalType => code(Validation, 1),
+            ExtensionError::UnsupportedExtensionType => code(Validation, 2),
+            ExtensionError::LibraryError(e) => e.error_code(),
+            ExtensionError::InvalidExtensionType(_) => code(Validation, 4),
+            ExtensionError::Capabilities(e) => e.error_code(),
+            ExtensionError::KeyPackageId(e) => e.error_code(),
+            ExtensionError::ParentHash(e) => e.error_code(),
+            ExtensionError::RatchetTree(e) => e.error_code(),
+            ExtensionError::InvalidExtension(e) => e.error_code(),
+        }
+    }
+}
+
+impl StableErrorCode for CapabilitiesExtensionError {
+    fn error_code(&self) -> ErrorCode {
+        let code =
+            |category, variant| ErrorCode::new(category, CAPABILITIES_EXTENSION_ERROR, variant);
+        match self {
+            CapabilitiesExtensionError::Invalid => code(Validation, 1),
+            CapabilitiesExtensionError::EmptyVersionsField => code(Validation, 2),
+            CapabilitiesExtensionError::UnsupportedCiphersuite => code(Validation, 3),
+        }
+    }
+}
+
+impl StableErrorCode for KeyPackageIdError {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, KEY_PACKAGE_ID_ERROR, variant);
+        match self {
+            KeyPackageIdError::Invalid => code(Validation, 1),
+        }
+    }
+}
+
+impl StableErrorCode for ParentHashError {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, PARENT_HASH_ERROR, variant);
+        match self {
+            ParentHashError::Invalid => code(Validation, 1),
+        }
+    }
+}
+
+impl StableErrorCode for RatchetTreeExtensionError {
+    fn error_code(&self) -> ErrorCode {
+        let code =
+            |category, variant| ErrorCode::new(category, RATCHET_TREE_EXTENSION_ERROR, variant);
+        match self {
+            RatchetTreeExtensionError::Invalid => code(Validation, 1),
+        }
+    }
+}
+
+impl StableErrorCode for CustomExtensionError {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, CUSTOM_EXTENSION_ERROR, variant);
+        match self {
+            CustomExtensionError::ReservedExtensionType => code(Usage, 1),
+            CustomExtensionError::WrongExtensionType => code(Usage, 2),
+            CustomExtensionError::TrailingData => code(Validation, 3),
+            CustomExtensionError::CodecError(_) => code(Validation, 4),
+        }
+    }
+}
+
+impl StableErrorCode for InvalidExtensionError {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, INVALID_EXTENSION_ERROR, variant);
+        match self {
+            InvalidExtensionError::Duplicate => code(Validation, 1),
+            InvalidExtensionError::NotFound => code(Validation, 2),
+        }
+    }
+}
+
+impl StableErrorCode for MessageDecryptionError {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, MESSAGE_DECRYPTION_ERROR, variant);
+        match self {
+            MessageDecryptionError::LibraryError(e) => e.error_code(),
+            MessageDecryptionError::GenerationOutOfBound => code(Protocol, 2),
+            MessageDecryptionError::AeadError => code(Crypto, 3),
+            MessageDecryptionError::WrongWireFormat => code(Validation, 4),
+            MessageDecryptionError::MalformedContent => code(Validation, 5),
+            MessageDecryptionError::SecretTreeError(e) => e.error_code(),
+            MessageDecryptionError::SenderError(e) => e.error_code(),
+        }
+    }
+}
+
+impl StableErrorCode for SenderError {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, SENDER_ERROR, variant);
+        match self {
+            SenderError::LibraryError(e) => e.error_code(),
+            SenderError::NotAMember => code(Protocol, 2),
+            SenderError::UnknownSender => code(Protocol, 3),
+        }
+    }
+}
+
+impl StableErrorCode for MlsMessageError {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, MLS_MESSAGE_ERROR, variant);
+        match self {
+            MlsMessageError::UnableToDecode => code(Validation, 1),
+            MlsMessageError::UnableToEncode => code(Internal, 2),
+        }
+    }
+}
+
+impl StableErrorCode for KeyPackageVerifyError {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, KEY_PACKAGE_VERIFY_ERROR, variant);
+        match self {
+            KeyPackageVerifyError::LibraryError(e) => e.error_code(),
+            KeyPackageVerifyError::InvalidLifetime => code(Validation, 2),
+            KeyPackageVerifyError::MissingLifetime => code(Validation, 3),
+            KeyPackageVerifyError::UnsupportedExtension => code(Validation, 4),
+            KeyPackageVerifyError::InvalidSignature => code(Validation, 5),
+            KeyPackageVerifyError::InvalidLeafNodeSignature => code(Validation, 6),
+            KeyPackageVerifyError::InvalidLeafNodeSourceType => code(Validation, 7),
+            KeyPackageVerifyError::InitKeyEqualsEncryptionKey => code(Validation, 8),
+            KeyPackageVerifyError::InvalidProtocolVersion => code(Validation, 9),
+        }
+    }
+}
+
+impl StableErrorCode for KeyPackageExtensionSupportError {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| {
+            ErrorCode::new(category, KEY_PACKAGE_EXTENSION_SUPPORT_ERROR, variant)
+        };
+        match self {
+            KeyPackageExtensionSupportError::UnsupportedExtension => code(Validation, 1),
+        }
+    }
+}
+
+impl<KeyStoreError> StableErrorCode for KeyPackageNewError<KeyStoreError> {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, KEY_PACKAGE_NEW_ERROR, variant);
+        match self {
+            KeyPackageNewError::LibraryError(e) => e.error_code(),
+            KeyPackageNewError::CiphersuiteSignatureSchemeMismatch => code(Usage, 2),
+            KeyPackageNewError::KeyStoreError(_) => code(Storage, 3),
+            KeyPackageNewError::SignatureError(e) => e.error_code(),
+        }
+    }
+}
+
+impl StableErrorCode for SecretTreeError {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, SECRET_TREE_ERROR, variant);
+        match self {
+            SecretTreeError::TooDistantInThePast => code(Protocol, 1),
+            SecretTreeError::TooDistantInTheFuture => code(Protocol, 2),
+            SecretTreeError::IndexOutOfBounds => code(Validation, 3),
+            SecretTreeError::SecretReuseError => code(Protocol, 4),
+            SecretTreeError::RatchetTypeError => code(Internal, 5),
+            SecretTreeError::RatchetTooLong => code(Protocol, 6),
+            SecretTreeError::LibraryError => code(Internal, 7),
+            SecretTreeError::CodecError(_) => code(Internal, 8),
+            SecretTreeError::CryptoError(_) => code(Crypto, 9),
+            SecretTreeError::KeyEvicted => code(Protocol, 10),
+        }
+    }
+}
+
+impl<KeyStoreError> StableErrorCode for NewGroupError<KeyStoreError> {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, NEW_GROUP_ERROR, variant);
+        match self {
+            NewGroupError::LibraryError(e) => e.error_code(),
+            NewGroupError::NoMatchingKeyPackage => code(Storage, 2),
+            NewGroupError::KeyStoreError(_) => code(Storage, 3),
+            NewGroupError::UnsupportedProposalType => code(Usage, 4),
+            NewGroupError::UnsupportedExtensionType => code(Usage, 5),
+            NewGroupError::InvalidExtensions(e) => e.error_code(),
+            NewGroupError::InvalidGroupId => code(Validation, 7),
+            NewGroupError::AddMembersError(e) => e.error_code(),
+        }
+    }
+}
+
+impl StableErrorCode for EmptyInputError {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, EMPTY_INPUT_ERROR, variant);
+        match self {
+            EmptyInputError::AddMembers => code(Usage, 1),
+            EmptyInputError::RemoveMembers => code(Usage, 2),
+        }
+    }
+}
+
+impl StableErrorCode for MlsGroupStateError {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, MLS_GROUP_STATE_ERROR, variant);
+        match self {
+            MlsGroupStateError::LibraryError(e) => e.error_code(),
+            MlsGroupStateError::UseAfterEviction => code(Usage, 2),
+            MlsGroupStateError::PendingProposal => code(Usage, 3),
+            MlsGroupStateError::PendingCommit => code(Usage, 4),
+            MlsGroupStateError::NoPendingCommit => code(Usage, 5),
+            MlsGroupStateError::PendingProposalNotFound => code(Usage, 6),
+            MlsGroupStateError::SnapshotActive => code(Usage, 7),
+            MlsGroupStateError::ApiMisuse(e) => e.error_code(),
+        }
+    }
+}
+
+impl StableErrorCode for ApiMisuseError {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, API_MISUSE_ERROR, variant);
+        match self {
+            ApiMisuseError::MessageWithPendingCommit => code(Usage, 1),
+            ApiMisuseError::OwnMessage => code(Usage, 2),
+            ApiMisuseError::StaleStagedCommit => code(Usage, 3),
+        }
+    }
+}
+
+impl<KeyStoreError> StableErrorCode for MergePendingCommitError<KeyStoreError> {
+    fn error_code(&self) -> ErrorCode {
+        match self {
+            MergePendingCommitError::MlsGroupStateError(e) => e.error_code(),
+            MergePendingCommitError::MergeCommitError(e) => e.error_code(),
+        }
+    }
+}
+
+impl StableErrorCode for ProcessMessageError {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, PROCESS_MESSAGE_ERROR, variant);
+        match self {
+            ProcessMessageError::LibraryError(e) => e.error_code(),
+            ProcessMessageError::IncompatibleWireFormat => code(Validation, 2),
+            ProcessMessageError::ValidationError(e) => e.error_code(),
+            ProcessMessageError::GroupStateError(e) => e.error_code(),
+            ProcessMessageError::InvalidSignature => code(Validation, 5),
+            ProcessMessageError::InvalidCommit(e) => e.error_code(),
+            ProcessMessageError::UnauthorizedExternalApplicationMessage => code(Validation, 7),
+            ProcessMessageError::UnsupportedProposalType => code(Validation, 8),
+            ProcessMessageError::MemoryLimitError(e) => e.error_code(),
+            ProcessMessageError::InvalidProposal(e) => e.error_code(),
+            ProcessMessageError::SequencingError(e) => e.error_code(),
+            ProcessMessageError::DowngradeAttempt => code(Validation, 12),
+            ProcessMessageError::CommitPolicyViolation(_) => code(Validation, 13),
+            ProcessMessageError::VersionMismatch => code(Validation, 14),
+        }
+    }
+}
+
+impl StableErrorCode for CreateMessageError {
+    fn error_code(&self) -> ErrorCode {
+        match self {
+            CreateMessageError::LibraryError(e) => e.error_code(),
+            CreateMessageError::GroupStateError(e) => e.error_code(),
+        }
+    }
+}
+
+impl<KeyStoreError> StableErrorCode for AddMembersError<KeyStoreError> {
+    fn error_code(&self) -> ErrorCode {
+        match self {
+            AddMembersError::LibraryError(e) => e.error_code(),
+            AddMembersError::EmptyInput(e) => e.error_code(),
+            AddMembersError::CreateCommitError(e) => e.error_code(),
+            AddMembersError::GroupStateError(e) => e.error_code(),
+        }
+    }
+}
+
+impl StableErrorCode for ProposeAddMemberError {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, PROPOSE_ADD_MEMBER_ERROR, variant);
+        match self {
+            ProposeAddMemberError::LibraryError(e) => e.error_code(),
+            ProposeAddMemberError::UnsupportedExtensions => code(Usage, 2),
+            ProposeAddMemberError::GroupStateError(e) => e.error_code(),
+            ProposeAddMemberError::LeafNodeValidation(e) => e.error_code(),
+        }
+    }
+}
+
+impl StableErrorCode for ProposeRemoveMemberError {
+    fn error_code(&self) -> ErrorCode {
+        let code =
+            |category, variant| ErrorCode::new(category, PROPOSE_REMOVE_MEMBER_ERROR, variant);
+        match self {
+            ProposeRemoveMemberError::LibraryError(e) => e.error_code(),
+            ProposeRemoveMemberError::GroupStateError(e) => e.error_code(),
+            ProposeRemoveMemberError::UnknownMember => code(Usage, 3),
+        }
+    }
+}
+
+impl<KeyStoreError> StableErrorCode for RemoveMembersError<KeyStoreError> {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, REMOVE_MEMBERS_ERROR, variant);
+        match self {
+            RemoveMembersError::LibraryError(e) => e.error_code(),
+            RemoveMembersError::EmptyInput(e) => e.error_code(),
+            RemoveMembersError::CreateCommitError(e) => e.error_code(),
+            RemoveMembersError::GroupStateError(e) => e.error_code(),
+            RemoveMembersError::UnknownMember => code(Usage, 5),
+        }
+    }
+}
+
+impl StableErrorCode for LeaveGroupError {
+    fn error_code(&self) -> ErrorCode {
+        match self {
+            LeaveGroupError::LibraryError(e) => e.error_code(),
+            LeaveGroupError::GroupStateError(e) => e.error_code(),
+        }
+    }
+}
+
+impl<KeyStoreError> StableErrorCode for SelfUpdateError<KeyStoreError> {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, SELF_UPDATE_ERROR, variant);
+        match self {
+            SelfUpdateError::LibraryError(e) => e.error_code(),
+            SelfUpdateError::CreateCommitError(e) => e.error_code(),
+            SelfUpdateError::GroupStateError(e) => e.error_code(),
+            SelfUpdateError::KeyStoreError => code(Storage, 4),
+        }
+    }
+}
+
+impl<KeyStoreError> StableErrorCode for ProposeSelfUpdateError<KeyStoreError> {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, PROPOSE_SELF_UPDATE_ERROR, variant);
+        match self {
+            ProposeSelfUpdateError::LibraryError(e) => e.error_code(),
+            ProposeSelfUpdateError::GroupStateError(e) => e.error_code(),
+            ProposeSelfUpdateError::KeyStoreError(_) => code(Storage, 3),
+            ProposeSelfUpdateError::PublicTreeError(e) => e.error_code(),
+        }
+    }
+}
+
+impl<KeyStoreError> StableErrorCode for CommitToPendingProposalsError<KeyStoreError> {
+    fn error_code(&self) -> ErrorCode {
+        match self {
+            CommitToPendingProposalsError::LibraryError(e) => e.error_code(),
+            CommitToPendingProposalsError::CreateCommitError(e) => e.error_code(),
+            CommitToPendingProposalsError::GroupStateError(e) => e.error_code(),
+        }
+    }
+}
+
+impl StableErrorCode for CommitOperationError {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, COMMIT_OPERATION_ERROR, variant);
+        match self {
+            CommitOperationError::LibraryError(e) => e.error_code(),
+            CommitOperationError::SignatureError(e) => e.error_code(),
+            CommitOperationError::Cancelled => code(Usage, 3),
+        }
+    }
+}
+
+impl StableErrorCode for StagePreparedCommitError {
+    fn error_code(&self) -> ErrorCode {
+        let code =
+            |category, variant| ErrorCode::new(category, STAGE_PREPARED_COMMIT_ERROR, variant);
+        match self {
+            StagePreparedCommitError::LibraryError(e) => e.error_code(),
+            StagePreparedCommitError::GroupStateError(e) => e.error_code(),
+            StagePreparedCommitError::StaleCommit => code(Protocol, 3),
+        }
+    }
+}
+
+impl StableErrorCode for AuditLogError {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, AUDIT_LOG_ERROR, variant);
+        match self {
+            AuditLogError::LibraryError(e) => e.error_code(),
+            AuditLogError::BrokenChain => code(Validation, 2),
+            AuditLogError::InvalidHash => code(Validation, 3),
+            AuditLogError::EpochNotIncreasing => code(Validation, 4),
+            AuditLogError::HeadMismatch => code(Validation, 5),
+        }
+    }
+}
+
+impl StableErrorCode for ExportGroupInfoError {
+    fn error_code(&self) -> ErrorCode {
+        match self {
+            ExportGroupInfoError::LibraryError(e) => e.error_code(),
+            ExportGroupInfoError::GroupStateError(e) => e.error_code(),
+        }
+    }
+}
+
+impl StableErrorCode for ExportSecretError {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, EXPORT_SECRET_ERROR, variant);
+        match self {
+            ExportSecretError::LibraryError(e) => e.error_code(),
+            ExportSecretError::KeyLengthTooLong => code(Usage, 2),
+            ExportSecretError::GroupStateError(e) => e.error_code(),
+            ExportSecretError::ReservedLabel => code(Usage, 4),
+        }
+    }
+}
+
+impl StableErrorCode for ProposePskError {
+    fn error_code(&self) -> ErrorCode {
+        match self {
+            ProposePskError::Psk(e) => e.error_code(),
+            ProposePskError::GroupStateError(e) => e.error_code(),
+            ProposePskError::LibraryError(e) => e.error_code(),
+        }
+    }
+}
+
+impl<KeyStoreError> StableErrorCode for ProposalError<KeyStoreError> {
+    fn error_code(&self) -> ErrorCode {
+        match self {
+            ProposalError::LibraryError(e) => e.error_code(),
+            ProposalError::ProposeAddMemberError(e) => e.error_code(),
+            ProposalError::CreateAddProposalError(e) => e.error_code(),
+            ProposalError::ProposeSelfUpdateError(e) => e.error_code(),
+            ProposalError::ProposeRemoveMemberError(e) => e.error_code(),
+            ProposalError::GroupStateError(e) => e.error_code(),
+            ProposalError::ValidationError(e) => e.error_code(),
+        }
+    }
+}
+
+impl<KeyStoreError> StableErrorCode for ResolveCommitConflictError<KeyStoreError> {
+    fn error_code(&self) -> ErrorCode {
+        match self {
+            ResolveCommitConflictError::LibraryError(e) => e.error_code(),
+            ResolveCommitConflictError::GroupStateError(e) => e.error_code(),
+            ResolveCommitConflictError::MergeCommitError(e) => e.error_code(),
+            ResolveCommitConflictError::ProposalError(e) => e.error_code(),
+        }
+    }
+}
+
+impl StableErrorCode for SharedMlsGroupError {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, SHARED_MLS_GROUP_ERROR, variant);
+        match self {
+            SharedMlsGroupError::WouldBlock => code(Usage, 1),
+            SharedMlsGroupError::Poisoned => code(Internal, 2),
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<OperationError: StableErrorCode, KeyStoreError> StableErrorCode
+    for AsyncGroupError<OperationError, KeyStoreError>
+{
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, ASYNC_GROUP_ERROR, variant);
+        match self {
+            AsyncGroupError::LibraryError(e) => e.error_code(),
+            AsyncGroupError::Operation(e) => e.error_code(),
+            AsyncGroupError::KeyStoreError(_) => code(Storage, 3),
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl StableErrorCode for KeyStoreCacheError {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, KEY_STORE_CACHE_ERROR, variant);
+        match self {
+            KeyStoreCacheError::SerializationError(_) => code(Storage, 1),
+        }
+    }
+}
+
+impl<KeyStoreError> StableErrorCode for WelcomeError<KeyStoreError> {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, WELCOME_ERROR, variant);
+        match self {
+            WelcomeError::GroupSecrets(e) => e.error_code(),
+            WelcomeError::PrivateInitKeyNotFound => code(Storage, 2),
+            WelcomeError::LibraryError(e) => e.error_code(),
+            WelcomeError::CiphersuiteMismatch => code(Validation, 4),
+            WelcomeError::VersionMismatch => code(Validation, 5),
+            WelcomeError::GroupInfo(e) => e.error_code(),
+            WelcomeError::JoinerSecretNotFound => code(Validation, 7),
+            WelcomeError::MissingRatchetTree => code(Validation, 8),
+            WelcomeError::ConfirmationTagMismatch => code(Validation, 9),
+            WelcomeError::InvalidGroupInfoSignature => code(Validation, 10),
+            WelcomeError::UnsupportedMlsVersion => code(Protocol, 11),
+            WelcomeError::UnsupportedCapability => code(Protocol, 12),
+            WelcomeError::UnknownSender => code(Validation, 13),
+            WelcomeError::MalformedWelcomeMessage => code(Validation, 14),
+            WelcomeError::UnableToDecrypt => code(Crypto, 15),
+            WelcomeError::UnsupportedExtensions => code(Protocol, 16),
+            WelcomeError::Psk(e) => e.error_code(),
+            WelcomeError::NoMatchingEncryptionKey => code(Storage, 18),
+            WelcomeError::NoMatchingKeyPackage => code(Storage, 19),
+            WelcomeError::KeyStoreError(_) => code(Storage, 20),
+            WelcomeError::PublicTreeError(e) => e.error_code(),
+            WelcomeError::PublicGroupError(e) => e.error_code(),
+            WelcomeError::LeafNodeValidation(e) => e.error_code(),
+            WelcomeError::InvalidParentGroupLink => code(Validation, 24),
+            WelcomeError::UnexpectedGroupId => code(Validation, 25),
+            WelcomeError::UnexpectedCiphersuite => code(Validation, 26),
+            WelcomeError::UnexpectedInviter => code(Validation, 27),
+            WelcomeError::TooManyMembers => code(Validation, 28),
+            WelcomeError::KeyPackageConsumed => code(Validation, 29),
+            WelcomeError::InvalidGroupId => code(Validation, 30),
+            WelcomeError::DowngradeAttempt => code(Validation, 31),
+        }
+    }
+}
+
+impl StableErrorCode for ExternalCommitError {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, EXTERNAL_COMMIT_ERROR, variant);
+        match self {
+            ExternalCommitError::LibraryError(e) => e.error_code(),
+            ExternalCommitError::MissingRatchetTree => code(Validation, 2),
+            ExternalCommitError::MissingExternalPub => code(Validation, 3),
+            ExternalCommitError::UnsupportedCiphersuite => code(Protocol, 4),
+            ExternalCommitError::UnknownSender => code(Validation, 5),
+            ExternalCommitError::InvalidGroupInfoSignature => code(Validation, 6),
+            ExternalCommitError::CommitError => code(Protocol, 7),
+            ExternalCommitError::PublicGroupError(e) => e.error_code(),
+            ExternalCommitError::MissingCredential => code(Usage, 9),
+            ExternalCommitError::InvalidGroupId => code(Validation, 10),
+            ExternalCommitError::DowngradeAttempt => code(Validation, 11),
+        }
+    }
+}
+
+impl StableErrorCode for StageCommitError {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, STAGE_COMMIT_ERROR, variant);
+        match self {
+            StageCommitError::LibraryError(e) => e.error_code(),
+            StageCommitError::EpochMismatch => code(Protocol, 2),
+            StageCommitError::OwnCommit => code(Usage, 3),
+            StageCommitError::WrongPlaintextContentType => code(Validation, 4),
+            StageCommitError::PathLeafNodeVerificationFailure => code(Validation, 5),
+            StageCommitError::RequiredPathNotFound => code(Validation, 6),
+            StageCommitError::ConfirmationTagMissing => code(Validation, 7),
+            StageCommitError::ConfirmationTagMismatch => code(Validation, 8),
+            StageCommitError::AttemptedSelfRemoval => code(Validation, 9),
+            StageCommitError::MissingProposal => code(Protocol, 10),
+            StageCommitError::OwnKeyNotFound => code(Storage, 11),
+            StageCommitError::InconsistentSenderIndex => code(Validation, 12),
+            StageCommitError::SenderTypeExternal => code(Validation, 13),
+            StageCommitError::SenderTypeNewMemberProposal => code(Validation, 14),
+            StageCommitError::TooManyNewMembers => code(Validation, 15),
+            StageCommitError::ProposalValidationError(e) => e.error_code(),
+            StageCommitError::PskError(e) => e.error_code(),
+            StageCommitError::ExternalCommitValidation(e) => e.error_code(),
+            StageCommitError::UpdatePathError(e) => e.error_code(),
+            StageCommitError::MissingDecryptionKey => code(Storage, 20),
+            StageCommitError::VerifiedUpdatePathError(e) => e.error_code(),
+            StageCommitError::RetiredExternalKey => code(Validation, 22),
+            StageCommitError::CommitLimitError(e) => e.error_code(),
+        }
+    }
+}
+
+impl<KeyStoreError> StableErrorCode for CreateCommitError<KeyStoreError> {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, CREATE_COMMIT_ERROR, variant);
+        match self {
+            CreateCommitError::LibraryError(e) => e.error_code(),
+            CreateCommitError::OwnKeyNotFound => code(Storage, 2),
+            CreateCommitError::CannotRemoveSelf => code(Usage, 3),
+            CreateCommitError::MissingProposal => code(Protocol, 4),
+            CreateCommitError::WrongProposalSenderType => code(Usage, 5),
+            CreateCommitError::PskError(e) => e.error_code(),
+            CreateCommitError::ProposalValidationError(e) => e.error_code(),
+            CreateCommitError::KeyStoreError(_) => code(Storage, 8),
+            CreateCommitError::KeyPackageGenerationError(e) => e.error_code(),
+            CreateCommitError::SignatureError(e) => e.error_code(),
+            CreateCommitError::MissingCredential => code(Usage, 11),
+            CreateCommitError::PublicTreeError(e) => e.error_code(),
+            CreateCommitError::InvalidExtensionError(e) => e.error_code(),
+        }
+    }
+}
+
+impl StableErrorCode for ValidationError {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, VALIDATION_ERROR, variant);
+        match self {
+            ValidationError::LibraryError(e) => e.error_code(),
+            ValidationError::WrongGroupId => code(Protocol, 2),
+            ValidationError::WrongEpoch => code(Protocol, 3),
+            ValidationError::NotACommit => code(Validation, 4),
+            ValidationError::NotAnExternalAddProposal => code(Validation, 5),
+            ValidationError::NoPath => code(Validation, 6),
+            ValidationError::UnencryptedApplicationMessage => code(Validation, 7),
+            ValidationError::UnknownMember => code(Validation, 8),
+            ValidationError::MissingMembershipTag => code(Validation, 9),
+            ValidationError::InvalidMembershipTag => code(Validation, 10),
+            ValidationError::MissingConfirmationTag => code(Validation, 11),
+            ValidationError::WrongWireFormat => code(Validation, 12),
+            ValidationError::InvalidSignature => code(Validation, 13),
+            ValidationError::NonMemberApplicationMessage => code(Validation, 14),
+            ValidationError::UnableToDecrypt(e) => e.error_code(),
+            ValidationError::NoPastEpochData => code(Protocol, 16),
+            ValidationError::UnauthorizedExternalSender => code(Validation, 17),
+            ValidationError::NoExternalSendersExtension => code(Validation, 18),
+            ValidationError::KeyPackageVerifyError(e) => e.error_code(),
+            ValidationError::UpdatePathError(e) => e.error_code(),
+            ValidationError::InvalidLeafNodeSignature => code(Validation, 21),
+            ValidationError::InvalidLeafNodeSourceType => code(Validation, 22),
+            ValidationError::InvalidSenderType => code(Validation, 23),
+            ValidationError::CommitterIncludedOwnUpdate => code(Validation, 24),
+            ValidationError::InvalidAddProposalCiphersuite => code(Validation, 25),
+            ValidationError::AmbiguousExternalSender => code(Validation, 26),
+        }
+    }
+}
+
+impl StableErrorCode for ProposalValidationError {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, PROPOSAL_VALIDATION_ERROR, variant);
+        match self {
+            ProposalValidationError::LibraryError(e) => e.error_code(),
+            ProposalValidationError::UnknownMember => code(Validation, 2),
+            ProposalValidationError::DuplicateSignatureKey(_) => code(Validation, 3),
+            ProposalValidationError::DuplicateEncryptionKey(_) => code(Validation, 4),
+            ProposalValidationError::DuplicateInitKey => code(Validation, 5),
+            ProposalValidationError::InitEncryptionKeyCollision => code(Validation, 6),
+            ProposalValidationError::DuplicateMemberRemoval => code(Validation, 7),
+            ProposalValidationError::UnknownMemberRemoval => code(Validation, 8),
+            ProposalValidationError::UpdateFromNonMember => code(Validation, 9),
+            ProposalValidationError::CommitterIncludedOwnUpdate => code(Validation, 10),
+            ProposalValidationError::InsufficientCapabilities => code(Validation, 11),
+            ProposalValidationError::InvalidAddProposalCiphersuiteOrVersion => code(Validation, 12),
+            ProposalValidationError::Psk(e) => e.error_code(),
+            ProposalValidationError::ExternalSenderOutOfScope => code(Validation, 14),
+            ProposalValidationError::StaleExternalSender => code(Validation, 15),
+        }
+    }
+}
+
+impl StableErrorCode for ExternalCommitValidationError {
+    fn error_code(&self) -> ErrorCode {
+        let code =
+            |category, variant| ErrorCode::new(category, EXTERNAL_COMMIT_VALIDATION_ERROR, variant);
+        match self {
+            ExternalCommitValidationError::LibraryError(e) => e.error_code(),
+            ExternalCommitValidationError::NoExternalInitProposals => code(Validation, 2),
+            ExternalCommitValidationError::MultipleExternalInitProposals => code(Validation, 3),
+            ExternalCommitValidationError::InvalidInlineProposals => code(Validation, 4),
+            ExternalCommitValidationError::MultipleRemoveProposals => code(Validation, 5),
+            ExternalCommitValidationError::InvalidRemoveProposal => code(Validation, 6),
+            ExternalCommitValidationError::NoPath => code(Validation, 7),
+            ExternalCommitValidationError::UnknownMemberRemoval => code(Validation, 8),
+            ExternalCommitValidationError::ReferencedProposal => code(Validation, 9),
+        }
+    }
+}
+
+impl StableErrorCode for CreateAddProposalError {
+    fn error_code(&self) -> ErrorCode {
+        match self {
+            CreateAddProposalError::LibraryError(e) => e.error_code(),
+            CreateAddProposalError::LeafNodeValidation(e) => e.error_code(),
+        }
+    }
+}
+
+impl<KeyStoreError> StableErrorCode for MergeCommitError<KeyStoreError> {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, MERGE_COMMIT_ERROR, variant);
+        match self {
+            MergeCommitError::LibraryError(e) => e.error_code(),
+            MergeCommitError::KeyStoreError(_) => code(Storage, 2),
+            MergeCommitError::GroupStateError(e) => e.error_code(),
+        }
+    }
+}
+
+impl StableErrorCode for CreationFromExternalError {
+    fn error_code(&self) -> ErrorCode {
+        let code =
+            |category, variant| ErrorCode::new(category, CREATION_FROM_EXTERNAL_ERROR, variant);
+        match self {
+            CreationFromExternalError::LibraryError(e) => e.error_code(),
+            CreationFromExternalError::TreeSyncError(e) => e.error_code(),
+            CreationFromExternalError::UnknownSender => code(Validation, 3),
+            CreationFromExternalError::InvalidGroupInfoSignature => code(Validation, 4),
+            CreationFromExternalError::TreeHashMismatch => code(Validation, 5),
+            CreationFromExternalError::UnsupportedMlsVersion => code(Protocol, 6),
+        }
+    }
+}
+
+impl StableErrorCode for PublicGroupBuildError {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, PUBLIC_GROUP_BUILD_ERROR, variant);
+        match self {
+            PublicGroupBuildError::LibraryError(e) => e.error_code(),
+            PublicGroupBuildError::UnsupportedProposalType => code(Usage, 2),
+            PublicGroupBuildError::UnsupportedExtensionType => code(Usage, 3),
+            PublicGroupBuildError::InvalidExtensions(e) => e.error_code(),
+        }
+    }
+}
+
+impl StableErrorCode for PskError {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, PSK_ERROR, variant);
+        match self {
+            PskError::LibraryError(e) => e.error_code(),
+            PskError::TooManyKeys => code(Validation, 2),
+            PskError::KeyNotFound => code(Storage, 3),
+            PskError::KeyStore => code(Storage, 4),
+            PskError::TypeMismatch { .. } => code(Validation, 5),
+            PskError::UsageMismatch { .. } => code(Validation, 6),
+            PskError::NonceLengthMismatch { .. } => code(Validation, 7),
+            PskError::Duplicate { .. } => code(Validation, 8),
+        }
+    }
+}
+
+impl StableErrorCode for GroupInfoError {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, GROUP_INFO_ERROR, variant);
+        match self {
+            GroupInfoError::DecryptionFailed => code(Crypto, 1),
+            GroupInfoError::Malformed => code(Validation, 2),
+        }
+    }
+}
+
+impl StableErrorCode for GroupSecretsError {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, GROUP_SECRETS_ERROR, variant);
+        match self {
+            GroupSecretsError::DecryptionFailed => code(Crypto, 1),
+            GroupSecretsError::Malformed => code(Validation, 2),
+        }
+    }
+}
+
+impl StableErrorCode for InspectError {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, INSPECT_ERROR, variant);
+        match self {
+            InspectError::LibraryError(e) => e.error_code(),
+            InspectError::EncodingError(_) => code(Internal, 2),
+            InspectError::DecodingError(_) => code(Validation, 3),
+        }
+    }
+}
+
+impl StableErrorCode for SetMetricsError {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, SET_METRICS_ERROR, variant);
+        match self {
+            SetMetricsError::AlreadySet => code(Usage, 1),
+        }
+    }
+}
+
+impl StableErrorCode for VersionError {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, VERSION_ERROR, variant);
+        match self {
+            VersionError::UnsupportedMlsVersion => code(Protocol, 1),
+            VersionError::VersionMismatch => code(Protocol, 2),
+            VersionError::NoCommonVersion => code(Protocol, 3),
+        }
+    }
+}
+
+#[cfg(test)]
+impl<KeyStoreError> StableErrorCode for LeafNodeGenerationError<KeyStoreError> {
+    fn error_code(&self) -> ErrorCode {
+        let code =
+            |category, variant| ErrorCode::new(category, LEAF_NODE_GENERATION_ERROR, variant);
+        match self {
+            LeafNodeGenerationError::LibraryError(e) => e.error_code(),
+            LeafNodeGenerationError::KeyStoreError(_) => code(Storage, 2),
+        }
+    }
+}
+
+impl StableErrorCode for PublicTreeError {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, PUBLIC_TREE_ERROR, variant);
+        match self {
+            PublicTreeError::LibraryError(e) => e.error_code(),
+            PublicTreeError::PublicKeyMismatch => code(Validation, 2),
+            PublicTreeError::DuplicateKeyPackage => code(Validation, 3),
+            PublicTreeError::MissingKeyPackage => code(Validation, 4),
+            PublicTreeError::MalformedTree => code(Validation, 5),
+            PublicTreeError::InvalidParentHash => code(Validation, 6),
+            PublicTreeError::IdentityMismatch => code(Validation, 7),
+            PublicTreeError::SignatureError(e) => e.error_code(),
+        }
+    }
+}
+
+impl StableErrorCode for ApplyUpdatePathError {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, APPLY_UPDATE_PATH_ERROR, variant);
+        match self {
+            ApplyUpdatePathError::LibraryError(e) => e.error_code(),
+            ApplyUpdatePathError::PathLengthMismatch => code(Validation, 2),
+            ApplyUpdatePathError::PathMismatch => code(Validation, 3),
+            ApplyUpdatePathError::ParentHashMismatch => code(Validation, 4),
+            ApplyUpdatePathError::MissingParentHash => code(Validation, 5),
+            ApplyUpdatePathError::UnableToDecrypt => code(Crypto, 6),
+            ApplyUpdatePathError::MissingSender => code(Validation, 7),
+            ApplyUpdatePathError::TreeFull => code(Validation, 8),
+            ApplyUpdatePathError::InconsistentSenderIndex => code(Validation, 9),
+        }
+    }
+}
+
+impl StableErrorCode for TreeSyncFromNodesError {
+    fn error_code(&self) -> ErrorCode {
+        match self {
+            TreeSyncFromNodesError::LibraryError(e) => e.error_code(),
+            TreeSyncFromNodesError::PublicTreeError(e) => e.error_code(),
+            TreeSyncFromNodesError::RatchetTreeError(e) => e.error_code(),
+        }
+    }
+}
+
+impl StableErrorCode for LeafNodeValidationError {
+    fn error_code(&self) -> ErrorCode {
+        let code =
+            |category, variant| ErrorCode::new(category, LEAF_NODE_VALIDATION_ERROR, variant);
+        match self {
+            LeafNodeValidationError::Lifetime(e) => e.error_code(),
+            LeafNodeValidationError::UnsupportedExtensions => code(Validation, 2),
+            LeafNodeValidationError::UnsupportedProposals => code(Validation, 3),
+            LeafNodeValidationError::UnsupportedCredentials => code(Validation, 4),
+            LeafNodeValidationError::CredentialNotInCapabilities => code(Validation, 5),
+            LeafNodeValidationError::ExtensionsNotInCapabilities => code(Validation, 6),
+            LeafNodeValidationError::SignatureKeyAlreadyInUse => code(Validation, 7),
+            LeafNodeValidationError::EncryptionKeyAlreadyInUse => code(Validation, 8),
+            LeafNodeValidationError::InvalidLeafNodeSource => code(Validation, 9),
+            LeafNodeValidationError::LeafNodeCredentialNotSupportedByMember => code(Validation, 10),
+            LeafNodeValidationError::MemberCredentialNotSupportedByLeafNode => code(Validation, 11),
+        }
+    }
+}
+
+impl StableErrorCode for LifetimeError {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, LIFETIME_ERROR, variant);
+        match self {
+            LifetimeError::RangeTooBig => code(Validation, 1),
+            LifetimeError::NotCurrent => code(Validation, 2),
+        }
+    }
+}
+
+impl StableErrorCode for UpdatePathError {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, UPDATE_PATH_ERROR, variant);
+        match self {
+            UpdatePathError::InvalidType => code(Validation, 1),
+            UpdatePathError::SignatureError(e) => e.error_code(),
+        }
+    }
+}
+
+impl StableErrorCode for RatchetTreeError {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, RATCHET_TREE_ERROR, variant);
+        match self {
+            RatchetTreeError::MissingNodes => code(Validation, 1),
+            RatchetTreeError::TrailingBlankNodes => code(Validation, 2),
+            RatchetTreeError::InvalidNodeSignature => code(Validation, 3),
+            RatchetTreeError::WrongNodeType => code(Validation, 4),
+        }
+    }
+}
+
+impl StableErrorCode for MemoryLimitError {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, MEMORY_LIMIT_ERROR, variant);
+        match self {
+            MemoryLimitError::TreeLimitExceeded => code(Protocol, 1),
+            MemoryLimitError::PendingProposalsLimitExceeded => code(Protocol, 2),
+        }
+    }
+}
+
+impl StableErrorCode for CommitLimitError {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, COMMIT_LIMIT_ERROR, variant);
+        match self {
+            CommitLimitError::TooManyProposals => code(Protocol, 1),
+            CommitLimitError::TooManyAdds => code(Protocol, 2),
+            CommitLimitError::TooLarge => code(Protocol, 3),
+            CommitLimitError::TooManyPathNodes => code(Protocol, 4),
+        }
+    }
+}
+
+#[cfg(feature = "speculative")]
+impl StableErrorCode for SpeculativeKeyStoreError {
+    fn error_code(&self) -> ErrorCode {
+        let code =
+            |category, variant| ErrorCode::new(category, SPECULATIVE_KEY_STORE_ERROR, variant);
+        match self {
+            SpeculativeKeyStoreError::SerializationError(_) => code(Storage, 1),
+        }
+    }
+}
+
+impl StableErrorCode for SetSecurityEventHandlerError {
+    fn error_code(&self) -> ErrorCode {
+        let code =
+            |category, variant| ErrorCode::new(category, SET_SECURITY_EVENT_HANDLER_ERROR, variant);
+        match self {
+            SetSecurityEventHandlerError::AlreadySet => code(Usage, 1),
+        }
+    }
+}
+
+impl StableErrorCode for MembershipPolicyError {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, MEMBERSHIP_POLICY_ERROR, variant);
+        match self {
+            MembershipPolicyError::UnauthorizedAdd => code(Protocol, 1),
+            MembershipPolicyError::UnauthorizedRemove => code(Protocol, 2),
+        }
+    }
+}
+
+impl StableErrorCode for GroupInfoValidationError {
+    fn error_code(&self) -> ErrorCode {
+        let code =
+            |category, variant| ErrorCode::new(category, GROUP_INFO_VALIDATION_ERROR, variant);
+        match self {
+            GroupInfoValidationError::UnknownSigner => code(Validation, 1),
+            GroupInfoValidationError::InvalidSignature => code(Validation, 2),
+            GroupInfoValidationError::GroupContextMismatch => code(Protocol, 3),
+            GroupInfoValidationError::ConfirmationTagMismatch => code(Protocol, 4),
+        }
+    }
+}
+
+impl StableErrorCode for KeyPackagePoolError {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, KEY_PACKAGE_POOL_ERROR, variant);
+        match self {
+            KeyPackagePoolError::LibraryError(e) => e.error_code(),
+            KeyPackagePoolError::KeyPackageVerifyError(e) => e.error_code(),
+            KeyPackagePoolError::LifetimeTooLong => code(Validation, 3),
+            KeyPackagePoolError::LifetimeTooShort => code(Validation, 4),
+            KeyPackagePoolError::DuplicateKeyPackage => code(Protocol, 5),
+            KeyPackagePoolError::InitKeyReuse => code(Validation, 6),
+        }
+    }
+}
+
+impl StableErrorCode for SequencingError {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, SEQUENCING_ERROR, variant);
+        match self {
+            SequencingError::MissingSequenceNumber => code(Validation, 1),
+            SequencingError::NonMonotonicSequenceNumber => code(Protocol, 2),
+        }
+    }
+}
+
+impl StableErrorCode for AbuseReportError {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, ABUSE_REPORT_ERROR, variant);
+        match self {
+            AbuseReportError::LibraryError(e) => e.error_code(),
+            AbuseReportError::NotAnApplicationMessage => code(Usage, 2),
+            AbuseReportError::UnavailableEpoch => code(Protocol, 3),
+            AbuseReportError::UnknownSender => code(Protocol, 4),
+            AbuseReportError::InvalidLeafNodeSignature => code(Validation, 5),
+            AbuseReportError::InvalidMembershipProof => code(Validation, 6),
+            AbuseReportError::InvalidMessageSignature => code(Validation, 7),
+        }
+    }
+}
+
+impl<KeyStoreError> StableErrorCode for DeviceError<KeyStoreError> {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, DEVICE_ERROR, variant);
+        match self {
+            DeviceError::LibraryError(e) => e.error_code(),
+            DeviceError::GroupStateError(e) => e.error_code(),
+            DeviceError::ForeignDevice => code(Usage, 3),
+            DeviceError::MissingKeyPackage => code(Usage, 4),
+            DeviceError::AddMembersError(e) => e.error_code(),
+            DeviceError::RemoveMembersError(e) => e.error_code(),
+        }
+    }
+}
+
+impl StableErrorCode for HistoryError {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, HISTORY_ERROR, variant);
+        match self {
+            HistoryError::LibraryError(e) => e.error_code(),
+            HistoryError::GroupStateError(e) => e.error_code(),
+            HistoryError::HistorySharingDisabled => code(Usage, 3),
+            HistoryError::NoHistoryKeys => code(Usage, 4),
+            HistoryError::WrongGroup => code(Validation, 5),
+            HistoryError::WrongEpoch => code(Validation, 6),
+            HistoryError::DecryptionFailed => code(Crypto, 7),
+            HistoryError::MalformedBundle => code(Validation, 8),
+        }
+    }
+}
+
+impl<KeyStoreError> StableErrorCode for SubgroupError<KeyStoreError> {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, SUBGROUP_ERROR, variant);
+        match self {
+            SubgroupError::LibraryError(e) => e.error_code(),
+            SubgroupError::GroupStateError(e) => e.error_code(),
+            SubgroupError::CiphersuiteMismatch => code(Usage, 3),
+            SubgroupError::NotAParentMember => code(Usage, 4),
+            SubgroupError::NotASubgroup => code(Validation, 5),
+            SubgroupError::KeyStoreError(_) => code(Storage, 6),
+            SubgroupError::Psk(e) => e.error_code(),
+            SubgroupError::NewGroupError(e) => e.error_code(),
+            SubgroupError::AddMembersError(e) => e.error_code(),
+            SubgroupError::MergePendingCommitError(e) => e.error_code(),
+            SubgroupError::WelcomeError(e) => e.error_code(),
+        }
+    }
+}
+
+impl<KeyStoreError> StableErrorCode for GroupMergeError<KeyStoreError> {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, GROUP_MERGE_ERROR, variant);
+        match self {
+            GroupMergeError::LibraryError(e) => e.error_code(),
+            GroupMergeError::GroupStateError(e) => e.error_code(),
+            GroupMergeError::KeyPackageMismatch => code(Usage, 3),
+            GroupMergeError::AddMembersError(e) => e.error_code(),
+            GroupMergeError::SelfUpdateError(e) => e.error_code(),
+        }
+    }
+}
+
+impl StableErrorCode for ExternalRemoveProposalError {
+    fn error_code(&self) -> ErrorCode {
+        let code =
+            |category, variant| ErrorCode::new(category, EXTERNAL_REMOVE_PROPOSAL_ERROR, variant);
+        match self {
+            ExternalRemoveProposalError::LibraryError(e) => e.error_code(),
+            ExternalRemoveProposalError::UnknownExternalSender => code(Usage, 2),
+            ExternalRemoveProposalError::UnknownMember => code(Usage, 3),
+            ExternalRemoveProposalError::ExternalSenderOutOfScope => code(Usage, 4),
+        }
+    }
+}
+
+impl<KeyStoreError> StableErrorCode for ContactPairError<KeyStoreError> {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, CONTACT_PAIR_ERROR, variant);
+        match self {
+            ContactPairError::LibraryError(e) => e.error_code(),
+            ContactPairError::NotAContactPair => code(Validation, 2),
+            ContactPairError::UnexpectedMessage => code(Validation, 3),
+            ContactPairError::PeerMismatch => code(Validation, 4),
+            ContactPairError::NewGroupError(e) => e.error_code(),
+            ContactPairError::WelcomeError(e) => e.error_code(),
+            ContactPairError::AddMembersError(e) => e.error_code(),
+            ContactPairError::SelfUpdateError(e) => e.error_code(),
+            ContactPairError::MergePendingCommitError(e) => e.error_code(),
+            ContactPairError::MergeCommitError(e) => e.error_code(),
+            ContactPairError::CreateMessageError(e) => e.error_code(),
+            ContactPairError::ProcessMessageError(e) => e.error_code(),
+        }
+    }
+}
+
+impl<KeyStoreError> StableErrorCode for MaintenanceError<KeyStoreError> {
+    fn error_code(&self) -> ErrorCode {
+        match self {
+            MaintenanceError::LibraryError(e) => e.error_code(),
+            MaintenanceError::GroupStateError(e) => e.error_code(),
+            MaintenanceError::SelfUpdateError(e) => e.error_code(),
+            MaintenanceError::ProposeSelfUpdateError(e) => e.error_code(),
+        }
+    }
+}
+
+impl StableErrorCode for WelcomeDeclineError {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, WELCOME_DECLINE_ERROR, variant);
+        match self {
+            WelcomeDeclineError::LibraryError(e) => e.error_code(),
+            WelcomeDeclineError::SignatureError(e) => e.error_code(),
+            WelcomeDeclineError::WrongGroup => code(Validation, 3),
+            WelcomeDeclineError::UnknownMember => code(Validation, 4),
+        }
+    }
+}
+
+impl StableErrorCode for ReissueWelcomeError {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, REISSUE_WELCOME_ERROR, variant);
+        match self {
+            ReissueWelcomeError::NoWelcome => code(Usage, 1),
+            ReissueWelcomeError::UnknownNewMember => code(Usage, 2),
+        }
+    }
+}
+
+impl StableErrorCode for DiscardEpochError {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, DISCARD_EPOCH_ERROR, variant);
+        match self {
+            DiscardEpochError::NotAPastEpoch => code(Usage, 1),
+            DiscardEpochError::UnknownEpoch => code(Usage, 2),
+        }
+    }
+}
+
+impl<KeyStoreError> StableErrorCode for SnapshotError<KeyStoreError> {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, SNAPSHOT_ERROR, variant);
+        match self {
+            SnapshotError::LibraryError(e) => e.error_code(),
+            SnapshotError::SnapshotActive => code(Usage, 2),
+            SnapshotError::NoSnapshot => code(Usage, 3),
+            SnapshotError::MissingSnapshot => code(Storage, 4),
+            SnapshotError::KeyStoreError(_) => code(Storage, 5),
+        }
+    }
+}
+
+impl StableErrorCode for RatchetExportError {
+    fn error_code(&self) -> ErrorCode {
+        let code = |category, variant| ErrorCode::new(category, RATCHET_EXPORT_ERROR, variant);
+        match self {
+            RatchetExportError::LibraryError(e) => e.error_code(),
+            RatchetExportError::GroupStateError(e) => e.error_code(),
+            RatchetExportError::InvalidSealingKey => code(Usage, 3),
+            RatchetExportError::UnknownSender => code(Usage, 4),
+            RatchetExportError::SecretTreeError(e) => e.error_code(),
+            RatchetExportError::DecryptionFailed => code(Crypto, 6),
+            RatchetExportError::MalformedState => code(Validation, 7),
+            RatchetExportError::WrongGroup => code(Validation, 8),
+            RatchetExportError::WrongEpoch => code(Validation, 9),
+            RatchetExportError::NotApplicationMessage => code(Validation, 10),
+            RatchetExportError::UnavailableGeneration => code(Protocol, 11),
+            RatchetExportError::MessageDecryptionError(e) => e.error_code(),
+            RatchetExportError::InvalidSignature => code(Crypto, 13),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::group::{MlsGroupStateError, ProcessMessageError, ValidationError};
+
+    #[test]
+    fn error_codes_are_stable() {
+        assert_eq!(ValidationError::WrongEpoch.error_code().code(), 403803);
+        assert_eq!(
+            MlsGroupStateError::PendingCommit.error_code().code(),
+            502004
+        );
+        assert_eq!(
+            SignatureError::VerificationError.error_code().code(),
+            100201
+        );
+        assert_eq!(LibraryError::custom("test").error_code().code(), 900103);
+        assert_eq!(
+            MessageDecryptionError::AeadError.error_code().to_string(),
+            "201103"
+        );
+    }
+
+    #[test]
+    fn wrapped_errors() {
+        // Wrapping errors return the code of the root cause.
+        assert_eq!(
+            ProcessMessageError::ValidationError(ValidationError::WrongEpoch).error_code(),
+            ValidationError::WrongEpoch.error_code()
+        );
+        let error = ProcessMessageError::ValidationError(ValidationError::UnableToDecrypt(
+            MessageDecryptionError::AeadError,
+        ));
+        assert_eq!(error.error_category(), ErrorCategory::Crypto);
+        let error: AddMembersError<()> = CreateCommitError::KeyStoreError(()).into();
+        assert_eq!(error.error_category(), ErrorCategory::Storage);
+        let error: ExportSecretError = LibraryError::custom("test").into();
+        assert_eq!(error.error_category(), ErrorCategory::Internal);
+    }
+
+    #[test]
+    fn error_severity() {
+        assert_eq!(
+            ValidationError::WrongEpoch.error_severity(),
+            ErrorSeverity::Recoverable
+        );
+        assert_eq!(
+            MessageDecryptionError::AeadError.error_severity(),
+            ErrorSeverity::Recoverable
+        );
+        let error: AddMembersError<()> = CreateCommitError::KeyStoreError(()).into();
+        assert_eq!(error.error_severity(), ErrorSeverity::RetrySafe);
+        assert_eq!(
+            MergeCommitError::KeyStoreError(()).error_severity(),
+            ErrorSeverity::FatalForGroup
+        );
+        assert_eq!(
+            MlsGroupStateError::UseAfterEviction.error_severity(),
+            ErrorSeverity::FatalForGroup
+        );
+        assert_eq!(
+            LibraryError::custom("test").error_severity(),
+            ErrorSeverity::FatalForGroup
+        );
+        assert_eq!(
+            SharedMlsGroupError::Poisoned.error_severity(),
+            ErrorSeverity::FatalForGroup
+        );
+    }
+
+    #[test]
+    fn parse_error_codes() {
+        let code = ValidationError::WrongEpoch.error_code();
+        assert_eq!(ErrorCode::from_u32(code.into()), Some(code));
+        assert_eq!(code.category(), ErrorCategory::Protocol);
+        assert_eq!(code.category().as_str(), "protocol");
+
+        for invalid in [0, 100200, 100001, 600101, 1_100_101] {
+            assert_eq!(ErrorCode::from_u32(invalid), None);
+        }
+    }
+}
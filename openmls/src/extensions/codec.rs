@@ -11,17 +11,39 @@ use crate::extensions::{
 use super::last_resort::LastResortExtension;
 
 fn vlbytes_len_len(length: usize) -> usize {
-    if length < 0x40 {
+    if length <= 0x3f {
         1
-    } else if length < 0x3fff {
+    } else if length <= 0x3fff {
         2
-    } else if length < 0x3fff_ffff {
+    } else if length <= 0x3fff_ffff {
         4
     } else {
         8
     }
 }
 
+/// Write the variable-length header of a `VLBytes` with `length` bytes of
+/// content, so that the content can be serialized straight into `writer`.
+fn write_vlbytes_len<W: Write>(length: usize, writer: &mut W) -> Result<usize, tls_codec::Error> {
+    if length > 0x3fff_ffff {
+        return Err(tls_codec::Error::InvalidVectorLength);
+    }
+    let length_len = vlbytes_len_len(length);
+    let prefix: u32 = match length_len {
+        1 => 0x00,
+        2 => 0x40,
+        _ => 0x80,
+    };
+    let header = ((prefix << 24) | length as u32).to_be_bytes();
+    let header = match length_len {
+        1 => &header[3..],
+        2 => &header[2..],
+        _ => &header[..],
+    };
+    writer.write_all(header)?;
+    Ok(length_len)
+}
+
 impl Size for Extension {
     #[inline]
     fn tls_serialized_len(&self) -> usize {
@@ -58,30 +80,40 @@ impl Serialize for Extension {
         // First write the extension type.
         let written = self.extension_type().tls_serialize(writer)?;
 
-        // Now serialize the extension into a separate byte vector.
-        let extension_data_len = self.tls_serialized_len();
-        let mut extension_data = Vec::with_capacity(extension_data_len);
+        // The extension data is a `VLBytes`. Its length is known upfront, so
+        // we write the length and then serialize the extension directly into
+        // the writer instead of into a separate byte vector first.
+        let extension_data_len = match self {
+            Extension::ApplicationId(e) => e.tls_serialized_len(),
+            Extension::RatchetTree(e) => e.tls_serialized_len(),
+            Extension::RequiredCapabilities(e) => e.tls_serialized_len(),
+            Extension::ExternalPub(e) => e.tls_serialized_len(),
+            Extension::ExternalSenders(e) => e.tls_serialized_len(),
+            Extension::LastResort(e) => e.tls_serialized_len(),
+            Extension::Unknown(_, e) => e.0.len(),
+        };
+        let length_written = write_vlbytes_len(extension_data_len, writer)?;
 
         let extension_data_written = match self {
-            Extension::ApplicationId(e) => e.tls_serialize(&mut extension_data),
-            Extension::RatchetTree(e) => e.tls_serialize(&mut extension_data),
-            Extension::RequiredCapabilities(e) => e.tls_serialize(&mut extension_data),
-            Extension::ExternalPub(e) => e.tls_serialize(&mut extension_data),
-            Extension::ExternalSenders(e) => e.tls_serialize(&mut extension_data),
-            Extension::LastResort(e) => e.tls_serialize(&mut extension_data),
-            Extension::Unknown(_, e) => extension_data
+            Extension::ApplicationId(e) => e.tls_serialize(writer),
+            Extension::RatchetTree(e) => e.tls_serialize(writer),
+            Extension::RequiredCapabilities(e) => e.tls_serialize(writer),
+            Extension::ExternalPub(e) => e.tls_serialize(writer),
+            Extension::ExternalSenders(e) => e.tls_serialize(writer),
+            Extension::LastResort(e) => e.tls_serialize(writer),
+            Extension::Unknown(_, e) => writer
                 .write_all(e.0.as_slice())
                 .map(|_| e.0.len())
                 .map_err(|_| tls_codec::Error::EndOfStream),
         }?;
-        debug_assert_eq!(
-            extension_data_written,
-            extension_data_len - 2 - vlbytes_len_len(extension_data_written)
-        );
-        debug_assert_eq!(extension_data_written, extension_data.len());
-
-        // Write the serialized extension out.
-        extension_data.tls_serialize(writer).map(|l| l + written)
+        // A mismatch would produce an undecodable extension.
+        if extension_data_written != extension_data_len {
+            return Err(tls_codec::Error::EncodingError(format!(
+                "Extension data has {extension_data_written} bytes instead of {extension_data_len}."
+            )));
+        }
+
+        Ok(written + length_written + extension_data_written)
     }
 }
 
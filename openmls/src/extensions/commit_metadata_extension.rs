@@ -0,0 +1,42 @@
+use tls_codec::{TlsDeserialize, TlsSerialize, TlsSize, VLBytes};
+
+use super::{CustomExtension, Deserialize, Serialize};
+
+/// # Commit metadata
+///
+/// An application-defined blob that the committer attaches to a commit, e.g.,
+/// the reason for removing a member. The extension is carried in the
+/// authenticated data of the commit, so the metadata is authenticated by the
+/// committer together with the changes of the commit. See
+/// [`MlsGroup::set_commit_metadata()`](crate::group::MlsGroup::set_commit_metadata())
+/// for details.
+///
+/// ```c
+/// struct {
+///     opaque metadata<V>;
+/// } CommitMetadata;
+/// ```
+#[derive(
+    PartialEq, Eq, Clone, Debug, Serialize, Deserialize, TlsSerialize, TlsDeserialize, TlsSize,
+)]
+pub struct CommitMetadataExtension {
+    metadata: VLBytes,
+}
+
+impl CommitMetadataExtension {
+    /// Creates a new `CommitMetadataExtension`.
+    pub fn new(metadata: &[u8]) -> Self {
+        Self {
+            metadata: metadata.into(),
+        }
+    }
+
+    /// Returns the metadata.
+    pub fn metadata(&self) -> &[u8] {
+        self.metadata.as_slice()
+    }
+}
+
+impl CustomExtension for CommitMetadataExtension {
+    const EXTENSION_TYPE: u16 = 0xff11;
+}
@@ -0,0 +1,95 @@
+use tls_codec::{Deserialize, Serialize, Size};
+
+use super::{errors::CustomExtensionError, Extension, ExtensionType, Extensions, UnknownExtension};
+
+/// # Custom Extensions
+///
+/// Applications can define their own extensions by implementing this trait
+/// for a type that can be encoded with the TLS presentation language. The
+/// easiest way to do this is to derive the encoding with the derive macros of
+/// the [`tls_codec`] crate, which is re-exported by OpenMLS. Length-prefixed
+/// vectors are encoded via `Vec<T>` or [`VLBytes`](tls_codec::VLBytes) and
+/// optional values via `Option<T>`, as defined in RFC 9420. The encoding
+/// therefore interoperates with other MLS implementations.
+///
+/// ```
+/// use openmls::{
+///     extensions::{CustomExtension, Extensions},
+///     tls_codec::{self, TlsDeserialize, TlsSerialize, TlsSize, VLBytes},
+/// };
+///
+/// #[derive(Debug, PartialEq, TlsSerialize, TlsDeserialize, TlsSize)]
+/// struct RoomExtension {
+///     name: VLBytes,
+///     moderators: Vec<VLBytes>,
+///     topic: Option<VLBytes>,
+/// }
+///
+/// impl CustomExtension for RoomExtension {
+///     const EXTENSION_TYPE: u16 = 0xff00;
+/// }
+///
+/// let room = RoomExtension {
+///     name: b"Lobby".to_vec().into(),
+///     moderators: vec![b"Alice".to_vec().into()],
+///     topic: None,
+/// };
+/// let extensions = Extensions::single(room.to_extension().unwrap());
+///
+/// assert_eq!(extensions.custom::<RoomExtension>().unwrap(), Some(room));
+/// ```
+pub trait CustomExtension: Serialize + Deserialize + Size + Sized {
+    /// The extension type of this extension.
+    ///
+    /// This must not be one of the extension types supported by OpenMLS (see
+    /// [`ExtensionType::is_supported()`]). Use a type from the private use
+    /// range (`0xff00` - `0xffff`) or one registered with IANA.
+    const EXTENSION_TYPE: u16;
+
+    /// Encode this custom extension as [`Extension`].
+    ///
+    /// Returns an error if [`Self::EXTENSION_TYPE`] is an extension type
+    /// supported by OpenMLS or if the extension can't be encoded.
+    fn to_extension(&self) -> Result<Extension, CustomExtensionError> {
+        if ExtensionType::from(Self::EXTENSION_TYPE).is_supported() {
+            return Err(CustomExtensionError::ReservedExtensionType);
+        }
+        let extension_data = self.tls_serialize_detached()?;
+        Ok(Extension::Unknown(
+            Self::EXTENSION_TYPE,
+            UnknownExtension(extension_data),
+        ))
+    }
+
+    /// Decode this custom extension from an [`Extension`].
+    ///
+    /// Returns an error if the extension has a different extension type or
+    /// if the extension data is malformed.
+    fn from_extension(extension: &Extension) -> Result<Self, CustomExtensionError> {
+        match extension {
+            Extension::Unknown(extension_type, UnknownExtension(extension_data))
+                if *extension_type == Self::EXTENSION_TYPE =>
+            {
+                let mut bytes = extension_data.as_slice();
+                let custom_extension = Self::tls_deserialize(&mut bytes)?;
+                // The extension data must be consumed entirely.
+                if !bytes.is_empty() {
+                    return Err(CustomExtensionError::TrailingData);
+                }
+                Ok(custom_extension)
+            }
+            _ => Err(CustomExtensionError::WrongExtensionType),
+        }
+    }
+}
+
+impl Extensions {
+    /// Get the custom extension of type `T` if there is any.
+    ///
+    /// Returns an error if the extension data is malformed.
+    pub fn custom<T: CustomExtension>(&self) -> Result<Option<T>, CustomExtensionError> {
+        self.find_by_type(ExtensionType::from(T::EXTENSION_TYPE))
+            .map(T::from_extension)
+            .transpose()
+    }
+}
@@ -83,6 +83,23 @@ pub enum RatchetTreeError {
     Invalid,
 }
 
+/// Custom extension error
+#[derive(Error, Debug, PartialEq, Clone)]
+pub enum CustomExtensionError {
+    /// The extension type of the custom extension is reserved for an extension supported by OpenMLS.
+    #[error("The extension type of the custom extension is reserved for an extension supported by OpenMLS.")]
+    ReservedExtensionType,
+    /// The extension is not of the custom extension's type.
+    #[error("The extension is not of the custom extension's type.")]
+    WrongExtensionType,
+    /// The extension data contains trailing bytes.
+    #[error("The extension data contains trailing bytes.")]
+    TrailingData,
+    /// See [`tls_codec::Error`] for more details.
+    #[error(transparent)]
+    CodecError(#[from] tls_codec::Error),
+}
+
 /// Invalid extension error
 #[derive(Error, Debug, PartialEq, Eq, Clone)]
 pub enum InvalidExtensionError {
@@ -0,0 +1,44 @@
+use tls_codec::{TlsDeserialize, TlsSerialize, TlsSize};
+
+use super::{CustomExtension, Deserialize, Serialize};
+
+/// # External key ID
+///
+/// Identifies the rotated external keypair that a GroupInfo advertises in its
+/// [`ExternalPubExtension`](super::ExternalPubExtension). The ID is the
+/// rotation period of the keypair.
+///
+/// The extension is carried in the GroupInfo and copied into the
+/// authenticated data of external commits to that GroupInfo, so that all
+/// members decrypt an external commit with the same external keypair. See
+/// [`ExternalKeyRotationPolicy`](crate::group::ExternalKeyRotationPolicy) for
+/// details.
+///
+/// ```c
+/// struct {
+///     uint64 period;
+/// } ExternalKeyId;
+/// ```
+#[derive(
+    PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize, TlsSerialize, TlsDeserialize, TlsSize,
+)]
+pub struct ExternalKeyIdExtension {
+    period: u64,
+}
+
+impl ExternalKeyIdExtension {
+    /// Creates a new `ExternalKeyIdExtension` for the keypair of the rotation
+    /// `period`.
+    pub fn new(period: u64) -> Self {
+        Self { period }
+    }
+
+    /// Returns the rotation period of the external keypair.
+    pub fn period(&self) -> u64 {
+        self.period
+    }
+}
+
+impl CustomExtension for ExternalKeyIdExtension {
+    const EXTENSION_TYPE: u16 = 0xff12;
+}
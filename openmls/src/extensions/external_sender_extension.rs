@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
-use tls_codec::{TlsDeserialize, TlsSerialize, TlsSize};
+use tls_codec::{TlsDeserialize, TlsSerialize, TlsSize, VLBytes};
 
+use super::CustomExtension;
 use crate::{ciphersuite::SignaturePublicKey, credentials::Credential};
 
 /// ExternalSender
@@ -29,11 +30,13 @@ impl ExternalSender {
         }
     }
 
-    pub(crate) fn credential(&self) -> &Credential {
+    /// Returns the credential of the external sender.
+    pub fn credential(&self) -> &Credential {
         &self.credential
     }
 
-    pub(crate) fn signature_key(&self) -> &SignaturePublicKey {
+    /// Returns the signature key of the external sender.
+    pub fn signature_key(&self) -> &SignaturePublicKey {
         &self.signature_key
     }
 }
@@ -63,6 +66,122 @@ impl SenderExtensionIndex {
     }
 }
 
+/// ExternalSenderScope
+///
+/// Restricts the external sender at `sender_index` in the
+/// [`ExternalSendersExtension`] to the members of a `domain`.
+///
+/// ```c
+/// struct {
+///   uint32 sender_index;
+///   opaque domain<V>;
+/// } ExternalSenderScope;
+/// ```
+#[derive(
+    Clone, PartialEq, Eq, Debug, Serialize, Deserialize, TlsSerialize, TlsDeserialize, TlsSize,
+)]
+pub struct ExternalSenderScope {
+    sender_index: SenderExtensionIndex,
+    domain: VLBytes,
+}
+
+impl ExternalSenderScope {
+    /// Creates a new `ExternalSenderScope` that restricts the external sender
+    /// at `sender_index` to `domain`.
+    pub fn new(sender_index: SenderExtensionIndex, domain: &[u8]) -> Self {
+        Self {
+            sender_index,
+            domain: domain.into(),
+        }
+    }
+
+    /// Returns the index of the scoped external sender.
+    pub fn sender_index(&self) -> SenderExtensionIndex {
+        self.sender_index
+    }
+
+    /// Returns the domain of the scoped external sender.
+    pub fn domain(&self) -> &[u8] {
+        self.domain.as_slice()
+    }
+}
+
+/// # External sender scopes
+///
+/// In federated deployments, the external senders of a group are usually the
+/// servers of the participating domains. This group context extension scopes
+/// external senders to domains: an external sender with one or more scopes
+/// may only propose to remove members of these domains. External senders
+/// without a scope are not restricted.
+///
+/// The domain of a member is the part of its credential identity after the
+/// last `@`, e.g., `example.com` for `alice@example.com`. Members whose
+/// identity doesn't contain an `@` are not part of any domain and can only be
+/// removed by external senders without a scope.
+///
+/// Since all members have to agree on which proposals are valid, the scopes
+/// are part of the group context. They are set with
+/// [`MlsGroupConfigBuilder::external_sender_scopes()`](crate::group::MlsGroupConfigBuilder::external_sender_scopes())
+/// when the group is created.
+///
+/// ```c
+/// ExternalSenderScope external_sender_scopes<V>;
+/// ```
+#[derive(
+    Clone,
+    PartialEq,
+    Eq,
+    Debug,
+    Default,
+    Serialize,
+    Deserialize,
+    TlsSerialize,
+    TlsDeserialize,
+    TlsSize,
+)]
+pub struct ExternalSenderScopesExtension {
+    scopes: Vec<ExternalSenderScope>,
+}
+
+impl ExternalSenderScopesExtension {
+    /// Creates a new `ExternalSenderScopesExtension`.
+    pub fn new(scopes: Vec<ExternalSenderScope>) -> Self {
+        Self { scopes }
+    }
+
+    /// Returns the scopes.
+    pub fn scopes(&self) -> &[ExternalSenderScope] {
+        &self.scopes
+    }
+
+    /// Returns `true` if there are no scopes.
+    pub fn is_empty(&self) -> bool {
+        self.scopes.is_empty()
+    }
+
+    /// Returns `true` if the external sender at `sender_index` may remove the
+    /// member with the given credential `identity`.
+    pub fn may_remove(&self, sender_index: SenderExtensionIndex, identity: &[u8]) -> bool {
+        let domain = identity
+            .iter()
+            .rposition(|byte| *byte == b'@')
+            .map(|position| &identity[position + 1..]);
+        let mut scopes = self
+            .scopes
+            .iter()
+            .filter(|scope| scope.sender_index == sender_index)
+            .peekable();
+        if scopes.peek().is_none() {
+            return true;
+        }
+        scopes.any(|scope| domain == Some(scope.domain()))
+    }
+}
+
+impl CustomExtension for ExternalSenderScopesExtension {
+    const EXTENSION_TYPE: u16 = 0xff0e;
+}
+
 #[cfg(test)]
 mod test {
     use openmls_basic_credential::SignatureKeyPair;
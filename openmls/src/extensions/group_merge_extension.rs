@@ -0,0 +1,63 @@
+use tls_codec::{TlsDeserialize, TlsSerialize, TlsSize, VLBytes};
+
+use super::{CustomExtension, Deserialize, Serialize};
+use crate::group::{GroupEpoch, GroupId};
+
+/// # Group merge
+///
+/// After a network partition, the members of a group may end up in two
+/// divergent groups. When the groups are merged, the members that are missing
+/// in the group that is kept are added to it and the other group is
+/// abandoned. This extension identifies the state of the abandoned group that
+/// was merged, so that the merge can be audited later.
+///
+/// The extension is carried in the authenticated data of the commit that
+/// performs the merge. See
+/// [`MlsGroup::merge_group()`](crate::group::MlsGroup::merge_group()) for
+/// details.
+///
+/// ```c
+/// struct {
+///     opaque group_id<V>;
+///     uint64 epoch;
+///     opaque tree_hash<V>;
+/// } GroupMerge;
+/// ```
+#[derive(
+    PartialEq, Eq, Clone, Debug, Serialize, Deserialize, TlsSerialize, TlsDeserialize, TlsSize,
+)]
+pub struct GroupMergeExtension {
+    group_id: GroupId,
+    epoch: GroupEpoch,
+    tree_hash: VLBytes,
+}
+
+impl GroupMergeExtension {
+    /// Creates a new `GroupMergeExtension`.
+    pub fn new(group_id: GroupId, epoch: GroupEpoch, tree_hash: &[u8]) -> Self {
+        Self {
+            group_id,
+            epoch,
+            tree_hash: tree_hash.into(),
+        }
+    }
+
+    /// Returns the ID of the merged group.
+    pub fn group_id(&self) -> &GroupId {
+        &self.group_id
+    }
+
+    /// Returns the epoch of the merged group.
+    pub fn epoch(&self) -> GroupEpoch {
+        self.epoch
+    }
+
+    /// Returns the tree hash of the merged group in its epoch.
+    pub fn tree_hash(&self) -> &[u8] {
+        self.tree_hash.as_slice()
+    }
+}
+
+impl CustomExtension for GroupMergeExtension {
+    const EXTENSION_TYPE: u16 = 0xff10;
+}
@@ -18,6 +18,15 @@
 //! - [`RatchetTreeExtension`] (GroupInfo extension)
 //! - [`RequiredCapabilitiesExtension`] (GroupContext extension)
 //! - [`ExternalPubExtension`] (GroupInfo extension)
+//! - [`ExternalKeyIdExtension`] (GroupInfo extension and commit authenticated
+//!   data, private use)
+//! - [`ExternalSenderScopesExtension`] (GroupContext extension, private use)
+//! - [`ParentGroupExtension`] (GroupContext extension, private use)
+//! - [`GroupMergeExtension`] (commit authenticated data, private use)
+//! - [`CommitMetadataExtension`] (commit authenticated data, private use)
+//!
+//! Applications can define their own extensions via the [`CustomExtension`]
+//! trait.
 
 use std::{
     fmt::Debug,
@@ -29,9 +38,14 @@ use serde::{Deserialize, Serialize};
 // Private
 mod application_id_extension;
 mod codec;
+mod commit_metadata_extension;
+mod custom_extension;
+mod external_key_id_extension;
 mod external_pub_extension;
 mod external_sender_extension;
+mod group_merge_extension;
 mod last_resort;
+mod parent_group_extension;
 mod ratchet_tree_extension;
 mod required_capabilities;
 use errors::*;
@@ -41,11 +55,17 @@ pub mod errors;
 
 // Public re-exports
 pub use application_id_extension::ApplicationIdExtension;
+pub use commit_metadata_extension::CommitMetadataExtension;
+pub use custom_extension::CustomExtension;
+pub use external_key_id_extension::ExternalKeyIdExtension;
 pub use external_pub_extension::ExternalPubExtension;
 pub use external_sender_extension::{
-    ExternalSender, ExternalSendersExtension, SenderExtensionIndex,
+    ExternalSender, ExternalSenderScope, ExternalSenderScopesExtension, ExternalSendersExtension,
+    SenderExtensionIndex,
 };
+pub use group_merge_extension::GroupMergeExtension;
 pub use last_resort::LastResortExtension;
+pub use parent_group_extension::ParentGroupExtension;
 pub use ratchet_tree_extension::RatchetTreeExtension;
 pub use required_capabilities::RequiredCapabilitiesExtension;
 
@@ -0,0 +1,55 @@
+use tls_codec::{TlsDeserialize, TlsSerialize, TlsSize};
+
+use super::{CustomExtension, Deserialize, Serialize};
+use crate::group::{GroupEpoch, GroupId};
+
+/// # Parent group
+///
+/// A subgroup is a new group of selected members of an existing group, e.g.,
+/// for a thread or a breakout room. This group context extension links the
+/// subgroup to the group it was branched from and to the epoch of that group
+/// in which it was branched.
+///
+/// The link is verifiable: the first commit of the subgroup injects the
+/// resumption PSK of the parent group's epoch with usage `branch` into the
+/// key schedule. Members that join the subgroup check that the PSK identified
+/// by this extension was used, so only members of the parent group can create
+/// subgroups that are linked to it. See
+/// [`MlsGroup::create_subgroup()`](crate::group::MlsGroup::create_subgroup())
+/// for details.
+///
+/// ```c
+/// struct {
+///     opaque group_id<V>;
+///     uint64 epoch;
+/// } ParentGroup;
+/// ```
+#[derive(
+    PartialEq, Eq, Clone, Debug, Serialize, Deserialize, TlsSerialize, TlsDeserialize, TlsSize,
+)]
+pub struct ParentGroupExtension {
+    group_id: GroupId,
+    epoch: GroupEpoch,
+}
+
+impl ParentGroupExtension {
+    /// Creates a new `ParentGroupExtension`.
+    pub fn new(group_id: GroupId, epoch: GroupEpoch) -> Self {
+        Self { group_id, epoch }
+    }
+
+    /// Returns the ID of the parent group.
+    pub fn group_id(&self) -> &GroupId {
+        &self.group_id
+    }
+
+    /// Returns the epoch of the parent group in which the subgroup was
+    /// branched.
+    pub fn epoch(&self) -> GroupEpoch {
+        self.epoch
+    }
+}
+
+impl CustomExtension for ParentGroupExtension {
+    const EXTENSION_TYPE: u16 = 0xff0f;
+}
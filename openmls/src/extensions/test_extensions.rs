@@ -4,7 +4,7 @@
 
 use openmls_rust_crypto::OpenMlsRustCrypto;
 use openmls_traits::key_store::OpenMlsKeyStore;
-use tls_codec::{Deserialize, Serialize};
+use tls_codec::{Deserialize, Serialize, TlsDeserialize, TlsSerialize, TlsSize, VLBytes};
 
 use super::*;
 use crate::{
@@ -35,6 +35,100 @@ fn application_id() {
     assert_eq!(&data[..], &serialized_extension_struct);
 }
 
+#[test]
+fn extension_data_length() {
+    // Extension data is written straight into the writer behind its length,
+    // which has to be encoded like any other `VLBytes`.
+    for length in [0, 0x3f, 0x40, 0x3fff, 0x4000] {
+        let data = vec![7u8; length];
+        let extension = Extension::Unknown(0xff00, UnknownExtension(data.clone()));
+
+        let bytes = extension
+            .tls_serialize_detached()
+            .expect("error encoding extension");
+        assert_eq!(bytes.len(), extension.tls_serialized_len());
+        assert_eq!(
+            &bytes[2..],
+            VLBytes::new(data).tls_serialize_detached().unwrap()
+        );
+        assert_eq!(
+            Extension::tls_deserialize(&mut bytes.as_slice()).expect("error decoding extension"),
+            extension
+        );
+    }
+}
+
+#[test]
+fn custom_extension() {
+    #[derive(Debug, PartialEq, TlsSerialize, TlsDeserialize, TlsSize)]
+    struct TestExtension {
+        name: VLBytes,
+        values: Vec<u16>,
+        flag: Option<u8>,
+    }
+
+    impl CustomExtension for TestExtension {
+        const EXTENSION_TYPE: u16 = 0xff00;
+    }
+
+    let test_extension = TestExtension {
+        name: vec![1, 2, 3].into(),
+        values: vec![4, 5],
+        flag: Some(6),
+    };
+    let ext = test_extension
+        .to_extension()
+        .expect("error encoding custom extension");
+    // The extension data is encoded as defined in RFC 9420.
+    let extension_bytes = vec![0xffu8, 0, 11, 3, 1, 2, 3, 4, 0, 4, 0, 5, 1, 6];
+    assert_eq!(
+        extension_bytes,
+        ext.tls_serialize_detached()
+            .expect("An unexpected error occurred.")
+    );
+
+    // Decoding yields an unknown extension that can be read as custom extension.
+    let extensions = Extensions::tls_deserialize(
+        &mut Extensions::single(ext)
+            .tls_serialize_detached()
+            .expect("An unexpected error occurred.")
+            .as_slice(),
+    )
+    .expect("An unexpected error occurred.");
+    assert_eq!(
+        extensions.custom::<TestExtension>(),
+        Ok(Some(test_extension))
+    );
+    assert_eq!(Extensions::empty().custom::<TestExtension>(), Ok(None));
+
+    // Other extensions are rejected.
+    let ext = Extension::ApplicationId(ApplicationIdExtension::new(b"test"));
+    assert_eq!(
+        TestExtension::from_extension(&ext),
+        Err(CustomExtensionError::WrongExtensionType)
+    );
+
+    // Trailing data is rejected.
+    let ext = Extension::Unknown(0xff00, UnknownExtension(vec![0, 0, 0, 0]));
+    assert_eq!(
+        TestExtension::from_extension(&ext),
+        Err(CustomExtensionError::TrailingData)
+    );
+
+    // Extension types supported by OpenMLS can't be used.
+    #[derive(TlsSerialize, TlsDeserialize, TlsSize)]
+    struct ReservedExtension {}
+
+    impl CustomExtension for ReservedExtension {
+        const EXTENSION_TYPE: u16 = 1;
+    }
+
+    assert_eq!(
+        ReservedExtension {}.to_extension(),
+        Err(CustomExtensionError::ReservedExtensionType)
+    );
+}
+
 // This tests the ratchet tree extension to deliver the public ratcheting tree
 // in-band
 #[apply(ciphersuites_and_providers)]
@@ -286,7 +380,11 @@ fn last_resort_extension(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvid
         .expect("error encoding key package with last resort extension");
     let decoded_kp = KeyPackageIn::tls_deserialize(&mut encoded_kp.as_slice())
         .expect("error decoding key package with last resort extension")
-        .validate(provider.crypto(), ProtocolVersion::default())
+        .validate(
+            provider.crypto(),
+            ProtocolVersion::default(),
+            provider.clock(),
+        )
         .expect("error validating key package with last resort extension");
     assert!(decoded_kp.last_resort());
 
@@ -320,7 +418,8 @@ fn last_resort_extension(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvid
             &alice_credential_with_key_and_signer.signer,
             &[kp.clone()],
         )
-        .expect("An unexpected error occurred.");
+        .expect("An unexpected error occurred.")
+        .into_parts();
 
     alice_group.merge_pending_commit(provider).unwrap();
 
@@ -332,11 +431,10 @@ fn last_resort_extension(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvid
     )
     .expect("An unexpected error occurred.");
 
-    // This should not have deleted the KP from the store
-    let kp: Option<KeyPackage> = provider.key_store().read(
-        kp.hash_ref(provider.crypto())
-            .expect("error hashing kp")
-            .as_slice(),
-    );
+    // This should not have deleted the KP from the store or marked it as
+    // consumed
+    let kp_ref = kp.hash_ref(provider.crypto()).expect("error hashing kp");
+    let kp: Option<KeyPackage> = provider.key_store().read(kp_ref.as_slice());
     assert!(kp.is_some());
+    assert!(!KeyPackage::is_consumed(provider.key_store(), &kp_ref));
 }
@@ -1,3 +1,5 @@
+#![deny(clippy::unwrap_used, clippy::expect_used)]
+
 use std::io::{Read, Write};
 use tls_codec::{Deserialize, Serialize, Size};
 
@@ -10,7 +12,8 @@ use super::{
 
 impl Size for PrivateMessageContent {
     fn tls_serialized_len(&self) -> usize {
-        self.content.tls_serialized_len() +
+        // The `content` is serialized without the `content_type`.
+        self.content.serialized_len_without_type() +
            self.auth.tls_serialized_len() +
             // Note: The padding is appended as a "raw" all-zero byte slice
             // with length `length_of_padding`. Thus, we only need to add
@@ -63,7 +66,7 @@ pub(super) fn deserialize_ciphertext_content<R: Read>(
 impl Deserialize for MlsMessageIn {
     fn tls_deserialize<R: Read>(bytes: &mut R) -> Result<Self, tls_codec::Error> {
         let version = ProtocolVersion::tls_deserialize(bytes)?;
-        let body = MlsMessageInBody::tls_deserialize(bytes)?;
+        let mut body = MlsMessageInBody::tls_deserialize(bytes)?;
 
         // KeyPackage version must match MlsMessage version.
         if let MlsMessageInBody::KeyPackage(key_package) = &body {
@@ -73,6 +76,19 @@ impl Deserialize for MlsMessageIn {
                 ));
             }
         }
+
+        // GroupInfo version must match MlsMessage version.
+        if let MlsMessageInBody::GroupInfo(group_info) = &body {
+            if group_info.protocol_version() != version {
+                return Err(tls_codec::Error::DecodingError(
+                    "GroupInfo version does not match MlsMessage version.".into(),
+                ));
+            }
+        }
+
+        // Protocol messages are checked against the version of the group when
+        // they are processed.
+        body.set_envelope_version(version);
         Ok(Self { version, body })
     }
 }
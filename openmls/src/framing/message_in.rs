@@ -14,10 +14,13 @@
 //! If an [`MlsMessageIn`] contains a [`PublicMessage`] or [`PrivateMessage`],
 //! can be used to determine which group can be used to process the message.
 
+#![deny(clippy::unwrap_used, clippy::expect_used)]
+
 use super::*;
 use crate::{
-    key_packages::KeyPackageIn, messages::group_info::VerifiableGroupInfo,
-    versions::ProtocolVersion,
+    key_packages::KeyPackageIn,
+    messages::group_info::VerifiableGroupInfo,
+    versions::{ProtocolVersion, VersionError},
 };
 
 /// Before use with the [`MlsGroup`] API, the message has to be unpacked via
@@ -93,6 +96,19 @@ pub enum MlsMessageInBody {
     KeyPackage(KeyPackageIn),
 }
 
+impl MlsMessageInBody {
+    /// Remember the protocol `version` of the envelope in protocol messages.
+    pub(crate) fn set_envelope_version(&mut self, version: ProtocolVersion) {
+        match self {
+            MlsMessageInBody::PublicMessage(message) => message.envelope_version = Some(version),
+            MlsMessageInBody::PrivateMessage(message) => message.set_envelope_version(version),
+            MlsMessageInBody::Welcome(_)
+            | MlsMessageInBody::GroupInfo(_)
+            | MlsMessageInBody::KeyPackage(_) => (),
+        }
+    }
+}
+
 impl MlsMessageIn {
     /// Returns the wire format.
     pub fn wire_format(&self) -> WireFormat {
@@ -105,12 +121,34 @@ impl MlsMessageIn {
         }
     }
 
+    /// Returns the protocol version of the message.
+    pub fn version(&self) -> ProtocolVersion {
+        self.version
+    }
+
     /// Extract the content of an [`MlsMessageIn`] after deserialization for use
     /// with the [`MlsGroup`] API.
+    ///
+    /// Public and private messages keep the protocol version of the
+    /// [`MlsMessageIn`], which [`MlsGroup::process_message()`] checks against
+    /// the version of the group.
     pub fn extract(self) -> MlsMessageInBody {
         self.body
     }
 
+    /// Extract the content of an [`MlsMessageIn`] after checking that the
+    /// message uses the `expected_version`, e.g., the version of the group the
+    /// message is processed in (see [`MlsGroup::version()`]).
+    ///
+    /// Returns a [`VersionError::VersionMismatch`] if the versions don't match.
+    pub fn extract_checked(
+        self,
+        expected_version: ProtocolVersion,
+    ) -> Result<MlsMessageInBody, VersionError> {
+        expected_version.check_matches(self.version)?;
+        Ok(self.body)
+    }
+
     #[cfg(any(test, feature = "test-utils"))]
     pub fn into_keypackage(self) -> Option<crate::key_packages::KeyPackage> {
         match self.body {
@@ -229,6 +267,15 @@ impl ProtocolMessage {
     pub fn is_handshake_message(&self) -> bool {
         self.content_type().is_handshake_message()
     }
+
+    /// Returns the protocol version of the [`MlsMessageIn`] the message was
+    /// received in, or `None` if it was deserialized without one.
+    pub(crate) fn envelope_version(&self) -> Option<ProtocolVersion> {
+        match self {
+            ProtocolMessage::PrivateMessage(ref m) => m.envelope_version(),
+            ProtocolMessage::PublicMessage(ref m) => m.envelope_version,
+        }
+    }
 }
 
 impl From<PrivateMessageIn> for ProtocolMessage {
@@ -7,7 +7,7 @@
 //!
 //! The [`MlsMessageOut`] struct is meant to be serialized upon its return from
 //! a function of the `MlsGroup` API so that it can be sent to the DS.
-use tls_codec::Serialize;
+use tls_codec::{Serialize, Size};
 
 use super::*;
 
@@ -143,6 +143,21 @@ impl MlsMessageOut {
         self.tls_serialize_detached()
             .map_err(|_| MlsMessageError::UnableToEncode)
     }
+
+    /// Returns the exact length of the serialized message without encoding
+    /// it, e.g. to check a message size limit or to allocate a buffer.
+    pub fn serialized_len(&self) -> usize {
+        self.tls_serialized_len()
+    }
+
+    /// Serializes the message into `writer` without building the whole
+    /// encoding in memory first, e.g. to stream a large [`Welcome`] or
+    /// [`GroupInfo`] to a socket or a file. Returns the number of bytes
+    /// written, or [`MlsMessageError::UnableToEncode`] on failure.
+    pub fn write_to<W: std::io::Write>(&self, writer: &mut W) -> Result<usize, MlsMessageError> {
+        self.tls_serialize(writer)
+            .map_err(|_| MlsMessageError::UnableToEncode)
+    }
 }
 
 // Convenience functions for tests and test-utils
@@ -202,7 +217,7 @@ impl From<MlsMessageIn> for MlsMessageOut {
 impl From<MlsMessageOut> for MlsMessageIn {
     fn from(mls_message_out: MlsMessageOut) -> Self {
         let version = mls_message_out.version;
-        let body = match mls_message_out.body {
+        let mut body = match mls_message_out.body {
             MlsMessageOutBody::PublicMessage(pm) => MlsMessageInBody::PublicMessage(pm.into()),
             MlsMessageOutBody::PrivateMessage(pm) => MlsMessageInBody::PrivateMessage(pm.into()),
             MlsMessageOutBody::Welcome(w) => MlsMessageInBody::Welcome(w),
@@ -211,6 +226,7 @@ impl From<MlsMessageOut> for MlsMessageIn {
             }
             MlsMessageOutBody::KeyPackage(kp) => MlsMessageInBody::KeyPackage(kp.into()),
         };
+        body.set_envelope_version(version);
         Self { version, body }
     }
 }
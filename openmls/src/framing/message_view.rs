@@ -0,0 +1,151 @@
+//! MLS Message (View)
+//!
+//! This module defines [`MlsMessageView`], a borrowed view of the outer fields
+//! of a serialized `MLSMessage`. It is meant for servers (e.g. a DS) that
+//! route or filter a large number of messages and only need the group ID, the
+//! epoch, or the content type of a message. Parsing a view doesn't allocate:
+//! the byte strings of the view are slices into the input.
+//!
+//! A view doesn't verify or fully decode the message. Clients process
+//! messages with [`MlsMessageIn`] instead.
+
+#![deny(clippy::unwrap_used, clippy::expect_used)]
+
+use tls_codec::Deserialize as TlsDeserializeTrait;
+
+use super::*;
+
+use crate::versions::ProtocolVersion;
+
+/// A borrowed view of a serialized `MLSMessage`.
+///
+/// The group ID, epoch, sender, content type and authenticated data are only
+/// available for [`WireFormat::PublicMessage`] and
+/// [`WireFormat::PrivateMessage`]. The sender of a private message is
+/// encrypted and thus not available either.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MlsMessageView<'a> {
+    version: ProtocolVersion,
+    wire_format: WireFormat,
+    group_id: Option<&'a [u8]>,
+    epoch: Option<GroupEpoch>,
+    sender: Option<Sender>,
+    content_type: Option<ContentType>,
+    authenticated_data: Option<&'a [u8]>,
+    body: &'a [u8],
+}
+
+impl<'a> MlsMessageView<'a> {
+    /// Parses the outer fields of the serialized message in `bytes`. Returns
+    /// [`MlsMessageError::UnableToDecode`] if they can't be decoded.
+    pub fn parse(bytes: &'a [u8]) -> Result<Self, MlsMessageError> {
+        Self::parse_fields(bytes).map_err(|_| MlsMessageError::UnableToDecode)
+    }
+
+    fn parse_fields(bytes: &'a [u8]) -> Result<Self, tls_codec::Error> {
+        let mut bytes = bytes;
+        let version = ProtocolVersion::tls_deserialize(&mut bytes)?;
+        let wire_format = WireFormat::tls_deserialize(&mut bytes)?;
+        let mut view = Self {
+            version,
+            wire_format,
+            group_id: None,
+            epoch: None,
+            sender: None,
+            content_type: None,
+            authenticated_data: None,
+            body: bytes,
+        };
+
+        match wire_format {
+            WireFormat::PublicMessage => {
+                view.group_id = Some(read_vl_slice(&mut bytes)?);
+                view.epoch = Some(GroupEpoch::tls_deserialize(&mut bytes)?);
+                view.sender = Some(Sender::tls_deserialize(&mut bytes)?);
+                view.authenticated_data = Some(read_vl_slice(&mut bytes)?);
+                view.content_type = Some(ContentType::tls_deserialize(&mut bytes)?);
+            }
+            WireFormat::PrivateMessage => {
+                view.group_id = Some(read_vl_slice(&mut bytes)?);
+                view.epoch = Some(GroupEpoch::tls_deserialize(&mut bytes)?);
+                view.content_type = Some(ContentType::tls_deserialize(&mut bytes)?);
+                view.authenticated_data = Some(read_vl_slice(&mut bytes)?);
+            }
+            WireFormat::Welcome | WireFormat::GroupInfo | WireFormat::KeyPackage => {}
+        }
+
+        Ok(view)
+    }
+
+    /// Returns the protocol version of the message.
+    pub fn version(&self) -> ProtocolVersion {
+        self.version
+    }
+
+    /// Returns the wire format of the message.
+    pub fn wire_format(&self) -> WireFormat {
+        self.wire_format
+    }
+
+    /// Returns the group ID of a public or private message.
+    pub fn group_id(&self) -> Option<&'a [u8]> {
+        self.group_id
+    }
+
+    /// Returns the epoch of a public or private message.
+    pub fn epoch(&self) -> Option<GroupEpoch> {
+        self.epoch
+    }
+
+    /// Returns the sender of a public message.
+    pub fn sender(&self) -> Option<&Sender> {
+        self.sender.as_ref()
+    }
+
+    /// Returns the content type of a public or private message.
+    pub fn content_type(&self) -> Option<ContentType> {
+        self.content_type
+    }
+
+    /// Returns the authenticated data of a public or private message.
+    pub fn authenticated_data(&self) -> Option<&'a [u8]> {
+        self.authenticated_data
+    }
+
+    /// Returns the serialized body of the message, i.e., everything after the
+    /// wire format.
+    pub fn body(&self) -> &'a [u8] {
+        self.body
+    }
+}
+
+/// Reads a variable-length vector as a slice of `bytes` and advances `bytes`
+/// past it. Like the `VLBytes` decoding, this only accepts the minimal
+/// encoding of the length.
+fn read_vl_slice<'a>(bytes: &mut &'a [u8]) -> Result<&'a [u8], tls_codec::Error> {
+    let input: &'a [u8] = bytes;
+    let first = *input.first().ok_or(tls_codec::Error::EndOfStream)?;
+    let (length_len, min_length) = match first >> 6 {
+        0 => (1, 0),
+        1 => (2, 0x40),
+        2 => (4, 0x4000),
+        _ => return Err(tls_codec::Error::InvalidVectorLength),
+    };
+    let header = input
+        .get(..length_len)
+        .ok_or(tls_codec::Error::EndOfStream)?;
+    let length = header[1..]
+        .iter()
+        .fold(usize::from(first & 0x3f), |length, byte| {
+            (length << 8) | usize::from(*byte)
+        });
+    if length < min_length {
+        return Err(tls_codec::Error::InvalidVectorLength);
+    }
+    let end = length_len + length;
+    let slice = input
+        .get(length_len..end)
+        .ok_or(tls_codec::Error::EndOfStream)?;
+    *bytes = &input[end..];
+    Ok(slice)
+}
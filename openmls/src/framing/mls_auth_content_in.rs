@@ -7,16 +7,18 @@
 //! signatures are verified before the content of an MLS [`PrivateMessageIn`] or
 //! [`PublicMessageIn`] can be accessed by processing functions of OpenMLS.
 
+#![deny(clippy::unwrap_used, clippy::expect_used)]
+
 use std::io::Read;
 
-use openmls_traits::{crypto::OpenMlsCrypto, types::Ciphersuite};
+use openmls_traits::{clock::OpenMlsClock, crypto::OpenMlsCrypto, types::Ciphersuite};
 use tls_codec::Serialize as TlsSerializeTrait;
 
 use super::{mls_auth_content::*, mls_content_in::*, *};
 use crate::{
     ciphersuite::signable::{SignedStruct, Verifiable, VerifiedStruct},
     credentials::CredentialWithKey,
-    group::errors::ValidationError,
+    group::{config::ValidationPolicy, errors::ValidationError},
     messages::proposals_in::ProposalIn,
     versions::ProtocolVersion,
 };
@@ -56,6 +58,8 @@ impl AuthenticatedContentIn {
         crypto: &impl OpenMlsCrypto,
         sender_context: Option<SenderContext>,
         protocol_version: ProtocolVersion,
+        clock: &impl OpenMlsClock,
+        policy: &ValidationPolicy,
     ) -> Result<AuthenticatedContent, ValidationError> {
         Ok(AuthenticatedContent {
             wire_format: self.wire_format,
@@ -64,14 +68,13 @@ impl AuthenticatedContentIn {
                 crypto,
                 sender_context,
                 protocol_version,
+                clock,
+                policy,
             )?,
             auth: self.auth,
         })
     }
-}
 
-#[cfg(any(feature = "test-utils", test))]
-impl AuthenticatedContentIn {
     /// Get the content body of the message.
     pub(crate) fn content(&self) -> &FramedContentBodyIn {
         &self.content.body
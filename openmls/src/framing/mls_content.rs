@@ -62,7 +62,8 @@ impl From<AuthenticatedContent> for FramedContent {
 ///     }
 /// } FramedContent;
 /// ```
-#[derive(Debug, PartialEq, Clone, Serialize, Deserialize, TlsSerialize, TlsSize)]
+#[derive(PartialEq, Clone, Serialize, Deserialize, TlsSerialize, TlsSize)]
+#[cfg_attr(feature = "content-debug", derive(Debug))]
 #[repr(u8)]
 pub(crate) enum FramedContentBody {
     #[tls_codec(discriminant = 1)]
@@ -73,6 +74,17 @@ pub(crate) enum FramedContentBody {
     Commit(Commit),
 }
 
+#[cfg(not(feature = "content-debug"))]
+impl std::fmt::Debug for FramedContentBody {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Application(_) => f.debug_tuple("Application").field(&"***").finish(),
+            Self::Proposal(proposal) => f.debug_tuple("Proposal").field(proposal).finish(),
+            Self::Commit(commit) => f.debug_tuple("Commit").field(commit).finish(),
+        }
+    }
+}
+
 impl FramedContentBody {
     /// Returns the [`ContentType`].
     pub(crate) fn content_type(&self) -> ContentType {
@@ -1,11 +1,13 @@
 //! This module contains the [`FramedContentIn`] struct and associated helper structs
 //! such as [`FramedContentTbsIn`], as well as their implementations.
 
+#![deny(clippy::unwrap_used, clippy::expect_used)]
+
 use crate::{
     ciphersuite::signable::Signable,
     error::LibraryError,
     framing::SenderContext,
-    group::{errors::ValidationError, GroupEpoch, GroupId},
+    group::{config::ValidationPolicy, errors::ValidationError, GroupEpoch, GroupId},
     messages::{proposals_in::ProposalIn, CommitIn},
     versions::ProtocolVersion,
 };
@@ -18,7 +20,7 @@ use super::{
     ContentType, Sender, WireFormat,
 };
 
-use openmls_traits::{crypto::OpenMlsCrypto, types::Ciphersuite};
+use openmls_traits::{clock::OpenMlsClock, crypto::OpenMlsCrypto, types::Ciphersuite};
 use serde::{Deserialize, Serialize};
 use tls_codec::{
     Deserialize as TlsDeserializeTrait, Serialize as TlsSerializeTrait, Size, TlsDeserialize,
@@ -55,15 +57,22 @@ impl FramedContentIn {
         crypto: &impl OpenMlsCrypto,
         sender_context: Option<SenderContext>,
         protocol_version: ProtocolVersion,
+        clock: &impl OpenMlsClock,
+        policy: &ValidationPolicy,
     ) -> Result<FramedContent, ValidationError> {
         Ok(FramedContent {
             group_id: self.group_id,
             epoch: self.epoch,
             sender: self.sender,
             authenticated_data: self.authenticated_data,
-            body: self
-                .body
-                .validate(ciphersuite, crypto, sender_context, protocol_version)?,
+            body: self.body.validate(
+                ciphersuite,
+                crypto,
+                sender_context,
+                protocol_version,
+                clock,
+                policy,
+            )?,
         })
     }
 }
@@ -90,9 +99,8 @@ impl From<AuthenticatedContentIn> for FramedContentIn {
 ///     }
 /// } FramedContent;
 /// ```
-#[derive(
-    Debug, PartialEq, Clone, Serialize, Deserialize, TlsSerialize, TlsDeserialize, TlsSize,
-)]
+#[derive(PartialEq, Clone, Serialize, Deserialize, TlsSerialize, TlsDeserialize, TlsSize)]
+#[cfg_attr(feature = "content-debug", derive(Debug))]
 #[repr(u8)]
 pub(crate) enum FramedContentBodyIn {
     #[tls_codec(discriminant = 1)]
@@ -103,6 +111,17 @@ pub(crate) enum FramedContentBodyIn {
     Commit(CommitIn),
 }
 
+#[cfg(not(feature = "content-debug"))]
+impl std::fmt::Debug for FramedContentBodyIn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Application(_) => f.debug_tuple("Application").field(&"***").finish(),
+            Self::Proposal(proposal) => f.debug_tuple("Proposal").field(proposal).finish(),
+            Self::Commit(commit) => f.debug_tuple("Commit").field(commit).finish(),
+        }
+    }
+}
+
 impl FramedContentBodyIn {
     /// Returns the [`ContentType`].
     pub(crate) fn content_type(&self) -> ContentType {
@@ -135,12 +154,21 @@ impl FramedContentBodyIn {
         crypto: &impl OpenMlsCrypto,
         sender_context: Option<SenderContext>,
         protocol_version: ProtocolVersion,
+        clock: &impl OpenMlsClock,
+        policy: &ValidationPolicy,
     ) -> Result<FramedContentBody, ValidationError> {
         Ok(match self {
             FramedContentBodyIn::Application(bytes) => FramedContentBody::Application(bytes),
-            FramedContentBodyIn::Proposal(proposal_in) => FramedContentBody::Proposal(
-                proposal_in.validate(crypto, ciphersuite, sender_context, protocol_version)?,
-            ),
+            FramedContentBodyIn::Proposal(proposal_in) => {
+                FramedContentBody::Proposal(proposal_in.validate(
+                    crypto,
+                    ciphersuite,
+                    sender_context,
+                    protocol_version,
+                    clock,
+                    policy,
+                )?)
+            }
             FramedContentBodyIn::Commit(commit_in) => {
                 let sender_context = sender_context
                     .ok_or(LibraryError::custom("Forgot the commit sender context"))?;
@@ -149,6 +177,8 @@ impl FramedContentBodyIn {
                     crypto,
                     sender_context,
                     protocol_version,
+                    clock,
+                    policy,
                 )?)
             }
         })
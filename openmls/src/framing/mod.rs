@@ -55,6 +55,7 @@ pub(crate) mod codec;
 
 pub(crate) mod message_in;
 pub(crate) mod message_out;
+pub(crate) mod message_view;
 pub(crate) mod mls_auth_content;
 pub(crate) mod mls_auth_content_in;
 pub(crate) mod mls_content;
@@ -85,6 +86,7 @@ pub mod errors;
 
 pub use message_in::*;
 pub use message_out::*;
+pub use message_view::*;
 pub use private_message::*;
 pub use private_message_in::*;
 pub use public_message::*;
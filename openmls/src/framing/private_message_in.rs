@@ -1,3 +1,5 @@
+#![deny(clippy::unwrap_used, clippy::expect_used)]
+
 use openmls_traits::crypto::OpenMlsCrypto;
 use openmls_traits::types::Ciphersuite;
 use tls_codec::{Deserialize, Serialize, TlsDeserialize, TlsSerialize, TlsSize};
@@ -11,7 +13,11 @@ use crate::{
     binary_tree::array_representation::LeafNodeIndex,
     error::LibraryError,
     framing::mls_content_in::FramedContentIn,
-    tree::{secret_tree::SecretType, sender_ratchet::SenderRatchetConfiguration},
+    tree::{
+        secret_tree::{SecretTreeError, SecretType},
+        sender_ratchet::SenderRatchetConfiguration,
+    },
+    versions::ProtocolVersion,
 };
 
 use super::*;
@@ -39,6 +45,10 @@ pub struct PrivateMessageIn {
     authenticated_data: VLBytes,
     encrypted_sender_data: VLBytes,
     ciphertext: VLBytes,
+    /// The protocol version of the [`MlsMessageIn`] the message was received
+    /// in. It isn't part of the encoding of the message.
+    #[tls_codec(skip)]
+    envelope_version: Option<ProtocolVersion>,
 }
 
 impl PrivateMessageIn {
@@ -48,16 +58,25 @@ impl PrivateMessageIn {
         message_secrets: &MessageSecrets,
         crypto: &impl OpenMlsCrypto,
         ciphersuite: Ciphersuite,
+    ) -> Result<MlsSenderData, MessageDecryptionError> {
+        self.decrypt_sender_data(message_secrets.sender_data_secret(), crypto, ciphersuite)
+    }
+
+    /// Decrypt the sender data from this [`PrivateMessageIn`] with the given
+    /// [`SenderDataSecret`].
+    pub(crate) fn decrypt_sender_data(
+        &self,
+        sender_data_secret: &SenderDataSecret,
+        crypto: &impl OpenMlsCrypto,
+        ciphersuite: Ciphersuite,
     ) -> Result<MlsSenderData, MessageDecryptionError> {
         log::debug!("Decrypting PrivateMessage");
         // Derive key from the key schedule using the ciphertext.
-        let sender_data_key = message_secrets
-            .sender_data_secret()
+        let sender_data_key = sender_data_secret
             .derive_aead_key(crypto, self.ciphertext.as_slice())
             .map_err(LibraryError::unexpected_crypto_error)?;
         // Derive initial nonce from the key schedule using the ciphertext.
-        let sender_data_nonce = message_secrets
-            .sender_data_secret()
+        let sender_data_nonce = sender_data_secret
             .derive_aead_nonce(ciphersuite, crypto, self.ciphertext.as_slice())
             .map_err(LibraryError::unexpected_crypto_error)?;
         // Serialize sender data AAD
@@ -94,7 +113,7 @@ impl PrivateMessageIn {
     fn decrypt(
         &self,
         crypto: &impl OpenMlsCrypto,
-        ratchet_key: AeadKey,
+        ratchet_key: &AeadKey,
         ratchet_nonce: &AeadNonce,
     ) -> Result<PrivateMessageContentIn, MessageDecryptionError> {
         // Serialize content AAD
@@ -166,8 +185,31 @@ impl PrivateMessageIn {
                     "  Ciphertext generation out of bounds {}\n\t{e:?}",
                     sender_data.generation
                 );
-                MessageDecryptionError::GenerationOutOfBound
+                match e {
+                    // Keep replays distinguishable from other failures.
+                    SecretTreeError::SecretReuseError | SecretTreeError::KeyEvicted => e.into(),
+                    _ => MessageDecryptionError::GenerationOutOfBound,
+                }
             })?;
+        self.decrypt_with_key(
+            crypto,
+            &ratchet_key,
+            ratchet_nonce,
+            sender_data,
+            message_secrets.serialized_context(),
+        )
+    }
+
+    /// Decrypts this [`PrivateMessage`] with the key material of the sender
+    /// ratchet into a [`VerifiableAuthenticatedContent`].
+    pub(crate) fn decrypt_with_key(
+        &self,
+        crypto: &impl OpenMlsCrypto,
+        ratchet_key: &AeadKey,
+        ratchet_nonce: AeadNonce,
+        sender_data: MlsSenderData,
+        serialized_context: &[u8],
+    ) -> Result<VerifiableAuthenticatedContentIn, MessageDecryptionError> {
         // Prepare the nonce by xoring with the reuse guard.
         let prepared_nonce = ratchet_nonce.xor_with_reuse_guard(&sender_data.reuse_guard);
         let private_message_content = self.decrypt(crypto, ratchet_key, &prepared_nonce)?;
@@ -189,7 +231,7 @@ impl PrivateMessageIn {
                 authenticated_data: self.authenticated_data.clone(),
                 body: private_message_content.content,
             },
-            Some(message_secrets.serialized_context().to_vec()),
+            Some(serialized_context.to_vec()),
             private_message_content.auth,
         );
         Ok(verifiable)
@@ -205,11 +247,28 @@ impl PrivateMessageIn {
         self.epoch
     }
 
+    /// Get the `authenticated_data` in the `PrivateMessage`.
+    pub(crate) fn authenticated_data(&self) -> &[u8] {
+        self.authenticated_data.as_slice()
+    }
+
     /// Get the `content_type` in the `PrivateMessage`.
     pub(crate) fn content_type(&self) -> ContentType {
         self.content_type
     }
 
+    /// Get the protocol version of the [`MlsMessageIn`] the `PrivateMessage`
+    /// was received in, if any.
+    pub(crate) fn envelope_version(&self) -> Option<ProtocolVersion> {
+        self.envelope_version
+    }
+
+    /// Set the protocol version of the [`MlsMessageIn`] the `PrivateMessage`
+    /// was received in.
+    pub(crate) fn set_envelope_version(&mut self, version: ProtocolVersion) {
+        self.envelope_version = Some(version);
+    }
+
     /// Set the ciphertext.
     #[cfg(test)]
     pub(crate) fn set_ciphertext(&mut self, ciphertext: Vec<u8>) {
@@ -217,6 +276,47 @@ impl PrivateMessageIn {
     }
 }
 
+#[cfg(any(test, feature = "test-utils"))]
+impl PrivateMessageIn {
+    /// Set the group id.
+    pub(crate) fn set_group_id(&mut self, group_id: GroupId) {
+        self.group_id = group_id;
+    }
+
+    /// Set the epoch.
+    pub(crate) fn set_epoch(&mut self, epoch: GroupEpoch) {
+        self.epoch = epoch;
+    }
+
+    /// Flip a bit in the encrypted sender data.
+    pub(crate) fn flip_sender_data_bit(&mut self) {
+        self.encrypted_sender_data = flip_first_bit(self.encrypted_sender_data.as_slice());
+    }
+
+    /// Flip a bit at the start of the ciphertext. This is the sample from
+    /// which the key for the sender data is derived.
+    pub(crate) fn flip_ciphertext_bit(&mut self) {
+        self.ciphertext = flip_first_bit(self.ciphertext.as_slice());
+    }
+
+    /// Flip a bit at the end of the ciphertext, i.e., in the tag of the
+    /// content. The sender data can still be decrypted.
+    pub(crate) fn flip_ciphertext_tag_bit(&mut self) {
+        let mut ciphertext = self.ciphertext.as_slice().to_vec();
+        if let Some(last) = ciphertext.last_mut() {
+            *last ^= 0x01;
+        }
+        self.ciphertext = ciphertext.into();
+    }
+}
+
+#[cfg(any(test, feature = "test-utils"))]
+fn flip_first_bit(bytes: &[u8]) -> VLBytes {
+    let mut bytes = bytes.to_vec();
+    bytes[0] ^= 0x01;
+    bytes.into()
+}
+
 // === Helper structs ===
 
 /// PrivateMessageContent
@@ -275,6 +375,7 @@ impl From<PrivateMessage> for PrivateMessageIn {
             authenticated_data: value.authenticated_data,
             encrypted_sender_data: value.encrypted_sender_data,
             ciphertext: value.ciphertext,
+            envelope_version: None,
         }
     }
 }
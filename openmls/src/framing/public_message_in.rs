@@ -3,6 +3,8 @@
 //! A PublicMessageIn is a framing structure for MLS messages. It can contain
 //! Proposals, Commits and application messages.
 
+#![deny(clippy::unwrap_used, clippy::expect_used)]
+
 use crate::{error::LibraryError, group::errors::ValidationError, versions::ProtocolVersion};
 
 use super::{
@@ -35,6 +37,10 @@ pub struct PublicMessageIn {
     pub(crate) content: FramedContentIn,
     pub(crate) auth: FramedContentAuthData,
     pub(crate) membership_tag: Option<MembershipTag>,
+    /// The protocol version of the [`MlsMessageIn`] the message was received
+    /// in. It isn't part of the encoding of the message.
+    #[serde(skip)]
+    pub(crate) envelope_version: Option<ProtocolVersion>,
 }
 
 #[cfg(any(test, feature = "test-utils"))]
@@ -42,6 +48,29 @@ impl PublicMessageIn {
     pub(crate) fn content(&self) -> &crate::framing::mls_content_in::FramedContentBodyIn {
         &self.content.body
     }
+
+    pub fn set_epoch(&mut self, epoch: u64) {
+        self.content.epoch = epoch.into();
+    }
+
+    /// Set the group id.
+    pub(crate) fn set_group_id(&mut self, group_id: GroupId) {
+        self.content.group_id = group_id;
+    }
+
+    /// Flip a bit in the signature.
+    pub(crate) fn flip_signature_bit(&mut self) {
+        let mut modified_signature = self.auth.signature.as_slice().to_vec();
+        modified_signature[0] ^= 0x01;
+        self.auth.signature.modify(&modified_signature);
+    }
+
+    /// Flip a bit in the membership tag, if there is one.
+    pub(crate) fn flip_membership_tag_bit(&mut self) {
+        if let Some(membership_tag) = &mut self.membership_tag {
+            membership_tag.0.flip_last_byte();
+        }
+    }
 }
 
 #[cfg(test)]
@@ -58,10 +87,6 @@ impl PublicMessageIn {
         self.content.body = content;
     }
 
-    pub fn set_epoch(&mut self, epoch: u64) {
-        self.content.epoch = epoch.into();
-    }
-
     /// Set the sender.
     pub(crate) fn set_sender(&mut self, sender: Sender) {
         self.content.sender = sender;
@@ -74,6 +99,7 @@ impl From<AuthenticatedContentIn> for PublicMessageIn {
             content: v.content,
             auth: v.auth,
             membership_tag: None,
+            envelope_version: None,
         }
     }
 }
@@ -89,6 +115,7 @@ impl PublicMessageIn {
             content,
             auth,
             membership_tag,
+            envelope_version: None,
         }
     }
 
@@ -102,7 +129,7 @@ impl PublicMessageIn {
         &self.content.sender
     }
 
-    #[cfg(test)]
+    #[cfg(any(test, feature = "test-utils"))]
     pub(crate) fn set_membership_tag(
         &mut self,
         provider: &impl openmls_traits::OpenMlsProvider,
@@ -278,6 +305,7 @@ impl From<PublicMessage> for PublicMessageIn {
             content: v.content.into(),
             auth: v.auth,
             membership_tag: v.membership_tag,
+            envelope_version: None,
         }
     }
 }
@@ -21,7 +21,7 @@ use crate::{
     key_packages::{test_key_packages::key_package, KeyPackageBundle},
     schedule::psk::{store::ResumptionPskStore, PskSecret},
     tree::{secret_tree::SecretTree, sender_ratchet::SenderRatchetConfiguration},
-    versions::ProtocolVersion,
+    versions::{ProtocolVersion, VersionError},
 };
 
 /// This tests serializing/deserializing PublicMessage
@@ -753,3 +753,77 @@ fn key_package_version(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider
     // Expect a decoding  error
     matches!(err, tls_codec::Error::DecodingError(_));
 }
+
+/// Test divergent protocol versions in GroupInfos
+#[apply(ciphersuites_and_providers)]
+fn group_info_version(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (_framing_parameters, group_alice, alice_signature_keys, _, _, _) =
+        setup_alice_bob_group(ciphersuite, provider);
+
+    let group_info = group_alice
+        .export_group_info(provider, &alice_signature_keys, true)
+        .expect("An unexpected error occurred.");
+
+    // The GroupInfo is for an MLS 1.0 group, but the message claims otherwise.
+    let message = MlsMessageOut {
+        version: ProtocolVersion::Mls10Draft11,
+        body: MlsMessageOutBody::GroupInfo(group_info),
+    };
+
+    let encoded = message
+        .tls_serialize_detached()
+        .expect("An unexpected error occurred.");
+
+    let err = MlsMessageIn::tls_deserialize(&mut encoded.as_slice())
+        .expect_err("Deserialization should have failed.");
+
+    // Expect a decoding error
+    assert!(matches!(err, tls_codec::Error::DecodingError(_)));
+}
+
+/// Test that messages are only extracted for the expected protocol version
+#[apply(ciphersuites_and_providers)]
+fn extract_checked_version(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (key_package, _, _) = key_package(ciphersuite, provider);
+
+    let message = MlsMessageOut {
+        version: ProtocolVersion::Mls10,
+        body: MlsMessageOutBody::KeyPackage(key_package),
+    };
+    let encoded = message
+        .tls_serialize_detached()
+        .expect("An unexpected error occurred.");
+    let message = MlsMessageIn::tls_deserialize(&mut encoded.as_slice())
+        .expect("An unexpected error occurred.");
+    assert_eq!(message.version(), ProtocolVersion::Mls10);
+
+    let err = message
+        .clone()
+        .extract_checked(ProtocolVersion::Mls10Draft11)
+        .expect_err("Extraction should have failed.");
+    assert_eq!(err, VersionError::VersionMismatch);
+
+    assert!(matches!(
+        message
+            .extract_checked(ProtocolVersion::Mls10)
+            .expect("An unexpected error occurred."),
+        MlsMessageInBody::KeyPackage(_)
+    ));
+}
+
+#[cfg(not(feature = "content-debug"))]
+#[test]
+fn redacted_application_message() {
+    let message = ApplicationMessage::new(
+        b"Hello, Bob!".to_vec(),
+        GroupEpoch::from(0),
+        WireFormat::PrivateMessage,
+        Signature::from(vec![]),
+    );
+    let body = FramedContentBody::Application(b"Hello, Bob!".to_vec().into());
+
+    for debug_output in [format!("{message:?}"), format!("{body:?}")] {
+        assert!(!debug_output.contains(&hex::encode(b"Hello, Bob!")));
+        assert!(!debug_output.contains("Hello"));
+    }
+}
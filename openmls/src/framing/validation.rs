@@ -21,9 +21,12 @@
 //!                          ProcessedMessage
 //!
 //! ```
+
 // TODO #106/#151: Update the above diagram
 
-use openmls_traits::{crypto::OpenMlsCrypto, types::Ciphersuite};
+#![deny(clippy::unwrap_used, clippy::expect_used)]
+
+use openmls_traits::{clock::OpenMlsClock, crypto::OpenMlsCrypto, types::Ciphersuite};
 
 use crate::{
     binary_tree::LeafNodeIndex,
@@ -31,10 +34,12 @@ use crate::{
     error::LibraryError,
     extensions::ExternalSendersExtension,
     group::{
+        config::ValidationPolicy,
         core_group::{proposals::QueuedProposal, staged_commit::StagedCommit},
         errors::ValidationError,
     },
-    tree::sender_ratchet::SenderRatchetConfiguration,
+    security_events,
+    tree::{secret_tree::SecretType, sender_ratchet::SenderRatchetConfiguration},
     treesync::TreeSync,
     versions::ProtocolVersion,
 };
@@ -93,7 +98,7 @@ impl DecryptedMessage {
     /// Constructs a [DecryptedMessage] from a [PrivateMessage] by attempting to decrypt it
     /// to a [VerifiableAuthenticatedContent] first.
     pub(crate) fn from_inbound_ciphertext(
-        ciphertext: PrivateMessageIn,
+        ciphertext: &PrivateMessageIn,
         crypto: &impl OpenMlsCrypto,
         group: &mut CoreGroup,
         sender_ratchet_configuration: &SenderRatchetConfiguration,
@@ -106,6 +111,14 @@ impl DecryptedMessage {
             .message_secrets_and_leaves_mut(ciphertext.epoch())
             .map_err(|_| MessageDecryptionError::AeadError)?;
         let sender_data = ciphertext.sender_data(message_secrets, crypto, ciphersuite)?;
+        // Per-sender overrides are applied by the identity of the sender in
+        // the current tree.
+        let identity = group
+            .public_group()
+            .leaf(sender_data.leaf_index)
+            .map(|leaf| leaf.credential().identity().to_vec());
+        let sender_ratchet_configuration =
+            sender_ratchet_configuration.for_sender(sender_data.leaf_index, identity.as_deref());
         let message_secrets = group
             .message_secrets_mut(ciphertext.epoch())
             .map_err(|_| MessageDecryptionError::AeadError)?;
@@ -114,10 +127,64 @@ impl DecryptedMessage {
             crypto,
             message_secrets,
             sender_data.leaf_index,
-            sender_ratchet_configuration,
+            &sender_ratchet_configuration,
+            sender_data,
+        );
+        let evicted_keys = message_secrets.secret_tree_mut().take_evicted_keys();
+        security_events::record_evicted_keys(group.group_id(), ciphertext.epoch(), evicted_keys);
+        let verifiable_content = verifiable_content?;
+        Self::from_verifiable_content(verifiable_content)
+    }
+
+    /// Constructs a [DecryptedMessage] from a [PrivateMessage] of the current
+    /// epoch whose generation is beyond the maximum forward distance of the
+    /// sender's ratchet, with key material derived from a copy of the ratchet.
+    /// Since the ratchet is not changed, the message must only be used to
+    /// authenticate the sender and is not processed any further. Returns the
+    /// message together with the leaf index of the sender, or `None` if the
+    /// generation is not beyond the forward distance.
+    pub(crate) fn from_desynchronized_ciphertext(
+        ciphertext: &PrivateMessageIn,
+        crypto: &impl OpenMlsCrypto,
+        group: &CoreGroup,
+        sender_ratchet_configuration: &SenderRatchetConfiguration,
+    ) -> Result<Option<(LeafNodeIndex, Self)>, ValidationError> {
+        if ciphertext.epoch() != group.context().epoch() {
+            return Ok(None);
+        }
+        let ciphersuite = group.ciphersuite();
+        let message_secrets = group.message_secrets();
+        let sender_data = ciphertext.sender_data(message_secrets, crypto, ciphersuite)?;
+        let sender_index = sender_data.leaf_index;
+        let identity = group
+            .public_group()
+            .leaf(sender_index)
+            .map(|leaf| leaf.credential().identity().to_vec());
+        let sender_ratchet_configuration =
+            sender_ratchet_configuration.for_sender(sender_index, identity.as_deref());
+        let Some((ratchet_key, ratchet_nonce)) = message_secrets
+            .secret_tree()
+            .secret_beyond_forward_distance(
+                ciphersuite,
+                crypto,
+                sender_index,
+                SecretType::from(&ciphertext.content_type()),
+                sender_data.generation,
+                &sender_ratchet_configuration,
+            )
+            .map_err(MessageDecryptionError::from)?
+        else {
+            return Ok(None);
+        };
+        let verifiable_content = ciphertext.decrypt_with_key(
+            crypto,
+            &ratchet_key,
+            ratchet_nonce,
             sender_data,
+            message_secrets.serialized_context(),
         )?;
         Self::from_verifiable_content(verifiable_content)
+            .map(|decrypted_message| Some((sender_index, decrypted_message)))
     }
 
     // Internal constructor function. Does the following checks:
@@ -272,13 +339,21 @@ impl UnverifiedMessage {
         ciphersuite: Ciphersuite,
         crypto: &impl OpenMlsCrypto,
         protocol_version: ProtocolVersion,
+        clock: &impl OpenMlsClock,
+        policy: &ValidationPolicy,
     ) -> Result<(AuthenticatedContent, Credential), ProcessMessageError> {
         let content: AuthenticatedContentIn = self
             .verifiable_content
             .verify(crypto, &self.sender_pk)
             .map_err(|_| ProcessMessageError::InvalidSignature)?;
-        let content =
-            content.validate(ciphersuite, crypto, self.sender_context, protocol_version)?;
+        let content = content.validate(
+            ciphersuite,
+            crypto,
+            self.sender_context,
+            protocol_version,
+            clock,
+            policy,
+        )?;
         Ok((content, self.credential))
     }
 
@@ -389,15 +464,63 @@ pub enum ProcessedMessageContent {
 }
 
 /// Application message received through a [ProcessedMessage].
-#[derive(Debug, PartialEq, Eq)]
+///
+/// The [`Debug`](std::fmt::Debug) output doesn't contain the message unless
+/// the `content-debug` feature is enabled.
+#[derive(PartialEq, Eq)]
+#[cfg_attr(feature = "content-debug", derive(Debug))]
 pub struct ApplicationMessage {
     bytes: Vec<u8>,
+    // The epoch, wire format and signature of the message are kept to report
+    // the message with an `AbuseReport`.
+    epoch: GroupEpoch,
+    wire_format: WireFormat,
+    signature: Signature,
+}
+
+#[cfg(not(feature = "content-debug"))]
+impl std::fmt::Debug for ApplicationMessage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ApplicationMessage")
+            .field("bytes", &"***")
+            .finish()
+    }
 }
 
 impl ApplicationMessage {
     /// Create a new [ApplicationMessage].
-    pub(crate) fn new(bytes: Vec<u8>) -> Self {
-        Self { bytes }
+    pub(crate) fn new(
+        bytes: Vec<u8>,
+        epoch: GroupEpoch,
+        wire_format: WireFormat,
+        signature: Signature,
+    ) -> Self {
+        Self {
+            bytes,
+            epoch,
+            wire_format,
+            signature,
+        }
+    }
+
+    /// Returns the inner bytes.
+    pub(crate) fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Returns the epoch in which the message was sent.
+    pub(crate) fn epoch(&self) -> GroupEpoch {
+        self.epoch
+    }
+
+    /// Returns the wire format of the message.
+    pub(crate) fn wire_format(&self) -> WireFormat {
+        self.wire_format
+    }
+
+    /// Returns the signature of the sender.
+    pub(crate) fn signature(&self) -> &Signature {
+        &self.signature
     }
 
     /// Returns the inner bytes and consumes the [`ApplicationMessage`].
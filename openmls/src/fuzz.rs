@@ -0,0 +1,138 @@
+//! # Fuzzing entry points
+//!
+//! This module is only available with the `fuzz` feature. It exposes the
+//! deserialization and (stateless) validation of the structures OpenMLS
+//! receives from the network, so that they can be plugged into a fuzzer.
+//!
+//! All entry points take the raw bytes produced by the fuzzer and return `true`
+//! if the input was accepted and `false` otherwise. They don't have any side
+//! effects, i.e., they don't access a key store or any other state. The only
+//! requirement is a crypto provider, which is used to verify signatures and
+//! compute hashes. Lifetimes of key packages are validated against the
+//! [`SystemClock`].
+//!
+//! The entry points never panic on their own. Every input that makes an entry
+//! point panic is a bug and should be added to the regression corpus in
+//! `fuzz/regressions/<entry point>/`, which is replayed by the
+//! `fuzz_regressions` test.
+//!
+//! With the `fuzz-assert` feature, which is enabled by the fuzz targets in
+//! `fuzz/`, the entry points for tree structures additionally assert that
+//! accepted inputs are encoded to the same bytes again, i.e., they panic for
+//! inputs that are decoded from a non-canonical encoding. Without the feature,
+//! this check is skipped.
+
+use openmls_traits::{crypto::OpenMlsCrypto, types::Ciphersuite};
+use tls_codec::{Deserialize, Serialize};
+
+use crate::{
+    framing::{MlsMessageIn, MlsMessageInBody},
+    group::{public_group::PublicGroup, GroupId, ProposalStore},
+    key_packages::{KeyPackageIn, SystemClock},
+    messages::{group_info::VerifiableGroupInfo, Welcome},
+    treesync::{treekem::UpdatePathIn, RatchetTreeIn},
+    versions::ProtocolVersion,
+};
+
+/// The ciphersuite used to verify ratchet trees in [`ratchet_tree_in()`].
+pub const RATCHET_TREE_CIPHERSUITE: Ciphersuite =
+    Ciphersuite::MLS_128_DHKEMX25519_AES128GCM_SHA256_Ed25519;
+
+/// Deserialize an [`MlsMessageIn`] and validate its body.
+///
+/// Key packages and group infos are validated as in [`key_package_in()`] and
+/// [`group_info()`]. Other messages can only be validated in the context of a
+/// group and are therefore only deserialized.
+pub fn mls_message_in(crypto: &impl OpenMlsCrypto, data: &[u8]) -> bool {
+    let message = match MlsMessageIn::tls_deserialize_exact(data) {
+        Ok(message) => message,
+        Err(_) => return false,
+    };
+    let version = message.version();
+    match message.extract() {
+        MlsMessageInBody::KeyPackage(key_package) => {
+            key_package.validate(crypto, version, &SystemClock).is_ok()
+        }
+        MlsMessageInBody::GroupInfo(group_info) => validate_group_info(crypto, group_info),
+        MlsMessageInBody::Welcome(_)
+        | MlsMessageInBody::PublicMessage(_)
+        | MlsMessageInBody::PrivateMessage(_) => true,
+    }
+}
+
+/// Deserialize a [`KeyPackageIn`] and validate it for MLS 1.0.
+pub fn key_package_in(crypto: &impl OpenMlsCrypto, data: &[u8]) -> bool {
+    KeyPackageIn::tls_deserialize_exact(data)
+        .map(|key_package| {
+            key_package
+                .validate(crypto, ProtocolVersion::Mls10, &SystemClock)
+                .is_ok()
+        })
+        .unwrap_or(false)
+}
+
+/// Deserialize a [`VerifiableGroupInfo`] and validate it.
+///
+/// The group info is only accepted if it contains a ratchet tree extension.
+/// The ratchet tree is used to verify the signature and the tree hash of the
+/// group info.
+pub fn group_info(crypto: &impl OpenMlsCrypto, data: &[u8]) -> bool {
+    VerifiableGroupInfo::tls_deserialize_exact(data)
+        .map(|group_info| validate_group_info(crypto, group_info))
+        .unwrap_or(false)
+}
+
+/// Deserialize a [`RatchetTreeIn`] and verify it. With the `fuzz-assert`
+/// feature, this panics if the tree isn't encoded to `data` again.
+///
+/// The tree is verified for [`RATCHET_TREE_CIPHERSUITE`] and an empty group
+/// ID.
+pub fn ratchet_tree_in(crypto: &impl OpenMlsCrypto, data: &[u8]) -> bool {
+    RatchetTreeIn::tls_deserialize_exact(data)
+        .map(|ratchet_tree| {
+            assert_round_trip(&ratchet_tree, data);
+            ratchet_tree
+                .into_verified(RATCHET_TREE_CIPHERSUITE, crypto, &GroupId::from_slice(&[]))
+                .is_ok()
+        })
+        .unwrap_or(false)
+}
+
+/// Deserialize the update path of a commit. With the `fuzz-assert` feature,
+/// this panics if the update path isn't encoded to `data` again.
+///
+/// An update path can only be verified in the context of a group and is
+/// therefore only deserialized.
+pub fn update_path_in(data: &[u8]) -> bool {
+    UpdatePathIn::tls_deserialize_exact(data)
+        .map(|update_path| assert_round_trip(&update_path, data))
+        .is_ok()
+}
+
+/// Deserialize a [`Welcome`].
+///
+/// A welcome can only be decrypted with the private key of a key package and is
+/// therefore only deserialized.
+pub fn welcome(data: &[u8]) -> bool {
+    Welcome::tls_deserialize_exact(data).is_ok()
+}
+
+/// Panic if `value` isn't encoded to the bytes it was decoded from.
+#[cfg(feature = "fuzz-assert")]
+fn assert_round_trip(value: &impl Serialize, data: &[u8]) {
+    let encoded = value
+        .tls_serialize_detached()
+        .expect("error encoding a decoded value");
+    assert_eq!(encoded, data, "decoded value is encoded differently");
+}
+
+#[cfg(not(feature = "fuzz-assert"))]
+fn assert_round_trip(_value: &impl Serialize, _data: &[u8]) {}
+
+fn validate_group_info(crypto: &impl OpenMlsCrypto, group_info: VerifiableGroupInfo) -> bool {
+    let ratchet_tree = match group_info.extensions().ratchet_tree() {
+        Some(ratchet_tree_extension) => ratchet_tree_extension.ratchet_tree().clone(),
+        None => return false,
+    };
+    PublicGroup::from_external(crypto, ratchet_tree, group_info, ProposalStore::new()).is_ok()
+}
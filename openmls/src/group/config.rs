@@ -38,3 +38,250 @@ impl Default for CryptoConfig {
         }
     }
 }
+
+/// Whether a violation of a check of a [`ValidationPolicy`] is an error, a
+/// warning, or not checked at all.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ValidationSeverity {
+    /// The violation is an error and the message is rejected, as required by
+    /// the MLS specification.
+    #[default]
+    Error,
+    /// The violation is logged as a warning and the message is accepted.
+    Warning,
+    /// The check is skipped.
+    Ignore,
+}
+
+impl ValidationSeverity {
+    /// Returns `error` if this is [`ValidationSeverity::Error`]. Otherwise,
+    /// the violation is logged if this is [`ValidationSeverity::Warning`] and
+    /// `Ok(())` is returned.
+    pub(crate) fn check<E: std::fmt::Display>(self, error: E) -> Result<(), E> {
+        match self {
+            ValidationSeverity::Error => Err(error),
+            ValidationSeverity::Warning => {
+                log::warn!("Ignoring a failed validation check: {error}");
+                Ok(())
+            }
+            ValidationSeverity::Ignore => Ok(()),
+        }
+    }
+}
+
+/// Defines which validation checks of incoming messages are errors, which are
+/// only logged as warnings and which are skipped, as well as the tolerated
+/// clock skew for lifetimes.
+///
+/// Relaxing checks deviates from the MLS specification and should only be
+/// used temporarily, e.g., while a fleet of clients is migrated to new
+/// capabilities or extensions and older clients would otherwise be rejected.
+/// The following checks can be relaxed:
+///
+/// * The lifetime of the key packages in Add proposals must cover the current
+///   time, as returned by the clock of the provider. The lifetime is extended
+///   by the clock skew tolerance of the policy in both directions.
+/// * The capabilities of new or updated leaf nodes must support the
+///   ciphersuite and version of the group, the required capabilities of the
+///   group and the credential types of all members.
+/// * The extensions of key packages and of new or updated leaf nodes must be
+///   listed in the capabilities of the leaf node.
+/// * Public and private messages must be received in an `MLSMessage` with the
+///   protocol version of the group. Messages that are processed without their
+///   `MLSMessage`, e.g., after deserializing a `PrivateMessageIn` directly,
+///   aren't checked. This check is enforced by both profiles and can only be
+///   relaxed explicitly.
+///
+/// The uniqueness of the signature, encryption and init keys of new or updated
+/// leaf nodes, key packages and update paths among the members and proposals,
+/// as required by RFC 9420, can't be relaxed.
+///
+/// In addition, proposals of external senders can be validated more strictly
+/// than required by RFC 9420 with
+/// [`ValidationPolicy::with_strict_external_senders()`]. This mode is off in
+/// both profiles. When it is on:
+///
+/// * The signature key of the external sender must appear only once in the
+///   `ExternalSendersExtension`, so that the proposal can be attributed to
+///   exactly one configured sender. The sender is available from
+///   [`QueuedProposal::external_sender()`](crate::group::QueuedProposal::external_sender()).
+/// * Proposals of external senders are only committed if the index of the
+///   sender still refers to the same sender in the current
+///   `ExternalSendersExtension`. Proposals with stale indices, e.g., stored
+///   before the extension changed, are rejected.
+///
+/// The default is [`ValidationPolicy::strict()`]. Custom policies are built
+/// from one of the profiles, e.g.,
+/// `ValidationPolicy::strict().with_extensions(ValidationSeverity::Warning)`.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ValidationPolicy {
+    lifetime: ValidationSeverity,
+    capabilities: ValidationSeverity,
+    extensions: ValidationSeverity,
+    #[serde(default)]
+    clock_skew: u64,
+    #[serde(default)]
+    strict_external_senders: bool,
+    #[serde(default)]
+    envelope_version: ValidationSeverity,
+}
+
+impl ValidationPolicy {
+    /// Returns the policy that enforces all checks, as required by the MLS
+    /// specification.
+    pub const fn strict() -> Self {
+        Self {
+            lifetime: ValidationSeverity::Error,
+            capabilities: ValidationSeverity::Error,
+            extensions: ValidationSeverity::Error,
+            clock_skew: 0,
+            strict_external_senders: false,
+            envelope_version: ValidationSeverity::Error,
+        }
+    }
+
+    /// Returns the policy that only logs violations of the lifetime,
+    /// capabilities and extensions checks, e.g., for the migration of a fleet
+    /// of clients. The envelope version is still enforced.
+    pub const fn lenient() -> Self {
+        Self {
+            lifetime: ValidationSeverity::Warning,
+            capabilities: ValidationSeverity::Warning,
+            extensions: ValidationSeverity::Warning,
+            clock_skew: 0,
+            strict_external_senders: false,
+            envelope_version: ValidationSeverity::Error,
+        }
+    }
+
+    /// Sets the severity of the lifetime check.
+    pub const fn with_lifetime(mut self, severity: ValidationSeverity) -> Self {
+        self.lifetime = severity;
+        self
+    }
+
+    /// Sets the severity of the capabilities check.
+    pub const fn with_capabilities(mut self, severity: ValidationSeverity) -> Self {
+        self.capabilities = severity;
+        self
+    }
+
+    /// Sets the severity of the extensions check.
+    pub const fn with_extensions(mut self, severity: ValidationSeverity) -> Self {
+        self.extensions = severity;
+        self
+    }
+
+    /// Sets the severity of the check of the protocol version of the
+    /// `MLSMessage` that public and private messages are received in.
+    pub const fn with_envelope_version(mut self, severity: ValidationSeverity) -> Self {
+        self.envelope_version = severity;
+        self
+    }
+
+    /// Enables or disables the strict validation of external senders.
+    pub const fn with_strict_external_senders(mut self, strict: bool) -> Self {
+        self.strict_external_senders = strict;
+        self
+    }
+
+    /// Sets the tolerance for skewed clocks in seconds. Lifetimes are
+    /// accepted if they cover the current time plus or minus `clock_skew`.
+    pub const fn with_clock_skew(mut self, clock_skew: u64) -> Self {
+        self.clock_skew = clock_skew;
+        self
+    }
+
+    /// Returns the severity of the lifetime check.
+    pub fn lifetime(&self) -> ValidationSeverity {
+        self.lifetime
+    }
+
+    /// Returns the severity of the capabilities check.
+    pub fn capabilities(&self) -> ValidationSeverity {
+        self.capabilities
+    }
+
+    /// Returns the severity of the extensions check.
+    pub fn extensions(&self) -> ValidationSeverity {
+        self.extensions
+    }
+
+    /// Returns the severity of the envelope version check.
+    pub fn envelope_version(&self) -> ValidationSeverity {
+        self.envelope_version
+    }
+
+    /// Returns the tolerance for skewed clocks in seconds.
+    pub fn clock_skew(&self) -> u64 {
+        self.clock_skew
+    }
+
+    /// Returns `true` if external senders are validated strictly.
+    pub fn strict_external_senders(&self) -> bool {
+        self.strict_external_senders
+    }
+}
+
+/// Bounds for incoming commits. Commits that exceed one of the bounds are
+/// rejected with a [`CommitLimitError`](crate::group::errors::CommitLimitError)
+/// before their proposals are validated and applied, so that a malicious
+/// member can't force pathological processing cost on the group.
+///
+/// `None` means that the respective property is not bounded, which is the
+/// default. The serialized size covers the proposals and the update path of
+/// the commit, but not the framing of the message.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CommitLimits {
+    max_proposals: Option<usize>,
+    max_adds: Option<usize>,
+    max_size: Option<usize>,
+    max_path_nodes: Option<usize>,
+}
+
+impl CommitLimits {
+    /// Sets the maximum number of proposals a commit may cover, both by value
+    /// and by reference.
+    pub const fn with_max_proposals(mut self, max_proposals: usize) -> Self {
+        self.max_proposals = Some(max_proposals);
+        self
+    }
+
+    /// Sets the maximum number of Add proposals a commit may cover.
+    pub const fn with_max_adds(mut self, max_adds: usize) -> Self {
+        self.max_adds = Some(max_adds);
+        self
+    }
+
+    /// Sets the maximum serialized size of a commit in bytes.
+    pub const fn with_max_size(mut self, max_size: usize) -> Self {
+        self.max_size = Some(max_size);
+        self
+    }
+
+    /// Sets the maximum number of nodes of the update path of a commit.
+    pub const fn with_max_path_nodes(mut self, max_path_nodes: usize) -> Self {
+        self.max_path_nodes = Some(max_path_nodes);
+        self
+    }
+
+    /// Returns the maximum number of proposals.
+    pub fn max_proposals(&self) -> Option<usize> {
+        self.max_proposals
+    }
+
+    /// Returns the maximum number of Add proposals.
+    pub fn max_adds(&self) -> Option<usize> {
+        self.max_adds
+    }
+
+    /// Returns the maximum serialized size in bytes.
+    pub fn max_size(&self) -> Option<usize> {
+        self.max_size
+    }
+
+    /// Returns the maximum number of update path nodes.
+    pub fn max_path_nodes(&self) -> Option<usize> {
+        self.max_path_nodes
+    }
+}
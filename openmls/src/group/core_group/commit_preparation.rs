@@ -0,0 +1,431 @@
+//! Commits are created in three steps, so that the expensive part, the
+//! encryption of the update path, can be interrupted and resumed:
+//!
+//! 1. [`CoreGroup::prepare_commit()`] validates and applies the proposals and
+//!    derives the new path. All reads from the key store happen here.
+//! 2. [`CommitPreparation::encrypt_path_nodes()`] encrypts the path secrets
+//!    node by node.
+//! 3. [`CommitPreparation::finish()`] signs the commit and computes the new
+//!    epoch secrets, the [`StagedCommit`] and the [`Welcome`].
+//!
+//! None of the steps modify the [`CoreGroup`] or write to the key store.
+
+use openmls_traits::{
+    clock::OpenMlsClock, crypto::OpenMlsCrypto, key_store::OpenMlsKeyStore, signatures::Signer,
+    OpenMlsProvider,
+};
+use tls_codec::Serialize as TlsSerializeTrait;
+
+use super::{
+    create_commit_params::{CommitType, CreateCommitParams},
+    proposals::ProposalQueue,
+    staged_commit::{MemberStagedCommitState, StagedCommit, StagedCommitState},
+    CoreGroup, CreateCommitResult,
+};
+use crate::{
+    ciphersuite::signable::Signable,
+    error::LibraryError,
+    extensions::{Extension, Extensions, RatchetTreeExtension},
+    framing::{mls_auth_content::AuthenticatedContent, FramingParameters, Sender, WireFormat},
+    group::{
+        errors::{CreateCommitError, ProposalQueueError},
+        public_group::diff::{
+            apply_proposals::ApplyProposalsValues,
+            compute_path::{PathComputationResult, PathPreparation},
+            PublicGroupDiff,
+        },
+    },
+    messages::{
+        group_info::GroupInfoTBS,
+        proposals::{PreSharedKeyProposal, Proposal, ProposalOrRef},
+        Commit, Welcome,
+    },
+    schedule::{
+        psk::{load_psks, PskSecret},
+        JoinerSecret, KeySchedule,
+    },
+};
+
+#[cfg(doc)]
+use crate::messages::group_info::GroupInfo;
+
+/// A commit whose proposals have been applied, but whose update path may not
+/// be encrypted yet.
+pub(crate) struct CommitPreparation<'a> {
+    group: &'a CoreGroup,
+    // The framing parameters are owned, so that the AAD doesn't have to
+    // outlive the preparation.
+    aad: Vec<u8>,
+    wire_format: WireFormat,
+    sender: Sender,
+    proposal_queue: ProposalQueue,
+    proposal_reference_list: Vec<ProposalOrRef>,
+    apply_proposals_values: ApplyProposalsValues,
+    diff: PublicGroupDiff<'a>,
+    path: Option<PathPreparation>,
+    psk_secret: PskSecret,
+    // The current time of the provider's clock, which determines the
+    // external keypair the GroupInfo advertises.
+    time: u64,
+}
+
+impl CoreGroup {
+    /// Validate and apply the proposals of the commit described by `params`
+    /// and derive a new path if necessary. See [`CommitPreparation`].
+    pub(crate) fn prepare_commit<'a, KeyStore: OpenMlsKeyStore>(
+        &'a self,
+        mut params: CreateCommitParams<'_>,
+        provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
+        signer: &impl Signer,
+    ) -> Result<CommitPreparation<'a>, CreateCommitError<KeyStore::Error>> {
+        let ciphersuite = self.ciphersuite();
+
+        let sender = match params.commit_type() {
+            CommitType::External => Sender::NewMemberCommit,
+            CommitType::Member => Sender::build_member(self.own_leaf_index()),
+        };
+
+        // The PSKs of the operation are committed with PreSharedKey proposals
+        // by value, so that they are part of the commit and the Welcome.
+        let inline_proposals =
+            params
+                .inline_proposals()
+                .iter()
+                .cloned()
+                .chain(params.psk_ids().iter().map(|psk_id| {
+                    Proposal::PreSharedKey(PreSharedKeyProposal::new(psk_id.clone()))
+                }))
+                .collect::<Vec<_>>();
+
+        // Filter proposals
+        let (proposal_queue, contains_own_updates) = ProposalQueue::filter_proposals(
+            ciphersuite,
+            provider.crypto(),
+            sender.clone(),
+            params.proposal_store(),
+            &inline_proposals,
+            self.own_leaf_index(),
+        )
+        .map_err(|e| match e {
+            ProposalQueueError::LibraryError(e) => e.into(),
+            ProposalQueueError::ProposalNotFound => CreateCommitError::MissingProposal,
+            ProposalQueueError::SenderError(_) => CreateCommitError::WrongProposalSenderType,
+        })?;
+
+        // TODO: #581 Filter proposals by support
+        // 11.2:
+        // Proposals with a non-default proposal type MUST NOT be included in a commit
+        // unless the proposal type is supported by all the members of the group that
+        // will process the Commit (i.e., not including any members being added
+        // or removed by the Commit).
+
+        let proposal_reference_list = proposal_queue.commit_list();
+
+        // Validate the proposals by doing the following checks:
+
+        // ValSem101
+        // ValSem102
+        // ValSem103
+        // ValSem104
+        self.public_group
+            .validate_key_uniqueness(&proposal_queue, None)?;
+        // ValSem105
+        self.public_group.validate_add_proposals(&proposal_queue)?;
+        // ValSem106
+        // ValSem109
+        self.public_group.validate_capabilities(&proposal_queue)?;
+        // ValSem107
+        // ValSem108
+        self.public_group
+            .validate_remove_proposals(&proposal_queue)?;
+        self.public_group
+            .validate_external_proposals(&proposal_queue)?;
+        self.public_group
+            .validate_pre_shared_key_proposals(&proposal_queue, params.psk_ids())?;
+        // Validate update proposals for member commits
+        if let Sender::Member(sender_index) = &sender {
+            // ValSem110
+            // ValSem111
+            // ValSem112
+            self.public_group
+                .validate_update_proposals(&proposal_queue, *sender_index)?;
+        }
+
+        // Make a copy of the public group to apply proposals safely
+        let mut diff = self.public_group.empty_diff();
+
+        // Apply proposals to tree
+        let apply_proposals_values =
+            diff.apply_proposals(&proposal_queue, self.own_leaf_index())?;
+        if apply_proposals_values.self_removed && params.commit_type() != CommitType::External {
+            return Err(CreateCommitError::CannotRemoveSelf);
+        }
+
+        let path = if apply_proposals_values.path_required
+            || contains_own_updates
+            || params.force_self_update()
+        {
+            // Derive the path. This includes updating the provisional group
+            // context by updating the epoch and computing the new tree hash.
+            // The path secrets are encrypted in later steps.
+            Some(diff.prepare_path(
+                provider,
+                self.own_leaf_index(),
+                apply_proposals_values.exclusion_list(),
+                params.commit_type(),
+                signer,
+                params.take_credential_with_key(),
+            )?)
+        } else {
+            // If path is not needed, update the group context
+            diff.update_group_context(provider.crypto())?;
+            None
+        };
+
+        // Prepare the PskSecret. This is the last read from the key store.
+        let psk_secret = {
+            let psks = load_psks(
+                provider.key_store(),
+                &self.resumption_psk_store,
+                &apply_proposals_values.presharedkeys,
+            )?;
+
+            PskSecret::new(provider.crypto(), ciphersuite, psks)?
+        };
+
+        Ok(CommitPreparation {
+            group: self,
+            aad: params.framing_parameters().aad().to_vec(),
+            wire_format: params.framing_parameters().wire_format(),
+            sender,
+            proposal_queue,
+            proposal_reference_list,
+            apply_proposals_values,
+            diff,
+            path,
+            psk_secret,
+            time: provider.clock().now(),
+        })
+    }
+}
+
+impl<'a> CommitPreparation<'a> {
+    /// The number of path nodes that have been encrypted.
+    pub(crate) fn encrypted_path_nodes(&self) -> usize {
+        self.path
+            .as_ref()
+            .map(|path| path.encrypted_nodes())
+            .unwrap_or_default()
+    }
+
+    /// The number of path nodes that have to be encrypted. This is zero if
+    /// the commit doesn't have a path.
+    pub(crate) fn path_nodes(&self) -> usize {
+        self.path
+            .as_ref()
+            .map(|path| path.nodes())
+            .unwrap_or_default()
+    }
+
+    /// Encrypt up to `max_nodes` of the path nodes that have not been
+    /// encrypted yet.
+    pub(crate) fn encrypt_path_nodes(
+        &mut self,
+        crypto: &impl OpenMlsCrypto,
+        max_nodes: usize,
+    ) -> Result<(), LibraryError> {
+        match self.path.as_mut() {
+            Some(path) => path.encrypt_nodes(crypto, max_nodes),
+            None => Ok(()),
+        }
+    }
+
+    /// Sign the commit and compute the new epoch. All path nodes have to be
+    /// encrypted before.
+    ///
+    /// The [`GroupInfo`] is only returned if the group uses the ratchet tree
+    /// extension.
+    pub(crate) fn finish<KeyStoreError>(
+        self,
+        crypto: &impl OpenMlsCrypto,
+        signer: &impl Signer,
+    ) -> Result<CreateCommitResult, CreateCommitError<KeyStoreError>> {
+        let CommitPreparation {
+            group,
+            aad,
+            wire_format,
+            sender,
+            proposal_queue,
+            proposal_reference_list,
+            apply_proposals_values,
+            mut diff,
+            path,
+            psk_secret,
+            time,
+        } = self;
+        let ciphersuite = group.ciphersuite();
+        let own_leaf_index = group.own_leaf_index();
+
+        let path_computation_result = match path {
+            Some(path) => path.finish()?,
+            None => PathComputationResult::default(),
+        };
+
+        // Create commit message
+        let commit = Commit {
+            proposals: proposal_reference_list,
+            path: path_computation_result.encrypted_path,
+        };
+
+        // Build AuthenticatedContent
+        let mut authenticated_content = AuthenticatedContent::commit(
+            FramingParameters::new(&aad, wire_format),
+            sender,
+            commit,
+            group.public_group.group_context(),
+            signer,
+        )?;
+
+        // Update the confirmed transcript hash using the commit we just created.
+        diff.update_confirmed_transcript_hash(crypto, &authenticated_content)?;
+
+        let serialized_provisional_group_context = diff
+            .group_context()
+            .tls_serialize_detached()
+            .map_err(LibraryError::missing_bound_check)?;
+
+        let joiner_secret = JoinerSecret::new(
+            crypto,
+            path_computation_result.commit_secret,
+            group.group_epoch_secrets().init_secret(),
+            &serialized_provisional_group_context,
+        )
+        .map_err(LibraryError::unexpected_crypto_error)?;
+
+        // Create key schedule
+        let mut key_schedule = KeySchedule::init(ciphersuite, crypto, &joiner_secret, psk_secret)?;
+
+        let welcome_secret = key_schedule
+            .welcome(crypto)
+            .map_err(|_| LibraryError::custom("Using the key schedule in the wrong state"))?;
+        key_schedule
+            .add_context(crypto, &serialized_provisional_group_context)
+            .map_err(|_| LibraryError::custom("Using the key schedule in the wrong state"))?;
+        let provisional_epoch_secrets = key_schedule
+            .epoch_secrets(crypto)
+            .map_err(|_| LibraryError::custom("Using the key schedule in the wrong state"))?;
+
+        // Calculate the confirmation tag
+        let confirmation_tag = provisional_epoch_secrets
+            .confirmation_key()
+            .tag(crypto, diff.group_context().confirmed_transcript_hash())
+            .map_err(LibraryError::unexpected_crypto_error)?;
+
+        // Set the confirmation tag
+        authenticated_content.set_confirmation_tag(confirmation_tag.clone());
+
+        diff.update_interim_transcript_hash(ciphersuite, crypto, confirmation_tag.clone())?;
+
+        // only computes the group info if necessary
+        let group_info = if !apply_proposals_values.invitation_list.is_empty()
+            || group.use_ratchet_tree_extension
+        {
+            let mut extensions = group.external_pub_extensions(
+                crypto,
+                provisional_epoch_secrets.external_secret(),
+                time,
+            )?;
+            // Create the ratchet tree extension if necessary
+            if group.use_ratchet_tree_extension {
+                extensions.insert(
+                    0,
+                    Extension::RatchetTree(RatchetTreeExtension::new(diff.export_ratchet_tree())),
+                );
+            }
+            let other_extensions = Extensions::from_vec(extensions)?;
+
+            // Create to-be-signed group info.
+            let group_info_tbs = {
+                GroupInfoTBS::new(
+                    diff.group_context().clone(),
+                    other_extensions,
+                    confirmation_tag,
+                    own_leaf_index,
+                )
+            };
+            // Sign to-be-signed group info.
+            Some(group_info_tbs.sign(signer)?)
+        } else {
+            None
+        };
+
+        // Check if new members were added and, if so, create welcome messages
+        let welcome_option = if !apply_proposals_values.invitation_list.is_empty() {
+            // Encrypt GroupInfo object
+            let (welcome_key, welcome_nonce) = welcome_secret
+                .derive_welcome_key_nonce(crypto)
+                .map_err(LibraryError::unexpected_crypto_error)?;
+            let encrypted_group_info = welcome_key
+                .aead_seal(
+                    crypto,
+                    group_info
+                        .as_ref()
+                        .ok_or_else(|| LibraryError::custom("GroupInfo was not computed"))?
+                        .tls_serialize_detached()
+                        .map_err(LibraryError::missing_bound_check)?
+                        .as_slice(),
+                    &[],
+                    &welcome_nonce,
+                )
+                .map_err(LibraryError::unexpected_crypto_error)?;
+
+            // Create group secrets for later use, so we can afterwards consume the
+            // `joiner_secret`.
+            let encrypted_secrets = diff.encrypt_group_secrets(
+                &joiner_secret,
+                apply_proposals_values.invitation_list,
+                path_computation_result.plain_path.as_deref(),
+                &apply_proposals_values.presharedkeys,
+                &encrypted_group_info,
+                crypto,
+                own_leaf_index,
+            )?;
+
+            // Create welcome message
+            let welcome = Welcome::new(ciphersuite, encrypted_secrets, encrypted_group_info);
+            Some(welcome)
+        } else {
+            None
+        };
+
+        let (provisional_group_epoch_secrets, provisional_message_secrets) =
+            provisional_epoch_secrets.split_secrets(
+                serialized_provisional_group_context,
+                diff.tree_size(),
+                own_leaf_index,
+            );
+
+        let staged_commit_state = MemberStagedCommitState::new(
+            provisional_group_epoch_secrets,
+            provisional_message_secrets,
+            diff.into_staged_diff(crypto, ciphersuite)?,
+            path_computation_result.new_keypairs,
+            // The committer is not allowed to include their own update
+            // proposal, so there is no extra keypair to store here.
+            None,
+        );
+        let staged_commit = StagedCommit::new(
+            proposal_queue,
+            StagedCommitState::GroupMember(Box::new(staged_commit_state)),
+            own_leaf_index,
+        )
+        .with_welcome(welcome_option.clone());
+
+        Ok(CreateCommitResult {
+            commit: authenticated_content,
+            welcome_option,
+            staged_commit,
+            group_info: group_info.filter(|_| group.use_ratchet_tree_extension),
+        })
+    }
+}
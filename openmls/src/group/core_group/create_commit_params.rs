@@ -4,7 +4,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     credentials::CredentialWithKey, framing::FramingParameters, group::ProposalStore,
-    messages::proposals::Proposal,
+    messages::proposals::Proposal, schedule::psk::PreSharedKeyId,
 };
 
 #[cfg(doc)]
@@ -24,6 +24,7 @@ pub(crate) struct CreateCommitParams<'a> {
     force_self_update: bool,                        // Optional
     commit_type: CommitType,                        // Optional (default is `Member`)
     credential_with_key: Option<CredentialWithKey>, // Mandatory for external commits
+    psk_ids: Vec<PreSharedKeyId>,                   // Optional
 }
 
 pub(crate) struct TempBuilderCCPM0 {}
@@ -58,6 +59,7 @@ impl<'a> TempBuilderCCPM1<'a> {
                 force_self_update: true,
                 commit_type: CommitType::Member,
                 credential_with_key: None,
+                psk_ids: vec![],
             },
         }
     }
@@ -81,6 +83,13 @@ impl<'a> CreateCommitParamsBuilder<'a> {
         self.ccp.credential_with_key = Some(credential_with_key);
         self
     }
+    /// PSKs of the operation that creates the commit, e.g., the resumption
+    /// PSK of the parent group when branching a subgroup. They are committed
+    /// with PreSharedKey proposals by value.
+    pub(crate) fn psk_ids(mut self, psk_ids: Vec<PreSharedKeyId>) -> Self {
+        self.ccp.psk_ids = psk_ids;
+        self
+    }
     pub(crate) fn build(self) -> CreateCommitParams<'a> {
         self.ccp
     }
@@ -90,7 +99,7 @@ impl<'a> CreateCommitParams<'a> {
     pub(crate) fn builder() -> TempBuilderCCPM0 {
         TempBuilderCCPM0 {}
     }
-    pub(crate) fn framing_parameters(&self) -> &FramingParameters {
+    pub(crate) fn framing_parameters(&self) -> &FramingParameters<'a> {
         &self.framing_parameters
     }
     pub(crate) fn proposal_store(&self) -> &ProposalStore {
@@ -108,4 +117,7 @@ impl<'a> CreateCommitParams<'a> {
     pub(crate) fn take_credential_with_key(&mut self) -> Option<CredentialWithKey> {
         self.credential_with_key.take()
     }
+    pub(crate) fn psk_ids(&self) -> &[PreSharedKeyId] {
+        &self.psk_ids
+    }
 }
@@ -367,7 +367,8 @@ pub fn generate_test_vector(cipher_suite: Ciphersuite) -> PassiveClientWelcomeTe
             &creator.signature_keypair,
             &[passive.key_package.clone()],
         )
-        .unwrap();
+        .unwrap()
+        .into_parts();
 
     creator_group
         .merge_pending_commit(&creator_provider)
@@ -557,7 +558,8 @@ fn propose_remove(
 fn commit(provider: &OpenMlsRustCrypto, creator: &GroupCandidate, group: &mut MlsGroup) -> Vec<u8> {
     let (mls_message_out_commit, _, _) = group
         .commit_to_pending_proposals(provider, &creator.signature_keypair)
-        .unwrap();
+        .unwrap()
+        .into_parts();
     group.merge_pending_commit(provider).unwrap();
 
     mls_message_out_commit.tls_serialize_detached().unwrap()
@@ -570,7 +572,8 @@ fn update_inline(
 ) -> TestEpoch {
     let (mls_message_out_commit, _, _) = group
         .self_update(provider, &candidate.signature_keypair)
-        .unwrap();
+        .unwrap()
+        .into_parts();
     group.merge_pending_commit(provider).unwrap();
 
     let proposals = vec![];
@@ -5,12 +5,11 @@
 //! This means that some functions that are not expected to fail and throw an
 //! error, will still return a `Result` since they may throw a `LibraryError`.
 
-// Private
-mod new_from_welcome;
-
 // Crate
+pub(crate) mod commit_preparation;
 pub(crate) mod create_commit_params;
 pub(crate) mod new_from_external_init;
+pub(crate) mod new_from_welcome;
 pub(crate) mod past_secrets;
 pub(crate) mod process;
 pub(crate) mod proposals;
@@ -33,14 +32,18 @@ mod test_past_secrets;
 mod test_proposals;
 
 use log::{debug, trace};
-use openmls_traits::{key_store::OpenMlsKeyStore, signatures::Signer, types::Ciphersuite};
+use openmls_traits::{
+    clock::OpenMlsClock,
+    key_store::OpenMlsKeyStore,
+    signatures::Signer,
+    types::{Ciphersuite, HpkeKeyPair},
+};
 use serde::{Deserialize, Serialize};
 use tls_codec::Serialize as TlsSerializeTrait;
 
 use self::{
-    create_commit_params::{CommitType, CreateCommitParams},
-    past_secrets::MessageSecretsStore,
-    staged_commit::{MemberStagedCommitState, StagedCommit, StagedCommitState},
+    create_commit_params::CreateCommitParams, past_secrets::MessageSecretsStore,
+    staged_commit::StagedCommit,
 };
 
 use super::{
@@ -50,7 +53,7 @@ use super::{
         ValidationError,
     },
     group_context::*,
-    public_group::{diff::compute_path::PathComputationResult, PublicGroup},
+    public_group::PublicGroup,
 };
 
 use crate::{
@@ -59,7 +62,10 @@ use crate::{
     credentials::*,
     error::LibraryError,
     framing::{mls_auth_content::AuthenticatedContent, *},
-    group::{config::CryptoConfig, *},
+    group::{
+        config::{CommitLimits, CryptoConfig, ValidationPolicy},
+        *,
+    },
     key_packages::*,
     messages::{
         group_info::{GroupInfo, GroupInfoTBS, VerifiableGroupInfo},
@@ -141,6 +147,11 @@ pub(crate) struct CoreGroup {
     message_secrets_store: MessageSecretsStore,
     // Resumption psk store. This is where the resumption psks are kept in a rollover list.
     pub(crate) resumption_psk_store: ResumptionPskStore,
+    // Policy for the rotation of the external keypair. Copied from the
+    // `MlsGroupConfig`, because all members must agree on the accepted
+    // external keypairs when staging external commits.
+    #[serde(default)]
+    external_key_rotation: ExternalKeyRotationPolicy,
 }
 
 /// Builder for [`CoreGroup`].
@@ -200,6 +211,28 @@ impl CoreGroupBuilder {
         }
         self
     }
+    /// Set the [`ExternalSenderScopesExtension`] of the [`CoreGroup`].
+    pub(crate) fn with_external_sender_scopes(
+        mut self,
+        external_sender_scopes: ExternalSenderScopesExtension,
+    ) -> Self {
+        self.public_group_builder = self
+            .public_group_builder
+            .with_external_sender_scopes(external_sender_scopes);
+        self
+    }
+    /// Set the [`ParentGroupExtension`] of the [`CoreGroup`].
+    pub(crate) fn with_parent_group(mut self, parent_group: ParentGroupExtension) -> Self {
+        self.public_group_builder = self.public_group_builder.with_parent_group(parent_group);
+        self
+    }
+    /// Set additional extensions of the initial [`GroupContext`].
+    pub(crate) fn with_group_context_extensions(mut self, extensions: Extensions) -> Self {
+        self.public_group_builder = self
+            .public_group_builder
+            .with_group_context_extensions(extensions);
+        self
+    }
     /// Set the number of past epochs the group should keep secrets.
     pub fn with_max_past_epoch_secrets(mut self, max_past_epochs: usize) -> Self {
         self.max_past_epochs = max_past_epochs;
@@ -293,6 +326,7 @@ impl CoreGroupBuilder {
             message_secrets_store,
             own_leaf_index: LeafNodeIndex::new(0),
             resumption_psk_store,
+            external_key_rotation: ExternalKeyRotationPolicy::default(),
         };
 
         // Store the private key of the own leaf in the key store as an epoch keypair.
@@ -300,6 +334,9 @@ impl CoreGroupBuilder {
             .store_epoch_keypairs(provider.key_store(), &[leaf_keypair])
             .map_err(CoreGroupBuildError::KeyStoreError)?;
 
+        #[cfg(feature = "check-invariants")]
+        group.check_invariants(provider.crypto(), "build");
+
         Ok(group)
     }
 }
@@ -483,13 +520,18 @@ impl CoreGroup {
         padding_size: usize,
         provider: &impl OpenMlsProvider,
     ) -> Result<PrivateMessage, MessageEncryptionError> {
-        PrivateMessage::try_from_authenticated_content(
+        let private_message = PrivateMessage::try_from_authenticated_content(
             &public_message,
             self.ciphersuite(),
             provider,
             self.message_secrets_store.message_secrets_mut(),
             padding_size,
-        )
+        );
+
+        #[cfg(feature = "check-invariants")]
+        self.check_invariants(provider.crypto(), "encrypt");
+
+        private_message
     }
 
     /// Decrypt an PrivateMessage into an PublicMessage
@@ -543,39 +585,58 @@ impl CoreGroup {
             .map_err(LibraryError::unexpected_crypto_error)?)
     }
 
+    /// Derives the exporter secret of the sub-exporter with the given
+    /// `namespace` from the current epoch.
+    pub(crate) fn sub_exporter_secret(
+        &self,
+        crypto: &impl OpenMlsCrypto,
+        namespace: &str,
+    ) -> Result<ExporterSecret, LibraryError> {
+        self.group_epoch_secrets
+            .exporter_secret()
+            .derive_sub_exporter_secret(self.ciphersuite(), crypto, namespace.as_bytes())
+            .map_err(LibraryError::unexpected_crypto_error)
+    }
+
     pub(crate) fn export_group_info(
+        &self,
+        provider: &impl OpenMlsProvider,
+        signer: &impl Signer,
+        with_ratchet_tree: bool,
+    ) -> Result<GroupInfo, LibraryError> {
+        self.export_group_info_at(
+            provider.crypto(),
+            signer,
+            with_ratchet_tree,
+            provider.clock().now(),
+        )
+    }
+
+    /// Export a group info object for this group that advertises the
+    /// external keypair of the rotation period at `time`.
+    pub(crate) fn export_group_info_at(
         &self,
         crypto: &impl OpenMlsCrypto,
         signer: &impl Signer,
         with_ratchet_tree: bool,
+        time: u64,
     ) -> Result<GroupInfo, LibraryError> {
-        let extensions = {
-            let ratchet_tree_extension = || {
+        let mut extensions = self.external_pub_extensions(
+            crypto,
+            self.group_epoch_secrets().external_secret(),
+            time,
+        )?;
+        if with_ratchet_tree {
+            extensions.insert(
+                0,
                 Extension::RatchetTree(RatchetTreeExtension::new(
                     self.public_group().export_ratchet_tree(),
-                ))
-            };
-
-            let external_pub_extension = || {
-                let external_pub = self
-                    .group_epoch_secrets()
-                    .external_secret()
-                    .derive_external_keypair(crypto, self.ciphersuite())
-                    .public;
-                Extension::ExternalPub(ExternalPubExtension::new(HpkePublicKey::from(external_pub)))
-            };
-
-            if with_ratchet_tree {
-                Extensions::from_vec(vec![ratchet_tree_extension(), external_pub_extension()])
-                    .map_err(|_| {
-                        LibraryError::custom(
-                            "There should not have been duplicate extensions here.",
-                        )
-                    })?
-            } else {
-                Extensions::single(external_pub_extension())
-            }
-        };
+                )),
+            );
+        }
+        let extensions = Extensions::from_vec(extensions).map_err(|_| {
+            LibraryError::custom("There should not have been duplicate extensions here.")
+        })?;
 
         // Create to-be-signed group info.
         let group_info_tbs = GroupInfoTBS::new(
@@ -690,6 +751,12 @@ impl CoreGroup {
         self.message_secrets_store.message_secrets()
     }
 
+    /// Returns the past epochs for which message secrets are stored, oldest
+    /// first.
+    pub(crate) fn past_epochs(&self) -> impl Iterator<Item = GroupEpoch> + '_ {
+        self.message_secrets_store.past_epochs()
+    }
+
     /// Sets the size of the [`MessageSecretsStore`], i.e. the number of past
     /// epochs to keep.
     /// This allows application messages from previous epochs to be decrypted.
@@ -697,6 +764,92 @@ impl CoreGroup {
         self.message_secrets_store.resize(max_past_epochs);
     }
 
+    /// Sets the [`ExternalKeyRotationPolicy`] that determines the external
+    /// keypair advertised in GroupInfos and the external keypairs accepted
+    /// for external commits.
+    pub(crate) fn set_external_key_rotation_policy(&mut self, policy: ExternalKeyRotationPolicy) {
+        self.external_key_rotation = policy;
+    }
+
+    pub(crate) fn set_validation_policy(&mut self, policy: ValidationPolicy) {
+        self.public_group.set_validation_policy(policy);
+    }
+
+    pub(crate) fn set_commit_limits(&mut self, commit_limits: CommitLimits) {
+        self.public_group.set_commit_limits(commit_limits);
+    }
+
+    /// Returns the GroupInfo extensions that advertise the external keypair
+    /// derived from `external_secret` at `time`: the [`ExternalPubExtension`]
+    /// and, if the keypair is rotated, the [`ExternalKeyIdExtension`] that
+    /// identifies it.
+    pub(crate) fn external_pub_extensions(
+        &self,
+        crypto: &impl OpenMlsCrypto,
+        external_secret: &ExternalSecret,
+        time: u64,
+    ) -> Result<Vec<Extension>, LibraryError> {
+        let period = self.external_key_rotation.period(time);
+        let external_pub = self
+            .external_keypair(crypto, external_secret, period)?
+            .public;
+        let mut extensions = vec![Extension::ExternalPub(ExternalPubExtension::new(
+            HpkePublicKey::from(external_pub),
+        ))];
+        if let Some(period) = period {
+            extensions.push(
+                ExternalKeyIdExtension::new(period)
+                    .to_extension()
+                    .map_err(|_| LibraryError::custom("Error encoding the external key ID"))?,
+            );
+        }
+        Ok(extensions)
+    }
+
+    /// Returns the external keypair derived from `external_secret` for the
+    /// rotation `period`, or the keypair defined by the MLS specification if
+    /// `period` is `None`.
+    pub(crate) fn external_keypair(
+        &self,
+        crypto: &impl OpenMlsCrypto,
+        external_secret: &ExternalSecret,
+        period: Option<u64>,
+    ) -> Result<HpkeKeyPair, LibraryError> {
+        match period {
+            Some(period) => external_secret
+                .derive_rotated_external_keypair(crypto, self.ciphersuite(), period)
+                .map_err(LibraryError::unexpected_crypto_error),
+            None => Ok(external_secret.derive_external_keypair(crypto, self.ciphersuite())),
+        }
+    }
+
+    /// Returns the approximate number of bytes used by the message secrets of
+    /// the current epoch.
+    pub(crate) fn epoch_secrets_memory_usage(&self) -> usize {
+        self.message_secrets_store.memory_usage(self.ciphersuite())
+    }
+
+    /// Returns the approximate number of bytes used by the message secrets of
+    /// past epochs.
+    pub(crate) fn past_epoch_secrets_memory_usage(&self) -> usize {
+        self.message_secrets_store
+            .past_epochs_memory_usage(self.ciphersuite())
+    }
+
+    /// Drop the message secrets of the oldest past epochs until the past
+    /// epochs use at most `max_bytes`. Returns the number of dropped epochs.
+    pub(crate) fn evict_past_epoch_secrets(&mut self, max_bytes: usize) -> usize {
+        let ciphersuite = self.ciphersuite();
+        self.message_secrets_store
+            .evict_past_epochs(ciphersuite, max_bytes)
+    }
+
+    /// Drops the message secrets of the past epoch `epoch`. Returns `false`
+    /// if no message secrets are kept for that epoch.
+    pub(crate) fn discard_past_epoch_secrets(&mut self, epoch: GroupEpoch) -> bool {
+        self.message_secrets_store.discard_epoch(epoch)
+    }
+
     /// Get the message secrets. Either from the secrets store or from the group.
     pub(crate) fn message_secrets_mut(
         &mut self,
@@ -788,6 +941,18 @@ impl CoreGroup {
             .unwrap_or_default()
     }
 
+    /// Returns the key store ID of the [`EncryptionKeyPair`]s of this group and
+    /// its current [`GroupEpoch`].
+    #[cfg(feature = "async")]
+    pub(crate) fn epoch_keypairs_id(&self) -> Vec<u8> {
+        EpochKeypairId::new(
+            self.group_id(),
+            self.context().epoch().as_u64(),
+            self.own_leaf_index(),
+        )
+        .0
+    }
+
     /// Delete the [`EncryptionKeyPair`]s from the previous [`GroupEpoch`] from
     /// the `provider`'s key store.
     ///
@@ -804,289 +969,29 @@ impl CoreGroup {
         store.delete::<Vec<EncryptionKeyPair>>(&k.0)
     }
 
+    /// Delete the [`EncryptionKeyPair`]s of the current [`GroupEpoch`] from
+    /// the `provider`'s key store.
+    pub(super) fn delete_epoch_keypairs<KeyStore: OpenMlsKeyStore>(
+        &self,
+        store: &KeyStore,
+    ) -> Result<(), KeyStore::Error> {
+        let k = EpochKeypairId::new(
+            self.group_id(),
+            self.context().epoch().as_u64(),
+            self.own_leaf_index(),
+        );
+        store.delete::<Vec<EncryptionKeyPair>>(&k.0)
+    }
+
     pub(crate) fn create_commit<KeyStore: OpenMlsKeyStore>(
         &self,
-        mut params: CreateCommitParams,
+        params: CreateCommitParams,
         provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
         signer: &impl Signer,
     ) -> Result<CreateCommitResult, CreateCommitError<KeyStore::Error>> {
-        let ciphersuite = self.ciphersuite();
-
-        let sender = match params.commit_type() {
-            CommitType::External => Sender::NewMemberCommit,
-            CommitType::Member => Sender::build_member(self.own_leaf_index()),
-        };
-
-        // Filter proposals
-        let (proposal_queue, contains_own_updates) = ProposalQueue::filter_proposals(
-            ciphersuite,
-            provider.crypto(),
-            sender.clone(),
-            params.proposal_store(),
-            params.inline_proposals(),
-            self.own_leaf_index(),
-        )
-        .map_err(|e| match e {
-            crate::group::errors::ProposalQueueError::LibraryError(e) => e.into(),
-            crate::group::errors::ProposalQueueError::ProposalNotFound => {
-                CreateCommitError::MissingProposal
-            }
-            crate::group::errors::ProposalQueueError::SenderError(_) => {
-                CreateCommitError::WrongProposalSenderType
-            }
-        })?;
-
-        // TODO: #581 Filter proposals by support
-        // 11.2:
-        // Proposals with a non-default proposal type MUST NOT be included in a commit
-        // unless the proposal type is supported by all the members of the group that
-        // will process the Commit (i.e., not including any members being added
-        // or removed by the Commit).
-
-        let proposal_reference_list = proposal_queue.commit_list();
-
-        // Validate the proposals by doing the following checks:
-
-        // ValSem101
-        // ValSem102
-        // ValSem103
-        // ValSem104
-        self.public_group
-            .validate_key_uniqueness(&proposal_queue, None)?;
-        // ValSem105
-        self.public_group.validate_add_proposals(&proposal_queue)?;
-        // ValSem106
-        // ValSem109
-        self.public_group.validate_capabilities(&proposal_queue)?;
-        // ValSem107
-        // ValSem108
-        self.public_group
-            .validate_remove_proposals(&proposal_queue)?;
-        self.public_group
-            .validate_pre_shared_key_proposals(&proposal_queue)?;
-        // Validate update proposals for member commits
-        if let Sender::Member(sender_index) = &sender {
-            // ValSem110
-            // ValSem111
-            // ValSem112
-            self.public_group
-                .validate_update_proposals(&proposal_queue, *sender_index)?;
-        }
-
-        // Make a copy of the public group to apply proposals safely
-        let mut diff = self.public_group.empty_diff();
-
-        // Apply proposals to tree
-        let apply_proposals_values =
-            diff.apply_proposals(&proposal_queue, self.own_leaf_index())?;
-        if apply_proposals_values.self_removed && params.commit_type() != CommitType::External {
-            return Err(CreateCommitError::CannotRemoveSelf);
-        }
-
-        let path_computation_result =
-            // If path is needed, compute path values
-            if apply_proposals_values.path_required
-                || contains_own_updates
-                || params.force_self_update()
-            {
-                // Process the path. This includes updating the provisional
-                // group context by updating the epoch and computing the new
-                // tree hash.
-                diff.compute_path(
-                    provider,
-                    self.own_leaf_index(),
-                    apply_proposals_values.exclusion_list(),
-                    params.commit_type(),
-                    signer,
-                    params.take_credential_with_key()
-                )?
-            } else {
-                // If path is not needed, update the group context and return
-                // empty path processing results
-                diff.update_group_context(provider.crypto())?;
-                PathComputationResult::default()
-            };
-
-        // Create commit message
-        let commit = Commit {
-            proposals: proposal_reference_list,
-            path: path_computation_result.encrypted_path,
-        };
-
-        // Build AuthenticatedContent
-        let mut authenticated_content = AuthenticatedContent::commit(
-            *params.framing_parameters(),
-            sender,
-            commit,
-            self.public_group.group_context(),
-            signer,
-        )?;
-
-        // Update the confirmed transcript hash using the commit we just created.
-        diff.update_confirmed_transcript_hash(provider.crypto(), &authenticated_content)?;
-
-        let serialized_provisional_group_context = diff
-            .group_context()
-            .tls_serialize_detached()
-            .map_err(LibraryError::missing_bound_check)?;
-
-        let joiner_secret = JoinerSecret::new(
-            provider.crypto(),
-            path_computation_result.commit_secret,
-            self.group_epoch_secrets().init_secret(),
-            &serialized_provisional_group_context,
-        )
-        .map_err(LibraryError::unexpected_crypto_error)?;
-
-        // Prepare the PskSecret
-        let psk_secret = {
-            let psks = load_psks(
-                provider.key_store(),
-                &self.resumption_psk_store,
-                &apply_proposals_values.presharedkeys,
-            )?;
-
-            PskSecret::new(provider.crypto(), ciphersuite, psks)?
-        };
-
-        // Create key schedule
-        let mut key_schedule =
-            KeySchedule::init(ciphersuite, provider.crypto(), &joiner_secret, psk_secret)?;
-
-        let serialized_provisional_group_context = diff
-            .group_context()
-            .tls_serialize_detached()
-            .map_err(LibraryError::missing_bound_check)?;
-
-        let welcome_secret = key_schedule
-            .welcome(provider.crypto())
-            .map_err(|_| LibraryError::custom("Using the key schedule in the wrong state"))?;
-        key_schedule
-            .add_context(provider.crypto(), &serialized_provisional_group_context)
-            .map_err(|_| LibraryError::custom("Using the key schedule in the wrong state"))?;
-        let provisional_epoch_secrets = key_schedule
-            .epoch_secrets(provider.crypto())
-            .map_err(|_| LibraryError::custom("Using the key schedule in the wrong state"))?;
-
-        // Calculate the confirmation tag
-        let confirmation_tag = provisional_epoch_secrets
-            .confirmation_key()
-            .tag(
-                provider.crypto(),
-                diff.group_context().confirmed_transcript_hash(),
-            )
-            .map_err(LibraryError::unexpected_crypto_error)?;
-
-        // Set the confirmation tag
-        authenticated_content.set_confirmation_tag(confirmation_tag.clone());
-
-        diff.update_interim_transcript_hash(
-            ciphersuite,
-            provider.crypto(),
-            confirmation_tag.clone(),
-        )?;
-
-        // only computes the group info if necessary
-        let group_info = if !apply_proposals_values.invitation_list.is_empty()
-            || self.use_ratchet_tree_extension
-        {
-            // Create the ratchet tree extension if necessary
-            let external_pub = provisional_epoch_secrets
-                .external_secret()
-                .derive_external_keypair(provider.crypto(), ciphersuite)
-                .public;
-            let external_pub_extension =
-                Extension::ExternalPub(ExternalPubExtension::new(external_pub.into()));
-            let other_extensions: Extensions = if self.use_ratchet_tree_extension {
-                Extensions::from_vec(vec![
-                    Extension::RatchetTree(RatchetTreeExtension::new(diff.export_ratchet_tree())),
-                    external_pub_extension,
-                ])?
-            } else {
-                Extensions::single(external_pub_extension)
-            };
-
-            // Create to-be-signed group info.
-            let group_info_tbs = {
-                GroupInfoTBS::new(
-                    diff.group_context().clone(),
-                    other_extensions,
-                    confirmation_tag,
-                    self.own_leaf_index(),
-                )
-            };
-            // Sign to-be-signed group info.
-            Some(group_info_tbs.sign(signer)?)
-        } else {
-            None
-        };
-
-        // Check if new members were added and, if so, create welcome messages
-        let welcome_option = if !apply_proposals_values.invitation_list.is_empty() {
-            // Encrypt GroupInfo object
-            let (welcome_key, welcome_nonce) = welcome_secret
-                .derive_welcome_key_nonce(provider.crypto())
-                .map_err(LibraryError::unexpected_crypto_error)?;
-            let encrypted_group_info = welcome_key
-                .aead_seal(
-                    provider.crypto(),
-                    group_info
-                        .as_ref()
-                        .ok_or_else(|| LibraryError::custom("GroupInfo was not computed"))?
-                        .tls_serialize_detached()
-                        .map_err(LibraryError::missing_bound_check)?
-                        .as_slice(),
-                    &[],
-                    &welcome_nonce,
-                )
-                .map_err(LibraryError::unexpected_crypto_error)?;
-
-            // Create group secrets for later use, so we can afterwards consume the
-            // `joiner_secret`.
-            let encrypted_secrets = diff.encrypt_group_secrets(
-                &joiner_secret,
-                apply_proposals_values.invitation_list,
-                path_computation_result.plain_path.as_deref(),
-                &apply_proposals_values.presharedkeys,
-                &encrypted_group_info,
-                provider.crypto(),
-                self.own_leaf_index(),
-            )?;
-
-            // Create welcome message
-            let welcome = Welcome::new(self.ciphersuite(), encrypted_secrets, encrypted_group_info);
-            Some(welcome)
-        } else {
-            None
-        };
-
-        let (provisional_group_epoch_secrets, provisional_message_secrets) =
-            provisional_epoch_secrets.split_secrets(
-                serialized_provisional_group_context,
-                diff.tree_size(),
-                self.own_leaf_index(),
-            );
-
-        let staged_commit_state = MemberStagedCommitState::new(
-            provisional_group_epoch_secrets,
-            provisional_message_secrets,
-            diff.into_staged_diff(provider.crypto(), ciphersuite)?,
-            path_computation_result.new_keypairs,
-            // The committer is not allowed to include their own update
-            // proposal, so there is no extra keypair to store here.
-            None,
-        );
-        let staged_commit = StagedCommit::new(
-            proposal_queue,
-            StagedCommitState::GroupMember(Box::new(staged_commit_state)),
-        );
-
-        Ok(CreateCommitResult {
-            commit: authenticated_content,
-            welcome_option,
-            staged_commit,
-            group_info: group_info.filter(|_| self.use_ratchet_tree_extension),
-        })
+        let mut preparation = self.prepare_commit(params, provider, signer)?;
+        preparation.encrypt_path_nodes(provider.crypto(), usize::MAX)?;
+        preparation.finish(provider.crypto(), signer)
     }
 
     #[cfg(test)]
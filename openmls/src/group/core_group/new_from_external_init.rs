@@ -1,3 +1,5 @@
+#![deny(clippy::unwrap_used, clippy::expect_used)]
+
 use crate::{
     binary_tree::array_representation::LeafNodeIndex,
     group::{
@@ -112,6 +114,7 @@ impl CoreGroup {
             own_leaf_index,
             // TODO(#1357)
             resumption_psk_store: ResumptionPskStore::new(32),
+            external_key_rotation: ExternalKeyRotationPolicy::default(),
         };
 
         let params = CreateCommitParams::builder()
@@ -129,6 +132,13 @@ impl CoreGroup {
             "Error creating commit {create_commit_result:?}"
         );
 
+        // The group only has secrets for the new epoch once the commit is
+        // merged, so only the public state can be checked.
+        #[cfg(feature = "check-invariants")]
+        group
+            .public_group()
+            .check_invariants(provider.crypto(), "join_by_external_commit");
+
         Ok((
             group,
             create_commit_result.map_err(|_| ExternalCommitError::CommitError)?,
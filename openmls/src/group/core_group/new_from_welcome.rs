@@ -1,38 +1,107 @@
+#![deny(clippy::unwrap_used, clippy::expect_used)]
+
 use log::debug;
 use openmls_traits::key_store::OpenMlsKeyStore;
 
 use crate::{
     ciphersuite::hash_ref::HashReference,
+    extensions::ParentGroupExtension,
     group::{core_group::*, errors::WelcomeError},
-    schedule::psk::store::ResumptionPskStore,
+    schedule::psk::{store::ResumptionPskStore, Psk, ResumptionPsk, ResumptionPskUsage},
     treesync::{
         errors::{DerivePathError, PublicTreeError},
         node::encryption_keys::EncryptionKeyPair,
     },
 };
 
+/// A [`CoreGroup`] that was staged from a Welcome message, but whose keys are
+/// not yet written to the key store.
+#[derive(Debug)]
+pub(crate) struct StagedCoreWelcome {
+    group: CoreGroup,
+    leaf_keypair: EncryptionKeyPair,
+    group_keypairs: Vec<EncryptionKeyPair>,
+    welcome_sender_index: LeafNodeIndex,
+    group_info_extensions: Extensions,
+}
+
+impl StagedCoreWelcome {
+    /// Returns the staged group. It must not be used before the keys are
+    /// stored with [`StagedCoreWelcome::into_core_group()`].
+    pub(crate) fn group(&self) -> &CoreGroup {
+        &self.group
+    }
+
+    /// Returns the leaf index of the member that created the Welcome.
+    pub(crate) fn welcome_sender_index(&self) -> LeafNodeIndex {
+        self.welcome_sender_index
+    }
+
+    /// Returns the extensions of the GroupInfo in the Welcome.
+    pub(crate) fn group_info_extensions(&self) -> &Extensions {
+        &self.group_info_extensions
+    }
+
+    /// Stores the keys of the epoch, deletes the encryption key of the key
+    /// package from the key store and returns the group.
+    pub(crate) fn into_core_group<KeyStore: OpenMlsKeyStore>(
+        self,
+        provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
+    ) -> Result<CoreGroup, WelcomeError<KeyStore::Error>> {
+        self.group
+            .store_epoch_keypairs(provider.key_store(), self.group_keypairs.as_slice())
+            .map_err(WelcomeError::KeyStoreError)?;
+        self.leaf_keypair
+            .delete_from_key_store(provider.key_store())
+            .map_err(|_| WelcomeError::NoMatchingEncryptionKey)?;
+
+        #[cfg(feature = "check-invariants")]
+        self.group
+            .check_invariants(provider.crypto(), "new_from_welcome");
+
+        Ok(self.group)
+    }
+}
+
 impl CoreGroup {
     // Join a group from a welcome message
+    #[cfg(test)]
     pub fn new_from_welcome<KeyStore: OpenMlsKeyStore>(
         welcome: Welcome,
         ratchet_tree: Option<RatchetTreeIn>,
         key_package_bundle: KeyPackageBundle,
         provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
-        mut resumption_psk_store: ResumptionPskStore,
+        resumption_psk_store: ResumptionPskStore,
     ) -> Result<Self, WelcomeError<KeyStore::Error>> {
+        Self::stage_welcome(
+            welcome,
+            |_| ratchet_tree,
+            key_package_bundle,
+            provider,
+            resumption_psk_store,
+        )?
+        .into_core_group(provider)
+    }
+
+    // Process a welcome message without writing to the key store. If the
+    // group info has no ratchet tree extension, the tree is fetched with
+    // `fetch_ratchet_tree`, which gets the expected tree hash.
+    pub(crate) fn stage_welcome<KeyStore: OpenMlsKeyStore>(
+        welcome: Welcome,
+        fetch_ratchet_tree: impl FnOnce(&[u8]) -> Option<RatchetTreeIn>,
+        key_package_bundle: KeyPackageBundle,
+        provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
+        mut resumption_psk_store: ResumptionPskStore,
+    ) -> Result<StagedCoreWelcome, WelcomeError<KeyStore::Error>> {
         log::debug!("CoreGroup::new_from_welcome_internal");
 
-        // Read the encryption key pair from the key store and delete it there.
-        // TODO #1207: Key store access happens as early as possible so it can
-        // be pulled up later more easily.
+        // Read the encryption key pair from the key store. It is deleted when
+        // the staged group is turned into a group.
         let leaf_keypair = EncryptionKeyPair::read_from_key_store(
             provider,
             key_package_bundle.key_package.leaf_node().encryption_key(),
         )
         .ok_or(WelcomeError::NoMatchingEncryptionKey)?;
-        leaf_keypair
-            .delete_from_key_store(provider.key_store())
-            .map_err(|_| WelcomeError::NoMatchingEncryptionKey)?;
 
         let ciphersuite = welcome.ciphersuite();
 
@@ -95,6 +164,18 @@ impl CoreGroup {
             provider.crypto(),
         )?;
 
+        // Make sure that we support the version of the group and that it is
+        // the version of the key package we were added with.
+        let version = verifiable_group_info.protocol_version();
+        if !version.is_supported() {
+            return Err(WelcomeError::UnsupportedMlsVersion);
+        }
+        if version != key_package_bundle.key_package().protocol_version() {
+            let e = WelcomeError::VersionMismatch;
+            debug!("new_from_welcome {:?}", e);
+            return Err(e);
+        }
+
         // Make sure that we can support the required capabilities in the group info.
         if let Some(required_capabilities) =
             verifiable_group_info.extensions().required_capabilities()
@@ -115,14 +196,14 @@ impl CoreGroup {
 
         // Build the ratchet tree
 
-        // Set nodes either from the extension or from the `nodes_option`.
+        // Set nodes either from the extension or from the fetched tree.
         // If we got a ratchet tree extension in the welcome, we enable it for
         // this group. Note that this is not strictly necessary. But there's
         // currently no other mechanism to enable the extension.
         let (ratchet_tree, enable_ratchet_tree_extension) =
             match verifiable_group_info.extensions().ratchet_tree() {
                 Some(extension) => (extension.ratchet_tree().clone(), true),
-                None => match ratchet_tree {
+                None => match fetch_ratchet_tree(verifiable_group_info.tree_hash()) {
                     Some(ratchet_tree) => (ratchet_tree, false),
                     None => return Err(WelcomeError::MissingRatchetTree),
                 },
@@ -130,15 +211,36 @@ impl CoreGroup {
 
         let welcome_sender_index = verifiable_group_info.signer();
 
-        // Since there is currently only the external pub extension, there is no
-        // group info extension of interest here.
-        let (public_group, _group_info_extensions) = PublicGroup::from_external(
+        let (public_group, group_info) = PublicGroup::from_external(
             provider.crypto(),
             ratchet_tree,
             verifiable_group_info,
             ProposalStore::new(),
         )?;
 
+        // If the group was branched from a parent group, the resumption PSK of
+        // the parent group must have been injected in the first epoch.
+        if let Some(parent_group) = public_group
+            .group_context()
+            .extensions()
+            .custom::<ParentGroupExtension>()
+            .map_err(|_| WelcomeError::InvalidParentGroupLink)?
+        {
+            let branch_psk = Psk::Resumption(ResumptionPsk::new(
+                ResumptionPskUsage::Branch,
+                parent_group.group_id().clone(),
+                parent_group.epoch(),
+            ));
+            if public_group.group_context().epoch() != GroupEpoch::from(1)
+                || !group_secrets
+                    .psks
+                    .iter()
+                    .any(|psk_id| psk_id.psk() == &branch_psk)
+            {
+                return Err(WelcomeError::InvalidParentGroupLink);
+            }
+        }
+
         // Find our own leaf in the tree.
         let own_leaf_index = public_group
             .members()
@@ -176,12 +278,12 @@ impl CoreGroup {
                         WelcomeError::PublicTreeError(PublicTreeError::PublicKeyMismatch)
                     }
                 })?;
-            vec![leaf_keypair]
+            vec![leaf_keypair.clone()]
                 .into_iter()
                 .chain(path_keypairs)
                 .collect()
         } else {
-            vec![leaf_keypair]
+            vec![leaf_keypair.clone()]
         };
 
         let (group_epoch_secrets, message_secrets) = {
@@ -236,12 +338,16 @@ impl CoreGroup {
             use_ratchet_tree_extension: enable_ratchet_tree_extension,
             message_secrets_store,
             resumption_psk_store,
+            external_key_rotation: ExternalKeyRotationPolicy::default(),
         };
-        group
-            .store_epoch_keypairs(provider.key_store(), group_keypairs.as_slice())
-            .map_err(WelcomeError::KeyStoreError)?;
 
-        Ok(group)
+        Ok(StagedCoreWelcome {
+            group,
+            leaf_keypair,
+            group_keypairs,
+            welcome_sender_index,
+            group_info_extensions: group_info.extensions().clone(),
+        })
     }
 
     // Helper functions
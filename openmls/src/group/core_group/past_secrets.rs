@@ -137,6 +137,25 @@ impl MessageSecretsStore {
         None
     }
 
+    /// Remove the message secrets of the past epoch `group_epoch`. The
+    /// secrets are zeroized when they are dropped. Returns `false` if no
+    /// message secrets are stored for that epoch.
+    pub(crate) fn discard_epoch(&mut self, group_epoch: impl Into<GroupEpoch>) -> bool {
+        let epoch = group_epoch.into().as_u64();
+        let trees = self.past_epoch_trees.len();
+        self.past_epoch_trees
+            .retain(|epoch_tree| epoch_tree.epoch != epoch);
+        self.past_epoch_trees.len() != trees
+    }
+
+    /// Returns the past epochs for which message secrets are stored, oldest
+    /// first.
+    pub(crate) fn past_epochs(&self) -> impl Iterator<Item = GroupEpoch> + '_ {
+        self.past_epoch_trees
+            .iter()
+            .map(|epoch_tree| GroupEpoch::from(epoch_tree.epoch))
+    }
+
     /// Return a slice with the [`Member`]s of the `group_epoch`.
     pub(crate) fn leaves_for_epoch(&self, group_epoch: impl Into<GroupEpoch>) -> &[Member] {
         let epoch = group_epoch.into().as_u64();
@@ -171,4 +190,53 @@ impl MessageSecretsStore {
     pub(crate) fn message_secrets(&self) -> &MessageSecrets {
         &self.message_secrets
     }
+
+    /// Returns the approximate number of bytes used by the message secrets of
+    /// the current epoch.
+    pub(crate) fn memory_usage(&self, ciphersuite: Ciphersuite) -> usize {
+        self.message_secrets.memory_usage(ciphersuite)
+    }
+
+    /// Returns the approximate number of bytes used by the message secrets and
+    /// members of past epochs.
+    pub(crate) fn past_epochs_memory_usage(&self, ciphersuite: Ciphersuite) -> usize {
+        self.past_epoch_trees
+            .iter()
+            .map(|epoch_tree| epoch_tree.memory_usage(ciphersuite))
+            .sum()
+    }
+
+    /// Remove the message secrets of the oldest past epochs until the past
+    /// epochs use at most `max_bytes`. Returns the number of removed epochs.
+    pub(crate) fn evict_past_epochs(
+        &mut self,
+        ciphersuite: Ciphersuite,
+        max_bytes: usize,
+    ) -> usize {
+        let mut usage = self.past_epochs_memory_usage(ciphersuite);
+        let mut evicted = 0;
+        while usage > max_bytes {
+            match self.past_epoch_trees.pop_front() {
+                Some(epoch_tree) => usage -= epoch_tree.memory_usage(ciphersuite),
+                None => break,
+            }
+            evicted += 1;
+        }
+        evicted
+    }
+}
+
+impl EpochTree {
+    fn memory_usage(&self, ciphersuite: Ciphersuite) -> usize {
+        let leaves: usize = self
+            .leaves
+            .iter()
+            .map(|member| {
+                member.credential.tls_serialized_len()
+                    + member.encryption_key.len()
+                    + member.signature_key.len()
+            })
+            .sum();
+        self.message_secrets.memory_usage(ciphersuite) + leaves
+    }
 }
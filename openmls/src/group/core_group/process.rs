@@ -1,7 +1,12 @@
+#![deny(clippy::unwrap_used, clippy::expect_used)]
+
 use core_group::proposals::QueuedProposal;
 
 use crate::{
-    framing::mls_content::FramedContentBody,
+    framing::{
+        errors::MessageDecryptionError, mls_content::FramedContentBody,
+        private_message_in::PrivateMessageIn,
+    },
     group::{
         errors::{MergeCommitError, StageCommitError, ValidationError},
         mls_group::errors::ProcessMessageError,
@@ -49,8 +54,13 @@ impl CoreGroup {
         // Checks the following semantic validation:
         //  - ValSem010
         //  - ValSem246 (as part of ValSem010)
-        let (content, credential) =
-            unverified_message.verify(self.ciphersuite(), provider.crypto(), self.version())?;
+        let (content, credential) = unverified_message.verify(
+            self.ciphersuite(),
+            provider.crypto(),
+            self.version(),
+            provider.clock(),
+            self.public_group().validation_policy(),
+        )?;
 
         match content.sender() {
             Sender::Member(_) | Sender::NewMemberCommit | Sender::NewMemberProposal => {
@@ -61,6 +71,9 @@ impl CoreGroup {
                     FramedContentBody::Application(application_message) => {
                         ProcessedMessageContent::ApplicationMessage(ApplicationMessage::new(
                             application_message.as_slice().to_owned(),
+                            content.epoch(),
+                            content.wire_format(),
+                            content.signature().clone(),
                         ))
                     }
                     FramedContentBody::Proposal(_) => {
@@ -103,13 +116,18 @@ impl CoreGroup {
                     FramedContentBody::Application(_) => {
                         Err(ProcessMessageError::UnauthorizedExternalApplicationMessage)
                     }
-                    FramedContentBody::Proposal(Proposal::Remove(_)) => {
+                    FramedContentBody::Proposal(Proposal::Remove(remove_proposal)) => {
+                        self.public_group()
+                            .validate_external_sender_scope(&sender, remove_proposal.removed())?;
                         let content = ProcessedMessageContent::ProposalMessage(Box::new(
                             QueuedProposal::from_authenticated_content_by_ref(
                                 self.ciphersuite(),
                                 provider.crypto(),
                                 content,
-                            )?,
+                            )?
+                            .with_external_sender(
+                                self.public_group().external_sender(&sender).cloned(),
+                            ),
                         ));
                         Ok(ProcessedMessage::new(
                             self.group_id().clone(),
@@ -124,7 +142,10 @@ impl CoreGroup {
                     FramedContentBody::Proposal(_) => {
                         Err(ProcessMessageError::UnsupportedProposalType)
                     }
-                    FramedContentBody::Commit(_) => unimplemented!(),
+                    // Rejected by ValSem112 when validating the content.
+                    FramedContentBody::Commit(_) => Err(ProcessMessageError::ValidationError(
+                        ValidationError::InvalidSenderType,
+                    )),
                 }
             }
         }
@@ -182,7 +203,7 @@ impl CoreGroup {
         //  - ValSem006
         //  - ValSem007 MembershipTag presence
         let decrypted_message =
-            self.decrypt_message(provider.crypto(), message, sender_ratchet_configuration)?;
+            self.decrypt_message(provider, message, sender_ratchet_configuration)?;
 
         let unverified_message = self
             .public_group
@@ -218,7 +239,7 @@ impl CoreGroup {
     ///  - ValSem007 MembershipTag presence
     pub(crate) fn decrypt_message(
         &mut self,
-        crypto: &impl OpenMlsCrypto,
+        provider: &impl OpenMlsProvider,
         message: ProtocolMessage,
         sender_ratchet_configuration: &SenderRatchetConfiguration,
     ) -> Result<DecryptedMessage, ValidationError> {
@@ -232,7 +253,7 @@ impl CoreGroup {
         // Checks the following semantic validation:
         //  - ValSem006
         //  - ValSem007 MembershipTag presence
-        match message {
+        let decrypted_message = match message {
             ProtocolMessage::PublicMessage(public_message) => {
                 // If the message is older than the current epoch, we need to fetch the correct secret tree first.
                 let message_secrets =
@@ -247,19 +268,75 @@ impl CoreGroup {
                     public_message,
                     message_secrets,
                     message_secrets.serialized_context().to_vec(),
-                    crypto,
+                    provider.crypto(),
                 )
             }
             ProtocolMessage::PrivateMessage(ciphertext) => {
                 // If the message is older than the current epoch, we need to fetch the correct secret tree first
-                DecryptedMessage::from_inbound_ciphertext(
-                    ciphertext,
-                    crypto,
+                let decrypted_message = DecryptedMessage::from_inbound_ciphertext(
+                    &ciphertext,
+                    provider.crypto(),
                     self,
                     sender_ratchet_configuration,
-                )
+                );
+                if let Err(ValidationError::UnableToDecrypt(
+                    MessageDecryptionError::GenerationOutOfBound,
+                )) = decrypted_message
+                {
+                    if let Err(e) = self.flag_desynchronized_sender(
+                        provider,
+                        &ciphertext,
+                        sender_ratchet_configuration,
+                    ) {
+                        log::debug!("Could not authenticate a desynchronized sender: {e:?}");
+                    }
+                }
+                decrypted_message
             }
-        }
+        };
+
+        #[cfg(feature = "check-invariants")]
+        self.check_invariants(provider.crypto(), "decrypt_message");
+
+        decrypted_message
+    }
+
+    /// Marks the sender of `ciphertext` as desynchronized if its generation is
+    /// beyond the maximum forward distance of the sender's ratchet and the
+    /// message is authentic, i.e., it decrypts with the key material derived
+    /// from a copy of the ratchet and its signature is valid. The message is
+    /// rejected either way, so that the sender can't be marked by a forged
+    /// generation in the sender data, which any member can encrypt.
+    fn flag_desynchronized_sender(
+        &mut self,
+        provider: &impl OpenMlsProvider,
+        ciphertext: &PrivateMessageIn,
+        sender_ratchet_configuration: &SenderRatchetConfiguration,
+    ) -> Result<(), ProcessMessageError> {
+        let Some((sender_index, decrypted_message)) =
+            DecryptedMessage::from_desynchronized_ciphertext(
+                ciphertext,
+                provider.crypto(),
+                self,
+                sender_ratchet_configuration,
+            )?
+        else {
+            return Ok(());
+        };
+        self.public_group
+            .parse_message(decrypted_message, &self.message_secrets_store)?
+            .verify(
+                self.ciphersuite(),
+                provider.crypto(),
+                self.version(),
+                provider.clock(),
+                self.public_group().validation_policy(),
+            )?;
+        self.message_secrets_store
+            .message_secrets_mut()
+            .secret_tree_mut()
+            .flag_desynchronized_sender(sender_index);
+        Ok(())
     }
 
     /// Helper function to read decryption keypairs.
@@ -304,6 +381,10 @@ impl CoreGroup {
         }
         // Empty the proposal store
         proposal_store.empty();
+
+        #[cfg(feature = "check-invariants")]
+        self.check_invariants(provider.crypto(), "merge_staged_commit");
+
         Ok(())
     }
 }
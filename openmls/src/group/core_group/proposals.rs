@@ -1,13 +1,17 @@
+#![deny(clippy::unwrap_used, clippy::expect_used)]
+
 use std::collections::{hash_map::Entry, HashMap, HashSet};
 
 use openmls_traits::crypto::OpenMlsCrypto;
 use openmls_traits::types::Ciphersuite;
 use serde::{Deserialize, Serialize};
+use tls_codec::Size;
 
 use crate::{
     binary_tree::array_representation::LeafNodeIndex,
     ciphersuite::hash_ref::ProposalRef,
     error::LibraryError,
+    extensions::ExternalSender,
     framing::{
         mls_auth_content::AuthenticatedContent, mls_content::FramedContentBody, Sender, SenderError,
     },
@@ -63,6 +67,14 @@ impl ProposalStore {
         self.queued_proposals.remove(index);
         Some(())
     }
+
+    /// Returns the approximate number of bytes used by the stored proposals.
+    pub(crate) fn memory_usage(&self) -> usize {
+        self.queued_proposals
+            .iter()
+            .map(QueuedProposal::memory_usage)
+            .sum()
+    }
 }
 
 /// Alternative representation of a Proposal, where the sender is extracted from
@@ -73,9 +85,20 @@ pub struct QueuedProposal {
     proposal_reference: ProposalRef,
     sender: Sender,
     proposal_or_ref_type: ProposalOrRefType,
+    // The configured sender that signed the proposal, if the sender is
+    // external.
+    #[serde(default)]
+    external_sender: Option<ExternalSender>,
 }
 
 impl QueuedProposal {
+    /// Returns the approximate number of bytes used by the proposal.
+    pub(crate) fn memory_usage(&self) -> usize {
+        self.proposal.tls_serialized_len()
+            + self.proposal_reference.tls_serialized_len()
+            + self.sender.tls_serialized_len()
+    }
+
     /// Creates a new [QueuedProposal] from an [PublicMessage]
     pub(crate) fn from_authenticated_content_by_ref(
         ciphersuite: Ciphersuite,
@@ -124,6 +147,7 @@ impl QueuedProposal {
             proposal_reference,
             sender: public_message.sender().clone(),
             proposal_or_ref_type,
+            external_sender: None,
         })
     }
 
@@ -144,9 +168,16 @@ impl QueuedProposal {
             proposal_reference,
             sender: sender.clone(),
             proposal_or_ref_type: ProposalOrRefType::Proposal,
+            external_sender: None,
         })
     }
 
+    /// Records the configured `external_sender` that signed the proposal.
+    pub(crate) fn with_external_sender(mut self, external_sender: Option<ExternalSender>) -> Self {
+        self.external_sender = external_sender;
+        self
+    }
+
     /// Returns the `Proposal` as a reference
     pub fn proposal(&self) -> &Proposal {
         &self.proposal
@@ -163,6 +194,11 @@ impl QueuedProposal {
     pub fn sender(&self) -> &Sender {
         &self.sender
     }
+    /// Returns the entry of the `ExternalSendersExtension` that signed the
+    /// proposal if the sender is external.
+    pub fn external_sender(&self) -> Option<&ExternalSender> {
+        self.external_sender.as_ref()
+    }
 }
 
 /// Helper struct to collect proposals such that they are unique and can be read
@@ -510,7 +546,8 @@ impl ProposalQueue {
                     // TODO: Validate proposal?
                     proposal_pool.insert(queued_proposal.proposal_reference(), queued_proposal);
                 }
-                Proposal::AppAck(_) => unimplemented!("See #291"),
+                // AppAck proposals are not supported yet (#291) and are dropped.
+                Proposal::AppAck(_) => {}
             }
         }
         // Check for presence of Removes and delete Updates
@@ -1,10 +1,14 @@
+#![deny(clippy::unwrap_used, clippy::expect_used)]
+
 use core::fmt::Debug;
 use std::mem;
 
-use openmls_traits::key_store::OpenMlsKeyStore;
+use openmls_traits::{key_store::OpenMlsKeyStore, types::HpkePrivateKey};
 use public_group::diff::{apply_proposals::ApplyProposalsValues, StagedPublicGroupDiff};
+use tls_codec::Deserialize as TlsDeserializeTrait;
 
 use super::{super::errors::*, proposals::ProposalStore, *};
+
 use crate::{
     framing::mls_auth_content::AuthenticatedContent,
     treesync::node::encryption_keys::EncryptionKeyPair,
@@ -14,26 +18,24 @@ impl CoreGroup {
     fn derive_epoch_secrets(
         &self,
         provider: &impl OpenMlsProvider,
-        apply_proposals_values: ApplyProposalsValues,
+        apply_proposals_values: &ApplyProposalsValues,
         epoch_secrets: &GroupEpochSecrets,
         commit_secret: CommitSecret,
+        external_priv: Option<&HpkePrivateKey>,
         serialized_provisional_group_context: &[u8],
     ) -> Result<EpochSecrets, StageCommitError> {
         // Check if we need to include the init secret from an external commit
         // we applied earlier or if we use the one from the previous epoch.
-        let joiner_secret = if let Some(ref external_init_proposal) =
-            apply_proposals_values.external_init_proposal_option
-        {
+        let joiner_secret = if let (Some(external_init_proposal), Some(external_priv)) = (
+            &apply_proposals_values.external_init_proposal_option,
+            external_priv,
+        ) {
             // Decrypt the content and derive the external init secret.
-            let external_priv = epoch_secrets
-                .external_secret()
-                .derive_external_keypair(provider.crypto(), self.ciphersuite())
-                .private;
             let init_secret = InitSecret::from_kem_output(
                 provider.crypto(),
                 self.ciphersuite(),
                 self.version(),
-                &external_priv,
+                external_priv,
                 external_init_proposal.kem_output(),
             )?;
             JoinerSecret::new(
@@ -80,6 +82,24 @@ impl CoreGroup {
             .map_err(|_| LibraryError::custom("Using the key schedule in the wrong state"))?)
     }
 
+    /// Returns the private key of the external keypair that the external
+    /// commit in `mls_content` is encrypted to. The keypair is identified by
+    /// the [`ExternalKeyIdExtension`] in the authenticated data of the commit
+    /// and is the keypair defined by the MLS specification if there is none.
+    fn external_private_key(
+        &self,
+        crypto: &impl OpenMlsCrypto,
+        mls_content: &AuthenticatedContent,
+    ) -> Result<HpkePrivateKey, LibraryError> {
+        let period = Extensions::tls_deserialize_exact(mls_content.authenticated_data())
+            .ok()
+            .and_then(|extensions| extensions.custom::<ExternalKeyIdExtension>().ok().flatten())
+            .map(|external_key_id| external_key_id.period());
+        Ok(self
+            .external_keypair(crypto, self.group_epoch_secrets().external_secret(), period)?
+            .private)
+    }
+
     /// Stages a commit message that was sent by another group member.
     /// This function does the following:
     ///  - Applies the proposals covered by the commit to the tree
@@ -149,6 +169,7 @@ impl CoreGroup {
             return Ok(StagedCommit::new(
                 proposal_queue,
                 StagedCommitState::PublicState(Box::new(staged_diff)),
+                sender_index,
             ));
         }
 
@@ -226,12 +247,24 @@ impl CoreGroup {
             .tls_serialize_detached()
             .map_err(LibraryError::missing_bound_check)?;
 
+        // External commits identify the external keypair they are encrypted
+        // to, so all members derive the same epoch secrets.
+        let external_priv = if apply_proposals_values
+            .external_init_proposal_option
+            .is_some()
+        {
+            Some(self.external_private_key(provider.crypto(), mls_content)?)
+        } else {
+            None
+        };
+
         let (provisional_group_secrets, provisional_message_secrets) = self
             .derive_epoch_secrets(
                 provider,
-                apply_proposals_values,
+                &apply_proposals_values,
                 self.group_epoch_secrets(),
                 commit_secret,
+                external_priv.as_ref(),
                 &serialized_provisional_group_context,
             )?
             .split_secrets(
@@ -271,7 +304,11 @@ impl CoreGroup {
                 new_leaf_keypair_option,
             )));
 
-        Ok(StagedCommit::new(proposal_queue, staged_commit_state))
+        Ok(StagedCommit::new(
+            proposal_queue,
+            staged_commit_state,
+            sender_index,
+        ))
     }
 
     /// Merges a [StagedCommit] into the group state and optionally return a [`SecretTree`]
@@ -385,18 +422,40 @@ pub(crate) enum StagedCommitState {
 pub struct StagedCommit {
     staged_proposal_queue: ProposalQueue,
     state: StagedCommitState,
+    committer: LeafNodeIndex,
+    // The Welcome message of an own commit that adds members.
+    #[serde(default)]
+    welcome: Option<Welcome>,
 }
 
 impl StagedCommit {
     /// Create a new [`StagedCommit`] from the provisional group state created
     /// during the commit process.
-    pub(crate) fn new(staged_proposal_queue: ProposalQueue, state: StagedCommitState) -> Self {
+    pub(crate) fn new(
+        staged_proposal_queue: ProposalQueue,
+        state: StagedCommitState,
+        committer: LeafNodeIndex,
+    ) -> Self {
         StagedCommit {
             staged_proposal_queue,
             state,
+            committer,
+            welcome: None,
         }
     }
 
+    /// Sets the Welcome message of an own commit.
+    pub(crate) fn with_welcome(mut self, welcome: Option<Welcome>) -> Self {
+        self.welcome = welcome;
+        self
+    }
+
+    /// Returns the Welcome message if this is an own commit that adds
+    /// members.
+    pub(crate) fn welcome(&self) -> Option<&Welcome> {
+        self.welcome.as_ref()
+    }
+
     /// Returns the Add proposals that are covered by the Commit message as in iterator over [QueuedAddProposal].
     pub fn add_proposals(&self) -> impl Iterator<Item = QueuedAddProposal> {
         self.staged_proposal_queue.add_proposals()
@@ -422,6 +481,12 @@ impl StagedCommit {
         self.staged_proposal_queue.queued_proposals()
     }
 
+    /// Returns the leaf index of the member that created the Commit message.
+    /// For an external commit, this is the leaf index of the new member.
+    pub fn committer(&self) -> LeafNodeIndex {
+        self.committer
+    }
+
     /// Returns `true` if the member was removed through a proposal covered by this Commit message
     /// and `false` otherwise.
     pub fn self_removed(&self) -> bool {
@@ -436,6 +501,14 @@ impl StagedCommit {
         }
     }
 
+    /// Returns the [`StagedPublicGroupDiff`] of the staged commit state.
+    pub(crate) fn staged_diff(&self) -> &StagedPublicGroupDiff {
+        match self.state {
+            StagedCommitState::PublicState(ref ps) => ps,
+            StagedCommitState::GroupMember(ref gm) => &gm.staged_diff,
+        }
+    }
+
     /// Consume this [`StagedCommit`] and return the internal [`StagedCommitState`].
     pub(crate) fn into_state(self) -> StagedCommitState {
         self.state
@@ -34,7 +34,7 @@ fn test_external_init(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider)
 
     // Have Alice export everything that Charly needs.
     let verifiable_group_info = group_alice
-        .export_group_info(provider.crypto(), &alice_signer, true)
+        .export_group_info(provider, &alice_signer, true)
         .unwrap()
         .into_verifiable_group_info();
 
@@ -111,7 +111,7 @@ fn test_external_init(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider)
 
     // Have Alice export everything that Bob needs.
     let verifiable_group_info = group_alice
-        .export_group_info(provider.crypto(), &alice_signer, false)
+        .export_group_info(provider, &alice_signer, false)
         .unwrap()
         .into_verifiable_group_info();
     let ratchet_tree = group_alice.public_group().export_ratchet_tree();
@@ -198,7 +198,7 @@ fn test_external_init_single_member_group(
 
     // Have Alice export everything that Charly needs.
     let verifiable_group_info = group_alice
-        .export_group_info(provider.crypto(), &alice_signer, false)
+        .export_group_info(provider, &alice_signer, false)
         .unwrap()
         .into_verifiable_group_info();
     let ratchet_tree = group_alice.public_group().export_ratchet_tree();
@@ -259,7 +259,7 @@ fn test_external_init_broken_signature(ciphersuite: Ciphersuite, provider: &impl
 
     let verifiable_group_info = {
         let mut verifiable_group_info = group_alice
-            .export_group_info(provider.crypto(), &alice_signer, true)
+            .export_group_info(provider, &alice_signer, true)
             .unwrap()
             .into_verifiable_group_info();
         verifiable_group_info.break_signature();
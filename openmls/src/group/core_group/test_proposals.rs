@@ -1,5 +1,6 @@
 use openmls_rust_crypto::OpenMlsRustCrypto;
 use openmls_traits::{types::Ciphersuite, OpenMlsProvider};
+use tls_codec::Deserialize;
 
 use super::CoreGroup;
 use crate::{
@@ -19,7 +20,7 @@ use crate::{
         CreateCommitParams, GroupContext, GroupId,
     },
     key_packages::{KeyPackageBundle, KeyPackageIn},
-    messages::proposals::{AddProposal, Proposal, ProposalOrRef, ProposalType},
+    messages::proposals::{AddProposal, AppAckProposal, Proposal, ProposalOrRef, ProposalType},
     schedule::psk::store::ResumptionPskStore,
     test_utils::*,
     treesync::errors::LeafNodeValidationError,
@@ -45,7 +46,7 @@ fn proposal_queue_functions(ciphersuite: Ciphersuite, provider: &impl OpenMlsPro
     let alice_update_key_package = alice_update_key_package_bundle.key_package();
     let kpi = KeyPackageIn::from(alice_update_key_package.clone());
     assert!(kpi
-        .validate(provider.crypto(), ProtocolVersion::Mls10)
+        .validate(provider.crypto(), ProtocolVersion::Mls10, provider.clock())
         .is_ok());
 
     let group_context = GroupContext::new(
@@ -189,7 +190,7 @@ fn proposal_queue_order(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvide
     let alice_update_key_package = alice_update_key_package_bundle.key_package();
     let kpi = KeyPackageIn::from(alice_update_key_package.clone());
     assert!(kpi
-        .validate(provider.crypto(), ProtocolVersion::Mls10)
+        .validate(provider.crypto(), ProtocolVersion::Mls10, provider.clock())
         .is_ok());
 
     let group_context = GroupContext::new(
@@ -677,3 +678,47 @@ fn test_group_context_extension_proposal(
             .expect("Error exporting secret.")
     )
 }
+
+/// AppAck proposals are not supported and must not stop members from
+/// committing to the other proposals.
+#[apply(ciphersuites_and_providers)]
+fn filter_app_ack_proposals(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let framing_parameters = FramingParameters::new(&[], WireFormat::PublicMessage);
+    let (_alice_credential, _alice_key_package_bundle, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let group_context = GroupContext::new(
+        ciphersuite,
+        GroupId::random(provider.rand()),
+        0,
+        vec![],
+        vec![],
+        Extensions::empty(),
+    );
+
+    // An AppAck proposal without any message ranges.
+    let app_ack = AppAckProposal::tls_deserialize_exact([0u8]).expect("error decoding AppAck");
+    let app_ack = AuthenticatedContent::member_proposal(
+        framing_parameters,
+        LeafNodeIndex::new(1),
+        Proposal::AppAck(app_ack),
+        &group_context,
+        &alice_signer,
+    )
+    .unwrap();
+    let proposal_store = ProposalStore::from_queued_proposal(
+        QueuedProposal::from_authenticated_content_by_ref(ciphersuite, provider.crypto(), app_ack)
+            .expect("Could not create QueuedProposal."),
+    );
+
+    let (proposal_queue, own_update) = ProposalQueue::filter_proposals(
+        ciphersuite,
+        provider.crypto(),
+        Sender::build_member(LeafNodeIndex::new(0)),
+        &proposal_store,
+        &[],
+        LeafNodeIndex::new(0),
+    )
+    .expect("Could not create ProposalQueue.");
+    assert!(!own_update);
+    assert_eq!(proposal_queue.queued_proposals().count(), 0);
+}
@@ -7,6 +7,7 @@ use thiserror::Error;
 pub use super::mls_group::errors::*;
 use super::public_group::errors::{CreationFromExternalError, PublicGroupBuildError};
 use crate::{
+    binary_tree::LeafNodeIndex,
     ciphersuite::signable::SignatureError,
     error::LibraryError,
     extensions::errors::{ExtensionError, InvalidExtensionError},
@@ -33,6 +34,9 @@ pub enum WelcomeError<KeyStoreError> {
     /// Ciphersuites in Welcome and key package bundle don't match.
     #[error("Ciphersuites in Welcome and key package bundle don't match.")]
     CiphersuiteMismatch,
+    /// Protocol versions in the Welcome's group info and key package bundle don't match.
+    #[error("Protocol versions in the Welcome's group info and key package bundle don't match.")]
+    VersionMismatch,
     /// See [`GroupInfoError`] for more details.
     #[error(transparent)]
     GroupInfo(#[from] GroupInfoError),
@@ -88,6 +92,35 @@ pub enum WelcomeError<KeyStoreError> {
     /// This error indicates the leaf node is invalid. See [`LeafNodeValidationError`] for more details.
     #[error(transparent)]
     LeafNodeValidation(#[from] LeafNodeValidationError),
+    /// The group claims to be a subgroup of a parent group, but the resumption
+    /// PSK of the parent group wasn't used.
+    #[error(
+        "The group claims to be a subgroup of a parent group, but the resumption PSK of the parent group wasn't used."
+    )]
+    InvalidParentGroupLink,
+    /// The group doesn't have the expected group ID.
+    #[error("The group doesn't have the expected group ID.")]
+    UnexpectedGroupId,
+    /// The group doesn't have the expected ciphersuite.
+    #[error("The group doesn't have the expected ciphersuite.")]
+    UnexpectedCiphersuite,
+    /// The Welcome message wasn't created by the expected member.
+    #[error("The Welcome message wasn't created by the expected member.")]
+    UnexpectedInviter,
+    /// The group has more members than expected.
+    #[error("The group has more members than expected.")]
+    TooManyMembers,
+    /// The key package that the Welcome message is for was already used to
+    /// join a group.
+    #[error("The key package that the Welcome message is for was already used to join a group.")]
+    KeyPackageConsumed,
+    /// The group ID is rejected by the group ID policy of the configuration.
+    #[error("The group ID is rejected by the group ID policy of the configuration.")]
+    InvalidGroupId,
+    /// The ciphersuite or protocol version of the group is rejected by the
+    /// downgrade policy of the configuration.
+    #[error("The ciphersuite or protocol version of the group is rejected by the downgrade policy of the configuration.")]
+    DowngradeAttempt,
 }
 
 /// External Commit error
@@ -121,6 +154,13 @@ pub enum ExternalCommitError {
     /// Credential is missing from external commit.
     #[error("Credential is missing from external commit.")]
     MissingCredential,
+    /// The group ID is rejected by the group ID policy of the configuration.
+    #[error("The group ID is rejected by the group ID policy of the configuration.")]
+    InvalidGroupId,
+    /// The ciphersuite or protocol version of the group is rejected by the
+    /// downgrade policy of the configuration.
+    #[error("The ciphersuite or protocol version of the group is rejected by the downgrade policy of the configuration.")]
+    DowngradeAttempt,
 }
 
 /// Stage Commit error
@@ -189,6 +229,14 @@ pub enum StageCommitError {
     /// See [`UpdatePathError`] for more details.
     #[error(transparent)]
     VerifiedUpdatePathError(#[from] UpdatePathError),
+    /// The external commit is encrypted to an external keypair that was retired by the rotation policy.
+    #[error(
+        "The external commit is encrypted to an external keypair that was retired by the rotation policy."
+    )]
+    RetiredExternalKey,
+    /// See [`CommitLimitError`] for more details.
+    #[error(transparent)]
+    CommitLimitError(#[from] CommitLimitError),
 }
 
 /// Create commit error
@@ -315,6 +363,11 @@ pub enum ValidationError {
         "The ciphersuite in the KeyPackage of the Add proposal does not match the group context."
     )]
     InvalidAddProposalCiphersuite,
+    /// The signature key of the external sender appears more than once in the ExternalSendersExtension.
+    #[error(
+        "The signature key of the external sender appears more than once in the ExternalSendersExtension."
+    )]
+    AmbiguousExternalSender,
 }
 
 /// Proposal validation error
@@ -326,12 +379,14 @@ pub enum ProposalValidationError {
     /// The sender could not be matched to a member of the group.
     #[error("The sender could not be matched to a member of the group.")]
     UnknownMember,
-    /// Duplicate signature key in proposals and group.
-    #[error("Duplicate signature key in proposals and group.")]
-    DuplicateSignatureKey,
-    /// Duplicate encryption key in proposals and group.
-    #[error("Duplicate encryption key in proposals and group.")]
-    DuplicateEncryptionKey,
+    /// Duplicate signature key in proposals and group. If the key is already
+    /// used by a member, the leaf index of the member is given.
+    #[error("Duplicate signature key in proposals and group{}.", .0.map(|index| format!(" (used by the member at leaf index {index})")).unwrap_or_default())]
+    DuplicateSignatureKey(Option<LeafNodeIndex>),
+    /// Duplicate encryption key in proposals and group. If the key is already
+    /// used by a member, the leaf index of the member is given.
+    #[error("Duplicate encryption key in proposals and group{}.", .0.map(|index| format!(" (used by the member at leaf index {index})")).unwrap_or_default())]
+    DuplicateEncryptionKey(Option<LeafNodeIndex>),
     /// Duplicate init key in proposals.
     #[error("Duplicate init key in proposals.")]
     DuplicateInitKey,
@@ -361,6 +416,14 @@ pub enum ProposalValidationError {
     /// See [`PskError`] for more details.
     #[error(transparent)]
     Psk(#[from] PskError),
+    /// The external sender is not allowed to remove the member.
+    #[error("The external sender is not allowed to remove the member.")]
+    ExternalSenderOutOfScope,
+    /// The sender index of the external proposal no longer refers to the sender that signed it.
+    #[error(
+        "The sender index of the external proposal no longer refers to the sender that signed it."
+    )]
+    StaleExternalSender,
 }
 
 /// External Commit validaton error
@@ -527,4 +590,35 @@ pub enum MergeCommitError<KeyStoreError> {
     /// Error accessing the key store.
     #[error("Error accessing the key store.")]
     KeyStoreError(KeyStoreError),
+    /// See [`MlsGroupStateError`] for more details.
+    #[error(transparent)]
+    GroupStateError(#[from] MlsGroupStateError),
+}
+
+/// Memory limit error
+#[derive(Error, Debug, PartialEq, Clone)]
+pub enum MemoryLimitError {
+    /// The ratchet tree would exceed the configured memory limit.
+    #[error("The ratchet tree would exceed the configured memory limit.")]
+    TreeLimitExceeded,
+    /// The pending proposals would exceed the configured memory limit.
+    #[error("The pending proposals would exceed the configured memory limit.")]
+    PendingProposalsLimitExceeded,
+}
+
+/// Commit limit error
+#[derive(Error, Debug, PartialEq, Clone)]
+pub enum CommitLimitError {
+    /// The commit covers more proposals than the configured limit.
+    #[error("The commit covers more proposals than the configured limit.")]
+    TooManyProposals,
+    /// The commit covers more Add proposals than the configured limit.
+    #[error("The commit covers more Add proposals than the configured limit.")]
+    TooManyAdds,
+    /// The serialized commit is larger than the configured limit.
+    #[error("The serialized commit is larger than the configured limit.")]
+    TooLarge,
+    /// The update path of the commit has more nodes than the configured limit.
+    #[error("The update path of the commit has more nodes than the configured limit.")]
+    TooManyPathNodes,
 }
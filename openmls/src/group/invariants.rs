@@ -0,0 +1,81 @@
+//! # Invariant checks
+//!
+//! With the `check-invariants` feature, groups validate their internal
+//! invariants after every operation that changes their state. A violation
+//! means that the state of the group was corrupted, e.g., by a bug in OpenMLS
+//! or by a modification of the persisted state. It is logged with a detailed
+//! report and the operation panics, so that the corruption is caught where it
+//! happens instead of causing errors in later epochs.
+//!
+//! The checks are expensive and are meant for testing, e.g., integration
+//! tests of an application. They should not be enabled in production.
+//!
+//! A [`PublicGroup`] is checked when it is created and before every message
+//! it processes, because merging a commit into a [`PublicGroup`] doesn't have
+//! access to a crypto provider. A [`CoreGroup`] is checked when it is created
+//! and after every message it encrypts or decrypts and every commit it
+//! merges, as long as its own leaf is in the tree.
+
+use openmls_traits::crypto::OpenMlsCrypto;
+
+use super::{CoreGroup, PublicGroup};
+
+impl PublicGroup {
+    /// Returns a description of every violated invariant of the group.
+    fn invariant_violations(&self, crypto: &impl OpenMlsCrypto) -> Vec<String> {
+        let mut violations = self
+            .treesync()
+            .invariant_violations(crypto, self.ciphersuite());
+        if self.treesync().tree_hash() != self.group_context().tree_hash() {
+            violations.push(format!(
+                "the tree hash {:x?} doesn't match the tree hash {:x?} of the group context",
+                self.treesync().tree_hash(),
+                self.group_context().tree_hash()
+            ));
+        }
+        violations
+    }
+
+    /// Check the invariants of the group after or before `operation` and
+    /// panic with a report if one is violated.
+    pub(crate) fn check_invariants(&self, crypto: &impl OpenMlsCrypto, operation: &str) {
+        report(self, operation, self.invariant_violations(crypto));
+    }
+}
+
+impl CoreGroup {
+    /// Check the invariants of the group after `operation` and panic with a
+    /// report if one is violated.
+    pub(crate) fn check_invariants(&self, crypto: &impl OpenMlsCrypto, operation: &str) {
+        let public_group = self.public_group();
+        // If the own leaf was removed, the commit was only partially applied
+        // and the group can't be used anymore.
+        if public_group.leaf(self.own_leaf_index()).is_none() {
+            return;
+        }
+        let mut violations = public_group.invariant_violations(crypto);
+        violations.extend(
+            self.message_secrets()
+                .secret_tree()
+                .invariant_violations(public_group.tree_size(), self.own_leaf_index()),
+        );
+        report(public_group, operation, violations);
+    }
+}
+
+fn report(public_group: &PublicGroup, operation: &str, violations: Vec<String>) {
+    if violations.is_empty() {
+        return;
+    }
+    let mut report = format!(
+        "Violated invariants in `{operation}` for group {:x?} in epoch {}:",
+        public_group.group_id().as_slice(),
+        public_group.group_context().epoch().as_u64()
+    );
+    for violation in violations {
+        report.push_str("\n  - ");
+        report.push_str(&violation);
+    }
+    log::error!("{report}");
+    panic!("{report}");
+}
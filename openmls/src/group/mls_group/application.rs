@@ -27,6 +27,13 @@ impl MlsGroup {
                 MlsGroupStateError::PendingProposal,
             ));
         }
+        if self.snapshot_active {
+            return Err(CreateMessageError::GroupStateError(
+                MlsGroupStateError::SnapshotActive,
+            ));
+        }
+        #[cfg(feature = "strict-api")]
+        self.detect_message_with_pending_commit()?;
 
         let ciphertext = self
             .group
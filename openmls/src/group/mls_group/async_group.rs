@@ -0,0 +1,903 @@
+//! # Async MLS group
+//!
+//! This module is only available with the `async` feature. It contains
+//! [`AsyncMlsGroup`], a facade over [`MlsGroup`] for applications whose storage
+//! backend is asynchronous, e.g. a database that is accessed through Tokio or
+//! IndexedDB in the browser. All operations that access the key store are
+//! `async fn`s and no operation blocks on storage.
+//!
+//! The storage backend implements [`AsyncOpenMlsKeyStore`], which stores opaque
+//! byte strings. Values are serialized in the same way as by the
+//! `openmls_memory_keystore`.
+//!
+//! ## How it works
+//!
+//! The MLS protocol logic is synchronous. Each operation is therefore executed
+//! against a cache that sits in front of the asynchronous key store:
+//!
+//! 1. The operation is executed on the cache. Values that the operation is
+//!    known to need, e.g. the encryption keys of the current epoch, are
+//!    fetched upfront. Writes and deletions are only recorded in the cache.
+//!    Reads of values that have not been fetched from the key store yet are
+//!    recorded as misses.
+//! 2. If there were misses, the group state is rolled back, the missing values
+//!    are fetched from the key store and the operation is executed again.
+//! 3. Once an operation completes without misses, the recorded writes and
+//!    deletions are applied to the key store if the operation was successful.
+//!
+//! Cryptographic operations are CPU bound and are executed on the calling task
+//! by the crypto provider. [`AsyncMlsGroup::commit_in_steps()`] creates a
+//! commit in steps and yields to the executor in between, so that commits in
+//! large groups don't block other tasks.
+//!
+//! ## Cancellation
+//!
+//! All futures returned by [`AsyncMlsGroup`] can be dropped at any `.await`
+//! without corrupting the group:
+//!
+//! * If the future is dropped before all writes have been stored, the group
+//!   is rolled back to its state before the operation. The values that have
+//!   been stored already are not referenced by the group. Epoch transition
+//!   subscriptions and the state change flag are not affected by the
+//!   rollback.
+//! * New values are stored before old values are deleted. If the future is
+//!   dropped while values are deleted, the operation has taken effect and
+//!   only some of the old values remain in the key store.
+
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    convert::Infallible,
+    future::Future,
+    mem,
+    ops::{Deref, DerefMut},
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use async_trait::async_trait;
+use openmls_traits::{
+    clock::OpenMlsClock,
+    crypto::OpenMlsCrypto,
+    key_store::{unwrap_blob, BlobHeader, MlsEntity, OpenMlsKeyStore},
+    random::OpenMlsRand,
+    signatures::Signer,
+    OpenMlsProvider,
+};
+
+use super::{errors::*, *};
+use crate::{
+    credentials::CredentialWithKey,
+    messages::group_info::{GroupInfo, VerifiableGroupInfo},
+    metrics, security_events,
+    treesync::RatchetTreeIn,
+};
+
+/// The asynchronous key store trait.
+///
+/// Values are opaque byte strings. Implementations don't have to interpret
+/// them.
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait AsyncOpenMlsKeyStore {
+    /// The error type returned by the [`AsyncOpenMlsKeyStore`].
+    type Error: std::error::Error + std::fmt::Debug + PartialEq;
+
+    /// Store the value `v` for ID `k`.
+    ///
+    /// Returns an error if storing fails.
+    async fn store(&self, k: &[u8], v: &[u8]) -> Result<(), Self::Error>;
+
+    /// Read the value stored for ID `k`.
+    ///
+    /// Returns [`None`] if no value is stored for `k` and an error if reading
+    /// fails.
+    async fn read(&self, k: &[u8]) -> Result<Option<Vec<u8>>, Self::Error>;
+
+    /// Delete the value stored for ID `k`.
+    ///
+    /// Returns an error if deleting fails.
+    async fn delete(&self, k: &[u8]) -> Result<(), Self::Error>;
+}
+
+/// The asynchronous counterpart of the [`OpenMlsProvider`] trait.
+pub trait AsyncOpenMlsProvider {
+    /// The crypto provider.
+    type CryptoProvider: OpenMlsCrypto;
+    /// The randomness provider.
+    type RandProvider: OpenMlsRand;
+    /// The asynchronous key store provider.
+    type KeyStoreProvider: AsyncOpenMlsKeyStore;
+    /// The clock provider.
+    type ClockProvider: OpenMlsClock;
+
+    /// Get the crypto provider.
+    fn crypto(&self) -> &Self::CryptoProvider;
+
+    /// Get the randomness provider.
+    fn rand(&self) -> &Self::RandProvider;
+
+    /// Get the asynchronous key store provider.
+    fn key_store(&self) -> &Self::KeyStoreProvider;
+
+    /// Get the clock provider.
+    fn clock(&self) -> &Self::ClockProvider;
+}
+
+/// The error type of the asynchronous key store of the provider `P`.
+pub type AsyncKeyStoreError<P> =
+    <<P as AsyncOpenMlsProvider>::KeyStoreProvider as AsyncOpenMlsKeyStore>::Error;
+
+/// The values read from the asynchronous key store. `None` means that no value
+/// is stored.
+type FetchedValues = HashMap<Vec<u8>, Option<Vec<u8>>>;
+
+/// A synchronous key store in front of an [`AsyncOpenMlsKeyStore`].
+///
+/// Values that were not fetched yet are recorded as misses.
+struct KeyStoreCache<'a> {
+    fetched: &'a FetchedValues,
+    // Values written (`Some`) and deleted (`None`) by the current operation.
+    written: RefCell<HashMap<Vec<u8>, Option<Vec<u8>>>>,
+    misses: RefCell<Vec<Vec<u8>>>,
+}
+
+impl<'a> KeyStoreCache<'a> {
+    fn new(fetched: &'a FetchedValues) -> Self {
+        Self {
+            fetched,
+            written: RefCell::new(HashMap::new()),
+            misses: RefCell::new(Vec::new()),
+        }
+    }
+}
+
+impl OpenMlsKeyStore for KeyStoreCache<'_> {
+    type Error = KeyStoreCacheError;
+
+    fn store<V: MlsEntity>(&self, k: &[u8], v: &V) -> Result<(), Self::Error> {
+        let value = serde_json::to_vec(v)
+            .map_err(|e| KeyStoreCacheError::SerializationError(e.to_string()))?;
+        let value = BlobHeader::for_entity::<V>().wrap(&value);
+        self.written.borrow_mut().insert(k.to_vec(), Some(value));
+        Ok(())
+    }
+
+    fn read<V: MlsEntity>(&self, k: &[u8]) -> Option<V> {
+        let written = self.written.borrow();
+        let value = match written.get(k).or_else(|| self.fetched.get(k)) {
+            Some(value) => value.as_ref()?,
+            None => {
+                self.misses.borrow_mut().push(k.to_vec());
+                return None;
+            }
+        };
+        serde_json::from_slice(unwrap_blob::<V>(value)?).ok()
+    }
+
+    fn delete<V: MlsEntity>(&self, k: &[u8]) -> Result<(), Self::Error> {
+        self.written.borrow_mut().insert(k.to_vec(), None);
+        Ok(())
+    }
+}
+
+/// The synchronous [`OpenMlsProvider`] that operations of an [`AsyncMlsGroup`]
+/// are executed with.
+struct CachingProvider<'a, P: AsyncOpenMlsProvider> {
+    provider: &'a P,
+    key_store: KeyStoreCache<'a>,
+}
+
+impl<'a, P: AsyncOpenMlsProvider> OpenMlsProvider for CachingProvider<'a, P> {
+    type CryptoProvider = P::CryptoProvider;
+    type RandProvider = P::RandProvider;
+    type KeyStoreProvider = KeyStoreCache<'a>;
+    type ClockProvider = P::ClockProvider;
+
+    fn crypto(&self) -> &Self::CryptoProvider {
+        self.provider.crypto()
+    }
+
+    fn rand(&self) -> &Self::RandProvider {
+        self.provider.rand()
+    }
+
+    fn key_store(&self) -> &Self::KeyStoreProvider {
+        &self.key_store
+    }
+
+    fn clock(&self) -> &Self::ClockProvider {
+        self.provider.clock()
+    }
+}
+
+/// Execute `operation` on `state` until it completes without cache misses and
+/// apply the writes of a successful operation to the key store.
+///
+/// The values for the IDs in `prefetch` are fetched before the operation is
+/// executed for the first time. `rollback` restores `state` before the
+/// operation is executed again, and if the returned future is dropped or
+/// writing to the key store fails before all values have been stored.
+async fn execute<P, S, T, E>(
+    provider: &P,
+    prefetch: Vec<Vec<u8>>,
+    state: &mut S,
+    rollback: impl Fn(&mut S) -> Result<(), LibraryError>,
+    mut operation: impl FnMut(&mut S, &CachingProvider<P>) -> Result<T, E>,
+) -> Result<T, AsyncGroupError<E, AsyncKeyStoreError<P>>>
+where
+    P: AsyncOpenMlsProvider,
+{
+    let mut guard = RollbackGuard {
+        state,
+        rollback,
+        armed: true,
+    };
+    let mut fetched = FetchedValues::new();
+    fetch(provider, &mut fetched, prefetch).await?;
+    loop {
+        let (result, written, misses, deferred_metrics, deferred_events) = {
+            let caching_provider = CachingProvider {
+                provider,
+                key_store: KeyStoreCache::new(&fetched),
+            };
+            // Metrics and security events are only reported once the
+            // operation has taken effect.
+            let ((result, deferred_metrics), deferred_events) = security_events::deferred(|| {
+                metrics::deferred(|| operation(guard.state, &caching_provider))
+            });
+            let KeyStoreCache {
+                written, misses, ..
+            } = caching_provider.key_store;
+            (
+                result,
+                written.into_inner(),
+                misses.into_inner(),
+                deferred_metrics,
+                deferred_events,
+            )
+        };
+
+        if misses.is_empty() {
+            let result = match result {
+                Ok(result) => result,
+                Err(e) => {
+                    // Failed operations leave the state as it is.
+                    guard.armed = false;
+                    deferred_metrics.report();
+                    deferred_events.report();
+                    return Err(AsyncGroupError::Operation(e));
+                }
+            };
+            // New values are stored before old values are deleted, so that
+            // the key store never lacks values the state refers to.
+            let mut deletes = Vec::new();
+            for (k, v) in written {
+                match v {
+                    Some(v) => provider
+                        .key_store()
+                        .store(&k, &v)
+                        .await
+                        .map_err(AsyncGroupError::KeyStoreError)?,
+                    None => deletes.push(k),
+                }
+            }
+            guard.armed = false;
+            deferred_metrics.report();
+            deferred_events.report();
+            for k in deletes {
+                provider
+                    .key_store()
+                    .delete(&k)
+                    .await
+                    .map_err(AsyncGroupError::KeyStoreError)?;
+            }
+            return Ok(result);
+        }
+
+        (guard.rollback)(guard.state)?;
+        fetch(provider, &mut fetched, misses).await?;
+    }
+}
+
+/// Rolls back the state of an operation if the operation doesn't complete,
+/// e.g. because its future is dropped while it waits for the key store.
+struct RollbackGuard<'a, S, R: Fn(&mut S) -> Result<(), LibraryError>> {
+    state: &'a mut S,
+    rollback: R,
+    armed: bool,
+}
+
+impl<S, R: Fn(&mut S) -> Result<(), LibraryError>> Drop for RollbackGuard<'_, S, R> {
+    fn drop(&mut self) {
+        if self.armed {
+            // There is no way to report an error here. The rollback only
+            // fails if the snapshot of the state can't be restored.
+            let _ = (self.rollback)(self.state);
+        }
+    }
+}
+
+async fn fetch<P: AsyncOpenMlsProvider, E>(
+    provider: &P,
+    fetched: &mut FetchedValues,
+    ids: Vec<Vec<u8>>,
+) -> Result<(), AsyncGroupError<E, AsyncKeyStoreError<P>>> {
+    for k in ids {
+        let value = provider
+            .key_store()
+            .read(&k)
+            .await
+            .map_err(AsyncGroupError::KeyStoreError)?;
+        fetched.insert(k, value);
+    }
+    Ok(())
+}
+
+/// A future that yields to the executor once, so that other tasks can run
+/// and the operation can be cancelled in between its steps.
+struct YieldNow(bool);
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            return Poll::Ready(());
+        }
+        self.0 = true;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
+
+/// A facade over [`MlsGroup`] for asynchronous key stores.
+///
+/// All operations that access the key store are `async fn`s that take an
+/// [`AsyncOpenMlsProvider`]. Operations that don't access the key store are
+/// available through [`Deref`] to the underlying [`MlsGroup`]. See the
+/// [module documentation](self) for details.
+#[derive(Debug)]
+pub struct AsyncMlsGroup {
+    group: MlsGroup,
+}
+
+impl From<MlsGroup> for AsyncMlsGroup {
+    fn from(group: MlsGroup) -> Self {
+        Self { group }
+    }
+}
+
+impl Deref for AsyncMlsGroup {
+    type Target = MlsGroup;
+
+    fn deref(&self) -> &Self::Target {
+        &self.group
+    }
+}
+
+impl DerefMut for AsyncMlsGroup {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.group
+    }
+}
+
+impl AsyncMlsGroup {
+    /// Returns the underlying [`MlsGroup`].
+    pub fn into_inner(self) -> MlsGroup {
+        self.group
+    }
+
+    /// Execute an operation that creates a new group.
+    async fn create<P: AsyncOpenMlsProvider, T, E>(
+        provider: &P,
+        operation: impl FnMut(&mut (), &CachingProvider<P>) -> Result<T, E>,
+    ) -> Result<T, AsyncGroupError<E, AsyncKeyStoreError<P>>> {
+        execute(provider, Vec::new(), &mut (), |_| Ok(()), operation).await
+    }
+
+    /// Execute an operation on this group. The group is rolled back before the
+    /// operation is executed again.
+    async fn update<P: AsyncOpenMlsProvider, T, E>(
+        &mut self,
+        provider: &P,
+        mut operation: impl FnMut(&mut MlsGroup, &CachingProvider<P>) -> Result<T, E>,
+    ) -> Result<T, AsyncGroupError<E, AsyncKeyStoreError<P>>> {
+        let snapshot = serde_json::to_vec(&self.group)
+            .map_err(|_| LibraryError::custom("Error serializing the group state."))?;
+        let state_changed = self.group.state_changed;
+        // Some values are expected to be in the key store and not finding them
+        // is treated as a bug. They are fetched upfront.
+        let prefetch = [self.group.group.epoch_keypairs_id()]
+            .into_iter()
+            .chain(
+                self.group
+                    .own_leaf_nodes
+                    .iter()
+                    .map(|leaf_node| leaf_node.encryption_key().to_bytes_with_prefix()),
+            )
+            .collect();
+        execute(
+            provider,
+            prefetch,
+            &mut self.group,
+            |group| {
+                let mut restored: MlsGroup = serde_json::from_slice(&snapshot)
+                    .map_err(|_| LibraryError::custom("Error restoring the group state."))?;
+                // The epoch subscribers and the exporter cache are not
+                // serialized. Like in `MlsGroup::rollback()`, they are moved
+                // to the restored group.
+                restored.epoch_subscribers = mem::take(&mut group.epoch_subscribers);
+                restored.exporter_cache = mem::take(&mut group.exporter_cache);
+                restored.state_changed = state_changed;
+                *group = restored;
+                Ok(())
+            },
+            |group, provider| operation(group, provider),
+        )
+        .await
+    }
+
+    // === Creation ===
+
+    /// Creates a new group with the creator as the only member (and a random
+    /// group ID). See [`MlsGroup::new()`].
+    pub async fn new<P: AsyncOpenMlsProvider>(
+        provider: &P,
+        signer: &impl Signer,
+        mls_group_config: &MlsGroupConfig,
+        credential_with_key: CredentialWithKey,
+    ) -> Result<Self, AsyncGroupError<NewGroupError<KeyStoreCacheError>, AsyncKeyStoreError<P>>>
+    {
+        Self::create(provider, |_, provider| {
+            MlsGroup::new(
+                provider,
+                signer,
+                mls_group_config,
+                credential_with_key.clone(),
+            )
+        })
+        .await
+        .map(Self::from)
+    }
+
+    /// Creates a new group with a given group ID with the creator as the only
+    /// member. See [`MlsGroup::new_with_group_id()`].
+    pub async fn new_with_group_id<P: AsyncOpenMlsProvider>(
+        provider: &P,
+        signer: &impl Signer,
+        mls_group_config: &MlsGroupConfig,
+        group_id: GroupId,
+        credential_with_key: CredentialWithKey,
+    ) -> Result<Self, AsyncGroupError<NewGroupError<KeyStoreCacheError>, AsyncKeyStoreError<P>>>
+    {
+        Self::create(provider, |_, provider| {
+            MlsGroup::new_with_group_id(
+                provider,
+                signer,
+                mls_group_config,
+                group_id.clone(),
+                credential_with_key.clone(),
+            )
+        })
+        .await
+        .map(Self::from)
+    }
+
+    /// Creates a new group from a [`Welcome`] message. See
+    /// [`MlsGroup::new_from_welcome()`].
+    pub async fn new_from_welcome<P: AsyncOpenMlsProvider>(
+        provider: &P,
+        mls_group_config: &MlsGroupConfig,
+        welcome: Welcome,
+        ratchet_tree: Option<RatchetTreeIn>,
+    ) -> Result<Self, AsyncGroupError<WelcomeError<KeyStoreCacheError>, AsyncKeyStoreError<P>>>
+    {
+        Self::create(provider, |_, provider| {
+            MlsGroup::new_from_welcome(
+                provider,
+                mls_group_config,
+                welcome.clone(),
+                ratchet_tree.clone(),
+            )
+        })
+        .await
+        .map(Self::from)
+    }
+
+    /// Creates a new group from a [`Welcome`] message and fetches the ratchet
+    /// tree with `fetch_ratchet_tree` if the Welcome message doesn't contain
+    /// it. See [`MlsGroup::new_from_welcome_fetching_tree()`].
+    ///
+    /// The Welcome message is decrypted a second time once the tree was
+    /// fetched.
+    pub async fn new_from_welcome_fetching_tree<P: AsyncOpenMlsProvider, F>(
+        provider: &P,
+        mls_group_config: &MlsGroupConfig,
+        welcome: Welcome,
+        fetch_ratchet_tree: impl FnOnce(Vec<u8>) -> F,
+    ) -> Result<Self, AsyncGroupError<WelcomeError<KeyStoreCacheError>, AsyncKeyStoreError<P>>>
+    where
+        F: Future<Output = Option<RatchetTreeIn>>,
+    {
+        let mut expected_tree_hash = None;
+        let result = Self::create(provider, |_, provider| {
+            MlsGroup::new_from_welcome_fetching_tree(
+                provider,
+                mls_group_config,
+                welcome.clone(),
+                |tree_hash| {
+                    expected_tree_hash = Some(tree_hash.to_vec());
+                    None
+                },
+            )
+        })
+        .await;
+        let ratchet_tree = match (result, expected_tree_hash) {
+            (
+                Err(AsyncGroupError::Operation(WelcomeError::MissingRatchetTree)),
+                Some(tree_hash),
+            ) => fetch_ratchet_tree(tree_hash).await,
+            (result, _) => return result.map(Self::from),
+        };
+        Self::new_from_welcome(provider, mls_group_config, welcome, ratchet_tree).await
+    }
+
+    /// Creates new groups from many [`Welcome`] messages at once. See
+    /// [`MlsGroup::new_from_welcomes()`].
+    ///
+    /// The key packages of all Welcome messages are fetched upfront, the
+    /// values that are still missing are fetched in one round for all Welcome
+    /// messages, and the writes of all joined groups are stored together.
+    #[allow(clippy::type_complexity)]
+    pub async fn new_from_welcomes<P: AsyncOpenMlsProvider>(
+        provider: &P,
+        mls_group_config: &MlsGroupConfig,
+        welcomes: Vec<(Welcome, Option<RatchetTreeIn>)>,
+    ) -> Result<
+        Vec<Result<Self, WelcomeError<KeyStoreCacheError>>>,
+        AsyncGroupError<Infallible, AsyncKeyStoreError<P>>,
+    > {
+        let mut prefetch: Vec<Vec<u8>> = welcomes
+            .iter()
+            .flat_map(|(welcome, _)| welcome.secrets())
+            .map(|secrets| secrets.new_member().as_slice().to_vec())
+            .collect();
+        prefetch.sort();
+        prefetch.dedup();
+        let groups = execute(
+            provider,
+            prefetch,
+            &mut (),
+            |_| Ok(()),
+            |_, provider| {
+                Ok::<_, Infallible>(MlsGroup::new_from_welcomes(
+                    provider,
+                    mls_group_config,
+                    welcomes.clone(),
+                ))
+            },
+        )
+        .await?;
+        Ok(groups
+            .into_iter()
+            .map(|group| group.map(Self::from))
+            .collect())
+    }
+
+    /// Joins a group through an external commit. See
+    /// [`MlsGroup::join_by_external_commit()`].
+    #[allow(clippy::type_complexity)]
+    pub async fn join_by_external_commit<P: AsyncOpenMlsProvider>(
+        provider: &P,
+        signer: &impl Signer,
+        ratchet_tree: Option<RatchetTreeIn>,
+        verifiable_group_info: VerifiableGroupInfo,
+        mls_group_config: &MlsGroupConfig,
+        aad: &[u8],
+        credential_with_key: CredentialWithKey,
+    ) -> Result<
+        (Self, MlsMessageOut, Option<GroupInfo>),
+        AsyncGroupError<ExternalCommitError, AsyncKeyStoreError<P>>,
+    > {
+        Self::create(provider, |_, provider| {
+            MlsGroup::join_by_external_commit(
+                provider,
+                signer,
+                ratchet_tree.clone(),
+                verifiable_group_info.clone(),
+                mls_group_config,
+                aad,
+                credential_with_key.clone(),
+            )
+        })
+        .await
+        .map(|(group, commit, group_info)| (Self::from(group), commit, group_info))
+    }
+
+    // === Load & save ===
+
+    /// Loads the state from persisted state. See [`MlsGroup::load()`].
+    pub async fn load<P: AsyncOpenMlsProvider>(
+        provider: &P,
+        group_id: &GroupId,
+    ) -> Result<Option<Self>, AsyncGroupError<Infallible, AsyncKeyStoreError<P>>> {
+        Self::create(provider, |_, provider| {
+            Ok(MlsGroup::load(group_id, provider.key_store()))
+        })
+        .await
+        .map(|group| group.map(Self::from))
+    }
+
+    /// Persists the state. See [`MlsGroup::save()`].
+    pub async fn save<P: AsyncOpenMlsProvider>(
+        &mut self,
+        provider: &P,
+    ) -> Result<(), AsyncGroupError<KeyStoreCacheError, AsyncKeyStoreError<P>>> {
+        self.update(provider, |group, provider| group.save(provider.key_store()))
+            .await
+    }
+
+    // === Membership ===
+
+    /// Adds members to the group. See [`MlsGroup::add_members()`].
+    pub async fn add_members<P: AsyncOpenMlsProvider>(
+        &mut self,
+        provider: &P,
+        signer: &impl Signer,
+        key_packages: &[KeyPackage],
+    ) -> Result<
+        CommitOutput<MlsMessageOut>,
+        AsyncGroupError<AddMembersError<KeyStoreCacheError>, AsyncKeyStoreError<P>>,
+    > {
+        self.update(provider, |group, provider| {
+            group.add_members(provider, signer, key_packages)
+        })
+        .await
+    }
+
+    /// Removes members from the group. See [`MlsGroup::remove_members()`].
+    pub async fn remove_members<P: AsyncOpenMlsProvider>(
+        &mut self,
+        provider: &P,
+        signer: &impl Signer,
+        members: &[LeafNodeIndex],
+    ) -> Result<
+        CommitOutput,
+        AsyncGroupError<RemoveMembersError<KeyStoreCacheError>, AsyncKeyStoreError<P>>,
+    > {
+        self.update(provider, |group, provider| {
+            group.remove_members(provider, signer, members)
+        })
+        .await
+    }
+
+    /// Leaves the group. See [`MlsGroup::leave_group()`].
+    pub async fn leave_group<P: AsyncOpenMlsProvider>(
+        &mut self,
+        provider: &P,
+        signer: &impl Signer,
+    ) -> Result<MlsMessageOut, AsyncGroupError<LeaveGroupError, AsyncKeyStoreError<P>>> {
+        self.update(provider, |group, provider| {
+            group.leave_group(provider, signer)
+        })
+        .await
+    }
+
+    // === Proposals & updates ===
+
+    /// Creates a proposal to add a member. See
+    /// [`MlsGroup::propose_add_member()`].
+    pub async fn propose_add_member<P: AsyncOpenMlsProvider>(
+        &mut self,
+        provider: &P,
+        signer: &impl Signer,
+        key_package: &KeyPackage,
+    ) -> Result<
+        (MlsMessageOut, ProposalRef),
+        AsyncGroupError<ProposeAddMemberError, AsyncKeyStoreError<P>>,
+    > {
+        self.update(provider, |group, provider| {
+            group.propose_add_member(provider, signer, key_package)
+        })
+        .await
+    }
+
+    /// Creates a proposal to remove a member. See
+    /// [`MlsGroup::propose_remove_member()`].
+    pub async fn propose_remove_member<P: AsyncOpenMlsProvider>(
+        &mut self,
+        provider: &P,
+        signer: &impl Signer,
+        member: LeafNodeIndex,
+    ) -> Result<
+        (MlsMessageOut, ProposalRef),
+        AsyncGroupError<ProposeRemoveMemberError, AsyncKeyStoreError<P>>,
+    > {
+        self.update(provider, |group, provider| {
+            group.propose_remove_member(provider, signer, member)
+        })
+        .await
+    }
+
+    /// Creates a proposal to update the own leaf node. See
+    /// [`MlsGroup::propose_self_update()`].
+    pub async fn propose_self_update<P: AsyncOpenMlsProvider>(
+        &mut self,
+        provider: &P,
+        signer: &impl Signer,
+        leaf_node: Option<LeafNode>,
+    ) -> Result<
+        (MlsMessageOut, ProposalRef),
+        AsyncGroupError<ProposeSelfUpdateError<KeyStoreCacheError>, AsyncKeyStoreError<P>>,
+    > {
+        self.update(provider, |group, provider| {
+            group.propose_self_update(provider, signer, leaf_node.clone())
+        })
+        .await
+    }
+
+    /// Updates the own leaf node with a commit. See
+    /// [`MlsGroup::self_update()`].
+    pub async fn self_update<P: AsyncOpenMlsProvider>(
+        &mut self,
+        provider: &P,
+        signer: &impl Signer,
+    ) -> Result<
+        CommitOutput,
+        AsyncGroupError<SelfUpdateError<KeyStoreCacheError>, AsyncKeyStoreError<P>>,
+    > {
+        self.update(provider, |group, provider| {
+            group.self_update(provider, signer)
+        })
+        .await
+    }
+
+    /// Creates a commit that covers all pending proposals. See
+    /// [`MlsGroup::commit_to_pending_proposals()`].
+    pub async fn commit_to_pending_proposals<P: AsyncOpenMlsProvider>(
+        &mut self,
+        provider: &P,
+        signer: &impl Signer,
+    ) -> Result<
+        CommitOutput,
+        AsyncGroupError<CommitToPendingProposalsError<KeyStoreCacheError>, AsyncKeyStoreError<P>>,
+    > {
+        self.update(provider, |group, provider| {
+            group.commit_to_pending_proposals(provider, signer)
+        })
+        .await
+    }
+
+    /// Creates a commit that covers all pending proposals in steps, yielding
+    /// to the executor between the steps. See [`MlsGroup::start_commit()`].
+    ///
+    /// The group is only modified once the commit has been created, when it
+    /// is staged as the pending commit. Dropping the future before leaves the
+    /// group and the key store unchanged.
+    pub async fn commit_in_steps<P: AsyncOpenMlsProvider>(
+        &mut self,
+        provider: &P,
+        signer: &impl Signer,
+    ) -> Result<
+        CommitOutput,
+        AsyncGroupError<CommitToPendingProposalsError<KeyStoreCacheError>, AsyncKeyStoreError<P>>,
+    > {
+        // Starting the commit only reads from the key store and doesn't
+        // modify the group, so it can simply be repeated on cache misses.
+        let mut fetched = FetchedValues::new();
+        let mut operation = loop {
+            let caching_provider = CachingProvider {
+                provider,
+                key_store: KeyStoreCache::new(&fetched),
+            };
+            let operation = self.group.start_commit(&caching_provider, signer);
+            let misses = caching_provider.key_store.misses.into_inner();
+            if misses.is_empty() {
+                break operation.map_err(AsyncGroupError::Operation)?;
+            }
+            drop(operation);
+            fetch(provider, &mut fetched, misses).await?;
+        };
+
+        while !operation.is_complete() {
+            operation.step(provider.crypto())?;
+            YieldNow(false).await;
+        }
+        let prepared_commit = operation
+            .finish(provider.crypto(), signer)
+            .map_err(|e| match e {
+                CommitOperationError::LibraryError(e) => AsyncGroupError::LibraryError(e),
+                CommitOperationError::SignatureError(e) => {
+                    AsyncGroupError::Operation(CommitToPendingProposalsError::CreateCommitError(
+                        CreateCommitError::SignatureError(e),
+                    ))
+                }
+                CommitOperationError::Cancelled => {
+                    LibraryError::custom("Finishing a commit can't be cancelled").into()
+                }
+            })?;
+
+        // Staging the commit doesn't access the key store.
+        let fetched = FetchedValues::new();
+        let caching_provider = CachingProvider {
+            provider,
+            key_store: KeyStoreCache::new(&fetched),
+        };
+        self.group
+            .stage_commit(&caching_provider, prepared_commit)
+            .map_err(|e| match e {
+                StagePreparedCommitError::LibraryError(e) => AsyncGroupError::LibraryError(e),
+                StagePreparedCommitError::GroupStateError(e) => {
+                    AsyncGroupError::Operation(CommitToPendingProposalsError::GroupStateError(e))
+                }
+                StagePreparedCommitError::StaleCommit => {
+                    LibraryError::custom("The group changed while creating a commit").into()
+                }
+            })
+    }
+
+    // === Processing ===
+
+    /// Processes an incoming message. See [`MlsGroup::process_message()`].
+    pub async fn process_message<P: AsyncOpenMlsProvider>(
+        &mut self,
+        provider: &P,
+        message: impl Into<ProtocolMessage>,
+    ) -> Result<ProcessedMessage, AsyncGroupError<ProcessMessageError, AsyncKeyStoreError<P>>> {
+        let message = message.into();
+        self.update(provider, |group, provider| {
+            group.process_message(provider, message.clone())
+        })
+        .await
+    }
+
+    /// Merges a [`StagedCommit`] into the group state. See
+    /// [`MlsGroup::merge_staged_commit()`].
+    pub async fn merge_staged_commit<P: AsyncOpenMlsProvider>(
+        &mut self,
+        provider: &P,
+        staged_commit: StagedCommit,
+    ) -> Result<(), AsyncGroupError<MergeCommitError<KeyStoreCacheError>, AsyncKeyStoreError<P>>>
+    {
+        // The staged commit is consumed by every attempt to merge it.
+        let staged_commit = serde_json::to_vec(&staged_commit)
+            .map_err(|_| LibraryError::custom("Error serializing the staged commit."))?;
+        self.update(provider, |group, provider| {
+            let staged_commit = serde_json::from_slice(&staged_commit)
+                .map_err(|_| LibraryError::custom("Error restoring the staged commit."))?;
+            group.merge_staged_commit(provider, staged_commit)
+        })
+        .await
+    }
+
+    /// Merges the pending commit into the group state. See
+    /// [`MlsGroup::merge_pending_commit()`].
+    pub async fn merge_pending_commit<P: AsyncOpenMlsProvider>(
+        &mut self,
+        provider: &P,
+    ) -> Result<
+        (),
+        AsyncGroupError<MergePendingCommitError<KeyStoreCacheError>, AsyncKeyStoreError<P>>,
+    > {
+        self.update(provider, |group, provider| {
+            group.merge_pending_commit(provider)
+        })
+        .await
+    }
+
+    // === Application messages ===
+
+    /// Creates an application message. See [`MlsGroup::create_message()`].
+    pub async fn create_message<P: AsyncOpenMlsProvider>(
+        &mut self,
+        provider: &P,
+        signer: &impl Signer,
+        message: &[u8],
+    ) -> Result<MlsMessageOut, AsyncGroupError<CreateMessageError, AsyncKeyStoreError<P>>> {
+        self.update(provider, |group, provider| {
+            group.create_message(provider, signer, message)
+        })
+        .await
+    }
+}
@@ -0,0 +1,388 @@
+//! # Audit log
+//!
+//! An [`MlsGroup`] can keep a local, tamper-evident log of the commits it
+//! merges, e.g., to satisfy audit requirements in an enterprise deployment.
+//! The log is disabled by default and enabled with
+//! [`MlsGroupConfigBuilder::audit_log()`](super::config::MlsGroupConfigBuilder::audit_log()).
+//! Logging starts with the first commit that is merged after it was enabled.
+//!
+//! Every [`AuditLogEntry`] records the new epoch, its confirmation tag, the
+//! committer and the members that were added or removed by the commit. The
+//! entries form a hash chain: the hash of each entry covers the group ID, the
+//! contents of the entry and the hash of the previous entry. Modifying,
+//! reordering or removing an entry other than the last one therefore breaks
+//! the chain, which is detected by [`AuditLog::verify()`].
+//!
+//! Removing entries from the end of the log can't be detected from the log
+//! alone. Applications that need to detect truncation should store the
+//! [`AuditLog::head()`] in a separate, trusted location, e.g., a write-once
+//! storage, and compare it with [`AuditLog::verify_head()`].
+//!
+//! The confirmation tag binds an entry to the group state of its epoch: it is
+//! the same for all members of the group, so logs of different members can be
+//! compared with each other.
+//!
+//! An [`AuditLog`] can be exported with the `tls_codec` or `serde`
+//! serialization and verified independently of the group.
+
+use openmls_traits::crypto::OpenMlsCrypto;
+use tls_codec::{
+    Serialize as TlsSerializeTrait, TlsDeserialize, TlsSerialize, TlsSize, VLByteSlice, VLBytes,
+};
+
+use super::{errors::AuditLogError, *};
+use crate::messages::ConfirmationTag;
+
+const AUDIT_LOG_LABEL: &[u8] = b"MLS 1.0 audit log entry";
+
+/// A member of the group as recorded in an [`AuditLogEntry`].
+#[derive(
+    Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TlsSerialize, TlsDeserialize, TlsSize,
+)]
+pub struct AuditMember {
+    leaf_index: LeafNodeIndex,
+    credential: Credential,
+}
+
+impl AuditMember {
+    /// Returns the leaf index of the member.
+    pub fn leaf_index(&self) -> LeafNodeIndex {
+        self.leaf_index
+    }
+
+    /// Returns the credential of the member.
+    pub fn credential(&self) -> &Credential {
+        &self.credential
+    }
+}
+
+/// An entry of the [`AuditLog`] that records a merged commit.
+#[derive(
+    Debug, Clone, PartialEq, Serialize, Deserialize, TlsSerialize, TlsDeserialize, TlsSize,
+)]
+pub struct AuditLogEntry {
+    pub(crate) epoch: GroupEpoch,
+    pub(crate) confirmation_tag: ConfirmationTag,
+    pub(crate) committer: AuditMember,
+    pub(crate) added: Vec<AuditMember>,
+    pub(crate) removed: Vec<AuditMember>,
+    pub(crate) previous_hash: VLBytes,
+    pub(crate) hash: VLBytes,
+}
+
+impl AuditLogEntry {
+    /// Returns the epoch the group entered with the commit.
+    pub fn epoch(&self) -> GroupEpoch {
+        self.epoch
+    }
+
+    /// Returns the confirmation tag of the epoch.
+    pub fn confirmation_tag(&self) -> &ConfirmationTag {
+        &self.confirmation_tag
+    }
+
+    /// Returns the member that created the commit. For an external commit,
+    /// this is the new member, who is also contained in
+    /// [`AuditLogEntry::added()`].
+    pub fn committer(&self) -> &AuditMember {
+        &self.committer
+    }
+
+    /// Returns the members that were added by the commit.
+    pub fn added(&self) -> &[AuditMember] {
+        &self.added
+    }
+
+    /// Returns the members that were removed by the commit, with the
+    /// credentials they had before the commit.
+    pub fn removed(&self) -> &[AuditMember] {
+        &self.removed
+    }
+
+    /// Returns the hash of the previous entry. This is empty for the first
+    /// entry of the log.
+    pub fn previous_hash(&self) -> &[u8] {
+        self.previous_hash.as_slice()
+    }
+
+    /// Returns the hash of this entry.
+    pub fn hash(&self) -> &[u8] {
+        self.hash.as_slice()
+    }
+
+    fn compute_hash(
+        &self,
+        crypto: &impl OpenMlsCrypto,
+        ciphersuite: Ciphersuite,
+        group_id: &GroupId,
+    ) -> Result<Vec<u8>, LibraryError> {
+        let input = AuditLogEntryInput {
+            label: VLByteSlice(AUDIT_LOG_LABEL),
+            group_id,
+            epoch: self.epoch,
+            confirmation_tag: &self.confirmation_tag,
+            committer: &self.committer,
+            added: &self.added,
+            removed: &self.removed,
+            previous_hash: VLByteSlice(self.previous_hash.as_slice()),
+        }
+        .tls_serialize_detached()
+        .map_err(LibraryError::missing_bound_check)?;
+        crypto
+            .hash(ciphersuite.hash_algorithm(), &input)
+            .map_err(LibraryError::unexpected_crypto_error)
+    }
+}
+
+#[derive(TlsSerialize, TlsSize)]
+struct AuditLogEntryInput<'a> {
+    label: VLByteSlice<'a>,
+    group_id: &'a GroupId,
+    epoch: GroupEpoch,
+    confirmation_tag: &'a ConfirmationTag,
+    committer: &'a AuditMember,
+    added: &'a [AuditMember],
+    removed: &'a [AuditMember],
+    previous_hash: VLByteSlice<'a>,
+}
+
+/// A tamper-evident log of the commits merged by a group. See the
+/// [module documentation](self) for details.
+#[derive(
+    Debug, Clone, PartialEq, Serialize, Deserialize, TlsSerialize, TlsDeserialize, TlsSize,
+)]
+pub struct AuditLog {
+    pub(crate) group_id: GroupId,
+    pub(crate) ciphersuite: Ciphersuite,
+    pub(crate) entries: Vec<AuditLogEntry>,
+}
+
+impl AuditLog {
+    fn new(group_id: GroupId, ciphersuite: Ciphersuite) -> Self {
+        Self {
+            group_id,
+            ciphersuite,
+            entries: vec![],
+        }
+    }
+
+    /// Returns the ID of the group the log belongs to.
+    pub fn group_id(&self) -> &GroupId {
+        &self.group_id
+    }
+
+    /// Returns the ciphersuite used to hash the entries.
+    pub fn ciphersuite(&self) -> Ciphersuite {
+        self.ciphersuite
+    }
+
+    /// Returns the entries of the log, oldest first.
+    pub fn entries(&self) -> &[AuditLogEntry] {
+        &self.entries
+    }
+
+    /// Returns the hash of the last entry, or an empty slice if the log is
+    /// empty.
+    pub fn head(&self) -> &[u8] {
+        self.entries
+            .last()
+            .map(|entry| entry.hash())
+            .unwrap_or_default()
+    }
+
+    /// Verify the hash chain of the log and that the epochs of the entries
+    /// are increasing.
+    pub fn verify(&self, crypto: &impl OpenMlsCrypto) -> Result<(), AuditLogError> {
+        let mut previous: Option<&AuditLogEntry> = None;
+        for entry in &self.entries {
+            if entry.previous_hash() != previous.map(|p| p.hash()).unwrap_or_default() {
+                return Err(AuditLogError::BrokenChain);
+            }
+            if let Some(previous) = previous {
+                if entry.epoch <= previous.epoch {
+                    return Err(AuditLogError::EpochNotIncreasing);
+                }
+            }
+            if entry.compute_hash(crypto, self.ciphersuite, &self.group_id)? != entry.hash() {
+                return Err(AuditLogError::InvalidHash);
+            }
+            previous = Some(entry);
+        }
+        Ok(())
+    }
+
+    /// Verify the log as with [`AuditLog::verify()`] and check that its head
+    /// matches the `expected_head` that was stored separately.
+    pub fn verify_head(
+        &self,
+        crypto: &impl OpenMlsCrypto,
+        expected_head: &[u8],
+    ) -> Result<(), AuditLogError> {
+        self.verify(crypto)?;
+        if self.head() != expected_head {
+            return Err(AuditLogError::HeadMismatch);
+        }
+        Ok(())
+    }
+
+    fn append(
+        &mut self,
+        crypto: &impl OpenMlsCrypto,
+        epoch: GroupEpoch,
+        confirmation_tag: ConfirmationTag,
+        committer: AuditMember,
+        added: Vec<AuditMember>,
+        removed: Vec<AuditMember>,
+    ) -> Result<(), LibraryError> {
+        let mut entry = AuditLogEntry {
+            epoch,
+            confirmation_tag,
+            committer,
+            added,
+            removed,
+            previous_hash: self.head().into(),
+            hash: VLBytes::new(vec![]),
+        };
+        entry.hash = entry
+            .compute_hash(crypto, self.ciphersuite, &self.group_id)?
+            .into();
+        self.entries.push(entry);
+        Ok(())
+    }
+}
+
+/// The parts of an [`AuditLogEntry`] that have to be collected before a
+/// commit is merged.
+pub(super) struct PendingAuditEntry {
+    added_encryption_keys: Vec<Vec<u8>>,
+    removed: Vec<AuditMember>,
+    committer_joined: bool,
+    committer: LeafNodeIndex,
+}
+
+/// The members that were added or removed by a merged commit.
+pub(super) struct MembershipDelta {
+    pub(super) committer: AuditMember,
+    pub(super) committer_joined: bool,
+    pub(super) added: Vec<AuditMember>,
+    pub(super) removed: Vec<AuditMember>,
+}
+
+impl MlsGroup {
+    /// Returns the [`AuditLog`] of the group, or `None` if the audit log is
+    /// not enabled in the [`MlsGroupConfig`] or no commit has been merged
+    /// since it was enabled.
+    pub fn audit_log(&self) -> Option<&AuditLog> {
+        self.audit_log.as_ref()
+    }
+
+    /// Collect the members that are removed by `staged_commit` before it is
+    /// merged. Returns `None` if neither the audit log nor a subscription to
+    /// [`EpochTransition`]s needs them.
+    pub(super) fn pending_audit_entry(
+        &self,
+        staged_commit: &StagedCommit,
+    ) -> Option<PendingAuditEntry> {
+        if !self.mls_group_config.audit_log && self.epoch_subscribers.is_empty() {
+            return None;
+        }
+        let removed = staged_commit
+            .remove_proposals()
+            .filter_map(|remove| {
+                let leaf_index = remove.remove_proposal().removed();
+                self.group
+                    .public_group()
+                    .leaf(leaf_index)
+                    .map(|leaf| AuditMember {
+                        leaf_index,
+                        credential: leaf.credential().clone(),
+                    })
+            })
+            .collect();
+        let added_encryption_keys = staged_commit
+            .add_proposals()
+            .map(|add| {
+                add.add_proposal()
+                    .key_package()
+                    .leaf_node()
+                    .encryption_key()
+                    .as_slice()
+                    .to_vec()
+            })
+            .collect();
+        let committer_joined = staged_commit
+            .queued_proposals()
+            .any(|queued| matches!(queued.proposal(), Proposal::ExternalInit(_)));
+        Some(PendingAuditEntry {
+            added_encryption_keys,
+            removed,
+            committer_joined,
+            committer: staged_commit.committer(),
+        })
+    }
+
+    /// Resolve the members of `pending` in the group state after the commit
+    /// was merged.
+    pub(super) fn membership_delta(
+        &self,
+        pending: PendingAuditEntry,
+    ) -> Result<MembershipDelta, LibraryError> {
+        let public_group = self.group.public_group();
+        let committer = public_group
+            .leaf(pending.committer)
+            .map(|leaf| AuditMember {
+                leaf_index: pending.committer,
+                credential: leaf.credential().clone(),
+            })
+            .ok_or_else(|| LibraryError::custom("The committer is not a member of the group"))?;
+        let mut added: Vec<AuditMember> = public_group
+            .members()
+            .filter(|member| {
+                pending
+                    .added_encryption_keys
+                    .contains(&member.encryption_key)
+            })
+            .map(|member| AuditMember {
+                leaf_index: member.index,
+                credential: member.credential,
+            })
+            .collect();
+        if pending.committer_joined {
+            added.push(committer.clone());
+        }
+        Ok(MembershipDelta {
+            committer,
+            committer_joined: pending.committer_joined,
+            added,
+            removed: pending.removed,
+        })
+    }
+
+    /// Append the entry for the commit that was just merged to the audit log,
+    /// if it is enabled.
+    pub(super) fn record_audit_entry(
+        &mut self,
+        crypto: &impl OpenMlsCrypto,
+        delta: &MembershipDelta,
+    ) -> Result<(), LibraryError> {
+        if !self.mls_group_config.audit_log {
+            return Ok(());
+        }
+        let public_group = self.group.public_group();
+        let epoch = public_group.group_context().epoch();
+        let confirmation_tag = public_group.confirmation_tag().clone();
+
+        let group_id = self.group_id().clone();
+        let ciphersuite = self.ciphersuite();
+        self.audit_log
+            .get_or_insert_with(|| AuditLog::new(group_id, ciphersuite))
+            .append(
+                crypto,
+                epoch,
+                confirmation_tag,
+                delta.committer.clone(),
+                delta.added.clone(),
+                delta.removed.clone(),
+            )
+    }
+}
@@ -0,0 +1,298 @@
+//! # Group builders
+//!
+//! [`MlsGroup::builder()`] returns an [`MlsGroupBuilder`] that creates a new
+//! group and [`MlsGroup::join_builder()`] a [`JoinBuilder`] that joins a group
+//! from a [`Welcome`] message. Options that have a default are set on the
+//! builders, the inputs that are always needed are passed to the final step.
+//!
+//! ```ignore
+//! let group = MlsGroup::builder()
+//!     .with_group_id(group_id)
+//!     .with_config(&mls_group_config)
+//!     .build(provider, signer, credential_with_key)?;
+//!
+//! let (group, commit_output) = MlsGroup::builder()
+//!     .with_config(&mls_group_config)
+//!     .with_initial_members(key_packages)
+//!     .build(provider, signer, credential_with_key)?;
+//!
+//! let group = MlsGroup::join_builder(welcome)
+//!     .with_config(&mls_group_config)
+//!     .with_ratchet_tree(ratchet_tree)
+//!     .build(provider)?;
+//! ```
+
+use openmls_traits::signatures::Signer;
+
+use super::*;
+use crate::{
+    credentials::CredentialWithKey,
+    extensions::{Extensions, ParentGroupExtension},
+    group::{
+        errors::{CoreGroupBuildError, WelcomeError},
+        public_group::errors::PublicGroupBuildError,
+    },
+    treesync::RatchetTreeIn,
+};
+
+/// A builder for a new [`MlsGroup`]. See the [module documentation](self)
+/// for details.
+#[derive(Debug, Clone, Default)]
+pub struct MlsGroupBuilder {
+    group_id: Option<GroupId>,
+    mls_group_config: MlsGroupConfig,
+    group_context_extensions: Option<Extensions>,
+    parent_group: Option<ParentGroupExtension>,
+}
+
+impl MlsGroupBuilder {
+    /// Sets the group ID. By default, a random group ID is used.
+    pub fn with_group_id(mut self, group_id: GroupId) -> Self {
+        self.group_id = Some(group_id);
+        self
+    }
+
+    /// Sets the configuration. By default, [`MlsGroupConfig::default()`] is
+    /// used.
+    pub fn with_config(mut self, mls_group_config: &MlsGroupConfig) -> Self {
+        self.mls_group_config = mls_group_config.clone();
+        self
+    }
+
+    /// Sets extensions that are added to the group context of the first
+    /// epoch, in addition to the ones that are derived from the
+    /// configuration. They must not contain an extension of the same type as
+    /// one of those.
+    pub fn with_group_context_extensions(mut self, extensions: Extensions) -> Self {
+        self.group_context_extensions = Some(extensions);
+        self
+    }
+
+    /// Links the group to its parent group.
+    pub(super) fn with_parent_group(mut self, parent_group: ParentGroupExtension) -> Self {
+        self.parent_group = Some(parent_group);
+        self
+    }
+
+    /// Adds the members with the given key packages with a commit right
+    /// after the group is created.
+    pub fn with_initial_members(self, key_packages: Vec<KeyPackage>) -> MlsGroupWithMembersBuilder {
+        MlsGroupWithMembersBuilder {
+            builder: self,
+            key_packages,
+        }
+    }
+
+    /// Creates the group with the creator as the only member.
+    pub fn build<KeyStore: OpenMlsKeyStore>(
+        self,
+        provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
+        signer: &impl Signer,
+        credential_with_key: CredentialWithKey,
+    ) -> Result<MlsGroup, NewGroupError<KeyStore::Error>> {
+        let mls_group_config = self.mls_group_config;
+        let group_id = self
+            .group_id
+            .unwrap_or_else(|| GroupId::random(provider.rand()));
+        if !mls_group_config.group_id_policy.accepts(&group_id) {
+            return Err(NewGroupError::InvalidGroupId);
+        }
+
+        // TODO #751
+        let group_config = CoreGroupConfig {
+            add_ratchet_tree_extension: mls_group_config.use_ratchet_tree_extension,
+        };
+
+        let mut group_builder = CoreGroup::builder(
+            group_id,
+            mls_group_config.crypto_config,
+            credential_with_key,
+        );
+        if let Some(parent_group) = self.parent_group {
+            group_builder = group_builder.with_parent_group(parent_group);
+        }
+        if let Some(extensions) = self.group_context_extensions {
+            group_builder = group_builder.with_group_context_extensions(extensions);
+        }
+        let mut group = group_builder
+            .with_config(group_config)
+            .with_required_capabilities(mls_group_config.required_capabilities.clone())
+            .with_external_senders(mls_group_config.external_senders.clone())
+            .with_external_sender_scopes(mls_group_config.external_sender_scopes.clone())
+            .with_max_past_epoch_secrets(mls_group_config.max_past_epochs)
+            .with_lifetime(*mls_group_config.lifetime())
+            .build(provider, signer)
+            .map_err(|e| match e {
+                CoreGroupBuildError::LibraryError(e) => e.into(),
+                // We don't support PSKs yet
+                CoreGroupBuildError::Psk(e) => {
+                    log::debug!("Unexpected PSK error: {:?}", e);
+                    LibraryError::custom("Unexpected PSK error").into()
+                }
+                CoreGroupBuildError::KeyStoreError(e) => NewGroupError::KeyStoreError(e),
+                CoreGroupBuildError::PublicGroupBuildError(e) => match e {
+                    PublicGroupBuildError::LibraryError(e) => e.into(),
+                    PublicGroupBuildError::UnsupportedProposalType => {
+                        NewGroupError::UnsupportedProposalType
+                    }
+                    PublicGroupBuildError::UnsupportedExtensionType => {
+                        NewGroupError::UnsupportedExtensionType
+                    }
+                    PublicGroupBuildError::InvalidExtensions(e) => {
+                        NewGroupError::InvalidExtensions(e)
+                    }
+                },
+            })?;
+
+        // We already add a resumption PSK for epoch 0 to make things more unified.
+        let resumption_psk = group.group_epoch_secrets().resumption_psk();
+        group
+            .resumption_psk_store
+            .add(group.context().epoch(), resumption_psk.clone());
+        group.set_external_key_rotation_policy(mls_group_config.external_key_rotation_policy);
+        group.set_validation_policy(mls_group_config.validation_policy);
+        group.set_commit_limits(mls_group_config.commit_limits);
+
+        let mut mls_group = MlsGroup {
+            mls_group_config,
+            group,
+            proposal_store: ProposalStore::new(),
+            own_leaf_nodes: vec![],
+            aad: vec![],
+            group_state: MlsGroupState::Operational,
+            state_changed: InnerState::Changed,
+            audit_log: None,
+            sequencing: Default::default(),
+            stale_artifacts: None,
+            history_keys: vec![],
+            commit_metadata: None,
+            leaf_age: None,
+            declined_members: Vec::new(),
+            issued_welcome: None,
+            pending_commit_messages: None,
+            past_epoch_authenticators: Vec::new(),
+            snapshot_active: false,
+            operation_journal: Default::default(),
+            exporter_cache: Default::default(),
+            epoch_subscribers: Default::default(),
+        };
+        mls_group.store_history_key(provider.crypto())?;
+
+        Ok(mls_group)
+    }
+}
+
+/// A builder for a new [`MlsGroup`] with initial members, see
+/// [`MlsGroupBuilder::with_initial_members()`].
+#[derive(Debug, Clone)]
+pub struct MlsGroupWithMembersBuilder {
+    builder: MlsGroupBuilder,
+    key_packages: Vec<KeyPackage>,
+}
+
+impl MlsGroupWithMembersBuilder {
+    /// Creates the group and adds the initial members. The commit that adds
+    /// them is merged right away.
+    ///
+    /// Returns the group and the [`CommitOutput`] of the commit, which
+    /// contains the [`Welcome`] message for the new members. There are no
+    /// other members that the commit has to be sent to, but it can be stored
+    /// with the delivery service.
+    pub fn build<KeyStore: OpenMlsKeyStore>(
+        self,
+        provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
+        signer: &impl Signer,
+        credential_with_key: CredentialWithKey,
+    ) -> Result<(MlsGroup, CommitOutput<MlsMessageOut>), NewGroupError<KeyStore::Error>> {
+        let mut mls_group = self.builder.build(provider, signer, credential_with_key)?;
+        let commit_output = mls_group.add_members(provider, signer, &self.key_packages)?;
+        mls_group
+            .merge_pending_commit(provider)
+            .map_err(|e| match e {
+                MergePendingCommitError::MergeCommitError(MergeCommitError::KeyStoreError(e)) => {
+                    NewGroupError::KeyStoreError(e)
+                }
+                MergePendingCommitError::MergeCommitError(MergeCommitError::LibraryError(e)) => {
+                    e.into()
+                }
+                MergePendingCommitError::MlsGroupStateError(_)
+                | MergePendingCommitError::MergeCommitError(MergeCommitError::GroupStateError(_)) => {
+                    LibraryError::custom("Unexpected group state after adding members").into()
+                }
+            })?;
+        Ok((mls_group, commit_output))
+    }
+}
+
+/// A builder that joins an [`MlsGroup`] from a [`Welcome`] message. See the
+/// [module documentation](self) for details.
+#[derive(Debug, Clone)]
+pub struct JoinBuilder {
+    welcome: Welcome,
+    mls_group_config: MlsGroupConfig,
+    ratchet_tree: Option<RatchetTreeIn>,
+    expectations: WelcomeExpectations,
+}
+
+impl JoinBuilder {
+    /// Sets the configuration. By default, [`MlsGroupConfig::default()`] is
+    /// used.
+    pub fn with_config(mut self, mls_group_config: &MlsGroupConfig) -> Self {
+        self.mls_group_config = mls_group_config.clone();
+        self
+    }
+
+    /// Sets the ratchet tree of the group, which is needed if the Welcome
+    /// message doesn't contain it.
+    pub fn with_ratchet_tree(mut self, ratchet_tree: RatchetTreeIn) -> Self {
+        self.ratchet_tree = Some(ratchet_tree);
+        self
+    }
+
+    /// Sets the parameters that the group is expected to have. See
+    /// [`StagedWelcome::new_from_welcome_with_expectations()`].
+    pub fn with_expectations(mut self, expectations: WelcomeExpectations) -> Self {
+        self.expectations = expectations;
+        self
+    }
+
+    /// Stages the group, so that it can be inspected before it is joined.
+    pub fn stage<KeyStore: OpenMlsKeyStore>(
+        self,
+        provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
+    ) -> Result<StagedWelcome, WelcomeError<KeyStore::Error>> {
+        StagedWelcome::new_from_welcome_with_expectations(
+            provider,
+            &self.mls_group_config,
+            self.welcome,
+            self.ratchet_tree,
+            &self.expectations,
+        )
+    }
+
+    /// Joins the group.
+    pub fn build<KeyStore: OpenMlsKeyStore>(
+        self,
+        provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
+    ) -> Result<MlsGroup, WelcomeError<KeyStore::Error>> {
+        self.stage(provider)?.into_group(provider)
+    }
+}
+
+impl MlsGroup {
+    /// Returns a builder for a new group.
+    pub fn builder() -> MlsGroupBuilder {
+        MlsGroupBuilder::default()
+    }
+
+    /// Returns a builder that joins a group from the given [`Welcome`]
+    /// message.
+    pub fn join_builder(welcome: Welcome) -> JoinBuilder {
+        JoinBuilder {
+            welcome,
+            mls_group_config: MlsGroupConfig::default(),
+            ratchet_tree: None,
+            expectations: WelcomeExpectations::default(),
+        }
+    }
+}
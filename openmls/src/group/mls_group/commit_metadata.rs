@@ -0,0 +1,107 @@
+//! # Commit metadata
+//!
+//! Applications often want to tell the other members why a commit was made,
+//! e.g., that a member was removed for spam. Sending the reason in a separate
+//! application message doesn't bind it to the commit: it may get lost, be
+//! delivered for a different commit or be sent by a different member.
+//!
+//! [`MlsGroup::set_commit_metadata()`] attaches an application-defined blob to
+//! the commits the member creates in the current epoch. The metadata is
+//! carried in a [`CommitMetadataExtension`] in the authenticated data of the
+//! commit, so it is authenticated by the committer together with the changes
+//! of the commit. Members that process the commit read it with
+//! [`MlsGroup::commit_metadata()`]. The metadata is cleared when the group
+//! moves to a new epoch.
+//!
+//! The authenticated data is not encrypted, not even in a
+//! [`PrivateMessage`](crate::framing::PrivateMessage), so the metadata is
+//! visible to the delivery service. The AAD set with [`MlsGroup::set_aad()`]
+//! is not used for commits that carry metadata.
+
+use tls_codec::{Deserialize as TlsDeserializeTrait, Serialize as TlsSerializeTrait};
+
+use super::*;
+use crate::extensions::{CommitMetadataExtension, Extension, Extensions};
+
+impl MlsGroup {
+    /// Attaches `metadata` to the commits this member creates in the current
+    /// epoch. See the [module documentation](self) for details.
+    pub fn set_commit_metadata(&mut self, metadata: &[u8]) {
+        self.commit_metadata = Some(metadata.to_vec());
+
+        // Since the state of the group might be changed, arm the state flag
+        self.flag_state_change();
+    }
+
+    /// Removes the metadata set with [`MlsGroup::set_commit_metadata()`].
+    pub fn clear_commit_metadata(&mut self) {
+        self.commit_metadata = None;
+
+        // Since the state of the group might be changed, arm the state flag
+        self.flag_state_change();
+    }
+
+    /// Returns the metadata that is attached to the commits of this member in
+    /// the current epoch.
+    pub fn pending_commit_metadata(&self) -> Option<&[u8]> {
+        self.commit_metadata.as_deref()
+    }
+
+    /// Returns the metadata of a processed message if it is a commit that
+    /// carries metadata.
+    pub fn commit_metadata(&self, processed_message: &ProcessedMessage) -> Option<Vec<u8>> {
+        self.commit_aad_extensions(processed_message)?
+            .custom::<CommitMetadataExtension>()
+            .ok()
+            .flatten()
+            .map(|extension| extension.metadata().to_vec())
+    }
+
+    /// Returns the [`CommitMetadataExtension`] for the metadata of this
+    /// member, if there is any.
+    pub(super) fn commit_metadata_extension(&self) -> Result<Option<Extension>, LibraryError> {
+        self.commit_metadata
+            .as_ref()
+            .map(|metadata| {
+                CommitMetadataExtension::new(metadata)
+                    .to_extension()
+                    .map_err(|_| LibraryError::custom("Error encoding commit metadata"))
+            })
+            .transpose()
+    }
+
+    /// Returns the authenticated data of the commits of this member before
+    /// sequencing tokens are applied: the [`CommitMetadataExtension`] if
+    /// metadata is set and the AAD of the group otherwise.
+    pub(super) fn unsequenced_commit_aad(&self) -> Result<Vec<u8>, LibraryError> {
+        match self.commit_metadata_extension()? {
+            Some(extension) => Extensions::single(extension)
+                .tls_serialize_detached()
+                .map_err(LibraryError::missing_bound_check),
+            None => Ok(self.aad.clone()),
+        }
+    }
+
+    /// Decodes the authenticated data of a processed commit as [`Extensions`].
+    /// Returns `None` if the message is not a commit or the authenticated
+    /// data doesn't contain extensions.
+    pub(super) fn commit_aad_extensions(
+        &self,
+        processed_message: &ProcessedMessage,
+    ) -> Option<Extensions> {
+        if !matches!(
+            processed_message.content(),
+            ProcessedMessageContent::StagedCommitMessage(_)
+        ) {
+            return None;
+        }
+        let authenticated_data = processed_message.authenticated_data();
+        let extensions = if self.mls_group_config.sequencing_tokens() {
+            let sequenced_aad = SequencedAad::decode(authenticated_data).ok()?;
+            Extensions::tls_deserialize_exact(sequenced_aad.aad())
+        } else {
+            Extensions::tls_deserialize_exact(authenticated_data)
+        };
+        extensions.ok()
+    }
+}
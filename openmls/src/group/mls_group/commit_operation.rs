@@ -0,0 +1,336 @@
+//! # Commits in steps
+//!
+//! Creating a commit in a large group is expensive: the new path secrets are
+//! encrypted to every member of the group. [`MlsGroup::start_commit()`]
+//! returns a [`CommitOperation`] that does this work in small, resumable
+//! steps, so that the application can yield to other tasks or cancel the
+//! operation in between.
+//!
+//! ## Nothing is persisted until the final merge
+//!
+//! A [`CommitOperation`] only borrows the group immutably and never writes to
+//! the key store. Dropping it at any point, before or after
+//! [`CommitOperation::finish()`], leaves the group and the key store exactly
+//! as they were.
+//!
+//! The [`PreparedCommit`] returned by [`CommitOperation::finish()`] is staged
+//! with [`MlsGroup::stage_commit()`], which sets it as the pending commit and
+//! returns the messages to send, just like
+//! [`MlsGroup::commit_to_pending_proposals()`]. The new key material is only
+//! written to the key store by [`MlsGroup::merge_pending_commit()`].
+//!
+//! [`MlsGroup::add_members()`], [`MlsGroup::remove_members()`],
+//! [`MlsGroup::self_update()`] and [`MlsGroup::commit_to_pending_proposals()`]
+//! prepare their commit in the same way, in one go, and stage it right away.
+//! If preparing the commit fails, the group and the key store are left
+//! unchanged.
+//!
+//! ```ignore
+//! let mut operation = group.start_commit(provider, signer)?;
+//! while !operation.is_complete() {
+//!     operation.step(provider.crypto())?;
+//!     // Yield to other tasks or check for cancellation here.
+//! }
+//! let prepared_commit = operation.finish(provider.crypto(), signer)?;
+//! let (commit, welcome, group_info) = group
+//!     .stage_commit(provider, prepared_commit)?
+//!     .into_parts();
+//! ```
+
+use std::{
+    ops::ControlFlow,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use openmls_traits::{crypto::OpenMlsCrypto, signatures::Signer};
+
+use super::{errors::*, *};
+use crate::group::{
+    core_group::{
+        commit_preparation::CommitPreparation, create_commit_params::CreateCommitParams,
+        CreateCommitResult,
+    },
+    errors::CreateCommitError,
+};
+
+/// The progress of a [`CommitOperation`], counted in path nodes that have
+/// been encrypted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    /// The number of steps that have been completed.
+    pub completed: usize,
+    /// The total number of steps. This is zero if the commit doesn't have a
+    /// path.
+    pub total: usize,
+}
+
+/// A point at which a [`CommitOperation`] checks whether it should continue.
+pub trait Checkpoint {
+    /// Called before each step with the progress so far. Returns
+    /// [`ControlFlow::Break`] to cancel the operation.
+    fn checkpoint(&mut self, progress: Progress) -> ControlFlow<()>;
+}
+
+impl<F: FnMut(Progress) -> ControlFlow<()>> Checkpoint for F {
+    fn checkpoint(&mut self, progress: Progress) -> ControlFlow<()> {
+        self(progress)
+    }
+}
+
+/// A [`Checkpoint`] that cancels the operation once
+/// [`CancellationToken::cancel()`] has been called on any of its clones.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /// Create a new token that is not cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel the operations that use this token or one of its clones.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Release);
+    }
+
+    /// Returns `true` if the token has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Acquire)
+    }
+}
+
+impl Checkpoint for CancellationToken {
+    fn checkpoint(&mut self, _progress: Progress) -> ControlFlow<()> {
+        if self.is_cancelled() {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    }
+}
+
+/// A commit that is created in steps. See the
+/// [module documentation](self) for details.
+pub struct CommitOperation<'a> {
+    group_id: GroupId,
+    epoch: GroupEpoch,
+    preparation: CommitPreparation<'a>,
+}
+
+impl std::fmt::Debug for CommitOperation<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CommitOperation")
+            .field("group_id", &self.group_id)
+            .field("epoch", &self.epoch)
+            .field("progress", &self.progress())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a> CommitOperation<'a> {
+    /// Returns the progress of the operation.
+    pub fn progress(&self) -> Progress {
+        Progress {
+            completed: self.preparation.encrypted_path_nodes(),
+            total: self.preparation.path_nodes(),
+        }
+    }
+
+    /// Returns `true` if all steps have been completed and the operation can
+    /// be finished.
+    pub fn is_complete(&self) -> bool {
+        let progress = self.progress();
+        progress.completed == progress.total
+    }
+
+    /// Execute the next step, i.e., encrypt the path secret of the next path
+    /// node to its copath resolution. Does nothing if the operation is
+    /// complete.
+    ///
+    /// Returns the progress after the step.
+    pub fn step(&mut self, crypto: &impl OpenMlsCrypto) -> Result<Progress, LibraryError> {
+        self.preparation.encrypt_path_nodes(crypto, 1)?;
+        Ok(self.progress())
+    }
+
+    /// Execute the remaining steps and finish the operation. The
+    /// `checkpoint` is called before each step and can cancel the operation.
+    pub fn run(
+        mut self,
+        crypto: &impl OpenMlsCrypto,
+        signer: &impl Signer,
+        checkpoint: &mut impl Checkpoint,
+    ) -> Result<PreparedCommit, CommitOperationError> {
+        while !self.is_complete() {
+            if checkpoint.checkpoint(self.progress()).is_break() {
+                return Err(CommitOperationError::Cancelled);
+            }
+            self.step(crypto)?;
+        }
+        self.finish(crypto, signer)
+    }
+
+    /// Execute the remaining steps, if any, and sign the commit.
+    ///
+    /// The returned [`PreparedCommit`] is staged with
+    /// [`MlsGroup::stage_commit()`].
+    pub fn finish(
+        mut self,
+        crypto: &impl OpenMlsCrypto,
+        signer: &impl Signer,
+    ) -> Result<PreparedCommit, CommitOperationError> {
+        self.preparation.encrypt_path_nodes(crypto, usize::MAX)?;
+        let result = self
+            .preparation
+            .finish::<std::convert::Infallible>(crypto, signer)
+            .map_err(|e| match e {
+                CreateCommitError::SignatureError(e) => CommitOperationError::SignatureError(e),
+                CreateCommitError::LibraryError(e) => CommitOperationError::LibraryError(e),
+                _ => CommitOperationError::LibraryError(LibraryError::custom(
+                    "Unexpected error when finishing a commit",
+                )),
+            })?;
+        Ok(PreparedCommit {
+            group_id: self.group_id,
+            epoch: self.epoch,
+            result,
+        })
+    }
+}
+
+/// A commit created by a [`CommitOperation`] that has not been staged yet.
+#[derive(Debug)]
+pub struct PreparedCommit {
+    group_id: GroupId,
+    epoch: GroupEpoch,
+    result: CreateCommitResult,
+}
+
+impl PreparedCommit {
+    /// Returns the epoch the commit was created in.
+    pub fn epoch(&self) -> GroupEpoch {
+        self.epoch
+    }
+
+    /// Returns `true` if the commit comes with a Welcome message.
+    pub(super) fn has_welcome(&self) -> bool {
+        self.result.welcome_option.is_some()
+    }
+}
+
+impl MlsGroup {
+    /// Starts a commit that covers the pending proposals that are currently
+    /// stored in the group's [ProposalStore], like
+    /// [`MlsGroup::commit_to_pending_proposals()`]. The commit is created in
+    /// steps by the returned [`CommitOperation`].
+    ///
+    /// This validates the proposals and derives the new path. It reads from
+    /// the key store, but neither modifies the group nor writes to the key
+    /// store.
+    ///
+    /// Returns an error if there is a pending commit.
+    pub fn start_commit<'a, KeyStore: OpenMlsKeyStore>(
+        &'a self,
+        provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
+        signer: &impl Signer,
+    ) -> Result<CommitOperation<'a>, CommitToPendingProposalsError<KeyStore::Error>> {
+        self.is_operational()?;
+
+        let aad = self.commit_aad()?;
+        let params = CreateCommitParams::builder()
+            .framing_parameters(self.commit_framing_parameters(&aad))
+            .proposal_store(&self.proposal_store)
+            .build();
+        let preparation = self.group.prepare_commit(params, provider, signer)?;
+
+        Ok(CommitOperation {
+            group_id: self.group_id().clone(),
+            epoch: self.epoch(),
+            preparation,
+        })
+    }
+
+    /// Creates the commit with the given `params` in one go, like a
+    /// [`CommitOperation`] that is run to completion. Neither modifies the
+    /// group nor writes to the key store.
+    pub(super) fn prepare_commit<KeyStore: OpenMlsKeyStore>(
+        &self,
+        params: CreateCommitParams,
+        provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
+        signer: &impl Signer,
+    ) -> Result<PreparedCommit, CreateCommitError<KeyStore::Error>> {
+        let result = self.group.create_commit(params, provider, signer)?;
+
+        Ok(PreparedCommit {
+            group_id: self.group_id().clone(),
+            epoch: self.epoch(),
+            result,
+        })
+    }
+
+    /// Stages a [`PreparedCommit`] as the pending commit of the group.
+    ///
+    /// Returns a [`CommitOutput`] as [`MlsGroup::commit_to_pending_proposals()`].
+    /// The commit is merged with [`MlsGroup::merge_pending_commit()`].
+    ///
+    /// Returns an error if there is a pending commit or if the group has moved
+    /// to another epoch since the commit was started.
+    pub fn stage_commit(
+        &mut self,
+        provider: &impl OpenMlsProvider,
+        prepared_commit: PreparedCommit,
+    ) -> Result<CommitOutput, StagePreparedCommitError> {
+        self.is_operational()?;
+        if &prepared_commit.group_id != self.group_id() || prepared_commit.epoch != self.epoch() {
+            return Err(StagePreparedCommitError::StaleCommit);
+        }
+
+        Ok(self.stage_prepared_commit(provider, prepared_commit)?)
+    }
+
+    /// Stages a [`PreparedCommit`] of the current epoch as the pending commit
+    /// of the group, after the caller checked that the group is operational.
+    pub(super) fn stage_prepared_commit(
+        &mut self,
+        provider: &impl OpenMlsProvider,
+        prepared_commit: PreparedCommit,
+    ) -> Result<CommitOutput, LibraryError> {
+        let create_commit_result = prepared_commit.result;
+
+        // Convert PublicMessage messages to MLSMessage and encrypt them if required by
+        // the configuration
+        let mls_message = self.content_to_mls_message(create_commit_result.commit, provider)?;
+
+        let affected_members = AffectedMembers::new(&create_commit_result.staged_commit);
+
+        // Set the current group state to [`MlsGroupState::PendingCommit`],
+        // storing the current [`StagedCommit`] from the commit results
+        self.group_state = MlsGroupState::PendingCommit(Box::new(PendingCommitState::Member(
+            create_commit_result.staged_commit,
+        )));
+
+        let welcome = create_commit_result
+            .welcome_option
+            .map(|w| MlsMessageOut::from_welcome(w, self.group.version()));
+        self.store_pending_commit_messages(
+            &mls_message,
+            welcome.as_ref(),
+            create_commit_result.group_info.as_ref(),
+        )?;
+
+        // Since the state of the group might be changed, arm the state flag
+        self.flag_state_change();
+
+        Ok(CommitOutput::new(
+            mls_message,
+            welcome,
+            create_commit_result.group_info,
+            affected_members,
+        ))
+    }
+}
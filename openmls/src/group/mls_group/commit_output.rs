@@ -0,0 +1,149 @@
+//! # Commit outputs
+//!
+//! The operations of an [`MlsGroup`] that create a commit, e.g.,
+//! [`MlsGroup::add_members()`] or [`MlsGroup::self_update()`], return a
+//! [`CommitOutput`]. It contains the messages that have to be sent and a
+//! summary of the members that are affected by the commit. Outputs that are
+//! added in the future are added to the [`CommitOutput`] as well, so that
+//! the signatures of the operations don't change.
+//!
+//! The messages are accessed through the accessors or taken with
+//! [`CommitOutput::into_parts()`]:
+//!
+//! ```ignore
+//! let (commit, welcome, group_info) = group
+//!     .add_members(provider, signer, &key_packages)?
+//!     .into_parts();
+//! ```
+
+use super::*;
+use crate::messages::group_info::GroupInfo;
+
+/// The members that are affected by a commit.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct AffectedMembers {
+    added: Vec<Credential>,
+    removed: Vec<LeafNodeIndex>,
+    updated: Vec<LeafNodeIndex>,
+}
+
+impl AffectedMembers {
+    /// Collect the members that are affected by `staged_commit`.
+    pub(super) fn new(staged_commit: &StagedCommit) -> Self {
+        Self {
+            added: staged_commit
+                .add_proposals()
+                .map(|add| {
+                    add.add_proposal()
+                        .key_package()
+                        .leaf_node()
+                        .credential()
+                        .clone()
+                })
+                .collect(),
+            removed: staged_commit
+                .remove_proposals()
+                .map(|remove| remove.remove_proposal().removed())
+                .collect(),
+            updated: staged_commit
+                .update_proposals()
+                .filter_map(|update| match update.sender() {
+                    Sender::Member(leaf_index) => Some(*leaf_index),
+                    _ => None,
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns the credentials of the members that are added by the commit.
+    /// Their leaf indices are known once the commit is merged.
+    pub fn added(&self) -> &[Credential] {
+        &self.added
+    }
+
+    /// Returns the leaf indices of the members that are removed by the
+    /// commit.
+    pub fn removed(&self) -> &[LeafNodeIndex] {
+        &self.removed
+    }
+
+    /// Returns the leaf indices of the members whose update proposals are
+    /// committed. The committer isn't included.
+    pub fn updated(&self) -> &[LeafNodeIndex] {
+        &self.updated
+    }
+}
+
+/// The output of an operation that creates a commit. See the
+/// [module documentation](self) for details.
+///
+/// `W` is the type of the Welcome message. It is [`MlsMessageOut`] for
+/// operations that always add members, e.g., [`MlsGroup::add_members()`], and
+/// `Option<MlsMessageOut>` for all others.
+#[derive(Debug, PartialEq)]
+pub struct CommitOutput<W = Option<MlsMessageOut>> {
+    commit: MlsMessageOut,
+    welcome: W,
+    group_info: Option<GroupInfo>,
+    affected_members: AffectedMembers,
+}
+
+impl<W> CommitOutput<W> {
+    pub(super) fn new(
+        commit: MlsMessageOut,
+        welcome: W,
+        group_info: Option<GroupInfo>,
+        affected_members: AffectedMembers,
+    ) -> Self {
+        Self {
+            commit,
+            welcome,
+            group_info,
+            affected_members,
+        }
+    }
+
+    /// Returns the commit.
+    pub fn commit(&self) -> &MlsMessageOut {
+        &self.commit
+    }
+
+    /// Returns the Welcome message for the members that are added by the
+    /// commit.
+    pub fn welcome(&self) -> &W {
+        &self.welcome
+    }
+
+    /// Returns the [`GroupInfo`] of the new epoch if the group has the
+    /// `use_ratchet_tree_extension` flag set.
+    pub fn group_info(&self) -> Option<&GroupInfo> {
+        self.group_info.as_ref()
+    }
+
+    /// Returns the members that are affected by the commit.
+    pub fn affected_members(&self) -> &AffectedMembers {
+        &self.affected_members
+    }
+
+    /// Returns the commit, the Welcome message and the [`GroupInfo`] and
+    /// consumes the [`CommitOutput`].
+    pub fn into_parts(self) -> (MlsMessageOut, W, Option<GroupInfo>) {
+        (self.commit, self.welcome, self.group_info)
+    }
+}
+
+impl CommitOutput {
+    /// Turns the output of a commit that adds members into a
+    /// [`CommitOutput`] with a mandatory Welcome message.
+    pub(super) fn with_welcome(self) -> Result<CommitOutput<MlsMessageOut>, LibraryError> {
+        let welcome = self
+            .welcome
+            .ok_or_else(|| LibraryError::custom("No secrets to generate commit message."))?;
+        Ok(CommitOutput::new(
+            self.commit,
+            welcome,
+            self.group_info,
+            self.affected_members,
+        ))
+    }
+}
@@ -0,0 +1,138 @@
+//! # Commit content policies
+//!
+//! Business rules about what a commit may change, e.g., that only
+//! administrators may remove members or that external commits must not carry
+//! certain proposals, can be declared with a [`CommitContentPolicy`] in the
+//! [`MlsGroupConfig`] instead of being checked by the application after every
+//! processed commit.
+//!
+//! A policy is a list of [`CommitRule`]s and the credential identities of the
+//! administrators of the group. The rules are evaluated by
+//! [`MlsGroup::process_message()`] after an incoming commit was staged.
+//! Commits that violate a rule are rejected with
+//! [`ProcessMessageError::CommitPolicyViolation`] and are not applied.
+//!
+//! Rules are not checked for own commits, since the application already
+//! controls which proposals it commits. The default policy has no rules.
+
+use serde::{Deserialize, Serialize};
+
+use super::*;
+use crate::messages::proposals::ProposalType;
+
+/// A rule about the proposals a commit may contain.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CommitRule {
+    /// Commits by non-administrators must not remove a member and add a
+    /// member with the same credential identity, i.e., must not replace a
+    /// member.
+    NoReplacementByNonAdmins,
+    /// Only administrators may commit proposals of the given type.
+    AdminOnly(ProposalType),
+    /// External commits must not contain proposals of the given type.
+    ForbiddenInExternalCommits(ProposalType),
+    /// Commits must not contain proposals of both types.
+    ForbiddenCombination(ProposalType, ProposalType),
+}
+
+/// The rules that incoming commits have to follow. See the
+/// [module documentation](self) for details.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommitContentPolicy {
+    rules: Vec<CommitRule>,
+    admins: Vec<Vec<u8>>,
+}
+
+impl CommitContentPolicy {
+    /// Adds `rule` to the policy.
+    pub fn with_rule(mut self, rule: CommitRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Sets the credential identities of the administrators of the group.
+    pub fn with_admins(mut self, admins: Vec<Vec<u8>>) -> Self {
+        self.admins = admins;
+        self
+    }
+
+    /// Returns the rules of the policy.
+    pub fn rules(&self) -> &[CommitRule] {
+        &self.rules
+    }
+
+    /// Returns the credential identities of the administrators.
+    pub fn admins(&self) -> &[Vec<u8>] {
+        &self.admins
+    }
+
+    /// Returns `true` if the member with the credential `identity` is an
+    /// administrator.
+    pub fn is_admin(&self, identity: &[u8]) -> bool {
+        self.admins.iter().any(|admin| admin == identity)
+    }
+}
+
+impl MlsGroup {
+    /// Check that the processed message, if it is a commit, follows the
+    /// [`CommitContentPolicy`] of the group.
+    pub(super) fn check_commit_policy(
+        &self,
+        processed_message: &ProcessedMessage,
+    ) -> Result<(), ProcessMessageError> {
+        let staged_commit = match processed_message.content() {
+            ProcessedMessageContent::StagedCommitMessage(staged_commit) => staged_commit,
+            _ => return Ok(()),
+        };
+        let policy = self.configuration().commit_content_policy();
+        let is_admin = policy.is_admin(processed_message.credential().identity());
+        let is_external = matches!(processed_message.sender(), Sender::NewMemberCommit);
+        let contains = |proposal_type: &ProposalType| {
+            staged_commit
+                .queued_proposals()
+                .any(|queued_proposal| queued_proposal.proposal().is_type(*proposal_type))
+        };
+
+        for rule in policy.rules() {
+            let violated = match rule {
+                CommitRule::NoReplacementByNonAdmins => {
+                    !is_admin && self.replaces_member(staged_commit)
+                }
+                CommitRule::AdminOnly(proposal_type) => !is_admin && contains(proposal_type),
+                CommitRule::ForbiddenInExternalCommits(proposal_type) => {
+                    is_external && contains(proposal_type)
+                }
+                CommitRule::ForbiddenCombination(first, second) => {
+                    contains(first) && contains(second)
+                }
+            };
+            if violated {
+                return Err(ProcessMessageError::CommitPolicyViolation(rule.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns `true` if `staged_commit` removes a member and adds a member
+    /// with the same credential identity.
+    fn replaces_member(&self, staged_commit: &StagedCommit) -> bool {
+        let removed_identities: Vec<&[u8]> = staged_commit
+            .remove_proposals()
+            .filter_map(|remove| {
+                self.group
+                    .public_group()
+                    .leaf(remove.remove_proposal().removed())
+            })
+            .map(|leaf_node| leaf_node.credential().identity())
+            .collect();
+        staged_commit.add_proposals().any(|add| {
+            removed_identities.contains(
+                &add.add_proposal()
+                    .key_package()
+                    .leaf_node()
+                    .credential()
+                    .identity(),
+            )
+        })
+    }
+}
@@ -29,7 +29,8 @@
 
 use super::*;
 use crate::{
-    group::config::CryptoConfig, key_packages::Lifetime,
+    group::config::{CommitLimits, CryptoConfig, ValidationPolicy},
+    key_packages::Lifetime,
     tree::sender_ratchet::SenderRatchetConfiguration,
 };
 use serde::{Deserialize, Serialize};
@@ -54,12 +55,45 @@ pub struct MlsGroupConfig {
     pub(crate) required_capabilities: RequiredCapabilitiesExtension,
     /// Senders authorized to send external remove proposals
     pub(crate) external_senders: ExternalSendersExtension,
+    /// Domains the external senders are restricted to
+    pub(crate) external_sender_scopes: ExternalSenderScopesExtension,
     /// Sender ratchet configuration
     pub(crate) sender_ratchet_configuration: SenderRatchetConfiguration,
     /// Lifetime of the own leaf node
     pub(crate) lifetime: Lifetime,
     /// Ciphersuite and protocol version
     pub(crate) crypto_config: CryptoConfig,
+    /// Flag to indicate that merged commits should be recorded in the audit
+    /// log
+    pub(crate) audit_log: bool,
+    /// Limits for the memory usage of the group
+    pub(crate) memory_limits: MemoryLimits,
+    /// Flag to indicate that commits carry sequencing tokens
+    pub(crate) sequencing_tokens: bool,
+    /// Number of epochs for which history keys are kept
+    pub(crate) history_epochs: usize,
+    /// Policy for Remove proposals of external senders
+    pub(crate) moderator_removal_policy: ModeratorRemovalPolicy,
+    /// Policy for scheduled updates of the own leaf
+    pub(crate) self_update_policy: SelfUpdatePolicy,
+    /// Number of past epochs for which epoch authenticators are kept
+    pub(crate) past_epoch_authenticators: usize,
+    /// Policy for the rotation of the external keypair
+    pub(crate) external_key_rotation_policy: ExternalKeyRotationPolicy,
+    /// Policy for the checks of incoming messages that are relaxed
+    pub(crate) validation_policy: ValidationPolicy,
+    /// Policy for the group IDs of created and joined groups
+    #[serde(skip)]
+    pub(crate) group_id_policy: GroupIdPolicy,
+    /// Pinned ciphersuites and protocol versions
+    pub(crate) downgrade_policy: DowngradePolicy,
+    /// Rules about the proposals of incoming commits
+    pub(crate) commit_content_policy: CommitContentPolicy,
+    /// Bounds for incoming commits
+    pub(crate) commit_limits: CommitLimits,
+    /// Number of operations that are kept in the operation journal
+    #[serde(default)]
+    pub(crate) operation_journal: usize,
 }
 
 impl MlsGroupConfig {
@@ -108,6 +142,11 @@ impl MlsGroupConfig {
         &self.external_senders
     }
 
+    /// Returns the [`MlsGroupConfig`] external sender scopes extension
+    pub fn external_sender_scopes(&self) -> &ExternalSenderScopesExtension {
+        &self.external_sender_scopes
+    }
+
     /// Returns the [`MlsGroupConfig`] lifetime configuration.
     pub fn lifetime(&self) -> &Lifetime {
         &self.lifetime
@@ -118,6 +157,81 @@ impl MlsGroupConfig {
         &self.crypto_config
     }
 
+    /// Returns the [`MlsGroupConfig`] boolean flag that indicates whether
+    /// the audit log is enabled.
+    pub fn audit_log(&self) -> bool {
+        self.audit_log
+    }
+
+    /// Returns the [`MlsGroupConfig`] memory limits.
+    pub fn memory_limits(&self) -> &MemoryLimits {
+        &self.memory_limits
+    }
+
+    /// Returns the [`MlsGroupConfig`] boolean flag that indicates whether
+    /// commits carry sequencing tokens.
+    pub fn sequencing_tokens(&self) -> bool {
+        self.sequencing_tokens
+    }
+
+    /// Returns the [`MlsGroupConfig`] number of epochs for which history keys
+    /// are kept.
+    pub fn history_epochs(&self) -> usize {
+        self.history_epochs
+    }
+
+    /// Returns the [`MlsGroupConfig`] moderator removal policy.
+    pub fn moderator_removal_policy(&self) -> ModeratorRemovalPolicy {
+        self.moderator_removal_policy
+    }
+
+    /// Returns the [`MlsGroupConfig`] self-update policy.
+    pub fn self_update_policy(&self) -> SelfUpdatePolicy {
+        self.self_update_policy
+    }
+
+    /// Returns the [`MlsGroupConfig`] number of past epochs for which epoch
+    /// authenticators are kept.
+    pub fn past_epoch_authenticators(&self) -> usize {
+        self.past_epoch_authenticators
+    }
+
+    /// Returns the [`MlsGroupConfig`] external key rotation policy.
+    pub fn external_key_rotation_policy(&self) -> ExternalKeyRotationPolicy {
+        self.external_key_rotation_policy
+    }
+
+    /// Returns the [`MlsGroupConfig`] validation policy.
+    pub fn validation_policy(&self) -> ValidationPolicy {
+        self.validation_policy
+    }
+
+    /// Returns the [`MlsGroupConfig`] group ID policy.
+    pub fn group_id_policy(&self) -> &GroupIdPolicy {
+        &self.group_id_policy
+    }
+
+    /// Returns the [`MlsGroupConfig`] downgrade policy.
+    pub fn downgrade_policy(&self) -> &DowngradePolicy {
+        &self.downgrade_policy
+    }
+
+    /// Returns the [`MlsGroupConfig`] commit content policy.
+    pub fn commit_content_policy(&self) -> &CommitContentPolicy {
+        &self.commit_content_policy
+    }
+
+    /// Returns the [`MlsGroupConfig`] commit limits.
+    pub fn commit_limits(&self) -> CommitLimits {
+        self.commit_limits
+    }
+
+    /// Returns the [`MlsGroupConfig`] number of operations that are kept in
+    /// the operation journal.
+    pub fn operation_journal(&self) -> usize {
+        self.operation_journal
+    }
+
     #[cfg(any(feature = "test-utils", test))]
     pub fn test_default(ciphersuite: Ciphersuite) -> Self {
         Self::builder()
@@ -220,6 +334,141 @@ impl MlsGroupConfigBuilder {
         self
     }
 
+    /// Sets the `external_sender_scopes` property of the MlsGroupConfig. See
+    /// [`ExternalSenderScopesExtension`] for details.
+    pub fn external_sender_scopes(
+        mut self,
+        external_sender_scopes: ExternalSenderScopesExtension,
+    ) -> Self {
+        self.config.external_sender_scopes = external_sender_scopes;
+        self
+    }
+
+    /// Sets the `audit_log` property of the MlsGroupConfig. If enabled, the
+    /// group records every merged commit in its [`AuditLog`].
+    pub fn audit_log(mut self, audit_log: bool) -> Self {
+        self.config.audit_log = audit_log;
+        self
+    }
+
+    /// Sets the `memory_limits` property of the MlsGroupConfig.
+    pub fn memory_limits(mut self, memory_limits: MemoryLimits) -> Self {
+        self.config.memory_limits = memory_limits;
+        self
+    }
+
+    /// Sets the `sequencing_tokens` property of the MlsGroupConfig. If
+    /// enabled, commits carry a sequence number that receivers check for
+    /// monotonicity. See [`SequencedAad`] for details.
+    pub fn sequencing_tokens(mut self, sequencing_tokens: bool) -> Self {
+        self.config.sequencing_tokens = sequencing_tokens;
+        self
+    }
+
+    /// Sets the `history_epochs` property of the MlsGroupConfig. If larger
+    /// than 0, the group keeps the [`HistoryKey`]s of this many epochs so that
+    /// the message history can be shared with new members. See
+    /// [`HistoryBundle`] for details.
+    ///
+    /// **WARNING**
+    ///
+    /// History keys give access to the archived messages of their epochs. The
+    /// number should be as low as possible.
+    pub fn history_epochs(mut self, history_epochs: usize) -> Self {
+        self.config.history_epochs = history_epochs;
+        self
+    }
+
+    /// Sets the `moderator_removal_policy` property of the MlsGroupConfig. It
+    /// defines how Remove proposals of the external senders of the group are
+    /// handled. See [`ModeratorRemovalPolicy`] for details.
+    pub fn moderator_removal_policy(
+        mut self,
+        moderator_removal_policy: ModeratorRemovalPolicy,
+    ) -> Self {
+        self.config.moderator_removal_policy = moderator_removal_policy;
+        self
+    }
+
+    /// Sets the `self_update_policy` property of the MlsGroupConfig. It
+    /// defines when and how [`MlsGroup::maintenance()`] updates the own leaf.
+    /// See [`SelfUpdatePolicy`] for details.
+    pub fn self_update_policy(mut self, self_update_policy: SelfUpdatePolicy) -> Self {
+        self.config.self_update_policy = self_update_policy;
+        self
+    }
+
+    /// Sets the `past_epoch_authenticators` property of the MlsGroupConfig.
+    /// The group keeps the [`EpochAuthenticator`](crate::schedule::EpochAuthenticator)s
+    /// of this many past epochs, so that claims about earlier epochs can be
+    /// verified with [`MlsGroup::epoch_authenticator_for_epoch()`]. The
+    /// default is 0.
+    pub fn past_epoch_authenticators(mut self, past_epoch_authenticators: usize) -> Self {
+        self.config.past_epoch_authenticators = past_epoch_authenticators;
+        self
+    }
+
+    /// Sets the `external_key_rotation_policy` property of the
+    /// MlsGroupConfig. It defines how often the external keypair used for
+    /// external commits is rotated. See [`ExternalKeyRotationPolicy`] for
+    /// details.
+    pub fn external_key_rotation_policy(
+        mut self,
+        external_key_rotation_policy: ExternalKeyRotationPolicy,
+    ) -> Self {
+        self.config.external_key_rotation_policy = external_key_rotation_policy;
+        self
+    }
+
+    /// Sets the `validation_policy` property of the MlsGroupConfig. It
+    /// defines which checks of incoming messages are errors, warnings or
+    /// skipped. The default is [`ValidationPolicy::strict()`].
+    pub fn validation_policy(mut self, validation_policy: ValidationPolicy) -> Self {
+        self.config.validation_policy = validation_policy;
+        self
+    }
+
+    /// Sets the `group_id_policy` property of the MlsGroupConfig. It defines
+    /// which group IDs are accepted when a group is created or joined. See
+    /// [`GroupIdPolicy`] for details.
+    pub fn group_id_policy(mut self, group_id_policy: GroupIdPolicy) -> Self {
+        self.config.group_id_policy = group_id_policy;
+        self
+    }
+
+    /// Sets the `downgrade_policy` property of the MlsGroupConfig. It pins
+    /// the ciphersuites and protocol versions of joined groups and of
+    /// reinitializations. See [`DowngradePolicy`] for details.
+    pub fn downgrade_policy(mut self, downgrade_policy: DowngradePolicy) -> Self {
+        self.config.downgrade_policy = downgrade_policy;
+        self
+    }
+
+    /// Sets the `commit_content_policy` property of the MlsGroupConfig. It
+    /// defines rules about the proposals of incoming commits. See
+    /// [`CommitContentPolicy`] for details.
+    pub fn commit_content_policy(mut self, commit_content_policy: CommitContentPolicy) -> Self {
+        self.config.commit_content_policy = commit_content_policy;
+        self
+    }
+
+    /// Sets the `commit_limits` property of the MlsGroupConfig. It bounds the
+    /// number of proposals, the size and the update path of incoming commits.
+    /// See [`CommitLimits`] for details.
+    pub fn commit_limits(mut self, commit_limits: CommitLimits) -> Self {
+        self.config.commit_limits = commit_limits;
+        self
+    }
+
+    /// Sets the `operation_journal` property of the MlsGroupConfig. If larger
+    /// than 0, the group records its last operations in a journal of this
+    /// size, e.g., to attach them to a bug report. See [`JournalEntry`] for
+    /// details.
+    pub fn operation_journal(mut self, operation_journal: usize) -> Self {
+        self.config.operation_journal = operation_journal;
+        self
+    }
+
     /// Finalizes the builder and retursn an `[MlsGroupConfig`].
     pub fn build(self) -> MlsGroupConfig {
         self.config
@@ -0,0 +1,216 @@
+//! # Commit conflicts
+//!
+//! A delivery service that orders handshake messages optimistically accepts
+//! only one of several concurrent commits for an epoch. A member whose own
+//! commit lost the race has to merge the winning commit instead of its pending
+//! commit. The proposals covered by the lost commit are lost with it, unless
+//! the member proposes them again in the new epoch.
+//!
+//! [`MlsGroup::resolve_commit_conflict()`] does this in one step: it discards
+//! the pending commit, merges the winning commit, and proposes the proposals
+//! of the lost commit that still apply to the new epoch again (by reference).
+//! The proposals that don't apply anymore are reported back to the
+//! application.
+//!
+//! A proposal still applies if:
+//!
+//! * [`Proposal::Add`]: no member of the new epoch uses the signature key or
+//!   the encryption key of the key package, e.g., because the winning commit
+//!   already added it.
+//! * [`Proposal::Remove`]: the removed leaf is still occupied by the same
+//!   member, identified by its signature key.
+//! * [`Proposal::PreSharedKey`]: the pre-shared key is an external one.
+//!
+//! All other proposals, including update proposals of other members, are
+//! tied to the epoch they were sent in and are reported as invalid.
+
+use std::mem;
+
+use openmls_traits::signatures::Signer;
+
+use super::{errors::ResolveCommitConflictError, proposal::Propose, *};
+use crate::{messages::proposals::Proposal, schedule::Psk, treesync::node::leaf_node::LeafNode};
+
+/// A proposal of a lost commit that has been proposed again in the new
+/// epoch.
+#[derive(Debug)]
+pub struct RequeuedProposal {
+    proposal: Proposal,
+    proposal_ref: ProposalRef,
+    message: MlsMessageOut,
+}
+
+impl RequeuedProposal {
+    /// Returns the proposal.
+    pub fn proposal(&self) -> &Proposal {
+        &self.proposal
+    }
+
+    /// Returns the reference of the new proposal.
+    pub fn proposal_ref(&self) -> &ProposalRef {
+        &self.proposal_ref
+    }
+
+    /// Returns the proposal message that has to be sent to the group.
+    pub fn message(&self) -> &MlsMessageOut {
+        &self.message
+    }
+
+    /// Consumes the [`RequeuedProposal`] and returns the proposal message that
+    /// has to be sent to the group.
+    pub fn into_message(self) -> MlsMessageOut {
+        self.message
+    }
+}
+
+/// The outcome of [`MlsGroup::resolve_commit_conflict()`].
+#[derive(Debug)]
+pub struct CommitConflictResolution {
+    requeued: Vec<RequeuedProposal>,
+    invalid: Vec<Proposal>,
+}
+
+impl CommitConflictResolution {
+    /// Returns the proposals that have been proposed again in the new epoch.
+    pub fn requeued(&self) -> &[RequeuedProposal] {
+        &self.requeued
+    }
+
+    /// Returns the proposals of the lost commit that don't apply to the new
+    /// epoch.
+    pub fn invalid(&self) -> &[Proposal] {
+        &self.invalid
+    }
+
+    /// Consumes the [`CommitConflictResolution`] and returns the proposal
+    /// messages that have to be sent to the group.
+    pub fn into_messages(self) -> Vec<MlsMessageOut> {
+        self.requeued
+            .into_iter()
+            .map(RequeuedProposal::into_message)
+            .collect()
+    }
+}
+
+impl MlsGroup {
+    /// Resolve a conflict between the pending commit of this member and the
+    /// `winning_commit` that the delivery service accepted instead. See the
+    /// [module documentation](self) for which proposals are proposed again.
+    ///
+    /// The pending commit is discarded and the `winning_commit` is merged. If
+    /// the winning commit removed this member, all proposals are reported as
+    /// invalid.
+    ///
+    /// Returns an error if there is no pending commit.
+    pub fn resolve_commit_conflict<KeyStore: OpenMlsKeyStore>(
+        &mut self,
+        provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
+        signer: &impl Signer,
+        winning_commit: StagedCommit,
+    ) -> Result<CommitConflictResolution, ResolveCommitConflictError<KeyStore::Error>> {
+        let lost_commit: StagedCommit =
+            match mem::replace(&mut self.group_state, MlsGroupState::Operational) {
+                MlsGroupState::PendingCommit(pending_commit_state) => match *pending_commit_state {
+                    PendingCommitState::Member(staged_commit) => staged_commit,
+                    pending_commit_state => {
+                        self.group_state =
+                            MlsGroupState::PendingCommit(Box::new(pending_commit_state));
+                        return Err(MlsGroupStateError::NoPendingCommit.into());
+                    }
+                },
+                group_state => {
+                    self.group_state = group_state;
+                    return Err(MlsGroupStateError::NoPendingCommit.into());
+                }
+            };
+
+        // Remember who the removed leaves belonged to before the winning
+        // commit changes the tree.
+        let lost_proposals: Vec<(Proposal, Option<LeafNode>)> = lost_commit
+            .queued_proposals()
+            .map(|queued_proposal| {
+                let removed_leaf = match queued_proposal.proposal() {
+                    Proposal::Remove(remove_proposal) => self
+                        .group
+                        .public_group()
+                        .leaf(remove_proposal.removed())
+                        .cloned(),
+                    _ => None,
+                };
+                (queued_proposal.proposal().clone(), removed_leaf)
+            })
+            .collect();
+
+        self.merge_staged_commit(provider, winning_commit)?;
+
+        let mut resolution = CommitConflictResolution {
+            requeued: Vec::new(),
+            invalid: Vec::new(),
+        };
+        for (proposal, removed_leaf) in lost_proposals {
+            let propose = if self.is_active() {
+                self.still_applicable(&proposal, removed_leaf.as_ref())
+            } else {
+                None
+            };
+            match propose {
+                Some(propose) => {
+                    let (message, proposal_ref) =
+                        self.propose(provider, signer, propose, ProposalOrRefType::Reference)?;
+                    resolution.requeued.push(RequeuedProposal {
+                        proposal,
+                        proposal_ref,
+                        message,
+                    });
+                }
+                None => resolution.invalid.push(proposal),
+            }
+        }
+
+        Ok(resolution)
+    }
+
+    /// Returns how to propose `proposal` again in the current epoch, or `None`
+    /// if it doesn't apply anymore.
+    fn still_applicable(
+        &self,
+        proposal: &Proposal,
+        removed_leaf: Option<&LeafNode>,
+    ) -> Option<Propose> {
+        match proposal {
+            Proposal::Add(add_proposal) => {
+                let leaf_node = add_proposal.key_package().leaf_node();
+                let signature_key = leaf_node.signature_key().as_slice();
+                let encryption_key = leaf_node.encryption_key().as_slice();
+                if self.members().any(|member| {
+                    member.signature_key == signature_key || member.encryption_key == encryption_key
+                }) {
+                    return None;
+                }
+                Some(Propose::Add(add_proposal.key_package().clone()))
+            }
+            Proposal::Remove(remove_proposal) => {
+                let removed = remove_proposal.removed();
+                let current_leaf = self.group.public_group().leaf(removed)?;
+                if removed == self.own_leaf_index()
+                    || current_leaf.signature_key() != removed_leaf?.signature_key()
+                {
+                    return None;
+                }
+                Some(Propose::Remove(removed.u32()))
+            }
+            Proposal::PreSharedKey(psk_proposal) => {
+                let psk_id = psk_proposal.clone().into_psk_id();
+                match psk_id.psk() {
+                    Psk::External(_) => Some(Propose::PreSharedKey(psk_id)),
+                    Psk::Resumption(_) => None,
+                }
+            }
+            Proposal::Update(_)
+            | Proposal::GroupContextExtensions(_)
+            | Proposal::ReInit(_)
+            | Proposal::ExternalInit(_)
+            | Proposal::AppAck(_) => None,
+        }
+    }
+}
@@ -0,0 +1,341 @@
+//! # Contact pairs
+//!
+//! Many applications have a conversation for every pair of contacts. A
+//! [`ContactPair`] wraps an [`MlsGroup`] with exactly two members and hides
+//! the proposals and commits of the group behind a simple API:
+//!
+//! * The group ID is derived from the identities of both contacts with
+//!   [`ContactPair::group_id()`], so both of them and the delivery service
+//!   know which group belongs to a pair of contacts without coordination.
+//! * [`ContactPair::new()`] creates the group and returns the Welcome message
+//!   for the peer, who joins with [`ContactPair::join()`].
+//! * [`ContactPair::send()`] and [`ContactPair::receive()`] send and receive
+//!   application data. Commits of the peer are merged when they are received.
+//! * [`ContactPair::update()`] updates the own leaf for post-compromise
+//!   security and merges the commit right away, since there is nobody else
+//!   in the group who could commit concurrently except the peer.
+//!
+//! If either contact loses the state of the group, e.g., after reinstalling
+//! the application, the group is re-initialized: the contact that lost the
+//! state calls [`ContactPair::new()`] again and the contact that still has the
+//! state calls [`ContactPair::reinit()`] if the peer's messages can't be
+//! processed anymore. Since the group ID is deterministic, the new group has
+//! the same group ID as the old one. [`ContactPair::receive()`] replaces the
+//! group with the new one when it receives the Welcome message for it and
+//! returns [`ContactPairEvent::Reinitialized`], provided that the peer's
+//! credential and signature key in the new group are the ones in the old
+//! group.
+//!
+//! If both contacts re-initialize the group concurrently, the group created by
+//! the contact with the smaller identity is kept by both of them. The contact
+//! with the smaller identity ignores the peer's Welcome message without
+//! consuming its own key package.
+//!
+//! Concurrent updates of both contacts are resolved in the same way as in any
+//! other group: the delivery service only accepts one of the commits. If the
+//! commit of the own update is rejected, the group has to be re-initialized.
+
+use openmls_traits::{crypto::OpenMlsCrypto, signatures::Signer, types::Ciphersuite};
+use tls_codec::{Serialize as TlsSerializeTrait, TlsSerialize, TlsSize, VLByteSlice};
+
+use super::{errors::ContactPairError, *};
+use crate::credentials::CredentialWithKey;
+
+/// The label of the input to the group ID derivation of a contact pair.
+const CONTACT_PAIR_LABEL: &[u8] = b"MLS 1.0 contact pair";
+
+/// The input to the group ID derivation of a contact pair. The identities
+/// are sorted, so both contacts derive the same ID.
+#[derive(TlsSerialize, TlsSize)]
+struct ContactPairGroupIdInput<'a> {
+    label: VLByteSlice<'a>,
+    ciphersuite: Ciphersuite,
+    first_identity: VLByteSlice<'a>,
+    second_identity: VLByteSlice<'a>,
+}
+
+/// The result of receiving a message with [`ContactPair::receive()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ContactPairEvent {
+    /// The peer sent application data.
+    Message(Vec<u8>),
+    /// The peer updated the group.
+    Updated,
+    /// The group was re-initialized by the peer.
+    Reinitialized,
+    /// The message didn't require any action from the application, e.g., a
+    /// proposal that is committed with the next [`ContactPair::update()`] or a
+    /// Welcome message for a re-initialized group that lost against the own
+    /// one.
+    Handled,
+}
+
+/// A group of two contacts. See the [module documentation](self) for details.
+#[derive(Debug)]
+pub struct ContactPair {
+    group: MlsGroup,
+    // Whether the group was created by this contact and no message of the
+    // peer was received in it yet.
+    awaiting_peer: bool,
+}
+
+impl ContactPair {
+    /// Derives the group ID of the contact pair with the given identities and
+    /// ciphersuite. The order of the identities doesn't matter.
+    pub fn group_id(
+        crypto: &impl OpenMlsCrypto,
+        ciphersuite: Ciphersuite,
+        identity: &[u8],
+        peer_identity: &[u8],
+    ) -> Result<GroupId, LibraryError> {
+        let (first_identity, second_identity) = if identity <= peer_identity {
+            (identity, peer_identity)
+        } else {
+            (peer_identity, identity)
+        };
+        let input = ContactPairGroupIdInput {
+            label: VLByteSlice(CONTACT_PAIR_LABEL),
+            ciphersuite,
+            first_identity: VLByteSlice(first_identity),
+            second_identity: VLByteSlice(second_identity),
+        }
+        .tls_serialize_detached()
+        .map_err(LibraryError::missing_bound_check)?;
+        let group_id = crypto
+            .hash(ciphersuite.hash_algorithm(), &input)
+            .map_err(LibraryError::unexpected_crypto_error)?;
+        Ok(GroupId::from_slice(&group_id))
+    }
+
+    /// Creates the group of the contact pair with the peer's `key_package`
+    /// and returns it together with the Welcome message for the peer.
+    ///
+    /// This is also used to re-initialize the group after the state of the
+    /// group was lost. The `mls_group_config` must have the
+    /// `use_ratchet_tree_extension` flag set, so that the peer can join
+    /// without a separate ratchet tree.
+    pub fn new<KeyStore: OpenMlsKeyStore>(
+        provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
+        signer: &impl Signer,
+        mls_group_config: &MlsGroupConfig,
+        credential_with_key: CredentialWithKey,
+        key_package: KeyPackage,
+    ) -> Result<(Self, MlsMessageOut), ContactPairError<KeyStore::Error>> {
+        let group_id = Self::group_id(
+            provider.crypto(),
+            mls_group_config.crypto_config().ciphersuite,
+            credential_with_key.credential.identity(),
+            key_package.leaf_node().credential().identity(),
+        )?;
+        let mut group = MlsGroup::new_with_group_id(
+            provider,
+            signer,
+            mls_group_config,
+            group_id,
+            credential_with_key,
+        )?;
+        let (_commit, welcome, _group_info) = group
+            .add_members(provider, signer, &[key_package])?
+            .into_parts();
+        group.merge_pending_commit(provider)?;
+        Ok((
+            Self {
+                group,
+                awaiting_peer: true,
+            },
+            welcome,
+        ))
+    }
+
+    /// Joins the group of a contact pair with the Welcome message from the
+    /// peer.
+    ///
+    /// Returns [`ContactPairError::NotAContactPair`] if the group doesn't have
+    /// exactly two members or its group ID isn't the one of the contact pair.
+    pub fn join<KeyStore: OpenMlsKeyStore>(
+        provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
+        mls_group_config: &MlsGroupConfig,
+        welcome: Welcome,
+    ) -> Result<Self, ContactPairError<KeyStore::Error>> {
+        let group = MlsGroup::new_from_welcome(provider, mls_group_config, welcome, None)?;
+        Self::from_group(provider.crypto(), group)
+    }
+
+    /// Wraps an existing `group`, e.g., one that was loaded from storage.
+    ///
+    /// Returns [`ContactPairError::NotAContactPair`] if the group doesn't have
+    /// exactly two members or its group ID isn't the one of the contact pair.
+    pub fn from_group<KeyStoreError>(
+        crypto: &impl OpenMlsCrypto,
+        group: MlsGroup,
+    ) -> Result<Self, ContactPairError<KeyStoreError>> {
+        let identities = group
+            .members()
+            .map(|member| member.credential.identity().to_vec())
+            .collect::<Vec<_>>();
+        let [identity, peer_identity] = identities.as_slice() else {
+            return Err(ContactPairError::NotAContactPair);
+        };
+        let group_id = Self::group_id(crypto, group.ciphersuite(), identity, peer_identity)?;
+        if &group_id != group.group_id() {
+            return Err(ContactPairError::NotAContactPair);
+        }
+        Ok(Self {
+            group,
+            awaiting_peer: false,
+        })
+    }
+
+    /// Returns the underlying group.
+    pub fn group(&self) -> &MlsGroup {
+        &self.group
+    }
+
+    /// Returns the underlying group and consumes the [`ContactPair`].
+    pub fn into_group(self) -> MlsGroup {
+        self.group
+    }
+
+    /// Returns the credential of the peer.
+    pub fn peer(&self) -> Result<Credential, LibraryError> {
+        self.peer_member().map(|member| member.credential)
+    }
+
+    /// Encrypts `message` for the peer.
+    pub fn send<KeyStore: OpenMlsKeyStore>(
+        &mut self,
+        provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
+        signer: &impl Signer,
+        message: &[u8],
+    ) -> Result<MlsMessageOut, ContactPairError<KeyStore::Error>> {
+        Ok(self.group.create_message(provider, signer, message)?)
+    }
+
+    /// Updates the own leaf and returns the commit for the peer. Proposals
+    /// that were received from the peer are committed as well.
+    pub fn update<KeyStore: OpenMlsKeyStore>(
+        &mut self,
+        provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
+        signer: &impl Signer,
+    ) -> Result<MlsMessageOut, ContactPairError<KeyStore::Error>> {
+        let (commit, _welcome, _group_info) =
+            self.group.self_update(provider, signer)?.into_parts();
+        self.group.merge_pending_commit(provider)?;
+        Ok(commit)
+    }
+
+    /// Re-initializes the group with a new `key_package` of the peer, e.g.,
+    /// because the peer lost the state of the group, and returns the Welcome
+    /// message for the peer. The own credential and signature key are kept.
+    pub fn reinit<KeyStore: OpenMlsKeyStore>(
+        &mut self,
+        provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
+        signer: &impl Signer,
+        key_package: KeyPackage,
+    ) -> Result<MlsMessageOut, ContactPairError<KeyStore::Error>> {
+        let own_leaf = self
+            .group
+            .own_leaf()
+            .ok_or_else(|| LibraryError::custom("The own leaf of a member is in the tree"))?;
+        let credential_with_key = CredentialWithKey {
+            credential: own_leaf.credential().clone(),
+            signature_key: own_leaf.signature_key().clone(),
+        };
+        let mls_group_config = self.group.configuration().clone();
+        let (contact_pair, welcome) = Self::new(
+            provider,
+            signer,
+            &mls_group_config,
+            credential_with_key,
+            key_package,
+        )?;
+        *self = contact_pair;
+        Ok(welcome)
+    }
+
+    /// Processes a message of the peer.
+    ///
+    /// Application data is returned in [`ContactPairEvent::Message`] and
+    /// commits are merged right away. A Welcome message for the contact pair
+    /// replaces the group if the peer re-initialized it.
+    ///
+    /// Returns [`ContactPairError::PeerMismatch`] if the peer's credential or
+    /// signature key in the group of the Welcome message isn't the one in the
+    /// current group. If the peer lost its signature key together with the
+    /// state of the group, the application has to authenticate the new
+    /// credential itself and join the group with [`ContactPair::join()`].
+    pub fn receive<KeyStore: OpenMlsKeyStore>(
+        &mut self,
+        provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
+        message: MlsMessageIn,
+    ) -> Result<ContactPairEvent, ContactPairError<KeyStore::Error>> {
+        let message = match message.extract() {
+            MlsMessageInBody::Welcome(welcome) => return self.receive_welcome(provider, welcome),
+            MlsMessageInBody::PublicMessage(message) => ProtocolMessage::from(message),
+            MlsMessageInBody::PrivateMessage(message) => ProtocolMessage::from(message),
+            MlsMessageInBody::GroupInfo(_) | MlsMessageInBody::KeyPackage(_) => {
+                return Err(ContactPairError::UnexpectedMessage)
+            }
+        };
+        let processed_message = self.group.process_message(provider, message)?;
+        self.awaiting_peer = false;
+        match processed_message.into_content() {
+            ProcessedMessageContent::ApplicationMessage(application_message) => {
+                Ok(ContactPairEvent::Message(application_message.into_bytes()))
+            }
+            ProcessedMessageContent::ProposalMessage(proposal) => {
+                self.group.store_pending_proposal(*proposal);
+                Ok(ContactPairEvent::Handled)
+            }
+            ProcessedMessageContent::StagedCommitMessage(staged_commit) => {
+                self.group.merge_staged_commit(provider, *staged_commit)?;
+                Ok(ContactPairEvent::Updated)
+            }
+            ProcessedMessageContent::ExternalJoinProposalMessage(_) => {
+                Err(ContactPairError::UnexpectedMessage)
+            }
+        }
+    }
+
+    fn receive_welcome<KeyStore: OpenMlsKeyStore>(
+        &mut self,
+        provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
+        welcome: Welcome,
+    ) -> Result<ContactPairEvent, ContactPairError<KeyStore::Error>> {
+        let peer = self.peer_member()?;
+        // If both contacts re-initialized the group concurrently, the group
+        // of the contact with the smaller identity is kept. This is decided
+        // before joining, so that the own key package isn't consumed for a
+        // group that is discarded anyway.
+        if self.awaiting_peer {
+            let own_identity = self
+                .group
+                .own_leaf()
+                .ok_or_else(|| LibraryError::custom("The own leaf of a member is in the tree"))?
+                .credential()
+                .identity();
+            if own_identity < peer.credential.identity() {
+                return Ok(ContactPairEvent::Handled);
+            }
+        }
+        let mls_group_config = self.group.configuration().clone();
+        let contact_pair = Self::join(provider, &mls_group_config, welcome)?;
+        if contact_pair.group.group_id() != self.group.group_id() {
+            return Err(ContactPairError::NotAContactPair);
+        }
+        let new_peer = contact_pair.peer_member()?;
+        if new_peer.credential != peer.credential || new_peer.signature_key != peer.signature_key {
+            return Err(ContactPairError::PeerMismatch);
+        }
+        *self = contact_pair;
+        Ok(ContactPairEvent::Reinitialized)
+    }
+
+    fn peer_member(&self) -> Result<Member, LibraryError> {
+        let own_index = self.group.own_leaf_index();
+        self.group
+            .members()
+            .find(|member| member.index != own_index)
+            .ok_or_else(|| LibraryError::custom("A contact pair has two members"))
+    }
+}
@@ -1,16 +1,23 @@
+use std::collections::HashMap;
+
 use openmls_traits::signatures::Signer;
 
 use super::*;
 use crate::{
-    ciphersuite::HpkePrivateKey,
+    ciphersuite::{hash_ref::KeyPackageRef, HpkePrivateKey},
     credentials::CredentialWithKey,
+    extensions::{Extensions, ParentGroupExtension},
     group::{
-        core_group::create_commit_params::CreateCommitParams,
-        errors::{CoreGroupBuildError, ExternalCommitError, WelcomeError},
-        public_group::errors::PublicGroupBuildError,
+        core_group::{
+            create_commit_params::CreateCommitParams, new_from_welcome::StagedCoreWelcome,
+        },
+        errors::{ExternalCommitError, WelcomeDeclineError, WelcomeError},
     },
+    key_packages::ConsumedKeyPackage,
     messages::group_info::{GroupInfo, VerifiableGroupInfo},
+    metrics::{self, Counter},
     schedule::psk::store::ResumptionPskStore,
+    security_events,
     treesync::RatchetTreeIn,
 };
 
@@ -46,64 +53,42 @@ impl MlsGroup {
         group_id: GroupId,
         credential_with_key: CredentialWithKey,
     ) -> Result<Self, NewGroupError<KeyStore::Error>> {
-        // TODO #751
-        let group_config = CoreGroupConfig {
-            add_ratchet_tree_extension: mls_group_config.use_ratchet_tree_extension,
-        };
-
-        let mut group = CoreGroup::builder(
+        Self::new_with_parent_group(
+            provider,
+            signer,
+            mls_group_config,
             group_id,
-            mls_group_config.crypto_config,
             credential_with_key,
+            None,
         )
-        .with_config(group_config)
-        .with_required_capabilities(mls_group_config.required_capabilities.clone())
-        .with_external_senders(mls_group_config.external_senders.clone())
-        .with_max_past_epoch_secrets(mls_group_config.max_past_epochs)
-        .with_lifetime(*mls_group_config.lifetime())
-        .build(provider, signer)
-        .map_err(|e| match e {
-            CoreGroupBuildError::LibraryError(e) => e.into(),
-            // We don't support PSKs yet
-            CoreGroupBuildError::Psk(e) => {
-                log::debug!("Unexpected PSK error: {:?}", e);
-                LibraryError::custom("Unexpected PSK error").into()
-            }
-            CoreGroupBuildError::KeyStoreError(e) => NewGroupError::KeyStoreError(e),
-            CoreGroupBuildError::PublicGroupBuildError(e) => match e {
-                PublicGroupBuildError::LibraryError(e) => e.into(),
-                PublicGroupBuildError::UnsupportedProposalType => {
-                    NewGroupError::UnsupportedProposalType
-                }
-                PublicGroupBuildError::UnsupportedExtensionType => {
-                    NewGroupError::UnsupportedExtensionType
-                }
-                PublicGroupBuildError::InvalidExtensions(e) => NewGroupError::InvalidExtensions(e),
-            },
-        })?;
-
-        // We already add a resumption PSK for epoch 0 to make things more unified.
-        let resumption_psk = group.group_epoch_secrets().resumption_psk();
-        group
-            .resumption_psk_store
-            .add(group.context().epoch(), resumption_psk.clone());
-
-        let mls_group = MlsGroup {
-            mls_group_config: mls_group_config.clone(),
-            group,
-            proposal_store: ProposalStore::new(),
-            own_leaf_nodes: vec![],
-            aad: vec![],
-            group_state: MlsGroupState::Operational,
-            state_changed: InnerState::Changed,
-        };
+    }
 
-        Ok(mls_group)
+    /// Creates a new group with a given group ID with the creator as the only
+    /// member. If a `parent_group` is given, it is added to the group context
+    /// to link the group to its parent group.
+    pub(super) fn new_with_parent_group<KeyStore: OpenMlsKeyStore>(
+        provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
+        signer: &impl Signer,
+        mls_group_config: &MlsGroupConfig,
+        group_id: GroupId,
+        credential_with_key: CredentialWithKey,
+        parent_group: Option<ParentGroupExtension>,
+    ) -> Result<Self, NewGroupError<KeyStore::Error>> {
+        let mut builder = MlsGroup::builder()
+            .with_group_id(group_id)
+            .with_config(mls_group_config);
+        if let Some(parent_group) = parent_group {
+            builder = builder.with_parent_group(parent_group);
+        }
+        builder.build(provider, signer, credential_with_key)
     }
 
     /// Creates a new group from a [`Welcome`] message. Returns an error
     /// ([`WelcomeError::NoMatchingKeyPackage`]) if no [`KeyPackage`]
     /// can be found.
+    ///
+    /// Use [`StagedWelcome::new_from_welcome()`] to inspect the group before
+    /// joining it.
     // TODO: #1326 This should take an MlsMessage rather than a Welcome message.
     pub fn new_from_welcome<KeyStore: OpenMlsKeyStore>(
         provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
@@ -111,61 +96,48 @@ impl MlsGroup {
         welcome: Welcome,
         ratchet_tree: Option<RatchetTreeIn>,
     ) -> Result<Self, WelcomeError<KeyStore::Error>> {
-        let resumption_psk_store =
-            ResumptionPskStore::new(mls_group_config.number_of_resumption_psks);
-        let (key_package, _) = welcome
-            .secrets()
-            .iter()
-            .find_map(|egs| {
-                let hash_ref = egs.new_member().as_slice().to_vec();
-                provider
-                    .key_store()
-                    .read(&hash_ref)
-                    .map(|kp: KeyPackage| (kp, hash_ref))
-            })
-            .ok_or(WelcomeError::NoMatchingKeyPackage)?;
-
-        // TODO #751
-        let private_key = provider
-            .key_store()
-            .read::<HpkePrivateKey>(key_package.hpke_init_key().as_slice())
-            .ok_or(WelcomeError::NoMatchingKeyPackage)?;
-        let key_package_bundle = KeyPackageBundle {
-            key_package,
-            private_key,
-        };
-
-        // Delete the [`KeyPackage`] and the corresponding private key from the
-        // key store, but only if it doesn't have a last resort extension.
-        if !key_package_bundle.key_package().last_resort() {
-            key_package_bundle
-                .key_package
-                .delete(provider)
-                .map_err(WelcomeError::KeyStoreError)?;
-        } else {
-            log::debug!("Key package has last resort extension, not deleting");
-        }
+        StagedWelcome::new_from_welcome(provider, mls_group_config, welcome, ratchet_tree)?
+            .into_group(provider)
+    }
 
-        let mut group = CoreGroup::new_from_welcome(
-            welcome,
-            ratchet_tree,
-            key_package_bundle,
+    /// Creates a new group from a [`Welcome`] message like
+    /// [`MlsGroup::new_from_welcome()`], but fetches the ratchet tree with
+    /// `fetch_ratchet_tree` if the Welcome message doesn't contain it.
+    ///
+    /// `fetch_ratchet_tree` is called with the tree hash of the group after
+    /// the Welcome message was decrypted, e.g., to download the tree from the
+    /// delivery service. It returns `None` if the tree isn't available, in
+    /// which case an error ([`WelcomeError::MissingRatchetTree`]) is returned.
+    pub fn new_from_welcome_fetching_tree<KeyStore: OpenMlsKeyStore>(
+        provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
+        mls_group_config: &MlsGroupConfig,
+        welcome: Welcome,
+        fetch_ratchet_tree: impl FnOnce(&[u8]) -> Option<RatchetTreeIn>,
+    ) -> Result<Self, WelcomeError<KeyStore::Error>> {
+        StagedWelcome::new_from_welcome_fetching_tree(
             provider,
-            resumption_psk_store,
-        )?;
-        group.set_max_past_epochs(mls_group_config.max_past_epochs);
-
-        let mls_group = MlsGroup {
-            mls_group_config: mls_group_config.clone(),
-            group,
-            proposal_store: ProposalStore::new(),
-            own_leaf_nodes: vec![],
-            aad: vec![],
-            group_state: MlsGroupState::Operational,
-            state_changed: InnerState::Changed,
-        };
+            mls_group_config,
+            welcome,
+            fetch_ratchet_tree,
+        )?
+        .into_group(provider)
+    }
 
-        Ok(mls_group)
+    /// Creates new groups from many [`Welcome`] messages at once. The
+    /// Welcome messages are staged with [`StagedWelcome::new_from_welcomes()`]
+    /// before any group is joined, so that the key packages are read once
+    /// and before they are deleted.
+    ///
+    /// Returns one result per Welcome message, in the order of `welcomes`.
+    pub fn new_from_welcomes<KeyStore: OpenMlsKeyStore>(
+        provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
+        mls_group_config: &MlsGroupConfig,
+        welcomes: Vec<(Welcome, Option<RatchetTreeIn>)>,
+    ) -> Vec<Result<Self, WelcomeError<KeyStore::Error>>> {
+        StagedWelcome::new_from_welcomes(provider, mls_group_config, welcomes)
+            .into_iter()
+            .map(|staged_welcome| staged_welcome?.into_group(provider))
+            .collect()
     }
 
     /// Join an existing group through an External Commit.
@@ -182,6 +154,10 @@ impl MlsGroup {
     ///
     /// Note: If there is a group member in the group with the same identity as
     /// us, this will create a remove proposal.
+    ///
+    /// If the GroupInfo advertises a rotated external keypair, the external
+    /// commit identifies the keypair in its authenticated data and `aad` is
+    /// not used. See [`ExternalKeyRotationPolicy`] for details.
     pub fn join_by_external_commit(
         provider: &impl OpenMlsProvider,
         signer: &impl Signer,
@@ -191,8 +167,22 @@ impl MlsGroup {
         aad: &[u8],
         credential_with_key: CredentialWithKey,
     ) -> Result<(Self, MlsMessageOut, Option<GroupInfo>), ExternalCommitError> {
+        if !mls_group_config
+            .group_id_policy
+            .accepts(verifiable_group_info.group_id())
+        {
+            return Err(ExternalCommitError::InvalidGroupId);
+        }
+        if !mls_group_config.downgrade_policy.accepts(
+            verifiable_group_info.ciphersuite(),
+            verifiable_group_info.protocol_version(),
+        ) {
+            return Err(ExternalCommitError::DowngradeAttempt);
+        }
+
         // Prepare the commit parameters
-        let framing_parameters = FramingParameters::new(aad, WireFormat::PublicMessage);
+        let aad = external_keys::external_commit_aad(&verifiable_group_info, aad)?;
+        let framing_parameters = FramingParameters::new(&aad, WireFormat::PublicMessage);
 
         let proposal_store = ProposalStore::new();
         let params = CreateCommitParams::builder()
@@ -208,8 +198,11 @@ impl MlsGroup {
             verifiable_group_info,
         )?;
         group.set_max_past_epochs(mls_group_config.max_past_epochs);
+        group.set_external_key_rotation_policy(mls_group_config.external_key_rotation_policy);
+        group.set_validation_policy(mls_group_config.validation_policy);
+        group.set_commit_limits(mls_group_config.commit_limits);
 
-        let mls_group = MlsGroup {
+        let mut mls_group = MlsGroup {
             mls_group_config: mls_group_config.clone(),
             group,
             proposal_store: ProposalStore::new(),
@@ -219,14 +212,418 @@ impl MlsGroup {
                 create_commit_result.staged_commit,
             ))),
             state_changed: InnerState::Changed,
+            audit_log: None,
+            sequencing: Default::default(),
+            stale_artifacts: None,
+            history_keys: vec![],
+            commit_metadata: None,
+            leaf_age: None,
+            declined_members: Vec::new(),
+            issued_welcome: None,
+            pending_commit_messages: None,
+            past_epoch_authenticators: Vec::new(),
+            snapshot_active: false,
+            operation_journal: Default::default(),
+            exporter_cache: Default::default(),
+            epoch_subscribers: Default::default(),
         };
 
         let public_message: PublicMessage = create_commit_result.commit.into();
+        let mls_message: MlsMessageOut = public_message.into();
+        mls_group.store_pending_commit_messages(
+            &mls_message,
+            None,
+            create_commit_result.group_info.as_ref(),
+        )?;
+
+        Ok((mls_group, mls_message, create_commit_result.group_info))
+    }
+}
+
+/// The parameters that the group of a [`Welcome`] message is expected to
+/// have, e.g., because the application showed them to the user when the
+/// invitation was accepted. They are checked by
+/// [`StagedWelcome::new_from_welcome_with_expectations()`], which prevents
+/// that a client joins a different group than the one the user agreed to.
+///
+/// Parameters that aren't set aren't checked.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WelcomeExpectations {
+    group_id: Option<GroupId>,
+    ciphersuite: Option<Ciphersuite>,
+    inviter_identity: Option<Vec<u8>>,
+    max_members: Option<usize>,
+}
+
+impl WelcomeExpectations {
+    /// Creates new [`WelcomeExpectations`] that don't check anything.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Expects the group to have the given group ID.
+    pub fn group_id(mut self, group_id: GroupId) -> Self {
+        self.group_id = Some(group_id);
+        self
+    }
+
+    /// Expects the group to use the given ciphersuite.
+    pub fn ciphersuite(mut self, ciphersuite: Ciphersuite) -> Self {
+        self.ciphersuite = Some(ciphersuite);
+        self
+    }
+
+    /// Expects the Welcome message to be created by the member with the
+    /// given identity.
+    pub fn inviter_identity(mut self, identity: Vec<u8>) -> Self {
+        self.inviter_identity = Some(identity);
+        self
+    }
+
+    /// Expects the group to have at most `max_members` members, including
+    /// the new member.
+    pub fn max_members(mut self, max_members: usize) -> Self {
+        self.max_members = Some(max_members);
+        self
+    }
+}
+
+/// A group that was staged from a [`Welcome`] message, but not yet joined.
+///
+/// Processing a Welcome message with [`StagedWelcome::new_from_welcome()`]
+/// decrypts and validates the group, but doesn't write to the key store. The
+/// application can inspect the group, e.g., to show the user who invited them
+/// to which group, and then either join the group with
+/// [`StagedWelcome::into_group()`] or decline the invitation with
+/// [`StagedWelcome::decline()`] or by dropping the [`StagedWelcome`]. The key
+/// package that the Welcome message was encrypted to is only deleted from the
+/// key store when the group is joined.
+#[derive(Debug)]
+pub struct StagedWelcome {
+    mls_group_config: MlsGroupConfig,
+    key_package_bundle: KeyPackageBundle,
+    key_package_ref: KeyPackageRef,
+    staged_welcome: StagedCoreWelcome,
+}
+
+impl StagedWelcome {
+    /// Stages a group from a [`Welcome`] message. Returns an error
+    /// ([`WelcomeError::NoMatchingKeyPackage`]) if no [`KeyPackage`]
+    /// can be found.
+    pub fn new_from_welcome<KeyStore: OpenMlsKeyStore>(
+        provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
+        mls_group_config: &MlsGroupConfig,
+        welcome: Welcome,
+        ratchet_tree: Option<RatchetTreeIn>,
+    ) -> Result<Self, WelcomeError<KeyStore::Error>> {
+        Self::new_from_welcome_fetching_tree(provider, mls_group_config, welcome, |_| ratchet_tree)
+    }
+
+    /// Stages a group from a [`Welcome`] message like
+    /// [`StagedWelcome::new_from_welcome()`] and checks that the group
+    /// matches the `expectations`. Returns an error
+    /// ([`WelcomeError::UnexpectedGroupId`],
+    /// [`WelcomeError::UnexpectedCiphersuite`],
+    /// [`WelcomeError::UnexpectedInviter`] or
+    /// [`WelcomeError::TooManyMembers`]) if it doesn't.
+    pub fn new_from_welcome_with_expectations<KeyStore: OpenMlsKeyStore>(
+        provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
+        mls_group_config: &MlsGroupConfig,
+        welcome: Welcome,
+        ratchet_tree: Option<RatchetTreeIn>,
+        expectations: &WelcomeExpectations,
+    ) -> Result<Self, WelcomeError<KeyStore::Error>> {
+        // The ciphersuite is checked before the Welcome message is decrypted.
+        if expectations
+            .ciphersuite
+            .is_some_and(|ciphersuite| ciphersuite != welcome.ciphersuite())
+        {
+            return Err(WelcomeError::UnexpectedCiphersuite);
+        }
+        let staged_welcome =
+            Self::new_from_welcome(provider, mls_group_config, welcome, ratchet_tree)?;
+
+        if expectations
+            .group_id
+            .as_ref()
+            .is_some_and(|group_id| group_id != staged_welcome.group_id())
+        {
+            return Err(WelcomeError::UnexpectedGroupId);
+        }
+        if let Some(inviter_identity) = &expectations.inviter_identity {
+            if staged_welcome.welcome_sender()?.identity() != inviter_identity.as_slice() {
+                return Err(WelcomeError::UnexpectedInviter);
+            }
+        }
+        if expectations
+            .max_members
+            .is_some_and(|max_members| staged_welcome.members().count() > max_members)
+        {
+            return Err(WelcomeError::TooManyMembers);
+        }
+        Ok(staged_welcome)
+    }
+
+    /// Stages a group from a [`Welcome`] message like
+    /// [`StagedWelcome::new_from_welcome()`], but fetches the ratchet tree
+    /// with `fetch_ratchet_tree` if the Welcome message doesn't contain it.
+    /// See [`MlsGroup::new_from_welcome_fetching_tree()`] for details.
+    pub fn new_from_welcome_fetching_tree<KeyStore: OpenMlsKeyStore>(
+        provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
+        mls_group_config: &MlsGroupConfig,
+        welcome: Welcome,
+        fetch_ratchet_tree: impl FnOnce(&[u8]) -> Option<RatchetTreeIn>,
+    ) -> Result<Self, WelcomeError<KeyStore::Error>> {
+        Self::stage(
+            provider,
+            mls_group_config,
+            welcome,
+            fetch_ratchet_tree,
+            |key_package_ref| provider.key_store().read(key_package_ref.as_slice()),
+        )
+    }
+
+    /// Stages groups from many [`Welcome`] messages at once, e.g., when a new
+    /// device joins all groups of its user. Each key package is read from
+    /// the key store only once, also if several Welcome messages are for the
+    /// same (last resort) key package, and nothing is written to the key
+    /// store.
+    ///
+    /// Returns one result per Welcome message, in the order of `welcomes`.
+    /// The groups can be joined with [`MlsGroup::new_from_welcomes()`] or
+    /// one by one with [`StagedWelcome::into_group()`].
+    pub fn new_from_welcomes<KeyStore: OpenMlsKeyStore>(
+        provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
+        mls_group_config: &MlsGroupConfig,
+        welcomes: Vec<(Welcome, Option<RatchetTreeIn>)>,
+    ) -> Vec<Result<Self, WelcomeError<KeyStore::Error>>> {
+        let mut key_packages = HashMap::new();
+        for secrets in welcomes.iter().flat_map(|(welcome, _)| welcome.secrets()) {
+            key_packages.entry(secrets.new_member()).or_insert_with_key(
+                |key_package_ref: &KeyPackageRef| {
+                    provider
+                        .key_store()
+                        .read::<KeyPackage>(key_package_ref.as_slice())
+                },
+            );
+        }
+        welcomes
+            .into_iter()
+            .map(|(welcome, ratchet_tree)| {
+                Self::stage(
+                    provider,
+                    mls_group_config,
+                    welcome,
+                    |_| ratchet_tree,
+                    |key_package_ref| key_packages.get(key_package_ref).cloned().flatten(),
+                )
+            })
+            .collect()
+    }
+
+    /// Stages a group from a [`Welcome`] message with the key package that
+    /// `read_key_package` returns for the first matching reference.
+    fn stage<KeyStore: OpenMlsKeyStore>(
+        provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
+        mls_group_config: &MlsGroupConfig,
+        welcome: Welcome,
+        fetch_ratchet_tree: impl FnOnce(&[u8]) -> Option<RatchetTreeIn>,
+        read_key_package: impl Fn(&KeyPackageRef) -> Option<KeyPackage>,
+    ) -> Result<Self, WelcomeError<KeyStore::Error>> {
+        let resumption_psk_store =
+            ResumptionPskStore::new(mls_group_config.number_of_resumption_psks);
+        let Some((key_package, key_package_ref)) = welcome.secrets().iter().find_map(|egs| {
+            let hash_ref = egs.new_member();
+            read_key_package(&hash_ref).map(|kp| (kp, hash_ref))
+        }) else {
+            let consumed = welcome
+                .secrets()
+                .iter()
+                .any(|egs| KeyPackage::is_consumed(provider.key_store(), &egs.new_member()));
+            return Err(if consumed {
+                WelcomeError::KeyPackageConsumed
+            } else {
+                WelcomeError::NoMatchingKeyPackage
+            });
+        };
+
+        // TODO #751
+        let private_key = provider
+            .key_store()
+            .read::<HpkePrivateKey>(key_package.hpke_init_key().as_slice())
+            .ok_or(WelcomeError::NoMatchingKeyPackage)?;
+        let key_package_bundle = KeyPackageBundle {
+            key_package,
+            private_key,
+        };
 
-        Ok((
-            mls_group,
-            public_message.into(),
-            create_commit_result.group_info,
-        ))
+        let staged_welcome = CoreGroup::stage_welcome(
+            welcome,
+            fetch_ratchet_tree,
+            key_package_bundle.clone(),
+            provider,
+            resumption_psk_store,
+        )
+        .inspect_err(security_events::record_welcome_error)?;
+        if !mls_group_config
+            .group_id_policy
+            .accepts(staged_welcome.group().group_id())
+        {
+            return Err(WelcomeError::InvalidGroupId);
+        }
+        if !mls_group_config.downgrade_policy.accepts(
+            staged_welcome.group().ciphersuite(),
+            staged_welcome.group().version(),
+        ) {
+            return Err(WelcomeError::DowngradeAttempt);
+        }
+
+        Ok(Self {
+            mls_group_config: mls_group_config.clone(),
+            key_package_bundle,
+            key_package_ref,
+            staged_welcome,
+        })
+    }
+
+    /// Returns the leaf index of the member that created the Welcome message.
+    pub fn welcome_sender_index(&self) -> LeafNodeIndex {
+        self.staged_welcome.welcome_sender_index()
+    }
+
+    /// Returns the credential of the member that created the Welcome message.
+    pub fn welcome_sender(&self) -> Result<&Credential, LibraryError> {
+        self.staged_welcome
+            .group()
+            .public_group()
+            .leaf(self.welcome_sender_index())
+            .map(|leaf| leaf.credential())
+            .ok_or_else(|| LibraryError::custom("The sender of the Welcome is in the tree"))
+    }
+
+    /// Returns the ciphersuite of the group.
+    pub fn ciphersuite(&self) -> Ciphersuite {
+        self.staged_welcome.group().ciphersuite()
+    }
+
+    /// Returns the group ID of the group.
+    pub fn group_id(&self) -> &GroupId {
+        self.staged_welcome.group().group_id()
+    }
+
+    /// Returns the epoch of the group.
+    pub fn epoch(&self) -> GroupEpoch {
+        self.staged_welcome.group().context().epoch()
+    }
+
+    /// Returns the members of the group, including the own leaf.
+    pub fn members(&self) -> impl Iterator<Item = Member> + '_ {
+        self.staged_welcome.group().public_group().members()
+    }
+
+    /// Returns the leaf index of the own leaf in the group.
+    pub fn own_leaf_index(&self) -> LeafNodeIndex {
+        self.staged_welcome.group().own_leaf_index()
+    }
+
+    /// Returns the extensions in the group context of the group.
+    pub fn group_context_extensions(&self) -> &Extensions {
+        self.staged_welcome.group().context().extensions()
+    }
+
+    /// Returns the extensions in the GroupInfo of the Welcome message,
+    /// including unknown extensions, e.g., metadata of the application such
+    /// as the name of the group. Unlike the group context extensions, they
+    /// are only signed by the sender of the Welcome message and not agreed
+    /// on by the group.
+    pub fn group_info_extensions(&self) -> &Extensions {
+        self.staged_welcome.group_info_extensions()
+    }
+
+    /// Returns the reference of the key package that the Welcome message was
+    /// encrypted to if it is consumed when the group is joined, or `None` if
+    /// it has a last resort extension. Once the group was joined with
+    /// [`StagedWelcome::into_group()`], the key package can be deleted from
+    /// the delivery service.
+    pub fn consumed_key_package(&self) -> Option<&KeyPackageRef> {
+        (!self.key_package_bundle.key_package().last_resort()).then_some(&self.key_package_ref)
+    }
+
+    /// Joins the group and stores the keys of the group. Unless it has a last
+    /// resort extension, the key package that the Welcome message was
+    /// encrypted to is deleted from the key store and marked as consumed (see
+    /// [`KeyPackage::is_consumed()`]). Staging the Welcome message again then
+    /// fails with [`WelcomeError::KeyPackageConsumed`].
+    ///
+    /// All values are computed before the key store is written to, and new
+    /// values are stored before old values are deleted.
+    pub fn into_group<KeyStore: OpenMlsKeyStore>(
+        self,
+        provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
+    ) -> Result<MlsGroup, WelcomeError<KeyStore::Error>> {
+        let consumed_key_package = self.consumed_key_package().cloned();
+        let mut group = self.staged_welcome.into_core_group(provider)?;
+        group.set_max_past_epochs(self.mls_group_config.max_past_epochs);
+        group.set_external_key_rotation_policy(self.mls_group_config.external_key_rotation_policy);
+        group.set_validation_policy(self.mls_group_config.validation_policy);
+        group.set_commit_limits(self.mls_group_config.commit_limits);
+
+        // Mark the [`KeyPackage`] as consumed and delete it and the
+        // corresponding private key from the key store, but only if it
+        // doesn't have a last resort extension.
+        match consumed_key_package {
+            Some(key_package_ref) => {
+                ConsumedKeyPackage::store(provider.key_store(), key_package_ref)
+                    .map_err(WelcomeError::KeyStoreError)?;
+                self.key_package_bundle
+                    .key_package
+                    .delete(provider)
+                    .map_err(WelcomeError::KeyStoreError)?;
+            }
+            None => log::debug!("Key package has last resort extension, not deleting"),
+        }
+        metrics::increment_counter(Counter::KeyPackageConsumed);
+
+        let mut mls_group = MlsGroup {
+            mls_group_config: self.mls_group_config,
+            group,
+            proposal_store: ProposalStore::new(),
+            own_leaf_nodes: vec![],
+            aad: vec![],
+            group_state: MlsGroupState::Operational,
+            state_changed: InnerState::Changed,
+            audit_log: None,
+            sequencing: Default::default(),
+            stale_artifacts: None,
+            history_keys: vec![],
+            commit_metadata: None,
+            leaf_age: None,
+            declined_members: Vec::new(),
+            issued_welcome: None,
+            pending_commit_messages: None,
+            past_epoch_authenticators: Vec::new(),
+            snapshot_active: false,
+            operation_journal: Default::default(),
+            exporter_cache: Default::default(),
+            epoch_subscribers: Default::default(),
+        };
+        mls_group.store_history_key(provider.crypto())?;
+
+        Ok(mls_group)
+    }
+
+    /// Declines the invitation and returns a [`WelcomeDecline`] that tells the
+    /// other members of the group that the own leaf won't be used. The
+    /// decline is signed with the `signer` of the own leaf. The key store
+    /// isn't changed.
+    pub fn decline(self, signer: &impl Signer) -> Result<WelcomeDecline, WelcomeDeclineError> {
+        let group = self.staged_welcome.group();
+        WelcomeDecline::new(
+            signer,
+            group.group_id().clone(),
+            group.context().epoch(),
+            group.own_leaf_index(),
+            group.own_leaf_node()?.encryption_key().clone(),
+        )
     }
 }
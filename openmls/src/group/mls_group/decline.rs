@@ -0,0 +1,216 @@
+//! # Declining Welcome messages
+//!
+//! A member that is added to a group is part of the ratchet tree as soon as
+//! the commit that adds it is merged. If the new member declines the
+//! invitation, e.g., because the user doesn't want to join the group, the
+//! other members wouldn't notice and keep encrypting to a member that never
+//! reads their messages.
+//!
+//! Instead of dropping the [`StagedWelcome`], the new member can decline the
+//! invitation with [`StagedWelcome::decline()`], which returns a
+//! [`WelcomeDecline`] for the other members. The application sends it to the
+//! inviter or to the whole group, e.g., through the delivery service. The
+//! decline is signed with the signature key of the new member and references
+//! the encryption key of the leaf that the Welcome message was created for,
+//! so it is bound to this specific invitation and can't be replayed after the
+//! member rejoined the group.
+//!
+//! Members process the decline with [`MlsGroup::process_welcome_decline()`],
+//! which flags the member as declined. [`MlsGroup::declined_members()`] lists
+//! the declined members until they are removed, e.g., with
+//! [`MlsGroup::remove_members()`].
+
+use openmls_traits::{crypto::OpenMlsCrypto, signatures::Signer};
+use tls_codec::{Serialize as TlsSerializeTrait, TlsDeserialize, TlsSerialize, TlsSize};
+
+use super::{errors::WelcomeDeclineError, *};
+use crate::{
+    ciphersuite::{
+        signable::{Signable, SignedStruct, Verifiable},
+        Signature,
+    },
+    treesync::node::encryption_keys::EncryptionKey,
+};
+
+const SIGNATURE_WELCOME_DECLINE_LABEL: &str = "WelcomeDeclineTBS";
+
+/// The signed content of a [`WelcomeDecline`].
+///
+/// ```c
+/// struct {
+///     opaque group_id<V>;
+///     uint64 epoch;
+///     uint32 leaf_index;
+///     HPKEPublicKey encryption_key;
+/// } WelcomeDeclineTBS;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, TlsSerialize, TlsSize)]
+struct WelcomeDeclineTbs {
+    group_id: GroupId,
+    epoch: GroupEpoch,
+    leaf_index: LeafNodeIndex,
+    encryption_key: EncryptionKey,
+}
+
+impl Signable for WelcomeDeclineTbs {
+    type SignedOutput = WelcomeDecline;
+
+    fn unsigned_payload(&self) -> Result<Vec<u8>, tls_codec::Error> {
+        self.tls_serialize_detached()
+    }
+
+    fn label(&self) -> &str {
+        SIGNATURE_WELCOME_DECLINE_LABEL
+    }
+}
+
+/// A signed message of a new member that declines the invitation to a group.
+/// See the [module documentation](self) for details.
+#[derive(
+    Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TlsSerialize, TlsDeserialize, TlsSize,
+)]
+pub struct WelcomeDecline {
+    group_id: GroupId,
+    epoch: GroupEpoch,
+    leaf_index: LeafNodeIndex,
+    encryption_key: EncryptionKey,
+    signature: Signature,
+}
+
+impl SignedStruct<WelcomeDeclineTbs> for WelcomeDecline {
+    fn from_payload(payload: WelcomeDeclineTbs, signature: Signature) -> Self {
+        Self {
+            group_id: payload.group_id,
+            epoch: payload.epoch,
+            leaf_index: payload.leaf_index,
+            encryption_key: payload.encryption_key,
+            signature,
+        }
+    }
+}
+
+impl Verifiable for WelcomeDecline {
+    fn unsigned_payload(&self) -> Result<Vec<u8>, tls_codec::Error> {
+        WelcomeDeclineTbs {
+            group_id: self.group_id.clone(),
+            epoch: self.epoch,
+            leaf_index: self.leaf_index,
+            encryption_key: self.encryption_key.clone(),
+        }
+        .tls_serialize_detached()
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn label(&self) -> &str {
+        SIGNATURE_WELCOME_DECLINE_LABEL
+    }
+}
+
+impl WelcomeDecline {
+    /// Creates and signs a new [`WelcomeDecline`].
+    pub(super) fn new(
+        signer: &impl Signer,
+        group_id: GroupId,
+        epoch: GroupEpoch,
+        leaf_index: LeafNodeIndex,
+        encryption_key: EncryptionKey,
+    ) -> Result<Self, WelcomeDeclineError> {
+        Ok(WelcomeDeclineTbs {
+            group_id,
+            epoch,
+            leaf_index,
+            encryption_key,
+        }
+        .sign(signer)?)
+    }
+
+    /// Returns the ID of the group whose invitation was declined.
+    pub fn group_id(&self) -> &GroupId {
+        &self.group_id
+    }
+
+    /// Returns the epoch in which the declining member was added.
+    pub fn epoch(&self) -> GroupEpoch {
+        self.epoch
+    }
+
+    /// Returns the leaf index of the declining member.
+    pub fn leaf_index(&self) -> LeafNodeIndex {
+        self.leaf_index
+    }
+}
+
+/// A member that declined the invitation to the group.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct DeclinedMember {
+    leaf_index: LeafNodeIndex,
+    encryption_key: EncryptionKey,
+}
+
+impl MlsGroup {
+    /// Processes a [`WelcomeDecline`] of a member and flags the member as
+    /// declined. See the [module documentation](self) for details.
+    ///
+    /// Returns the leaf index of the declining member, or an error if the
+    /// decline is for another group, if its signature is invalid or if the
+    /// leaf that was created for the invitation isn't in the group anymore,
+    /// e.g., because the member was removed or updated its leaf.
+    pub fn process_welcome_decline(
+        &mut self,
+        crypto: &impl OpenMlsCrypto,
+        decline: WelcomeDecline,
+    ) -> Result<LeafNodeIndex, WelcomeDeclineError> {
+        if decline.group_id() != self.group_id() {
+            return Err(WelcomeDeclineError::WrongGroup);
+        }
+        let leaf_node = self
+            .group
+            .public_group()
+            .leaf(decline.leaf_index)
+            .filter(|leaf_node| leaf_node.encryption_key() == &decline.encryption_key)
+            .ok_or(WelcomeDeclineError::UnknownMember)?;
+        let signature_key = leaf_node
+            .signature_key()
+            .clone()
+            .into_signature_public_key_enriched(self.ciphersuite().signature_algorithm());
+        decline.verify_no_out(crypto, &signature_key)?;
+
+        if !self
+            .declined_members
+            .iter()
+            .any(|declined_member| declined_member.leaf_index == decline.leaf_index)
+        {
+            self.declined_members.push(DeclinedMember {
+                leaf_index: decline.leaf_index,
+                encryption_key: decline.encryption_key,
+            });
+            self.flag_state_change();
+        }
+        Ok(decline.leaf_index)
+    }
+
+    /// Returns the leaf indices of the members that declined their invitation
+    /// and weren't removed yet.
+    pub fn declined_members(&self) -> Vec<LeafNodeIndex> {
+        self.declined_members
+            .iter()
+            .map(|declined_member| declined_member.leaf_index)
+            .collect()
+    }
+
+    /// Drops the declined members whose leaf changed, e.g., because they were
+    /// removed.
+    pub(super) fn prune_declined_members(&mut self) {
+        let public_group = self.group.public_group();
+        self.declined_members.retain(|declined_member| {
+            public_group
+                .leaf(declined_member.leaf_index)
+                .is_some_and(|leaf_node| {
+                    leaf_node.encryption_key() == &declined_member.encryption_key
+                })
+        });
+    }
+}
@@ -0,0 +1,212 @@
+//! # Multiple devices
+//!
+//! In many applications, a user has several devices, and each device is a
+//! separate member of the user's groups with its own leaf, credential and
+//! signature key. The [`DeviceManager`] helps with keeping the devices of a
+//! user in sync across groups. It is built on top of
+//! [`MlsGroup::add_members()`] and [`MlsGroup::remove_members()`]:
+//!
+//! * [`DeviceManager::add_device()`] adds a new device to all groups of the
+//!   user, e.g., after the user linked a new phone.
+//! * [`DeviceManager::remove_device()`] removes a device from all groups of
+//!   the user, e.g., after the device was lost.
+//! * [`DeviceManager::devices()`] lists the devices of a user in a group.
+//!
+//! OpenMLS doesn't know which credentials belong to the same user. The
+//! application provides this mapping with a [`UserIdentity`], e.g., a closure
+//! that extracts the user ID from credential identities like
+//! `alice@example.com/phone`.
+//!
+//! Adding and removing devices creates a pending commit in every affected
+//! group. As with the underlying functions, the application has to send the
+//! commits and Welcome messages to the delivery service and merge the
+//! pending commits once they are accepted. Since the groups are independent,
+//! the result is reported per group and a failure in one group doesn't affect
+//! the others.
+
+use openmls_traits::signatures::Signer;
+
+use super::{errors::DeviceError, *};
+use crate::messages::group_info::GroupInfo;
+
+/// Maps credentials to the users they belong to.
+///
+/// The trait is implemented for closures of the type
+/// `Fn(&Credential) -> Option<Vec<u8>>`.
+pub trait UserIdentity {
+    /// Returns the ID of the user that the `credential` belongs to, or `None`
+    /// if the credential doesn't belong to a user.
+    fn user_id(&self, credential: &Credential) -> Option<Vec<u8>>;
+}
+
+impl<F> UserIdentity for F
+where
+    F: Fn(&Credential) -> Option<Vec<u8>>,
+{
+    fn user_id(&self, credential: &Credential) -> Option<Vec<u8>> {
+        self(credential)
+    }
+}
+
+/// The commit that adds or removes a device in a group.
+#[derive(Debug)]
+pub struct DeviceCommit {
+    commit: MlsMessageOut,
+    welcome: Option<MlsMessageOut>,
+    group_info: Option<GroupInfo>,
+}
+
+impl DeviceCommit {
+    /// Returns the commit.
+    pub fn commit(&self) -> &MlsMessageOut {
+        &self.commit
+    }
+
+    /// Returns the Welcome message for the new members, e.g., the device that
+    /// was added.
+    pub fn welcome(&self) -> Option<&MlsMessageOut> {
+        self.welcome.as_ref()
+    }
+
+    /// Returns the [`GroupInfo`] of the new epoch if the group has the
+    /// `use_ratchet_tree_extension` flag set.
+    pub fn group_info(&self) -> Option<&GroupInfo> {
+        self.group_info.as_ref()
+    }
+
+    /// Returns the commit, the Welcome message and the [`GroupInfo`] and
+    /// consumes the [`DeviceCommit`].
+    pub fn into_parts(self) -> (MlsMessageOut, Option<MlsMessageOut>, Option<GroupInfo>) {
+        (self.commit, self.welcome, self.group_info)
+    }
+}
+
+/// The results of adding or removing a device, per group.
+pub type DeviceCommitResults<KeyStoreError> =
+    Vec<(GroupId, Result<DeviceCommit, DeviceError<KeyStoreError>>)>;
+
+/// Manages the devices of users across groups. See the
+/// [module documentation](self) for details.
+#[derive(Debug, Clone)]
+pub struct DeviceManager<U: UserIdentity> {
+    user_identity: U,
+}
+
+impl<U: UserIdentity> DeviceManager<U> {
+    /// Create a new [`DeviceManager`] that uses the given [`UserIdentity`] to
+    /// map credentials to users.
+    pub fn new(user_identity: U) -> Self {
+        Self { user_identity }
+    }
+
+    /// Returns the members of the `group` that are devices of the user with
+    /// the given ID.
+    pub fn devices(&self, group: &MlsGroup, user_id: &[u8]) -> Vec<Member> {
+        group
+            .members()
+            .filter(|member| {
+                self.user_identity.user_id(&member.credential).as_deref() == Some(user_id)
+            })
+            .collect()
+    }
+
+    /// Add a new device of the user to the `groups`. This is done by another
+    /// device of the same user that is a member of the groups.
+    ///
+    /// `key_package_for` returns a key package of the new device for a group,
+    /// e.g., one with the ciphersuite of the group. Groups in which the new
+    /// device is already a member are skipped.
+    ///
+    /// Returns an error for a group if its key package doesn't belong to the
+    /// same user as the own device or if no key package is available.
+    pub fn add_device<'a, KeyStore: OpenMlsKeyStore>(
+        &self,
+        provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
+        signer: &impl Signer,
+        groups: impl IntoIterator<Item = &'a mut MlsGroup>,
+        mut key_package_for: impl FnMut(&MlsGroup) -> Option<KeyPackage>,
+    ) -> DeviceCommitResults<KeyStore::Error> {
+        let mut results = Vec::new();
+        for group in groups {
+            let key_package = match key_package_for(group) {
+                Some(key_package) => key_package,
+                None => {
+                    results.push((
+                        group.group_id().clone(),
+                        Err(DeviceError::MissingKeyPackage),
+                    ));
+                    continue;
+                }
+            };
+            let signature_key = key_package.leaf_node().signature_key().as_slice();
+            if group
+                .members()
+                .any(|member| member.signature_key == signature_key)
+            {
+                continue;
+            }
+            let result = self.add_device_to_group(provider, signer, group, key_package);
+            results.push((group.group_id().clone(), result));
+        }
+        results
+    }
+
+    /// Remove the device with the given `credential` from the `groups`. Groups
+    /// in which the device is not a member are skipped.
+    pub fn remove_device<'a, KeyStore: OpenMlsKeyStore>(
+        &self,
+        provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
+        signer: &impl Signer,
+        groups: impl IntoIterator<Item = &'a mut MlsGroup>,
+        credential: &Credential,
+    ) -> DeviceCommitResults<KeyStore::Error> {
+        let mut results = Vec::new();
+        for group in groups {
+            let leaf_indices: Vec<LeafNodeIndex> = group
+                .members()
+                .filter(|member| &member.credential == credential)
+                .map(|member| member.index)
+                .collect();
+            if leaf_indices.is_empty() {
+                continue;
+            }
+            let result = group
+                .remove_members(provider, signer, &leaf_indices)
+                .map(CommitOutput::into_parts)
+                .map(|(commit, welcome, group_info)| DeviceCommit {
+                    commit,
+                    welcome,
+                    group_info,
+                })
+                .map_err(DeviceError::from);
+            results.push((group.group_id().clone(), result));
+        }
+        results
+    }
+
+    fn add_device_to_group<KeyStore: OpenMlsKeyStore>(
+        &self,
+        provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
+        signer: &impl Signer,
+        group: &mut MlsGroup,
+        key_package: KeyPackage,
+    ) -> Result<DeviceCommit, DeviceError<KeyStore::Error>> {
+        let own_user_id = self.user_identity.user_id(group.credential()?);
+        if own_user_id.is_none()
+            || self
+                .user_identity
+                .user_id(key_package.leaf_node().credential())
+                != own_user_id
+        {
+            return Err(DeviceError::ForeignDevice);
+        }
+        let (commit, welcome, group_info) = group
+            .add_members(provider, signer, &[key_package])?
+            .into_parts();
+        Ok(DeviceCommit {
+            commit,
+            welcome: Some(welcome),
+            group_info,
+        })
+    }
+}
@@ -0,0 +1,131 @@
+//! # Downgrade protection
+//!
+//! A client that only trusts some ciphersuites and protocol versions can pin
+//! them with a [`DowngradePolicy`] in the [`MlsGroupConfig`]. Both lists are
+//! ordered by preference, from the strongest to the weakest entry. An empty
+//! list accepts all ciphersuites or versions, which is the default.
+//!
+//! The policy is checked whenever the configuration could move the client to
+//! a group with a weaker ciphersuite or version:
+//!
+//! * [`StagedWelcome`] and [`MlsGroup::new_from_welcome()`] fail with
+//!   [`WelcomeError::DowngradeAttempt`] if the group of the Welcome message
+//!   uses a ciphersuite or version that is not pinned,
+//! * [`MlsGroup::join_by_external_commit()`] fails with
+//!   [`ExternalCommitError::DowngradeAttempt`] if the group info does, and
+//! * [`MlsGroup::process_message()`] fails with
+//!   [`ProcessMessageError::DowngradeAttempt`] for ReInit proposals, or
+//!   commits covering them, that move the group to a ciphersuite or version
+//!   that is not pinned or that is pinned after the current one.
+//!
+//! [`WelcomeError::DowngradeAttempt`]: crate::group::WelcomeError::DowngradeAttempt
+//! [`ExternalCommitError::DowngradeAttempt`]: crate::group::ExternalCommitError::DowngradeAttempt
+
+use serde::{Deserialize, Serialize};
+
+use super::*;
+use crate::messages::proposals::Proposal;
+
+/// The ciphersuites and protocol versions a client accepts, in order of
+/// preference. See the [module documentation](self) for details.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DowngradePolicy {
+    ciphersuites: Vec<Ciphersuite>,
+    versions: Vec<ProtocolVersion>,
+}
+
+impl DowngradePolicy {
+    /// Creates a new [`DowngradePolicy`] that pins the given `ciphersuites`
+    /// and `versions`, ordered from the strongest to the weakest. An empty
+    /// list accepts all ciphersuites or versions.
+    pub fn new(ciphersuites: Vec<Ciphersuite>, versions: Vec<ProtocolVersion>) -> Self {
+        Self {
+            ciphersuites,
+            versions,
+        }
+    }
+
+    /// Returns the pinned ciphersuites.
+    pub fn ciphersuites(&self) -> &[Ciphersuite] {
+        &self.ciphersuites
+    }
+
+    /// Returns the pinned protocol versions.
+    pub fn versions(&self) -> &[ProtocolVersion] {
+        &self.versions
+    }
+
+    /// Returns `true` if a group with `ciphersuite` and `version` is
+    /// accepted by the policy.
+    pub fn accepts(&self, ciphersuite: Ciphersuite, version: ProtocolVersion) -> bool {
+        rank(&self.ciphersuites, &ciphersuite).is_some() && rank(&self.versions, &version).is_some()
+    }
+
+    /// Returns `true` if a group with `ciphersuite` and `version` may move to
+    /// `new_ciphersuite` and `new_version`, i.e., if the new ones are
+    /// accepted and not pinned after the current ones. Current ciphersuites
+    /// and versions that are not pinned may move to any accepted one.
+    pub fn accepts_transition(
+        &self,
+        ciphersuite: Ciphersuite,
+        version: ProtocolVersion,
+        new_ciphersuite: Ciphersuite,
+        new_version: ProtocolVersion,
+    ) -> bool {
+        let not_weaker = |current: Option<usize>, new: Option<usize>| match (current, new) {
+            (Some(current), Some(new)) => new <= current,
+            (None, Some(_)) => true,
+            (_, None) => false,
+        };
+        not_weaker(
+            rank(&self.ciphersuites, &ciphersuite),
+            rank(&self.ciphersuites, &new_ciphersuite),
+        ) && not_weaker(
+            rank(&self.versions, &version),
+            rank(&self.versions, &new_version),
+        )
+    }
+}
+
+/// Returns the position of `value` in `pinned`, `Some(0)` if nothing is
+/// pinned and `None` if `value` isn't pinned.
+fn rank<T: PartialEq>(pinned: &[T], value: &T) -> Option<usize> {
+    if pinned.is_empty() {
+        return Some(0);
+    }
+    pinned.iter().position(|pinned| pinned == value)
+}
+
+impl MlsGroup {
+    /// Check that the ReInit proposals in the processed message `content` are
+    /// allowed by the [`DowngradePolicy`] of the group.
+    pub(super) fn check_downgrade(
+        &self,
+        content: &ProcessedMessageContent,
+    ) -> Result<(), ProcessMessageError> {
+        let policy = self.configuration().downgrade_policy();
+        let is_downgrade = |queued_proposal: &QueuedProposal| match queued_proposal.proposal() {
+            Proposal::ReInit(reinit) => !policy.accepts_transition(
+                self.ciphersuite(),
+                self.version(),
+                reinit.ciphersuite,
+                reinit.version,
+            ),
+            _ => false,
+        };
+        let downgrade = match content {
+            ProcessedMessageContent::ProposalMessage(queued_proposal)
+            | ProcessedMessageContent::ExternalJoinProposalMessage(queued_proposal) => {
+                is_downgrade(queued_proposal)
+            }
+            ProcessedMessageContent::StagedCommitMessage(staged_commit) => {
+                staged_commit.queued_proposals().any(is_downgrade)
+            }
+            ProcessedMessageContent::ApplicationMessage(_) => false,
+        };
+        if downgrade {
+            return Err(ProcessMessageError::DowngradeAttempt);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,118 @@
+//! # Ephemeral keys
+//!
+//! Features like typing indicators or presence beacons need keys that all
+//! members of a group share, but they don't need the guarantees of
+//! application messages and shouldn't advance the message ratchets of the
+//! group, e.g., because they are sent very frequently or over a different
+//! channel.
+//!
+//! [`MlsGroup::ephemeral_key()`] derives an [`EphemeralKey`] for a label
+//! from the exporter secret of the current epoch. All members of the epoch
+//! derive the same key for the same label, and keys with different labels
+//! are independent. Deriving a key doesn't change the state of the group.
+//!
+//! Ephemeral keys rotate with the epoch: after a commit, the group derives a
+//! new key for the same label, so removed members can't derive the keys of
+//! later epochs. [`EphemeralKey::rotate()`] re-derives a key if the group has
+//! moved to a new epoch since the key was derived. Since the exporter secrets
+//! of past epochs aren't kept, the keys of past epochs can't be derived
+//! again; applications should accept data protected with an outdated key
+//! only for a short transition period, if at all.
+
+use openmls_traits::crypto::OpenMlsCrypto;
+use tls_codec::SecretVLBytes;
+
+use super::*;
+
+/// The exporter label for ephemeral keys. The label of the key is used as the
+/// exporter context.
+pub(super) const EPHEMERAL_KEY_LABEL: &str = "ephemeral key";
+
+/// A key for ephemeral features that is derived from an epoch of a group. See
+/// the [module documentation](self) for details.
+///
+/// Note: This has a hand-written `Debug` implementation.
+#[derive(Clone, PartialEq, Eq)]
+pub struct EphemeralKey {
+    group_id: GroupId,
+    epoch: GroupEpoch,
+    label: String,
+    key: SecretVLBytes,
+}
+
+impl std::fmt::Debug for EphemeralKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EphemeralKey")
+            .field("group_id", &self.group_id)
+            .field("epoch", &self.epoch)
+            .field("label", &self.label)
+            .field("key", &"***")
+            .finish()
+    }
+}
+
+impl EphemeralKey {
+    /// Returns the ID of the group the key was derived from.
+    pub fn group_id(&self) -> &GroupId {
+        &self.group_id
+    }
+
+    /// Returns the epoch the key was derived from.
+    pub fn epoch(&self) -> GroupEpoch {
+        self.epoch
+    }
+
+    /// Returns the label of the key.
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Returns the key as byte slice.
+    pub fn as_slice(&self) -> &[u8] {
+        self.key.as_slice()
+    }
+
+    /// Returns `true` if the key was derived from the current epoch of
+    /// `group`.
+    pub fn is_current(&self, group: &MlsGroup) -> bool {
+        &self.group_id == group.group_id() && self.epoch == group.epoch()
+    }
+
+    /// Re-derives the key from the current epoch of `group` if it was derived
+    /// from an earlier epoch. Returns `true` if the key was rotated.
+    pub fn rotate(
+        &mut self,
+        crypto: &impl OpenMlsCrypto,
+        group: &MlsGroup,
+    ) -> Result<bool, ExportSecretError> {
+        if self.is_current(group) {
+            return Ok(false);
+        }
+        *self = group.ephemeral_key(crypto, &self.label, self.key.as_slice().len())?;
+        Ok(true)
+    }
+}
+
+impl MlsGroup {
+    /// Derives an [`EphemeralKey`] with the given `label` and `key_length`
+    /// from the current epoch. See the [module documentation](self) for
+    /// details.
+    ///
+    /// Returns [`ExportSecretError::KeyLengthTooLong`] if the requested key
+    /// length is too long.
+    pub fn ephemeral_key(
+        &self,
+        crypto: &impl OpenMlsCrypto,
+        label: &str,
+        key_length: usize,
+    ) -> Result<EphemeralKey, ExportSecretError> {
+        let key =
+            self.export_reserved_secret(crypto, EPHEMERAL_KEY_LABEL, label.as_bytes(), key_length)?;
+        Ok(EphemeralKey {
+            group_id: self.group_id().clone(),
+            epoch: self.epoch(),
+            label: label.to_owned(),
+            key: key.into(),
+        })
+    }
+}
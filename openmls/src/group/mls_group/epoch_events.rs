@@ -0,0 +1,210 @@
+//! # Epoch transitions
+//!
+//! Higher layers such as sync engines or user interfaces usually need to know
+//! when the epoch of a group changes, why it changed and which members were
+//! added or removed. Instead of diffing the group state before and after
+//! every merge, they can subscribe to the [`EpochTransition`]s of an
+//! [`MlsGroup`] with [`MlsGroup::subscribe_epoch_transitions()`].
+//!
+//! An [`EpochTransition`] is emitted whenever a commit is merged, i.e., by
+//! [`MlsGroup::merge_staged_commit()`] and
+//! [`MlsGroup::merge_pending_commit()`], after the group state was updated.
+//! It contains the old and the new epoch, the [`EpochChangeCause`], the
+//! committer, the members that were added or removed, and the epoch
+//! authenticator and confirmation tag of the new epoch. If the own member was
+//! removed by the commit, the secrets of the new epoch are unknown and the
+//! transition contains no epoch authenticator and confirmation tag.
+//!
+//! The handlers are called synchronously on the thread that merges the
+//! commit and should therefore return quickly, e.g., by forwarding the
+//! transition to a queue. Subscriptions are not part of the persisted group
+//! state and have to be renewed after the group is loaded.
+
+use std::sync::Arc;
+
+use super::{audit::MembershipDelta, *};
+use crate::messages::ConfirmationTag;
+
+/// The reason for an [`EpochTransition`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EpochChangeCause {
+    /// The own commit was merged.
+    OwnCommit,
+    /// The commit of another member was merged.
+    MemberCommit,
+    /// An external commit was merged, i.e., the committer joined the group.
+    /// This is also the cause if the own external commit was merged.
+    ExternalCommit,
+}
+
+/// A change of the epoch of an [`MlsGroup`]. See the
+/// [module documentation](self) for details.
+#[derive(Debug, Clone)]
+pub struct EpochTransition {
+    group_id: GroupId,
+    old_epoch: GroupEpoch,
+    new_epoch: GroupEpoch,
+    cause: EpochChangeCause,
+    committer: AuditMember,
+    added: Vec<AuditMember>,
+    removed: Vec<AuditMember>,
+    epoch_authenticator: Option<EpochAuthenticator>,
+    confirmation_tag: Option<ConfirmationTag>,
+}
+
+impl EpochTransition {
+    /// Returns the ID of the group.
+    pub fn group_id(&self) -> &GroupId {
+        &self.group_id
+    }
+
+    /// Returns the epoch before the transition.
+    pub fn old_epoch(&self) -> GroupEpoch {
+        self.old_epoch
+    }
+
+    /// Returns the epoch after the transition.
+    pub fn new_epoch(&self) -> GroupEpoch {
+        self.new_epoch
+    }
+
+    /// Returns the reason for the transition.
+    pub fn cause(&self) -> EpochChangeCause {
+        self.cause
+    }
+
+    /// Returns the member that created the commit.
+    pub fn committer(&self) -> &AuditMember {
+        &self.committer
+    }
+
+    /// Returns the members that were added, including the committer of an
+    /// external commit.
+    pub fn added(&self) -> &[AuditMember] {
+        &self.added
+    }
+
+    /// Returns the members that were removed.
+    pub fn removed(&self) -> &[AuditMember] {
+        &self.removed
+    }
+
+    /// Returns the epoch authenticator of the new epoch, or `None` if the
+    /// own member was removed.
+    pub fn epoch_authenticator(&self) -> Option<&EpochAuthenticator> {
+        self.epoch_authenticator.as_ref()
+    }
+
+    /// Returns the confirmation tag of the new epoch, or `None` if the own
+    /// member was removed.
+    pub fn confirmation_tag(&self) -> Option<&ConfirmationTag> {
+        self.confirmation_tag.as_ref()
+    }
+}
+
+/// A subscription to the [`EpochTransition`]s of an [`MlsGroup`], as
+/// returned by [`MlsGroup::subscribe_epoch_transitions()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EpochSubscription(u64);
+
+/// A handler for [`EpochTransition`]s.
+type EpochTransitionHandler = Arc<dyn Fn(&EpochTransition) + Send + Sync>;
+
+/// The handlers subscribed to the [`EpochTransition`]s of a group.
+///
+/// Note: This has a hand-written `Debug` implementation.
+#[derive(Default)]
+pub(crate) struct EpochSubscribers {
+    next_id: u64,
+    handlers: Vec<(EpochSubscription, EpochTransitionHandler)>,
+}
+
+impl std::fmt::Debug for EpochSubscribers {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EpochSubscribers")
+            .field("handlers", &self.handlers.len())
+            .finish()
+    }
+}
+
+impl EpochSubscribers {
+    pub(super) fn is_empty(&self) -> bool {
+        self.handlers.is_empty()
+    }
+}
+
+impl MlsGroup {
+    /// Subscribes `handler` to the [`EpochTransition`]s of the group. The
+    /// handler is called after every merged commit until the returned
+    /// [`EpochSubscription`] is cancelled with
+    /// [`MlsGroup::unsubscribe_epoch_transitions()`]. See the
+    /// [module documentation](self) for details.
+    pub fn subscribe_epoch_transitions(
+        &mut self,
+        handler: impl Fn(&EpochTransition) + Send + Sync + 'static,
+    ) -> EpochSubscription {
+        let subscription = EpochSubscription(self.epoch_subscribers.next_id);
+        self.epoch_subscribers.next_id += 1;
+        self.epoch_subscribers
+            .handlers
+            .push((subscription, Arc::new(handler)));
+        subscription
+    }
+
+    /// Cancels the given `subscription`. Returns `false` if it was already
+    /// cancelled.
+    pub fn unsubscribe_epoch_transitions(&mut self, subscription: EpochSubscription) -> bool {
+        let subscribers = self.epoch_subscribers.handlers.len();
+        self.epoch_subscribers
+            .handlers
+            .retain(|(id, _)| *id != subscription);
+        self.epoch_subscribers.handlers.len() != subscribers
+    }
+
+    /// Emit the [`EpochTransition`] from `old_epoch` to the current epoch to
+    /// all subscribed handlers.
+    pub(super) fn notify_epoch_transition(
+        &self,
+        old_epoch: GroupEpoch,
+        own_commit: bool,
+        self_removed: bool,
+        delta: MembershipDelta,
+    ) {
+        if self.epoch_subscribers.is_empty() {
+            return;
+        }
+        let cause = if delta.committer_joined {
+            EpochChangeCause::ExternalCommit
+        } else if own_commit {
+            EpochChangeCause::OwnCommit
+        } else {
+            EpochChangeCause::MemberCommit
+        };
+        // The group state isn't advanced to the new epoch if the own member
+        // was removed.
+        let mut new_epoch = old_epoch;
+        new_epoch.increment();
+        let (epoch_authenticator, confirmation_tag) = if self_removed {
+            (None, None)
+        } else {
+            (
+                Some(self.epoch_authenticator().clone()),
+                Some(self.group.public_group().confirmation_tag().clone()),
+            )
+        };
+        let transition = EpochTransition {
+            group_id: self.group_id().clone(),
+            old_epoch,
+            new_epoch,
+            cause,
+            committer: delta.committer,
+            added: delta.added,
+            removed: delta.removed,
+            epoch_authenticator,
+            confirmation_tag,
+        };
+        for (_, handler) in &self.epoch_subscribers.handlers {
+            handler(&transition);
+        }
+    }
+}
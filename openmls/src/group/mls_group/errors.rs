@@ -8,13 +8,17 @@
 use thiserror::Error;
 
 use crate::{
+    ciphersuite::signable::SignatureError,
     error::LibraryError,
     extensions::errors::InvalidExtensionError,
+    framing::errors::MessageDecryptionError,
     group::errors::{
-        CreateAddProposalError, CreateCommitError, MergeCommitError, StageCommitError,
-        ValidationError,
+        CreateAddProposalError, CreateCommitError, MemoryLimitError, MergeCommitError,
+        ProposalValidationError, StageCommitError, ValidationError, WelcomeError,
     },
+    group::mls_group::CommitRule,
     schedule::errors::PskError,
+    tree::secret_tree::SecretTreeError,
     treesync::errors::{LeafNodeValidationError, PublicTreeError},
 };
 
@@ -39,6 +43,12 @@ pub enum NewGroupError<KeyStoreError> {
     /// Invalid extensions set in configuration
     #[error("Invalid extensions set in configuration")]
     InvalidExtensions(InvalidExtensionError),
+    /// The group ID is rejected by the group ID policy of the configuration.
+    #[error("The group ID is rejected by the group ID policy of the configuration.")]
+    InvalidGroupId,
+    /// See [`AddMembersError`] for more details.
+    #[error(transparent)]
+    AddMembersError(#[from] AddMembersError<KeyStoreError>),
 }
 
 /// EmptyInput error
@@ -73,6 +83,31 @@ pub enum MlsGroupStateError {
     /// Requested pending proposal hasn't been found in local pending proposals
     #[error("Requested pending proposal hasn't been found in local pending proposals.")]
     PendingProposalNotFound,
+    /// Can't send messages while a snapshot of the group is taken.
+    #[error("Can't send messages while a snapshot of the group is taken.")]
+    SnapshotActive,
+    /// See [`ApiMisuseError`] for more details.
+    #[error(transparent)]
+    ApiMisuse(#[from] ApiMisuseError),
+}
+
+/// A misuse of the API that is detected with the `strict-api` feature.
+#[derive(Error, Debug, PartialEq, Eq, Clone)]
+pub enum ApiMisuseError {
+    /// Tried to send an application message while a commit is pending. The
+    /// message is sent in the old epoch, which the other members may not
+    /// keep once the commit is merged.
+    #[error("Tried to send an application message while a commit is pending.")]
+    MessageWithPendingCommit,
+    /// Tried to process a message that was sent by this member.
+    #[error("Tried to process a message that was sent by this member.")]
+    OwnMessage,
+    /// Tried to merge a staged commit that wasn't staged by processing a
+    /// commit in the current epoch of the group.
+    #[error(
+        "Tried to merge a staged commit that wasn't staged in the current epoch of the group."
+    )]
+    StaleStagedCommit,
 }
 
 /// Error merging pending commit
@@ -113,6 +148,66 @@ pub enum ProcessMessageError {
     /// The proposal is invalid for the Sender of type [External](crate::prelude::Sender::External)
     #[error("The proposal is invalid for the Sender of type External")]
     UnsupportedProposalType,
+    /// See [`MemoryLimitError`] for more details.
+    #[error(transparent)]
+    MemoryLimitError(#[from] MemoryLimitError),
+    /// See [`ProposalValidationError`] for more details.
+    #[error(transparent)]
+    InvalidProposal(#[from] ProposalValidationError),
+    /// See [`SequencingError`] for more details.
+    #[error(transparent)]
+    SequencingError(#[from] SequencingError),
+    /// A ReInit proposal moves the group to a ciphersuite or protocol version
+    /// that is rejected by the downgrade policy of the configuration.
+    #[error("A ReInit proposal moves the group to a ciphersuite or protocol version that is rejected by the downgrade policy of the configuration.")]
+    DowngradeAttempt,
+    /// The commit violates the given rule of the commit content policy of the
+    /// configuration.
+    #[error(
+        "The commit violates the rule {0:?} of the commit content policy of the configuration."
+    )]
+    CommitPolicyViolation(CommitRule),
+    /// The message was received in an `MLSMessage` with a protocol version
+    /// other than the one of the group.
+    #[error("The message was received in an MLSMessage with a protocol version other than the one of the group.")]
+    VersionMismatch,
+}
+
+/// Sequencing error
+#[derive(Error, Debug, PartialEq, Eq, Clone)]
+pub enum SequencingError {
+    /// The authenticated data of the commit doesn't contain a sequence number.
+    #[error("The authenticated data of the commit doesn't contain a sequence number.")]
+    MissingSequenceNumber,
+    /// The sequence number of the commit is not larger than the last one.
+    #[error("The sequence number of the commit is not larger than the last one.")]
+    NonMonotonicSequenceNumber,
+}
+
+/// Abuse report error
+#[derive(Error, Debug, PartialEq, Eq, Clone)]
+pub enum AbuseReportError {
+    /// See [`LibraryError`] for more details.
+    #[error(transparent)]
+    LibraryError(#[from] LibraryError),
+    /// The message is not an application message.
+    #[error("The message is not an application message.")]
+    NotAnApplicationMessage,
+    /// The message was not sent in the current epoch of the group.
+    #[error("The message was not sent in the current epoch of the group.")]
+    UnavailableEpoch,
+    /// The sender of the message is not a member of the group.
+    #[error("The sender of the message is not a member of the group.")]
+    UnknownSender,
+    /// The signature of the leaf node of the sender is invalid.
+    #[error("The signature of the leaf node of the sender is invalid.")]
+    InvalidLeafNodeSignature,
+    /// The membership proof doesn't match the tree hash of the group.
+    #[error("The membership proof doesn't match the tree hash of the group.")]
+    InvalidMembershipProof,
+    /// The signature of the message is invalid.
+    #[error("The signature of the message is invalid.")]
+    InvalidMessageSignature,
 }
 
 /// Create message error
@@ -174,6 +269,24 @@ pub enum ProposeRemoveMemberError {
     UnknownMember,
 }
 
+/// Error creating an external remove proposal
+#[derive(Error, Debug, PartialEq, Clone)]
+pub enum ExternalRemoveProposalError {
+    /// See [`LibraryError`] for more details.
+    #[error(transparent)]
+    LibraryError(#[from] LibraryError),
+    /// The sender index doesn't refer to an external sender of the group.
+    #[error("The sender index doesn't refer to an external sender of the group.")]
+    UnknownExternalSender,
+    /// The member that should be removed can not be found.
+    #[error("The member that should be removed can not be found.")]
+    UnknownMember,
+    /// The external sender may not remove the member according to the
+    /// external sender scopes of the group.
+    #[error("The external sender may not remove the member according to the external sender scopes of the group.")]
+    ExternalSenderOutOfScope,
+}
+
 /// Remove members error
 #[derive(Error, Debug, PartialEq, Clone)]
 pub enum RemoveMembersError<KeyStoreError> {
@@ -254,6 +367,54 @@ pub enum CommitToPendingProposalsError<KeyStoreError> {
     GroupStateError(#[from] MlsGroupStateError),
 }
 
+/// Commit operation error
+#[derive(Error, Debug, PartialEq, Clone)]
+pub enum CommitOperationError {
+    /// See [`LibraryError`] for more details.
+    #[error(transparent)]
+    LibraryError(#[from] LibraryError),
+    /// See [`SignatureError`] for more details.
+    #[error(transparent)]
+    SignatureError(#[from] SignatureError),
+    /// The operation was cancelled at a checkpoint.
+    #[error("The operation was cancelled at a checkpoint.")]
+    Cancelled,
+}
+
+/// Stage prepared commit error
+#[derive(Error, Debug, PartialEq, Clone)]
+pub enum StagePreparedCommitError {
+    /// See [`LibraryError`] for more details.
+    #[error(transparent)]
+    LibraryError(#[from] LibraryError),
+    /// See [`MlsGroupStateError`] for more details.
+    #[error(transparent)]
+    GroupStateError(#[from] MlsGroupStateError),
+    /// The commit was prepared for a different group or epoch.
+    #[error("The commit was prepared for a different group or epoch.")]
+    StaleCommit,
+}
+
+/// Audit log error
+#[derive(Error, Debug, PartialEq, Clone)]
+pub enum AuditLogError {
+    /// See [`LibraryError`] for more details.
+    #[error(transparent)]
+    LibraryError(#[from] LibraryError),
+    /// An entry doesn't reference the hash of the previous entry.
+    #[error("An entry doesn't reference the hash of the previous entry.")]
+    BrokenChain,
+    /// The hash of an entry doesn't match its contents.
+    #[error("The hash of an entry doesn't match its contents.")]
+    InvalidHash,
+    /// The epochs of the entries are not increasing.
+    #[error("The epochs of the entries are not increasing.")]
+    EpochNotIncreasing,
+    /// The head of the log doesn't match the expected head.
+    #[error("The head of the log doesn't match the expected head.")]
+    HeadMismatch,
+}
+
 /// Errors that can happen when exporting a group info object.
 #[derive(Error, Debug, PartialEq, Clone)]
 pub enum ExportGroupInfoError {
@@ -277,6 +438,9 @@ pub enum ExportSecretError {
     /// See [`MlsGroupStateError`] for more details.
     #[error(transparent)]
     GroupStateError(#[from] MlsGroupStateError),
+    /// The label is reserved for keys that OpenMLS exports.
+    #[error("The label is reserved for keys that OpenMLS exports.")]
+    ReservedLabel,
 }
 
 /// Propose PSK error
@@ -318,3 +482,339 @@ pub enum ProposalError<KeyStoreError> {
     #[error(transparent)]
     ValidationError(#[from] ValidationError),
 }
+
+/// Resolve commit conflict error
+#[derive(Error, Debug, PartialEq, Clone)]
+pub enum ResolveCommitConflictError<KeyStoreError> {
+    /// See [`LibraryError`] for more details.
+    #[error(transparent)]
+    LibraryError(#[from] LibraryError),
+    /// See [`MlsGroupStateError`] for more details.
+    #[error(transparent)]
+    GroupStateError(#[from] MlsGroupStateError),
+    /// See [`MergeCommitError`] for more details.
+    #[error(transparent)]
+    MergeCommitError(#[from] MergeCommitError<KeyStoreError>),
+    /// See [`ProposalError`] for more details.
+    #[error(transparent)]
+    ProposalError(#[from] ProposalError<KeyStoreError>),
+}
+
+/// Device error
+#[derive(Error, Debug, PartialEq, Clone)]
+pub enum DeviceError<KeyStoreError> {
+    /// See [`LibraryError`] for more details.
+    #[error(transparent)]
+    LibraryError(#[from] LibraryError),
+    /// See [`MlsGroupStateError`] for more details.
+    #[error(transparent)]
+    GroupStateError(#[from] MlsGroupStateError),
+    /// The device belongs to another user.
+    #[error("The device belongs to another user.")]
+    ForeignDevice,
+    /// No key package of the device is available for the group.
+    #[error("No key package of the device is available for the group.")]
+    MissingKeyPackage,
+    /// See [`AddMembersError`] for more details.
+    #[error(transparent)]
+    AddMembersError(#[from] AddMembersError<KeyStoreError>),
+    /// See [`RemoveMembersError`] for more details.
+    #[error(transparent)]
+    RemoveMembersError(#[from] RemoveMembersError<KeyStoreError>),
+}
+
+/// History error
+#[derive(Error, Debug, PartialEq, Clone)]
+pub enum HistoryError {
+    /// See [`LibraryError`] for more details.
+    #[error(transparent)]
+    LibraryError(#[from] LibraryError),
+    /// See [`MlsGroupStateError`] for more details.
+    #[error(transparent)]
+    GroupStateError(#[from] MlsGroupStateError),
+    /// History sharing is disabled in the group configuration.
+    #[error("History sharing is disabled in the group configuration.")]
+    HistorySharingDisabled,
+    /// The group has no history keys for the requested epochs.
+    #[error("The group has no history keys for the requested epochs.")]
+    NoHistoryKeys,
+    /// The history bundle belongs to another group.
+    #[error("The history bundle belongs to another group.")]
+    WrongGroup,
+    /// The history bundle was not exported in the current epoch.
+    #[error("The history bundle was not exported in the current epoch.")]
+    WrongEpoch,
+    /// The history bundle could not be decrypted.
+    #[error("The history bundle could not be decrypted.")]
+    DecryptionFailed,
+    /// The history bundle is malformed.
+    #[error("The history bundle is malformed.")]
+    MalformedBundle,
+}
+
+/// Ratchet export error
+#[derive(Error, Debug, PartialEq, Clone)]
+pub enum RatchetExportError {
+    /// See [`LibraryError`] for more details.
+    #[error(transparent)]
+    LibraryError(#[from] LibraryError),
+    /// See [`MlsGroupStateError`] for more details.
+    #[error(transparent)]
+    GroupStateError(#[from] MlsGroupStateError),
+    /// The sealing key doesn't have the AEAD key length of the ciphersuite.
+    #[error("The sealing key doesn't have the AEAD key length of the ciphersuite.")]
+    InvalidSealingKey,
+    /// The sender isn't another member of the group.
+    #[error("The sender isn't another member of the group.")]
+    UnknownSender,
+    /// See [`SecretTreeError`] for more details.
+    #[error(transparent)]
+    SecretTreeError(#[from] SecretTreeError),
+    /// The sealed ratchet state could not be decrypted.
+    #[error("The sealed ratchet state could not be decrypted.")]
+    DecryptionFailed,
+    /// The sealed ratchet state is malformed.
+    #[error("The sealed ratchet state is malformed.")]
+    MalformedState,
+    /// The message belongs to another group.
+    #[error("The message belongs to another group.")]
+    WrongGroup,
+    /// The message is not from the epoch of the ratchet state.
+    #[error("The message is not from the epoch of the ratchet state.")]
+    WrongEpoch,
+    /// The message is not an encrypted application message.
+    #[error("The message is not an encrypted application message.")]
+    NotApplicationMessage,
+    /// The ratchet state doesn't contain the key of the message generation.
+    #[error("The ratchet state doesn't contain the key of the message generation.")]
+    UnavailableGeneration,
+    /// See [`MessageDecryptionError`] for more details.
+    #[error(transparent)]
+    MessageDecryptionError(#[from] MessageDecryptionError),
+    /// The signature of the message is invalid.
+    #[error("The signature of the message is invalid.")]
+    InvalidSignature,
+}
+
+/// Subgroup error
+#[derive(Error, Debug, PartialEq, Clone)]
+pub enum SubgroupError<KeyStoreError> {
+    /// See [`LibraryError`] for more details.
+    #[error(transparent)]
+    LibraryError(#[from] LibraryError),
+    /// See [`MlsGroupStateError`] for more details.
+    #[error(transparent)]
+    GroupStateError(#[from] MlsGroupStateError),
+    /// The ciphersuite of the subgroup differs from the one of the parent
+    /// group.
+    #[error("The ciphersuite of the subgroup differs from the one of the parent group.")]
+    CiphersuiteMismatch,
+    /// A key package doesn't belong to a member of the parent group.
+    #[error("A key package doesn't belong to a member of the parent group.")]
+    NotAParentMember,
+    /// The group is not a subgroup of the parent group.
+    #[error("The group is not a subgroup of the parent group.")]
+    NotASubgroup,
+    /// Error accessing the key store.
+    #[error("Error accessing the key store.")]
+    KeyStoreError(KeyStoreError),
+    /// See [`PskError`] for more details.
+    #[error(transparent)]
+    Psk(#[from] PskError),
+    /// See [`NewGroupError`] for more details.
+    #[error(transparent)]
+    NewGroupError(#[from] NewGroupError<KeyStoreError>),
+    /// See [`AddMembersError`] for more details.
+    #[error(transparent)]
+    AddMembersError(#[from] AddMembersError<KeyStoreError>),
+    /// See [`MergePendingCommitError`] for more details.
+    #[error(transparent)]
+    MergePendingCommitError(#[from] MergePendingCommitError<KeyStoreError>),
+    /// See [`WelcomeError`] for more details.
+    #[error(transparent)]
+    WelcomeError(#[from] WelcomeError<KeyStoreError>),
+}
+
+/// Group merge error
+#[derive(Error, Debug, PartialEq, Clone)]
+pub enum GroupMergeError<KeyStoreError> {
+    /// See [`LibraryError`] for more details.
+    #[error(transparent)]
+    LibraryError(#[from] LibraryError),
+    /// See [`MlsGroupStateError`] for more details.
+    #[error(transparent)]
+    GroupStateError(#[from] MlsGroupStateError),
+    /// A key package doesn't belong to the member it was provided for.
+    #[error("A key package doesn't belong to the member it was provided for.")]
+    KeyPackageMismatch,
+    /// See [`AddMembersError`] for more details.
+    #[error(transparent)]
+    AddMembersError(#[from] AddMembersError<KeyStoreError>),
+    /// See [`SelfUpdateError`] for more details.
+    #[error(transparent)]
+    SelfUpdateError(#[from] SelfUpdateError<KeyStoreError>),
+}
+
+/// Contact pair error
+#[derive(Error, Debug, PartialEq, Clone)]
+pub enum ContactPairError<KeyStoreError> {
+    /// See [`LibraryError`] for more details.
+    #[error(transparent)]
+    LibraryError(#[from] LibraryError),
+    /// The group doesn't have exactly two members or its group ID isn't the
+    /// one of the contact pair.
+    #[error("The group doesn't have exactly two members or its group ID isn't the one of the contact pair.")]
+    NotAContactPair,
+    /// The message can't be received in a contact pair.
+    #[error("The message can't be received in a contact pair.")]
+    UnexpectedMessage,
+    /// The peer's credential or signature key in the re-initialized group
+    /// isn't the one in the current group.
+    #[error("The peer's credential or signature key in the re-initialized group isn't the one in the current group.")]
+    PeerMismatch,
+    /// See [`NewGroupError`] for more details.
+    #[error(transparent)]
+    NewGroupError(#[from] NewGroupError<KeyStoreError>),
+    /// See [`WelcomeError`] for more details.
+    #[error(transparent)]
+    WelcomeError(#[from] WelcomeError<KeyStoreError>),
+    /// See [`AddMembersError`] for more details.
+    #[error(transparent)]
+    AddMembersError(#[from] AddMembersError<KeyStoreError>),
+    /// See [`SelfUpdateError`] for more details.
+    #[error(transparent)]
+    SelfUpdateError(#[from] SelfUpdateError<KeyStoreError>),
+    /// See [`MergePendingCommitError`] for more details.
+    #[error(transparent)]
+    MergePendingCommitError(#[from] MergePendingCommitError<KeyStoreError>),
+    /// See [`MergeCommitError`] for more details.
+    #[error(transparent)]
+    MergeCommitError(#[from] MergeCommitError<KeyStoreError>),
+    /// See [`CreateMessageError`] for more details.
+    #[error(transparent)]
+    CreateMessageError(#[from] CreateMessageError),
+    /// See [`ProcessMessageError`] for more details.
+    #[error(transparent)]
+    ProcessMessageError(#[from] ProcessMessageError),
+}
+
+/// Maintenance error
+#[derive(Error, Debug, PartialEq, Clone)]
+pub enum MaintenanceError<KeyStoreError> {
+    /// See [`LibraryError`] for more details.
+    #[error(transparent)]
+    LibraryError(#[from] LibraryError),
+    /// See [`MlsGroupStateError`] for more details.
+    #[error(transparent)]
+    GroupStateError(#[from] MlsGroupStateError),
+    /// See [`SelfUpdateError`] for more details.
+    #[error(transparent)]
+    SelfUpdateError(#[from] SelfUpdateError<KeyStoreError>),
+    /// See [`ProposeSelfUpdateError`] for more details.
+    #[error(transparent)]
+    ProposeSelfUpdateError(#[from] ProposeSelfUpdateError<KeyStoreError>),
+}
+
+/// Discard epoch error
+#[derive(Error, Debug, PartialEq, Eq, Clone)]
+pub enum DiscardEpochError {
+    /// The epoch is not a past epoch of the group.
+    #[error("The epoch is not a past epoch of the group.")]
+    NotAPastEpoch,
+    /// No message secrets are kept for the epoch.
+    #[error("No message secrets are kept for the epoch.")]
+    UnknownEpoch,
+}
+
+/// Snapshot error
+#[derive(Error, Debug, PartialEq, Clone)]
+pub enum SnapshotError<KeyStoreError> {
+    /// See [`LibraryError`] for more details.
+    #[error(transparent)]
+    LibraryError(#[from] LibraryError),
+    /// A snapshot of the group was already taken.
+    #[error("A snapshot of the group was already taken.")]
+    SnapshotActive,
+    /// No snapshot of the group was taken.
+    #[error("No snapshot of the group was taken.")]
+    NoSnapshot,
+    /// The snapshot of the group was not found in the key store.
+    #[error("The snapshot of the group was not found in the key store.")]
+    MissingSnapshot,
+    /// Error accessing the key store.
+    #[error("Error accessing the key store.")]
+    KeyStoreError(KeyStoreError),
+}
+
+/// Reissue Welcome error
+#[derive(Error, Debug, PartialEq, Clone)]
+pub enum ReissueWelcomeError {
+    /// The current epoch wasn't created by an own commit that added members.
+    #[error("The current epoch wasn't created by an own commit that added members.")]
+    NoWelcome,
+    /// The Welcome message doesn't contain secrets for the key package.
+    #[error("The Welcome message doesn't contain secrets for the key package.")]
+    UnknownNewMember,
+}
+
+/// Welcome decline error
+#[derive(Error, Debug, PartialEq, Clone)]
+pub enum WelcomeDeclineError {
+    /// See [`LibraryError`] for more details.
+    #[error(transparent)]
+    LibraryError(#[from] LibraryError),
+    /// See [`SignatureError`] for more details.
+    #[error(transparent)]
+    SignatureError(#[from] SignatureError),
+    /// The decline is for another group.
+    #[error("The decline is for another group.")]
+    WrongGroup,
+    /// The leaf that was created for the invitation isn't in the group.
+    #[error("The leaf that was created for the invitation isn't in the group.")]
+    UnknownMember,
+}
+
+/// Shared group error
+#[derive(Error, Debug, PartialEq, Eq, Clone)]
+pub enum SharedMlsGroupError {
+    /// The group is locked by another operation.
+    #[error("The group is locked by another operation.")]
+    WouldBlock,
+    /// An operation panicked while it held the lock. The state of the group is
+    /// unknown.
+    #[error("An operation panicked while it held the lock. The state of the group is unknown.")]
+    Poisoned,
+}
+
+/// Async group error
+#[cfg(feature = "async")]
+#[derive(Error, Debug, PartialEq, Clone)]
+pub enum AsyncGroupError<OperationError, KeyStoreError> {
+    /// See [`LibraryError`] for more details.
+    #[error(transparent)]
+    LibraryError(#[from] LibraryError),
+    /// The operation failed.
+    #[error("The operation failed: {0}")]
+    Operation(OperationError),
+    /// Error accessing the key store.
+    #[error("Error accessing the key store.")]
+    KeyStoreError(KeyStoreError),
+}
+
+/// Key store cache error
+#[cfg(feature = "async")]
+#[derive(Error, Debug, PartialEq, Eq, Clone)]
+pub enum KeyStoreCacheError {
+    /// A value could not be serialized.
+    #[error("A value could not be serialized: {0}")]
+    SerializationError(String),
+}
+
+/// Speculative key store error
+#[cfg(feature = "speculative")]
+#[derive(Error, Debug, PartialEq, Eq, Clone)]
+pub enum SpeculativeKeyStoreError {
+    /// A value could not be serialized.
+    #[error("A value could not be serialized: {0}")]
+    SerializationError(String),
+}
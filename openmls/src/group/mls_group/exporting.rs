@@ -1,19 +1,341 @@
+//! # Exporting secrets
+//!
+//! [`MlsGroup::export_secret()`] derives secrets for the application from
+//! the exporter secret of the current epoch, as `MLS-Exporter(Label, Context,
+//! Length)` defined in Section 8.5 of RFC 9420. All members of an epoch
+//! derive the same secret for the same label, context and length. The
+//! exporter secret is never exposed, and the following guarantees hold for
+//! the exported secrets:
+//!
+//! * Secrets with different labels or contexts are independent: knowing the
+//!   secret for one label doesn't reveal anything about the secret for
+//!   another label. The context is hashed into the derivation, so it binds
+//!   the secret to data of any length, e.g., a transcript or an identifier
+//!   of the feature that uses the secret.
+//! * Secrets of different epochs are independent, so members that join later
+//!   or were removed earlier can't derive them.
+//! * Labels that OpenMLS uses for its own keys, such as the keys of
+//!   [`HistoryBundle`]s and [`EphemeralKey`]s, are reserved. Exporting with
+//!   one of them fails with [`ExportSecretError::ReservedLabel`], so that
+//!   application secrets never coincide with keys that OpenMLS uses.
+//!
+//! Labels are given as an [`ExporterLabel`]. Applications should define one
+//! constant label per purpose, e.g.,
+//! `const CALL_KEY: ExporterLabel = ExporterLabel::new("com.example.call");`,
+//! instead of building labels at runtime. A plain `&str` can be used as well.
+//!
+//! Exported secrets are derived again for every call unless the label was
+//! created with [`ExporterLabel::cached()`]. The group then keeps the secrets
+//! of the label until the epoch changes or
+//! [`MlsGroup::clear_exported_secrets()`] is called. The cache is never
+//! persisted.
+//!
+//! Applications that consist of independent subsystems can hand each of them
+//! a [`SubExporter`] created with [`MlsGroup::sub_exporter()`] instead of
+//! coordinating labels between them. A sub-exporter is bound to a namespace,
+//! e.g., the name of a feature or module, and exports secrets in the same
+//! way as the group, but from a child of the exporter secret that is derived
+//! for the namespace. Secrets exported by sub-exporters of different
+//! namespaces are independent even if the same label is used, and they are
+//! independent from the secrets exported by the group itself. Sub-exporters
+//! can be nested with [`SubExporter::sub_exporter()`]. A sub-exporter is
+//! bound to the epoch it was created in and doesn't cache secrets.
+
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    sync::{Mutex, PoisonError},
+};
+
 use openmls_traits::crypto::OpenMlsCrypto;
 use openmls_traits::signatures::Signer;
+use tls_codec::SecretVLBytes;
 
-use crate::{group::errors::ExporterError, schedule::EpochAuthenticator};
+use crate::{
+    group::errors::ExporterError,
+    messages::ConfirmationTag,
+    schedule::{EpochAuthenticator, ExporterSecret, SUB_EXPORTER_LABEL},
+};
 
 use super::*;
 
+/// Labels that OpenMLS uses to export its own keys and that applications
+/// can't export secrets with.
+const RESERVED_EXPORTER_LABELS: [&str; 4] = [
+    history::HISTORY_KEY_LABEL,
+    history::HISTORY_SHARING_LABEL,
+    ephemeral::EPHEMERAL_KEY_LABEL,
+    SUB_EXPORTER_LABEL,
+];
+
+/// The label of a secret exported with [`MlsGroup::export_secret()`]. See
+/// the [module documentation](self) for details.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ExporterLabel {
+    label: Cow<'static, str>,
+    cached: bool,
+}
+
+impl ExporterLabel {
+    /// Creates a new [`ExporterLabel`] whose secrets are derived again for
+    /// every export.
+    pub const fn new(label: &'static str) -> Self {
+        Self {
+            label: Cow::Borrowed(label),
+            cached: false,
+        }
+    }
+
+    /// Creates a new [`ExporterLabel`] whose secrets are kept by the group
+    /// until the epoch changes.
+    pub const fn cached(label: &'static str) -> Self {
+        Self {
+            label: Cow::Borrowed(label),
+            cached: true,
+        }
+    }
+
+    /// Returns the label as string.
+    pub fn as_str(&self) -> &str {
+        &self.label
+    }
+
+    /// Returns `true` if the secrets exported with the label are cached.
+    pub fn is_cached(&self) -> bool {
+        self.cached
+    }
+}
+
+impl From<&str> for ExporterLabel {
+    fn from(label: &str) -> Self {
+        Self {
+            label: Cow::Owned(label.to_owned()),
+            cached: false,
+        }
+    }
+}
+
+impl From<String> for ExporterLabel {
+    fn from(label: String) -> Self {
+        Self {
+            label: Cow::Owned(label),
+            cached: false,
+        }
+    }
+}
+
+/// The epoch, label, context and length of a cached secret.
+type ExporterCacheKey = (GroupEpoch, String, Vec<u8>, usize);
+
+/// The secrets exported with cached [`ExporterLabel`]s.
+///
+/// Note: This has a hand-written `Debug` implementation.
+#[derive(Default)]
+pub(crate) struct ExporterCache {
+    secrets: Mutex<HashMap<ExporterCacheKey, SecretVLBytes>>,
+}
+
+impl std::fmt::Debug for ExporterCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ExporterCache")
+            .field("secrets", &self.len())
+            .finish()
+    }
+}
+
+impl ExporterCache {
+    fn get(&self, key: &ExporterCacheKey) -> Option<Vec<u8>> {
+        self.secrets
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(key)
+            .map(|secret| secret.as_slice().to_vec())
+    }
+
+    fn insert(&self, key: ExporterCacheKey, secret: &[u8]) {
+        self.secrets
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(key, secret.into());
+    }
+
+    /// Removes all secrets.
+    pub(crate) fn clear(&self) {
+        self.secrets
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+
+    /// Returns the number of cached secrets.
+    pub(crate) fn len(&self) -> usize {
+        self.secrets
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len()
+    }
+}
+
+/// An exporter for the secrets of a namespace, as returned by
+/// [`MlsGroup::sub_exporter()`]. See the [module documentation](self) for
+/// details.
+///
+/// Note: This has a hand-written `Debug` implementation.
+pub struct SubExporter {
+    namespace: String,
+    epoch: GroupEpoch,
+    ciphersuite: Ciphersuite,
+    secret: ExporterSecret,
+}
+
+impl std::fmt::Debug for SubExporter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SubExporter")
+            .field("namespace", &self.namespace)
+            .field("epoch", &self.epoch)
+            .finish()
+    }
+}
+
+impl SubExporter {
+    /// Returns the namespace of the sub-exporter. The namespaces of nested
+    /// sub-exporters are separated by `/`.
+    pub fn namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    /// Returns the epoch the sub-exporter was created in.
+    pub fn epoch(&self) -> GroupEpoch {
+        self.epoch
+    }
+
+    /// Exports a secret with the given `label`, `context` and `key_length`
+    /// from the namespace of the sub-exporter. The secrets are never cached,
+    /// regardless of the label.
+    ///
+    /// Returns [`ExportSecretError::ReservedLabel`] if the label is reserved
+    /// by OpenMLS.
+    /// Returns [`ExportSecretError::KeyLengthTooLong`] if the requested
+    /// key length is too long.
+    pub fn export_secret(
+        &self,
+        crypto: &impl OpenMlsCrypto,
+        label: impl Into<ExporterLabel>,
+        context: &[u8],
+        key_length: usize,
+    ) -> Result<Vec<u8>, ExportSecretError> {
+        let label = label.into();
+        if RESERVED_EXPORTER_LABELS.contains(&label.as_str()) {
+            return Err(ExportSecretError::ReservedLabel);
+        }
+        if key_length > u16::MAX.into() {
+            return Err(ExportSecretError::KeyLengthTooLong);
+        }
+        Ok(self
+            .secret
+            .derive_exported_secret(
+                self.ciphersuite,
+                crypto,
+                label.as_str(),
+                context,
+                key_length,
+            )
+            .map_err(LibraryError::unexpected_crypto_error)?)
+    }
+
+    /// Creates a sub-exporter for the given `namespace` within the namespace
+    /// of this sub-exporter.
+    pub fn sub_exporter(
+        &self,
+        crypto: &impl OpenMlsCrypto,
+        namespace: &str,
+    ) -> Result<SubExporter, LibraryError> {
+        let secret = self
+            .secret
+            .derive_sub_exporter_secret(self.ciphersuite, crypto, namespace.as_bytes())
+            .map_err(LibraryError::unexpected_crypto_error)?;
+        Ok(SubExporter {
+            namespace: format!("{}/{}", self.namespace, namespace),
+            epoch: self.epoch,
+            ciphersuite: self.ciphersuite,
+            secret,
+        })
+    }
+}
+
 impl MlsGroup {
     // === Export secrets ===
 
-    /// Exports a secret from the current epoch.
+    /// Exports a secret with the given `label`, `context` and `key_length`
+    /// from the current epoch. See the [module documentation](self) for
+    /// details.
+    ///
+    /// Returns [`ExportSecretError::ReservedLabel`] if the label is reserved
+    /// by OpenMLS.
     /// Returns [`ExportSecretError::KeyLengthTooLong`] if the requested
     /// key length is too long.
     /// Returns [`ExportSecretError::GroupStateError(MlsGroupStateError::UseAfterEviction)`](MlsGroupStateError::UseAfterEviction)
     /// if the group is not active.
     pub fn export_secret(
+        &self,
+        crypto: &impl OpenMlsCrypto,
+        label: impl Into<ExporterLabel>,
+        context: &[u8],
+        key_length: usize,
+    ) -> Result<Vec<u8>, ExportSecretError> {
+        let label = label.into();
+        if RESERVED_EXPORTER_LABELS.contains(&label.as_str()) {
+            return Err(ExportSecretError::ReservedLabel);
+        }
+        if !label.is_cached() {
+            return self.export_reserved_secret(crypto, label.as_str(), context, key_length);
+        }
+        let key = (
+            self.epoch(),
+            label.as_str().to_owned(),
+            context.to_vec(),
+            key_length,
+        );
+        if let Some(secret) = self.exporter_cache.get(&key) {
+            return Ok(secret);
+        }
+        let secret = self.export_reserved_secret(crypto, label.as_str(), context, key_length)?;
+        self.exporter_cache.insert(key, &secret);
+        Ok(secret)
+    }
+
+    /// Creates a [`SubExporter`] for the given `namespace` from the current
+    /// epoch. See the [module documentation](self) for details.
+    ///
+    /// Returns [`ExportSecretError::GroupStateError(MlsGroupStateError::UseAfterEviction)`](MlsGroupStateError::UseAfterEviction)
+    /// if the group is not active.
+    pub fn sub_exporter(
+        &self,
+        crypto: &impl OpenMlsCrypto,
+        namespace: &str,
+    ) -> Result<SubExporter, ExportSecretError> {
+        if !self.is_active() {
+            return Err(ExportSecretError::GroupStateError(
+                MlsGroupStateError::UseAfterEviction,
+            ));
+        }
+        Ok(SubExporter {
+            namespace: namespace.to_owned(),
+            epoch: self.epoch(),
+            ciphersuite: self.ciphersuite(),
+            secret: self.group.sub_exporter_secret(crypto, namespace)?,
+        })
+    }
+
+    /// Removes the secrets of cached [`ExporterLabel`]s. They are derived
+    /// again when they are exported the next time.
+    pub fn clear_exported_secrets(&self) {
+        self.exporter_cache.clear();
+    }
+
+    /// Exports a secret from the current epoch, also with a label that is
+    /// reserved for the keys of OpenMLS.
+    pub(super) fn export_reserved_secret(
         &self,
         crypto: &impl OpenMlsCrypto,
         label: &str,
@@ -40,6 +362,64 @@ impl MlsGroup {
         self.group.epoch_authenticator()
     }
 
+    /// Returns the [`GroupContext`] of the current epoch. It is the same for
+    /// all members of the group.
+    pub fn export_group_context(&self) -> &GroupContext {
+        self.group.context()
+    }
+
+    /// Returns the confirmation tag of the current epoch. It is the same for
+    /// all members of the group and authenticates the group state of the
+    /// epoch.
+    pub fn confirmation_tag(&self) -> &ConfirmationTag {
+        self.group.public_group().confirmation_tag()
+    }
+
+    /// Returns the confirmed transcript hash of the current epoch, i.e., the
+    /// hash of all commits up to and including the one that created the
+    /// epoch.
+    pub fn confirmed_transcript_hash(&self) -> &[u8] {
+        self.group.context().confirmed_transcript_hash()
+    }
+
+    /// Returns the interim transcript hash of the current epoch, i.e., the
+    /// confirmed transcript hash combined with the confirmation tag of the
+    /// epoch.
+    pub fn interim_transcript_hash(&self) -> &[u8] {
+        self.group.public_group().interim_transcript_hash()
+    }
+
+    /// Returns the epoch authenticator of the given `epoch`. The epoch
+    /// authenticators of past epochs are only available for the number of
+    /// epochs set with
+    /// [`MlsGroupConfigBuilder::past_epoch_authenticators()`](super::config::MlsGroupConfigBuilder::past_epoch_authenticators()).
+    /// Returns `None` for other epochs.
+    pub fn epoch_authenticator_for_epoch(&self, epoch: GroupEpoch) -> Option<&EpochAuthenticator> {
+        if epoch == self.epoch() {
+            return Some(self.epoch_authenticator());
+        }
+        self.past_epoch_authenticators
+            .iter()
+            .find(|(past_epoch, _)| *past_epoch == epoch)
+            .map(|(_, epoch_authenticator)| epoch_authenticator)
+    }
+
+    /// Store the epoch authenticator of the epoch that was just left and
+    /// discard the ones of epochs that are older than the configured number of
+    /// past epochs.
+    pub(super) fn store_epoch_authenticator(
+        &mut self,
+        epoch_authenticator: (GroupEpoch, EpochAuthenticator),
+    ) {
+        let past_epochs = self.mls_group_config.past_epoch_authenticators as u64;
+        if past_epochs > 0 {
+            self.past_epoch_authenticators.push(epoch_authenticator);
+        }
+        let current_epoch = self.epoch().as_u64();
+        self.past_epoch_authenticators
+            .retain(|(epoch, _)| epoch.as_u64().saturating_add(past_epochs) >= current_epoch);
+    }
+
     /// Returns the resumption PSK secret of the current epoch.
     pub fn resumption_psk_secret(&self) -> &ResumptionPskSecret {
         self.group.resumption_psk_secret()
@@ -51,16 +431,18 @@ impl MlsGroup {
         self.group.resumption_psk_store.get(epoch)
     }
 
-    /// Export a group info object for this group.
+    /// Export a group info object for this group. The GroupInfo advertises
+    /// the external keypair of the rotation period at the current time of the
+    /// `provider`'s clock.
     pub fn export_group_info(
         &self,
-        crypto: &impl OpenMlsCrypto,
+        provider: &impl OpenMlsProvider,
         signer: &impl Signer,
         with_ratchet_tree: bool,
     ) -> Result<MlsMessageOut, ExportGroupInfoError> {
         Ok(self
             .group
-            .export_group_info(crypto, signer, with_ratchet_tree)?
+            .export_group_info(provider, signer, with_ratchet_tree)?
             .into())
     }
 }
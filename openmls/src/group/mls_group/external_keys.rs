@@ -0,0 +1,180 @@
+//! # External keypair rotation
+//!
+//! Non-members join a group with an external commit that is encrypted to the
+//! HPKE external keypair advertised in the `external_pub` extension of a
+//! [`GroupInfo`](crate::messages::group_info::GroupInfo). By default, the
+//! external keypair is derived from the `external_secret` of the epoch and
+//! only changes with the epoch, so a published GroupInfo remains usable for
+//! as long as the group does not commit.
+//!
+//! The [`ExternalKeyRotationPolicy`] of the [`MlsGroupConfig`] rotates the
+//! external keypair independently of commits. Time is divided into rotation
+//! periods of `rotation_interval` seconds and the keypair of each period is
+//! derived from the `external_secret` and the period number, so all members
+//! agree on the keypair of a period without communicating.
+//! [`MlsGroup::rotate_external_keypair()`] exports a GroupInfo that
+//! advertises the keypair of the current period and should be called at least
+//! once per period to re-publish the GroupInfo.
+//!
+//! A rotated GroupInfo carries an [`ExternalKeyIdExtension`] with the
+//! period of its external keypair, which
+//! [`MlsGroup::join_by_external_commit()`] copies into the authenticated data
+//! of the external commit. Members decrypt an external commit with exactly the
+//! keypair it identifies, or with the keypair defined by the MLS specification
+//! if it doesn't identify one. Processing an external commit therefore doesn't
+//! depend on the clock of the member, so members with skewed clocks always
+//! agree on whether it is valid. The AAD passed to
+//! [`MlsGroup::join_by_external_commit()`] is not used for external commits
+//! to a rotated keypair.
+//!
+//! The keypairs of earlier periods are retired, but remain usable for
+//! `grace_period` seconds after they were replaced, so that joiners who
+//! fetched the GroupInfo shortly before a rotation can still join. Whether a
+//! keypair is retired depends on the time, so it can't be checked by each
+//! member without forking the group. Instead, the party that orders the
+//! messages of the group, usually the delivery service, drops external
+//! commits to retired keypairs before they are fanned out:
+//! [`ExternalKeyRotationPolicy::check_external_commit()`] returns
+//! [`StageCommitError::RetiredExternalKey`] for them.
+//!
+//! All members must use the same policy, otherwise they advertise keypairs
+//! that the delivery service considers retired.
+//!
+use openmls_traits::{clock::OpenMlsClock, crypto::OpenMlsCrypto, signatures::Signer};
+use serde::{Deserialize, Serialize};
+use tls_codec::{Deserialize as TlsDeserializeTrait, Serialize as TlsSerializeTrait};
+
+use super::{errors::ExportGroupInfoError, *};
+use crate::{
+    extensions::{CustomExtension, Extensions, ExternalKeyIdExtension},
+    group::errors::StageCommitError,
+    messages::group_info::VerifiableGroupInfo,
+};
+
+/// Defines how often the external keypair of the group is rotated. See the
+/// [module documentation](self) for details.
+///
+/// The default policy never rotates the external keypair, i.e., the
+/// external keypair is the one defined by the MLS specification.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExternalKeyRotationPolicy {
+    rotation_interval: Option<u64>,
+    grace_period: u64,
+}
+
+impl ExternalKeyRotationPolicy {
+    /// Creates a new [`ExternalKeyRotationPolicy`] that rotates the external
+    /// keypair every `rotation_interval` seconds and retires a replaced
+    /// keypair `grace_period` seconds after its rotation.
+    ///
+    /// A `rotation_interval` of 0 disables the rotation.
+    pub fn new(rotation_interval: u64, grace_period: u64) -> Self {
+        Self {
+            rotation_interval: Some(rotation_interval).filter(|interval| *interval > 0),
+            grace_period,
+        }
+    }
+
+    /// Returns the rotation interval in seconds, or `None` if the external
+    /// keypair is not rotated.
+    pub fn rotation_interval(&self) -> Option<u64> {
+        self.rotation_interval
+    }
+
+    /// Returns the number of seconds for which a replaced external keypair
+    /// is still usable.
+    pub fn grace_period(&self) -> u64 {
+        self.grace_period
+    }
+
+    /// Returns the rotation period at `time`, or `None` if the external
+    /// keypair is not rotated.
+    pub(crate) fn period(&self, time: u64) -> Option<u64> {
+        self.rotation_interval.map(|interval| time / interval)
+    }
+
+    /// Returns `true` if the external keypair of the rotation `period` is
+    /// retired at `time`, i.e., it was replaced more than `grace_period`
+    /// seconds ago.
+    pub fn is_retired(&self, period: u64, time: u64) -> bool {
+        self.period(time.saturating_sub(self.grace_period))
+            .map_or(false, |oldest| period < oldest)
+    }
+
+    /// Checks that the serialized `message` is not an external commit to an
+    /// external keypair that is retired at `time`. Messages that are not
+    /// external commits or that don't identify a rotated keypair pass the
+    /// check.
+    ///
+    /// This is meant for the delivery service, or whichever party orders the
+    /// messages of the group, so that only one clock decides which external
+    /// commits are accepted. See the [module documentation](self) for
+    /// details.
+    pub fn check_external_commit(
+        &self,
+        message: &MlsMessageView,
+        time: u64,
+    ) -> Result<(), StageCommitError> {
+        if message.sender() != Some(&Sender::NewMemberCommit) {
+            return Ok(());
+        }
+        let period = message
+            .authenticated_data()
+            .and_then(|aad| Extensions::tls_deserialize_exact(aad).ok())
+            .and_then(|extensions| extensions.custom::<ExternalKeyIdExtension>().ok().flatten())
+            .map(|external_key_id| external_key_id.period());
+        match period {
+            Some(period) if self.is_retired(period, time) => {
+                Err(StageCommitError::RetiredExternalKey)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Returns the authenticated data of an external commit to
+/// `verifiable_group_info`: the [`ExternalKeyIdExtension`] of the GroupInfo
+/// if it advertises a rotated external keypair and `aad` otherwise.
+pub(super) fn external_commit_aad(
+    verifiable_group_info: &VerifiableGroupInfo,
+    aad: &[u8],
+) -> Result<Vec<u8>, LibraryError> {
+    let external_key_id = verifiable_group_info
+        .extensions()
+        .custom::<ExternalKeyIdExtension>()
+        .ok()
+        .flatten();
+    match external_key_id {
+        Some(external_key_id) => Extensions::single(
+            external_key_id
+                .to_extension()
+                .map_err(|_| LibraryError::custom("Error encoding the external key ID"))?,
+        )
+        .tls_serialize_detached()
+        .map_err(LibraryError::missing_bound_check),
+        None => Ok(aad.to_vec()),
+    }
+}
+
+impl MlsGroup {
+    /// Exports a signed GroupInfo that advertises the external keypair of the
+    /// rotation period at the time of the given `clock`, so that it can be
+    /// re-published when the external keypair is rotated. See the
+    /// [module documentation](self) for details.
+    ///
+    /// If the [`ExternalKeyRotationPolicy`] of the group does not rotate the
+    /// external keypair, this is equivalent to
+    /// [`MlsGroup::export_group_info()`].
+    pub fn rotate_external_keypair(
+        &self,
+        crypto: &impl OpenMlsCrypto,
+        signer: &impl Signer,
+        clock: &impl OpenMlsClock,
+        with_ratchet_tree: bool,
+    ) -> Result<MlsMessageOut, ExportGroupInfoError> {
+        Ok(self
+            .group
+            .export_group_info_at(crypto, signer, with_ratchet_tree, clock.now())?
+            .into())
+    }
+}
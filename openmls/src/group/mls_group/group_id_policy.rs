@@ -0,0 +1,80 @@
+//! # Group ID policies
+//!
+//! Group IDs are chosen by the creator of a group and are otherwise opaque to
+//! OpenMLS. Deployments that encode information in group IDs, e.g., a tenant
+//! prefix or a domain, can reject malformed or foreign group IDs with a
+//! [`GroupIdPolicy`] in the [`MlsGroupConfig`].
+//!
+//! The policy is checked whenever a group is created or joined with the
+//! configuration:
+//!
+//! * [`MlsGroup::new()`] and [`MlsGroup::new_with_group_id()`] fail with
+//!   [`NewGroupError::InvalidGroupId`],
+//! * [`StagedWelcome`] and [`MlsGroup::new_from_welcome()`] fail with
+//!   [`WelcomeError::InvalidGroupId`] after the Welcome message was
+//!   decrypted, and
+//! * [`MlsGroup::join_by_external_commit()`] fails with
+//!   [`ExternalCommitError::InvalidGroupId`] before the commit is created.
+//!
+//! The policy is not part of the persisted group state and is not applied to
+//! groups that already exist.
+//!
+//! [`WelcomeError::InvalidGroupId`]: crate::group::WelcomeError::InvalidGroupId
+//! [`ExternalCommitError::InvalidGroupId`]: crate::group::ExternalCommitError::InvalidGroupId
+
+use std::{fmt, sync::Arc};
+
+use super::*;
+
+/// A validation function for group IDs.
+type GroupIdValidator = Arc<dyn Fn(&GroupId) -> bool + Send + Sync>;
+
+/// Defines which group IDs are accepted when a group is created or joined.
+/// See the [module documentation](self) for details.
+///
+/// The default policy accepts all group IDs.
+///
+/// Note: This has hand-written `Debug` and `PartialEq` implementations.
+/// Policies are equal if they use the same validation function.
+#[derive(Clone, Default)]
+pub struct GroupIdPolicy {
+    validator: Option<GroupIdValidator>,
+}
+
+impl GroupIdPolicy {
+    /// Creates a new [`GroupIdPolicy`] that accepts the group IDs for which
+    /// `validator` returns `true`.
+    pub fn new(validator: impl Fn(&GroupId) -> bool + Send + Sync + 'static) -> Self {
+        Self {
+            validator: Some(Arc::new(validator)),
+        }
+    }
+
+    /// Returns `true` if `group_id` is accepted by the policy.
+    pub fn accepts(&self, group_id: &GroupId) -> bool {
+        match &self.validator {
+            Some(validator) => validator(group_id),
+            None => true,
+        }
+    }
+}
+
+impl fmt::Debug for GroupIdPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GroupIdPolicy")
+            .field("validator", &self.validator.is_some())
+            .finish()
+    }
+}
+
+impl PartialEq for GroupIdPolicy {
+    fn eq(&self, other: &Self) -> bool {
+        match (&self.validator, &other.validator) {
+            (Some(validator), Some(other_validator)) => Arc::ptr_eq(validator, other_validator),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for GroupIdPolicy {}
@@ -0,0 +1,213 @@
+//! # Group merge
+//!
+//! After a network partition, the members of a group may end up in two
+//! divergent groups, e.g., because two members created a commit in the same
+//! epoch and the delivery service lost track of which one was accepted, or
+//! because the group was re-created while some members were unreachable. The
+//! divergent groups can't be merged on the protocol level. Instead, one of
+//! them is kept and the members that are only in the other group are added to
+//! it:
+//!
+//! 1. [`MlsGroup::merge_winner()`] picks the group that is kept. The choice is
+//!    deterministic, so that all members that know both groups pick the same
+//!    one.
+//! 2. [`MlsGroup::merge_group()`] creates a commit in the winning group that
+//!    adds the members of the other group that are missing and returns the
+//!    Welcome message for them.
+//! 3. The commit carries a [`GroupMergeExtension`] in its authenticated data
+//!    that identifies the state of the group that was merged. Members read it
+//!    with [`MlsGroup::group_merge_linkage()`] when they process the commit,
+//!    e.g., to record the merge for audit or to archive the other group.
+//!
+//! The other group is not modified. It is up to the application to stop
+//! using it once the merge commit was accepted by the delivery service.
+
+use openmls_traits::signatures::Signer;
+use tls_codec::Serialize as TlsSerializeTrait;
+
+use super::{errors::GroupMergeError, *};
+use crate::{
+    extensions::{Extensions, GroupMergeExtension},
+    messages::group_info::GroupInfo,
+};
+
+/// The result of [`MlsGroup::merge_group()`].
+#[derive(Debug)]
+pub struct GroupMerge {
+    commit: MlsMessageOut,
+    welcome: Option<MlsMessageOut>,
+    group_info: Option<GroupInfo>,
+    linkage: GroupMergeExtension,
+    added: Vec<Credential>,
+    missing_key_packages: Vec<Credential>,
+}
+
+impl GroupMerge {
+    /// Returns the commit that merges the groups.
+    pub fn commit(&self) -> &MlsMessageOut {
+        &self.commit
+    }
+
+    /// Returns the Welcome message for the added members or [`None`] if no
+    /// members were added.
+    pub fn welcome(&self) -> Option<&MlsMessageOut> {
+        self.welcome.as_ref()
+    }
+
+    /// Returns the [`GroupInfo`] of the new epoch if the group has the
+    /// `use_ratchet_tree_extension` flag set.
+    pub fn group_info(&self) -> Option<&GroupInfo> {
+        self.group_info.as_ref()
+    }
+
+    /// Returns the linkage extension that is carried in the commit.
+    pub fn linkage(&self) -> &GroupMergeExtension {
+        &self.linkage
+    }
+
+    /// Returns the credentials of the members that are added by the commit.
+    pub fn added(&self) -> &[Credential] {
+        &self.added
+    }
+
+    /// Returns the credentials of the members of the other group that are
+    /// missing in this group but couldn't be added, because no key package
+    /// was available for them.
+    pub fn missing_key_packages(&self) -> &[Credential] {
+        &self.missing_key_packages
+    }
+
+    /// Consumes the merge and returns the commit, the Welcome message and the
+    /// [`GroupInfo`].
+    pub fn into_messages(self) -> (MlsMessageOut, Option<MlsMessageOut>, Option<GroupInfo>) {
+        (self.commit, self.welcome, self.group_info)
+    }
+}
+
+impl MlsGroup {
+    /// Picks the group that is kept when divergent groups are merged. See the
+    /// [module documentation](self) for details.
+    ///
+    /// The group with the most members wins. Ties are broken by the highest
+    /// epoch and then by the smallest tree hash. Returns [`None`] if `groups`
+    /// is empty.
+    pub fn merge_winner<'a>(
+        groups: impl IntoIterator<Item = &'a MlsGroup>,
+    ) -> Option<&'a MlsGroup> {
+        groups.into_iter().max_by(|a, b| {
+            a.members()
+                .count()
+                .cmp(&b.members().count())
+                .then(a.epoch().cmp(&b.epoch()))
+                .then_with(|| {
+                    b.group
+                        .context()
+                        .tree_hash()
+                        .cmp(a.group.context().tree_hash())
+                })
+        })
+    }
+
+    /// Merges the divergent group `other` into this group. See the
+    /// [module documentation](self) for details.
+    ///
+    /// Members of `other` whose credential is not the credential of a member
+    /// of this group are added with the key package returned by
+    /// `key_package_for`. Members for which no key package is returned are
+    /// skipped and listed in [`GroupMerge::missing_key_packages()`]. If no
+    /// members are missing, the commit is an empty commit with a path, so that
+    /// the merge is still recorded in the group.
+    ///
+    /// The commit carries a [`GroupMergeExtension`] for `other` in its
+    /// authenticated data, next to the metadata set with
+    /// [`MlsGroup::set_commit_metadata()`]. The AAD set with
+    /// [`MlsGroup::set_aad()`] is not used for this commit.
+    ///
+    /// Like other commits, the commit is pending and has to be merged with
+    /// [`MlsGroup::merge_pending_commit()`] once it was accepted by the
+    /// delivery service.
+    pub fn merge_group<KeyStore: OpenMlsKeyStore>(
+        &mut self,
+        provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
+        signer: &impl Signer,
+        other: &MlsGroup,
+        mut key_package_for: impl FnMut(&Member) -> Option<KeyPackage>,
+    ) -> Result<GroupMerge, GroupMergeError<KeyStore::Error>> {
+        self.is_operational()?;
+
+        let mut key_packages = vec![];
+        let mut added = vec![];
+        let mut missing_key_packages = vec![];
+        for member in other.members() {
+            if self
+                .members()
+                .any(|own_member| own_member.credential == member.credential)
+            {
+                continue;
+            }
+            match key_package_for(&member) {
+                Some(key_package) => {
+                    if key_package.leaf_node().credential() != &member.credential {
+                        return Err(GroupMergeError::KeyPackageMismatch);
+                    }
+                    added.push(member.credential);
+                    key_packages.push(key_package);
+                }
+                None => missing_key_packages.push(member.credential),
+            }
+        }
+
+        let linkage = GroupMergeExtension::new(
+            other.group_id().clone(),
+            other.epoch(),
+            other.group.context().tree_hash(),
+        );
+        let mut extensions = vec![linkage
+            .to_extension()
+            .map_err(|_| LibraryError::custom("Error encoding group merge"))?];
+        extensions.extend(self.commit_metadata_extension()?);
+        let aad = Extensions::from_vec(extensions)
+            .map_err(|_| LibraryError::custom("Error encoding group merge"))?
+            .tls_serialize_detached()
+            .map_err(LibraryError::missing_bound_check)?;
+
+        // The commit AAD is derived from `self.aad`, so that sequencing tokens
+        // are still applied.
+        let application_aad = std::mem::replace(&mut self.aad, aad);
+        let commit_metadata = self.commit_metadata.take();
+        let result = if key_packages.is_empty() {
+            self.self_update(provider, signer)
+                .map(CommitOutput::into_parts)
+                .map_err(GroupMergeError::from)
+        } else {
+            self.add_members(provider, signer, &key_packages)
+                .map(CommitOutput::into_parts)
+                .map(|(commit, welcome, group_info)| (commit, Some(welcome), group_info))
+                .map_err(GroupMergeError::from)
+        };
+        self.aad = application_aad;
+        self.commit_metadata = commit_metadata;
+        let (commit, welcome, group_info) = result?;
+
+        Ok(GroupMerge {
+            commit,
+            welcome,
+            group_info,
+            linkage,
+            added,
+            missing_key_packages,
+        })
+    }
+
+    /// Returns the [`GroupMergeExtension`] of a processed message if it is a
+    /// commit created by [`MlsGroup::merge_group()`].
+    pub fn group_merge_linkage(
+        &self,
+        processed_message: &ProcessedMessage,
+    ) -> Option<GroupMergeExtension> {
+        self.commit_aad_extensions(processed_message)?
+            .custom::<GroupMergeExtension>()
+            .ok()
+            .flatten()
+    }
+}
@@ -0,0 +1,309 @@
+//! # History sharing
+//!
+//! Members that join a group can't decrypt the messages that were sent before
+//! they joined. Applications that want to offer a "share chat history"
+//! feature can let existing members hand over the keys that protect the
+//! message history of past epochs.
+//!
+//! History sharing is disabled by default and enabled with
+//! [`MlsGroupConfigBuilder::history_epochs()`](super::config::MlsGroupConfigBuilder::history_epochs()).
+//! When enabled, the group derives a [`HistoryKey`] for every epoch from the
+//! exporter secret of the epoch and keeps the history keys of the configured
+//! number of most recent epochs. The application uses
+//! [`MlsGroup::history_key()`] to encrypt its archive of the messages of an
+//! epoch, e.g., the decrypted application messages.
+//!
+//! To share the history with new members, an existing member exports the
+//! history keys of a range of epochs with [`MlsGroup::export_history()`]. The
+//! resulting [`HistoryBundle`] is encrypted with a key that is exported from
+//! the current epoch and can be sent to the new members together with the
+//! encrypted archive. After joining, the new members import the keys with
+//! [`MlsGroup::import_history()`].
+//!
+//! The security boundaries are as follows:
+//!
+//! * History keys are derived with the exporter. They don't reveal the epoch
+//!   secrets or the secret tree of an epoch, so they can't be used to decrypt
+//!   the MLS messages of the epoch, to forge messages or to derive the secrets
+//!   of other epochs.
+//! * A [`HistoryBundle`] can be decrypted by all members of the epoch in which
+//!   it was exported, but by nobody else. It must therefore be exported after
+//!   the new members were added and before the next commit.
+//! * Only the history of the epochs in the exported range is shared. Keeping
+//!   history keys around weakens the forward secrecy of the archived
+//!   messages, so the number of kept epochs should be as low as possible.
+
+use std::ops::RangeBounds;
+
+use openmls_traits::{crypto::OpenMlsCrypto, types::CryptoError};
+use tls_codec::{
+    Deserialize as TlsDeserializeTrait, SecretVLBytes, Serialize as TlsSerializeTrait,
+    TlsDeserialize, TlsSerialize, TlsSize, VLBytes,
+};
+
+use super::{errors::HistoryError, *};
+use crate::group::errors::ExporterError;
+
+/// The exporter label for history keys.
+pub(super) const HISTORY_KEY_LABEL: &str = "history key";
+/// The exporter label for the key that encrypts a [`HistoryBundle`].
+pub(super) const HISTORY_SHARING_LABEL: &str = "history sharing";
+
+/// The key that protects the message history of an epoch. See the
+/// [module documentation](self) for details.
+///
+/// Note: This has a hand-written `Debug` implementation.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, TlsSerialize, TlsDeserialize, TlsSize)]
+pub struct HistoryKey {
+    epoch: GroupEpoch,
+    key: SecretVLBytes,
+}
+
+impl std::fmt::Debug for HistoryKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut ds = f.debug_struct("HistoryKey");
+        ds.field("epoch", &self.epoch);
+
+        #[cfg(feature = "crypto-debug")]
+        ds.field("key", &self.key);
+        #[cfg(not(feature = "crypto-debug"))]
+        ds.field("key", &"***");
+
+        ds.finish()
+    }
+}
+
+impl HistoryKey {
+    /// Returns the epoch of the history key.
+    pub fn epoch(&self) -> GroupEpoch {
+        self.epoch
+    }
+
+    /// Returns the key as byte slice.
+    pub fn as_slice(&self) -> &[u8] {
+        self.key.as_slice()
+    }
+}
+
+/// The encrypted history keys of a range of epochs. See the
+/// [module documentation](self) for details.
+#[derive(
+    Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TlsSerialize, TlsDeserialize, TlsSize,
+)]
+pub struct HistoryBundle {
+    group_id: GroupId,
+    epoch: GroupEpoch,
+    nonce: VLBytes,
+    ciphertext: VLBytes,
+}
+
+impl HistoryBundle {
+    /// Returns the ID of the group of the bundle.
+    pub fn group_id(&self) -> &GroupId {
+        &self.group_id
+    }
+
+    /// Returns the epoch in which the bundle was exported. Only members of
+    /// this epoch can import the bundle.
+    pub fn epoch(&self) -> GroupEpoch {
+        self.epoch
+    }
+}
+
+impl MlsGroup {
+    /// Returns the [`HistoryKey`] of the given epoch, or `None` if the group
+    /// doesn't have it.
+    pub fn history_key(&self, epoch: GroupEpoch) -> Option<&HistoryKey> {
+        self.history_keys
+            .iter()
+            .find(|history_key| history_key.epoch == epoch)
+    }
+
+    /// Returns all [`HistoryKey`]s of the group, ordered by epoch.
+    pub fn history_keys(&self) -> &[HistoryKey] {
+        &self.history_keys
+    }
+
+    /// Export the history keys of the given range of `epochs` in a
+    /// [`HistoryBundle`] that can be imported by all members of the current
+    /// epoch. See the [module documentation](self) for details.
+    ///
+    /// Returns an error if history sharing is disabled, if the group is
+    /// inactive or if the group has no history keys in the range.
+    pub fn export_history(
+        &self,
+        provider: &impl OpenMlsProvider,
+        epochs: impl RangeBounds<GroupEpoch>,
+    ) -> Result<HistoryBundle, HistoryError> {
+        self.check_history_sharing()?;
+        let history_keys: Vec<HistoryKey> = self
+            .history_keys
+            .iter()
+            .filter(|history_key| epochs.contains(&history_key.epoch))
+            .cloned()
+            .collect();
+        if history_keys.is_empty() {
+            return Err(HistoryError::NoHistoryKeys);
+        }
+
+        let ciphersuite = self.ciphersuite();
+        let nonce = provider
+            .rand()
+            .random_vec(ciphersuite.aead_nonce_length())
+            .map_err(|_| {
+                LibraryError::unexpected_crypto_error(CryptoError::InsufficientRandomness)
+            })?;
+        let plaintext = history_keys
+            .tls_serialize_detached()
+            .map_err(LibraryError::missing_bound_check)?;
+        let ciphertext = provider
+            .crypto()
+            .aead_encrypt(
+                ciphersuite.aead_algorithm(),
+                &self.history_sharing_key(provider.crypto())?,
+                &plaintext,
+                &nonce,
+                &self.history_bundle_aad(self.epoch())?,
+            )
+            .map_err(LibraryError::unexpected_crypto_error)?;
+
+        Ok(HistoryBundle {
+            group_id: self.group_id().clone(),
+            epoch: self.epoch(),
+            nonce: nonce.into(),
+            ciphertext: ciphertext.into(),
+        })
+    }
+
+    /// Import the history keys of a [`HistoryBundle`] that was exported in the
+    /// current epoch. Keys of epochs for which the group already has a history
+    /// key are ignored. Returns the number of imported keys.
+    ///
+    /// Imported keys are kept like the keys derived by the group, i.e., keys
+    /// of epochs that are older than the configured number of epochs are
+    /// discarded. See the [module documentation](self) for details.
+    pub fn import_history(
+        &mut self,
+        crypto: &impl OpenMlsCrypto,
+        history_bundle: &HistoryBundle,
+    ) -> Result<usize, HistoryError> {
+        self.check_history_sharing()?;
+        if &history_bundle.group_id != self.group_id() {
+            return Err(HistoryError::WrongGroup);
+        }
+        if history_bundle.epoch != self.epoch() {
+            return Err(HistoryError::WrongEpoch);
+        }
+
+        let plaintext = crypto
+            .aead_decrypt(
+                self.ciphersuite().aead_algorithm(),
+                &self.history_sharing_key(crypto)?,
+                history_bundle.ciphertext.as_slice(),
+                history_bundle.nonce.as_slice(),
+                &self.history_bundle_aad(history_bundle.epoch)?,
+            )
+            .map_err(|_| HistoryError::DecryptionFailed)?;
+        let history_keys = Vec::<HistoryKey>::tls_deserialize_exact(plaintext)
+            .map_err(|_| HistoryError::MalformedBundle)?;
+
+        let mut imported = 0;
+        for history_key in history_keys {
+            if history_key.epoch <= self.epoch() && self.history_key(history_key.epoch).is_none() {
+                self.history_keys.push(history_key);
+                imported += 1;
+            }
+        }
+        self.history_keys
+            .sort_by_key(|history_key| history_key.epoch);
+        self.prune_history_keys();
+
+        // Since the state of the group might be changed, arm the state flag
+        self.flag_state_change();
+
+        Ok(imported)
+    }
+
+    /// Derive and store the [`HistoryKey`] of the current epoch if history
+    /// sharing is enabled.
+    pub(super) fn store_history_key(
+        &mut self,
+        crypto: &impl OpenMlsCrypto,
+    ) -> Result<(), LibraryError> {
+        if self.mls_group_config.history_epochs == 0 {
+            self.history_keys.clear();
+            return Ok(());
+        }
+        if self.history_key(self.epoch()).is_none() {
+            let key = self.export_group_secret(
+                crypto,
+                HISTORY_KEY_LABEL,
+                self.ciphersuite().hash_length(),
+            )?;
+            self.history_keys.push(HistoryKey {
+                epoch: self.epoch(),
+                key: key.into(),
+            });
+        }
+        self.prune_history_keys();
+        Ok(())
+    }
+
+    /// Discard the history keys of epochs that are older than the configured
+    /// number of epochs.
+    fn prune_history_keys(&mut self) {
+        let history_epochs = self.mls_group_config.history_epochs as u64;
+        let current_epoch = self.epoch().as_u64();
+        self.history_keys.retain(|history_key| {
+            history_key.epoch.as_u64().saturating_add(history_epochs) > current_epoch
+        });
+    }
+
+    fn check_history_sharing(&self) -> Result<(), HistoryError> {
+        if self.mls_group_config.history_epochs == 0 {
+            return Err(HistoryError::HistorySharingDisabled);
+        }
+        if !self.is_active() {
+            return Err(MlsGroupStateError::UseAfterEviction.into());
+        }
+        Ok(())
+    }
+
+    /// Returns the key that encrypts [`HistoryBundle`]s in the current epoch.
+    fn history_sharing_key(&self, crypto: &impl OpenMlsCrypto) -> Result<Vec<u8>, LibraryError> {
+        self.export_group_secret(
+            crypto,
+            HISTORY_SHARING_LABEL,
+            self.ciphersuite().aead_key_length(),
+        )
+    }
+
+    fn export_group_secret(
+        &self,
+        crypto: &impl OpenMlsCrypto,
+        label: &str,
+        key_length: usize,
+    ) -> Result<Vec<u8>, LibraryError> {
+        self.group
+            .export_secret(crypto, label, self.group_id().as_slice(), key_length)
+            .map_err(|e| match e {
+                ExporterError::LibraryError(e) => e,
+                ExporterError::KeyLengthTooLong => {
+                    LibraryError::custom("History key length is too long")
+                }
+            })
+    }
+
+    /// Returns the authenticated data of a [`HistoryBundle`], which binds the
+    /// bundle to the group and epoch.
+    fn history_bundle_aad(&self, epoch: GroupEpoch) -> Result<Vec<u8>, LibraryError> {
+        let mut aad = self
+            .group_id()
+            .tls_serialize_detached()
+            .map_err(LibraryError::missing_bound_check)?;
+        epoch
+            .tls_serialize(&mut aad)
+            .map_err(LibraryError::missing_bound_check)?;
+        Ok(aad)
+    }
+}
@@ -0,0 +1,80 @@
+//! # Cache invalidation
+//!
+//! Delivery services often cache artifacts that new members need to join a
+//! group, e.g., the [`GroupInfo`] for external commits, the exported ratchet
+//! tree and the `external_pub` extension. Members upload fresh versions of
+//! these artifacts, but the DS can't tell from the ciphertexts it forwards
+//! when its cached versions become stale.
+//!
+//! After an [`MlsGroup`] merged a commit, [`MlsGroup::stale_artifacts()`]
+//! tells the application which of these artifacts are now stale, so that it
+//! can invalidate exactly these instead of invalidating everything on every
+//! message:
+//!
+//! * The [`GroupInfo`] and the `external_pub` are bound to the epoch and are
+//!   stale after every commit.
+//! * The ratchet tree is only stale if the commit changed the tree, i.e., if
+//!   the tree hash of the new epoch differs from the one of the old epoch.
+//!
+//! Proposals and application messages never make any of these artifacts
+//! stale.
+//!
+//! [`GroupInfo`]: crate::messages::group_info::GroupInfo
+
+use super::*;
+
+/// The published artifacts of a group that are stale after a merged commit.
+/// See the [module documentation](self) for details.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StaleArtifacts {
+    epoch: GroupEpoch,
+    group_info: bool,
+    ratchet_tree: bool,
+    external_pub: bool,
+}
+
+impl StaleArtifacts {
+    /// Returns the epoch of the group after the commit was merged. Artifacts
+    /// of earlier epochs are stale.
+    pub fn epoch(&self) -> GroupEpoch {
+        self.epoch
+    }
+
+    /// Returns `true` if the
+    /// [`GroupInfo`](crate::messages::group_info::GroupInfo) is stale.
+    pub fn group_info(&self) -> bool {
+        self.group_info
+    }
+
+    /// Returns `true` if the exported ratchet tree is stale.
+    pub fn ratchet_tree(&self) -> bool {
+        self.ratchet_tree
+    }
+
+    /// Returns `true` if the `external_pub` is stale.
+    pub fn external_pub(&self) -> bool {
+        self.external_pub
+    }
+}
+
+impl MlsGroup {
+    /// Returns the published artifacts that are stale since the last commit
+    /// merged by this group, or `None` if no commit has been merged since the
+    /// group was created or joined.
+    pub fn stale_artifacts(&self) -> Option<&StaleArtifacts> {
+        self.stale_artifacts.as_ref()
+    }
+
+    /// Returns the artifacts that become stale when the given commit is
+    /// merged.
+    pub(super) fn pending_stale_artifacts(&self, staged_commit: &StagedCommit) -> StaleArtifacts {
+        let new_context = staged_commit.group_context();
+        let epoch_changed = new_context.epoch() != self.epoch();
+        StaleArtifacts {
+            epoch: new_context.epoch(),
+            group_info: epoch_changed,
+            ratchet_tree: new_context.tree_hash() != self.group.context().tree_hash(),
+            external_pub: epoch_changed,
+        }
+    }
+}
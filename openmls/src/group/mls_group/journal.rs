@@ -0,0 +1,180 @@
+//! # Operation journal
+//!
+//! When a group ends up in a broken state, e.g., because it can't process the
+//! commits of other members anymore, the state alone rarely tells how it got
+//! there. An [`MlsGroup`] can keep a journal of its last operations, so that
+//! support can reconstruct the sequence of operations that led to the
+//! broken state.
+//!
+//! The journal is disabled by default and enabled with
+//! [`MlsGroupConfigBuilder::operation_journal()`](super::config::MlsGroupConfigBuilder::operation_journal()),
+//! which sets the number of entries that are kept. Once the journal is full,
+//! the oldest entry is discarded for every new one. The journal is persisted
+//! with the group and is part of the [`GroupDump`] of
+//! [`MlsGroup::debug_dump()`].
+//!
+//! Every [`JournalEntry`] records the type of the operation, a hash of its
+//! parameters, the epoch of the group after the operation and the
+//! [`ErrorCode`] if the operation failed. The journal contains no message
+//! content and no secrets. The parameters are only recorded as a hash, which
+//! lets the application match an entry to a message that it logged, e.g., by
+//! hashing the messages it received with the hash function of the
+//! ciphersuite. The hash covers:
+//!
+//! * [`OperationType::ProcessMessage`]: the serialized `PublicMessage` or
+//!   `PrivateMessage`.
+//! * [`OperationType::AddMembers`]: the serialized key packages.
+//! * [`OperationType::RemoveMembers`]: the serialized leaf indices.
+//! * [`OperationType::CommitToPendingProposals`]: the serialized references of
+//!   the pending proposals.
+//!
+//! The other operations don't have parameters and their hash is empty.
+
+use std::collections::VecDeque;
+
+use openmls_traits::crypto::OpenMlsCrypto;
+use tls_codec::Serialize as TlsSerializeTrait;
+
+use super::*;
+use crate::{
+    error::{ErrorCode, StableErrorCode},
+    inspect::{HexBytes, JournalEntryDump},
+};
+
+/// The operations that are recorded in the journal of a group.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum OperationType {
+    /// [`MlsGroup::process_message()`]
+    ProcessMessage,
+    /// [`MlsGroup::add_members()`]
+    AddMembers,
+    /// [`MlsGroup::remove_members()`]
+    RemoveMembers,
+    /// [`MlsGroup::self_update()`]
+    SelfUpdate,
+    /// [`MlsGroup::commit_to_pending_proposals()`]
+    CommitToPendingProposals,
+    /// [`MlsGroup::merge_staged_commit()`], which is also called by
+    /// [`MlsGroup::merge_pending_commit()`].
+    MergeCommit,
+}
+
+impl OperationType {
+    /// Returns the name of the operation, e.g., for logs.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OperationType::ProcessMessage => "process_message",
+            OperationType::AddMembers => "add_members",
+            OperationType::RemoveMembers => "remove_members",
+            OperationType::SelfUpdate => "self_update",
+            OperationType::CommitToPendingProposals => "commit_to_pending_proposals",
+            OperationType::MergeCommit => "merge_commit",
+        }
+    }
+}
+
+/// An operation recorded in the journal of a group. See the
+/// [module documentation](self) for details.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    operation: OperationType,
+    parameters_hash: Vec<u8>,
+    epoch: GroupEpoch,
+    error: Option<u32>,
+}
+
+impl JournalEntry {
+    /// Returns the type of the operation.
+    pub fn operation(&self) -> OperationType {
+        self.operation
+    }
+
+    /// Returns the hash of the parameters of the operation. It is empty if
+    /// the operation doesn't have parameters.
+    pub fn parameters_hash(&self) -> &[u8] {
+        &self.parameters_hash
+    }
+
+    /// Returns the epoch of the group after the operation.
+    pub fn epoch(&self) -> GroupEpoch {
+        self.epoch
+    }
+
+    /// Returns the code of the error if the operation failed.
+    pub fn error(&self) -> Option<ErrorCode> {
+        self.error.and_then(ErrorCode::from_u32)
+    }
+
+    pub(crate) fn dump(&self) -> JournalEntryDump {
+        JournalEntryDump {
+            operation: self.operation.as_str().to_owned(),
+            parameters_hash: HexBytes::from(self.parameters_hash.as_slice()),
+            epoch: self.epoch.as_u64(),
+            error: self.error,
+        }
+    }
+}
+
+impl MlsGroup {
+    /// Returns the entries of the operation journal, oldest first.
+    pub fn operation_journal(&self) -> impl Iterator<Item = &JournalEntry> {
+        self.operation_journal.iter()
+    }
+
+    /// Records the `result` of an operation in the journal if it is enabled.
+    /// `parameters` are the serialized parameters of the operation.
+    pub(super) fn record_operation<T, E: StableErrorCode>(
+        &mut self,
+        crypto: &impl OpenMlsCrypto,
+        operation: OperationType,
+        parameters: &[u8],
+        result: &Result<T, E>,
+    ) {
+        let capacity = self.mls_group_config.operation_journal;
+        if capacity == 0 {
+            self.operation_journal.clear();
+            return;
+        }
+        let parameters_hash = if parameters.is_empty() {
+            vec![]
+        } else {
+            // The journal is only a debugging aid, a failure to hash the
+            // parameters must not fail the operation.
+            crypto
+                .hash(self.ciphersuite().hash_algorithm(), parameters)
+                .unwrap_or_default()
+        };
+        while self.operation_journal.len() >= capacity {
+            self.operation_journal.pop_front();
+        }
+        self.operation_journal.push_back(JournalEntry {
+            operation,
+            parameters_hash,
+            epoch: self.epoch(),
+            error: result.as_ref().err().map(|e| e.error_code().code()),
+        });
+    }
+}
+
+/// Returns the serialized `message` as it is hashed for the journal.
+pub(super) fn protocol_message_parameters(message: &ProtocolMessage) -> Vec<u8> {
+    match message {
+        ProtocolMessage::PrivateMessage(message) => message.tls_serialize_detached(),
+        ProtocolMessage::PublicMessage(message) => message.tls_serialize_detached(),
+    }
+    .unwrap_or_default()
+}
+
+/// Returns the serialized `values` as they are hashed for the journal.
+pub(super) fn serialized_parameters<V: TlsSerializeTrait>(values: &[V]) -> Vec<u8> {
+    values
+        .iter()
+        .map(|value| value.tls_serialize_detached())
+        .collect::<Result<Vec<_>, _>>()
+        .map(|values| values.concat())
+        .unwrap_or_default()
+}
+
+/// The operation journal of a group.
+pub(super) type OperationJournal = VecDeque<JournalEntry>;
@@ -0,0 +1,182 @@
+//! # Scheduled self-updates
+//!
+//! Post-compromise security requires that members update their leaf
+//! regularly: until a member updates its leaf, an attacker who compromised
+//! the member's keys can keep decrypting the messages of the group. Many
+//! applications enforce a policy like "every member updates its leaf at least
+//! once a week".
+//!
+//! The [`SelfUpdatePolicy`] of the [`MlsGroupConfig`] defines the maximum age
+//! of the own leaf and whether it is updated with a commit or a proposal. The
+//! application calls [`MlsGroup::maintenance()`] regularly, e.g., when the
+//! application starts or once a day, with an [`OpenMlsClock`] such as the
+//! [`SystemClock`]. The group tracks when the own leaf was last updated and
+//! returns the update commit or proposal once the leaf is too old. The
+//! message is handled like the result of [`MlsGroup::self_update()`] or
+//! [`MlsGroup::propose_self_update()`].
+//!
+//! The age of the leaf is measured from the first call to
+//! [`MlsGroup::maintenance()`] after the leaf was updated, also if it was
+//! updated without maintenance, e.g., with [`MlsGroup::self_update()`].
+//!
+//! [`SystemClock`]: crate::key_packages::SystemClock
+
+use openmls_traits::{clock::OpenMlsClock, signatures::Signer};
+use serde::{Deserialize, Serialize};
+
+use super::{errors::MaintenanceError, *};
+use crate::{messages::group_info::GroupInfo, treesync::node::encryption_keys::EncryptionKey};
+
+/// Defines how the own leaf is updated by [`MlsGroup::maintenance()`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SelfUpdateMode {
+    /// The own leaf is updated with a commit.
+    #[default]
+    Commit,
+    /// The own leaf is updated with a proposal that another member commits.
+    Proposal,
+}
+
+/// Defines when and how the own leaf is updated by
+/// [`MlsGroup::maintenance()`]. See the [module documentation](self) for
+/// details.
+///
+/// The default policy never updates the own leaf.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SelfUpdatePolicy {
+    max_leaf_age: Option<u64>,
+    mode: SelfUpdateMode,
+}
+
+impl SelfUpdatePolicy {
+    /// Creates a new [`SelfUpdatePolicy`] that updates the own leaf with the
+    /// given `mode` once it is older than `max_leaf_age` seconds.
+    pub fn new(max_leaf_age: u64, mode: SelfUpdateMode) -> Self {
+        Self {
+            max_leaf_age: Some(max_leaf_age),
+            mode,
+        }
+    }
+
+    /// Returns the maximum age of the own leaf in seconds, or `None` if the
+    /// own leaf is never updated.
+    pub fn max_leaf_age(&self) -> Option<u64> {
+        self.max_leaf_age
+    }
+
+    /// Returns how the own leaf is updated.
+    pub fn mode(&self) -> SelfUpdateMode {
+        self.mode
+    }
+}
+
+/// The message that updates the own leaf, as returned by
+/// [`MlsGroup::maintenance()`].
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug)]
+pub enum MaintenanceMessage {
+    /// A commit that updates the own leaf. It is pending until it is merged
+    /// with [`MlsGroup::merge_pending_commit()`].
+    Commit {
+        /// The commit.
+        commit: MlsMessageOut,
+        /// The Welcome message for members that are added by proposals
+        /// that are committed as well.
+        welcome: Option<MlsMessageOut>,
+        /// The [`GroupInfo`] of the new epoch if the group has the
+        /// `use_ratchet_tree_extension` flag set.
+        group_info: Option<GroupInfo>,
+    },
+    /// A proposal that updates the own leaf.
+    Proposal {
+        /// The proposal.
+        proposal: MlsMessageOut,
+        /// The reference of the proposal.
+        proposal_ref: ProposalRef,
+    },
+}
+
+/// The time at which the own leaf was first seen by
+/// [`MlsGroup::maintenance()`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct LeafAge {
+    encryption_key: EncryptionKey,
+    updated_at: u64,
+}
+
+impl MlsGroup {
+    /// Updates the own leaf if it is older than the maximum age of the
+    /// [`SelfUpdatePolicy`] of the group at the current time of the `clock`.
+    /// See the [module documentation](self) for details.
+    ///
+    /// Returns `None` if the own leaf doesn't need to be updated yet or if a
+    /// commit or an update proposal of the own leaf is already pending, or if
+    /// a snapshot of the group is taken.
+    pub fn maintenance<KeyStore: OpenMlsKeyStore>(
+        &mut self,
+        provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
+        signer: &impl Signer,
+        clock: &impl OpenMlsClock,
+    ) -> Result<Option<MaintenanceMessage>, MaintenanceError<KeyStore::Error>> {
+        match self.is_operational() {
+            Err(MlsGroupStateError::PendingCommit | MlsGroupStateError::SnapshotActive) => {
+                return Ok(None)
+            }
+            result => result?,
+        }
+        let policy = self.configuration().self_update_policy();
+        let Some(max_leaf_age) = policy.max_leaf_age() else {
+            return Ok(None);
+        };
+        let now = clock.now();
+        let encryption_key = self
+            .own_leaf()
+            .ok_or_else(|| LibraryError::custom("The own leaf of a member is in the tree"))?
+            .encryption_key()
+            .clone();
+        let updated_at = match &self.leaf_age {
+            Some(leaf_age) if leaf_age.encryption_key == encryption_key => leaf_age.updated_at,
+            _ => {
+                self.leaf_age = Some(LeafAge {
+                    encryption_key,
+                    updated_at: now,
+                });
+                self.flag_state_change();
+                now
+            }
+        };
+        if now.saturating_sub(updated_at) < max_leaf_age {
+            return Ok(None);
+        }
+        match policy.mode() {
+            SelfUpdateMode::Commit => {
+                let (commit, welcome, group_info) =
+                    self.self_update(provider, signer)?.into_parts();
+                Ok(Some(MaintenanceMessage::Commit {
+                    commit,
+                    welcome,
+                    group_info,
+                }))
+            }
+            SelfUpdateMode::Proposal if self.own_leaf_nodes.is_empty() => {
+                let (proposal, proposal_ref) = self.propose_self_update(provider, signer, None)?;
+                Ok(Some(MaintenanceMessage::Proposal {
+                    proposal,
+                    proposal_ref,
+                }))
+            }
+            SelfUpdateMode::Proposal => Ok(None),
+        }
+    }
+
+    /// Returns the time (in seconds since the Unix epoch) at which
+    /// [`MlsGroup::maintenance()`] first saw the current own leaf, or `None`
+    /// if it didn't see it yet.
+    pub fn own_leaf_updated_at(&self) -> Option<u64> {
+        let encryption_key = self.own_leaf()?.encryption_key();
+        self.leaf_age
+            .as_ref()
+            .filter(|leaf_age| &leaf_age.encryption_key == encryption_key)
+            .map(|leaf_age| leaf_age.updated_at)
+    }
+}
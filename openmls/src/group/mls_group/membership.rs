@@ -6,12 +6,14 @@ use core_group::create_commit_params::CreateCommitParams;
 use openmls_traits::signatures::Signer;
 
 use super::{
-    errors::{AddMembersError, LeaveGroupError, RemoveMembersError},
+    errors::{AddMembersError, LeaveGroupError, ReissueWelcomeError, RemoveMembersError},
     *,
 };
 use crate::{
-    binary_tree::array_representation::LeafNodeIndex, messages::group_info::GroupInfo,
-    treesync::LeafNode,
+    binary_tree::array_representation::LeafNodeIndex,
+    ciphersuite::hash_ref::KeyPackageRef,
+    schedule::psk::PreSharedKeyId,
+    treesync::{KeyUniquenessViolation, LeafNode},
 };
 
 impl MlsGroup {
@@ -22,20 +24,37 @@ impl MlsGroup {
     /// This operation results in a Commit with a `path`, i.e. it includes an
     /// update of the committer's leaf [KeyPackage].
     ///
-    /// If successful, it returns a triple of [`MlsMessageOut`]s, where the first
-    /// contains the commit, the second one the [Welcome] and the third an optional [GroupInfo] that
-    /// will be [Some] if the group has the `use_ratchet_tree_extension` flag set.
+    /// If successful, it returns a [`CommitOutput`] with the commit, the
+    /// [Welcome] and an optional `GroupInfo` that will be [Some] if the group
+    /// has the `use_ratchet_tree_extension` flag set.
     ///
     /// Returns an error if there is a pending commit.
-    // FIXME: #1217
-    #[allow(clippy::type_complexity)]
     pub fn add_members<KeyStore: OpenMlsKeyStore>(
         &mut self,
         provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
         signer: &impl Signer,
         key_packages: &[KeyPackage],
-    ) -> Result<(MlsMessageOut, MlsMessageOut, Option<GroupInfo>), AddMembersError<KeyStore::Error>>
-    {
+    ) -> Result<CommitOutput<MlsMessageOut>, AddMembersError<KeyStore::Error>> {
+        let result = self.add_members_with_psks(provider, signer, key_packages, vec![]);
+        self.record_operation(
+            provider.crypto(),
+            OperationType::AddMembers,
+            &journal::serialized_parameters(key_packages),
+            &result,
+        );
+        result
+    }
+
+    /// Adds members to the group like [`MlsGroup::add_members()`] and commits
+    /// PreSharedKey proposals for the PSKs with the given IDs, which may be
+    /// resumption PSKs with usage `branch`.
+    pub(super) fn add_members_with_psks<KeyStore: OpenMlsKeyStore>(
+        &mut self,
+        provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
+        signer: &impl Signer,
+        key_packages: &[KeyPackage],
+        psk_ids: Vec<PreSharedKeyId>,
+    ) -> Result<CommitOutput<MlsMessageOut>, AddMembersError<KeyStore::Error>> {
         self.is_operational()?;
 
         if key_packages.is_empty() {
@@ -54,38 +73,46 @@ impl MlsGroup {
 
         // Create Commit over all proposals
         // TODO #751
+        let aad = self.commit_aad()?;
         let params = CreateCommitParams::builder()
-            .framing_parameters(self.framing_parameters())
+            .framing_parameters(self.commit_framing_parameters(&aad))
             .proposal_store(&self.proposal_store)
             .inline_proposals(inline_proposals)
+            .psk_ids(psk_ids)
             .build();
-        let create_commit_result = self.group.create_commit(params, provider, signer)?;
-
-        let welcome = match create_commit_result.welcome_option {
-            Some(welcome) => welcome,
-            None => {
-                return Err(LibraryError::custom("No secrets to generate commit message.").into())
-            }
-        };
-
-        // Convert PublicMessage messages to MLSMessage and encrypt them if required by
-        // the configuration
-        let mls_messages = self.content_to_mls_message(create_commit_result.commit, provider)?;
-
-        // Set the current group state to [`MlsGroupState::PendingCommit`],
-        // storing the current [`StagedCommit`] from the commit results
-        self.group_state = MlsGroupState::PendingCommit(Box::new(PendingCommitState::Member(
-            create_commit_result.staged_commit,
-        )));
+        let prepared_commit = self.prepare_commit(params, provider, signer)?;
+        if !prepared_commit.has_welcome() {
+            return Err(LibraryError::custom("No secrets to generate commit message.").into());
+        }
 
-        // Since the state of the group might be changed, arm the state flag
-        self.flag_state_change();
+        Ok(self
+            .stage_prepared_commit(provider, prepared_commit)?
+            .with_welcome()?)
+    }
 
-        Ok((
-            mls_messages,
-            MlsMessageOut::from_welcome(welcome, self.group.version()),
-            create_commit_result.group_info,
-        ))
+    /// Re-issues the Welcome message for the new member with the given
+    /// [`KeyPackageRef`], e.g., because the new member lost the original
+    /// Welcome message. This is only possible if the current epoch was created
+    /// by an own commit that added the new member, i.e., before the next
+    /// commit is merged.
+    ///
+    /// The re-issued Welcome message only contains the secrets of the new
+    /// member and is encrypted to the same key package as the original one.
+    /// A new member that lost the private key of its key package can't join
+    /// with it and has to be removed and added again: the leaf node in the
+    /// tree contains the encryption key of the original key package, so a
+    /// fresh key package needs a new commit in any case.
+    pub fn reissue_welcome(
+        &self,
+        key_package_ref: &KeyPackageRef,
+    ) -> Result<MlsMessageOut, ReissueWelcomeError> {
+        let welcome = self
+            .issued_welcome
+            .as_ref()
+            .ok_or(ReissueWelcomeError::NoWelcome)?
+            .for_new_member(key_package_ref)
+            .ok_or(ReissueWelcomeError::UnknownNewMember)?;
+        Ok(MlsMessageOut::from_welcome(welcome, self.group.version()))
     }
 
     /// Returns a reference to the own [`LeafNode`].
@@ -97,26 +124,35 @@ impl MlsGroup {
     ///
     /// Members are removed by providing the member's leaf index.
     ///
-    /// If successful, it returns a tuple of [`MlsMessageOut`] (containing the
-    /// commit), an optional [`MlsMessageOut`] (containing the [`Welcome`]) and the current
-    /// [GroupInfo].
+    /// If successful, it returns a [`CommitOutput`] with the commit, an
+    /// optional [`Welcome`] and the current `GroupInfo`.
     /// The [Welcome] is [Some] when the queue of pending proposals contained
     /// add proposals
-    /// The [GroupInfo] is [Some] if the group has the `use_ratchet_tree_extension` flag set.
-
+    /// The `GroupInfo` is [Some] if the group has the `use_ratchet_tree_extension` flag set.
     ///
     /// Returns an error if there is a pending commit.
-    // FIXME: #1217
-    #[allow(clippy::type_complexity)]
     pub fn remove_members<KeyStore: OpenMlsKeyStore>(
         &mut self,
         provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
         signer: &impl Signer,
         members: &[LeafNodeIndex],
-    ) -> Result<
-        (MlsMessageOut, Option<MlsMessageOut>, Option<GroupInfo>),
-        RemoveMembersError<KeyStore::Error>,
-    > {
+    ) -> Result<CommitOutput, RemoveMembersError<KeyStore::Error>> {
+        let result = self._remove_members(provider, signer, members);
+        self.record_operation(
+            provider.crypto(),
+            OperationType::RemoveMembers,
+            &journal::serialized_parameters(members),
+            &result,
+        );
+        result
+    }
+
+    fn _remove_members<KeyStore: OpenMlsKeyStore>(
+        &mut self,
+        provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
+        signer: &impl Signer,
+        members: &[LeafNodeIndex],
+    ) -> Result<CommitOutput, RemoveMembersError<KeyStore::Error>> {
         self.is_operational()?;
 
         if members.is_empty() {
@@ -133,33 +169,15 @@ impl MlsGroup {
 
         // Create Commit over all proposals
         // TODO #751
+        let aad = self.commit_aad()?;
         let params = CreateCommitParams::builder()
-            .framing_parameters(self.framing_parameters())
+            .framing_parameters(self.commit_framing_parameters(&aad))
             .proposal_store(&self.proposal_store)
             .inline_proposals(inline_proposals)
             .build();
-        let create_commit_result = self.group.create_commit(params, provider, signer)?;
-
-        // Convert PublicMessage messages to MLSMessage and encrypt them if required by
-        // the configuration
-        let mls_message = self.content_to_mls_message(create_commit_result.commit, provider)?;
-
-        // Set the current group state to [`MlsGroupState::PendingCommit`],
-        // storing the current [`StagedCommit`] from the commit results
-        self.group_state = MlsGroupState::PendingCommit(Box::new(PendingCommitState::Member(
-            create_commit_result.staged_commit,
-        )));
+        let prepared_commit = self.prepare_commit(params, provider, signer)?;
 
-        // Since the state of the group might be changed, arm the state flag
-        self.flag_state_change();
-
-        Ok((
-            mls_message,
-            create_commit_result
-                .welcome_option
-                .map(|w| MlsMessageOut::from_welcome(w, self.group.version())),
-            create_commit_result.group_info,
-        ))
+        Ok(self.stage_prepared_commit(provider, prepared_commit)?)
     }
 
     /// Leave the group.
@@ -205,6 +223,13 @@ impl MlsGroup {
             .leaf(leaf_index)
             .map(|leaf| leaf.credential())
     }
+
+    /// Returns the signature and encryption keys that are used by more than
+    /// one member of the group, together with the leaf indices of the
+    /// members. See [`PublicGroup::key_uniqueness_violations()`] for details.
+    pub fn key_uniqueness_violations(&self) -> Vec<KeyUniquenessViolation> {
+        self.group.public_group().key_uniqueness_violations()
+    }
 }
 
 /// Helper `enum` that classifies the kind of remove operation. This can be used to
@@ -0,0 +1,209 @@
+//! # Memory accounting
+//!
+//! Applications and servers that hold many groups in memory can inspect how
+//! much memory a group uses with [`MlsGroup::memory_usage()`] and
+//! [`PublicGroup::memory_usage()`](crate::group::PublicGroup::memory_usage()),
+//! and cap it with [`MemoryLimits`].
+//!
+//! The numbers are approximations based on the length of the serialized
+//! nodes and proposals and of the secrets. They don't include the overhead of
+//! the allocator and of the data structures.
+//!
+//! An [`MlsGroup`] enforces the [`MemoryLimits`] of its [`MlsGroupConfig`]:
+//!
+//! * Incoming proposals that would exceed the limit of the pending proposals
+//!   and incoming commits that would grow the tree beyond the limit of the
+//!   tree are rejected with a [`MemoryLimitError`].
+//! * When a commit is merged, the message secrets of the oldest past epochs
+//!   are dropped until the past epochs fit into their limit. Application
+//!   messages of these epochs can't be decrypted anymore.
+//!
+//! A [`PublicGroup`](crate::group::PublicGroup) doesn't have a configuration.
+//! Servers can check processed messages against their own limits with
+//! [`PublicGroup::check_memory_limits()`](crate::group::PublicGroup::check_memory_limits()).
+
+use serde::{Deserialize, Serialize};
+
+use super::*;
+use crate::group::{errors::MemoryLimitError, public_group::PublicGroup};
+
+/// The approximate memory usage of a group in bytes. See the
+/// [module documentation](self) for details.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MemoryUsage {
+    tree: usize,
+    epoch_secrets: usize,
+    past_epoch_secrets: usize,
+    pending_proposals: usize,
+}
+
+impl MemoryUsage {
+    /// Returns the number of bytes used by the nodes of the ratchet tree.
+    pub fn tree(&self) -> usize {
+        self.tree
+    }
+
+    /// Returns the number of bytes used by the message secrets of the current
+    /// epoch, including the secret tree.
+    pub fn epoch_secrets(&self) -> usize {
+        self.epoch_secrets
+    }
+
+    /// Returns the number of bytes used by the message secrets and members of
+    /// past epochs that are kept to decrypt late application messages.
+    pub fn past_epoch_secrets(&self) -> usize {
+        self.past_epoch_secrets
+    }
+
+    /// Returns the number of bytes used by the pending proposals.
+    pub fn pending_proposals(&self) -> usize {
+        self.pending_proposals
+    }
+
+    /// Returns the total number of bytes.
+    pub fn total(&self) -> usize {
+        self.tree + self.epoch_secrets + self.past_epoch_secrets + self.pending_proposals
+    }
+}
+
+/// Limits for the memory usage of a group in bytes. `None` means that the
+/// respective part of the group is not limited, which is the default. See the
+/// [module documentation](self) for details.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryLimits {
+    max_tree: Option<usize>,
+    max_past_epoch_secrets: Option<usize>,
+    max_pending_proposals: Option<usize>,
+}
+
+impl MemoryLimits {
+    /// Create new memory limits
+    pub fn new(
+        max_tree: Option<usize>,
+        max_past_epoch_secrets: Option<usize>,
+        max_pending_proposals: Option<usize>,
+    ) -> Self {
+        Self {
+            max_tree,
+            max_past_epoch_secrets,
+            max_pending_proposals,
+        }
+    }
+
+    /// Returns the maximum number of bytes of the ratchet tree.
+    pub fn max_tree(&self) -> Option<usize> {
+        self.max_tree
+    }
+
+    /// Returns the maximum number of bytes of the message secrets of past
+    /// epochs.
+    pub fn max_past_epoch_secrets(&self) -> Option<usize> {
+        self.max_past_epoch_secrets
+    }
+
+    /// Returns the maximum number of bytes of the pending proposals.
+    pub fn max_pending_proposals(&self) -> Option<usize> {
+        self.max_pending_proposals
+    }
+
+    /// Check that storing the proposal or merging the commit in `content`
+    /// doesn't exceed the limits, given the `public_group` and the memory
+    /// used by its `pending_proposals`.
+    pub(crate) fn check(
+        &self,
+        public_group: &PublicGroup,
+        pending_proposals: usize,
+        content: &ProcessedMessageContent,
+    ) -> Result<(), MemoryLimitError> {
+        match content {
+            ProcessedMessageContent::ProposalMessage(queued_proposal)
+            | ProcessedMessageContent::ExternalJoinProposalMessage(queued_proposal) => {
+                match self.max_pending_proposals {
+                    Some(max) if pending_proposals + queued_proposal.memory_usage() > max => {
+                        Err(MemoryLimitError::PendingProposalsLimitExceeded)
+                    }
+                    _ => Ok(()),
+                }
+            }
+            ProcessedMessageContent::StagedCommitMessage(staged_commit) => match self.max_tree {
+                Some(max)
+                    if public_group
+                        .treesync()
+                        .memory_usage_after(staged_commit.staged_diff().tree_diff())
+                        > max =>
+                {
+                    Err(MemoryLimitError::TreeLimitExceeded)
+                }
+                _ => Ok(()),
+            },
+            ProcessedMessageContent::ApplicationMessage(_) => Ok(()),
+        }
+    }
+}
+
+impl MlsGroup {
+    /// Returns the approximate [`MemoryUsage`] of the group.
+    pub fn memory_usage(&self) -> MemoryUsage {
+        MemoryUsage {
+            tree: self.group.public_group().treesync().memory_usage(),
+            epoch_secrets: self.group.epoch_secrets_memory_usage(),
+            past_epoch_secrets: self.group.past_epoch_secrets_memory_usage(),
+            pending_proposals: self.proposal_store.memory_usage(),
+        }
+    }
+
+    /// Check that the processed message `content` doesn't exceed the
+    /// [`MemoryLimits`] of the group.
+    pub(super) fn check_memory_limits(
+        &self,
+        content: &ProcessedMessageContent,
+    ) -> Result<(), MemoryLimitError> {
+        self.configuration().memory_limits().check(
+            self.group.public_group(),
+            self.proposal_store.memory_usage(),
+            content,
+        )
+    }
+
+    /// Drop the message secrets of the oldest past epochs until the past
+    /// epochs fit into the [`MemoryLimits`] of the group.
+    pub(super) fn enforce_past_epoch_secrets_limit(&mut self) {
+        if let Some(max) = self
+            .configuration()
+            .memory_limits()
+            .max_past_epoch_secrets()
+        {
+            let evicted = self.group.evict_past_epoch_secrets(max);
+            if evicted > 0 {
+                log::debug!("Dropped the message secrets of {evicted} past epochs.");
+            }
+        }
+    }
+}
+
+impl PublicGroup {
+    /// Returns the approximate [`MemoryUsage`] of the group, i.e., of the tree
+    /// and the pending proposals. A [`PublicGroup`] doesn't hold secrets.
+    pub fn memory_usage(&self) -> MemoryUsage {
+        MemoryUsage {
+            tree: self.treesync().memory_usage(),
+            pending_proposals: self.proposal_store().memory_usage(),
+            ..Default::default()
+        }
+    }
+
+    /// Check that storing the proposal or merging the commit of the processed
+    /// `message` doesn't exceed the given `limits`. The limit of the past
+    /// epoch secrets doesn't apply to a [`PublicGroup`].
+    pub fn check_memory_limits(
+        &self,
+        message: &ProcessedMessage,
+        limits: &MemoryLimits,
+    ) -> Result<(), MemoryLimitError> {
+        limits.check(
+            self,
+            self.proposal_store().memory_usage(),
+            message.content(),
+        )
+    }
+}
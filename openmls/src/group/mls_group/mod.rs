@@ -10,22 +10,110 @@ use crate::{
     error::LibraryError,
     framing::{mls_auth_content::AuthenticatedContent, *},
     group::*,
+    inspect::{
+        Divergence, GroupDump, GroupStateDump, Inspect, PublicStateDump, RatchetTypeDump,
+        SenderRatchetDump,
+    },
     key_packages::{KeyPackage, KeyPackageBundle},
     messages::{proposals::*, Welcome},
-    schedule::ResumptionPskSecret,
+    schedule::{EpochAuthenticator, ResumptionPskSecret},
+    tree::secret_tree::SecretType,
     treesync::{node::leaf_node::LeafNode, RatchetTree},
+    versions::ProtocolVersion,
 };
 use openmls_traits::{key_store::OpenMlsKeyStore, types::Ciphersuite, OpenMlsProvider};
 
 // Private
 mod application;
+#[cfg(feature = "async")]
+mod async_group;
+mod audit;
+mod builder;
+mod commit_metadata;
+mod commit_operation;
+mod commit_output;
+mod commit_policy;
+mod conflict;
+mod contact_pair;
 mod creation;
+mod decline;
+mod devices;
+mod downgrade;
+mod ephemeral;
+mod epoch_events;
 mod exporting;
+mod external_keys;
+mod group_id_policy;
+mod group_merge;
+mod history;
+mod invalidation;
+mod journal;
+mod maintenance;
+mod memory;
+mod moderation;
+mod ratchet_export;
+mod receipts;
+mod reporting;
+mod resend;
+mod resynchronization;
+mod retention;
+mod sequencing;
+mod shared;
+mod snapshot;
+#[cfg(feature = "speculative")]
+mod speculative;
+#[cfg(feature = "strict-api")]
+mod strict;
+mod subgroups;
 mod updates;
+mod verification;
+mod view;
 
 use config::*;
 use errors::*;
 
+// Public
+#[cfg(feature = "async")]
+pub use async_group::{
+    AsyncKeyStoreError, AsyncMlsGroup, AsyncOpenMlsKeyStore, AsyncOpenMlsProvider,
+};
+pub use audit::{AuditLog, AuditLogEntry, AuditMember};
+pub use builder::{JoinBuilder, MlsGroupBuilder, MlsGroupWithMembersBuilder};
+pub use commit_operation::{
+    CancellationToken, Checkpoint, CommitOperation, PreparedCommit, Progress,
+};
+pub use commit_output::{AffectedMembers, CommitOutput};
+pub use commit_policy::{CommitContentPolicy, CommitRule};
+pub use conflict::{CommitConflictResolution, RequeuedProposal};
+pub use contact_pair::{ContactPair, ContactPairEvent};
+pub use creation::{StagedWelcome, WelcomeExpectations};
+pub use decline::WelcomeDecline;
+pub use devices::{DeviceCommit, DeviceCommitResults, DeviceManager, UserIdentity};
+pub use downgrade::DowngradePolicy;
+pub use ephemeral::EphemeralKey;
+pub use epoch_events::{EpochChangeCause, EpochSubscription, EpochTransition};
+pub use exporting::{ExporterLabel, SubExporter};
+pub use external_keys::ExternalKeyRotationPolicy;
+pub use group_id_policy::GroupIdPolicy;
+pub use group_merge::GroupMerge;
+pub use history::{HistoryBundle, HistoryKey};
+pub use invalidation::StaleArtifacts;
+pub use journal::{JournalEntry, OperationType};
+pub use maintenance::{MaintenanceMessage, SelfUpdateMode, SelfUpdatePolicy};
+pub use memory::{MemoryLimits, MemoryUsage};
+pub use moderation::{ModeratorRemovalPolicy, RemovalEvent, Remover};
+pub use ratchet_export::{CompanionMessage, CompanionRatchetState, SealedRatchetState};
+pub use receipts::{MemberReceipt, Receipt, ReceiptTracker, ReceiptType};
+pub use reporting::AbuseReport;
+pub use resend::PendingCommitMessages;
+pub use retention::RetainedMessageKey;
+pub use sequencing::SequencedAad;
+pub use shared::{SharedMlsGroup, SharedMlsGroupReadGuard, SharedMlsGroupWriteGuard};
+#[cfg(feature = "speculative")]
+pub use speculative::{SpeculativeGroup, SpeculativeKeyStore, SpeculativeProvider};
+pub use verification::VerificationCode;
+pub use view::PublicGroupView;
+
 // Crate
 pub(crate) mod config;
 pub(crate) mod errors;
@@ -35,8 +123,86 @@ pub(crate) mod proposal;
 pub(crate) mod ser;
 
 // Tests
+#[cfg(all(test, feature = "async"))]
+mod test_async_group;
+#[cfg(test)]
+mod test_audit_log;
+#[cfg(test)]
+mod test_builder;
+#[cfg(test)]
+mod test_commit_limits;
+#[cfg(test)]
+mod test_commit_metadata;
+#[cfg(test)]
+mod test_commit_operation;
+#[cfg(test)]
+mod test_commit_policy;
+#[cfg(test)]
+mod test_conflict;
+#[cfg(test)]
+mod test_contact_pair;
+#[cfg(test)]
+mod test_devices;
+#[cfg(test)]
+mod test_downgrade;
+#[cfg(test)]
+mod test_ephemeral;
+#[cfg(test)]
+mod test_epoch_events;
+#[cfg(test)]
+mod test_external_keys;
+#[cfg(test)]
+mod test_group_id_policy;
+#[cfg(test)]
+mod test_group_merge;
+#[cfg(test)]
+mod test_history;
+#[cfg(test)]
+mod test_invalidation;
+#[cfg(test)]
+mod test_journal;
+#[cfg(test)]
+mod test_key_uniqueness;
+#[cfg(test)]
+mod test_maintenance;
+#[cfg(test)]
+mod test_memory;
 #[cfg(test)]
 mod test_mls_group;
+#[cfg(test)]
+mod test_ratchet_export;
+#[cfg(test)]
+mod test_receipts;
+#[cfg(test)]
+mod test_reissue_welcome;
+#[cfg(test)]
+mod test_reporting;
+#[cfg(test)]
+mod test_resynchronization;
+#[cfg(test)]
+mod test_retention;
+#[cfg(test)]
+mod test_sequencing;
+#[cfg(test)]
+mod test_shared_group;
+#[cfg(test)]
+mod test_snapshot;
+#[cfg(all(test, feature = "speculative"))]
+mod test_speculative;
+#[cfg(test)]
+mod test_staged_welcome;
+#[cfg(all(test, feature = "strict-api"))]
+mod test_strict;
+#[cfg(test)]
+mod test_subgroups;
+#[cfg(test)]
+mod test_validation_policy;
+#[cfg(test)]
+mod test_verification_code;
+#[cfg(test)]
+mod test_view;
+#[cfg(test)]
+mod test_welcome_decline;
 
 /// Pending Commit state. Differentiates between Commits issued by group members
 /// and External Commits.
@@ -170,6 +336,48 @@ pub struct MlsGroup {
     // is set to `InnerState::Changed` whenever an the internal group state is change and is set to
     // `InnerState::Persisted` once the state has been persisted.
     state_changed: InnerState,
+    // The audit log of the group if it is enabled in the configuration. See
+    // [`AuditLog`] for more information.
+    audit_log: Option<AuditLog>,
+    // The sequence numbers of the commits if sequencing tokens are enabled in
+    // the configuration. See [`SequencedAad`] for more information.
+    sequencing: sequencing::SequencingState,
+    // The published artifacts that are stale since the last merged commit.
+    // See [`StaleArtifacts`] for more information.
+    stale_artifacts: Option<StaleArtifacts>,
+    // The history keys of past epochs if history sharing is enabled in the
+    // configuration. See [`HistoryKey`] for more information.
+    history_keys: Vec<HistoryKey>,
+    // The metadata that is attached to the commits of the current epoch. See
+    // [`MlsGroup::set_commit_metadata()`] for more information.
+    commit_metadata: Option<Vec<u8>>,
+    // The time at which the current own leaf was first seen by
+    // [`MlsGroup::maintenance()`].
+    leaf_age: Option<maintenance::LeafAge>,
+    // The members that declined their invitation and weren't removed yet. See
+    // [`MlsGroup::process_welcome_decline()`] for more information.
+    declined_members: Vec<decline::DeclinedMember>,
+    // The Welcome message of the own commit that created the current epoch.
+    // See [`MlsGroup::reissue_welcome()`] for more information.
+    issued_welcome: Option<Welcome>,
+    // The messages of the pending commit as they were returned when it was
+    // created. See [`MlsGroup::resend_pending_commit()`] for more information.
+    pending_commit_messages: Option<PendingCommitMessages>,
+    // The epoch authenticators of past epochs, if they are kept according to
+    // the configuration, ordered by epoch.
+    past_epoch_authenticators: Vec<(GroupEpoch, EpochAuthenticator)>,
+    // A flag that indicates if a snapshot of the group was taken. See
+    // [`MlsGroup::snapshot()`] for more information.
+    snapshot_active: bool,
+    // The last operations on the group if the operation journal is enabled in
+    // the configuration. See [`JournalEntry`] for more information.
+    operation_journal: journal::OperationJournal,
+    // The secrets exported with cached labels in the current epoch. See
+    // [`ExporterLabel`] for more information.
+    exporter_cache: exporting::ExporterCache,
+    // The handlers subscribed to the epoch transitions of the group. They are
+    // not persisted. See [`EpochTransition`] for more information.
+    epoch_subscribers: epoch_events::EpochSubscribers,
 }
 
 impl MlsGroup {
@@ -183,6 +391,11 @@ impl MlsGroup {
     /// Sets the configuration.
     pub fn set_configuration(&mut self, mls_group_config: &MlsGroupConfig) {
         self.mls_group_config = mls_group_config.clone();
+        self.group
+            .set_external_key_rotation_policy(mls_group_config.external_key_rotation_policy);
+        self.group
+            .set_validation_policy(mls_group_config.validation_policy);
+        self.group.set_commit_limits(mls_group_config.commit_limits);
 
         // Since the state of the group might be changed, arm the state flag
         self.flag_state_change();
@@ -208,6 +421,11 @@ impl MlsGroup {
         self.group.ciphersuite()
     }
 
+    /// Returns the group's protocol version.
+    pub fn version(&self) -> ProtocolVersion {
+        self.group.version()
+    }
+
     /// Returns whether the own client is still a member of the group or if it
     /// was already evicted
     pub fn is_active(&self) -> bool {
@@ -285,7 +503,8 @@ impl MlsGroup {
         match self.group_state {
             MlsGroupState::PendingCommit(ref pending_commit_state) => {
                 if let PendingCommitState::Member(_) = **pending_commit_state {
-                    self.group_state = MlsGroupState::Operational
+                    self.group_state = MlsGroupState::Operational;
+                    self.pending_commit_messages = None;
                 }
             }
             MlsGroupState::Operational | MlsGroupState::Inactive => (),
@@ -322,6 +541,87 @@ impl MlsGroup {
     pub fn export_ratchet_tree(&self) -> RatchetTree {
         self.group.public_group().export_ratchet_tree()
     }
+
+    // === Debugging ===
+
+    /// Returns a description of the group that can be attached to bug reports.
+    ///
+    /// The [`GroupDump`] contains the epoch, the ratchet tree including the
+    /// credentials of all members, the group context extensions as well as the
+    /// pending proposals and commit. It does not contain any secrets.
+    pub fn debug_dump(&self) -> Result<GroupDump, LibraryError> {
+        self.dump()
+    }
+
+    /// Returns the generations and the number of kept keys of skipped
+    /// messages of the ratchets in which the messages of other members are
+    /// decrypted in the current epoch. Ratchets of members whose messages
+    /// were never decrypted are omitted. See [`SenderRatchetDump`].
+    pub fn sender_ratchets(&self) -> Vec<SenderRatchetDump> {
+        self.group
+            .message_secrets()
+            .secret_tree()
+            .decryption_ratchets()
+            .into_iter()
+            .map(
+                |(sender, secret_type, generation, skipped_keys)| SenderRatchetDump {
+                    leaf_index: sender.u32(),
+                    ratchet_type: match secret_type {
+                        SecretType::HandshakeSecret => RatchetTypeDump::Handshake,
+                        SecretType::ApplicationSecret => RatchetTypeDump::Application,
+                    },
+                    generation,
+                    highest_decrypted_generation: generation.checked_sub(1),
+                    skipped_keys: skipped_keys as u32,
+                },
+            )
+            .collect()
+    }
+
+    /// Exports the public state of the group for a comparison with other
+    /// implementations. See [`PublicStateDump`].
+    pub fn export_public_state(&self) -> Result<PublicStateDump, LibraryError> {
+        self.group.public_group().dump()
+    }
+
+    /// Compares the public state of the group with the state of another
+    /// implementation and returns the first field in which they diverge or
+    /// `None` if the states are equivalent.
+    pub fn compare_public_state(
+        &self,
+        theirs: &PublicStateDump,
+    ) -> Result<Option<Divergence>, LibraryError> {
+        Ok(self.export_public_state()?.first_divergence(theirs))
+    }
+}
+
+impl Inspect for MlsGroup {
+    type Dump = GroupDump;
+
+    fn dump(&self) -> Result<Self::Dump, LibraryError> {
+        let state = match self.group_state {
+            MlsGroupState::PendingCommit(_) => GroupStateDump::PendingCommit,
+            MlsGroupState::Operational => GroupStateDump::Operational,
+            MlsGroupState::Inactive => GroupStateDump::Inactive,
+        };
+        Ok(GroupDump {
+            state,
+            own_leaf_index: self.own_leaf_index().u32(),
+            group_context: self.group.context().dump()?,
+            ratchet_tree: self.export_ratchet_tree().dump()?,
+            pending_proposals: self
+                .pending_proposals()
+                .map(Inspect::dump)
+                .collect::<Result<_, _>>()?,
+            pending_commit: self.pending_commit().map(Inspect::dump).transpose()?,
+            sender_ratchets: self.sender_ratchets(),
+            operation_journal: self
+                .operation_journal
+                .iter()
+                .map(JournalEntry::dump)
+                .collect(),
+        })
+    }
 }
 
 // Private methods of MlsGroup
@@ -376,9 +676,18 @@ impl MlsGroup {
         )
     }
 
+    /// Returns the framing parameters for own commits with the given `aad`,
+    /// see [`MlsGroup::commit_aad()`].
+    pub(crate) fn commit_framing_parameters<'a>(&self, aad: &'a [u8]) -> FramingParameters<'a> {
+        FramingParameters::new(aad, self.mls_group_config.wire_format_policy().outgoing())
+    }
+
     /// Check if the group is operational. Throws an error if the group is
-    /// inactive or if there is a pending commit.
+    /// inactive, if there is a pending commit or if a snapshot was taken.
     fn is_operational(&self) -> Result<(), MlsGroupStateError> {
+        if self.snapshot_active {
+            return Err(MlsGroupStateError::SnapshotActive);
+        }
         match self.group_state {
             MlsGroupState::PendingCommit(_) => Err(MlsGroupStateError::PendingCommit),
             MlsGroupState::Inactive => Err(MlsGroupStateError::UseAfterEviction),
@@ -389,11 +698,6 @@ impl MlsGroup {
 
 // Methods used in tests
 impl MlsGroup {
-    #[cfg(any(feature = "test-utils", test))]
-    pub fn export_group_context(&self) -> &GroupContext {
-        self.group.context()
-    }
-
     #[cfg(any(feature = "test-utils", test))]
     pub fn tree_hash(&self) -> &[u8] {
         self.group.public_group().group_context().tree_hash()
@@ -405,7 +709,7 @@ impl MlsGroup {
     }
 
     /// Returns the underlying [CoreGroup].
-    #[cfg(test)]
+    #[cfg(any(feature = "test-utils", test))]
     pub(crate) fn group(&self) -> &CoreGroup {
         &self.group
     }
@@ -0,0 +1,188 @@
+//! # Moderation
+//!
+//! Groups can be moderated by parties that are not members of the group,
+//! e.g., the server of an enterprise deployment or the servers of the domains
+//! of a federated group. Moderators are configured as external senders of the
+//! group with
+//! [`MlsGroupConfigBuilder::external_senders()`](super::config::MlsGroupConfigBuilder::external_senders())
+//! and remove members with external Remove proposals:
+//!
+//! 1. The moderator tracks the group with a [`PublicGroup`] and creates the
+//!    signed proposal with [`ExternalProposal::new_remove_member()`].
+//! 2. External senders can't commit, so a member has to commit the proposal.
+//!    With [`ModeratorRemovalPolicy::AutoCommit`], members store moderator
+//!    removals when they process them, and
+//!    [`MlsGroup::commit_moderator_removals()`] commits them. To avoid
+//!    conflicting commits, only one member commits: the member with the
+//!    lowest leaf index that isn't removed.
+//! 3. Members that process or create the commit tell moderator removals
+//!    apart from removals by members with [`MlsGroup::removal_events()`].
+//!
+//! [`PublicGroup`]: crate::group::PublicGroup
+//! [`ExternalProposal::new_remove_member()`]: crate::messages::external_proposals::ExternalProposal::new_remove_member()
+
+use core_group::staged_commit::StagedCommit;
+use openmls_traits::signatures::Signer;
+use serde::{Deserialize, Serialize};
+
+use super::*;
+use crate::extensions::SenderExtensionIndex;
+
+/// Defines how a member handles Remove proposals of moderators, i.e., of the
+/// external senders of the group. See the [module documentation](self) for
+/// details.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ModeratorRemovalPolicy {
+    /// Moderator removals are handled by the application like other
+    /// proposals.
+    #[default]
+    Manual,
+    /// Moderator removals are stored when they are processed and committed
+    /// with [`MlsGroup::commit_moderator_removals()`].
+    AutoCommit,
+}
+
+/// The party that removed a member from the group.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Remover {
+    /// The member left the group.
+    Itself,
+    /// Another member (indicated by the leaf index) removed the member.
+    Member(LeafNodeIndex),
+    /// A moderator (indicated by the index of the external sender) removed
+    /// the member.
+    Moderator(SenderExtensionIndex),
+    /// The member rejoined the group with an external commit, which removed
+    /// its previous leaf.
+    Rejoined,
+}
+
+/// A member that is removed by a commit. See
+/// [`MlsGroup::removal_events()`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemovalEvent {
+    removed: LeafNodeIndex,
+    credential: Credential,
+    remover: Remover,
+}
+
+impl RemovalEvent {
+    /// Returns the leaf index of the removed member.
+    pub fn removed(&self) -> LeafNodeIndex {
+        self.removed
+    }
+
+    /// Returns the credential of the removed member.
+    pub fn credential(&self) -> &Credential {
+        &self.credential
+    }
+
+    /// Returns the party that removed the member.
+    pub fn remover(&self) -> &Remover {
+        &self.remover
+    }
+
+    /// Returns `true` if the member was removed by a moderator.
+    pub fn is_moderator_removal(&self) -> bool {
+        matches!(self.remover, Remover::Moderator(_))
+    }
+}
+
+impl MlsGroup {
+    /// Returns the pending Remove proposals of moderators, i.e., of the
+    /// external senders of the group.
+    pub fn pending_moderator_removals(&self) -> impl Iterator<Item = &QueuedProposal> {
+        self.proposal_store
+            .proposals()
+            .filter(|queued_proposal| is_moderator_removal(queued_proposal))
+    }
+
+    /// Commits the pending proposals if the group has the
+    /// [`ModeratorRemovalPolicy::AutoCommit`] policy and there are pending
+    /// moderator removals. See the [module documentation](self) for details.
+    ///
+    /// Only the member with the lowest leaf index that isn't removed commits.
+    /// All other members get [`None`], as do members with the
+    /// [`ModeratorRemovalPolicy::Manual`] policy. Applications can therefore
+    /// call this function after every processed message.
+    ///
+    /// Like [`MlsGroup::commit_to_pending_proposals()`], the commit covers all
+    /// pending proposals and has to be merged with
+    /// [`MlsGroup::merge_pending_commit()`] once it was accepted by the
+    /// delivery service.
+    pub fn commit_moderator_removals<KeyStore: OpenMlsKeyStore>(
+        &mut self,
+        provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
+        signer: &impl Signer,
+    ) -> Result<Option<CommitOutput>, CommitToPendingProposalsError<KeyStore::Error>> {
+        if self.mls_group_config.moderator_removal_policy != ModeratorRemovalPolicy::AutoCommit {
+            return Ok(None);
+        }
+        let removed: Vec<LeafNodeIndex> = self
+            .pending_moderator_removals()
+            .filter_map(|queued_proposal| match queued_proposal.proposal() {
+                Proposal::Remove(remove_proposal) => Some(remove_proposal.removed()),
+                _ => None,
+            })
+            .collect();
+        if removed.is_empty() {
+            return Ok(None);
+        }
+        let committer = self
+            .members()
+            .map(|member| member.index)
+            .filter(|index| !removed.contains(index))
+            .min();
+        if committer != Some(self.own_leaf_index()) {
+            return Ok(None);
+        }
+        self.commit_to_pending_proposals(provider, signer).map(Some)
+    }
+
+    /// Returns the members that are removed by `staged_commit` and who
+    /// removed them, so that moderator removals can be told apart from
+    /// removals by members.
+    ///
+    /// This has to be called before the commit is merged, since the
+    /// credentials of the removed members are taken from the current epoch.
+    pub fn removal_events(&self, staged_commit: &StagedCommit) -> Vec<RemovalEvent> {
+        staged_commit
+            .remove_proposals()
+            .filter_map(|queued_remove_proposal| {
+                let removed = queued_remove_proposal.remove_proposal().removed();
+                let remover = match queued_remove_proposal.sender() {
+                    Sender::Member(sender) if *sender == removed => Remover::Itself,
+                    Sender::Member(sender) => Remover::Member(*sender),
+                    Sender::External(sender_index) => Remover::Moderator(*sender_index),
+                    Sender::NewMemberProposal | Sender::NewMemberCommit => Remover::Rejoined,
+                };
+                let credential = self.group.public_group().leaf(removed)?.credential();
+                Some(RemovalEvent {
+                    removed,
+                    credential: credential.clone(),
+                    remover,
+                })
+            })
+            .collect()
+    }
+
+    /// Store the proposal of `processed_message` if it is a moderator removal
+    /// and the group has the [`ModeratorRemovalPolicy::AutoCommit`] policy.
+    pub(super) fn store_moderator_removal(&mut self, processed_message: &ProcessedMessage) {
+        if self.mls_group_config.moderator_removal_policy != ModeratorRemovalPolicy::AutoCommit {
+            return;
+        }
+        if let ProcessedMessageContent::ProposalMessage(queued_proposal) =
+            processed_message.content()
+        {
+            if is_moderator_removal(queued_proposal) {
+                self.store_pending_proposal(queued_proposal.as_ref().clone());
+            }
+        }
+    }
+}
+
+fn is_moderator_removal(queued_proposal: &QueuedProposal) -> bool {
+    matches!(queued_proposal.sender(), Sender::External(_))
+        && matches!(queued_proposal.proposal(), Proposal::Remove(_))
+}
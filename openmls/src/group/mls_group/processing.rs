@@ -1,12 +1,14 @@
 //! Processing functions of an [`MlsGroup`] for incoming messages.
 
+#![deny(clippy::unwrap_used, clippy::expect_used)]
+
 use std::mem;
 
 use core_group::staged_commit::StagedCommit;
 use openmls_traits::signatures::Signer;
 
 use crate::{
-    group::core_group::create_commit_params::CreateCommitParams, messages::group_info::GroupInfo,
+    group::core_group::create_commit_params::CreateCommitParams, metrics, security_events,
 };
 
 use crate::group::errors::MergeCommitError;
@@ -20,6 +22,12 @@ impl MlsGroup {
     /// and semantic validation of the message. It returns a [ProcessedMessage]
     /// enum.
     ///
+    /// Messages that were received in an
+    /// [`MlsMessageIn`](crate::framing::MlsMessageIn) with a protocol
+    /// version other than the one of the group are rejected, unless the check
+    /// is relaxed in the [`ValidationPolicy`](crate::group::config::ValidationPolicy)
+    /// of the configuration.
+    ///
     /// # Errors:
     /// Returns an [`ProcessMessageError`] when the validation checks fail
     /// with the exact reason of the failure.
@@ -48,23 +56,79 @@ impl MlsGroup {
             return Err(ProcessMessageError::IncompatibleWireFormat);
         }
 
+        // Check that the message was received in an envelope of the group's
+        // protocol version
+        if let Some(version) = message.envelope_version() {
+            if version != self.version() {
+                self.configuration()
+                    .validation_policy()
+                    .envelope_version()
+                    .check(ProcessMessageError::VersionMismatch)?;
+            }
+        }
+        #[cfg(feature = "strict-api")]
+        self.detect_own_message(provider.crypto(), &message)?;
+
         // Since the state of the group might be changed, arm the state flag
         self.flag_state_change();
 
         // Parse the message
         let sender_ratchet_configuration =
             self.configuration().sender_ratchet_configuration().clone();
-        self.group.process_message(
-            provider,
-            message,
-            &sender_ratchet_configuration,
-            &self.proposal_store,
-            &self.own_leaf_nodes,
-        )
+        let content_type = message.content_type();
+        // Only serialize the message if it is recorded.
+        let parameters = if self.mls_group_config.operation_journal > 0 {
+            journal::protocol_message_parameters(&message)
+        } else {
+            vec![]
+        };
+        let result = self
+            .group
+            .process_message(
+                provider,
+                message,
+                &sender_ratchet_configuration,
+                &self.proposal_store,
+                &self.own_leaf_nodes,
+            )
+            .and_then(|processed_message| {
+                self.check_memory_limits(processed_message.content())?;
+                self.check_downgrade(processed_message.content())?;
+                self.check_commit_policy(&processed_message)?;
+                self.check_sequence_number(&processed_message)?;
+                self.store_moderator_removal(&processed_message);
+                Ok(processed_message)
+            })
+            .inspect(|processed_message| {
+                security_events::record_processed_message(
+                    self.group.public_group(),
+                    processed_message,
+                )
+            })
+            .inspect_err(|e| {
+                metrics::record_process_message_error(content_type, e);
+                security_events::record_process_message_error(self.group_id(), self.epoch(), e);
+            });
+        self.record_operation(
+            provider.crypto(),
+            OperationType::ProcessMessage,
+            &parameters,
+            &result,
+        );
+        result
     }
 
-    /// Stores a standalone proposal in the internal [ProposalStore]
+    /// Stores a standalone proposal in the internal [ProposalStore]. Proposals
+    /// that are already stored, e.g., moderator removals that were stored
+    /// when they were processed (see [`ModeratorRemovalPolicy`]), are not
+    /// stored again.
     pub fn store_pending_proposal(&mut self, proposal: QueuedProposal) {
+        if self.proposal_store.proposals().any(|queued_proposal| {
+            queued_proposal.proposal_reference() == proposal.proposal_reference()
+        }) {
+            return;
+        }
+
         // Store the proposal in in the internal ProposalStore
         self.proposal_store.add(proposal);
 
@@ -77,48 +141,46 @@ impl MlsGroup {
     /// created even if there are no valid pending proposals.
     ///
     /// Returns an error if there is a pending commit. Otherwise it returns a
-    /// tuple of `Commit, Option<Welcome>, Option<GroupInfo>`, where `Commit`
-    /// and `Welcome` are MlsMessages of the type [`MlsMessageOut`].
-    // FIXME: #1217
-    #[allow(clippy::type_complexity)]
+    /// [`CommitOutput`] with the commit, a Welcome message if the pending
+    /// proposals add members and the `GroupInfo` if the group has the
+    /// `use_ratchet_tree_extension` flag set.
     pub fn commit_to_pending_proposals<KeyStore: OpenMlsKeyStore>(
         &mut self,
         provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
         signer: &impl Signer,
-    ) -> Result<
-        (MlsMessageOut, Option<MlsMessageOut>, Option<GroupInfo>),
-        CommitToPendingProposalsError<KeyStore::Error>,
-    > {
+    ) -> Result<CommitOutput, CommitToPendingProposalsError<KeyStore::Error>> {
+        let proposal_refs: Vec<ProposalRef> = self
+            .proposal_store
+            .proposals()
+            .map(|proposal| proposal.proposal_reference())
+            .collect();
+        let result = self._commit_to_pending_proposals(provider, signer);
+        self.record_operation(
+            provider.crypto(),
+            OperationType::CommitToPendingProposals,
+            &journal::serialized_parameters(&proposal_refs),
+            &result,
+        );
+        result
+    }
+
+    fn _commit_to_pending_proposals<KeyStore: OpenMlsKeyStore>(
+        &mut self,
+        provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
+        signer: &impl Signer,
+    ) -> Result<CommitOutput, CommitToPendingProposalsError<KeyStore::Error>> {
         self.is_operational()?;
 
         // Create Commit over all pending proposals
         // TODO #751
+        let aad = self.commit_aad()?;
         let params = CreateCommitParams::builder()
-            .framing_parameters(self.framing_parameters())
+            .framing_parameters(self.commit_framing_parameters(&aad))
             .proposal_store(&self.proposal_store)
             .build();
-        let create_commit_result = self.group.create_commit(params, provider, signer)?;
-
-        // Convert PublicMessage messages to MLSMessage and encrypt them if required by
-        // the configuration
-        let mls_message = self.content_to_mls_message(create_commit_result.commit, provider)?;
-
-        // Set the current group state to [`MlsGroupState::PendingCommit`],
-        // storing the current [`StagedCommit`] from the commit results
-        self.group_state = MlsGroupState::PendingCommit(Box::new(PendingCommitState::Member(
-            create_commit_result.staged_commit,
-        )));
-
-        // Since the state of the group might be changed, arm the state flag
-        self.flag_state_change();
+        let prepared_commit = self.prepare_commit(params, provider, signer)?;
 
-        Ok((
-            mls_message,
-            create_commit_result
-                .welcome_option
-                .map(|w| MlsMessageOut::from_welcome(w, self.group.version())),
-            create_commit_result.group_info,
-        ))
+        Ok(self.stage_prepared_commit(provider, prepared_commit)?)
     }
 
     /// Merge a [StagedCommit] into the group after inspection. As this advances
@@ -128,6 +190,19 @@ impl MlsGroup {
         provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
         staged_commit: StagedCommit,
     ) -> Result<(), MergeCommitError<KeyStore::Error>> {
+        let result = self._merge_staged_commit(provider, staged_commit);
+        self.record_operation(provider.crypto(), OperationType::MergeCommit, &[], &result);
+        result
+    }
+
+    fn _merge_staged_commit<KeyStore: OpenMlsKeyStore>(
+        &mut self,
+        provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
+        staged_commit: StagedCommit,
+    ) -> Result<(), MergeCommitError<KeyStore::Error>> {
+        #[cfg(feature = "strict-api")]
+        self.detect_stale_staged_commit(&staged_commit)?;
+
         // Check if we were removed from the group
         if staged_commit.self_removed() {
             self.group_state = MlsGroupState::Inactive;
@@ -136,10 +211,34 @@ impl MlsGroup {
         // Since the state of the group might be changed, arm the state flag
         self.flag_state_change();
 
+        let pending_audit_entry = self.pending_audit_entry(&staged_commit);
+        let stale_artifacts = self.pending_stale_artifacts(&staged_commit);
+        let welcome = staged_commit.welcome().cloned();
+        let epoch_authenticator = (self.epoch(), self.epoch_authenticator().clone());
+        let old_epoch = self.epoch();
+        let own_commit = staged_commit.committer() == self.own_leaf_index();
+        let self_removed = staged_commit.self_removed();
+
         // Merge staged commit
         self.group
             .merge_staged_commit(provider, staged_commit, &mut self.proposal_store)?;
 
+        let membership_delta = pending_audit_entry
+            .map(|pending| self.membership_delta(pending))
+            .transpose()?;
+        if let Some(delta) = &membership_delta {
+            self.record_audit_entry(provider.crypto(), delta)?;
+        }
+
+        self.stale_artifacts = Some(stale_artifacts);
+        self.store_history_key(provider.crypto())?;
+        self.commit_metadata = None;
+        self.prune_declined_members();
+        self.issued_welcome = welcome;
+        self.store_epoch_authenticator(epoch_authenticator);
+
+        self.enforce_past_epoch_secrets_limit();
+
         // Extract and store the resumption psk for the current epoch
         let resumption_psk = self.group.group_epoch_secrets().resumption_psk();
         self.group
@@ -149,9 +248,16 @@ impl MlsGroup {
         // Delete own KeyPackageBundles
         self.own_leaf_nodes.clear();
 
+        // Delete the exported secrets of the previous epoch
+        self.exporter_cache.clear();
+
         // Delete a potential pending commit
         self.clear_pending_commit();
 
+        if let Some(delta) = membership_delta {
+            self.notify_epoch_transition(old_epoch, own_commit, self_removed, delta);
+        }
+
         Ok(())
     }
 
@@ -164,7 +270,9 @@ impl MlsGroup {
         match &self.group_state {
             MlsGroupState::PendingCommit(_) => {
                 let old_state = mem::replace(&mut self.group_state, MlsGroupState::Operational);
+                self.pending_commit_messages = None;
                 if let MlsGroupState::PendingCommit(pending_commit_state) = old_state {
+                    self.merge_own_sequence_number();
                     self.merge_staged_commit(provider, (*pending_commit_state).into())?;
                 }
                 Ok(())
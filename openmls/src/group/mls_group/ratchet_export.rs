@@ -0,0 +1,495 @@
+//! # Companion processes
+//!
+//! Some applications decrypt messages in a separate process that can't access
+//! the state of the group, e.g., a push-notification extension that shows the
+//! content of a message before the application is started. Handing the group
+//! state to such a process would desynchronize the sender ratchets of the
+//! main process, since every decryption advances the ratchets.
+//!
+//! Instead, the main process exports the key material that the companion
+//! process needs with [`MlsGroup::export_ratchet_state()`]. The export
+//! contains the keys of the next `generations` application messages of the
+//! given senders and is sealed with a key that the application shares with the
+//! companion process. The companion process opens the [`SealedRatchetState`]
+//! with [`SealedRatchetState::open()`] and decrypts application messages with
+//! [`CompanionRatchetState::decrypt_message()`]. It never writes back: when the
+//! main process later processes the same messages, it decrypts them as usual.
+//!
+//! The security boundaries are as follows:
+//!
+//! * The export only contains key material for application messages of the
+//!   epoch in which it was exported. It doesn't contain the ratchet secrets, so
+//!   the companion process can't derive keys of later generations, of other
+//!   senders or of handshake messages.
+//! * The companion process verifies the signature of every message, but it
+//!   doesn't detect replays. Applications should treat its output as a preview
+//!   only.
+//! * Keys of messages that were skipped by the main process aren't exported.
+//!   The exported keys are only deleted when the companion process discards the
+//!   [`CompanionRatchetState`], so it should be discarded as soon as possible.
+
+use std::collections::BTreeMap;
+
+use openmls_traits::{crypto::OpenMlsCrypto, types::CryptoError};
+use tls_codec::{
+    Deserialize as TlsDeserializeTrait, SecretVLBytes, Serialize as TlsSerializeTrait,
+    TlsDeserialize, TlsSerialize, TlsSize, VLBytes,
+};
+
+use super::{errors::RatchetExportError, *};
+use crate::{
+    ciphersuite::{
+        signable::Verifiable, AeadKey, AeadNonce, OpenMlsSignaturePublicKey, Secret,
+        SignaturePublicKey,
+    },
+    framing::{mls_auth_content_in::AuthenticatedContentIn, mls_content_in::FramedContentBodyIn},
+    schedule::SenderDataSecret,
+    tree::sender_ratchet::RatchetKeyMaterial,
+};
+
+/// The exported ratchet state of a group, sealed for a companion process. See
+/// the [module documentation](self) for details.
+#[derive(
+    Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TlsSerialize, TlsDeserialize, TlsSize,
+)]
+pub struct SealedRatchetState {
+    group_id: GroupId,
+    epoch: GroupEpoch,
+    ciphersuite: Ciphersuite,
+    version: ProtocolVersion,
+    nonce: VLBytes,
+    ciphertext: VLBytes,
+}
+
+/// The content of a [`SealedRatchetState`].
+///
+/// Note: This has a hand-written `Debug` implementation.
+#[derive(TlsSerialize, TlsDeserialize, TlsSize)]
+pub(super) struct RatchetStateContent {
+    pub(super) serialized_context: VLBytes,
+    pub(super) sender_data_secret: SecretVLBytes,
+    pub(super) senders: Vec<ExportedSender>,
+}
+
+impl std::fmt::Debug for RatchetStateContent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut ds = f.debug_struct("RatchetStateContent");
+        ds.field("serialized_context", &self.serialized_context);
+
+        #[cfg(feature = "crypto-debug")]
+        ds.field("sender_data_secret", &self.sender_data_secret);
+        #[cfg(not(feature = "crypto-debug"))]
+        ds.field("sender_data_secret", &"***");
+
+        ds.field("senders", &self.senders).finish()
+    }
+}
+
+#[derive(Debug, TlsSerialize, TlsDeserialize, TlsSize)]
+pub(super) struct ExportedSender {
+    pub(super) leaf_index: LeafNodeIndex,
+    pub(super) credential: Credential,
+    pub(super) signature_key: SignaturePublicKey,
+    pub(super) keys: Vec<ExportedKey>,
+}
+
+/// Note: This has a hand-written `Debug` implementation.
+#[derive(TlsSerialize, TlsDeserialize, TlsSize)]
+pub(super) struct ExportedKey {
+    pub(super) generation: u32,
+    pub(super) key: SecretVLBytes,
+    pub(super) nonce: SecretVLBytes,
+}
+
+impl std::fmt::Debug for ExportedKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut ds = f.debug_struct("ExportedKey");
+        ds.field("generation", &self.generation);
+
+        #[cfg(feature = "crypto-debug")]
+        ds.field("key", &self.key).field("nonce", &self.nonce);
+        #[cfg(not(feature = "crypto-debug"))]
+        ds.field("key", &"***").field("nonce", &"***");
+
+        ds.finish()
+    }
+}
+
+impl SealedRatchetState {
+    /// Returns the ID of the group of the exported state.
+    pub fn group_id(&self) -> &GroupId {
+        &self.group_id
+    }
+
+    /// Returns the epoch in which the state was exported. Only messages of
+    /// this epoch can be decrypted with it.
+    pub fn epoch(&self) -> GroupEpoch {
+        self.epoch
+    }
+
+    /// Opens the sealed state with the `sealing_key` that was used to export
+    /// it.
+    pub fn open(
+        &self,
+        crypto: &impl OpenMlsCrypto,
+        sealing_key: &[u8],
+    ) -> Result<CompanionRatchetState, RatchetExportError> {
+        let plaintext = crypto
+            .aead_decrypt(
+                self.ciphersuite.aead_algorithm(),
+                sealing_key,
+                self.ciphertext.as_slice(),
+                self.nonce.as_slice(),
+                &sealed_state_aad(&self.group_id, self.epoch, self.ciphersuite, self.version)?,
+            )
+            .map_err(|_| RatchetExportError::DecryptionFailed)?;
+        let content = RatchetStateContent::tls_deserialize_exact(plaintext)
+            .map_err(|_| RatchetExportError::MalformedState)?;
+
+        let ciphersuite = self.ciphersuite;
+        let mut senders = BTreeMap::new();
+        for sender in content.senders {
+            let mut keys = BTreeMap::new();
+            for key in sender.keys {
+                if key.key.as_slice().len() != ciphersuite.aead_key_length()
+                    || key.nonce.as_slice().len() != ciphersuite.aead_nonce_length()
+                {
+                    return Err(RatchetExportError::MalformedState);
+                }
+                let secret = |bytes: &SecretVLBytes| {
+                    Secret::from_slice(bytes.as_slice(), self.version, ciphersuite)
+                };
+                keys.insert(
+                    key.generation,
+                    (
+                        AeadKey::from_secret(secret(&key.key)),
+                        AeadNonce::from_secret(secret(&key.nonce)),
+                    ),
+                );
+            }
+            senders.insert(
+                sender.leaf_index.u32(),
+                CompanionSender {
+                    credential: sender.credential,
+                    signature_key: OpenMlsSignaturePublicKey::from_signature_key(
+                        sender.signature_key,
+                        ciphersuite.signature_algorithm(),
+                    ),
+                    keys,
+                },
+            );
+        }
+
+        Ok(CompanionRatchetState {
+            group_id: self.group_id.clone(),
+            epoch: self.epoch,
+            ciphersuite,
+            serialized_context: content.serialized_context.into(),
+            sender_data_secret: SenderDataSecret::from_slice(
+                content.sender_data_secret.as_slice(),
+                self.version,
+                ciphersuite,
+            ),
+            senders,
+        })
+    }
+}
+
+/// The opened ratchet state of a group that a companion process uses to
+/// decrypt application messages. See the [module documentation](self) for
+/// details.
+///
+/// Note: This has a hand-written `Debug` implementation.
+pub struct CompanionRatchetState {
+    group_id: GroupId,
+    epoch: GroupEpoch,
+    ciphersuite: Ciphersuite,
+    serialized_context: Vec<u8>,
+    sender_data_secret: SenderDataSecret,
+    senders: BTreeMap<u32, CompanionSender>,
+}
+
+struct CompanionSender {
+    credential: Credential,
+    signature_key: OpenMlsSignaturePublicKey,
+    keys: BTreeMap<u32, RatchetKeyMaterial>,
+}
+
+impl std::fmt::Debug for CompanionRatchetState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CompanionRatchetState")
+            .field("group_id", &self.group_id)
+            .field("epoch", &self.epoch)
+            .field("ciphersuite", &self.ciphersuite)
+            .field("senders", &self.senders())
+            .finish()
+    }
+}
+
+impl CompanionRatchetState {
+    /// Returns the ID of the group of the state.
+    pub fn group_id(&self) -> &GroupId {
+        &self.group_id
+    }
+
+    /// Returns the epoch of the state.
+    pub fn epoch(&self) -> GroupEpoch {
+        self.epoch
+    }
+
+    /// Returns the leaf indices of the senders whose messages can be
+    /// decrypted.
+    pub fn senders(&self) -> Vec<LeafNodeIndex> {
+        self.senders
+            .keys()
+            .copied()
+            .map(LeafNodeIndex::new)
+            .collect()
+    }
+
+    /// Returns the generations of the messages of the given `sender` that can
+    /// be decrypted.
+    pub fn generations(&self, sender: LeafNodeIndex) -> Vec<u32> {
+        self.senders
+            .get(&sender.u32())
+            .map(|sender| sender.keys.keys().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// Decrypts an application message of the epoch of the state and verifies
+    /// its signature, without changing the state.
+    ///
+    /// Returns an error if the message isn't an encrypted application message
+    /// of the group and epoch, or if the state doesn't contain the key of the
+    /// sender and generation of the message.
+    pub fn decrypt_message(
+        &self,
+        crypto: &impl OpenMlsCrypto,
+        message: impl Into<ProtocolMessage>,
+    ) -> Result<CompanionMessage, RatchetExportError> {
+        let message = match message.into() {
+            ProtocolMessage::PrivateMessage(message) => message,
+            ProtocolMessage::PublicMessage(_) => {
+                return Err(RatchetExportError::NotApplicationMessage)
+            }
+        };
+        if message.group_id() != &self.group_id {
+            return Err(RatchetExportError::WrongGroup);
+        }
+        if message.epoch() != self.epoch {
+            return Err(RatchetExportError::WrongEpoch);
+        }
+        if message.content_type() != ContentType::Application {
+            return Err(RatchetExportError::NotApplicationMessage);
+        }
+
+        let sender_data =
+            message.decrypt_sender_data(&self.sender_data_secret, crypto, self.ciphersuite)?;
+        let sender_index = sender_data.leaf_index;
+        let generation = sender_data.generation;
+        let sender = self
+            .senders
+            .get(&sender_index.u32())
+            .ok_or(RatchetExportError::UnknownSender)?;
+        let (key, nonce) = sender
+            .keys
+            .get(&generation)
+            .ok_or(RatchetExportError::UnavailableGeneration)?;
+        let verifiable_content = message.decrypt_with_key(
+            crypto,
+            key,
+            nonce.clone(),
+            sender_data,
+            &self.serialized_context,
+        )?;
+        let content: AuthenticatedContentIn = verifiable_content
+            .verify(crypto, &sender.signature_key)
+            .map_err(|_| RatchetExportError::InvalidSignature)?;
+        let FramedContentBodyIn::Application(application_data) = content.content() else {
+            return Err(RatchetExportError::NotApplicationMessage);
+        };
+
+        Ok(CompanionMessage {
+            sender: sender_index,
+            credential: sender.credential.clone(),
+            generation,
+            authenticated_data: message.authenticated_data().to_vec(),
+            application_data: application_data.as_slice().to_vec(),
+        })
+    }
+}
+
+/// An application message decrypted with a [`CompanionRatchetState`].
+///
+/// Note: This has a hand-written `Debug` implementation.
+#[derive(Clone, PartialEq, Eq)]
+pub struct CompanionMessage {
+    sender: LeafNodeIndex,
+    credential: Credential,
+    generation: u32,
+    authenticated_data: Vec<u8>,
+    application_data: Vec<u8>,
+}
+
+impl std::fmt::Debug for CompanionMessage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut ds = f.debug_struct("CompanionMessage");
+        ds.field("sender", &self.sender)
+            .field("credential", &self.credential)
+            .field("generation", &self.generation);
+
+        #[cfg(feature = "content-debug")]
+        ds.field("authenticated_data", &self.authenticated_data)
+            .field("application_data", &self.application_data);
+        #[cfg(not(feature = "content-debug"))]
+        ds.field("authenticated_data", &"***")
+            .field("application_data", &"***");
+
+        ds.finish()
+    }
+}
+
+impl CompanionMessage {
+    /// Returns the leaf index of the sender.
+    pub fn sender(&self) -> LeafNodeIndex {
+        self.sender
+    }
+
+    /// Returns the credential of the sender.
+    pub fn credential(&self) -> &Credential {
+        &self.credential
+    }
+
+    /// Returns the generation of the message in the sender's ratchet.
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    /// Returns the authenticated data of the message.
+    pub fn authenticated_data(&self) -> &[u8] {
+        &self.authenticated_data
+    }
+
+    /// Returns the application data of the message.
+    pub fn application_data(&self) -> &[u8] {
+        &self.application_data
+    }
+
+    /// Consumes the message and returns the application data.
+    pub fn into_application_data(self) -> Vec<u8> {
+        self.application_data
+    }
+}
+
+impl MlsGroup {
+    /// Export the key material of the next `generations` application messages
+    /// of the given `senders` in a [`SealedRatchetState`] for a companion
+    /// process. The state is sealed with the `sealing_key`, which must have
+    /// the AEAD key length of the ciphersuite of the group. See the
+    /// [module documentation](self) for details.
+    ///
+    /// The state of the group isn't changed. Returns an error if the group is
+    /// inactive or if a sender isn't another member of the group.
+    pub fn export_ratchet_state(
+        &self,
+        provider: &impl OpenMlsProvider,
+        sealing_key: &[u8],
+        senders: &[LeafNodeIndex],
+        generations: u32,
+    ) -> Result<SealedRatchetState, RatchetExportError> {
+        if !self.is_active() {
+            return Err(MlsGroupStateError::UseAfterEviction.into());
+        }
+        let ciphersuite = self.ciphersuite();
+        if sealing_key.len() != ciphersuite.aead_key_length() {
+            return Err(RatchetExportError::InvalidSealingKey);
+        }
+
+        let message_secrets = self.group.message_secrets();
+        let mut exported_senders = Vec::with_capacity(senders.len());
+        for &sender in senders {
+            if sender == self.own_leaf_index() {
+                return Err(RatchetExportError::UnknownSender);
+            }
+            let leaf = self
+                .group
+                .public_group()
+                .leaf(sender)
+                .ok_or(RatchetExportError::UnknownSender)?;
+            let keys = message_secrets
+                .secret_tree()
+                .upcoming_application_keys(ciphersuite, provider.crypto(), sender, generations)?
+                .into_iter()
+                .map(|(generation, (key, nonce))| ExportedKey {
+                    generation,
+                    key: key.as_slice().into(),
+                    nonce: nonce.as_slice().into(),
+                })
+                .collect();
+            exported_senders.push(ExportedSender {
+                leaf_index: sender,
+                credential: leaf.credential().clone(),
+                signature_key: leaf.signature_key().clone(),
+                keys,
+            });
+        }
+        let content = RatchetStateContent {
+            serialized_context: message_secrets.serialized_context().into(),
+            sender_data_secret: message_secrets.sender_data_secret().as_slice().into(),
+            senders: exported_senders,
+        };
+
+        let version = self.group.version();
+        let nonce = provider
+            .rand()
+            .random_vec(ciphersuite.aead_nonce_length())
+            .map_err(|_| {
+                LibraryError::unexpected_crypto_error(CryptoError::InsufficientRandomness)
+            })?;
+        let plaintext = content
+            .tls_serialize_detached()
+            .map_err(LibraryError::missing_bound_check)?;
+        let ciphertext = provider
+            .crypto()
+            .aead_encrypt(
+                ciphersuite.aead_algorithm(),
+                sealing_key,
+                &plaintext,
+                &nonce,
+                &sealed_state_aad(self.group_id(), self.epoch(), ciphersuite, version)?,
+            )
+            .map_err(LibraryError::unexpected_crypto_error)?;
+
+        Ok(SealedRatchetState {
+            group_id: self.group_id().clone(),
+            epoch: self.epoch(),
+            ciphersuite,
+            version,
+            nonce: nonce.into(),
+            ciphertext: ciphertext.into(),
+        })
+    }
+}
+
+/// Returns the authenticated data of a [`SealedRatchetState`], which binds the
+/// state to the group, epoch and ciphersuite.
+fn sealed_state_aad(
+    group_id: &GroupId,
+    epoch: GroupEpoch,
+    ciphersuite: Ciphersuite,
+    version: ProtocolVersion,
+) -> Result<Vec<u8>, LibraryError> {
+    let mut aad = group_id
+        .tls_serialize_detached()
+        .map_err(LibraryError::missing_bound_check)?;
+    epoch
+        .tls_serialize(&mut aad)
+        .map_err(LibraryError::missing_bound_check)?;
+    ciphersuite
+        .tls_serialize(&mut aad)
+        .map_err(LibraryError::missing_bound_check)?;
+    version
+        .tls_serialize(&mut aad)
+        .map_err(LibraryError::missing_bound_check)?;
+    Ok(aad)
+}
@@ -0,0 +1,281 @@
+//! # Delivery and read receipts
+//!
+//! Many applications show whether a message was delivered to or read by the
+//! other members. OpenMLS offers an optional format for such receipts, so
+//! that all clients of an application use the same semantics:
+//!
+//! * Messages are identified by their [`MessageRef`], the hash of the
+//!   serialized MLS message, which the sender and all receivers compute with
+//!   [`MlsGroup::message_ref()`].
+//! * A [`Receipt`] confirms the delivery or the reading of a batch of
+//!   messages. It is sent as an application message with
+//!   [`MlsGroup::create_receipt()`], so it is encrypted and authenticated
+//!   like any other application message. The application data of a receipt
+//!   starts with a dedicated content type, so receipts can be told apart
+//!   from other application messages with [`Receipt::decode()`].
+//! * A [`ReceiptTracker`] aggregates the receipts for the messages the member
+//!   sent. Receipts only ever upgrade the state of a member for a message: a
+//!   read message is also delivered, and a delivery receipt that arrives
+//!   after a read receipt doesn't change anything.
+//!
+//! Sending receipts is up to the application, e.g., it can batch the
+//! delivery receipts for all messages it received while it was offline into
+//! a single receipt.
+
+use openmls_traits::{crypto::OpenMlsCrypto, signatures::Signer};
+use tls_codec::{
+    Deserialize as TlsDeserializeTrait, Serialize as TlsSerializeTrait, TlsDeserialize,
+    TlsSerialize, TlsSize, VLBytes,
+};
+
+use super::{errors::CreateMessageError, *};
+use crate::ciphersuite::hash_ref::{make_message_ref, MessageRef};
+
+/// The content type at the beginning of the application data of a receipt.
+const RECEIPT_CONTENT_TYPE: &[u8] = b"application/mls-receipt";
+
+/// The kind of a [`Receipt`].
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Serialize,
+    Deserialize,
+    TlsSerialize,
+    TlsDeserialize,
+    TlsSize,
+)]
+#[repr(u8)]
+pub enum ReceiptType {
+    /// The messages were delivered.
+    Delivered = 1,
+    /// The messages were read.
+    Read = 2,
+}
+
+/// A receipt for a batch of messages. See the [module documentation](self)
+/// for details.
+///
+/// ```c
+/// struct {
+///     ReceiptType receipt_type;
+///     MessageRef message_refs<V>;
+/// } Receipt;
+/// ```
+#[derive(
+    Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TlsSerialize, TlsDeserialize, TlsSize,
+)]
+pub struct Receipt {
+    receipt_type: ReceiptType,
+    message_refs: Vec<MessageRef>,
+}
+
+/// The application data of a receipt.
+#[derive(TlsSerialize, TlsDeserialize, TlsSize)]
+struct ReceiptContent {
+    content_type: VLBytes,
+    receipt: Receipt,
+}
+
+impl Receipt {
+    /// Creates a new [`Receipt`].
+    pub fn new(receipt_type: ReceiptType, message_refs: Vec<MessageRef>) -> Self {
+        Self {
+            receipt_type,
+            message_refs,
+        }
+    }
+
+    /// Returns the type of the receipt.
+    pub fn receipt_type(&self) -> ReceiptType {
+        self.receipt_type
+    }
+
+    /// Returns the references of the messages the receipt is for.
+    pub fn message_refs(&self) -> &[MessageRef] {
+        &self.message_refs
+    }
+
+    /// Decodes a [`Receipt`] from the application data of an application
+    /// message. Returns `None` if the application data isn't a receipt.
+    pub fn decode(application_data: &[u8]) -> Option<Self> {
+        let content = ReceiptContent::tls_deserialize_exact(application_data).ok()?;
+        (content.content_type.as_slice() == RECEIPT_CONTENT_TYPE).then_some(content.receipt)
+    }
+
+    /// Encodes the receipt as application data.
+    fn encode(&self) -> Result<Vec<u8>, LibraryError> {
+        ReceiptContent {
+            content_type: RECEIPT_CONTENT_TYPE.into(),
+            receipt: self.clone(),
+        }
+        .tls_serialize_detached()
+        .map_err(LibraryError::missing_bound_check)
+    }
+}
+
+/// The state of a message for a member in a [`ReceiptTracker`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemberReceipt {
+    credential: Credential,
+    receipt_type: ReceiptType,
+}
+
+impl MemberReceipt {
+    /// Returns the credential of the member.
+    pub fn credential(&self) -> &Credential {
+        &self.credential
+    }
+
+    /// Returns the most advanced receipt type the member sent for the
+    /// message.
+    pub fn receipt_type(&self) -> ReceiptType {
+        self.receipt_type
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct TrackedMessage {
+    message_ref: MessageRef,
+    receipts: Vec<MemberReceipt>,
+}
+
+/// Aggregates the receipts of the other members for the messages that this
+/// member sent. See the [module documentation](self) for details.
+///
+/// The tracker is independent of the group, so applications can persist it
+/// with `serde` and keep it across epochs.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReceiptTracker {
+    messages: Vec<TrackedMessage>,
+}
+
+impl ReceiptTracker {
+    /// Creates a new, empty [`ReceiptTracker`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts tracking the receipts for the message with the given
+    /// reference.
+    pub fn track(&mut self, message_ref: MessageRef) {
+        if self.message(&message_ref).is_none() {
+            self.messages.push(TrackedMessage {
+                message_ref,
+                receipts: vec![],
+            });
+        }
+    }
+
+    /// Stops tracking the receipts for the message with the given reference.
+    pub fn untrack(&mut self, message_ref: &MessageRef) {
+        self.messages
+            .retain(|message| &message.message_ref != message_ref);
+    }
+
+    /// Records the receipt in `processed_message`, if it is one, for the
+    /// sender of the message and returns it. Receipts for messages that
+    /// aren't tracked are ignored.
+    pub fn record(&mut self, processed_message: &ProcessedMessage) -> Option<Receipt> {
+        let receipt = match processed_message.content() {
+            ProcessedMessageContent::ApplicationMessage(application_message) => {
+                Receipt::decode(application_message.bytes())?
+            }
+            _ => return None,
+        };
+        let sender = processed_message.credential();
+        let tracked_messages = self
+            .messages
+            .iter_mut()
+            .filter(|message| receipt.message_refs().contains(&message.message_ref));
+        for message in tracked_messages {
+            match message
+                .receipts
+                .iter_mut()
+                .find(|member_receipt| &member_receipt.credential == sender)
+            {
+                Some(member_receipt) => {
+                    member_receipt.receipt_type =
+                        member_receipt.receipt_type.max(receipt.receipt_type())
+                }
+                None => message.receipts.push(MemberReceipt {
+                    credential: sender.clone(),
+                    receipt_type: receipt.receipt_type(),
+                }),
+            }
+        }
+        Some(receipt)
+    }
+
+    /// Returns the receipts of the members for the message with the given
+    /// reference, or `None` if the message isn't tracked.
+    pub fn receipts(&self, message_ref: &MessageRef) -> Option<&[MemberReceipt]> {
+        self.message(message_ref)
+            .map(|message| message.receipts.as_slice())
+    }
+
+    /// Returns the credentials of the members that the message with the
+    /// given reference was delivered to, including the members that read it.
+    pub fn delivered_to(&self, message_ref: &MessageRef) -> Vec<&Credential> {
+        self.members_with(message_ref, ReceiptType::Delivered)
+    }
+
+    /// Returns the credentials of the members that read the message with the
+    /// given reference.
+    pub fn read_by(&self, message_ref: &MessageRef) -> Vec<&Credential> {
+        self.members_with(message_ref, ReceiptType::Read)
+    }
+
+    fn message(&self, message_ref: &MessageRef) -> Option<&TrackedMessage> {
+        self.messages
+            .iter()
+            .find(|message| &message.message_ref == message_ref)
+    }
+
+    fn members_with(
+        &self,
+        message_ref: &MessageRef,
+        receipt_type: ReceiptType,
+    ) -> Vec<&Credential> {
+        self.receipts(message_ref)
+            .unwrap_or_default()
+            .iter()
+            .filter(|member_receipt| member_receipt.receipt_type >= receipt_type)
+            .map(MemberReceipt::credential)
+            .collect()
+    }
+}
+
+impl MlsGroup {
+    /// Computes the [`MessageRef`] of a serialized MLS message, e.g., of the
+    /// serialized [`MlsMessageOut`] of an application message or of the
+    /// bytes received from the delivery service.
+    pub fn message_ref(
+        &self,
+        crypto: &impl OpenMlsCrypto,
+        message: &[u8],
+    ) -> Result<MessageRef, LibraryError> {
+        make_message_ref(message, self.ciphersuite(), crypto)
+            .map_err(LibraryError::unexpected_crypto_error)
+    }
+
+    /// Creates an application message with a [`Receipt`] of the given type
+    /// for the messages with the given references. See the
+    /// [module documentation](self) for details.
+    ///
+    /// Returns the same errors as [`MlsGroup::create_message()`].
+    pub fn create_receipt(
+        &mut self,
+        provider: &impl OpenMlsProvider,
+        signer: &impl Signer,
+        receipt_type: ReceiptType,
+        message_refs: &[MessageRef],
+    ) -> Result<MlsMessageOut, CreateMessageError> {
+        let application_data = Receipt::new(receipt_type, message_refs.to_vec()).encode()?;
+        self.create_message(provider, signer, &application_data)
+    }
+}
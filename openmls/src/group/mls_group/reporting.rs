@@ -0,0 +1,240 @@
+//! # Abuse reporting
+//!
+//! Members of a group can report abusive application messages to a server,
+//! e.g., the service provider's moderation service. The server can't decrypt
+//! the messages it delivers, so it needs material from the reporting member
+//! that it can verify on its own.
+//!
+//! [`MlsGroup::report_application_message()`] exports an [`AbuseReport`] for
+//! a received application message. The report contains:
+//!
+//! * the [`GroupContext`] of the epoch in which the message was sent,
+//! * the leaf node of the sender, including its [`Credential`],
+//! * a [`MembershipProof`] that shows that this leaf node is part of the
+//!   ratchet tree of the epoch,
+//! * the application message, its authenticated data and the signature of the
+//!   sender.
+//!
+//! The server verifies the report with [`AbuseReport::verify()`]: the leaf
+//! node must be signed by its owner, the membership proof must lead to the
+//! tree hash of the group context, and the sender must have signed the
+//! message together with the group context. A member therefore can't forge a
+//! report for a message that the sender didn't send in this group and epoch.
+//! The server should additionally check that the group ID and epoch of the
+//! report match a group and epoch it knows.
+//!
+//! The report contains the plaintext of the reported message, but no secrets
+//! of the group. Besides the sender's leaf node, the membership proof only
+//! contains the parent nodes on the sender's direct path and tree hashes, so
+//! the other members of the group aren't revealed.
+//!
+//! Reports can only be created for messages of the current epoch, since the
+//! group context and the ratchet tree of past epochs aren't kept.
+
+use openmls_traits::crypto::OpenMlsCrypto;
+use tls_codec::{Serialize as TlsSerializeTrait, TlsDeserialize, TlsSerialize, TlsSize, VLBytes};
+
+use super::{errors::AbuseReportError, *};
+use crate::{
+    ciphersuite::{signable::Verifiable, Signature},
+    framing::mls_content::{FramedContentBody, FramedContentTbs},
+    treesync::{
+        node::leaf_node::{LeafNodeIn, TreePosition, VerifiableLeafNode},
+        MembershipProof,
+    },
+};
+
+/// Verifiable material to report an application message. See the
+/// [module documentation](self) for details.
+#[derive(
+    Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TlsSerialize, TlsDeserialize, TlsSize,
+)]
+pub struct AbuseReport {
+    group_context: GroupContext,
+    sender: LeafNodeIndex,
+    leaf_node: LeafNodeIn,
+    membership_proof: MembershipProof,
+    wire_format: WireFormat,
+    authenticated_data: VLBytes,
+    application_data: VLBytes,
+    signature: Signature,
+}
+
+impl AbuseReport {
+    /// Returns the [`GroupContext`] of the epoch in which the message was
+    /// sent.
+    pub fn group_context(&self) -> &GroupContext {
+        &self.group_context
+    }
+
+    /// Returns the leaf index of the sender.
+    pub fn sender(&self) -> LeafNodeIndex {
+        self.sender
+    }
+
+    /// Returns the [`Credential`] of the sender.
+    pub fn credential(&self) -> &Credential {
+        self.leaf_node.credential()
+    }
+
+    /// Returns the [`MembershipProof`] of the sender.
+    pub fn membership_proof(&self) -> &MembershipProof {
+        &self.membership_proof
+    }
+
+    /// Returns the authenticated data of the message.
+    pub fn authenticated_data(&self) -> &[u8] {
+        self.authenticated_data.as_slice()
+    }
+
+    /// Returns the reported application message.
+    pub fn application_data(&self) -> &[u8] {
+        self.application_data.as_slice()
+    }
+
+    /// Returns the hash of the signed content of the message. The hash
+    /// identifies the message, e.g., to detect duplicate reports.
+    pub fn message_hash(&self, crypto: &impl OpenMlsCrypto) -> Result<Vec<u8>, LibraryError> {
+        crypto
+            .hash(
+                self.group_context.ciphersuite().hash_algorithm(),
+                &self.signed_content()?,
+            )
+            .map_err(LibraryError::unexpected_crypto_error)
+    }
+
+    /// Verify the report.
+    ///
+    /// Returns an error if the leaf node of the sender or the message aren't
+    /// signed by the sender or if the membership proof doesn't match the tree
+    /// hash of the group context.
+    pub fn verify(&self, crypto: &impl OpenMlsCrypto) -> Result<(), AbuseReportError> {
+        let ciphersuite = self.group_context.ciphersuite();
+        if self.membership_proof.leaf_index() != self.sender {
+            return Err(AbuseReportError::InvalidMembershipProof);
+        }
+
+        // Verify the leaf node at its position in the tree.
+        let verifiable_leaf_node = self.leaf_node.clone().into_verifiable_leaf_node();
+        let signature_key = verifiable_leaf_node
+            .signature_key()
+            .clone()
+            .into_signature_public_key_enriched(ciphersuite.signature_algorithm());
+        let tree_position = TreePosition::new(self.group_context.group_id().clone(), self.sender);
+        let leaf_node = match verifiable_leaf_node {
+            VerifiableLeafNode::KeyPackage(leaf_node) => {
+                leaf_node.verify::<LeafNode>(crypto, &signature_key)
+            }
+            VerifiableLeafNode::Update(mut leaf_node) => {
+                leaf_node.add_tree_position(tree_position);
+                leaf_node.verify::<LeafNode>(crypto, &signature_key)
+            }
+            VerifiableLeafNode::Commit(mut leaf_node) => {
+                leaf_node.add_tree_position(tree_position);
+                leaf_node.verify::<LeafNode>(crypto, &signature_key)
+            }
+        }
+        .map_err(|_| AbuseReportError::InvalidLeafNodeSignature)?;
+
+        if !self.membership_proof.verify(
+            crypto,
+            ciphersuite,
+            &leaf_node,
+            self.group_context.tree_hash(),
+        )? {
+            return Err(AbuseReportError::InvalidMembershipProof);
+        }
+
+        ReportedContent {
+            payload: self.signed_content()?,
+            signature: self.signature.clone(),
+        }
+        .verify_no_out(crypto, &signature_key)
+        .map_err(|_| AbuseReportError::InvalidMessageSignature)
+    }
+
+    /// Returns the serialized `FramedContentTBS` that the sender signed.
+    fn signed_content(&self) -> Result<Vec<u8>, LibraryError> {
+        let serialized_context = self
+            .group_context
+            .tls_serialize_detached()
+            .map_err(LibraryError::missing_bound_check)?;
+        FramedContentTbs::new(
+            self.wire_format,
+            self.group_context.group_id().clone(),
+            self.group_context.epoch(),
+            Sender::Member(self.sender),
+            self.authenticated_data.clone(),
+            FramedContentBody::Application(self.application_data.clone()),
+        )
+        .with_context(serialized_context)
+        .tls_serialize_detached()
+        .map_err(LibraryError::missing_bound_check)
+    }
+}
+
+/// The signed content of a reported message.
+struct ReportedContent {
+    payload: Vec<u8>,
+    signature: Signature,
+}
+
+impl Verifiable for ReportedContent {
+    fn unsigned_payload(&self) -> Result<Vec<u8>, tls_codec::Error> {
+        Ok(self.payload.clone())
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn label(&self) -> &str {
+        "FramedContentTBS"
+    }
+}
+
+impl MlsGroup {
+    /// Export an [`AbuseReport`] for an application message that this group
+    /// processed. See the [module documentation](self) for details.
+    ///
+    /// Returns an error if the message is not an application message or if
+    /// it wasn't sent in the current epoch.
+    pub fn report_application_message(
+        &self,
+        crypto: &impl OpenMlsCrypto,
+        processed_message: &ProcessedMessage,
+    ) -> Result<AbuseReport, AbuseReportError> {
+        let application_message = match processed_message.content() {
+            ProcessedMessageContent::ApplicationMessage(application_message) => application_message,
+            _ => return Err(AbuseReportError::NotAnApplicationMessage),
+        };
+        if application_message.epoch() != self.epoch() {
+            return Err(AbuseReportError::UnavailableEpoch);
+        }
+        let sender = match processed_message.sender() {
+            Sender::Member(leaf_index) => *leaf_index,
+            _ => return Err(AbuseReportError::UnknownSender),
+        };
+        let leaf_node = self
+            .group
+            .public_group()
+            .leaf(sender)
+            .ok_or(AbuseReportError::UnknownSender)?;
+        let membership_proof = self.group.public_group().treesync().membership_proof(
+            crypto,
+            self.ciphersuite(),
+            sender,
+        )?;
+
+        Ok(AbuseReport {
+            group_context: self.group.context().clone(),
+            sender,
+            leaf_node: leaf_node.clone().into(),
+            membership_proof,
+            wire_format: application_message.wire_format(),
+            authenticated_data: processed_message.authenticated_data().into(),
+            application_data: application_message.bytes().into(),
+            signature: application_message.signature().clone(),
+        })
+    }
+}
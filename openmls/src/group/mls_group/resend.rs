@@ -0,0 +1,99 @@
+//! # Resending the pending commit
+//!
+//! When the delivery service times out on a commit, the application can't
+//! tell whether the DS accepted it. Creating a new commit in that situation
+//! may fork the group: if the DS did accept the first commit, the members
+//! merge it while this member merges the second one.
+//!
+//! The group therefore keeps the serialized messages of its pending commit.
+//! [`MlsGroup::resend_pending_commit()`] returns them byte for byte, so that
+//! the application can send the same commit again until the DS either
+//! confirms or rejects it. A DS that deduplicates messages by their bytes
+//! then sees the same commit twice and members that already processed it
+//! reject the copy as a message for a past epoch.
+//!
+//! The messages are persisted with the group and can be resent after a
+//! restart. They are discarded together with the pending commit, i.e., when
+//! it is merged or cleared, or when another commit is merged.
+//!
+//! Creating a commit in steps with [`MlsGroup::start_commit()`] doesn't have
+//! any side effects before [`MlsGroup::stage_commit()`], see
+//! [`CommitOperation`](super::CommitOperation).
+
+use tls_codec::Serialize as TlsSerializeTrait;
+
+use super::*;
+use crate::messages::group_info::GroupInfo;
+
+/// The serialized messages of the pending commit of a group. See the
+/// [module documentation](self) for details.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingCommitMessages {
+    epoch: GroupEpoch,
+    commit: Vec<u8>,
+    welcome: Option<Vec<u8>>,
+    group_info: Option<Vec<u8>>,
+}
+
+impl PendingCommitMessages {
+    /// Returns the epoch the commit was created in.
+    pub fn epoch(&self) -> GroupEpoch {
+        self.epoch
+    }
+
+    /// Returns the serialized `MLSMessage` of the commit.
+    pub fn commit(&self) -> &[u8] {
+        &self.commit
+    }
+
+    /// Returns the serialized `MLSMessage` of the Welcome message, if the
+    /// commit added members.
+    pub fn welcome(&self) -> Option<&[u8]> {
+        self.welcome.as_deref()
+    }
+
+    /// Returns the serialized [`GroupInfo`] of the new epoch, if one was
+    /// created with the commit.
+    pub fn group_info(&self) -> Option<&[u8]> {
+        self.group_info.as_deref()
+    }
+}
+
+impl MlsGroup {
+    /// Returns the messages of the pending commit exactly as they were
+    /// returned when the commit was created, so that they can be sent again.
+    ///
+    /// Returns an error if there is no pending commit.
+    pub fn resend_pending_commit(&self) -> Result<&PendingCommitMessages, MlsGroupStateError> {
+        match (&self.group_state, &self.pending_commit_messages) {
+            (MlsGroupState::PendingCommit(_), Some(messages)) if messages.epoch == self.epoch() => {
+                Ok(messages)
+            }
+            _ => Err(MlsGroupStateError::NoPendingCommit),
+        }
+    }
+
+    /// Keeps the messages of a commit that was just set as the pending commit.
+    pub(super) fn store_pending_commit_messages(
+        &mut self,
+        commit: &MlsMessageOut,
+        welcome: Option<&MlsMessageOut>,
+        group_info: Option<&GroupInfo>,
+    ) -> Result<(), LibraryError> {
+        self.pending_commit_messages = Some(PendingCommitMessages {
+            epoch: self.epoch(),
+            commit: commit
+                .tls_serialize_detached()
+                .map_err(LibraryError::missing_bound_check)?,
+            welcome: welcome
+                .map(|welcome| welcome.tls_serialize_detached())
+                .transpose()
+                .map_err(LibraryError::missing_bound_check)?,
+            group_info: group_info
+                .map(|group_info| group_info.tls_serialize_detached())
+                .transpose()
+                .map_err(LibraryError::missing_bound_check)?,
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,79 @@
+//! # Ratchet resynchronization
+//!
+//! A receiver only ratchets the keys of a sender forward by up to the
+//! `maximum_forward_distance` of the [`SenderRatchetConfiguration`] at a
+//! time. If a sender is further ahead, e.g., because the receiver missed
+//! many of its messages or restored an old state, none of the sender's
+//! messages of the current epoch can be decrypted anymore. The only way to
+//! recover is to start a new epoch, which resets the ratchets of all
+//! senders.
+//!
+//! [`MlsGroup::desynchronized_senders()`] returns the senders of the current
+//! epoch for which this happened, and [`MlsGroup::resynchronize()`] creates
+//! the smallest commit that starts a new epoch.
+//!
+//! The generation of a message is part of the sender data, which every member
+//! can encrypt. A sender is therefore only reported once a message beyond the
+//! forward distance decrypts with the key derived from a copy of the sender's
+//! ratchet and its signature is valid. The message is still rejected. Senders
+//! that are more than 65536 generations ahead are not reported, since
+//! deriving their key would take too long.
+
+use openmls_traits::signatures::Signer;
+
+use super::*;
+use crate::{
+    group::core_group::create_commit_params::CreateCommitParams, messages::group_info::GroupInfo,
+};
+
+impl MlsGroup {
+    /// Returns the members that sent a message of the current epoch in a
+    /// generation beyond the `maximum_forward_distance` of their ratchet.
+    /// Their messages can't be decrypted until the next epoch. See the
+    /// [module documentation](self) for details.
+    pub fn desynchronized_senders(&self) -> Vec<LeafNodeIndex> {
+        self.group
+            .message_secrets()
+            .secret_tree()
+            .desynchronized_senders()
+    }
+
+    /// Creates a commit without proposals that updates the own leaf, if
+    /// [`MlsGroup::desynchronized_senders()`] is not empty. Once the commit is
+    /// merged, the ratchets of all senders start over in the new epoch.
+    ///
+    /// If successful, it returns `None` if no sender is desynchronized, and
+    /// otherwise the [`MlsMessageOut`] containing the commit and the
+    /// [`GroupInfo`], which is [`Some`] if the group has the
+    /// `use_ratchet_tree_extension` flag set. Pending proposals are not
+    /// committed and have to be proposed again in the new epoch.
+    ///
+    /// Returns an error if there is a pending commit.
+    #[allow(clippy::type_complexity)]
+    pub fn resynchronize<KeyStore: OpenMlsKeyStore>(
+        &mut self,
+        provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
+        signer: &impl Signer,
+    ) -> Result<Option<(MlsMessageOut, Option<GroupInfo>)>, SelfUpdateError<KeyStore::Error>> {
+        self.is_operational()?;
+
+        if self.desynchronized_senders().is_empty() {
+            return Ok(None);
+        }
+
+        let aad = self.commit_aad()?;
+        // Commit to an empty proposal store. The commit still contains an
+        // update path, since self-updates are forced by default.
+        let proposal_store = ProposalStore::new();
+        let params = CreateCommitParams::builder()
+            .framing_parameters(self.commit_framing_parameters(&aad))
+            .proposal_store(&proposal_store)
+            .build();
+        let prepared_commit = self.prepare_commit(params, provider, signer)?;
+
+        let (commit, _welcome, group_info) = self
+            .stage_prepared_commit(provider, prepared_commit)?
+            .into_parts();
+        Ok(Some((commit, group_info)))
+    }
+}
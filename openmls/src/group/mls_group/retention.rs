@@ -0,0 +1,207 @@
+//! # Message retention
+//!
+//! Application messages can only be decrypted as long as the message secrets
+//! of their epoch are kept. An [`MlsGroup`] keeps the secrets of the current
+//! epoch and of up to [`MlsGroupConfig::max_past_epochs()`] past epochs,
+//! unless they are dropped earlier to stay within the [`MemoryLimits`]. Within
+//! an epoch, the [`SenderRatchetConfiguration`] determines how far messages
+//! of a sender may be out of order, and every key is deleted once it has been
+//! used.
+//!
+//! Applications and delivery services that store ciphertexts for a client,
+//! e.g., while the client is offline or waiting for a commit, can ask the
+//! group which of them are still worth keeping:
+//!
+//! * [`MlsGroup::decryptable_epochs()`] returns the epochs for which message
+//!   secrets are kept. Ciphertexts of older epochs can be dropped.
+//! * [`MlsGroup::can_decrypt_application_message()`] checks whether an
+//!   application message of a specific sender and generation can still be
+//!   decrypted.
+//! * [`MlsGroup::discard_epoch()`] drops the message secrets of a specific
+//!   past epoch, e.g., once the application has confirmed that all messages
+//!   of that epoch were delivered.
+//!
+//! Messages of future epochs may become decryptable once the group has
+//! caught up and are never reported as decryptable.
+//!
+//! The key of a message is removed from the secret tree as soon as it is
+//! derived for decryption and zeroized when it is dropped after the message
+//! was decrypted. Only the keys of skipped messages are kept, within the
+//! bounds of the configuration above:
+//!
+//! * [`MlsGroup::retained_message_keys()`] lists the keys that are kept.
+//! * [`MlsGroup::stale_message_keys()`] lists the kept keys that the
+//!   configuration doesn't allow to keep. It is always empty unless the group
+//!   state is corrupted, so applications can use it to audit a stored state.
+
+use super::{errors::DiscardEpochError, *};
+use crate::tree::secret_tree::SecretType;
+
+/// The key of a skipped message that is kept by an [`MlsGroup`], as returned
+/// by [`MlsGroup::retained_message_keys()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetainedMessageKey {
+    epoch: GroupEpoch,
+    sender: LeafNodeIndex,
+    generation: u32,
+    handshake: bool,
+}
+
+impl RetainedMessageKey {
+    /// Returns the epoch of the message.
+    pub fn epoch(&self) -> GroupEpoch {
+        self.epoch
+    }
+
+    /// Returns the leaf index of the sender of the message.
+    pub fn sender(&self) -> LeafNodeIndex {
+        self.sender
+    }
+
+    /// Returns the generation of the message in the sender's ratchet.
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    /// Returns `true` if the key is for a handshake message and `false` if
+    /// it is for an application message.
+    pub fn is_handshake(&self) -> bool {
+        self.handshake
+    }
+}
+
+impl MlsGroup {
+    /// Returns the epochs whose application messages can still be decrypted,
+    /// oldest first. The last epoch is the current epoch.
+    pub fn decryptable_epochs(&self) -> Vec<GroupEpoch> {
+        self.group
+            .past_epochs()
+            .chain(std::iter::once(self.epoch()))
+            .collect()
+    }
+
+    /// Drops the message secrets of the past `epoch`, so that no further
+    /// messages of that epoch can be decrypted. The secrets, including the
+    /// keys of skipped messages, are zeroized in memory. Since the group
+    /// state changes, it has to be persisted again and previously persisted
+    /// states have to be deleted to remove the secrets from storage.
+    ///
+    /// Returns an error if `epoch` is not a past epoch or if no message
+    /// secrets are kept for it, e.g., because it was already discarded.
+    pub fn discard_epoch(&mut self, epoch: GroupEpoch) -> Result<(), DiscardEpochError> {
+        if epoch >= self.epoch() {
+            return Err(DiscardEpochError::NotAPastEpoch);
+        }
+        if !self.group.discard_past_epoch_secrets(epoch) {
+            return Err(DiscardEpochError::UnknownEpoch);
+        }
+
+        // Since the state of the group changed, arm the state flag
+        self.flag_state_change();
+        Ok(())
+    }
+
+    /// Returns `true` if an application message that the member at `sender`
+    /// sent in `epoch` with the given ratchet `generation` can still be
+    /// decrypted.
+    ///
+    /// This is `false` for messages of epochs that are not in
+    /// [`decryptable_epochs()`](Self::decryptable_epochs()), for messages whose
+    /// key has already been used or deleted, for messages that are too far in
+    /// the future according to the [`SenderRatchetConfiguration`], and for the
+    /// own messages of this client.
+    pub fn can_decrypt_application_message(
+        &self,
+        epoch: GroupEpoch,
+        sender: LeafNodeIndex,
+        generation: u32,
+    ) -> bool {
+        if epoch > self.epoch() {
+            return false;
+        }
+        let identity = self
+            .group
+            .public_group()
+            .leaf(sender)
+            .map(|leaf| leaf.credential().identity());
+        let sender_ratchet_configuration = self
+            .configuration()
+            .sender_ratchet_configuration()
+            .for_sender(sender, identity);
+        match self.group.message_secrets_for_epoch(epoch) {
+            Ok(message_secrets) => message_secrets.secret_tree().can_decrypt(
+                sender,
+                SecretType::ApplicationSecret,
+                generation,
+                &sender_ratchet_configuration,
+            ),
+            Err(_) => false,
+        }
+    }
+
+    /// Returns the keys of skipped messages that the group keeps, ordered by
+    /// epoch. Keys of messages that were decrypted are never kept. See the
+    /// [module documentation](self) for details.
+    pub fn retained_message_keys(&self) -> Vec<RetainedMessageKey> {
+        self.skipped_message_keys()
+            .into_iter()
+            .map(|(retained_key, _)| retained_key)
+            .collect()
+    }
+
+    /// Returns the kept keys of messages of epochs that are older than
+    /// [`MlsGroupConfig::max_past_epochs()`] or of generations that are
+    /// further in the past than the `out_of_order_tolerance` of the
+    /// [`SenderRatchetConfiguration`] of the sender allows.
+    ///
+    /// The group deletes these keys when it advances, so the result is empty
+    /// unless the group state was corrupted.
+    pub fn stale_message_keys(&self) -> Vec<RetainedMessageKey> {
+        let current_epoch = self.epoch().as_u64();
+        let max_past_epochs = self.configuration().max_past_epochs() as u64;
+        self.skipped_message_keys()
+            .into_iter()
+            .filter(|(retained_key, ratchet_generation)| {
+                let sender = retained_key.sender;
+                let identity = self
+                    .group
+                    .public_group()
+                    .leaf(sender)
+                    .map(|leaf| leaf.credential().identity());
+                let out_of_order_tolerance = self
+                    .configuration()
+                    .sender_ratchet_configuration()
+                    .for_sender(sender, identity)
+                    .out_of_order_tolerance();
+                current_epoch - retained_key.epoch.as_u64() > max_past_epochs
+                    || ratchet_generation - retained_key.generation > out_of_order_tolerance
+            })
+            .map(|(retained_key, _)| retained_key)
+            .collect()
+    }
+
+    /// Returns the keys of skipped messages of all kept epochs together with
+    /// the generation of their sender ratchet.
+    fn skipped_message_keys(&self) -> Vec<(RetainedMessageKey, u32)> {
+        self.decryptable_epochs()
+            .into_iter()
+            .filter_map(|epoch| {
+                let message_secrets = self.group.message_secrets_for_epoch(epoch).ok()?;
+                Some((epoch, message_secrets.secret_tree().skipped_keys()))
+            })
+            .flat_map(|(epoch, skipped_keys)| {
+                skipped_keys.into_iter().map(
+                    move |(sender, secret_type, generation, ratchet_generation)| {
+                        let retained_key = RetainedMessageKey {
+                            epoch,
+                            sender,
+                            generation,
+                            handshake: matches!(secret_type, SecretType::HandshakeSecret),
+                        };
+                        (retained_key, ratchet_generation)
+                    },
+                )
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,160 @@
+//! # Sequencing tokens
+//!
+//! A delivery service (DS) that orders the handshake messages of a group can
+//! assign increasing sequence numbers to the commits it accepts. If the
+//! members bind these numbers into their commits, a DS that reorders or
+//! withholds commits is noticed by the receivers.
+//!
+//! Sequencing tokens are disabled by default and enabled with
+//! [`MlsGroupConfigBuilder::sequencing_tokens()`](super::config::MlsGroupConfigBuilder::sequencing_tokens()).
+//! All members of a group have to enable them. When enabled:
+//!
+//! * The authenticated data of every commit that the group creates is a
+//!   [`SequencedAad`] that contains the sequence number and the AAD set with
+//!   [`MlsGroup::set_aad()`]. The sequence number is set with
+//!   [`MlsGroup::set_sequence_number()`], e.g., after asking the DS for the
+//!   next number. If it isn't set, the number following the last one seen is
+//!   used.
+//! * Incoming commits must carry a [`SequencedAad`] with a sequence number
+//!   that is larger than the one of the last commit that the group processed
+//!   or merged. Otherwise, processing fails with a [`SequencingError`].
+//!
+//! Since the AAD is covered by the signature of the committer, the DS can't
+//! change the sequence number of a commit. It can still read it from public
+//! messages and check that the committer used the number it assigned. The
+//! application can recover its own AAD from
+//! [`ProcessedMessage::authenticated_data()`](crate::framing::ProcessedMessage::authenticated_data()) with
+//! [`SequencedAad::decode()`].
+//!
+//! Proposals and application messages don't carry sequence numbers.
+
+use tls_codec::{
+    Deserialize as TlsDeserializeTrait, Serialize as TlsSerializeTrait, TlsDeserialize,
+    TlsSerialize, TlsSize, VLBytes,
+};
+
+use super::{errors::SequencingError, *};
+
+/// The authenticated data of commits if sequencing tokens are enabled.
+///
+/// ```c
+/// struct {
+///   uint64 sequence_number;
+///   opaque aad<V>;
+/// } SequencedAad;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, TlsSerialize, TlsDeserialize, TlsSize)]
+pub struct SequencedAad {
+    sequence_number: u64,
+    aad: VLBytes,
+}
+
+impl SequencedAad {
+    /// Create a new [`SequencedAad`].
+    pub fn new(sequence_number: u64, aad: &[u8]) -> Self {
+        Self {
+            sequence_number,
+            aad: aad.into(),
+        }
+    }
+
+    /// Decode a [`SequencedAad`] from the authenticated data of a commit.
+    pub fn decode(authenticated_data: &[u8]) -> Result<Self, SequencingError> {
+        Self::tls_deserialize_exact(authenticated_data)
+            .map_err(|_| SequencingError::MissingSequenceNumber)
+    }
+
+    /// Returns the sequence number.
+    pub fn sequence_number(&self) -> u64 {
+        self.sequence_number
+    }
+
+    /// Returns the AAD of the application.
+    pub fn aad(&self) -> &[u8] {
+        self.aad.as_slice()
+    }
+}
+
+/// The sequence numbers of an [`MlsGroup`].
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct SequencingState {
+    // The sequence number for the next own commit.
+    next: Option<u64>,
+    // The sequence number of the last commit that was processed or merged.
+    last: Option<u64>,
+}
+
+impl SequencingState {
+    fn outgoing(&self) -> u64 {
+        self.next
+            .unwrap_or_else(|| self.last.map_or(0, |last| last.saturating_add(1)))
+    }
+}
+
+impl MlsGroup {
+    /// Set the sequence number for the next commit created by this group.
+    /// This has no effect unless sequencing tokens are enabled in the
+    /// [`MlsGroupConfig`]. See the [module documentation](self) for details.
+    ///
+    /// Returns an error if there is a pending commit.
+    pub fn set_sequence_number(&mut self, sequence_number: u64) -> Result<(), MlsGroupStateError> {
+        self.is_operational()?;
+        self.sequencing.next = Some(sequence_number);
+
+        // Since the state of the group might be changed, arm the state flag
+        self.flag_state_change();
+
+        Ok(())
+    }
+
+    /// Returns the sequence number of the last commit that this group
+    /// processed or merged, if sequencing tokens are enabled.
+    pub fn last_sequence_number(&self) -> Option<u64> {
+        self.sequencing.last
+    }
+
+    /// Returns the authenticated data for the next own commit.
+    pub(super) fn commit_aad(&self) -> Result<Vec<u8>, LibraryError> {
+        let aad = self.unsequenced_commit_aad()?;
+        if !self.mls_group_config.sequencing_tokens() {
+            return Ok(aad);
+        }
+        SequencedAad::new(self.sequencing.outgoing(), &aad)
+            .tls_serialize_detached()
+            .map_err(LibraryError::missing_bound_check)
+    }
+
+    /// Check the sequence number of an incoming commit and remember it.
+    pub(super) fn check_sequence_number(
+        &mut self,
+        processed_message: &ProcessedMessage,
+    ) -> Result<(), SequencingError> {
+        if !self.mls_group_config.sequencing_tokens()
+            || !matches!(
+                processed_message.content(),
+                ProcessedMessageContent::StagedCommitMessage(_)
+            )
+        {
+            return Ok(());
+        }
+        let sequence_number =
+            SequencedAad::decode(processed_message.authenticated_data())?.sequence_number();
+        if self
+            .sequencing
+            .last
+            .is_some_and(|last| sequence_number <= last)
+        {
+            return Err(SequencingError::NonMonotonicSequenceNumber);
+        }
+        self.sequencing.last = Some(sequence_number);
+        Ok(())
+    }
+
+    /// Remember the sequence number of the own commit that is merged.
+    pub(super) fn merge_own_sequence_number(&mut self) {
+        if self.mls_group_config.sequencing_tokens() {
+            self.sequencing.last = Some(self.sequencing.outgoing());
+            self.sequencing.next = None;
+        }
+    }
+}
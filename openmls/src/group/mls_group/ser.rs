@@ -1,63 +1,224 @@
-// TODO #245: Remove this once we have a proper serialization format
-#![allow(deprecated)]
+//! # Persisted group state
+//!
+//! The persisted state of an [`MlsGroup`] is a serde structure of its own and
+//! independent of the TLS wire format. Every version of the state has a
+//! dedicated type that describes its fields, e.g., [`MlsGroupStateV1`], and a
+//! migration to the next version. Loading a state deserializes it with the
+//! type of its version and migrates it to the current version.
+//!
+//! Fields may be added to the current version as long as they have a default,
+//! so that older states of the same version can still be loaded. Any other
+//! change to the fields or to the serde representation of the types they
+//! contain requires a new version with its own type and a migration from the
+//! previous one. States with a higher version than
+//! [`MLS_GROUP_STATE_VERSION`] are rejected when loading.
+
+use std::fmt;
 
 use super::*;
 use crate::schedule::psk::store::ResumptionPskStore;
 
 use openmls_traits::key_store::{MlsEntity, MlsEntityId};
 use serde::{
+    de,
     ser::{SerializeStruct, Serializer},
     Deserialize, Serialize,
 };
 
-/// Helper struct that contains the serializable values of an `MlsGroup.
-#[deprecated(
-    since = "0.4.1",
-    note = "It is temporarily exposed, it will be private again after #245"
-)]
-#[derive(Serialize, Deserialize)]
-pub struct SerializedMlsGroup {
+/// The version of the persisted [`MlsGroup`] state written by this version of
+/// OpenMLS.
+///
+/// States of older versions are migrated to this version when they are
+/// loaded, states with a higher version are rejected.
+pub const MLS_GROUP_STATE_VERSION: u16 = 2;
+
+/// The `state_version` field of version `V` of the persisted state. It fails
+/// to deserialize for any other version.
+struct StateVersion<const V: u16>;
+
+impl<'de, const V: u16> Deserialize<'de> for StateVersion<V> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let version = u16::deserialize(deserializer)?;
+        if version != V {
+            return Err(de::Error::custom(format!(
+                "expected group state version {V}, got {version}"
+            )));
+        }
+        Ok(StateVersion)
+    }
+}
+
+/// Version 1 of the persisted state.
+///
+/// States that were written before the state was versioned don't contain a
+/// `state_version` and are version 1 as well. Version 1 contains the
+/// resumption PSK store twice, in the `group` and in `resumption_psk_store`.
+#[derive(Deserialize)]
+struct MlsGroupStateV1 {
+    #[serde(default)]
+    #[allow(dead_code)]
+    state_version: Option<StateVersion<1>>,
     mls_group_config: MlsGroupConfig,
     group: CoreGroup,
     proposal_store: ProposalStore,
     own_leaf_nodes: Vec<LeafNode>,
     aad: Vec<u8>,
+    #[allow(dead_code)]
     resumption_psk_store: ResumptionPskStore,
     group_state: MlsGroupState,
+    audit_log: Option<AuditLog>,
+    #[serde(default)]
+    sequencing: sequencing::SequencingState,
+    #[serde(default)]
+    stale_artifacts: Option<StaleArtifacts>,
+    #[serde(default)]
+    history_keys: Vec<HistoryKey>,
+    #[serde(default)]
+    commit_metadata: Option<Vec<u8>>,
+    #[serde(default)]
+    leaf_age: Option<maintenance::LeafAge>,
+    #[serde(default)]
+    declined_members: Vec<decline::DeclinedMember>,
+    #[serde(default)]
+    issued_welcome: Option<Welcome>,
+    #[serde(default)]
+    pending_commit_messages: Option<PendingCommitMessages>,
+    #[serde(default)]
+    past_epoch_authenticators: Vec<(GroupEpoch, EpochAuthenticator)>,
+    #[serde(default)]
+    snapshot_active: bool,
+    #[serde(default)]
+    operation_journal: journal::OperationJournal,
 }
 
-#[allow(clippy::from_over_into)]
-impl Into<MlsGroup> for SerializedMlsGroup {
-    fn into(self) -> MlsGroup {
+/// Version 2 of the persisted state, which is written by
+/// [`MlsGroup::save()`].
+///
+/// The resumption PSK store is only persisted as part of the `group`.
+#[derive(Deserialize)]
+struct MlsGroupStateV2 {
+    #[allow(dead_code)]
+    state_version: StateVersion<2>,
+    mls_group_config: MlsGroupConfig,
+    group: CoreGroup,
+    proposal_store: ProposalStore,
+    own_leaf_nodes: Vec<LeafNode>,
+    aad: Vec<u8>,
+    group_state: MlsGroupState,
+    audit_log: Option<AuditLog>,
+    sequencing: sequencing::SequencingState,
+    stale_artifacts: Option<StaleArtifacts>,
+    history_keys: Vec<HistoryKey>,
+    commit_metadata: Option<Vec<u8>>,
+    leaf_age: Option<maintenance::LeafAge>,
+    declined_members: Vec<decline::DeclinedMember>,
+    issued_welcome: Option<Welcome>,
+    pending_commit_messages: Option<PendingCommitMessages>,
+    past_epoch_authenticators: Vec<(GroupEpoch, EpochAuthenticator)>,
+    snapshot_active: bool,
+    operation_journal: journal::OperationJournal,
+}
+
+/// Migrates a version 1 state by dropping the copy of the resumption PSK
+/// store, which was never read.
+impl From<MlsGroupStateV1> for MlsGroupStateV2 {
+    fn from(state: MlsGroupStateV1) -> Self {
+        Self {
+            state_version: StateVersion,
+            mls_group_config: state.mls_group_config,
+            group: state.group,
+            proposal_store: state.proposal_store,
+            own_leaf_nodes: state.own_leaf_nodes,
+            aad: state.aad,
+            group_state: state.group_state,
+            audit_log: state.audit_log,
+            sequencing: state.sequencing,
+            stale_artifacts: state.stale_artifacts,
+            history_keys: state.history_keys,
+            commit_metadata: state.commit_metadata,
+            leaf_age: state.leaf_age,
+            declined_members: state.declined_members,
+            issued_welcome: state.issued_welcome,
+            pending_commit_messages: state.pending_commit_messages,
+            past_epoch_authenticators: state.past_epoch_authenticators,
+            snapshot_active: state.snapshot_active,
+            operation_journal: state.operation_journal,
+        }
+    }
+}
+
+impl From<MlsGroupStateV2> for MlsGroup {
+    fn from(state: MlsGroupStateV2) -> Self {
         MlsGroup {
-            mls_group_config: self.mls_group_config,
-            group: self.group,
-            proposal_store: self.proposal_store,
-            own_leaf_nodes: self.own_leaf_nodes,
-            aad: self.aad,
-            group_state: self.group_state,
+            mls_group_config: state.mls_group_config,
+            group: state.group,
+            proposal_store: state.proposal_store,
+            own_leaf_nodes: state.own_leaf_nodes,
+            aad: state.aad,
+            group_state: state.group_state,
             state_changed: InnerState::Persisted,
+            audit_log: state.audit_log,
+            sequencing: state.sequencing,
+            stale_artifacts: state.stale_artifacts,
+            history_keys: state.history_keys,
+            commit_metadata: state.commit_metadata,
+            leaf_age: state.leaf_age,
+            declined_members: state.declined_members,
+            issued_welcome: state.issued_welcome,
+            pending_commit_messages: state.pending_commit_messages,
+            past_epoch_authenticators: state.past_epoch_authenticators,
+            snapshot_active: state.snapshot_active,
+            operation_journal: state.operation_journal,
+            exporter_cache: Default::default(),
+            epoch_subscribers: Default::default(),
         }
     }
 }
 
+/// A persisted state of any version. The versions are tried from the newest
+/// to the oldest, states with an unknown version end up in `Unsupported`.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PersistedMlsGroup {
+    V2(MlsGroupStateV2),
+    V1(MlsGroupStateV1),
+    Unsupported { state_version: u16 },
+}
+
 impl MlsEntity for MlsGroup {
     const ID: MlsEntityId = MlsEntityId::GroupState;
+    const VERSION: u16 = MLS_GROUP_STATE_VERSION;
 }
 
+/// Writes the fields of [`MlsGroupStateV2`].
 impl Serialize for MlsGroup {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        let mut state = serializer.serialize_struct("SerializedMlsGroup", 6)?;
+        let mut state = serializer.serialize_struct("MlsGroupStateV2", 19)?;
+        state.serialize_field("state_version", &MLS_GROUP_STATE_VERSION)?;
         state.serialize_field("mls_group_config", &self.mls_group_config)?;
         state.serialize_field("group", &self.group)?;
         state.serialize_field("proposal_store", &self.proposal_store)?;
         state.serialize_field("own_leaf_nodes", &self.own_leaf_nodes)?;
         state.serialize_field("aad", &self.aad)?;
-        state.serialize_field("resumption_psk_store", &self.group.resumption_psk_store)?;
         state.serialize_field("group_state", &self.group_state)?;
+        state.serialize_field("audit_log", &self.audit_log)?;
+        state.serialize_field("sequencing", &self.sequencing)?;
+        state.serialize_field("stale_artifacts", &self.stale_artifacts)?;
+        state.serialize_field("history_keys", &self.history_keys)?;
+        state.serialize_field("commit_metadata", &self.commit_metadata)?;
+        state.serialize_field("leaf_age", &self.leaf_age)?;
+        state.serialize_field("declined_members", &self.declined_members)?;
+        state.serialize_field("issued_welcome", &self.issued_welcome)?;
+        state.serialize_field("pending_commit_messages", &self.pending_commit_messages)?;
+        state.serialize_field("past_epoch_authenticators", &self.past_epoch_authenticators)?;
+        state.serialize_field("snapshot_active", &self.snapshot_active)?;
+        state.serialize_field("operation_journal", &self.operation_journal)?;
         state.end()
     }
 }
@@ -67,7 +228,25 @@ impl<'de> Deserialize<'de> for MlsGroup {
     where
         D: serde::Deserializer<'de>,
     {
-        let sgroup = SerializedMlsGroup::deserialize(deserializer)?;
-        Ok(sgroup.into())
+        match PersistedMlsGroup::deserialize(deserializer)? {
+            PersistedMlsGroup::V2(state) => Ok(state.into()),
+            PersistedMlsGroup::V1(state) => Ok(MlsGroupStateV2::from(state).into()),
+            PersistedMlsGroup::Unsupported { state_version } => {
+                Err(de::Error::custom(UnsupportedStateVersion(state_version)))
+            }
+        }
+    }
+}
+
+/// The error of a state that doesn't match the type of its version.
+struct UnsupportedStateVersion(u16);
+
+impl fmt::Display for UnsupportedStateVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0 > MLS_GROUP_STATE_VERSION {
+            write!(f, "unsupported group state version {}", self.0)
+        } else {
+            write!(f, "invalid group state of version {}", self.0)
+        }
     }
 }
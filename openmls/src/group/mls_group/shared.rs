@@ -0,0 +1,171 @@
+//! # Shared MLS group
+//!
+//! This module contains [`SharedMlsGroup`], a handle to an [`MlsGroup`] that
+//! can be cloned and shared between threads. It synchronizes access to the
+//! group internally, so that applications don't have to build their own
+//! locking around [`MlsGroup`], whose operations take `&mut self`.
+//!
+//! ## Ordering guarantees
+//!
+//! All operations that modify the group, i.e., processing messages, creating
+//! messages and commits, and merging commits, are executed while holding the
+//! write lock of the group. They are therefore totally ordered: each operation
+//! observes the effects of all operations that released the lock before it.
+//! In particular:
+//!
+//! * An application message created after a commit has been merged is sent in
+//!   the new epoch. A message created while the commit is still only staged or
+//!   pending is sent in the old epoch.
+//! * Processing a commit with [`MlsGroup::process_message()`] and merging the
+//!   resulting [`StagedCommit`] are two operations. To make sure that no
+//!   message is created in between, process and merge the commit while
+//!   holding a single [`SharedMlsGroupWriteGuard`].
+//!
+//! The order in which waiting threads acquire the lock is not specified. If
+//! the application requires e.g. that incoming messages are processed before
+//! outgoing messages are sent, it has to order the operations itself, e.g.
+//! by driving both from a single task.
+//!
+//! ## Avoiding deadlocks
+//!
+//! [`SharedMlsGroup::read()`] and [`SharedMlsGroup::write()`] block until
+//! the lock is available. Threads that must not block, e.g. UI threads, use
+//! [`SharedMlsGroup::try_read()`] and [`SharedMlsGroup::try_write()`], which
+//! return [`SharedMlsGroupError::WouldBlock`] instead.
+//!
+//! The lock is not reentrant: a thread that holds a guard must not lock the
+//! same group again.
+
+use std::{
+    ops::{Deref, DerefMut},
+    sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard, TryLockError},
+};
+
+use super::{errors::SharedMlsGroupError, MlsGroup};
+#[cfg(doc)]
+use crate::group::StagedCommit;
+
+/// A handle to an [`MlsGroup`] that can be shared between threads.
+///
+/// Cloning the handle is cheap and yields a handle to the same group. See the
+/// [module documentation](self) for the ordering guarantees.
+#[derive(Debug, Clone)]
+pub struct SharedMlsGroup {
+    group: Arc<RwLock<MlsGroup>>,
+}
+
+impl SharedMlsGroup {
+    /// Create a shared handle to `group`.
+    pub fn new(group: MlsGroup) -> Self {
+        Self {
+            group: Arc::new(RwLock::new(group)),
+        }
+    }
+
+    /// Lock the group for reading, blocking until the lock is available.
+    ///
+    /// Multiple readers can hold the lock at the same time.
+    ///
+    /// Returns [`SharedMlsGroupError::Poisoned`] if an operation panicked
+    /// while it held the write lock.
+    pub fn read(&self) -> Result<SharedMlsGroupReadGuard<'_>, SharedMlsGroupError> {
+        self.group
+            .read()
+            .map(|guard| SharedMlsGroupReadGuard { guard })
+            .map_err(|_| SharedMlsGroupError::Poisoned)
+    }
+
+    /// Lock the group for reading without blocking.
+    ///
+    /// Returns [`SharedMlsGroupError::WouldBlock`] if the group is locked for
+    /// writing and [`SharedMlsGroupError::Poisoned`] if an operation panicked
+    /// while it held the write lock.
+    pub fn try_read(&self) -> Result<SharedMlsGroupReadGuard<'_>, SharedMlsGroupError> {
+        match self.group.try_read() {
+            Ok(guard) => Ok(SharedMlsGroupReadGuard { guard }),
+            Err(TryLockError::WouldBlock) => Err(SharedMlsGroupError::WouldBlock),
+            Err(TryLockError::Poisoned(_)) => Err(SharedMlsGroupError::Poisoned),
+        }
+    }
+
+    /// Lock the group for writing, blocking until the lock is available.
+    ///
+    /// Returns [`SharedMlsGroupError::Poisoned`] if an operation panicked
+    /// while it held the write lock.
+    pub fn write(&self) -> Result<SharedMlsGroupWriteGuard<'_>, SharedMlsGroupError> {
+        self.group
+            .write()
+            .map(|guard| SharedMlsGroupWriteGuard { guard })
+            .map_err(|_| SharedMlsGroupError::Poisoned)
+    }
+
+    /// Lock the group for writing without blocking.
+    ///
+    /// Returns [`SharedMlsGroupError::WouldBlock`] if the group is locked and
+    /// [`SharedMlsGroupError::Poisoned`] if an operation panicked while it
+    /// held the write lock.
+    pub fn try_write(&self) -> Result<SharedMlsGroupWriteGuard<'_>, SharedMlsGroupError> {
+        match self.group.try_write() {
+            Ok(guard) => Ok(SharedMlsGroupWriteGuard { guard }),
+            Err(TryLockError::WouldBlock) => Err(SharedMlsGroupError::WouldBlock),
+            Err(TryLockError::Poisoned(_)) => Err(SharedMlsGroupError::Poisoned),
+        }
+    }
+
+    /// Return the group if this is the only handle to it.
+    ///
+    /// Returns the handle itself if there are other handles.
+    pub fn try_into_inner(self) -> Result<MlsGroup, Self> {
+        match Arc::try_unwrap(self.group) {
+            Ok(lock) => match lock.into_inner() {
+                Ok(group) => Ok(group),
+                // The group is returned even if an operation panicked. The
+                // caller is the only owner and decides what to do with it.
+                Err(poisoned) => Ok(poisoned.into_inner()),
+            },
+            Err(group) => Err(Self { group }),
+        }
+    }
+}
+
+impl From<MlsGroup> for SharedMlsGroup {
+    fn from(group: MlsGroup) -> Self {
+        Self::new(group)
+    }
+}
+
+/// Read access to a [`SharedMlsGroup`]. The lock is released when the guard is
+/// dropped.
+#[derive(Debug)]
+pub struct SharedMlsGroupReadGuard<'a> {
+    guard: RwLockReadGuard<'a, MlsGroup>,
+}
+
+impl Deref for SharedMlsGroupReadGuard<'_> {
+    type Target = MlsGroup;
+
+    fn deref(&self) -> &Self::Target {
+        &self.guard
+    }
+}
+
+/// Write access to a [`SharedMlsGroup`]. The lock is released when the guard
+/// is dropped.
+#[derive(Debug)]
+pub struct SharedMlsGroupWriteGuard<'a> {
+    guard: RwLockWriteGuard<'a, MlsGroup>,
+}
+
+impl Deref for SharedMlsGroupWriteGuard<'_> {
+    type Target = MlsGroup;
+
+    fn deref(&self) -> &Self::Target {
+        &self.guard
+    }
+}
+
+impl DerefMut for SharedMlsGroupWriteGuard<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.guard
+    }
+}
@@ -0,0 +1,194 @@
+//! # Snapshots
+//!
+//! Some applications need to apply a commit tentatively, run their own checks
+//! on the new epoch, e.g., against an application-level access control list,
+//! and undo the commit if the checks fail. [`MlsGroup::snapshot()`] writes the
+//! full group state to the key store of the provider, including the
+//! encryption keypairs of the current epoch and of own pending updates, which
+//! are deleted from the key store when a commit is merged.
+//!
+//! After the snapshot was taken, the commit is merged as usual with
+//! [`MlsGroup::merge_staged_commit()`] or [`MlsGroup::merge_pending_commit()`].
+//! The application then either
+//!
+//! * keeps the new state with [`MlsGroup::release_snapshot()`], which deletes
+//!   the snapshot, or
+//! * restores the state of the snapshot with [`MlsGroup::rollback()`], which
+//!   also deletes the encryption keypairs of the rolled back epoch.
+//!
+//! While a snapshot is taken, the group can't send messages: creating
+//! application messages, proposals and commits fails with
+//! [`MlsGroupStateError::SnapshotActive`], since other members might process
+//! messages of an epoch that is rolled back. Incoming messages can be
+//! processed, but all their effects are rolled back as well.
+//!
+//! A rollback restores the group state, which includes the proposal store,
+//! the resumption PSKs and the history keys, and the encryption keypairs in
+//! the key store. These are the only key store entries that merging a commit
+//! changes: PSKs and key packages in the key store are only read when a
+//! commit is processed, and are neither consumed by the merge nor restored by
+//! the rollback. Key store entries that the application writes or deletes
+//! itself while the snapshot is taken, e.g., PSKs or the key packages
+//! consumed when joining other groups, are not reverted.
+//!
+//! The snapshot is part of the persisted group state, so an application that
+//! crashes while a snapshot is taken can decide after loading the group
+//! whether to release the snapshot or to roll back. Epoch transition
+//! subscriptions are not affected by a rollback, but no transition is emitted
+//! for it. Note that the snapshot contains the secrets of the epoch it was
+//! taken in and weakens forward secrecy until it is released.
+
+use std::mem;
+
+use openmls_traits::key_store::{MlsEntity, MlsEntityId};
+use serde::{Deserialize, Serialize};
+
+use super::{errors::SnapshotError, *};
+use crate::treesync::node::encryption_keys::EncryptionKeyPair;
+
+/// The key store label of group snapshots.
+const SNAPSHOT_LABEL: &[u8] = b"MLS 1.0 group snapshot";
+/// The key store label of the keypairs of group snapshots.
+const SNAPSHOT_KEYPAIRS_LABEL: &[u8] = b"MLS 1.0 group snapshot keypairs";
+
+/// The key store entries that are deleted when a commit is merged, but are
+/// needed to roll back a group to the epoch of the snapshot.
+#[derive(Serialize, Deserialize)]
+struct SnapshotKeypairs {
+    epoch_keypairs: Vec<EncryptionKeyPair>,
+    leaf_keypairs: Vec<EncryptionKeyPair>,
+}
+
+impl MlsEntity for SnapshotKeypairs {
+    const ID: MlsEntityId = MlsEntityId::EncryptionKeyPair;
+}
+
+/// Returns the key store ID with the given `label` for the group with
+/// `group_id`.
+fn snapshot_id(label: &[u8], group_id: &GroupId) -> Vec<u8> {
+    let mut id = label.to_vec();
+    id.extend_from_slice(group_id.as_slice());
+    id
+}
+
+impl MlsGroup {
+    /// Writes a snapshot of the group state to the key store, so that the
+    /// group can be rolled back to it with [`MlsGroup::rollback()`]. See the
+    /// [module documentation](self) for details.
+    ///
+    /// Returns [`SnapshotError::SnapshotActive`] if a snapshot was already
+    /// taken.
+    pub fn snapshot<KeyStore: OpenMlsKeyStore>(
+        &mut self,
+        provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
+    ) -> Result<(), SnapshotError<KeyStore::Error>> {
+        if self.snapshot_active {
+            return Err(SnapshotError::SnapshotActive);
+        }
+
+        let leaf_keypairs = self
+            .own_leaf_nodes
+            .iter()
+            .map(|leaf_node| {
+                EncryptionKeyPair::read_from_key_store(provider, leaf_node.encryption_key())
+                    .ok_or_else(|| LibraryError::custom("Missing leaf keypair in key store"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let keypairs = SnapshotKeypairs {
+            epoch_keypairs: self.group.read_epoch_keypairs(provider.key_store()),
+            leaf_keypairs,
+        };
+        let key_store = provider.key_store();
+        key_store
+            .store(
+                &snapshot_id(SNAPSHOT_KEYPAIRS_LABEL, self.group_id()),
+                &keypairs,
+            )
+            .map_err(SnapshotError::KeyStoreError)?;
+        // The group is stored before the flag is set, so that the restored
+        // group has no snapshot.
+        key_store
+            .store(&snapshot_id(SNAPSHOT_LABEL, self.group_id()), &*self)
+            .map_err(SnapshotError::KeyStoreError)?;
+
+        self.snapshot_active = true;
+        self.flag_state_change();
+        Ok(())
+    }
+
+    /// Returns `true` if a snapshot was taken with [`MlsGroup::snapshot()`]
+    /// and was neither released nor rolled back.
+    pub fn has_snapshot(&self) -> bool {
+        self.snapshot_active
+    }
+
+    /// Deletes the snapshot and keeps the current group state. See the
+    /// [module documentation](self) for details.
+    ///
+    /// Returns [`SnapshotError::NoSnapshot`] if no snapshot was taken.
+    pub fn release_snapshot<KeyStore: OpenMlsKeyStore>(
+        &mut self,
+        provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
+    ) -> Result<(), SnapshotError<KeyStore::Error>> {
+        if !self.snapshot_active {
+            return Err(SnapshotError::NoSnapshot);
+        }
+        self.delete_snapshot(provider.key_store())
+            .map_err(SnapshotError::KeyStoreError)?;
+
+        self.snapshot_active = false;
+        self.flag_state_change();
+        Ok(())
+    }
+
+    /// Restores the group state of the snapshot and deletes the snapshot. See
+    /// the [module documentation](self) for details.
+    ///
+    /// Returns [`SnapshotError::NoSnapshot`] if no snapshot was taken.
+    pub fn rollback<KeyStore: OpenMlsKeyStore>(
+        &mut self,
+        provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
+    ) -> Result<(), SnapshotError<KeyStore::Error>> {
+        if !self.snapshot_active {
+            return Err(SnapshotError::NoSnapshot);
+        }
+        let key_store = provider.key_store();
+        let mut group: MlsGroup = key_store
+            .read(&snapshot_id(SNAPSHOT_LABEL, self.group_id()))
+            .ok_or(SnapshotError::MissingSnapshot)?;
+        let keypairs: SnapshotKeypairs = key_store
+            .read(&snapshot_id(SNAPSHOT_KEYPAIRS_LABEL, self.group_id()))
+            .ok_or(SnapshotError::MissingSnapshot)?;
+
+        // Replace the keypairs of the current epoch with the ones of the
+        // snapshot.
+        self.group
+            .delete_epoch_keypairs(key_store)
+            .map_err(SnapshotError::KeyStoreError)?;
+        group
+            .group
+            .store_epoch_keypairs(key_store, &keypairs.epoch_keypairs)
+            .map_err(SnapshotError::KeyStoreError)?;
+        for keypair in &keypairs.leaf_keypairs {
+            keypair
+                .write_to_key_store(key_store)
+                .map_err(SnapshotError::KeyStoreError)?;
+        }
+        self.delete_snapshot(key_store)
+            .map_err(SnapshotError::KeyStoreError)?;
+
+        group.epoch_subscribers = mem::take(&mut self.epoch_subscribers);
+        *self = group;
+        self.flag_state_change();
+        Ok(())
+    }
+
+    /// Deletes the snapshot of the group from the key store.
+    fn delete_snapshot<KeyStore: OpenMlsKeyStore>(
+        &self,
+        key_store: &KeyStore,
+    ) -> Result<(), KeyStore::Error> {
+        key_store.delete::<MlsGroup>(&snapshot_id(SNAPSHOT_LABEL, self.group_id()))?;
+        key_store.delete::<SnapshotKeypairs>(&snapshot_id(SNAPSHOT_KEYPAIRS_LABEL, self.group_id()))
+    }
+}
@@ -0,0 +1,156 @@
+//! # Speculative groups
+//!
+//! This module is only available with the `speculative` feature. It lets
+//! applications ask "what would the group look like if ...", e.g., to show
+//! the tree and the epoch of a commit in a planning UI before it is created,
+//! or to simulate the groups of many clients in a delivery service.
+//!
+//! [`MlsGroup::speculative_clone()`] returns a [`SpeculativeGroup`], an
+//! in-memory copy of the group that accepts the same operations as the
+//! original. Operations are executed with [`SpeculativeGroup::apply()`] and a
+//! [`SpeculativeProvider`] in front of the application's provider:
+//!
+//! * Values that the operation stores or deletes are only recorded in the
+//!   speculative group. The key store of the application is never written
+//!   to.
+//! * Values that the operation reads are looked up in the recorded values
+//!   first and in the key store of the application otherwise.
+//!
+//! The original group is not changed by anything that is done with the copy.
+//! When the copy is dropped, all of its state is gone. A speculative group
+//! can't be turned back into an [`MlsGroup`], since its keys only exist in
+//! memory. To actually perform an operation, perform it on the original
+//! group.
+//!
+//! ```ignore
+//! let mut speculative_group = mls_group.speculative_clone()?;
+//! let epoch = speculative_group.apply(provider, |group, provider| {
+//!     group.commit_to_pending_proposals(provider, signer)?;
+//!     group.merge_pending_commit(provider)?;
+//!     Ok(group.epoch())
+//! })?;
+//! ```
+
+use std::{cell::RefCell, collections::HashMap, ops::Deref};
+
+use openmls_traits::key_store::{unwrap_blob, BlobHeader, MlsEntity, OpenMlsKeyStore};
+
+use super::{errors::SpeculativeKeyStoreError, *};
+
+/// The values written (`Some`) and deleted (`None`) by the operations on a
+/// [`SpeculativeGroup`].
+type RecordedValues = RefCell<HashMap<Vec<u8>, Option<Vec<u8>>>>;
+
+/// An in-memory copy of an [`MlsGroup`]. See the
+/// [module documentation](self) for details.
+#[derive(Debug)]
+pub struct SpeculativeGroup {
+    group: MlsGroup,
+    recorded: RecordedValues,
+}
+
+impl SpeculativeGroup {
+    /// Executes `operation` on the copy of the group. The operation is
+    /// given a [`SpeculativeProvider`] that has to be used in place of
+    /// `provider`.
+    pub fn apply<P: OpenMlsProvider, T>(
+        &mut self,
+        provider: &P,
+        operation: impl FnOnce(&mut MlsGroup, &SpeculativeProvider<P>) -> T,
+    ) -> T {
+        let speculative_provider = SpeculativeProvider {
+            provider,
+            key_store: SpeculativeKeyStore {
+                key_store: provider.key_store(),
+                recorded: &self.recorded,
+            },
+        };
+        operation(&mut self.group, &speculative_provider)
+    }
+}
+
+impl Deref for SpeculativeGroup {
+    type Target = MlsGroup;
+
+    fn deref(&self) -> &MlsGroup {
+        &self.group
+    }
+}
+
+/// A key store in front of the key store of the application that keeps all
+/// writes and deletions in memory.
+pub struct SpeculativeKeyStore<'a, KeyStore: OpenMlsKeyStore> {
+    key_store: &'a KeyStore,
+    recorded: &'a RecordedValues,
+}
+
+impl<KeyStore: OpenMlsKeyStore> OpenMlsKeyStore for SpeculativeKeyStore<'_, KeyStore> {
+    type Error = SpeculativeKeyStoreError;
+
+    fn store<V: MlsEntity>(&self, k: &[u8], v: &V) -> Result<(), Self::Error> {
+        let value = serde_json::to_vec(v)
+            .map_err(|e| SpeculativeKeyStoreError::SerializationError(e.to_string()))?;
+        let value = BlobHeader::for_entity::<V>().wrap(&value);
+        self.recorded.borrow_mut().insert(k.to_vec(), Some(value));
+        Ok(())
+    }
+
+    fn read<V: MlsEntity>(&self, k: &[u8]) -> Option<V> {
+        match self.recorded.borrow().get(k) {
+            Some(value) => serde_json::from_slice(unwrap_blob::<V>(value.as_ref()?)?).ok(),
+            None => self.key_store.read(k),
+        }
+    }
+
+    fn delete<V: MlsEntity>(&self, k: &[u8]) -> Result<(), Self::Error> {
+        self.recorded.borrow_mut().insert(k.to_vec(), None);
+        Ok(())
+    }
+}
+
+/// The [`OpenMlsProvider`] that operations on a [`SpeculativeGroup`] are
+/// executed with. It uses the crypto, randomness and clock providers of the
+/// application and a [`SpeculativeKeyStore`].
+pub struct SpeculativeProvider<'a, P: OpenMlsProvider> {
+    provider: &'a P,
+    key_store: SpeculativeKeyStore<'a, P::KeyStoreProvider>,
+}
+
+impl<'a, P: OpenMlsProvider> OpenMlsProvider for SpeculativeProvider<'a, P> {
+    type CryptoProvider = P::CryptoProvider;
+    type RandProvider = P::RandProvider;
+    type KeyStoreProvider = SpeculativeKeyStore<'a, P::KeyStoreProvider>;
+    type ClockProvider = P::ClockProvider;
+
+    fn crypto(&self) -> &Self::CryptoProvider {
+        self.provider.crypto()
+    }
+
+    fn rand(&self) -> &Self::RandProvider {
+        self.provider.rand()
+    }
+
+    fn key_store(&self) -> &Self::KeyStoreProvider {
+        &self.key_store
+    }
+
+    fn clock(&self) -> &Self::ClockProvider {
+        self.provider.clock()
+    }
+}
+
+impl MlsGroup {
+    /// Returns an in-memory copy of the group that operations can be
+    /// executed on without changing the group or the key store. See
+    /// [`SpeculativeGroup`].
+    pub fn speculative_clone(&self) -> Result<SpeculativeGroup, LibraryError> {
+        let serialized = serde_json::to_vec(self)
+            .map_err(|_| LibraryError::custom("Error serializing the group state."))?;
+        let group = serde_json::from_slice(&serialized)
+            .map_err(|_| LibraryError::custom("Error restoring the group state."))?;
+        Ok(SpeculativeGroup {
+            group,
+            recorded: RefCell::new(HashMap::new()),
+        })
+    }
+}
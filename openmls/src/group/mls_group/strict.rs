@@ -0,0 +1,92 @@
+//! # Strict API mode
+//!
+//! With the `strict-api` feature, an [`MlsGroup`] detects common misuses of
+//! its API that the protocol doesn't reject by itself, or only rejects with
+//! an error that doesn't point to the cause. A misuse is logged and returned
+//! as an [`ApiMisuseError`], wrapped in an [`MlsGroupStateError`]:
+//!
+//! * [`ApiMisuseError::MessageWithPendingCommit`]: [`MlsGroup::create_message()`]
+//!   is called while a commit is pending. The message is sent in the old
+//!   epoch, which the other members may not keep once the commit is merged.
+//! * [`ApiMisuseError::OwnMessage`]: [`MlsGroup::process_message()`] is called
+//!   with a message that the group sent itself, e.g., because the delivery
+//!   service echoes messages back to the sender. Own commits have to be
+//!   merged with [`MlsGroup::merge_pending_commit()`] instead.
+//! * [`ApiMisuseError::StaleStagedCommit`]: [`MlsGroup::merge_staged_commit()`]
+//!   is called with a [`StagedCommit`] that wasn't returned by
+//!   [`MlsGroup::process_message()`] in the current epoch of the group, e.g.,
+//!   a staged commit of another group or one that was kept across an epoch
+//!   change.
+//!
+//! The checks are meant for development, e.g., integration tests of an
+//! application. They change the behavior of the API and should not be
+//! enabled in production.
+
+use openmls_traits::crypto::OpenMlsCrypto;
+
+use super::*;
+
+impl MlsGroup {
+    /// Returns an error if an application message is created while a
+    /// commit is pending.
+    pub(super) fn detect_message_with_pending_commit(&self) -> Result<(), MlsGroupStateError> {
+        if self.pending_commit().is_some() {
+            return Err(self.report(ApiMisuseError::MessageWithPendingCommit));
+        }
+        Ok(())
+    }
+
+    /// Returns an error if `message` was sent by the own leaf. The sender of
+    /// a [`PrivateMessageIn`] is only known after decrypting the sender data.
+    /// If that fails, the message is left to the regular processing, which
+    /// reports the error.
+    pub(super) fn detect_own_message(
+        &self,
+        crypto: &impl OpenMlsCrypto,
+        message: &ProtocolMessage,
+    ) -> Result<(), MlsGroupStateError> {
+        let own_message = match message {
+            ProtocolMessage::PublicMessage(message) => {
+                message.sender() == &Sender::Member(self.own_leaf_index())
+            }
+            ProtocolMessage::PrivateMessage(message) => self
+                .group
+                .message_secrets_for_epoch(message.epoch())
+                .ok()
+                .and_then(|message_secrets| {
+                    message
+                        .sender_data(message_secrets, crypto, self.ciphersuite())
+                        .ok()
+                })
+                .is_some_and(|sender_data| sender_data.leaf_index == self.own_leaf_index()),
+        };
+        if own_message {
+            return Err(self.report(ApiMisuseError::OwnMessage));
+        }
+        Ok(())
+    }
+
+    /// Returns an error if `staged_commit` doesn't lead from the current
+    /// epoch of the group to the next one.
+    pub(super) fn detect_stale_staged_commit(
+        &self,
+        staged_commit: &StagedCommit,
+    ) -> Result<(), MlsGroupStateError> {
+        let group_context = staged_commit.group_context();
+        if group_context.group_id() != self.group_id()
+            || group_context.epoch().as_u64() != self.epoch().as_u64() + 1
+        {
+            return Err(self.report(ApiMisuseError::StaleStagedCommit));
+        }
+        Ok(())
+    }
+
+    fn report(&self, misuse: ApiMisuseError) -> MlsGroupStateError {
+        log::error!(
+            "API misuse in group {:x?} in epoch {}: {misuse}",
+            self.group_id().as_slice(),
+            self.epoch().as_u64()
+        );
+        misuse.into()
+    }
+}
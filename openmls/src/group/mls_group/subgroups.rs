@@ -0,0 +1,222 @@
+//! # Subgroups
+//!
+//! Applications often need a smaller group of some of the members of a group,
+//! e.g., for a thread or a breakout room. Such a subgroup is a separate MLS
+//! group that is linked to its parent group as described in the "Subgroup
+//! Branching" section of the MLS specification:
+//!
+//! * The group context of the subgroup contains a [`ParentGroupExtension`]
+//!   with the ID of the parent group and the epoch of the parent group in
+//!   which the subgroup was branched.
+//! * The commit that adds the members to the subgroup contains a
+//!   PreSharedKey proposal for the resumption PSK of this epoch of the parent
+//!   group with usage `branch`, which is injected into the key schedule of the
+//!   subgroup.
+//!
+//! [`MlsGroup::create_subgroup()`] creates a subgroup with members of the
+//! parent group and returns the Welcome message for them. The members join
+//! with [`MlsGroup::join_subgroup()`] on their instance of the parent group.
+//! When a member joins a group with a [`ParentGroupExtension`], it checks
+//! that the resumption PSK of the parent group was used. Since only members
+//! of the parent group know the resumption PSK, the link can't be forged by
+//! outsiders. The resumption PSK of the parent epoch must still be available
+//! to the joining members, either because the parent group is still in this
+//! epoch or because it is kept in the resumption PSK store (see
+//! [`MlsGroupConfigBuilder::number_of_resumption_psks()`](super::config::MlsGroupConfigBuilder::number_of_resumption_psks())).
+//!
+//! Subgroups are independent groups after they were created. Members can be
+//! added to or removed from the parent group and the subgroup independently.
+//! [`MlsGroup::parent_group()`] and [`MlsGroup::subgroups()`] help with
+//! keeping track of the subgroups of a group.
+
+use openmls_traits::signatures::Signer;
+
+use super::{errors::SubgroupError, *};
+use crate::{
+    credentials::CredentialWithKey,
+    extensions::ParentGroupExtension,
+    messages::group_info::GroupInfo,
+    schedule::psk::{PreSharedKeyId, Psk, PskBundle, ResumptionPsk, ResumptionPskUsage},
+    treesync::RatchetTreeIn,
+};
+
+impl MlsGroup {
+    /// Returns the [`ParentGroupExtension`] of the group if the group is a
+    /// subgroup of another group.
+    pub fn parent_group(&self) -> Option<ParentGroupExtension> {
+        self.group
+            .context()
+            .extensions()
+            .custom::<ParentGroupExtension>()
+            .ok()
+            .flatten()
+    }
+
+    /// Returns the subgroups of this group among the given `groups`.
+    pub fn subgroups<'a>(
+        &self,
+        groups: impl IntoIterator<Item = &'a MlsGroup>,
+    ) -> Vec<&'a MlsGroup> {
+        groups
+            .into_iter()
+            .filter(|group| {
+                group
+                    .parent_group()
+                    .map(|parent_group| parent_group.group_id() == self.group_id())
+                    .unwrap_or(false)
+            })
+            .collect()
+    }
+
+    /// Create a subgroup of this group with the members of the given
+    /// `key_packages`. See the [module documentation](self) for details.
+    ///
+    /// The key packages must belong to members of this group, i.e., their
+    /// credentials must be credentials of members of this group, and the
+    /// subgroup must have the same ciphersuite as this group.
+    ///
+    /// If successful, it returns the subgroup, a Welcome message for the new
+    /// members and an optional [`GroupInfo`] that will be [Some] if the
+    /// subgroup has the `use_ratchet_tree_extension` flag set. The commit that
+    /// adds the members is merged immediately, since nobody but the creator is
+    /// a member of the subgroup before.
+    #[allow(clippy::type_complexity)]
+    pub fn create_subgroup<KeyStore: OpenMlsKeyStore>(
+        &self,
+        provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
+        signer: &impl Signer,
+        mls_group_config: &MlsGroupConfig,
+        group_id: GroupId,
+        credential_with_key: CredentialWithKey,
+        key_packages: &[KeyPackage],
+    ) -> Result<(MlsGroup, MlsMessageOut, Option<GroupInfo>), SubgroupError<KeyStore::Error>> {
+        self.is_operational()?;
+        if mls_group_config.crypto_config.ciphersuite != self.ciphersuite() {
+            return Err(SubgroupError::CiphersuiteMismatch);
+        }
+        for key_package in key_packages {
+            let credential = key_package.leaf_node().credential();
+            if !self
+                .members()
+                .any(|member| &member.credential == credential)
+            {
+                return Err(SubgroupError::NotAParentMember);
+            }
+        }
+
+        let psk_id = PreSharedKeyId::new(
+            self.ciphersuite(),
+            provider.rand(),
+            Psk::Resumption(ResumptionPsk::new(
+                ResumptionPskUsage::Branch,
+                self.group_id().clone(),
+                self.epoch(),
+            )),
+        )
+        .map_err(LibraryError::unexpected_crypto_error)?;
+        psk_id.write_to_key_store(
+            provider,
+            self.ciphersuite(),
+            self.resumption_psk_secret().as_slice(),
+        )?;
+
+        let result = Self::branch(
+            provider,
+            signer,
+            mls_group_config,
+            group_id,
+            credential_with_key,
+            key_packages,
+            ParentGroupExtension::new(self.group_id().clone(), self.epoch()),
+            psk_id.clone(),
+        );
+
+        delete_psk(provider, &psk_id)?;
+        result
+    }
+
+    /// Join a subgroup of this group from a [`Welcome`] message. See the
+    /// [module documentation](self) for details.
+    ///
+    /// Returns an error if the group of the Welcome message is not a subgroup
+    /// of this group.
+    pub fn join_subgroup<KeyStore: OpenMlsKeyStore>(
+        &self,
+        provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
+        mls_group_config: &MlsGroupConfig,
+        welcome: Welcome,
+        ratchet_tree: Option<RatchetTreeIn>,
+    ) -> Result<MlsGroup, SubgroupError<KeyStore::Error>> {
+        // The epoch in which the subgroup was branched is encrypted in the
+        // Welcome message, so all available resumption PSKs are provided.
+        let mut psk_ids = vec![];
+        let resumption_psks = std::iter::once((self.epoch(), self.resumption_psk_secret())).chain(
+            self.group
+                .resumption_psk_store
+                .iter()
+                .filter(|(epoch, _)| *epoch != self.epoch()),
+        );
+        for (epoch, resumption_psk) in resumption_psks {
+            let psk_id = PreSharedKeyId::resumption(
+                ResumptionPskUsage::Branch,
+                self.group_id().clone(),
+                epoch,
+                vec![],
+            );
+            psk_id.write_to_key_store(provider, self.ciphersuite(), resumption_psk.as_slice())?;
+            psk_ids.push(psk_id);
+        }
+
+        let result = MlsGroup::new_from_welcome(provider, mls_group_config, welcome, ratchet_tree);
+
+        // Delete all PSKs, even if deleting one of them fails, and return the
+        // first error afterwards.
+        psk_ids
+            .iter()
+            .map(|psk_id| delete_psk(provider, psk_id))
+            .fold(Ok(()), Result::and)?;
+        let subgroup = result?;
+        match subgroup.parent_group() {
+            Some(parent_group) if parent_group.group_id() == self.group_id() => Ok(subgroup),
+            _ => Err(SubgroupError::NotASubgroup),
+        }
+    }
+
+    /// Create the subgroup and add the members with the branch PSK.
+    #[allow(clippy::too_many_arguments, clippy::type_complexity)]
+    fn branch<KeyStore: OpenMlsKeyStore>(
+        provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
+        signer: &impl Signer,
+        mls_group_config: &MlsGroupConfig,
+        group_id: GroupId,
+        credential_with_key: CredentialWithKey,
+        key_packages: &[KeyPackage],
+        parent_group: ParentGroupExtension,
+        psk_id: PreSharedKeyId,
+    ) -> Result<(MlsGroup, MlsMessageOut, Option<GroupInfo>), SubgroupError<KeyStore::Error>> {
+        let mut subgroup = MlsGroup::new_with_parent_group(
+            provider,
+            signer,
+            mls_group_config,
+            group_id,
+            credential_with_key,
+            Some(parent_group),
+        )?;
+        let (_commit, welcome, group_info) = subgroup
+            .add_members_with_psks(provider, signer, key_packages, vec![psk_id])?
+            .into_parts();
+        subgroup.merge_pending_commit(provider)?;
+        Ok((subgroup, welcome, group_info))
+    }
+}
+
+/// Delete the PSK with the given ID from the key store.
+fn delete_psk<KeyStore: OpenMlsKeyStore>(
+    provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
+    psk_id: &PreSharedKeyId,
+) -> Result<(), SubgroupError<KeyStore::Error>> {
+    provider
+        .key_store()
+        .delete::<PskBundle>(&psk_id.keystore_id()?)
+        .map_err(SubgroupError::KeyStoreError)
+}
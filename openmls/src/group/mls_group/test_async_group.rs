@@ -0,0 +1,567 @@
+use std::{
+    collections::HashMap,
+    convert::Infallible,
+    future::Future,
+    pin::{pin, Pin},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, RwLock,
+    },
+    task::{Context, Poll, Wake},
+};
+
+use async_trait::async_trait;
+use openmls_rust_crypto::{OpenMlsRustCrypto, RustCrypto};
+use openmls_traits::{
+    key_store::{unwrap_blob, BlobHeader, MlsEntity, OpenMlsKeyStore},
+    types::Ciphersuite,
+    OpenMlsProvider,
+};
+use rstest::*;
+use rstest_reuse::{self, *};
+
+use super::*;
+use crate::{
+    group::{config::CryptoConfig, test_core_group::setup_client},
+    schedule::{ExternalPsk, PreSharedKeyId, Psk},
+    test_utils::*,
+};
+
+struct NoopWaker;
+
+impl Wake for NoopWaker {
+    fn wake(self: Arc<Self>) {}
+}
+
+/// Poll `future` once.
+fn poll_once<F: Future>(future: Pin<&mut F>) -> Poll<F::Output> {
+    let waker = Arc::new(NoopWaker).into();
+    let mut context = Context::from_waker(&waker);
+    future.poll(&mut context)
+}
+
+/// Poll `future` to completion. The futures in this test never wait for
+/// anything, so there is no need for a real executor.
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    loop {
+        if let Poll::Ready(output) = poll_once(future.as_mut()) {
+            return output;
+        }
+    }
+}
+
+/// A future that is pending once, like a key store that waits for I/O.
+struct PendingOnce(bool);
+
+impl Future for PendingOnce {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            return Poll::Ready(());
+        }
+        self.0 = true;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
+
+#[derive(Default)]
+struct AsyncMemoryKeyStore {
+    values: RwLock<HashMap<Vec<u8>, Vec<u8>>>,
+    reads: RwLock<usize>,
+    // If set, reads are pending once before they complete.
+    slow_reads: RwLock<bool>,
+}
+
+#[async_trait]
+impl AsyncOpenMlsKeyStore for AsyncMemoryKeyStore {
+    type Error = Infallible;
+
+    async fn store(&self, k: &[u8], v: &[u8]) -> Result<(), Self::Error> {
+        self.values.write().unwrap().insert(k.to_vec(), v.to_vec());
+        Ok(())
+    }
+
+    async fn read(&self, k: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+        if *self.slow_reads.read().unwrap() {
+            PendingOnce(false).await;
+        }
+        *self.reads.write().unwrap() += 1;
+        Ok(self.values.read().unwrap().get(k).cloned())
+    }
+
+    async fn delete(&self, k: &[u8]) -> Result<(), Self::Error> {
+        self.values.write().unwrap().remove(k);
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct AsyncProvider {
+    crypto: RustCrypto,
+    key_store: AsyncMemoryKeyStore,
+}
+
+impl AsyncOpenMlsProvider for AsyncProvider {
+    type CryptoProvider = RustCrypto;
+    type RandProvider = RustCrypto;
+    type KeyStoreProvider = AsyncMemoryKeyStore;
+    type ClockProvider = RustCrypto;
+
+    fn crypto(&self) -> &Self::CryptoProvider {
+        &self.crypto
+    }
+
+    fn rand(&self) -> &Self::RandProvider {
+        &self.crypto
+    }
+
+    fn key_store(&self) -> &Self::KeyStoreProvider {
+        &self.key_store
+    }
+
+    fn clock(&self) -> &Self::ClockProvider {
+        &self.crypto
+    }
+}
+
+/// A synchronous view of an [`AsyncProvider`], to create key packages.
+struct SyncProvider<'a>(&'a AsyncProvider);
+
+impl OpenMlsKeyStore for AsyncMemoryKeyStore {
+    type Error = Infallible;
+
+    fn store<V: MlsEntity>(&self, k: &[u8], v: &V) -> Result<(), Self::Error> {
+        let value = BlobHeader::for_entity::<V>().wrap(&serde_json::to_vec(v).unwrap());
+        self.values.write().unwrap().insert(k.to_vec(), value);
+        Ok(())
+    }
+
+    fn read<V: MlsEntity>(&self, k: &[u8]) -> Option<V> {
+        let values = self.values.read().unwrap();
+        serde_json::from_slice(unwrap_blob::<V>(values.get(k)?)?).ok()
+    }
+
+    fn delete<V: MlsEntity>(&self, k: &[u8]) -> Result<(), Self::Error> {
+        self.values.write().unwrap().remove(k);
+        Ok(())
+    }
+}
+
+impl OpenMlsProvider for SyncProvider<'_> {
+    type CryptoProvider = RustCrypto;
+    type RandProvider = RustCrypto;
+    type KeyStoreProvider = AsyncMemoryKeyStore;
+    type ClockProvider = RustCrypto;
+
+    fn crypto(&self) -> &Self::CryptoProvider {
+        &self.0.crypto
+    }
+
+    fn rand(&self) -> &Self::RandProvider {
+        &self.0.crypto
+    }
+
+    fn key_store(&self) -> &Self::KeyStoreProvider {
+        &self.0.key_store
+    }
+
+    fn clock(&self) -> &Self::ClockProvider {
+        &self.0.crypto
+    }
+}
+
+#[apply(ciphersuites_and_providers)]
+fn async_group(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let alice_provider = AsyncProvider::default();
+
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential, bob_kpb, bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+
+    let mls_group_config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .use_ratchet_tree_extension(true)
+        .build();
+
+    // === Alice creates a group and adds Bob ===
+    let mut alice_group = block_on(AsyncMlsGroup::new_with_group_id(
+        &alice_provider,
+        &alice_signer,
+        &mls_group_config,
+        GroupId::from_slice(b"Test Group"),
+        alice_credential_with_key,
+    ))
+    .expect("error creating group");
+
+    let (_commit, welcome, _group_info) = block_on(alice_group.add_members(
+        &alice_provider,
+        &alice_signer,
+        &[bob_kpb.key_package().clone()],
+    ))
+    .expect("error adding Bob")
+    .into_parts();
+    block_on(alice_group.merge_pending_commit(&alice_provider)).expect("error merging commit");
+    assert_eq!(alice_group.epoch().as_u64(), 1);
+
+    let welcome = welcome.into_welcome().expect("expected a welcome");
+    let mut bob_group = MlsGroup::new_from_welcome(provider, &mls_group_config, welcome, None)
+        .expect("error joining group");
+
+    // === Alice saves and loads the group ===
+    block_on(alice_group.save(&alice_provider)).expect("error saving group");
+    let mut alice_group = block_on(AsyncMlsGroup::load(
+        &alice_provider,
+        &GroupId::from_slice(b"Test Group"),
+    ))
+    .expect("error loading group")
+    .expect("group not found");
+    assert!(block_on(AsyncMlsGroup::load(
+        &alice_provider,
+        &GroupId::from_slice(b"Unknown Group"),
+    ))
+    .expect("error loading group")
+    .is_none());
+
+    // === Application messages ===
+    let message = block_on(alice_group.create_message(&alice_provider, &alice_signer, b"Hi"))
+        .expect("error creating message");
+    let processed_message = bob_group
+        .process_message(
+            provider,
+            message
+                .into_protocol_message()
+                .expect("expected a protocol message"),
+        )
+        .expect("error processing message");
+    match processed_message.into_content() {
+        ProcessedMessageContent::ApplicationMessage(message) => {
+            assert_eq!(message.into_bytes(), b"Hi")
+        }
+        _ => panic!("Expected an application message."),
+    }
+
+    // === Bob updates ===
+    // Processing the commit requires Alice's encryption keys of the current
+    // epoch, which have to be fetched from the key store first.
+    let (commit, _welcome, _group_info) = bob_group
+        .self_update(provider, &bob_signer)
+        .expect("error updating")
+        .into_parts();
+    bob_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+
+    let reads_before = *alice_provider.key_store.reads.read().unwrap();
+    let processed_message = block_on(
+        alice_group.process_message(
+            &alice_provider,
+            commit
+                .into_protocol_message()
+                .expect("expected a protocol message"),
+        ),
+    )
+    .expect("error processing commit");
+    assert!(*alice_provider.key_store.reads.read().unwrap() > reads_before);
+    match processed_message.into_content() {
+        ProcessedMessageContent::StagedCommitMessage(staged_commit) => {
+            block_on(alice_group.merge_staged_commit(&alice_provider, *staged_commit))
+                .expect("error merging commit")
+        }
+        _ => panic!("Expected a staged commit."),
+    }
+    assert_eq!(alice_group.epoch(), bob_group.epoch());
+    assert_eq!(
+        alice_group
+            .export_secret(alice_provider.crypto(), "label", b"context", 32)
+            .expect("error exporting secret"),
+        bob_group
+            .export_secret(provider.crypto(), "label", b"context", 32)
+            .expect("error exporting secret")
+    );
+
+    // === Alice commits in steps ===
+    // The future yields after each step. Dropping it leaves the group
+    // unchanged.
+    let epoch = alice_group.epoch();
+    {
+        let future = pin!(alice_group.commit_in_steps(&alice_provider, &alice_signer));
+        assert!(poll_once(future).is_pending());
+    }
+    assert_eq!(alice_group.epoch(), epoch);
+    assert!(alice_group.pending_commit().is_none());
+
+    let (commit, _welcome, _group_info) =
+        block_on(alice_group.commit_in_steps(&alice_provider, &alice_signer))
+            .expect("error committing")
+            .into_parts();
+    block_on(alice_group.merge_pending_commit(&alice_provider)).expect("error merging commit");
+    let processed_message = bob_group
+        .process_message(
+            provider,
+            commit
+                .into_protocol_message()
+                .expect("expected a protocol message"),
+        )
+        .expect("error processing commit");
+    match processed_message.into_content() {
+        ProcessedMessageContent::StagedCommitMessage(staged_commit) => bob_group
+            .merge_staged_commit(provider, *staged_commit)
+            .expect("error merging commit"),
+        _ => panic!("Expected a staged commit."),
+    }
+    assert_eq!(alice_group.epoch(), bob_group.epoch());
+
+    // === The futures can be spawned on multi-threaded executors ===
+    fn assert_send<T: Send>(_: &T) {}
+    let future = alice_group.self_update(&alice_provider, &alice_signer);
+    assert_send(&future);
+    drop(future);
+    let future = alice_group.commit_in_steps(&alice_provider, &alice_signer);
+    assert_send(&future);
+    drop(future);
+
+    // === Errors of the operation are passed through ===
+    assert!(matches!(
+        block_on(alice_group.remove_members(&alice_provider, &alice_signer, &[])),
+        Err(AsyncGroupError::Operation(RemoveMembersError::EmptyInput(
+            _
+        )))
+    ));
+}
+
+#[apply(ciphersuites_and_providers)]
+fn async_welcome_fetching_tree(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let bob_provider = AsyncProvider::default();
+
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (bob_credential_with_key, _bob_kpb, bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+    let bob_kpb = KeyPackageBundle::new(
+        &SyncProvider(&bob_provider),
+        &bob_signer,
+        ciphersuite,
+        bob_credential_with_key,
+    );
+
+    let mls_group_config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .build();
+
+    // === Alice creates a group without the ratchet tree extension ===
+    let mut alice_group = MlsGroup::new(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        alice_credential_with_key,
+    )
+    .expect("error creating group");
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(provider, &alice_signer, &[bob_kpb.key_package().clone()])
+        .expect("error adding Bob")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    let welcome = welcome.into_welcome().expect("expected a welcome");
+    let tree_hash = alice_group.export_group_context().tree_hash().to_vec();
+
+    // === The tree isn't available ===
+    assert!(matches!(
+        block_on(AsyncMlsGroup::new_from_welcome_fetching_tree(
+            &bob_provider,
+            &mls_group_config,
+            welcome.clone(),
+            |_| async { None },
+        )),
+        Err(AsyncGroupError::Operation(WelcomeError::MissingRatchetTree))
+    ));
+
+    // === Bob fetches the tree and joins ===
+    let ratchet_tree = alice_group.export_ratchet_tree();
+    let bob_group = block_on(AsyncMlsGroup::new_from_welcome_fetching_tree(
+        &bob_provider,
+        &mls_group_config,
+        welcome,
+        |expected_tree_hash| async move {
+            assert_eq!(expected_tree_hash, tree_hash);
+            Some(ratchet_tree.into())
+        },
+    ))
+    .expect("error joining group");
+    assert_eq!(bob_group.epoch(), alice_group.epoch());
+}
+
+#[apply(ciphersuites_and_providers)]
+fn async_welcome_batch(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let bob_provider = AsyncProvider::default();
+
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (bob_credential_with_key, _bob_kpb, bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+
+    let mls_group_config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .use_ratchet_tree_extension(true)
+        .build();
+
+    // === Alice invites Bob to two groups ===
+    let mut alice_groups = Vec::new();
+    let mut welcomes = Vec::new();
+    for _ in 0..2 {
+        let bob_kpb = KeyPackageBundle::new(
+            &SyncProvider(&bob_provider),
+            &bob_signer,
+            ciphersuite,
+            bob_credential_with_key.clone(),
+        );
+        let mut alice_group = MlsGroup::new(
+            provider,
+            &alice_signer,
+            &mls_group_config,
+            alice_credential_with_key.clone(),
+        )
+        .expect("error creating group");
+        let (_commit, welcome, _group_info) = alice_group
+            .add_members(provider, &alice_signer, &[bob_kpb.key_package().clone()])
+            .expect("error adding Bob")
+            .into_parts();
+        alice_group
+            .merge_pending_commit(provider)
+            .expect("error merging commit");
+        welcomes.push((welcome.into_welcome().expect("expected a welcome"), None));
+        alice_groups.push(alice_group);
+    }
+
+    // === Bob joins both groups at once ===
+    let results = block_on(AsyncMlsGroup::new_from_welcomes(
+        &bob_provider,
+        &mls_group_config,
+        welcomes,
+    ))
+    .expect("error accessing the key store");
+    assert_eq!(results.len(), 2);
+    for (result, alice_group) in results.into_iter().zip(&alice_groups) {
+        let bob_group = result.expect("error joining group");
+        assert_eq!(bob_group.group_id(), alice_group.group_id());
+        assert_eq!(bob_group.epoch(), alice_group.epoch());
+    }
+}
+
+#[apply(ciphersuites_and_providers)]
+fn async_rollback_keeps_subscribers(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let alice_provider = AsyncProvider::default();
+
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential, bob_kpb, bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+
+    let mls_group_config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .use_ratchet_tree_extension(true)
+        .build();
+
+    let mut alice_group = block_on(AsyncMlsGroup::new(
+        &alice_provider,
+        &alice_signer,
+        &mls_group_config,
+        alice_credential_with_key,
+    ))
+    .expect("error creating group");
+    let (_commit, welcome, _group_info) = block_on(alice_group.add_members(
+        &alice_provider,
+        &alice_signer,
+        &[bob_kpb.key_package().clone()],
+    ))
+    .expect("error adding Bob")
+    .into_parts();
+    block_on(alice_group.merge_pending_commit(&alice_provider)).expect("error merging commit");
+    let welcome = welcome.into_welcome().expect("expected a welcome");
+    let mut bob_group = MlsGroup::new_from_welcome(provider, &mls_group_config, welcome, None)
+        .expect("error joining group");
+
+    let transitions = Arc::new(AtomicUsize::new(0));
+    let counter = transitions.clone();
+    alice_group.subscribe_epoch_transitions(move |_| {
+        counter.fetch_add(1, Ordering::SeqCst);
+    });
+    assert_eq!(alice_group.state_changed(), InnerState::Changed);
+
+    // === Bob commits to an external PSK ===
+    // Alice only fetches the PSK from the key store after the first attempt
+    // to process the commit, which is then rolled back.
+    let psk_id = PreSharedKeyId::new(
+        ciphersuite,
+        provider.rand(),
+        Psk::External(ExternalPsk::new(b"psk".to_vec())),
+    )
+    .expect("error creating PSK ID");
+    psk_id
+        .write_to_key_store(provider, ciphersuite, b"secret")
+        .expect("error storing PSK");
+    psk_id
+        .write_to_key_store(&SyncProvider(&alice_provider), ciphersuite, b"secret")
+        .expect("error storing PSK");
+    let (proposal, _) = bob_group
+        .propose_external_psk(provider, &bob_signer, psk_id)
+        .expect("error proposing PSK");
+    let (commit, _welcome, _group_info) = bob_group
+        .commit_to_pending_proposals(provider, &bob_signer)
+        .expect("error committing")
+        .into_parts();
+    bob_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+
+    let processed_message = block_on(
+        alice_group.process_message(
+            &alice_provider,
+            proposal
+                .into_protocol_message()
+                .expect("expected a protocol message"),
+        ),
+    )
+    .expect("error processing proposal");
+    match processed_message.into_content() {
+        ProcessedMessageContent::ProposalMessage(proposal) => {
+            alice_group.store_pending_proposal(*proposal)
+        }
+        _ => panic!("Expected a proposal."),
+    }
+    let commit = commit
+        .into_protocol_message()
+        .expect("expected a protocol message");
+
+    // === The operation is cancelled while it waits for the key store ===
+    *alice_provider.key_store.slow_reads.write().unwrap() = true;
+    {
+        let future = pin!(alice_group.process_message(&alice_provider, commit.clone()));
+        assert!(poll_once(future).is_pending());
+    }
+    *alice_provider.key_store.slow_reads.write().unwrap() = false;
+    assert_eq!(alice_group.state_changed(), InnerState::Changed);
+
+    // === The operation is rolled back and executed again ===
+    let reads_before = *alice_provider.key_store.reads.read().unwrap();
+    let processed_message = block_on(alice_group.process_message(&alice_provider, commit))
+        .expect("error processing commit");
+    // The epoch keypairs are prefetched, the PSK is fetched after the
+    // rollback.
+    assert!(*alice_provider.key_store.reads.read().unwrap() >= reads_before + 2);
+    assert_eq!(alice_group.state_changed(), InnerState::Changed);
+    match processed_message.into_content() {
+        ProcessedMessageContent::StagedCommitMessage(staged_commit) => {
+            block_on(alice_group.merge_staged_commit(&alice_provider, *staged_commit))
+                .expect("error merging commit")
+        }
+        _ => panic!("Expected a staged commit."),
+    }
+    assert_eq!(alice_group.epoch(), bob_group.epoch());
+    assert_eq!(transitions.load(Ordering::SeqCst), 1);
+}
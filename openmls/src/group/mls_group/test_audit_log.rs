@@ -0,0 +1,191 @@
+use openmls_traits::{types::Ciphersuite, OpenMlsProvider};
+use rstest::*;
+use rstest_reuse::{self, *};
+use tls_codec::{Deserialize as TlsDeserializeTrait, Serialize as TlsSerializeTrait};
+
+use super::*;
+use crate::{
+    group::{config::CryptoConfig, errors::AuditLogError, test_core_group::setup_client},
+    test_utils::*,
+};
+
+#[apply(ciphersuites_and_providers)]
+fn audit_log(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential, bob_kpb, bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+    let (_charlie_credential, charlie_kpb, _charlie_signer, _charlie_pk) =
+        setup_client("Charlie", ciphersuite, provider);
+
+    let mls_group_config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .use_ratchet_tree_extension(true)
+        .audit_log(true)
+        .build();
+
+    // === Alice creates a group with Bob and Charlie ===
+    let mut alice_group = MlsGroup::new_with_group_id(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        GroupId::from_slice(b"Test Group"),
+        alice_credential_with_key,
+    )
+    .expect("error creating group");
+    assert!(alice_group.audit_log().is_none());
+
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(
+            provider,
+            &alice_signer,
+            &[
+                bob_kpb.key_package().clone(),
+                charlie_kpb.key_package().clone(),
+            ],
+        )
+        .expect("error adding Bob and Charlie")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    let welcome = welcome.into_welcome().expect("expected a welcome");
+    let mut bob_group = MlsGroup::new_from_welcome(provider, &mls_group_config, welcome, None)
+        .expect("error joining group");
+    assert!(bob_group.audit_log().is_none());
+
+    let audit_log = alice_group.audit_log().expect("no audit log");
+    assert_eq!(audit_log.group_id(), alice_group.group_id());
+    assert_eq!(audit_log.entries().len(), 1);
+    let entry = &audit_log.entries()[0];
+    assert_eq!(entry.epoch(), GroupEpoch::from(1));
+    assert_eq!(entry.committer().leaf_index(), LeafNodeIndex::new(0));
+    assert_eq!(entry.committer().credential().identity(), b"Alice");
+    let added: Vec<(LeafNodeIndex, &[u8])> = entry
+        .added()
+        .iter()
+        .map(|member| (member.leaf_index(), member.credential().identity()))
+        .collect();
+    assert_eq!(
+        added,
+        vec![
+            (LeafNodeIndex::new(1), b"Bob".as_slice()),
+            (LeafNodeIndex::new(2), b"Charlie".as_slice())
+        ]
+    );
+    assert!(entry.removed().is_empty());
+    assert!(entry.previous_hash().is_empty());
+    assert_eq!(audit_log.head(), entry.hash());
+
+    // === Bob removes Charlie ===
+    let (commit, _welcome, _group_info) = bob_group
+        .remove_members(provider, &bob_signer, &[LeafNodeIndex::new(2)])
+        .expect("error removing Charlie")
+        .into_parts();
+    bob_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    let processed_message = alice_group
+        .process_message(
+            provider,
+            commit
+                .into_protocol_message()
+                .expect("expected a protocol message"),
+        )
+        .expect("error processing commit");
+    match processed_message.into_content() {
+        ProcessedMessageContent::StagedCommitMessage(staged_commit) => alice_group
+            .merge_staged_commit(provider, *staged_commit)
+            .expect("error merging commit"),
+        _ => panic!("Expected a staged commit."),
+    }
+
+    let alice_log = alice_group.audit_log().expect("no audit log");
+    let bob_log = bob_group.audit_log().expect("no audit log");
+    assert_eq!(alice_log.entries().len(), 2);
+    assert_eq!(bob_log.entries().len(), 1);
+    let entry = &alice_log.entries()[1];
+    assert_eq!(entry.previous_hash(), alice_log.entries()[0].hash());
+    assert_eq!(entry.epoch(), GroupEpoch::from(2));
+    assert_eq!(entry.committer().credential().identity(), b"Bob");
+    assert!(entry.added().is_empty());
+    assert_eq!(entry.removed().len(), 1);
+    assert_eq!(entry.removed()[0].leaf_index(), LeafNodeIndex::new(2));
+    assert_eq!(entry.removed()[0].credential().identity(), b"Charlie");
+
+    // Both members agree on the contents of the entry.
+    let bob_entry = &bob_log.entries()[0];
+    assert_eq!(bob_entry.confirmation_tag(), entry.confirmation_tag());
+    assert_eq!(bob_entry.committer(), entry.committer());
+    assert_eq!(bob_entry.removed(), entry.removed());
+
+    alice_log.verify(provider.crypto()).expect("invalid log");
+    bob_log.verify(provider.crypto()).expect("invalid log");
+
+    // === The log can be exported and verified ===
+    let head = alice_log.head().to_vec();
+    let exported = alice_log
+        .tls_serialize_detached()
+        .expect("error serializing log");
+    let imported =
+        AuditLog::tls_deserialize_exact(exported.as_slice()).expect("error deserializing log");
+    assert_eq!(&imported, alice_log);
+    imported
+        .verify_head(provider.crypto(), &head)
+        .expect("invalid log");
+
+    // === Tampering is detected ===
+    let mut tampered = imported.clone();
+    tampered.entries[0].removed = tampered.entries[1].removed.clone();
+    assert_eq!(
+        tampered.verify(provider.crypto()),
+        Err(AuditLogError::InvalidHash)
+    );
+
+    let mut tampered = imported.clone();
+    tampered.entries.remove(0);
+    assert_eq!(
+        tampered.verify(provider.crypto()),
+        Err(AuditLogError::BrokenChain)
+    );
+
+    let mut tampered = imported.clone();
+    tampered.group_id = GroupId::from_slice(b"Other Group");
+    assert_eq!(
+        tampered.verify(provider.crypto()),
+        Err(AuditLogError::InvalidHash)
+    );
+
+    let mut truncated = imported;
+    truncated.entries.pop();
+    truncated.verify(provider.crypto()).expect("invalid log");
+    assert_eq!(
+        truncated.verify_head(provider.crypto(), &head),
+        Err(AuditLogError::HeadMismatch)
+    );
+
+    // === Commits without membership changes are recorded as well ===
+    alice_group
+        .self_update(provider, &alice_signer)
+        .expect("error updating");
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    let alice_log = alice_group.audit_log().expect("no audit log");
+    assert_eq!(alice_log.entries().len(), 3);
+    let entry = &alice_log.entries()[2];
+    assert_eq!(entry.epoch(), GroupEpoch::from(3));
+    assert!(entry.added().is_empty());
+    assert!(entry.removed().is_empty());
+    assert_eq!(
+        entry.confirmation_tag(),
+        alice_group.group.public_group().confirmation_tag()
+    );
+    alice_log.verify(provider.crypto()).expect("invalid log");
+
+    // The log is persisted with the group.
+    let serialized = serde_json::to_vec(&alice_group).expect("error serializing group");
+    let restored: MlsGroup =
+        serde_json::from_slice(&serialized).expect("error deserializing group");
+    assert_eq!(restored.audit_log(), alice_group.audit_log());
+}
@@ -0,0 +1,103 @@
+use openmls_traits::{types::Ciphersuite, OpenMlsProvider};
+use rstest::*;
+use rstest_reuse::{self, *};
+
+use super::*;
+use crate::{
+    extensions::{Extension, ExtensionType, Extensions, UnknownExtension},
+    group::{
+        config::CryptoConfig,
+        errors::{NewGroupError, WelcomeError},
+        test_core_group::setup_client,
+    },
+    test_utils::*,
+};
+
+#[apply(ciphersuites_and_providers)]
+fn group_builder(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential, bob_kpb, _bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+    let (_charlie_credential, charlie_kpb, _charlie_signer, _charlie_pk) =
+        setup_client("Charlie", ciphersuite, provider);
+
+    let mls_group_config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .use_ratchet_tree_extension(true)
+        .build();
+
+    // === The group context can be extended ===
+    let group_id = GroupId::from_slice(b"Test Group");
+    let extension = Extension::Unknown(0xff00, UnknownExtension(vec![1, 2, 3]));
+    let alice_group = MlsGroup::builder()
+        .with_group_id(group_id.clone())
+        .with_config(&mls_group_config)
+        .with_group_context_extensions(Extensions::single(extension))
+        .build(provider, &alice_signer, alice_credential_with_key.clone())
+        .expect("error creating group");
+    assert_eq!(alice_group.group_id(), &group_id);
+    let extensions = alice_group.export_group_context().extensions();
+    assert!(extensions.contains(ExtensionType::Unknown(0xff00)));
+    assert!(extensions.contains(ExtensionType::RequiredCapabilities));
+
+    // Extensions of the configuration can't be set twice.
+    let extension = Extension::RequiredCapabilities(Default::default());
+    assert!(matches!(
+        MlsGroup::builder()
+            .with_config(&mls_group_config)
+            .with_group_context_extensions(Extensions::single(extension))
+            .build(provider, &alice_signer, alice_credential_with_key.clone()),
+        Err(NewGroupError::InvalidExtensions(_))
+    ));
+
+    // === Alice creates a group with Bob and Charlie ===
+    let (alice_group, commit_output) = MlsGroup::builder()
+        .with_config(&mls_group_config)
+        .with_initial_members(vec![
+            bob_kpb.key_package().clone(),
+            charlie_kpb.key_package().clone(),
+        ])
+        .build(provider, &alice_signer, alice_credential_with_key)
+        .expect("error creating group");
+    assert!(alice_group.pending_commit().is_none());
+    assert_eq!(alice_group.members().count(), 3);
+    assert_eq!(
+        commit_output.affected_members().added(),
+        &[
+            bob_kpb.key_package().leaf_node().credential().clone(),
+            charlie_kpb.key_package().leaf_node().credential().clone(),
+        ]
+    );
+    assert!(commit_output.affected_members().removed().is_empty());
+    let (_commit, welcome, _group_info) = commit_output.into_parts();
+    let welcome = welcome.into_welcome().expect("expected a welcome");
+
+    // === Bob and Charlie join ===
+    let error = MlsGroup::join_builder(welcome.clone())
+        .with_config(&mls_group_config)
+        .with_expectations(WelcomeExpectations::new().group_id(group_id))
+        .build(provider)
+        .expect_err("joined an unexpected group");
+    assert_eq!(error, WelcomeError::UnexpectedGroupId);
+
+    let bob_group = MlsGroup::join_builder(welcome.clone())
+        .with_config(&mls_group_config)
+        .build(provider)
+        .expect("error joining group");
+    let charlie_group = MlsGroup::join_builder(welcome)
+        .with_config(&mls_group_config)
+        .with_ratchet_tree(alice_group.export_ratchet_tree().into())
+        .with_expectations(WelcomeExpectations::new().max_members(3))
+        .stage(provider)
+        .expect("error staging group")
+        .into_group(provider)
+        .expect("error joining group");
+    for group in [&bob_group, &charlie_group] {
+        assert_eq!(group.epoch(), alice_group.epoch());
+        assert_eq!(
+            group.export_ratchet_tree(),
+            alice_group.export_ratchet_tree()
+        );
+    }
+}
@@ -0,0 +1,152 @@
+use openmls_traits::{types::Ciphersuite, OpenMlsProvider};
+use rstest::*;
+use rstest_reuse::{self, *};
+
+use super::*;
+use crate::{
+    group::{
+        config::{CommitLimits, CryptoConfig},
+        errors::{CommitLimitError, StageCommitError},
+        test_core_group::setup_client,
+    },
+    test_utils::*,
+};
+
+fn merge_commit(group: &mut MlsGroup, provider: &impl OpenMlsProvider, message: ProtocolMessage) {
+    let processed_message = group
+        .process_message(provider, message)
+        .expect("error processing commit");
+    match processed_message.into_content() {
+        ProcessedMessageContent::StagedCommitMessage(staged_commit) => group
+            .merge_staged_commit(provider, *staged_commit)
+            .expect("error merging commit"),
+        _ => panic!("Expected a staged commit."),
+    }
+}
+
+#[apply(ciphersuites_and_providers)]
+fn commit_limits(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential, bob_kpb, _bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+    let (_charlie_credential, charlie_kpb, _charlie_signer, _charlie_pk) =
+        setup_client("Charlie", ciphersuite, provider);
+    let (_dave_credential, dave_kpb, _dave_signer, _dave_pk) =
+        setup_client("Dave", ciphersuite, provider);
+
+    // Plaintext commits can be processed again after they were rejected.
+    let config_with_limits = |commit_limits: CommitLimits| {
+        MlsGroupConfig::builder()
+            .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+            .wire_format_policy(PURE_PLAINTEXT_WIRE_FORMAT_POLICY)
+            .use_ratchet_tree_extension(true)
+            .commit_limits(commit_limits)
+            .build()
+    };
+    let config = config_with_limits(CommitLimits::default());
+    assert_eq!(config.commit_limits(), CommitLimits::default());
+    assert_eq!(CommitLimits::default().max_adds(), None);
+
+    let mut alice_group =
+        MlsGroup::new(provider, &alice_signer, &config, alice_credential_with_key)
+            .expect("error creating group");
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(provider, &alice_signer, &[bob_kpb.key_package().clone()])
+        .expect("error adding Bob")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    let welcome = welcome.into_welcome().expect("expected a welcome");
+    let limits = CommitLimits::default().with_max_adds(1);
+    let mut bob_group =
+        MlsGroup::new_from_welcome(provider, &config_with_limits(limits), welcome, None)
+            .expect("error joining group");
+    assert_eq!(
+        bob_group.group.public_group().commit_limits().max_adds(),
+        Some(1)
+    );
+
+    // === Bob rejects a commit that adds two members ===
+    let (commit, _welcome, _group_info) = alice_group
+        .add_members(
+            provider,
+            &alice_signer,
+            &[
+                charlie_kpb.key_package().clone(),
+                dave_kpb.key_package().clone(),
+            ],
+        )
+        .expect("error adding Charlie and Dave")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    let commit = commit.into_protocol_message().unwrap();
+    assert_eq!(
+        bob_group
+            .process_message(provider, commit.clone())
+            .expect_err("accepted too many adds"),
+        ProcessMessageError::InvalidCommit(StageCommitError::CommitLimitError(
+            CommitLimitError::TooManyAdds
+        ))
+    );
+
+    // === Bob rejects the commit if it covers too many proposals ===
+    bob_group.set_configuration(&config_with_limits(
+        CommitLimits::default().with_max_proposals(1),
+    ));
+    assert_eq!(
+        bob_group
+            .process_message(provider, commit.clone())
+            .expect_err("accepted too many proposals"),
+        ProcessMessageError::InvalidCommit(StageCommitError::CommitLimitError(
+            CommitLimitError::TooManyProposals
+        ))
+    );
+
+    // === Bob accepts the commit within the limits ===
+    bob_group.set_configuration(&config_with_limits(
+        CommitLimits::default()
+            .with_max_adds(2)
+            .with_max_proposals(2),
+    ));
+    merge_commit(&mut bob_group, provider, commit);
+
+    // === Bob rejects an update that is too large or has too long a path ===
+    let (commit, _welcome, _group_info) = alice_group
+        .self_update(provider, &alice_signer)
+        .expect("error updating")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    let commit = commit.into_protocol_message().unwrap();
+    bob_group.set_configuration(&config_with_limits(
+        CommitLimits::default().with_max_size(1),
+    ));
+    assert_eq!(
+        bob_group
+            .process_message(provider, commit.clone())
+            .expect_err("accepted a commit that is too large"),
+        ProcessMessageError::InvalidCommit(StageCommitError::CommitLimitError(
+            CommitLimitError::TooLarge
+        ))
+    );
+    bob_group.set_configuration(&config_with_limits(
+        CommitLimits::default().with_max_path_nodes(0),
+    ));
+    assert_eq!(
+        bob_group
+            .process_message(provider, commit.clone())
+            .expect_err("accepted too many path nodes"),
+        ProcessMessageError::InvalidCommit(StageCommitError::CommitLimitError(
+            CommitLimitError::TooManyPathNodes
+        ))
+    );
+
+    bob_group.set_configuration(&config);
+    merge_commit(&mut bob_group, provider, commit);
+    assert_eq!(bob_group.epoch(), alice_group.epoch());
+}
@@ -0,0 +1,112 @@
+use openmls_traits::{types::Ciphersuite, OpenMlsProvider};
+use rstest::*;
+use rstest_reuse::{self, *};
+
+use super::*;
+use crate::{
+    group::{config::CryptoConfig, test_core_group::setup_client},
+    test_utils::*,
+};
+
+#[apply(ciphersuites_and_providers)]
+fn commit_metadata(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential, bob_kpb, _bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+    let (_charlie_credential, charlie_kpb, _charlie_signer, _charlie_pk) =
+        setup_client("Charlie", ciphersuite, provider);
+
+    let mls_group_config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .use_ratchet_tree_extension(true)
+        .sequencing_tokens(true)
+        .build();
+
+    // === Alice creates a group with Bob and Charlie ===
+    let mut alice_group = MlsGroup::new(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        alice_credential_with_key,
+    )
+    .expect("error creating group");
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(
+            provider,
+            &alice_signer,
+            &[
+                bob_kpb.key_package().clone(),
+                charlie_kpb.key_package().clone(),
+            ],
+        )
+        .expect("error adding Bob and Charlie")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    let welcome = welcome.into_welcome().expect("expected a welcome");
+    let mut bob_group = MlsGroup::new_from_welcome(provider, &mls_group_config, welcome, None)
+        .expect("error joining group");
+
+    // === Alice removes Charlie and tells why ===
+    alice_group.set_aad(b"application AAD");
+    alice_group.set_commit_metadata(b"removed for spam");
+    assert_eq!(
+        alice_group.pending_commit_metadata(),
+        Some(b"removed for spam".as_slice())
+    );
+    let charlie_index = alice_group
+        .members()
+        .find(|member| member.credential.identity() == b"Charlie")
+        .expect("Charlie is not a member")
+        .index;
+    let (commit, _welcome, _group_info) = alice_group
+        .remove_members(provider, &alice_signer, &[charlie_index])
+        .expect("error removing Charlie")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    assert!(alice_group.pending_commit_metadata().is_none());
+
+    let processed_message = bob_group
+        .process_message(
+            provider,
+            commit
+                .into_protocol_message()
+                .expect("expected a protocol message"),
+        )
+        .expect("error processing commit");
+    assert_eq!(
+        bob_group.commit_metadata(&processed_message),
+        Some(b"removed for spam".to_vec())
+    );
+    match processed_message.into_content() {
+        ProcessedMessageContent::StagedCommitMessage(staged_commit) => bob_group
+            .merge_staged_commit(provider, *staged_commit)
+            .expect("error merging commit"),
+        _ => panic!("Expected a staged commit."),
+    }
+
+    // === Commits without metadata carry the AAD of the group ===
+    let (commit, _welcome, _group_info) = alice_group
+        .self_update(provider, &alice_signer)
+        .expect("error updating")
+        .into_parts();
+    let processed_message = bob_group
+        .process_message(
+            provider,
+            commit
+                .into_protocol_message()
+                .expect("expected a protocol message"),
+        )
+        .expect("error processing commit");
+    assert!(bob_group.commit_metadata(&processed_message).is_none());
+    assert_eq!(
+        SequencedAad::decode(processed_message.authenticated_data())
+            .expect("error decoding AAD")
+            .aad(),
+        b"application AAD"
+    );
+}
@@ -0,0 +1,372 @@
+use std::ops::ControlFlow;
+
+use openmls_traits::{types::Ciphersuite, OpenMlsProvider};
+use rstest::*;
+use rstest_reuse::{self, *};
+use tls_codec::{Deserialize, Serialize};
+
+use super::*;
+use crate::{
+    group::{config::CryptoConfig, errors::MlsGroupStateError, test_core_group::setup_client},
+    test_utils::*,
+};
+
+#[apply(ciphersuites_and_providers)]
+fn commit_operation(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential, bob_kpb, _bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+    let (_charlie_credential, charlie_kpb, _charlie_signer, _charlie_pk) =
+        setup_client("Charlie", ciphersuite, provider);
+    let (_dave_credential, dave_kpb, _dave_signer, _dave_pk) =
+        setup_client("Dave", ciphersuite, provider);
+
+    let mls_group_config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .use_ratchet_tree_extension(true)
+        .build();
+
+    // === Alice creates a group with Bob and Charlie ===
+    let mut alice_group = MlsGroup::new_with_group_id(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        GroupId::from_slice(b"Test Group"),
+        alice_credential_with_key,
+    )
+    .expect("error creating group");
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(
+            provider,
+            &alice_signer,
+            &[
+                bob_kpb.key_package().clone(),
+                charlie_kpb.key_package().clone(),
+            ],
+        )
+        .expect("error adding Bob and Charlie")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    let welcome = welcome.into_welcome().expect("expected a welcome");
+    let mut bob_group = MlsGroup::new_from_welcome(provider, &mls_group_config, welcome, None)
+        .expect("error joining group");
+
+    let epoch = alice_group.epoch();
+    let exported_secret = alice_group
+        .export_secret(provider.crypto(), "label", b"context", 32)
+        .expect("error exporting secret");
+
+    // === Cancelling an operation leaves the group unchanged ===
+    let (proposal, _proposal_ref) = alice_group
+        .propose_add_member(provider, &alice_signer, dave_kpb.key_package())
+        .expect("error proposing to add Dave");
+    let processed_message = bob_group
+        .process_message(
+            provider,
+            proposal
+                .into_protocol_message()
+                .expect("expected a protocol message"),
+        )
+        .expect("error processing proposal");
+    match processed_message.into_content() {
+        ProcessedMessageContent::ProposalMessage(proposal) => {
+            bob_group.store_pending_proposal(*proposal)
+        }
+        _ => panic!("Expected a proposal."),
+    }
+
+    let operation = alice_group
+        .start_commit(provider, &alice_signer)
+        .expect("error starting commit");
+    let progress = operation.progress();
+    assert_eq!(progress.completed, 0);
+    assert!(progress.total > 1);
+    assert!(!operation.is_complete());
+
+    let token = CancellationToken::new();
+    token.clone().cancel();
+    assert!(token.is_cancelled());
+    assert_eq!(
+        operation
+            .run(provider.crypto(), &alice_signer, &mut token.clone())
+            .expect_err("the operation was not cancelled"),
+        CommitOperationError::Cancelled
+    );
+
+    // Cancel after the first step.
+    let operation = alice_group
+        .start_commit(provider, &alice_signer)
+        .expect("error starting commit");
+    let mut checkpoints = Vec::new();
+    let mut checkpoint = |progress: Progress| {
+        checkpoints.push(progress.completed);
+        if progress.completed == 1 {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    };
+    assert_eq!(
+        operation
+            .run(provider.crypto(), &alice_signer, &mut checkpoint)
+            .expect_err("the operation was not cancelled"),
+        CommitOperationError::Cancelled
+    );
+    assert_eq!(checkpoints, vec![0, 1]);
+
+    // A finished but unstaged commit doesn't change the group either.
+    let _prepared_commit = alice_group
+        .start_commit(provider, &alice_signer)
+        .expect("error starting commit")
+        .finish(provider.crypto(), &alice_signer)
+        .expect("error finishing commit");
+
+    assert_eq!(alice_group.epoch(), epoch);
+    assert!(alice_group.pending_commit().is_none());
+    assert_eq!(alice_group.pending_proposals().count(), 1);
+    assert_eq!(
+        alice_group
+            .export_secret(provider.crypto(), "label", b"context", 32)
+            .expect("error exporting secret"),
+        exported_secret
+    );
+
+    // === Alice commits in steps ===
+    let mut operation = alice_group
+        .start_commit(provider, &alice_signer)
+        .expect("error starting commit");
+    let total = operation.progress().total;
+    for completed in 1..=total {
+        let progress = operation.step(provider.crypto()).expect("error in step");
+        assert_eq!(progress, Progress { completed, total });
+    }
+    assert!(operation.is_complete());
+    let prepared_commit = operation
+        .finish(provider.crypto(), &alice_signer)
+        .expect("error finishing commit");
+    assert_eq!(prepared_commit.epoch(), epoch);
+
+    let (commit, welcome, group_info) = alice_group
+        .stage_commit(provider, prepared_commit)
+        .expect("error staging commit")
+        .into_parts();
+    assert!(welcome.is_some());
+    assert!(group_info.is_some());
+    assert!(alice_group.pending_commit().is_some());
+
+    // No commit can be started while a commit is pending.
+    assert_eq!(
+        alice_group
+            .start_commit(provider, &alice_signer)
+            .expect_err("started a commit with a pending commit"),
+        CommitToPendingProposalsError::GroupStateError(MlsGroupStateError::PendingCommit)
+    );
+
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+
+    // Bob processes the commit and both agree on the new epoch.
+    let processed_message = bob_group
+        .process_message(
+            provider,
+            commit
+                .into_protocol_message()
+                .expect("expected a protocol message"),
+        )
+        .expect("error processing commit");
+    match processed_message.into_content() {
+        ProcessedMessageContent::StagedCommitMessage(staged_commit) => bob_group
+            .merge_staged_commit(provider, *staged_commit)
+            .expect("error merging commit"),
+        _ => panic!("Expected a staged commit."),
+    }
+    assert_eq!(alice_group.epoch(), bob_group.epoch());
+    assert_eq!(alice_group.members().count(), 4);
+    assert_eq!(
+        alice_group
+            .export_secret(provider.crypto(), "label", b"context", 32)
+            .expect("error exporting secret"),
+        bob_group
+            .export_secret(provider.crypto(), "label", b"context", 32)
+            .expect("error exporting secret")
+    );
+
+    // === Prepared commits are checked before they are staged ===
+    let prepared_commit = alice_group
+        .start_commit(provider, &alice_signer)
+        .expect("error starting commit")
+        .finish(provider.crypto(), &alice_signer)
+        .expect("error finishing commit");
+    alice_group
+        .self_update(provider, &alice_signer)
+        .expect("error updating");
+    assert_eq!(
+        alice_group
+            .stage_commit(provider, prepared_commit)
+            .expect_err("staged a commit with a pending commit"),
+        StagePreparedCommitError::GroupStateError(MlsGroupStateError::PendingCommit)
+    );
+
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+
+    let prepared_commit = alice_group
+        .start_commit(provider, &alice_signer)
+        .expect("error starting commit")
+        .finish(provider.crypto(), &alice_signer)
+        .expect("error finishing commit");
+    alice_group
+        .self_update(provider, &alice_signer)
+        .expect("error updating");
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    assert_eq!(
+        alice_group
+            .stage_commit(provider, prepared_commit)
+            .expect_err("staged a stale commit"),
+        StagePreparedCommitError::StaleCommit
+    );
+}
+
+#[apply(ciphersuites_and_providers)]
+fn resend_pending_commit(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential, bob_kpb, _bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+
+    let mls_group_config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .use_ratchet_tree_extension(true)
+        .build();
+    let mut alice_group = MlsGroup::new(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        alice_credential_with_key,
+    )
+    .expect("error creating group");
+    assert_eq!(
+        alice_group.resend_pending_commit(),
+        Err(MlsGroupStateError::NoPendingCommit)
+    );
+
+    // === Alice resends the commit that adds Bob ===
+    let (commit, welcome, group_info) = alice_group
+        .add_members(provider, &alice_signer, &[bob_kpb.key_package().clone()])
+        .expect("error adding Bob")
+        .into_parts();
+    let messages = alice_group
+        .resend_pending_commit()
+        .expect("no pending commit")
+        .clone();
+    assert_eq!(messages.epoch(), alice_group.epoch());
+    assert_eq!(messages.commit(), commit.tls_serialize_detached().unwrap());
+    assert_eq!(
+        messages.welcome(),
+        Some(welcome.tls_serialize_detached().unwrap().as_slice())
+    );
+    assert_eq!(
+        messages.group_info(),
+        group_info
+            .map(|group_info| group_info.tls_serialize_detached().unwrap())
+            .as_deref()
+    );
+
+    // The messages survive a restart.
+    let serialized = serde_json::to_vec(&alice_group).expect("error serializing group");
+    let mut alice_group: MlsGroup =
+        serde_json::from_slice(&serialized).expect("error deserializing group");
+    assert_eq!(alice_group.resend_pending_commit(), Ok(&messages));
+
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    assert!(alice_group.pending_commit_messages.is_none());
+    assert_eq!(
+        alice_group.resend_pending_commit(),
+        Err(MlsGroupStateError::NoPendingCommit)
+    );
+    let welcome = MlsMessageIn::tls_deserialize_exact(messages.welcome().unwrap())
+        .expect("error decoding welcome")
+        .into_welcome()
+        .expect("expected a welcome");
+    let mut bob_group = MlsGroup::new_from_welcome(provider, &mls_group_config, welcome, None)
+        .expect("error joining group");
+
+    // === Alice resends a commit that was created in steps ===
+    let prepared_commit = alice_group
+        .start_commit(provider, &alice_signer)
+        .expect("error starting commit")
+        .finish(provider.crypto(), &alice_signer)
+        .expect("error finishing commit");
+    assert_eq!(
+        alice_group.resend_pending_commit(),
+        Err(MlsGroupStateError::NoPendingCommit)
+    );
+    let (commit, _welcome, _group_info) = alice_group
+        .stage_commit(provider, prepared_commit)
+        .expect("error staging commit")
+        .into_parts();
+    let resent_commit = alice_group
+        .resend_pending_commit()
+        .expect("no pending commit")
+        .commit()
+        .to_vec();
+    assert_eq!(resent_commit, commit.tls_serialize_detached().unwrap());
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+
+    // Bob processes the commit once and rejects the copy.
+    let resent_commit = || {
+        MlsMessageIn::tls_deserialize_exact(&resent_commit)
+            .expect("error decoding commit")
+            .into_protocol_message()
+            .expect("expected a protocol message")
+    };
+    let processed_message = bob_group
+        .process_message(provider, resent_commit())
+        .expect("error processing commit");
+    match processed_message.into_content() {
+        ProcessedMessageContent::StagedCommitMessage(staged_commit) => bob_group
+            .merge_staged_commit(provider, *staged_commit)
+            .expect("error merging commit"),
+        _ => panic!("Expected a staged commit."),
+    }
+    assert_eq!(bob_group.epoch(), alice_group.epoch());
+    assert!(bob_group
+        .process_message(provider, resent_commit())
+        .is_err());
+
+    // === The messages are discarded with a cleared commit ===
+    alice_group
+        .self_update(provider, &alice_signer)
+        .expect("error updating");
+    assert!(alice_group.pending_commit_messages.is_some());
+    alice_group.clear_pending_commit();
+    assert!(alice_group.pending_commit_messages.is_none());
+    assert_eq!(
+        alice_group.resend_pending_commit(),
+        Err(MlsGroupStateError::NoPendingCommit)
+    );
+
+    // === A commit that can't be prepared leaves the group unchanged ===
+    // Bob is already a member, so his key package can't be added again.
+    let epoch = alice_group.epoch();
+    alice_group
+        .add_members(provider, &alice_signer, &[bob_kpb.key_package().clone()])
+        .expect_err("added Bob twice");
+    assert!(alice_group.pending_commit().is_none());
+    assert!(alice_group.pending_commit_messages.is_none());
+    assert_eq!(alice_group.epoch(), epoch);
+    alice_group
+        .self_update(provider, &alice_signer)
+        .expect("error updating");
+}
@@ -0,0 +1,179 @@
+use openmls_traits::{types::Ciphersuite, OpenMlsProvider};
+use rstest::*;
+use rstest_reuse::{self, *};
+
+use super::*;
+use crate::{
+    binary_tree::LeafNodeIndex, group::config::CryptoConfig, group::test_core_group::setup_client,
+    messages::proposals::ProposalType, test_utils::*,
+};
+
+#[test]
+fn commit_content_policy() {
+    let policy = CommitContentPolicy::default()
+        .with_rule(CommitRule::NoReplacementByNonAdmins)
+        .with_rule(CommitRule::AdminOnly(ProposalType::Remove))
+        .with_admins(vec![b"Alice".to_vec()]);
+    assert_eq!(
+        policy.rules(),
+        &[
+            CommitRule::NoReplacementByNonAdmins,
+            CommitRule::AdminOnly(ProposalType::Remove)
+        ]
+    );
+    assert_eq!(policy.admins(), &[b"Alice".to_vec()]);
+    assert!(policy.is_admin(b"Alice"));
+    assert!(!policy.is_admin(b"Bob"));
+
+    let config = MlsGroupConfig::builder()
+        .commit_content_policy(policy.clone())
+        .build();
+    assert_eq!(config.commit_content_policy(), &policy);
+    assert!(MlsGroupConfig::default()
+        .commit_content_policy()
+        .rules()
+        .is_empty());
+}
+
+#[apply(ciphersuites_and_providers)]
+fn commit_policy_enforcement(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential, bob_kpb, bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+    let (_charlie_credential, charlie_kpb, _charlie_signer, _charlie_pk) =
+        setup_client("Charlie", ciphersuite, provider);
+    let (_new_charlie_credential, new_charlie_kpb, _new_charlie_signer, _new_charlie_pk) =
+        setup_client("Charlie", ciphersuite, provider);
+    let (dave_credential_with_key, _dave_kpb, dave_signer, _dave_pk) =
+        setup_client("Dave", ciphersuite, provider);
+
+    // Plaintext commits can be processed again after they were rejected.
+    let config_with_policy = |policy: CommitContentPolicy| {
+        MlsGroupConfig::builder()
+            .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+            .wire_format_policy(PURE_PLAINTEXT_WIRE_FORMAT_POLICY)
+            .use_ratchet_tree_extension(true)
+            .commit_content_policy(policy)
+            .build()
+    };
+    let policy = CommitContentPolicy::default()
+        .with_rule(CommitRule::NoReplacementByNonAdmins)
+        .with_admins(vec![b"Alice".to_vec()]);
+    let config = config_with_policy(policy.clone());
+
+    let mut alice_group =
+        MlsGroup::new(provider, &alice_signer, &config, alice_credential_with_key)
+            .expect("error creating group");
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(
+            provider,
+            &alice_signer,
+            &[
+                bob_kpb.key_package().clone(),
+                charlie_kpb.key_package().clone(),
+            ],
+        )
+        .expect("error adding Bob and Charlie")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    let welcome = welcome.into_welcome().expect("expected a welcome");
+    let mut bob_group =
+        MlsGroup::new_from_welcome(provider, &config, welcome, None).expect("error joining group");
+
+    // === Bob replaces Charlie with a new client of Charlie ===
+    let (proposal, _proposal_ref) = bob_group
+        .propose_add_member(provider, &bob_signer, new_charlie_kpb.key_package())
+        .expect("error proposing to add Charlie");
+    let (commit, _welcome, _group_info) = bob_group
+        .remove_members(provider, &bob_signer, &[LeafNodeIndex::new(2)])
+        .expect("error removing Charlie")
+        .into_parts();
+    let processed = alice_group
+        .process_message(provider, proposal.into_protocol_message().unwrap())
+        .expect("error processing proposal");
+    match processed.into_content() {
+        ProcessedMessageContent::ProposalMessage(queued_proposal) => {
+            alice_group.store_pending_proposal(*queued_proposal)
+        }
+        _ => panic!("expected a proposal"),
+    }
+    let commit = commit.into_protocol_message().unwrap();
+
+    // === Alice rejects the replacement by a non-admin ===
+    assert_eq!(
+        alice_group
+            .process_message(provider, commit.clone())
+            .expect_err("accepted a replacement by a non-admin"),
+        ProcessMessageError::CommitPolicyViolation(CommitRule::NoReplacementByNonAdmins)
+    );
+
+    // === Alice rejects the combination of proposals ===
+    alice_group.set_configuration(&config_with_policy(
+        CommitContentPolicy::default()
+            .with_rule(CommitRule::ForbiddenCombination(
+                ProposalType::Add,
+                ProposalType::Remove,
+            ))
+            .with_admins(vec![b"Bob".to_vec()]),
+    ));
+    assert_eq!(
+        alice_group
+            .process_message(provider, commit.clone())
+            .expect_err("accepted a forbidden combination"),
+        ProcessMessageError::CommitPolicyViolation(CommitRule::ForbiddenCombination(
+            ProposalType::Add,
+            ProposalType::Remove
+        ))
+    );
+
+    // === Alice accepts it once Bob is an admin ===
+    alice_group.set_configuration(&config_with_policy(
+        CommitContentPolicy::default()
+            .with_rule(CommitRule::NoReplacementByNonAdmins)
+            .with_rule(CommitRule::AdminOnly(ProposalType::Remove))
+            .with_admins(vec![b"Bob".to_vec()]),
+    ));
+    let processed = alice_group
+        .process_message(provider, commit)
+        .expect("rejected a replacement by an admin");
+    match processed.into_content() {
+        ProcessedMessageContent::StagedCommitMessage(staged_commit) => alice_group
+            .merge_staged_commit(provider, *staged_commit)
+            .expect("error merging commit"),
+        _ => panic!("expected a commit"),
+    }
+    bob_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+
+    // === Alice rejects the ExternalInit proposal of Dave's external commit ===
+    alice_group.set_configuration(&config_with_policy(policy.with_rule(
+        CommitRule::ForbiddenInExternalCommits(ProposalType::ExternalInit),
+    )));
+    let group_info = bob_group
+        .export_group_info(provider, &bob_signer, true)
+        .expect("error exporting group info")
+        .into_verifiable_group_info()
+        .expect("expected a group info");
+    let (_dave_group, commit, _group_info) = MlsGroup::join_by_external_commit(
+        provider,
+        &dave_signer,
+        None,
+        group_info,
+        &config,
+        b"",
+        dave_credential_with_key,
+    )
+    .expect("error joining by external commit");
+    assert_eq!(
+        alice_group
+            .process_message(provider, commit.into_protocol_message().unwrap())
+            .expect_err("accepted a forbidden proposal in an external commit"),
+        ProcessMessageError::CommitPolicyViolation(CommitRule::ForbiddenInExternalCommits(
+            ProposalType::ExternalInit
+        ))
+    );
+}
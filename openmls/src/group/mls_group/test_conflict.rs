@@ -0,0 +1,197 @@
+use openmls_traits::{types::Ciphersuite, OpenMlsProvider};
+use rstest::*;
+use rstest_reuse::{self, *};
+
+use super::*;
+use crate::{
+    group::{config::CryptoConfig, test_core_group::setup_client},
+    messages::proposals::Proposal,
+    test_utils::*,
+};
+
+#[apply(ciphersuites_and_providers)]
+fn resolve_commit_conflict(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential, bob_kpb, bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+    let (_charlie_credential, charlie_kpb, _charlie_signer, _charlie_pk) =
+        setup_client("Charlie", ciphersuite, provider);
+    let (_dave_credential, dave_kpb, _dave_signer, _dave_pk) =
+        setup_client("Dave", ciphersuite, provider);
+    let (_eve_credential, eve_kpb, _eve_signer, _eve_pk) =
+        setup_client("Eve", ciphersuite, provider);
+
+    let mls_group_config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .use_ratchet_tree_extension(true)
+        .build();
+
+    // === Alice creates a group with Bob and Charlie ===
+    let mut alice_group = MlsGroup::new_with_group_id(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        GroupId::from_slice(b"Test Group"),
+        alice_credential_with_key,
+    )
+    .expect("error creating group");
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(
+            provider,
+            &alice_signer,
+            &[
+                bob_kpb.key_package().clone(),
+                charlie_kpb.key_package().clone(),
+            ],
+        )
+        .expect("error adding Bob and Charlie")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    let welcome = welcome.into_welcome().expect("expected a welcome");
+    let mut bob_group = MlsGroup::new_from_welcome(provider, &mls_group_config, welcome, None)
+        .expect("error joining group");
+
+    // Without a pending commit, there is no conflict to resolve.
+    let (bob_commit, _welcome, _group_info) = bob_group
+        .self_update(provider, &bob_signer)
+        .expect("error creating commit")
+        .into_parts();
+    let staged_commit = match alice_group
+        .process_message(
+            provider,
+            bob_commit
+                .into_protocol_message()
+                .expect("expected a protocol message"),
+        )
+        .expect("error processing commit")
+        .into_content()
+    {
+        ProcessedMessageContent::StagedCommitMessage(staged_commit) => *staged_commit,
+        _ => panic!("Expected a staged commit."),
+    };
+    assert_eq!(
+        alice_group
+            .resolve_commit_conflict(provider, &alice_signer, staged_commit)
+            .expect_err("resolved a conflict without a pending commit"),
+        ResolveCommitConflictError::GroupStateError(MlsGroupStateError::NoPendingCommit)
+    );
+    bob_group.clear_pending_commit();
+
+    // === Alice and Bob commit concurrently ===
+    alice_group
+        .propose_add_member(provider, &alice_signer, dave_kpb.key_package())
+        .expect("error proposing to add Dave");
+    alice_group
+        .propose_add_member(provider, &alice_signer, eve_kpb.key_package())
+        .expect("error proposing to add Eve");
+    alice_group
+        .propose_remove_member(provider, &alice_signer, LeafNodeIndex::new(2))
+        .expect("error proposing to remove Charlie");
+    alice_group
+        .commit_to_pending_proposals(provider, &alice_signer)
+        .expect("error creating commit");
+
+    let (bob_commit, _welcome, _group_info) = bob_group
+        .add_members(provider, &bob_signer, &[dave_kpb.key_package().clone()])
+        .expect("error adding Dave")
+        .into_parts();
+    bob_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+
+    // === Bob's commit wins ===
+    let staged_commit = match alice_group
+        .process_message(
+            provider,
+            bob_commit
+                .into_protocol_message()
+                .expect("expected a protocol message"),
+        )
+        .expect("error processing commit")
+        .into_content()
+    {
+        ProcessedMessageContent::StagedCommitMessage(staged_commit) => *staged_commit,
+        _ => panic!("Expected a staged commit."),
+    };
+    let resolution = alice_group
+        .resolve_commit_conflict(provider, &alice_signer, staged_commit)
+        .expect("error resolving conflict");
+    assert!(alice_group.pending_commit().is_none());
+    assert_eq!(alice_group.epoch(), bob_group.epoch());
+
+    // Dave has already been added by Bob.
+    assert_eq!(resolution.invalid().len(), 1);
+    assert!(matches!(
+        &resolution.invalid()[0],
+        Proposal::Add(add_proposal) if add_proposal.key_package() == dave_kpb.key_package()
+    ));
+
+    // Adding Eve and removing Charlie still apply.
+    assert_eq!(resolution.requeued().len(), 2);
+    assert!(resolution
+        .requeued()
+        .iter()
+        .any(|requeued| matches!(requeued.proposal(), Proposal::Add(_))));
+    assert!(resolution
+        .requeued()
+        .iter()
+        .any(|requeued| matches!(requeued.proposal(), Proposal::Remove(_))));
+    assert_eq!(alice_group.pending_proposals().count(), 2);
+
+    // === Bob receives the requeued proposals and Alice commits them ===
+    for message in resolution.into_messages() {
+        let processed_message = bob_group
+            .process_message(
+                provider,
+                message
+                    .into_protocol_message()
+                    .expect("expected a protocol message"),
+            )
+            .expect("error processing proposal");
+        match processed_message.into_content() {
+            ProcessedMessageContent::ProposalMessage(proposal) => {
+                bob_group.store_pending_proposal(*proposal)
+            }
+            _ => panic!("Expected a proposal."),
+        }
+    }
+    let (commit, _welcome, _group_info) = alice_group
+        .commit_to_pending_proposals(provider, &alice_signer)
+        .expect("error creating commit")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    let processed_message = bob_group
+        .process_message(
+            provider,
+            commit
+                .into_protocol_message()
+                .expect("expected a protocol message"),
+        )
+        .expect("error processing commit");
+    match processed_message.into_content() {
+        ProcessedMessageContent::StagedCommitMessage(staged_commit) => bob_group
+            .merge_staged_commit(provider, *staged_commit)
+            .expect("error merging commit"),
+        _ => panic!("Expected a staged commit."),
+    }
+
+    // Alice, Bob, Dave and Eve are in the group.
+    assert_eq!(alice_group.epoch(), bob_group.epoch());
+    assert_eq!(alice_group.members().count(), 4);
+    assert!(alice_group
+        .members()
+        .all(|member| member.credential.identity() != b"Charlie"));
+    assert_eq!(
+        alice_group
+            .export_secret(provider.crypto(), "label", b"context", 32)
+            .expect("error exporting secret"),
+        bob_group
+            .export_secret(provider.crypto(), "label", b"context", 32)
+            .expect("error exporting secret")
+    );
+}
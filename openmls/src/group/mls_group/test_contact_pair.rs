@@ -0,0 +1,222 @@
+use openmls_traits::{types::Ciphersuite, OpenMlsProvider};
+use rstest::*;
+use rstest_reuse::{self, *};
+
+use super::*;
+use crate::{
+    ciphersuite::HpkePrivateKey,
+    group::{config::CryptoConfig, errors::ContactPairError, test_core_group::setup_client},
+    test_utils::*,
+};
+
+#[apply(ciphersuites_and_providers)]
+fn contact_pair(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (bob_credential_with_key, bob_kpb, bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+
+    let mls_group_config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .use_ratchet_tree_extension(true)
+        .build();
+
+    // The group ID doesn't depend on the order of the identities.
+    let group_id = ContactPair::group_id(provider.crypto(), ciphersuite, b"Alice", b"Bob")
+        .expect("error deriving group ID");
+    assert_eq!(
+        ContactPair::group_id(provider.crypto(), ciphersuite, b"Bob", b"Alice")
+            .expect("error deriving group ID"),
+        group_id
+    );
+    assert_ne!(
+        ContactPair::group_id(provider.crypto(), ciphersuite, b"Alice", b"Charlie")
+            .expect("error deriving group ID"),
+        group_id
+    );
+
+    // === Alice creates the contact pair and Bob joins ===
+    let (mut alice_pair, welcome) = ContactPair::new(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        alice_credential_with_key.clone(),
+        bob_kpb.key_package().clone(),
+    )
+    .expect("error creating contact pair");
+    let mut bob_pair = ContactPair::join(
+        provider,
+        &mls_group_config,
+        welcome.into_welcome().expect("expected a welcome"),
+    )
+    .expect("error joining contact pair");
+    assert_eq!(alice_pair.group().group_id(), &group_id);
+    assert_eq!(bob_pair.group().group_id(), &group_id);
+    assert_eq!(
+        alice_pair.peer().expect("error reading peer"),
+        bob_credential_with_key.credential
+    );
+
+    // === Alice and Bob exchange messages ===
+    let message = alice_pair
+        .send(provider, &alice_signer, b"Hello Bob")
+        .expect("error sending message");
+    assert_eq!(
+        bob_pair
+            .receive(provider, message.into())
+            .expect("error receiving message"),
+        ContactPairEvent::Message(b"Hello Bob".to_vec())
+    );
+
+    // === Bob updates ===
+    let commit = bob_pair
+        .update(provider, &bob_signer)
+        .expect("error updating");
+    assert_eq!(
+        alice_pair
+            .receive(provider, commit.into())
+            .expect("error receiving commit"),
+        ContactPairEvent::Updated
+    );
+    assert_eq!(alice_pair.group().epoch(), bob_pair.group().epoch());
+
+    // === Bob loses his state and re-initializes the group ===
+    drop(bob_pair);
+    let alice_kpb = KeyPackageBundle::new(
+        provider,
+        &alice_signer,
+        ciphersuite,
+        alice_credential_with_key.clone(),
+    );
+    let (mut bob_pair, welcome) = ContactPair::new(
+        provider,
+        &bob_signer,
+        &mls_group_config,
+        bob_credential_with_key.clone(),
+        alice_kpb.key_package().clone(),
+    )
+    .expect("error creating contact pair");
+    assert_eq!(
+        alice_pair
+            .receive(provider, welcome.into())
+            .expect("error receiving welcome"),
+        ContactPairEvent::Reinitialized
+    );
+    assert_eq!(alice_pair.group().group_id(), &group_id);
+    let message = alice_pair
+        .send(provider, &alice_signer, b"Welcome back")
+        .expect("error sending message");
+    assert_eq!(
+        bob_pair
+            .receive(provider, message.into())
+            .expect("error receiving message"),
+        ContactPairEvent::Message(b"Welcome back".to_vec())
+    );
+
+    // === Both re-initialize concurrently ===
+    let bob_kpb = KeyPackageBundle::new(
+        provider,
+        &bob_signer,
+        ciphersuite,
+        bob_credential_with_key.clone(),
+    );
+    let alice_kpb = KeyPackageBundle::new(
+        provider,
+        &alice_signer,
+        ciphersuite,
+        alice_credential_with_key.clone(),
+    );
+    let alice_welcome = alice_pair
+        .reinit(provider, &alice_signer, bob_kpb.key_package().clone())
+        .expect("error re-initializing");
+    let bob_welcome = bob_pair
+        .reinit(provider, &bob_signer, alice_kpb.key_package().clone())
+        .expect("error re-initializing");
+
+    // Alice's group is kept, since her identity is smaller.
+    assert_eq!(
+        alice_pair
+            .receive(provider, bob_welcome.into())
+            .expect("error receiving welcome"),
+        ContactPairEvent::Handled
+    );
+    // Alice's key package wasn't consumed for Bob's discarded group.
+    assert!(provider
+        .key_store()
+        .read::<HpkePrivateKey>(alice_kpb.key_package().hpke_init_key().as_slice())
+        .is_some());
+    assert_eq!(
+        bob_pair
+            .receive(provider, alice_welcome.into())
+            .expect("error receiving welcome"),
+        ContactPairEvent::Reinitialized
+    );
+    let message = bob_pair
+        .send(provider, &bob_signer, b"Hello again")
+        .expect("error sending message");
+    assert_eq!(
+        alice_pair
+            .receive(provider, message.into())
+            .expect("error receiving message"),
+        ContactPairEvent::Message(b"Hello again".to_vec())
+    );
+
+    // A re-initialized group with another signature key of the peer doesn't
+    // replace the group.
+    let (mallory_credential_with_key, _mallory_kpb, mallory_signer, _mallory_pk) =
+        setup_client("Bob", ciphersuite, provider);
+    assert_eq!(
+        mallory_credential_with_key.credential,
+        bob_credential_with_key.credential
+    );
+    let alice_kpb = KeyPackageBundle::new(
+        provider,
+        &alice_signer,
+        ciphersuite,
+        alice_credential_with_key,
+    );
+    let (_mallory_pair, welcome) = ContactPair::new(
+        provider,
+        &mallory_signer,
+        &mls_group_config,
+        mallory_credential_with_key,
+        alice_kpb.key_package().clone(),
+    )
+    .expect("error creating contact pair");
+    let epoch = alice_pair.group().epoch();
+    assert_eq!(
+        alice_pair
+            .receive(provider, welcome.into())
+            .expect_err("expected an error"),
+        ContactPairError::PeerMismatch
+    );
+    assert_eq!(alice_pair.group().epoch(), epoch);
+    let message = alice_pair
+        .send(provider, &alice_signer, b"Still here")
+        .expect("error sending message");
+    assert_eq!(
+        bob_pair
+            .receive(provider, message.into())
+            .expect("error receiving message"),
+        ContactPairEvent::Message(b"Still here".to_vec())
+    );
+
+    // Groups with more than two members aren't contact pairs.
+    let (_charlie_credential, charlie_kpb, _charlie_signer, _charlie_pk) =
+        setup_client("Charlie", ciphersuite, provider);
+    let mut group = alice_pair.into_group();
+    group
+        .add_members(
+            provider,
+            &alice_signer,
+            &[charlie_kpb.key_package().clone()],
+        )
+        .expect("error adding Charlie");
+    group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    assert_eq!(
+        ContactPair::from_group::<()>(provider.crypto(), group).expect_err("expected an error"),
+        ContactPairError::NotAContactPair
+    );
+}
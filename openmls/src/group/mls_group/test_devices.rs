@@ -0,0 +1,168 @@
+use openmls_traits::{types::Ciphersuite, OpenMlsProvider};
+use rstest::*;
+use rstest_reuse::{self, *};
+
+use super::*;
+use crate::{
+    group::{config::CryptoConfig, errors::DeviceError, test_core_group::setup_client},
+    test_utils::*,
+};
+
+// Credential identities have the form `user/device`.
+fn user_id(credential: &Credential) -> Option<Vec<u8>> {
+    credential
+        .identity()
+        .split(|byte| *byte == b'/')
+        .next()
+        .map(|user_id| user_id.to_vec())
+}
+
+#[apply(ciphersuites_and_providers)]
+fn multiple_devices(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (laptop_credential, _laptop_kpb, laptop_signer, _laptop_pk) =
+        setup_client("alice/laptop", ciphersuite, provider);
+    let (phone_credential, _phone_kpb, phone_signer, _phone_pk) =
+        setup_client("alice/phone", ciphersuite, provider);
+    let (_bob_credential, bob_kpb, _bob_signer, _bob_pk) =
+        setup_client("bob/laptop", ciphersuite, provider);
+
+    let mls_group_config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .use_ratchet_tree_extension(true)
+        .build();
+
+    // === Alice's laptop is in two groups, one of them with Bob ===
+    let mut group_with_bob = MlsGroup::new_with_group_id(
+        provider,
+        &laptop_signer,
+        &mls_group_config,
+        GroupId::from_slice(b"Group with Bob"),
+        laptop_credential.clone(),
+    )
+    .expect("error creating group");
+    group_with_bob
+        .add_members(provider, &laptop_signer, &[bob_kpb.key_package().clone()])
+        .expect("error adding Bob");
+    group_with_bob
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    let mut own_group = MlsGroup::new_with_group_id(
+        provider,
+        &laptop_signer,
+        &mls_group_config,
+        GroupId::from_slice(b"Own group"),
+        laptop_credential,
+    )
+    .expect("error creating group");
+
+    let device_manager = DeviceManager::new(user_id);
+    assert_eq!(device_manager.devices(&group_with_bob, b"alice").len(), 1);
+    assert_eq!(device_manager.devices(&group_with_bob, b"bob").len(), 1);
+
+    // === Only devices of Alice can be added ===
+    let results = device_manager.add_device(
+        provider,
+        &laptop_signer,
+        [&mut own_group],
+        |_group: &MlsGroup| None,
+    );
+    assert_eq!(results.len(), 1);
+    assert_eq!(
+        results[0]
+            .1
+            .as_ref()
+            .expect_err("added a device without a key package"),
+        &DeviceError::MissingKeyPackage
+    );
+    let results = device_manager.add_device(
+        provider,
+        &laptop_signer,
+        [&mut own_group],
+        |_group: &MlsGroup| {
+            let (_credential, kpb, _signer, _pk) = setup_client("bob/phone", ciphersuite, provider);
+            Some(kpb.key_package().clone())
+        },
+    );
+    assert_eq!(
+        results[0].1.as_ref().expect_err("added a device of Bob"),
+        &DeviceError::ForeignDevice
+    );
+    assert!(own_group.pending_commit().is_none());
+
+    // === Alice adds her phone to all groups ===
+    let results = device_manager.add_device(
+        provider,
+        &laptop_signer,
+        [&mut group_with_bob, &mut own_group],
+        |_group: &MlsGroup| {
+            Some(
+                KeyPackageBundle::new(
+                    provider,
+                    &phone_signer,
+                    ciphersuite,
+                    phone_credential.clone(),
+                )
+                .key_package()
+                .clone(),
+            )
+        },
+    );
+    assert_eq!(results.len(), 2);
+    assert_eq!(&results[0].0, group_with_bob.group_id());
+    assert_eq!(&results[1].0, own_group.group_id());
+    for (_group_id, result) in results {
+        let device_commit = result.expect("error adding device");
+        assert!(device_commit.welcome().is_some());
+    }
+    for group in [&mut group_with_bob, &mut own_group] {
+        group
+            .merge_pending_commit(provider)
+            .expect("error merging commit");
+        assert_eq!(device_manager.devices(group, b"alice").len(), 2);
+    }
+    assert_eq!(device_manager.devices(&group_with_bob, b"bob").len(), 1);
+
+    // Groups in which the phone is already a member are skipped.
+    let phone_key_package = KeyPackageBundle::new(
+        provider,
+        &phone_signer,
+        ciphersuite,
+        phone_credential.clone(),
+    )
+    .key_package()
+    .clone();
+    let results = device_manager.add_device(
+        provider,
+        &laptop_signer,
+        [&mut group_with_bob],
+        |_group: &MlsGroup| Some(phone_key_package.clone()),
+    );
+    assert!(results.is_empty());
+
+    // === Alice lost her phone and removes it everywhere ===
+    let results = device_manager.remove_device(
+        provider,
+        &laptop_signer,
+        [&mut group_with_bob, &mut own_group],
+        &phone_credential.credential,
+    );
+    assert_eq!(results.len(), 2);
+    for (_group_id, result) in results {
+        result.expect("error removing device");
+    }
+    for group in [&mut group_with_bob, &mut own_group] {
+        group
+            .merge_pending_commit(provider)
+            .expect("error merging commit");
+        assert_eq!(device_manager.devices(group, b"alice").len(), 1);
+    }
+
+    // The phone isn't a member of any group anymore.
+    let results = device_manager.remove_device(
+        provider,
+        &laptop_signer,
+        [&mut group_with_bob, &mut own_group],
+        &phone_credential.credential,
+    );
+    assert!(results.is_empty());
+}
@@ -0,0 +1,165 @@
+use openmls_traits::{types::Ciphersuite, OpenMlsProvider};
+use rstest::*;
+use rstest_reuse::{self, *};
+
+use super::*;
+use crate::{
+    framing::AuthenticatedContent,
+    group::{
+        config::CryptoConfig,
+        errors::{ExternalCommitError, WelcomeError},
+        test_core_group::setup_client,
+    },
+    messages::proposals::{Proposal, ReInitProposal},
+    test_utils::*,
+};
+
+/// Returns a ciphersuite that is different from `ciphersuite`.
+fn other_ciphersuite(ciphersuite: Ciphersuite) -> Ciphersuite {
+    if ciphersuite == Ciphersuite::MLS_128_DHKEMX25519_AES128GCM_SHA256_Ed25519 {
+        Ciphersuite::MLS_128_DHKEMX25519_CHACHA20POLY1305_SHA256_Ed25519
+    } else {
+        Ciphersuite::MLS_128_DHKEMX25519_AES128GCM_SHA256_Ed25519
+    }
+}
+
+#[test]
+fn downgrade_policy() {
+    let strong = Ciphersuite::MLS_256_DHKEMX448_AES256GCM_SHA512_Ed448;
+    let weak = Ciphersuite::MLS_128_DHKEMX25519_AES128GCM_SHA256_Ed25519;
+    let unpinned = Ciphersuite::MLS_128_DHKEMP256_AES128GCM_SHA256_P256;
+    let mls10 = ProtocolVersion::Mls10;
+    let draft = ProtocolVersion::Mls10Draft11;
+
+    let policy = DowngradePolicy::new(vec![strong, weak], vec![mls10]);
+    assert_eq!(policy.ciphersuites(), &[strong, weak]);
+    assert_eq!(policy.versions(), &[mls10]);
+    assert!(policy.accepts(strong, mls10));
+    assert!(policy.accepts(weak, mls10));
+    assert!(!policy.accepts(unpinned, mls10));
+    assert!(!policy.accepts(strong, draft));
+
+    assert!(policy.accepts_transition(weak, mls10, strong, mls10));
+    assert!(policy.accepts_transition(strong, mls10, strong, mls10));
+    assert!(!policy.accepts_transition(strong, mls10, weak, mls10));
+    assert!(policy.accepts_transition(unpinned, mls10, weak, mls10));
+    assert!(!policy.accepts_transition(weak, mls10, unpinned, mls10));
+    assert!(!policy.accepts_transition(strong, mls10, strong, draft));
+
+    let default = DowngradePolicy::default();
+    assert!(default.accepts(unpinned, draft));
+    assert!(default.accepts_transition(strong, mls10, unpinned, draft));
+
+    let config = MlsGroupConfig::builder()
+        .downgrade_policy(policy.clone())
+        .build();
+    assert_eq!(config.downgrade_policy(), &policy);
+}
+
+#[apply(ciphersuites_and_providers)]
+fn downgrade_protection(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential, bob_kpb, _bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+    let (charlie_credential_with_key, _charlie_kpb, charlie_signer, _charlie_pk) =
+        setup_client("Charlie", ciphersuite, provider);
+    let weaker = other_ciphersuite(ciphersuite);
+
+    let config_with_policy = |policy: DowngradePolicy| {
+        MlsGroupConfig::builder()
+            .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+            .use_ratchet_tree_extension(true)
+            .downgrade_policy(policy)
+            .build()
+    };
+    let config = config_with_policy(DowngradePolicy::default());
+    let other_config = config_with_policy(DowngradePolicy::new(vec![weaker], vec![]));
+    let pinned_config = config_with_policy(DowngradePolicy::new(
+        vec![ciphersuite, weaker],
+        vec![ProtocolVersion::Mls10],
+    ));
+
+    let mut alice_group =
+        MlsGroup::new(provider, &alice_signer, &config, alice_credential_with_key)
+            .expect("error creating group");
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(provider, &alice_signer, &[bob_kpb.key_package().clone()])
+        .expect("error adding Bob")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    let welcome = welcome.into_welcome().expect("expected a welcome");
+
+    // === Bob only joins groups with pinned ciphersuites ===
+    assert_eq!(
+        MlsGroup::new_from_welcome(provider, &other_config, welcome.clone(), None)
+            .expect_err("joined a group with a ciphersuite that is not pinned"),
+        WelcomeError::DowngradeAttempt
+    );
+    let mut bob_group = MlsGroup::new_from_welcome(provider, &pinned_config, welcome, None)
+        .expect("error joining group");
+
+    // === Charlie doesn't join the group by external commit ===
+    let group_info = alice_group
+        .export_group_info(provider, &alice_signer, true)
+        .expect("error exporting group info")
+        .into_verifiable_group_info()
+        .expect("expected a group info");
+    assert_eq!(
+        MlsGroup::join_by_external_commit(
+            provider,
+            &charlie_signer,
+            None,
+            group_info,
+            &other_config,
+            b"",
+            charlie_credential_with_key,
+        )
+        .expect_err("joined a group with a ciphersuite that is not pinned"),
+        ExternalCommitError::DowngradeAttempt
+    );
+
+    // Alice proposes to reinitialize the group with `ciphersuite`.
+    let mut propose_reinit = |ciphersuite: Ciphersuite| {
+        let proposal = Proposal::ReInit(ReInitProposal {
+            group_id: alice_group.group_id().clone(),
+            version: ProtocolVersion::Mls10,
+            ciphersuite,
+            extensions: Extensions::empty(),
+        });
+        let content = AuthenticatedContent::member_proposal(
+            alice_group.framing_parameters(),
+            alice_group.own_leaf_index(),
+            proposal,
+            alice_group.group.context(),
+            &alice_signer,
+        )
+        .expect("error creating proposal");
+        alice_group
+            .content_to_mls_message(content, provider)
+            .expect("error creating message")
+    };
+
+    // === Bob rejects a ReInit to a weaker ciphersuite ===
+    let message = propose_reinit(weaker);
+    assert_eq!(
+        bob_group
+            .process_message(provider, message.into_protocol_message().unwrap())
+            .expect_err("accepted a downgrade"),
+        ProcessMessageError::DowngradeAttempt
+    );
+
+    // === Bob accepts it without the policy and the ReInit to the same one ===
+    bob_group.set_configuration(&config);
+    let message = propose_reinit(weaker);
+    bob_group
+        .process_message(provider, message.into_protocol_message().unwrap())
+        .expect("rejected a ReInit without a policy");
+    bob_group.set_configuration(&pinned_config);
+    let message = propose_reinit(ciphersuite);
+    bob_group
+        .process_message(provider, message.into_protocol_message().unwrap())
+        .expect("rejected a ReInit to the same ciphersuite");
+}
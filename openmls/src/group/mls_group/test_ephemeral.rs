@@ -0,0 +1,103 @@
+use openmls_traits::{types::Ciphersuite, OpenMlsProvider};
+use rstest::*;
+use rstest_reuse::{self, *};
+
+use super::*;
+use crate::{
+    group::{config::CryptoConfig, test_core_group::setup_client},
+    test_utils::*,
+};
+
+#[apply(ciphersuites_and_providers)]
+fn ephemeral_keys(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential, bob_kpb, _bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+
+    let mls_group_config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .use_ratchet_tree_extension(true)
+        .build();
+
+    // === Alice creates a group with Bob ===
+    let mut alice_group = MlsGroup::new(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        alice_credential_with_key,
+    )
+    .expect("error creating group");
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(provider, &alice_signer, &[bob_kpb.key_package().clone()])
+        .expect("error adding Bob")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    let welcome = welcome.into_welcome().expect("expected a welcome");
+    let mut bob_group = MlsGroup::new_from_welcome(provider, &mls_group_config, welcome, None)
+        .expect("error joining group");
+
+    // === Both derive the same keys ===
+    let mut alice_typing_key = alice_group
+        .ephemeral_key(provider.crypto(), "typing", 32)
+        .expect("error deriving key");
+    let bob_typing_key = bob_group
+        .ephemeral_key(provider.crypto(), "typing", 32)
+        .expect("error deriving key");
+    assert_eq!(alice_typing_key, bob_typing_key);
+    assert_eq!(alice_typing_key.as_slice().len(), 32);
+    assert_eq!(alice_typing_key.label(), "typing");
+    assert_eq!(alice_typing_key.epoch(), alice_group.epoch());
+    assert!(alice_typing_key.is_current(&alice_group));
+
+    // Keys with different labels are independent and don't depend on the
+    // application's exported secrets.
+    let presence_key = alice_group
+        .ephemeral_key(provider.crypto(), "presence", 32)
+        .expect("error deriving key");
+    assert_ne!(presence_key.as_slice(), alice_typing_key.as_slice());
+    assert_ne!(
+        alice_group
+            .export_secret(provider.crypto(), "typing", &[], 32)
+            .expect("error exporting secret"),
+        alice_typing_key.as_slice()
+    );
+
+    // Deriving keys doesn't affect the message ratchets.
+    let message = alice_group
+        .create_message(provider, &alice_signer, b"Hello Bob")
+        .expect("error creating message");
+    bob_group
+        .process_message(
+            provider,
+            message
+                .into_protocol_message()
+                .expect("expected a protocol message"),
+        )
+        .expect("error processing message");
+
+    // === Keys rotate with the epoch ===
+    assert!(!alice_typing_key
+        .rotate(provider.crypto(), &alice_group)
+        .expect("error rotating key"));
+    alice_group
+        .self_update(provider, &alice_signer)
+        .expect("error updating");
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    assert!(!alice_typing_key.is_current(&alice_group));
+    assert!(alice_typing_key
+        .rotate(provider.crypto(), &alice_group)
+        .expect("error rotating key"));
+    assert_eq!(alice_typing_key.epoch(), alice_group.epoch());
+    assert_ne!(alice_typing_key.as_slice(), bob_typing_key.as_slice());
+    assert_eq!(alice_typing_key.as_slice().len(), 32);
+
+    // The key material isn't printed.
+    assert!(
+        !format!("{:?}", alice_typing_key).contains(&format!("{:?}", alice_typing_key.as_slice()))
+    );
+}
@@ -0,0 +1,141 @@
+use std::sync::{Arc, Mutex};
+
+use openmls_traits::{types::Ciphersuite, OpenMlsProvider};
+use rstest::*;
+use rstest_reuse::{self, *};
+
+use super::*;
+use crate::{
+    group::{config::CryptoConfig, test_core_group::setup_client},
+    test_utils::*,
+};
+
+/// Subscribe to the epoch transitions of `group` and collect them.
+fn collect_transitions(group: &mut MlsGroup) -> Arc<Mutex<Vec<EpochTransition>>> {
+    let transitions = Arc::new(Mutex::new(Vec::new()));
+    let sink = transitions.clone();
+    group.subscribe_epoch_transitions(move |transition| {
+        sink.lock().unwrap().push(transition.clone());
+    });
+    transitions
+}
+
+#[apply(ciphersuites_and_providers)]
+fn epoch_transitions(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential, bob_kpb, bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+
+    let config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .use_ratchet_tree_extension(true)
+        .build();
+
+    let mut alice_group =
+        MlsGroup::new(provider, &alice_signer, &config, alice_credential_with_key)
+            .expect("error creating group");
+    let alice_transitions = collect_transitions(&mut alice_group);
+
+    // === Alice adds Bob ===
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(provider, &alice_signer, &[bob_kpb.key_package().clone()])
+        .expect("error adding Bob")
+        .into_parts();
+    assert!(alice_transitions.lock().unwrap().is_empty());
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+
+    let transition = alice_transitions.lock().unwrap().pop().unwrap();
+    assert_eq!(transition.group_id(), alice_group.group_id());
+    assert_eq!(transition.old_epoch(), GroupEpoch::from(0));
+    assert_eq!(transition.new_epoch(), GroupEpoch::from(1));
+    assert_eq!(transition.cause(), EpochChangeCause::OwnCommit);
+    assert_eq!(transition.committer().leaf_index(), LeafNodeIndex::new(0));
+    assert_eq!(transition.added().len(), 1);
+    assert_eq!(transition.added()[0].leaf_index(), LeafNodeIndex::new(1));
+    assert!(transition.removed().is_empty());
+    assert_eq!(
+        transition.epoch_authenticator(),
+        Some(alice_group.epoch_authenticator())
+    );
+
+    let welcome = welcome.into_welcome().expect("expected a welcome");
+    let mut bob_group =
+        MlsGroup::new_from_welcome(provider, &config, welcome, None).expect("error joining group");
+    let bob_transitions = collect_transitions(&mut bob_group);
+
+    // === Bob updates his leaf ===
+    let (commit, _welcome, _group_info) = bob_group
+        .self_update(provider, &bob_signer)
+        .expect("error updating")
+        .into_parts();
+    bob_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    let processed = alice_group
+        .process_message(provider, commit.into_protocol_message().unwrap())
+        .expect("error processing commit");
+    let ProcessedMessageContent::StagedCommitMessage(staged_commit) = processed.into_content()
+    else {
+        panic!("expected a commit");
+    };
+    alice_group
+        .merge_staged_commit(provider, *staged_commit)
+        .expect("error merging commit");
+
+    let bob_transition = bob_transitions.lock().unwrap().pop().unwrap();
+    let alice_transition = alice_transitions.lock().unwrap().pop().unwrap();
+    assert_eq!(bob_transition.cause(), EpochChangeCause::OwnCommit);
+    assert_eq!(alice_transition.cause(), EpochChangeCause::MemberCommit);
+    assert_eq!(
+        alice_transition.committer().leaf_index(),
+        LeafNodeIndex::new(1)
+    );
+    assert!(alice_transition.added().is_empty());
+    assert!(alice_transition.removed().is_empty());
+    // All members observe the same authentication data.
+    assert_eq!(
+        alice_transition.epoch_authenticator(),
+        bob_transition.epoch_authenticator()
+    );
+    assert_eq!(
+        alice_transition.confirmation_tag(),
+        bob_transition.confirmation_tag()
+    );
+
+    // === A cancelled subscription isn't called anymore ===
+    let subscription = alice_group.subscribe_epoch_transitions(|_| panic!("unsubscribed"));
+    assert!(alice_group.unsubscribe_epoch_transitions(subscription));
+    assert!(!alice_group.unsubscribe_epoch_transitions(subscription));
+
+    // === Alice removes Bob ===
+    let (commit, _welcome, _group_info) = alice_group
+        .remove_members(provider, &alice_signer, &[LeafNodeIndex::new(1)])
+        .expect("error removing Bob")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    let processed = bob_group
+        .process_message(provider, commit.into_protocol_message().unwrap())
+        .expect("error processing commit");
+    let ProcessedMessageContent::StagedCommitMessage(staged_commit) = processed.into_content()
+    else {
+        panic!("expected a commit");
+    };
+    bob_group
+        .merge_staged_commit(provider, *staged_commit)
+        .expect("error merging commit");
+
+    let bob_transition = bob_transitions.lock().unwrap().pop().unwrap();
+    assert_eq!(bob_transition.new_epoch(), GroupEpoch::from(3));
+    assert_eq!(bob_transition.removed().len(), 1);
+    assert!(bob_transition.epoch_authenticator().is_none());
+    assert_eq!(
+        bob_transition.removed()[0].leaf_index(),
+        LeafNodeIndex::new(1)
+    );
+    assert_eq!(alice_transitions.lock().unwrap().len(), 1);
+}
@@ -0,0 +1,200 @@
+use openmls_traits::{clock::OpenMlsClock, types::Ciphersuite, OpenMlsProvider};
+use rstest::*;
+use rstest_reuse::{self, *};
+use tls_codec::{Deserialize, Serialize};
+
+use super::*;
+use crate::{
+    extensions::ExternalKeyIdExtension,
+    group::{config::CryptoConfig, errors::StageCommitError, test_core_group::setup_client},
+    test_utils::*,
+};
+
+const DAY: u64 = 24 * 60 * 60;
+
+/// The start of a rotation period.
+const PERIOD_START: u64 = 19_000 * DAY;
+
+struct FixedClock(u64);
+
+impl OpenMlsClock for FixedClock {
+    fn now(&self) -> u64 {
+        self.0
+    }
+}
+
+/// A provider that uses the crypto and the key store of `provider`, but
+/// takes the current time from `clock`.
+struct FixedClockProvider<'a, P> {
+    provider: &'a P,
+    clock: FixedClock,
+}
+
+impl<P: OpenMlsProvider> OpenMlsProvider for FixedClockProvider<'_, P> {
+    type CryptoProvider = P::CryptoProvider;
+    type RandProvider = P::RandProvider;
+    type KeyStoreProvider = P::KeyStoreProvider;
+    type ClockProvider = FixedClock;
+
+    fn crypto(&self) -> &Self::CryptoProvider {
+        self.provider.crypto()
+    }
+
+    fn rand(&self) -> &Self::RandProvider {
+        self.provider.rand()
+    }
+
+    fn key_store(&self) -> &Self::KeyStoreProvider {
+        self.provider.key_store()
+    }
+
+    fn clock(&self) -> &Self::ClockProvider {
+        &self.clock
+    }
+}
+
+#[test]
+fn external_key_rotation_policy() {
+    let policy = ExternalKeyRotationPolicy::new(DAY, DAY);
+    assert_eq!(policy.rotation_interval(), Some(DAY));
+    assert_eq!(policy.grace_period(), DAY);
+    assert_eq!(
+        ExternalKeyRotationPolicy::new(0, DAY).rotation_interval(),
+        None
+    );
+
+    // The keypair of a period is retired a grace period after it was
+    // replaced.
+    let period = PERIOD_START / DAY;
+    assert!(!policy.is_retired(period, PERIOD_START));
+    assert!(!policy.is_retired(period, PERIOD_START + 2 * DAY - 1));
+    assert!(policy.is_retired(period, PERIOD_START + 2 * DAY));
+    assert!(!ExternalKeyRotationPolicy::default().is_retired(0, u64::MAX));
+}
+
+#[apply(ciphersuites_and_providers)]
+fn external_key_rotation(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential_with_key, bob_kpb, _bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+
+    let policy = ExternalKeyRotationPolicy::new(DAY, DAY);
+    let config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .use_ratchet_tree_extension(true)
+        .external_key_rotation_policy(policy)
+        .build();
+    assert_eq!(config.external_key_rotation_policy(), policy);
+
+    let mut alice_group =
+        MlsGroup::new(provider, &alice_signer, &config, alice_credential_with_key)
+            .expect("error creating group");
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(provider, &alice_signer, &[bob_kpb.key_package().clone()])
+        .expect("error adding Bob")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    let mut bob_group = MlsGroup::new_from_welcome(
+        provider,
+        &config,
+        welcome.into_welcome().expect("expected a welcome"),
+        None,
+    )
+    .expect("error joining group");
+
+    // Rotated GroupInfos advertise different keys.
+    let group_info = |time: u64| {
+        alice_group
+            .rotate_external_keypair(provider.crypto(), &alice_signer, &FixedClock(time), true)
+            .expect("error exporting group info")
+            .into_verifiable_group_info()
+            .expect("expected a group info")
+    };
+    let external_pub = |time: u64| {
+        group_info(time)
+            .extensions()
+            .external_pub()
+            .expect("missing external pub")
+            .external_pub()
+            .clone()
+    };
+    assert_eq!(
+        external_pub(PERIOD_START),
+        external_pub(PERIOD_START + DAY - 1)
+    );
+    assert_ne!(external_pub(PERIOD_START), external_pub(PERIOD_START + DAY));
+
+    // Charlie joins with the GroupInfo of the last second of a period, so the
+    // external commit identifies the keypair of that period.
+    let time = PERIOD_START + DAY - 1;
+    let group_info = group_info(time);
+    assert_eq!(
+        group_info
+            .extensions()
+            .custom::<ExternalKeyIdExtension>()
+            .expect("error decoding the external key ID"),
+        Some(ExternalKeyIdExtension::new(PERIOD_START / DAY))
+    );
+    let (charlie_credential_with_key, _charlie_kpb, charlie_signer, _charlie_pk) =
+        setup_client("Charlie", ciphersuite, provider);
+    let (mut charlie_group, commit, _group_info) = MlsGroup::join_by_external_commit(
+        provider,
+        &charlie_signer,
+        None,
+        group_info,
+        &config,
+        b"",
+        charlie_credential_with_key,
+    )
+    .expect("error joining by external commit");
+    charlie_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    let commit = commit
+        .tls_serialize_detached()
+        .expect("error serializing commit");
+
+    // The delivery service decides whether the keypair is retired.
+    let message = MlsMessageView::parse(&commit).expect("error parsing commit");
+    policy
+        .check_external_commit(&message, time + DAY)
+        .expect("external commit within the grace period rejected");
+    assert_eq!(
+        policy
+            .check_external_commit(&message, time + 2 * DAY)
+            .expect_err("external commit to a retired keypair accepted"),
+        StageCommitError::RetiredExternalKey
+    );
+
+    // Alice's clock is still in the period of the keypair, Bob's clock is
+    // already past the grace period. Both accept the external commit and end
+    // up in the same epoch as Charlie.
+    for (group, clock) in [(&mut alice_group, time), (&mut bob_group, time + 2 * DAY)] {
+        let clock_provider = FixedClockProvider {
+            provider,
+            clock: FixedClock(clock),
+        };
+        let message = MlsMessageIn::tls_deserialize_exact(&commit)
+            .expect("error deserializing commit")
+            .into_protocol_message()
+            .expect("expected a protocol message");
+        let processed_message = group
+            .process_message(&clock_provider, message)
+            .expect("external commit rejected");
+        let ProcessedMessageContent::StagedCommitMessage(staged_commit) =
+            processed_message.into_content()
+        else {
+            panic!("expected a commit");
+        };
+        group
+            .merge_staged_commit(&clock_provider, *staged_commit)
+            .expect("error merging commit");
+        assert_eq!(
+            group.epoch_authenticator(),
+            charlie_group.epoch_authenticator()
+        );
+    }
+}
@@ -0,0 +1,124 @@
+use openmls_traits::{types::Ciphersuite, OpenMlsProvider};
+use rstest::*;
+use rstest_reuse::{self, *};
+
+use super::*;
+use crate::{
+    group::{
+        config::CryptoConfig,
+        errors::{ExternalCommitError, WelcomeError},
+        test_core_group::setup_client,
+    },
+    test_utils::*,
+};
+
+/// A policy that only accepts the group IDs of tenant A.
+fn tenant_a_policy() -> GroupIdPolicy {
+    GroupIdPolicy::new(|group_id| group_id.as_slice().starts_with(b"tenant-a/"))
+}
+
+#[test]
+fn group_id_policy() {
+    let policy = tenant_a_policy();
+    assert!(policy.accepts(&GroupId::from_slice(b"tenant-a/group")));
+    assert!(!policy.accepts(&GroupId::from_slice(b"tenant-b/group")));
+    assert!(GroupIdPolicy::default().accepts(&GroupId::from_slice(b"tenant-b/group")));
+
+    // Policies are only equal if they share the validation function.
+    assert_eq!(policy, policy.clone());
+    assert_ne!(policy, tenant_a_policy());
+    assert_ne!(policy, GroupIdPolicy::default());
+
+    let config = MlsGroupConfig::builder()
+        .group_id_policy(policy.clone())
+        .build();
+    assert_eq!(config.group_id_policy(), &policy);
+    assert_eq!(config.clone(), config);
+}
+
+#[apply(ciphersuites_and_providers)]
+fn group_id_policy_enforcement(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential, bob_kpb, _bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+    let (charlie_credential_with_key, _charlie_kpb, charlie_signer, _charlie_pk) =
+        setup_client("Charlie", ciphersuite, provider);
+
+    let config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .use_ratchet_tree_extension(true)
+        .build();
+    let tenant_a_config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .use_ratchet_tree_extension(true)
+        .group_id_policy(tenant_a_policy())
+        .build();
+
+    // === Groups are only created with accepted group IDs ===
+    assert_eq!(
+        MlsGroup::new(
+            provider,
+            &alice_signer,
+            &tenant_a_config,
+            alice_credential_with_key.clone(),
+        )
+        .expect_err("created a group with a random group ID"),
+        NewGroupError::InvalidGroupId
+    );
+    MlsGroup::new_with_group_id(
+        provider,
+        &alice_signer,
+        &tenant_a_config,
+        GroupId::from_slice(b"tenant-a/group"),
+        alice_credential_with_key.clone(),
+    )
+    .expect("error creating group");
+
+    // === Alice creates a group of tenant B without a policy ===
+    let mut alice_group = MlsGroup::new_with_group_id(
+        provider,
+        &alice_signer,
+        &config,
+        GroupId::from_slice(b"tenant-b/group"),
+        alice_credential_with_key,
+    )
+    .expect("error creating group");
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(provider, &alice_signer, &[bob_kpb.key_package().clone()])
+        .expect("error adding Bob")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    let welcome = welcome.into_welcome().expect("expected a welcome");
+
+    // === Bob rejects the Welcome message with the policy ===
+    assert_eq!(
+        MlsGroup::new_from_welcome(provider, &tenant_a_config, welcome.clone(), None)
+            .expect_err("joined a group of another tenant"),
+        WelcomeError::InvalidGroupId
+    );
+    // The key package wasn't consumed.
+    MlsGroup::new_from_welcome(provider, &config, welcome, None).expect("error joining group");
+
+    // === Charlie doesn't join the group by external commit ===
+    let group_info = alice_group
+        .export_group_info(provider, &alice_signer, true)
+        .expect("error exporting group info")
+        .into_verifiable_group_info()
+        .expect("expected a group info");
+    assert_eq!(
+        MlsGroup::join_by_external_commit(
+            provider,
+            &charlie_signer,
+            None,
+            group_info,
+            &tenant_a_config,
+            b"",
+            charlie_credential_with_key,
+        )
+        .expect_err("joined a group of another tenant"),
+        ExternalCommitError::InvalidGroupId
+    );
+}
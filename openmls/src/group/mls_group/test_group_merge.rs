@@ -0,0 +1,198 @@
+use openmls_traits::{types::Ciphersuite, OpenMlsProvider};
+use rstest::*;
+use rstest_reuse::{self, *};
+
+use super::*;
+use crate::{
+    extensions::GroupMergeExtension,
+    group::{config::CryptoConfig, errors::GroupMergeError, test_core_group::setup_client},
+    test_utils::*,
+};
+
+#[apply(ciphersuites_and_providers)]
+fn merge_divergent_groups(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential, bob_kpb, _bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+    let (charlie_credential_with_key, charlie_kpb, charlie_signer, _charlie_pk) =
+        setup_client("Charlie", ciphersuite, provider);
+    let (_dave_credential, dave_kpb, _dave_signer, _dave_pk) =
+        setup_client("Dave", ciphersuite, provider);
+
+    let mls_group_config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .use_ratchet_tree_extension(true)
+        .build();
+
+    // === Alice ends up in two divergent groups ===
+    let mut alice_group = MlsGroup::new_with_group_id(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        GroupId::from_slice(b"Group A"),
+        alice_credential_with_key.clone(),
+    )
+    .expect("error creating group");
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(
+            provider,
+            &alice_signer,
+            &[
+                bob_kpb.key_package().clone(),
+                dave_kpb.key_package().clone(),
+            ],
+        )
+        .expect("error adding Bob and Dave")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    let welcome = welcome.into_welcome().expect("expected a welcome");
+    let mut bob_group = MlsGroup::new_from_welcome(provider, &mls_group_config, welcome, None)
+        .expect("error joining group");
+
+    let mut divergent_group = MlsGroup::new_with_group_id(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        GroupId::from_slice(b"Group B"),
+        alice_credential_with_key,
+    )
+    .expect("error creating group");
+    divergent_group
+        .add_members(
+            provider,
+            &alice_signer,
+            &[charlie_kpb.key_package().clone()],
+        )
+        .expect("error adding Charlie");
+    divergent_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+
+    // === The group with more members wins ===
+    let winner =
+        MlsGroup::merge_winner([&divergent_group, &alice_group]).expect("expected a winner");
+    assert_eq!(winner.group_id(), alice_group.group_id());
+    assert!(MlsGroup::merge_winner([]).is_none());
+
+    // === Key packages must belong to the missing members ===
+    assert_eq!(
+        alice_group
+            .merge_group(provider, &alice_signer, &divergent_group, |_member| Some(
+                bob_kpb.key_package().clone()
+            ))
+            .expect_err("merged with a key package of the wrong member"),
+        GroupMergeError::KeyPackageMismatch
+    );
+    assert!(alice_group.pending_commit().is_none());
+
+    // === Alice merges the divergent group and adds Charlie ===
+    let charlie_key_package = KeyPackageBundle::new(
+        provider,
+        &charlie_signer,
+        ciphersuite,
+        charlie_credential_with_key.clone(),
+    )
+    .key_package()
+    .clone();
+    let group_merge = alice_group
+        .merge_group(provider, &alice_signer, &divergent_group, |member| {
+            (member.credential == charlie_credential_with_key.credential)
+                .then(|| charlie_key_package.clone())
+        })
+        .expect("error merging groups");
+    let linkage = GroupMergeExtension::new(
+        divergent_group.group_id().clone(),
+        divergent_group.epoch(),
+        divergent_group.tree_hash(),
+    );
+    assert_eq!(group_merge.linkage(), &linkage);
+    assert_eq!(
+        group_merge.added(),
+        std::slice::from_ref(&charlie_credential_with_key.credential)
+    );
+    assert!(group_merge.missing_key_packages().is_empty());
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    assert_eq!(alice_group.members().count(), 4);
+
+    // Bob sees the linkage in the commit.
+    let (commit, welcome, _group_info) = group_merge.into_messages();
+    let processed_message = bob_group
+        .process_message(
+            provider,
+            commit
+                .into_protocol_message()
+                .expect("expected a protocol message"),
+        )
+        .expect("error processing commit");
+    assert_eq!(
+        bob_group.group_merge_linkage(&processed_message),
+        Some(linkage)
+    );
+    match processed_message.into_content() {
+        ProcessedMessageContent::StagedCommitMessage(staged_commit) => bob_group
+            .merge_staged_commit(provider, *staged_commit)
+            .expect("error merging commit"),
+        _ => panic!("Expected a staged commit."),
+    }
+
+    // Charlie joins the winning group.
+    let welcome = welcome
+        .expect("expected a welcome")
+        .into_welcome()
+        .expect("expected a welcome");
+    let charlie_group = MlsGroup::new_from_welcome(provider, &mls_group_config, welcome, None)
+        .expect("error joining group");
+    assert_eq!(charlie_group.group_id(), alice_group.group_id());
+    assert_eq!(
+        charlie_group.epoch_authenticator(),
+        alice_group.epoch_authenticator()
+    );
+
+    // === Merging again doesn't add anyone, but records the merge ===
+    let group_merge = alice_group
+        .merge_group(provider, &alice_signer, &divergent_group, |_member| None)
+        .expect("error merging groups");
+    assert!(group_merge.added().is_empty());
+    assert!(group_merge.welcome().is_none());
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    let processed_message = bob_group
+        .process_message(
+            provider,
+            group_merge
+                .commit()
+                .clone()
+                .into_protocol_message()
+                .expect("expected a protocol message"),
+        )
+        .expect("error processing commit");
+    assert!(bob_group.group_merge_linkage(&processed_message).is_some());
+
+    // === Regular commits don't carry a linkage ===
+    let (commit, _welcome, _group_info) = alice_group
+        .self_update(provider, &alice_signer)
+        .expect("error updating")
+        .into_parts();
+    if let ProcessedMessageContent::StagedCommitMessage(staged_commit) =
+        processed_message.into_content()
+    {
+        bob_group
+            .merge_staged_commit(provider, *staged_commit)
+            .expect("error merging commit");
+    }
+    let processed_message = bob_group
+        .process_message(
+            provider,
+            commit
+                .into_protocol_message()
+                .expect("expected a protocol message"),
+        )
+        .expect("error processing commit");
+    assert!(bob_group.group_merge_linkage(&processed_message).is_none());
+}
@@ -0,0 +1,155 @@
+use openmls_traits::{types::Ciphersuite, OpenMlsProvider};
+use rstest::*;
+use rstest_reuse::{self, *};
+use tls_codec::{Deserialize as TlsDeserializeTrait, Serialize as TlsSerializeTrait};
+
+use super::*;
+use crate::{
+    group::{config::CryptoConfig, errors::HistoryError, test_core_group::setup_client},
+    test_utils::*,
+};
+
+#[apply(ciphersuites_and_providers)]
+fn share_history(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential, bob_kpb, _bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+
+    let mls_group_config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .use_ratchet_tree_extension(true)
+        .history_epochs(3)
+        .build();
+
+    // === Alice creates a group and moves it through a few epochs ===
+    let mut alice_group = MlsGroup::new_with_group_id(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        GroupId::from_slice(b"Test Group"),
+        alice_credential_with_key,
+    )
+    .expect("error creating group");
+    assert_eq!(alice_group.history_keys().len(), 1);
+    for _ in 0..3 {
+        alice_group
+            .self_update(provider, &alice_signer)
+            .expect("error updating");
+        alice_group
+            .merge_pending_commit(provider)
+            .expect("error merging commit");
+    }
+
+    // Only the keys of the last three epochs are kept.
+    let epochs: Vec<u64> = alice_group
+        .history_keys()
+        .iter()
+        .map(|history_key| history_key.epoch().as_u64())
+        .collect();
+    assert_eq!(epochs, vec![1, 2, 3]);
+    assert!(alice_group.history_key(GroupEpoch::from(0)).is_none());
+
+    // === Alice adds Bob and shares the history of two epochs ===
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(provider, &alice_signer, &[bob_kpb.key_package().clone()])
+        .expect("error adding Bob")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    let history_bundle = alice_group
+        .export_history(provider, GroupEpoch::from(2)..GroupEpoch::from(4))
+        .expect("error exporting history");
+    assert_eq!(history_bundle.epoch(), alice_group.epoch());
+
+    let welcome = welcome.into_welcome().expect("expected a welcome");
+    let mut bob_group = MlsGroup::new_from_welcome(provider, &mls_group_config, welcome, None)
+        .expect("error joining group");
+    assert_eq!(bob_group.history_keys().len(), 1);
+    assert_eq!(
+        bob_group.history_key(bob_group.epoch()),
+        alice_group.history_key(alice_group.epoch())
+    );
+
+    // A modified bundle is rejected.
+    let mut serialized = history_bundle
+        .tls_serialize_detached()
+        .expect("error serializing bundle");
+    assert_eq!(
+        HistoryBundle::tls_deserialize_exact(&serialized).expect("error deserializing bundle"),
+        history_bundle
+    );
+    let last = serialized.len() - 1;
+    serialized[last] ^= 0xff;
+    let modified_bundle =
+        HistoryBundle::tls_deserialize_exact(&serialized).expect("error deserializing bundle");
+    assert_eq!(
+        bob_group.import_history(provider.crypto(), &modified_bundle),
+        Err(HistoryError::DecryptionFailed)
+    );
+
+    assert_eq!(
+        bob_group
+            .import_history(provider.crypto(), &history_bundle)
+            .expect("error importing history"),
+        2
+    );
+    for epoch in [2, 3] {
+        let epoch = GroupEpoch::from(epoch);
+        assert!(bob_group.history_key(epoch).is_some());
+        assert_eq!(bob_group.history_key(epoch), alice_group.history_key(epoch));
+    }
+    assert!(bob_group.history_key(GroupEpoch::from(1)).is_none());
+
+    // Importing the bundle again doesn't add any keys.
+    assert_eq!(
+        bob_group
+            .import_history(provider.crypto(), &history_bundle)
+            .expect("error importing history"),
+        0
+    );
+
+    // === Bundles can only be imported in the epoch they were exported in ===
+    alice_group
+        .self_update(provider, &alice_signer)
+        .expect("error updating");
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    assert_eq!(
+        alice_group.import_history(provider.crypto(), &history_bundle),
+        Err(HistoryError::WrongEpoch)
+    );
+    assert_eq!(
+        alice_group
+            .export_history(provider, GroupEpoch::from(0)..GroupEpoch::from(2))
+            .expect_err("exported history keys that were discarded"),
+        HistoryError::NoHistoryKeys
+    );
+}
+
+#[apply(ciphersuites_and_providers)]
+fn history_sharing_disabled(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+
+    let mls_group_config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .build();
+    let alice_group = MlsGroup::new(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        alice_credential_with_key,
+    )
+    .expect("error creating group");
+
+    assert!(alice_group.history_keys().is_empty());
+    assert_eq!(
+        alice_group
+            .export_history(provider, ..)
+            .expect_err("exported history while disabled"),
+        HistoryError::HistorySharingDisabled
+    );
+}
@@ -0,0 +1,104 @@
+use openmls_traits::{types::Ciphersuite, OpenMlsProvider};
+use rstest::*;
+use rstest_reuse::{self, *};
+
+use super::*;
+use crate::{
+    group::{config::CryptoConfig, test_core_group::setup_client},
+    test_utils::*,
+};
+
+#[apply(ciphersuites_and_providers)]
+fn stale_artifacts(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential, bob_kpb, bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+
+    let mls_group_config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .use_ratchet_tree_extension(true)
+        .build();
+
+    let mut alice_group = MlsGroup::new_with_group_id(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        GroupId::from_slice(b"Test Group"),
+        alice_credential_with_key,
+    )
+    .expect("error creating group");
+    assert!(alice_group.stale_artifacts().is_none());
+
+    // === Alice adds Bob ===
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(provider, &alice_signer, &[bob_kpb.key_package().clone()])
+        .expect("error adding Bob")
+        .into_parts();
+    // Nothing is stale before the commit is merged.
+    assert!(alice_group.stale_artifacts().is_none());
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    let stale_artifacts = alice_group
+        .stale_artifacts()
+        .expect("no stale artifacts after merging a commit");
+    assert_eq!(stale_artifacts.epoch(), alice_group.epoch());
+    assert!(stale_artifacts.group_info());
+    assert!(stale_artifacts.ratchet_tree());
+    assert!(stale_artifacts.external_pub());
+
+    let welcome = welcome.into_welcome().expect("expected a welcome");
+    let mut bob_group = MlsGroup::new_from_welcome(provider, &mls_group_config, welcome, None)
+        .expect("error joining group");
+    assert!(bob_group.stale_artifacts().is_none());
+
+    // === Application messages don't make anything stale ===
+    let message = alice_group
+        .create_message(provider, &alice_signer, b"Hello")
+        .expect("error creating message");
+    bob_group
+        .process_message(
+            provider,
+            message
+                .into_protocol_message()
+                .expect("expected a protocol message"),
+        )
+        .expect("error processing message");
+    assert!(bob_group.stale_artifacts().is_none());
+
+    // === Bob updates ===
+    let (commit, _welcome, _group_info) = bob_group
+        .self_update(provider, &bob_signer)
+        .expect("error updating")
+        .into_parts();
+    bob_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    let processed_message = alice_group
+        .process_message(
+            provider,
+            commit
+                .into_protocol_message()
+                .expect("expected a protocol message"),
+        )
+        .expect("error processing commit");
+    match processed_message.into_content() {
+        ProcessedMessageContent::StagedCommitMessage(staged_commit) => alice_group
+            .merge_staged_commit(provider, *staged_commit)
+            .expect("error merging commit"),
+        _ => panic!("Expected a staged commit."),
+    }
+    assert_eq!(alice_group.stale_artifacts(), bob_group.stale_artifacts());
+    let stale_artifacts = alice_group
+        .stale_artifacts()
+        .expect("no stale artifacts after merging a commit");
+    assert_eq!(stale_artifacts.epoch(), alice_group.epoch());
+    assert!(stale_artifacts.ratchet_tree());
+
+    // The stale artifacts are persisted with the group.
+    let serialized = serde_json::to_vec(&alice_group).expect("error serializing group");
+    let restored: MlsGroup =
+        serde_json::from_slice(&serialized).expect("error deserializing group");
+    assert_eq!(restored.stale_artifacts(), alice_group.stale_artifacts());
+}
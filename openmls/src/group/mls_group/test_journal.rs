@@ -0,0 +1,123 @@
+use openmls_traits::{crypto::OpenMlsCrypto, types::Ciphersuite, OpenMlsProvider};
+use rstest::*;
+use rstest_reuse::{self, *};
+use tls_codec::Serialize;
+
+use super::*;
+use crate::{
+    error::StableErrorCode,
+    group::{config::CryptoConfig, errors::RemoveMembersError, test_core_group::setup_client},
+    test_utils::*,
+};
+
+#[apply(ciphersuites_and_providers)]
+fn operation_journal(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential, bob_kpb, _bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+
+    let crypto_config = CryptoConfig::with_default_version(ciphersuite);
+    let alice_config = MlsGroupConfig::builder()
+        .crypto_config(crypto_config)
+        .use_ratchet_tree_extension(true)
+        .operation_journal(3)
+        .build();
+    let bob_config = MlsGroupConfig::builder()
+        .crypto_config(crypto_config)
+        .use_ratchet_tree_extension(true)
+        .build();
+
+    let mut alice_group = MlsGroup::new(
+        provider,
+        &alice_signer,
+        &alice_config,
+        alice_credential_with_key,
+    )
+    .expect("error creating group");
+    assert_eq!(alice_group.operation_journal().count(), 0);
+
+    // === Alice adds Bob ===
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(provider, &alice_signer, &[bob_kpb.key_package().clone()])
+        .expect("error adding Bob")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    let welcome = welcome.into_welcome().expect("expected a welcome");
+    let mut bob_group = MlsGroup::new_from_welcome(provider, &bob_config, welcome, None)
+        .expect("error joining group");
+
+    let entries: Vec<JournalEntry> = alice_group.operation_journal().cloned().collect();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].operation(), OperationType::AddMembers);
+    assert_eq!(entries[0].epoch().as_u64(), 0);
+    assert_eq!(entries[0].error(), None);
+    let key_package_hash = provider
+        .crypto()
+        .hash(
+            ciphersuite.hash_algorithm(),
+            &bob_kpb.key_package().tls_serialize_detached().unwrap(),
+        )
+        .unwrap();
+    assert_eq!(entries[0].parameters_hash(), key_package_hash.as_slice());
+    assert_eq!(entries[1].operation(), OperationType::MergeCommit);
+    assert_eq!(entries[1].epoch().as_u64(), 1);
+    assert!(entries[1].parameters_hash().is_empty());
+
+    // === A failed operation is recorded with its error code ===
+    let error = alice_group
+        .remove_members(provider, &alice_signer, &[])
+        .expect_err("removing no members should fail");
+    assert!(matches!(error, RemoveMembersError::EmptyInput(_)));
+    let entry = alice_group.operation_journal().last().unwrap().clone();
+    assert_eq!(entry.operation(), OperationType::RemoveMembers);
+    assert_eq!(entry.error(), Some(error.error_code()));
+
+    // === The oldest entries are discarded ===
+    let (commit, _welcome, _group_info) = alice_group
+        .self_update(provider, &alice_signer)
+        .expect("error updating")
+        .into_parts();
+    let operations: Vec<OperationType> = alice_group
+        .operation_journal()
+        .map(JournalEntry::operation)
+        .collect();
+    assert_eq!(
+        operations,
+        vec![
+            OperationType::MergeCommit,
+            OperationType::RemoveMembers,
+            OperationType::SelfUpdate
+        ]
+    );
+
+    // === The journal is part of the debug dump and persisted ===
+    let dump = alice_group.debug_dump().expect("error dumping group");
+    assert_eq!(dump.operation_journal.len(), 3);
+    assert_eq!(dump.operation_journal[1].operation, "remove_members");
+    assert_eq!(
+        dump.operation_journal[1].error,
+        Some(error.error_code().code())
+    );
+    alice_group
+        .save(provider.key_store())
+        .expect("error saving group");
+    let loaded_group =
+        MlsGroup::load(alice_group.group_id(), provider.key_store()).expect("error loading group");
+    assert!(loaded_group
+        .operation_journal()
+        .eq(alice_group.operation_journal()));
+
+    // === Bob doesn't keep a journal ===
+    bob_group
+        .process_message(provider, commit.into_protocol_message().unwrap())
+        .expect("error processing commit");
+    assert_eq!(bob_group.operation_journal().count(), 0);
+    assert!(bob_group
+        .debug_dump()
+        .expect("error dumping group")
+        .operation_journal
+        .is_empty());
+}
@@ -0,0 +1,197 @@
+use openmls_traits::{types::Ciphersuite, OpenMlsProvider};
+use rstest::*;
+use rstest_reuse::{self, *};
+
+use super::*;
+use crate::{
+    binary_tree::LeafNodeIndex,
+    credentials::CredentialWithKey,
+    group::{
+        config::{CryptoConfig, ValidationPolicy},
+        errors::{CreateCommitError, ProposalValidationError},
+        test_core_group::setup_client,
+    },
+    test_utils::*,
+};
+
+/// Asserts that the incrementally updated key index of `group` matches the
+/// one that is built from scratch when the group is loaded.
+fn assert_key_index_consistent(group: &MlsGroup) {
+    let serialized = serde_json::to_vec(group).expect("error serializing group");
+    let loaded: MlsGroup = serde_json::from_slice(&serialized).expect("error loading group");
+    assert_eq!(
+        group.group.public_group().treesync().key_index(),
+        loaded.group.public_group().treesync().key_index()
+    );
+}
+
+#[apply(ciphersuites_and_providers)]
+fn key_index(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential, bob_kpb, bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+    let (_charlie_credential, charlie_kpb, _charlie_signer, _charlie_pk) =
+        setup_client("Charlie", ciphersuite, provider);
+
+    let config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .use_ratchet_tree_extension(true)
+        .build();
+
+    let mut alice_group =
+        MlsGroup::new(provider, &alice_signer, &config, alice_credential_with_key)
+            .expect("error creating group");
+    assert_key_index_consistent(&alice_group);
+
+    // === Alice adds Bob and Charlie ===
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(
+            provider,
+            &alice_signer,
+            &[
+                bob_kpb.key_package().clone(),
+                charlie_kpb.key_package().clone(),
+            ],
+        )
+        .expect("error adding Bob and Charlie")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    assert_key_index_consistent(&alice_group);
+    let welcome = welcome.into_welcome().expect("expected a welcome");
+    let mut bob_group =
+        MlsGroup::new_from_welcome(provider, &config, welcome, None).expect("error joining group");
+    assert_key_index_consistent(&bob_group);
+
+    // === Bob updates his leaf and Alice merges the commit ===
+    let bob_encryption_key = bob_kpb.key_package().leaf_node().encryption_key().clone();
+    let (commit, _welcome, _group_info) = bob_group
+        .self_update(provider, &bob_signer)
+        .expect("error updating")
+        .into_parts();
+    bob_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    let processed = alice_group
+        .process_message(provider, commit.into_protocol_message().unwrap())
+        .expect("error processing commit");
+    match processed.into_content() {
+        ProcessedMessageContent::StagedCommitMessage(staged_commit) => alice_group
+            .merge_staged_commit(provider, *staged_commit)
+            .expect("error merging commit"),
+        _ => panic!("expected a commit"),
+    }
+    assert_key_index_consistent(&alice_group);
+    let key_index = alice_group.group.public_group().treesync().key_index();
+    assert_eq!(
+        key_index
+            .encryption_key_leaves(bob_encryption_key.as_slice())
+            .count(),
+        0
+    );
+    let bob_leaf = bob_group.own_leaf_node().expect("missing own leaf");
+    assert_eq!(
+        key_index
+            .encryption_key_leaves(bob_leaf.encryption_key().as_slice())
+            .collect::<Vec<_>>(),
+        vec![LeafNodeIndex::new(1)]
+    );
+
+    // === Alice removes Charlie, which truncates the tree ===
+    alice_group
+        .remove_members(provider, &alice_signer, &[LeafNodeIndex::new(2)])
+        .expect("error removing Charlie");
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    assert_key_index_consistent(&alice_group);
+    assert_eq!(
+        alice_group
+            .group
+            .public_group()
+            .treesync()
+            .key_index()
+            .signature_key_leaves(
+                charlie_kpb
+                    .key_package()
+                    .leaf_node()
+                    .signature_key()
+                    .as_slice()
+            )
+            .count(),
+        0
+    );
+    assert!(alice_group.key_uniqueness_violations().is_empty());
+}
+
+#[apply(ciphersuites_and_providers)]
+fn key_uniqueness_is_enforced(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential, bob_kpb, _bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+    let (charlie_credential_with_key, _charlie_kpb, _charlie_signer, _charlie_pk) =
+        setup_client("Charlie", ciphersuite, provider);
+
+    let config_with_policy = |policy: ValidationPolicy| {
+        MlsGroupConfig::builder()
+            .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+            .validation_policy(policy)
+            .build()
+    };
+
+    let mut alice_group = MlsGroup::new(
+        provider,
+        &alice_signer,
+        &config_with_policy(ValidationPolicy::strict()),
+        alice_credential_with_key.clone(),
+    )
+    .expect("error creating group");
+    alice_group
+        .add_members(provider, &alice_signer, &[bob_kpb.key_package().clone()])
+        .expect("error adding Bob");
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+
+    // Charlie's key package uses Alice's signature key.
+    let charlie_key_package = KeyPackage::builder()
+        .build(
+            CryptoConfig::with_default_version(ciphersuite),
+            provider,
+            &alice_signer,
+            CredentialWithKey {
+                credential: charlie_credential_with_key.credential,
+                signature_key: alice_credential_with_key.signature_key.clone(),
+            },
+        )
+        .expect("error creating key package");
+
+    // === The collision is reported with Alice's leaf index ===
+    assert_eq!(
+        alice_group
+            .add_members(
+                provider,
+                &alice_signer,
+                std::slice::from_ref(&charlie_key_package),
+            )
+            .expect_err("added a member with a used signature key"),
+        AddMembersError::CreateCommitError(CreateCommitError::ProposalValidationError(
+            ProposalValidationError::DuplicateSignatureKey(Some(LeafNodeIndex::new(0)))
+        ))
+    );
+
+    // === The lenient profile doesn't relax the check ===
+    alice_group.set_configuration(&config_with_policy(ValidationPolicy::lenient()));
+    assert_eq!(
+        alice_group
+            .add_members(provider, &alice_signer, &[charlie_key_package])
+            .expect_err("added a member with a used signature key"),
+        AddMembersError::CreateCommitError(CreateCommitError::ProposalValidationError(
+            ProposalValidationError::DuplicateSignatureKey(Some(LeafNodeIndex::new(0)))
+        ))
+    );
+    assert!(alice_group.key_uniqueness_violations().is_empty());
+}
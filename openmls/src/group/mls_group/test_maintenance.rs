@@ -0,0 +1,191 @@
+use openmls_traits::{clock::OpenMlsClock, types::Ciphersuite, OpenMlsProvider};
+use rstest::*;
+use rstest_reuse::{self, *};
+
+use super::*;
+use crate::{
+    group::{config::CryptoConfig, test_core_group::setup_client},
+    test_utils::*,
+};
+
+const WEEK: u64 = 7 * 24 * 60 * 60;
+
+struct FixedClock(u64);
+
+impl OpenMlsClock for FixedClock {
+    fn now(&self) -> u64 {
+        self.0
+    }
+}
+
+#[apply(ciphersuites_and_providers)]
+fn scheduled_self_updates(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential, bob_kpb, bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+
+    let alice_config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .use_ratchet_tree_extension(true)
+        .self_update_policy(SelfUpdatePolicy::new(WEEK, SelfUpdateMode::Commit))
+        .build();
+    let bob_config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .use_ratchet_tree_extension(true)
+        .self_update_policy(SelfUpdatePolicy::new(WEEK, SelfUpdateMode::Proposal))
+        .build();
+
+    // === Alice creates a group with Bob ===
+    let mut alice_group = MlsGroup::new(
+        provider,
+        &alice_signer,
+        &alice_config,
+        alice_credential_with_key,
+    )
+    .expect("error creating group");
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(provider, &alice_signer, &[bob_kpb.key_package().clone()])
+        .expect("error adding Bob")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    let welcome = welcome.into_welcome().expect("expected a welcome");
+    let mut bob_group = MlsGroup::new_from_welcome(provider, &bob_config, welcome, None)
+        .expect("error joining group");
+
+    // === The first maintenance starts the clock ===
+    let start = 1_700_000_000;
+    assert!(alice_group.own_leaf_updated_at().is_none());
+    for (group, signer) in [
+        (&mut alice_group, &alice_signer),
+        (&mut bob_group, &bob_signer),
+    ] {
+        assert!(group
+            .maintenance(provider, signer, &FixedClock(start))
+            .expect("error during maintenance")
+            .is_none());
+        assert_eq!(group.own_leaf_updated_at(), Some(start));
+    }
+    assert!(alice_group
+        .maintenance(provider, &alice_signer, &FixedClock(start + WEEK - 1))
+        .expect("error during maintenance")
+        .is_none());
+
+    // === Alice's leaf is too old and she commits an update ===
+    let commit = match alice_group
+        .maintenance(provider, &alice_signer, &FixedClock(start + WEEK))
+        .expect("error during maintenance")
+    {
+        Some(MaintenanceMessage::Commit { commit, .. }) => commit,
+        other => panic!("Expected a commit, got {other:?}"),
+    };
+    // The commit is pending, so no further update is created.
+    assert!(alice_group
+        .maintenance(provider, &alice_signer, &FixedClock(start + WEEK))
+        .expect("error during maintenance")
+        .is_none());
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    let processed_message = bob_group
+        .process_message(
+            provider,
+            commit
+                .into_protocol_message()
+                .expect("expected a protocol message"),
+        )
+        .expect("error processing commit");
+    match processed_message.into_content() {
+        ProcessedMessageContent::StagedCommitMessage(staged_commit) => bob_group
+            .merge_staged_commit(provider, *staged_commit)
+            .expect("error merging commit"),
+        _ => panic!("Expected a staged commit."),
+    }
+
+    // The new leaf is seen at the next maintenance.
+    assert!(alice_group
+        .maintenance(provider, &alice_signer, &FixedClock(start + WEEK + 1))
+        .expect("error during maintenance")
+        .is_none());
+    assert_eq!(alice_group.own_leaf_updated_at(), Some(start + WEEK + 1));
+
+    // === Bob's leaf is too old and he proposes an update ===
+    let proposal = match bob_group
+        .maintenance(provider, &bob_signer, &FixedClock(start + WEEK))
+        .expect("error during maintenance")
+    {
+        Some(MaintenanceMessage::Proposal { proposal, .. }) => proposal,
+        other => panic!("Expected a proposal, got {other:?}"),
+    };
+    // The proposal is pending, so no further update is created.
+    assert!(bob_group
+        .maintenance(provider, &bob_signer, &FixedClock(start + WEEK))
+        .expect("error during maintenance")
+        .is_none());
+
+    // Alice commits Bob's proposal.
+    let processed_message = alice_group
+        .process_message(
+            provider,
+            proposal
+                .into_protocol_message()
+                .expect("expected a protocol message"),
+        )
+        .expect("error processing proposal");
+    match processed_message.into_content() {
+        ProcessedMessageContent::ProposalMessage(proposal) => {
+            alice_group.store_pending_proposal(*proposal)
+        }
+        _ => panic!("Expected a proposal."),
+    }
+    let (commit, _welcome, _group_info) = alice_group
+        .commit_to_pending_proposals(provider, &alice_signer)
+        .expect("error committing proposals")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    let processed_message = bob_group
+        .process_message(
+            provider,
+            commit
+                .into_protocol_message()
+                .expect("expected a protocol message"),
+        )
+        .expect("error processing commit");
+    match processed_message.into_content() {
+        ProcessedMessageContent::StagedCommitMessage(staged_commit) => bob_group
+            .merge_staged_commit(provider, *staged_commit)
+            .expect("error merging commit"),
+        _ => panic!("Expected a staged commit."),
+    }
+    assert!(bob_group.own_leaf_updated_at().is_none());
+    assert!(bob_group
+        .maintenance(provider, &bob_signer, &FixedClock(start + 2 * WEEK - 1))
+        .expect("error during maintenance")
+        .is_none());
+    assert_eq!(bob_group.own_leaf_updated_at(), Some(start + 2 * WEEK - 1));
+
+    // === Without a policy, the leaf is never updated ===
+    let config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .build();
+    assert_eq!(config.self_update_policy(), SelfUpdatePolicy::default());
+    let (charlie_credential_with_key, _charlie_kpb, charlie_signer, _charlie_pk) =
+        setup_client("Charlie", ciphersuite, provider);
+    let mut charlie_group = MlsGroup::new(
+        provider,
+        &charlie_signer,
+        &config,
+        charlie_credential_with_key,
+    )
+    .expect("error creating group");
+    for now in [start, start + 52 * WEEK] {
+        assert!(charlie_group
+            .maintenance(provider, &charlie_signer, &FixedClock(now))
+            .expect("error during maintenance")
+            .is_none());
+    }
+}
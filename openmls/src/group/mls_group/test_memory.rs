@@ -0,0 +1,149 @@
+use openmls_traits::{types::Ciphersuite, OpenMlsProvider};
+use rstest::*;
+use rstest_reuse::{self, *};
+
+use super::*;
+use crate::{
+    group::{config::CryptoConfig, errors::MemoryLimitError, test_core_group::setup_client},
+    test_utils::*,
+};
+
+fn process_commit(
+    group: &mut MlsGroup,
+    provider: &impl OpenMlsProvider,
+    commit: MlsMessageOut,
+) -> Result<(), ProcessMessageError> {
+    let processed_message = group.process_message(
+        provider,
+        commit
+            .into_protocol_message()
+            .expect("expected a protocol message"),
+    )?;
+    match processed_message.into_content() {
+        ProcessedMessageContent::StagedCommitMessage(staged_commit) => group
+            .merge_staged_commit(provider, *staged_commit)
+            .expect("error merging commit"),
+        _ => panic!("Expected a staged commit."),
+    }
+    Ok(())
+}
+
+#[apply(ciphersuites_and_providers)]
+fn memory_limits(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential, bob_kpb, _bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+    let (_charlie_credential, charlie_kpb, _charlie_signer, _charlie_pk) =
+        setup_client("Charlie", ciphersuite, provider);
+
+    let mls_group_config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .use_ratchet_tree_extension(true)
+        .max_past_epochs(3)
+        .build();
+
+    let mut alice_group = MlsGroup::new_with_group_id(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        GroupId::from_slice(b"Test Group"),
+        alice_credential_with_key,
+    )
+    .expect("error creating group");
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(provider, &alice_signer, &[bob_kpb.key_package().clone()])
+        .expect("error adding Bob")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    let welcome = welcome.into_welcome().expect("expected a welcome");
+    let mut bob_group = MlsGroup::new_from_welcome(provider, &mls_group_config, welcome, None)
+        .expect("error joining group");
+
+    let usage = bob_group.memory_usage();
+    assert!(usage.tree() > 0);
+    assert!(usage.epoch_secrets() > 0);
+    assert_eq!(usage.past_epoch_secrets(), 0);
+    assert_eq!(usage.pending_proposals(), 0);
+    assert_eq!(usage.total(), usage.tree() + usage.epoch_secrets());
+    assert_eq!(
+        bob_group.group.public_group().memory_usage().tree(),
+        usage.tree()
+    );
+
+    // Without limits, the secrets of past epochs are kept.
+    let (commit, _welcome, _group_info) = alice_group
+        .self_update(provider, &alice_signer)
+        .expect("error updating")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    process_commit(&mut bob_group, provider, commit).expect("error processing commit");
+    assert!(bob_group.memory_usage().past_epoch_secrets() > 0);
+
+    // With a limit, the secrets of past epochs are dropped on the next merge.
+    let limited_config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .use_ratchet_tree_extension(true)
+        .max_past_epochs(3)
+        .memory_limits(MemoryLimits::new(None, Some(0), Some(0)))
+        .build();
+    bob_group.set_configuration(&limited_config);
+    let (commit, _welcome, _group_info) = alice_group
+        .self_update(provider, &alice_signer)
+        .expect("error updating")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    process_commit(&mut bob_group, provider, commit).expect("error processing commit");
+    assert_eq!(bob_group.memory_usage().past_epoch_secrets(), 0);
+
+    // Proposals that exceed the limit are rejected.
+    let (proposal, proposal_ref) = alice_group
+        .propose_self_update(provider, &alice_signer, None)
+        .expect("error proposing update");
+    let err = bob_group
+        .process_message(
+            provider,
+            proposal
+                .into_protocol_message()
+                .expect("expected a protocol message"),
+        )
+        .expect_err("processed a proposal over the limit");
+    assert_eq!(
+        err,
+        ProcessMessageError::MemoryLimitError(MemoryLimitError::PendingProposalsLimitExceeded)
+    );
+    alice_group
+        .remove_pending_proposal(proposal_ref)
+        .expect("error removing proposal");
+
+    // Commits that grow the tree beyond the limit are rejected.
+    let tree_limit = bob_group.memory_usage().tree();
+    let limited_config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .use_ratchet_tree_extension(true)
+        .max_past_epochs(3)
+        .memory_limits(MemoryLimits::new(Some(tree_limit), None, None))
+        .build();
+    bob_group.set_configuration(&limited_config);
+    let (commit, _welcome, _group_info) = alice_group
+        .add_members(
+            provider,
+            &alice_signer,
+            &[charlie_kpb.key_package().clone()],
+        )
+        .expect("error adding Charlie")
+        .into_parts();
+    let err = process_commit(&mut bob_group, provider, commit)
+        .expect_err("processed a commit over the limit");
+    assert_eq!(
+        err,
+        ProcessMessageError::MemoryLimitError(MemoryLimitError::TreeLimitExceeded)
+    );
+    assert_eq!(bob_group.memory_usage().tree(), tree_limit);
+}
@@ -1,9 +1,14 @@
 use core_group::test_core_group::setup_client;
 use openmls_rust_crypto::OpenMlsRustCrypto;
-use openmls_traits::{key_store::OpenMlsKeyStore, OpenMlsProvider};
+use openmls_traits::{
+    key_store::{detect, unwrap_blob, BlobHeader, MlsEntityId, OpenMlsKeyStore},
+    OpenMlsProvider,
+};
 
+use super::ser::MLS_GROUP_STATE_VERSION;
 use crate::{
     binary_tree::LeafNodeIndex,
+    error::{ErrorSeverity, StableErrorCode},
     framing::*,
     group::{config::CryptoConfig, errors::*, *},
     key_packages::*,
@@ -56,6 +61,143 @@ fn test_mls_group_persistence(ciphersuite: Ciphersuite, provider: &impl OpenMlsP
     );
 }
 
+#[apply(ciphersuites_and_providers)]
+fn state_version(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let alice_group = MlsGroup::new(
+        provider,
+        &alice_signer,
+        &MlsGroupConfig::test_default(ciphersuite),
+        alice_credential_with_key,
+    )
+    .expect("error creating group");
+
+    let mut state = serde_json::to_value(&alice_group).expect("error serializing group");
+    assert_eq!(state["state_version"], MLS_GROUP_STATE_VERSION);
+    assert!(state.get("resumption_psk_store").is_none());
+
+    // Version 1 states contain a copy of the resumption PSK store. They are
+    // loaded and migrated, with or without a version.
+    let mut v1_state = state.clone();
+    v1_state["state_version"] = 1.into();
+    v1_state["resumption_psk_store"] = state["group"]["resumption_psk_store"].clone();
+    let mut v1_blob = b"OMLS".to_vec();
+    v1_blob.extend_from_slice(&1u16.to_be_bytes());
+    v1_blob.push(MlsEntityId::GroupState.content_type());
+    v1_blob.extend(serde_json::to_vec(&v1_state).expect("error serializing state"));
+    let loaded_group: MlsGroup =
+        serde_json::from_slice(unwrap_blob::<MlsGroup>(&v1_blob).expect("version 1 blob rejected"))
+            .expect("error loading version 1 state");
+    assert_eq!(loaded_group.epoch(), alice_group.epoch());
+    assert_eq!(
+        loaded_group.export_secret(provider.crypto(), "test", &[], 32),
+        alice_group.export_secret(provider.crypto(), "test", &[], 32)
+    );
+    v1_state
+        .as_object_mut()
+        .unwrap()
+        .remove("state_version")
+        .unwrap();
+    let loaded_group: MlsGroup =
+        serde_json::from_value(v1_state).expect("error loading unversioned state");
+    assert_eq!(loaded_group.epoch(), alice_group.epoch());
+
+    // States from a newer version are rejected.
+    state["state_version"] = (MLS_GROUP_STATE_VERSION + 1).into();
+    let error = serde_json::from_value::<MlsGroup>(state)
+        .err()
+        .expect("loaded a state from a newer version");
+    assert!(error
+        .to_string()
+        .contains("unsupported group state version"));
+
+    // Persisted blobs describe their content.
+    let blob = BlobHeader::for_entity::<MlsGroup>()
+        .wrap(&serde_json::to_vec(&alice_group).expect("error serializing group"));
+    let (header, content) = detect(&blob).expect("error detecting blob");
+    assert_eq!(header.entity(), MlsEntityId::GroupState);
+    assert_eq!(header.version(), MLS_GROUP_STATE_VERSION);
+    assert_eq!(unwrap_blob::<MlsGroup>(&blob), Some(content));
+    assert_eq!(unwrap_blob::<KeyPackage>(&blob), None);
+    assert!(serde_json::from_slice::<MlsGroup>(content).is_ok());
+}
+
+#[apply(ciphersuites_and_providers)]
+fn recoverable_errors_leave_state_untouched(
+    ciphersuite: Ciphersuite,
+    provider: &impl OpenMlsProvider,
+) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential, bob_kpb, _bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+
+    let mls_group_config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .wire_format_policy(PURE_PLAINTEXT_WIRE_FORMAT_POLICY)
+        .use_ratchet_tree_extension(true)
+        .build();
+    let mut alice_group = MlsGroup::new(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        alice_credential_with_key,
+    )
+    .expect("error creating group");
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(provider, &alice_signer, &[bob_kpb.key_package().clone()])
+        .expect("error adding Bob")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    let mut bob_group = MlsGroup::new_from_welcome(
+        provider,
+        &mls_group_config,
+        welcome.into_welcome().expect("expected a welcome"),
+        None,
+    )
+    .expect("error joining group");
+
+    // Alice commits twice, Bob receives the second commit first.
+    let mut commits = Vec::new();
+    for _ in 0..2 {
+        let (commit, _welcome, _group_info) = alice_group
+            .self_update(provider, &alice_signer)
+            .expect("error updating")
+            .into_parts();
+        alice_group
+            .merge_pending_commit(provider)
+            .expect("error merging commit");
+        commits.push(commit.into_protocol_message().unwrap());
+    }
+
+    let state = serde_json::to_vec(&bob_group).expect("error serializing group");
+    let error = bob_group
+        .process_message(provider, commits[1].clone())
+        .expect_err("processed a commit from a future epoch");
+    assert_eq!(error.error_severity(), ErrorSeverity::Recoverable);
+    assert_eq!(
+        serde_json::to_vec(&bob_group).expect("error serializing group"),
+        state
+    );
+
+    // Bob can continue with the group.
+    for commit in commits {
+        let processed_message = bob_group
+            .process_message(provider, commit)
+            .expect("error processing commit");
+        match processed_message.into_content() {
+            ProcessedMessageContent::StagedCommitMessage(staged_commit) => bob_group
+                .merge_staged_commit(provider, *staged_commit)
+                .expect("error merging commit"),
+            _ => panic!("Expected a staged commit."),
+        }
+    }
+    assert_eq!(bob_group.epoch(), alice_group.epoch());
+}
+
 // This tests if the remover is correctly passed to the callback when one member
 // issues a RemoveProposal and another members issues the next Commit.
 #[apply(ciphersuites_and_providers)]
@@ -87,7 +229,8 @@ fn remover(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     // === Alice adds Bob ===
     let (_queued_message, welcome, _group_info) = alice_group
         .add_members(provider, &alice_signer, &[bob_kpb.key_package().clone()])
-        .expect("Could not add member to group.");
+        .expect("Could not add member to group.")
+        .into_parts();
 
     alice_group
         .merge_pending_commit(provider)
@@ -104,7 +247,8 @@ fn remover(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     // === Bob adds Charlie ===
     let (queued_messages, welcome, _group_info) = bob_group
         .add_members(provider, &bob_signer, &[charlie_kpb.key_package().clone()])
-        .unwrap();
+        .unwrap()
+        .into_parts();
 
     let alice_processed_message = alice_group
         .process_message(
@@ -176,7 +320,8 @@ fn remover(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     // Charlie commits
     let (_queued_messages, _welcome, _group_info) = charlie_group
         .commit_to_pending_proposals(provider, &charlie_signer)
-        .expect("Could not commit proposal");
+        .expect("Could not commit proposal")
+        .into_parts();
 
     // Check that we receive the correct proposal
     if let Some(staged_commit) = charlie_group.pending_commit() {
@@ -237,6 +382,295 @@ fn export_secret(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     )
 }
 
+#[apply(ciphersuites_and_providers)]
+fn labeled_export_secret(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    const LABEL: ExporterLabel = ExporterLabel::new("test");
+    const CACHED_LABEL: ExporterLabel = ExporterLabel::cached("test");
+
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let mls_group_config = MlsGroupConfig::test_default(ciphersuite);
+    let mut alice_group = MlsGroup::new(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        alice_credential_with_key,
+    )
+    .expect("An unexpected error occurred.");
+
+    // Typed labels derive the same secrets as plain labels.
+    let secret = alice_group
+        .export_secret(provider.crypto(), "test", b"context", 32)
+        .expect("An unexpected error occurred.");
+    assert_eq!(
+        alice_group
+            .export_secret(provider.crypto(), LABEL, b"context", 32)
+            .expect("An unexpected error occurred."),
+        secret
+    );
+
+    // Labels of OpenMLS keys are reserved.
+    for label in [
+        "history key",
+        "history sharing",
+        "ephemeral key",
+        "sub-exporter",
+    ] {
+        assert_eq!(
+            alice_group.export_secret(provider.crypto(), label, b"context", 32),
+            Err(ExportSecretError::ReservedLabel)
+        );
+    }
+
+    // Only secrets of cached labels are kept, until the epoch changes.
+    assert_eq!(alice_group.exporter_cache.len(), 0);
+    for _ in 0..2 {
+        assert_eq!(
+            alice_group
+                .export_secret(provider.crypto(), CACHED_LABEL, b"context", 32)
+                .expect("An unexpected error occurred."),
+            secret
+        );
+    }
+    assert_eq!(alice_group.exporter_cache.len(), 1);
+    alice_group.clear_exported_secrets();
+    assert_eq!(alice_group.exporter_cache.len(), 0);
+    alice_group
+        .export_secret(provider.crypto(), CACHED_LABEL, b"context", 32)
+        .expect("An unexpected error occurred.");
+
+    alice_group
+        .self_update(provider, &alice_signer)
+        .expect("An unexpected error occurred.");
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("An unexpected error occurred.");
+    assert_eq!(alice_group.exporter_cache.len(), 0);
+    assert_ne!(
+        alice_group
+            .export_secret(provider.crypto(), CACHED_LABEL, b"context", 32)
+            .expect("An unexpected error occurred."),
+        secret
+    );
+}
+
+#[apply(ciphersuites_and_providers)]
+fn sub_exporters(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let mls_group_config = MlsGroupConfig::test_default(ciphersuite);
+    let alice_group = MlsGroup::new(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        alice_credential_with_key,
+    )
+    .expect("An unexpected error occurred.");
+
+    let calls = alice_group
+        .sub_exporter(provider.crypto(), "calls")
+        .expect("An unexpected error occurred.");
+    let files = alice_group
+        .sub_exporter(provider.crypto(), "files")
+        .expect("An unexpected error occurred.");
+    assert_eq!(calls.namespace(), "calls");
+    assert_eq!(calls.epoch(), alice_group.epoch());
+
+    // The same label derives independent secrets in different namespaces
+    // and in the group itself.
+    let export = |exporter: &SubExporter| {
+        exporter
+            .export_secret(provider.crypto(), "key", b"context", 32)
+            .expect("An unexpected error occurred.")
+    };
+    let group_secret = alice_group
+        .export_secret(provider.crypto(), "key", b"context", 32)
+        .expect("An unexpected error occurred.");
+    assert_ne!(export(&calls), export(&files));
+    assert_ne!(export(&calls), group_secret);
+    assert_eq!(
+        export(&calls),
+        export(
+            &alice_group
+                .sub_exporter(provider.crypto(), "calls")
+                .expect("An unexpected error occurred.")
+        )
+    );
+
+    // Nested sub-exporters are independent from their parent.
+    let video = calls
+        .sub_exporter(provider.crypto(), "video")
+        .expect("An unexpected error occurred.");
+    assert_eq!(video.namespace(), "calls/video");
+    assert_ne!(export(&video), export(&calls));
+
+    assert_eq!(
+        calls.export_secret(provider.crypto(), "sub-exporter", b"video", 32),
+        Err(ExportSecretError::ReservedLabel)
+    );
+    assert_eq!(
+        calls.export_secret(provider.crypto(), "key", b"", usize::MAX),
+        Err(ExportSecretError::KeyLengthTooLong)
+    );
+}
+
+#[apply(ciphersuites_and_providers)]
+fn past_epoch_authenticators(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let mls_group_config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .past_epoch_authenticators(2)
+        .build();
+    let mut alice_group = MlsGroup::new(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        alice_credential_with_key,
+    )
+    .expect("An unexpected error occurred.");
+
+    let mut epoch_authenticators = vec![alice_group.epoch_authenticator().clone()];
+    for _ in 0..3 {
+        alice_group
+            .self_update(provider, &alice_signer)
+            .expect("An unexpected error occurred.");
+        alice_group
+            .merge_pending_commit(provider)
+            .expect("An unexpected error occurred.");
+        epoch_authenticators.push(alice_group.epoch_authenticator().clone());
+    }
+
+    // The current epoch and the two previous epochs are kept, also when the
+    // group is stored.
+    let serialized = serde_json::to_vec(&alice_group).expect("An unexpected error occurred.");
+    let loaded_group: MlsGroup =
+        serde_json::from_slice(&serialized).expect("An unexpected error occurred.");
+    for group in [&alice_group, &loaded_group] {
+        assert!(group
+            .epoch_authenticator_for_epoch(GroupEpoch::from(0))
+            .is_none());
+        for (epoch, epoch_authenticator) in epoch_authenticators.iter().enumerate().skip(1) {
+            assert_eq!(
+                group.epoch_authenticator_for_epoch(GroupEpoch::from(epoch as u64)),
+                Some(epoch_authenticator)
+            );
+        }
+        assert!(group
+            .epoch_authenticator_for_epoch(GroupEpoch::from(4))
+            .is_none());
+    }
+
+    // Past epoch authenticators are discarded when the window is closed.
+    alice_group.set_configuration(
+        &MlsGroupConfig::builder()
+            .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+            .build(),
+    );
+    alice_group
+        .self_update(provider, &alice_signer)
+        .expect("An unexpected error occurred.");
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("An unexpected error occurred.");
+    assert!(alice_group
+        .epoch_authenticator_for_epoch(GroupEpoch::from(3))
+        .is_none());
+    assert!(alice_group
+        .epoch_authenticator_for_epoch(GroupEpoch::from(4))
+        .is_some());
+}
+
+#[apply(ciphersuites_and_providers)]
+fn authenticated_epoch_values(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential, bob_kpb, _bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+    let mls_group_config = MlsGroupConfig::test_default(ciphersuite);
+    let mut alice_group = MlsGroup::new(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        alice_credential_with_key,
+    )
+    .expect("An unexpected error occurred.");
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(provider, &alice_signer, &[bob_kpb.key_package().clone()])
+        .expect("An unexpected error occurred.")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("An unexpected error occurred.");
+    let welcome = welcome
+        .into_welcome()
+        .expect("An unexpected error occurred.");
+    let mut bob_group = MlsGroup::new_from_welcome(
+        provider,
+        &mls_group_config,
+        welcome,
+        Some(alice_group.export_ratchet_tree().into()),
+    )
+    .expect("An unexpected error occurred.");
+
+    // All members agree on the authenticated values of each epoch.
+    let assert_agreement = |alice_group: &MlsGroup, bob_group: &MlsGroup| {
+        assert_eq!(alice_group.confirmation_tag(), bob_group.confirmation_tag());
+        assert_eq!(
+            alice_group.confirmation_tag().as_slice().len(),
+            ciphersuite.hash_length()
+        );
+        assert_eq!(
+            alice_group.confirmed_transcript_hash(),
+            bob_group.confirmed_transcript_hash()
+        );
+        assert_eq!(
+            alice_group.interim_transcript_hash(),
+            bob_group.interim_transcript_hash()
+        );
+        assert_ne!(
+            alice_group.confirmed_transcript_hash(),
+            alice_group.interim_transcript_hash()
+        );
+        assert_eq!(
+            alice_group.export_group_context(),
+            bob_group.export_group_context()
+        );
+    };
+    assert_agreement(&alice_group, &bob_group);
+
+    // The confirmation tag of the epoch is the one of the commit that created
+    // it.
+    let (commit, _welcome, _group_info) = alice_group
+        .self_update(provider, &alice_signer)
+        .expect("An unexpected error occurred.")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("An unexpected error occurred.");
+    let commit = commit
+        .into_protocol_message()
+        .expect("An unexpected error occurred.");
+    let ProtocolMessage::PublicMessage(public_message) = &commit else {
+        panic!("Expected a public message.");
+    };
+    assert_eq!(
+        public_message.confirmation_tag(),
+        Some(alice_group.confirmation_tag())
+    );
+    let processed = bob_group
+        .process_message(provider, commit)
+        .expect("An unexpected error occurred.");
+    let ProcessedMessageContent::StagedCommitMessage(staged_commit) = processed.into_content()
+    else {
+        panic!("Expected a commit.");
+    };
+    bob_group
+        .merge_staged_commit(provider, *staged_commit)
+        .expect("An unexpected error occurred.");
+    assert_agreement(&alice_group, &bob_group);
+}
+
 #[apply(ciphersuites_and_providers)]
 fn test_invalid_plaintext(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     // Some basic setup functions for the MlsGroup.
@@ -271,7 +705,8 @@ fn test_invalid_plaintext(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvi
 
     let (mls_message, _welcome_option, _group_info) = client
         .self_update(Commit, &group_id, None)
-        .expect("error creating self update");
+        .expect("error creating self update")
+        .into_parts();
 
     // Store the context and membership key so that we can re-compute the membership tag later.
     let client_groups = client.groups.read().unwrap();
@@ -385,7 +820,8 @@ fn test_pending_commit_logic(ciphersuite: Ciphersuite, provider: &impl OpenMlsPr
     println!("\nCreating commit with add proposal.");
     let (_msg, _welcome_option, _group_info) = alice_group
         .self_update(provider, &alice_signer)
-        .expect("error creating self-update commit");
+        .expect("error creating self-update commit")
+        .into_parts();
     println!("Done creating commit.");
 
     // There should be a pending commit after issueing a proposal.
@@ -450,7 +886,8 @@ fn test_pending_commit_logic(ciphersuite: Ciphersuite, provider: &impl OpenMlsPr
     // Creating a new commit should commit the same proposals.
     let (_msg, welcome_option, _group_info) = alice_group
         .self_update(provider, &alice_signer)
-        .expect("error creating self-update commit");
+        .expect("error creating self-update commit")
+        .into_parts();
 
     // Merging the pending commit should clear the pending commit and we should
     // end up in the same state as bob.
@@ -482,11 +919,13 @@ fn test_pending_commit_logic(ciphersuite: Ciphersuite, provider: &impl OpenMlsPr
     // While a commit is pending, merging Bob's commit should clear the pending commit.
     let (_msg, _welcome_option, _group_info) = alice_group
         .self_update(provider, &alice_signer)
-        .expect("error creating self-update commit");
+        .expect("error creating self-update commit")
+        .into_parts();
 
     let (msg, _welcome_option, _group_info) = bob_group
         .self_update(provider, &bob_signer)
-        .expect("error creating self-update commit");
+        .expect("error creating self-update commit")
+        .into_parts();
 
     let alice_processed_message = alice_group
         .process_message(provider, msg.into_protocol_message().unwrap())
@@ -535,7 +974,8 @@ fn key_package_deletion(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvide
     // === Alice adds Bob ===
     let (_queued_message, welcome, _group_info) = alice_group
         .add_members(provider, &alice_signer, &[bob_key_package.clone()])
-        .unwrap();
+        .unwrap()
+        .into_parts();
 
     alice_group.merge_pending_commit(provider).unwrap();
 
@@ -601,7 +1041,8 @@ fn remove_prosposal_by_ref(ciphersuite: Ciphersuite, provider: &impl OpenMlsProv
     // alice adds bob and bob processes the welcome
     let (_, welcome, _) = alice_group
         .add_members(provider, &alice_signer, &[bob_key_package])
-        .unwrap();
+        .unwrap()
+        .into_parts();
     alice_group.merge_pending_commit(provider).unwrap();
     let mut bob_group = MlsGroup::new_from_welcome(
         provider,
@@ -629,7 +1070,8 @@ fn remove_prosposal_by_ref(ciphersuite: Ciphersuite, provider: &impl OpenMlsProv
     // the commit should have no proposal
     let (commit, _, _) = alice_group
         .commit_to_pending_proposals(provider, &alice_signer)
-        .unwrap();
+        .unwrap()
+        .into_parts();
     let msg = bob_group
         .process_message(provider, MlsMessageIn::from(commit))
         .unwrap();
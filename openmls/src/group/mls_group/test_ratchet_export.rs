@@ -0,0 +1,266 @@
+use openmls_traits::{random::OpenMlsRand, types::Ciphersuite, OpenMlsProvider};
+use rstest::*;
+use rstest_reuse::{self, *};
+
+use super::*;
+use crate::{
+    credentials::CredentialType,
+    group::{config::CryptoConfig, errors::RatchetExportError, test_core_group::setup_client},
+    test_utils::*,
+};
+
+#[apply(ciphersuites_and_providers)]
+fn companion_decryption(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential, bob_kpb, _bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+
+    let mls_group_config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .use_ratchet_tree_extension(true)
+        .build();
+
+    // === Alice creates a group with Bob ===
+    let mut alice_group = MlsGroup::new(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        alice_credential_with_key.clone(),
+    )
+    .expect("error creating group");
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(provider, &alice_signer, &[bob_kpb.key_package().clone()])
+        .expect("error adding Bob")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    let welcome = welcome.into_welcome().expect("expected a welcome");
+    let mut bob_group = MlsGroup::new_from_welcome(provider, &mls_group_config, welcome, None)
+        .expect("error joining group");
+    let alice_index = alice_group.own_leaf_index();
+
+    let sealing_key = provider
+        .rand()
+        .random_vec(ciphersuite.aead_key_length())
+        .expect("error generating sealing key");
+    assert_eq!(
+        bob_group
+            .export_ratchet_state(provider, &sealing_key[1..], &[alice_index], 2)
+            .expect_err("expected an error"),
+        RatchetExportError::InvalidSealingKey
+    );
+    assert_eq!(
+        bob_group
+            .export_ratchet_state(provider, &sealing_key, &[bob_group.own_leaf_index()], 2)
+            .expect_err("expected an error"),
+        RatchetExportError::UnknownSender
+    );
+
+    // === Bob exports the keys of Alice's next two messages ===
+    let sealed_state = bob_group
+        .export_ratchet_state(provider, &sealing_key, &[alice_index], 2)
+        .expect("error exporting ratchet state");
+    assert_eq!(sealed_state.epoch(), bob_group.epoch());
+    let mut wrong_key = sealing_key.clone();
+    wrong_key[0] ^= 1;
+    assert_eq!(
+        sealed_state
+            .open(provider.crypto(), &wrong_key)
+            .expect_err("expected an error"),
+        RatchetExportError::DecryptionFailed
+    );
+    let companion_state = sealed_state
+        .open(provider.crypto(), &sealing_key)
+        .expect("error opening ratchet state");
+    assert_eq!(companion_state.senders(), vec![alice_index]);
+    assert_eq!(companion_state.generations(alice_index), vec![0, 1]);
+
+    // === The companion decrypts Alice's messages without changing Bob's group ===
+    let messages: Vec<_> = (0..3)
+        .map(|i| {
+            alice_group
+                .create_message(provider, &alice_signer, format!("message {i}").as_bytes())
+                .expect("error creating message")
+                .into_protocol_message()
+                .expect("expected a protocol message")
+        })
+        .collect();
+    for (i, message) in messages.iter().take(2).enumerate() {
+        let companion_message = companion_state
+            .decrypt_message(provider.crypto(), message.clone())
+            .expect("error decrypting message");
+        assert_eq!(companion_message.sender(), alice_index);
+        assert_eq!(
+            companion_message.credential(),
+            &alice_credential_with_key.credential
+        );
+        assert_eq!(companion_message.generation(), i as u32);
+        assert_eq!(
+            companion_message.application_data(),
+            format!("message {i}").as_bytes()
+        );
+    }
+    assert_eq!(
+        companion_state
+            .decrypt_message(provider.crypto(), messages[2].clone())
+            .expect_err("expected an error"),
+        RatchetExportError::UnavailableGeneration
+    );
+    for message in messages.iter().take(2) {
+        let processed_message = bob_group
+            .process_message(provider, message.clone())
+            .expect("error processing message");
+        assert!(matches!(
+            processed_message.into_content(),
+            ProcessedMessageContent::ApplicationMessage(_)
+        ));
+    }
+
+    // The next export starts at the next generation of the ratchet.
+    let companion_state = bob_group
+        .export_ratchet_state(provider, &sealing_key, &[alice_index], 2)
+        .expect("error exporting ratchet state")
+        .open(provider.crypto(), &sealing_key)
+        .expect("error opening ratchet state");
+    assert_eq!(companion_state.generations(alice_index), vec![2, 3]);
+    companion_state
+        .decrypt_message(provider.crypto(), messages[2].clone())
+        .expect("error decrypting message");
+
+    // === Only application messages of the epoch can be decrypted ===
+    let (commit, _welcome, _group_info) = alice_group
+        .self_update(provider, &alice_signer)
+        .expect("error updating")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    assert_eq!(
+        companion_state
+            .decrypt_message(
+                provider.crypto(),
+                commit
+                    .into_protocol_message()
+                    .expect("expected a protocol message"),
+            )
+            .expect_err("expected an error"),
+        RatchetExportError::NotApplicationMessage
+    );
+    let message = alice_group
+        .create_message(provider, &alice_signer, b"next epoch")
+        .expect("error creating message")
+        .into_protocol_message()
+        .expect("expected a protocol message");
+    assert_eq!(
+        companion_state
+            .decrypt_message(provider.crypto(), message)
+            .expect_err("expected an error"),
+        RatchetExportError::WrongEpoch
+    );
+}
+
+/// Asserts that `bytes` appear neither as a list nor hex-encoded in the
+/// `debug_output`.
+#[cfg(not(all(feature = "crypto-debug", feature = "content-debug")))]
+fn assert_redacted(debug_output: &str, bytes: &[u8]) {
+    let list = format!("{bytes:?}");
+    assert!(!debug_output.contains(list.trim_start_matches('[').trim_end_matches(']')));
+    assert!(!debug_output.contains(&hex::encode(bytes)));
+}
+
+#[cfg(not(feature = "crypto-debug"))]
+#[test]
+fn redacted_key_material() {
+    use super::ratchet_export::{ExportedKey, ExportedSender, RatchetStateContent};
+
+    let sender_data_secret = vec![0xa1; 32];
+    let key = vec![0xb2; 16];
+    let nonce = vec![0xc3; 12];
+    let content = RatchetStateContent {
+        serialized_context: vec![1, 2, 3].into(),
+        sender_data_secret: sender_data_secret.as_slice().into(),
+        senders: vec![ExportedSender {
+            leaf_index: LeafNodeIndex::new(1),
+            credential: Credential::new(b"Alice".to_vec(), CredentialType::Basic)
+                .expect("error creating credential"),
+            signature_key: vec![4, 5, 6].into(),
+            keys: vec![ExportedKey {
+                generation: 7,
+                key: key.as_slice().into(),
+                nonce: nonce.as_slice().into(),
+            }],
+        }],
+    };
+
+    for debug_output in [
+        format!("{content:?}"),
+        format!("{:?}", content.senders[0].keys[0]),
+    ] {
+        assert!(debug_output.contains("***"));
+        for bytes in [&sender_data_secret, &key, &nonce] {
+            assert_redacted(&debug_output, bytes);
+        }
+    }
+}
+
+#[cfg(not(feature = "content-debug"))]
+#[apply(ciphersuites_and_providers)]
+fn redacted_companion_message(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential, bob_kpb, _bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+
+    let mls_group_config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .use_ratchet_tree_extension(true)
+        .build();
+    let mut alice_group = MlsGroup::new(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        alice_credential_with_key,
+    )
+    .expect("error creating group");
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(provider, &alice_signer, &[bob_kpb.key_package().clone()])
+        .expect("error adding Bob")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    let welcome = welcome.into_welcome().expect("expected a welcome");
+    let bob_group = MlsGroup::new_from_welcome(provider, &mls_group_config, welcome, None)
+        .expect("error joining group");
+
+    let sealing_key = provider
+        .rand()
+        .random_vec(ciphersuite.aead_key_length())
+        .expect("error generating sealing key");
+    let companion_state = bob_group
+        .export_ratchet_state(provider, &sealing_key, &[alice_group.own_leaf_index()], 1)
+        .expect("error exporting ratchet state")
+        .open(provider.crypto(), &sealing_key)
+        .expect("error opening ratchet state");
+
+    let application_data = b"a very secret message".to_vec();
+    let authenticated_data = b"some secret authenticated data".to_vec();
+    alice_group.set_aad(&authenticated_data);
+    let message = alice_group
+        .create_message(provider, &alice_signer, &application_data)
+        .expect("error creating message")
+        .into_protocol_message()
+        .expect("expected a protocol message");
+    let companion_message = companion_state
+        .decrypt_message(provider.crypto(), message)
+        .expect("error decrypting message");
+    assert_eq!(companion_message.authenticated_data(), authenticated_data);
+
+    let debug_output = format!("{companion_message:?}");
+    assert!(debug_output.contains("***"));
+    for bytes in [&application_data, &authenticated_data] {
+        assert_redacted(&debug_output, bytes);
+    }
+}
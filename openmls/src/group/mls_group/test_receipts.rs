@@ -0,0 +1,162 @@
+use openmls_traits::{types::Ciphersuite, OpenMlsProvider};
+use rstest::*;
+use rstest_reuse::{self, *};
+use tls_codec::Serialize as TlsSerializeTrait;
+
+use super::*;
+use crate::{
+    group::{config::CryptoConfig, test_core_group::setup_client},
+    test_utils::*,
+};
+
+/// Process `message` in `group` and return the processed message.
+fn receive(
+    group: &mut MlsGroup,
+    provider: &impl OpenMlsProvider,
+    message: MlsMessageOut,
+) -> ProcessedMessage {
+    group
+        .process_message(
+            provider,
+            message
+                .into_protocol_message()
+                .expect("expected a protocol message"),
+        )
+        .expect("error processing message")
+}
+
+#[apply(ciphersuites_and_providers)]
+fn receipts(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (bob_credential_with_key, bob_kpb, bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+    let (charlie_credential_with_key, charlie_kpb, charlie_signer, _charlie_pk) =
+        setup_client("Charlie", ciphersuite, provider);
+
+    let mls_group_config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .use_ratchet_tree_extension(true)
+        .build();
+
+    // === Alice creates a group with Bob and Charlie ===
+    let mut alice_group = MlsGroup::new(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        alice_credential_with_key,
+    )
+    .expect("error creating group");
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(
+            provider,
+            &alice_signer,
+            &[
+                bob_kpb.key_package().clone(),
+                charlie_kpb.key_package().clone(),
+            ],
+        )
+        .expect("error adding Bob and Charlie")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    let welcome = welcome.into_welcome().expect("expected a welcome");
+    let mut bob_group =
+        MlsGroup::new_from_welcome(provider, &mls_group_config, welcome.clone(), None)
+            .expect("error joining group");
+    let mut charlie_group = MlsGroup::new_from_welcome(provider, &mls_group_config, welcome, None)
+        .expect("error joining group");
+
+    // === Alice sends two messages and tracks them ===
+    let mut tracker = ReceiptTracker::new();
+    let mut message_refs = vec![];
+    for text in [b"Hello", b"World"] {
+        let message = alice_group
+            .create_message(provider, &alice_signer, text)
+            .expect("error creating message");
+        let bytes = message
+            .tls_serialize_detached()
+            .expect("error serializing message");
+        let message_ref = alice_group
+            .message_ref(provider.crypto(), &bytes)
+            .expect("error computing message reference");
+        tracker.track(message_ref.clone());
+        message_refs.push(message_ref);
+
+        // Bob and Charlie compute the same references.
+        for group in [&mut bob_group, &mut charlie_group] {
+            assert_eq!(
+                group
+                    .message_ref(provider.crypto(), &bytes)
+                    .expect("error computing message reference"),
+                message_refs[message_refs.len() - 1]
+            );
+            let processed_message = receive(group, provider, message.clone());
+            assert!(tracker.record(&processed_message).is_none());
+        }
+    }
+    assert_ne!(message_refs[0], message_refs[1]);
+    assert_eq!(tracker.receipts(&message_refs[0]), Some([].as_slice()));
+
+    // === Bob confirms the delivery of both messages in one receipt ===
+    let receipt = bob_group
+        .create_receipt(provider, &bob_signer, ReceiptType::Delivered, &message_refs)
+        .expect("error creating receipt");
+    let processed_message = receive(&mut alice_group, provider, receipt);
+    assert_eq!(
+        tracker.record(&processed_message),
+        Some(Receipt::new(ReceiptType::Delivered, message_refs.clone()))
+    );
+
+    // === Charlie reads the first message ===
+    let receipt = charlie_group
+        .create_receipt(
+            provider,
+            &charlie_signer,
+            ReceiptType::Read,
+            &message_refs[..1],
+        )
+        .expect("error creating receipt");
+    tracker.record(&receive(&mut alice_group, provider, receipt));
+
+    // A late delivery receipt doesn't downgrade the read receipt.
+    let receipt = charlie_group
+        .create_receipt(
+            provider,
+            &charlie_signer,
+            ReceiptType::Delivered,
+            &message_refs,
+        )
+        .expect("error creating receipt");
+    tracker.record(&receive(&mut alice_group, provider, receipt));
+
+    let bob_credential = &bob_credential_with_key.credential;
+    let charlie_credential = &charlie_credential_with_key.credential;
+    assert_eq!(
+        tracker.delivered_to(&message_refs[0]),
+        vec![bob_credential, charlie_credential]
+    );
+    assert_eq!(tracker.read_by(&message_refs[0]), vec![charlie_credential]);
+    assert_eq!(
+        tracker.delivered_to(&message_refs[1]),
+        vec![bob_credential, charlie_credential]
+    );
+    assert!(tracker.read_by(&message_refs[1]).is_empty());
+
+    // === Receipts for untracked messages are ignored ===
+    tracker.untrack(&message_refs[1]);
+    assert!(tracker.receipts(&message_refs[1]).is_none());
+    let receipt = bob_group
+        .create_receipt(provider, &bob_signer, ReceiptType::Read, &message_refs)
+        .expect("error creating receipt");
+    tracker.record(&receive(&mut alice_group, provider, receipt));
+    assert!(tracker.receipts(&message_refs[1]).is_none());
+    assert_eq!(
+        tracker.read_by(&message_refs[0]),
+        vec![bob_credential, charlie_credential]
+    );
+
+    // Regular application messages aren't receipts.
+    assert!(Receipt::decode(b"Hello").is_none());
+}
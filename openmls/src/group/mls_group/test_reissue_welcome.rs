@@ -0,0 +1,99 @@
+use openmls_traits::{types::Ciphersuite, OpenMlsProvider};
+use rstest::*;
+use rstest_reuse::{self, *};
+
+use super::*;
+use crate::{
+    group::{config::CryptoConfig, errors::ReissueWelcomeError, test_core_group::setup_client},
+    test_utils::*,
+};
+
+#[apply(ciphersuites_and_providers)]
+fn reissue_welcome(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential, bob_kpb, _bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+    let (_charlie_credential, charlie_kpb, _charlie_signer, _charlie_pk) =
+        setup_client("Charlie", ciphersuite, provider);
+    let bob_key_package_ref = bob_kpb
+        .key_package()
+        .hash_ref(provider.crypto())
+        .expect("error computing key package reference");
+
+    let mls_group_config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .use_ratchet_tree_extension(true)
+        .build();
+
+    // === Alice adds Bob and Charlie ===
+    let mut alice_group = MlsGroup::new(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        alice_credential_with_key,
+    )
+    .expect("error creating group");
+    let (_commit, _welcome, _group_info) = alice_group
+        .add_members(
+            provider,
+            &alice_signer,
+            &[
+                bob_kpb.key_package().clone(),
+                charlie_kpb.key_package().clone(),
+            ],
+        )
+        .expect("error adding Bob and Charlie")
+        .into_parts();
+
+    // The Welcome message can't be re-issued before the commit is merged.
+    assert_eq!(
+        alice_group
+            .reissue_welcome(&bob_key_package_ref)
+            .expect_err("expected an error"),
+        ReissueWelcomeError::NoWelcome
+    );
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+
+    // === Bob lost the Welcome message and Alice re-issues it ===
+    let welcome = alice_group
+        .reissue_welcome(&bob_key_package_ref)
+        .expect("error re-issuing welcome")
+        .into_welcome()
+        .expect("expected a welcome");
+    assert_eq!(welcome.secrets().len(), 1);
+    let bob_group = MlsGroup::new_from_welcome(provider, &mls_group_config, welcome, None)
+        .expect("error joining group");
+    assert_eq!(bob_group.epoch(), alice_group.epoch());
+    assert_eq!(bob_group.own_leaf_index(), LeafNodeIndex::new(1));
+
+    // Only the new members of the commit have secrets in the Welcome.
+    let (_dave_credential, dave_kpb, _dave_signer, _dave_pk) =
+        setup_client("Dave", ciphersuite, provider);
+    let dave_key_package_ref = dave_kpb
+        .key_package()
+        .hash_ref(provider.crypto())
+        .expect("error computing key package reference");
+    assert_eq!(
+        alice_group
+            .reissue_welcome(&dave_key_package_ref)
+            .expect_err("expected an error"),
+        ReissueWelcomeError::UnknownNewMember
+    );
+
+    // === The Welcome message is dropped with the next commit ===
+    alice_group
+        .self_update(provider, &alice_signer)
+        .expect("error updating");
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    assert_eq!(
+        alice_group
+            .reissue_welcome(&bob_key_package_ref)
+            .expect_err("expected an error"),
+        ReissueWelcomeError::NoWelcome
+    );
+}
@@ -0,0 +1,134 @@
+use openmls_traits::{types::Ciphersuite, OpenMlsProvider};
+use rstest::*;
+use rstest_reuse::{self, *};
+use tls_codec::{Deserialize as TlsDeserializeTrait, Serialize as TlsSerializeTrait};
+
+use super::*;
+use crate::{
+    group::{config::CryptoConfig, errors::AbuseReportError, test_core_group::setup_client},
+    test_utils::*,
+};
+
+#[apply(ciphersuites_and_providers)]
+fn abuse_report(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential, bob_kpb, bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+    let (_charlie_credential, charlie_kpb, _charlie_signer, _charlie_pk) =
+        setup_client("Charlie", ciphersuite, provider);
+
+    let mls_group_config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .use_ratchet_tree_extension(true)
+        .build();
+
+    let mut alice_group = MlsGroup::new_with_group_id(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        GroupId::from_slice(b"Test Group"),
+        alice_credential_with_key,
+    )
+    .expect("error creating group");
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(
+            provider,
+            &alice_signer,
+            &[
+                bob_kpb.key_package().clone(),
+                charlie_kpb.key_package().clone(),
+            ],
+        )
+        .expect("error adding Bob and Charlie")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    let welcome = welcome.into_welcome().expect("expected a welcome");
+    let mut bob_group = MlsGroup::new_from_welcome(provider, &mls_group_config, welcome, None)
+        .expect("error joining group");
+
+    // === Alice reports a message of Bob ===
+    bob_group.set_aad(b"Bob's AAD");
+    let message = bob_group
+        .create_message(provider, &bob_signer, b"Abusive message")
+        .expect("error creating message");
+    let processed_message = alice_group
+        .process_message(
+            provider,
+            message
+                .into_protocol_message()
+                .expect("expected a protocol message"),
+        )
+        .expect("error processing message");
+    let report = alice_group
+        .report_application_message(provider.crypto(), &processed_message)
+        .expect("error creating report");
+    assert_eq!(report.sender(), bob_group.own_leaf_index());
+    assert_eq!(report.credential().identity(), b"Bob");
+    assert_eq!(report.application_data(), b"Abusive message");
+    assert_eq!(report.authenticated_data(), b"Bob's AAD");
+    assert_eq!(report.group_context().epoch(), alice_group.epoch());
+
+    // === The server verifies the report ===
+    let serialized = report
+        .tls_serialize_detached()
+        .expect("error serializing report");
+    let received =
+        AbuseReport::tls_deserialize_exact(&serialized).expect("error deserializing report");
+    assert_eq!(received, report);
+    received
+        .verify(provider.crypto())
+        .expect("error verifying report");
+    assert_eq!(
+        received
+            .message_hash(provider.crypto())
+            .expect("error hashing message"),
+        report
+            .message_hash(provider.crypto())
+            .expect("error hashing message")
+    );
+
+    // A modified message is detected.
+    let position = serialized
+        .windows(b"Abusive message".len())
+        .position(|window| window == b"Abusive message")
+        .expect("message not found in report");
+    let mut modified = serialized.clone();
+    modified[position] ^= 0xff;
+    let modified =
+        AbuseReport::tls_deserialize_exact(&modified).expect("error deserializing report");
+    assert_eq!(
+        modified.verify(provider.crypto()),
+        Err(AbuseReportError::InvalidMessageSignature)
+    );
+
+    // === Only application messages of the current epoch can be reported ===
+    let (commit, _welcome, _group_info) = bob_group
+        .self_update(provider, &bob_signer)
+        .expect("error updating")
+        .into_parts();
+    let processed_commit = alice_group
+        .process_message(
+            provider,
+            commit
+                .into_protocol_message()
+                .expect("expected a protocol message"),
+        )
+        .expect("error processing commit");
+    assert_eq!(
+        alice_group.report_application_message(provider.crypto(), &processed_commit),
+        Err(AbuseReportError::NotAnApplicationMessage)
+    );
+    match processed_commit.into_content() {
+        ProcessedMessageContent::StagedCommitMessage(staged_commit) => alice_group
+            .merge_staged_commit(provider, *staged_commit)
+            .expect("error merging commit"),
+        _ => panic!("Expected a staged commit."),
+    }
+    assert_eq!(
+        alice_group.report_application_message(provider.crypto(), &processed_message),
+        Err(AbuseReportError::UnavailableEpoch)
+    );
+}
@@ -0,0 +1,142 @@
+use openmls_traits::{types::Ciphersuite, OpenMlsProvider};
+use rstest::*;
+use rstest_reuse::{self, *};
+
+use super::*;
+use crate::{
+    framing::ProcessedMessageContent,
+    group::{config::CryptoConfig, test_core_group::setup_client},
+    test_utils::*,
+    tree::sender_ratchet::SenderRatchetConfiguration,
+};
+
+#[apply(ciphersuites_and_providers)]
+fn resynchronize_desynchronized_sender(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential, bob_kpb, bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+
+    let mls_group_config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .use_ratchet_tree_extension(true)
+        .sender_ratchet_configuration(SenderRatchetConfiguration::new(0, 2))
+        .build();
+
+    let mut alice_group = MlsGroup::new(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        alice_credential_with_key,
+    )
+    .expect("error creating group");
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(provider, &alice_signer, &[bob_kpb.key_package().clone()])
+        .expect("error adding Bob")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    let welcome = welcome.into_welcome().expect("expected a welcome");
+    let mut bob_group = MlsGroup::new_from_welcome(provider, &mls_group_config, welcome, None)
+        .expect("error joining group");
+
+    // Nothing to do while all senders are in sync.
+    assert!(alice_group.desynchronized_senders().is_empty());
+    assert!(alice_group
+        .resynchronize(provider, &alice_signer)
+        .expect("error resynchronizing")
+        .is_none());
+
+    // Alice misses Bob's first messages and receives two that are too far
+    // ahead.
+    let mut messages = (0..5)
+        .map(|_| {
+            bob_group
+                .create_message(provider, &bob_signer, b"Hello")
+                .expect("error creating message")
+                .into_protocol_message()
+                .expect("expected a protocol message")
+        })
+        .collect::<Vec<_>>();
+    let message = messages.pop().expect("expected a message");
+
+    // A sender is not marked as desynchronized by a message that isn't
+    // authentic.
+    let mut forged_message = messages.pop().expect("expected a message");
+    if let ProtocolMessage::PrivateMessage(private_message) = &mut forged_message {
+        private_message.flip_ciphertext_tag_bit();
+    }
+    alice_group
+        .process_message(provider, forged_message)
+        .expect_err("decrypted a message beyond the forward distance");
+    assert!(alice_group.desynchronized_senders().is_empty());
+
+    let err = alice_group
+        .process_message(provider, message)
+        .expect_err("decrypted a message beyond the forward distance");
+    assert!(matches!(
+        err,
+        ProcessMessageError::ValidationError(ValidationError::UnableToDecrypt(_))
+    ));
+    assert_eq!(
+        alice_group.desynchronized_senders(),
+        vec![bob_group.own_leaf_index()]
+    );
+
+    // The commit starts a new epoch and updates Alice's leaf.
+    let encryption_key = alice_group
+        .own_leaf_node()
+        .expect("expected own leaf")
+        .encryption_key()
+        .clone();
+    let (commit, _group_info) = alice_group
+        .resynchronize(provider, &alice_signer)
+        .expect("error resynchronizing")
+        .expect("expected a commit");
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    assert_ne!(
+        alice_group
+            .own_leaf_node()
+            .expect("expected own leaf")
+            .encryption_key(),
+        &encryption_key
+    );
+    assert!(alice_group.desynchronized_senders().is_empty());
+
+    let processed_message = bob_group
+        .process_message(
+            provider,
+            commit
+                .into_protocol_message()
+                .expect("expected a protocol message"),
+        )
+        .expect("error processing commit");
+    let ProcessedMessageContent::StagedCommitMessage(staged_commit) =
+        processed_message.into_content()
+    else {
+        panic!("expected a commit");
+    };
+    assert_eq!(staged_commit.add_proposals().count(), 0);
+    assert_eq!(staged_commit.update_proposals().count(), 0);
+    assert_eq!(staged_commit.remove_proposals().count(), 0);
+    bob_group
+        .merge_staged_commit(provider, *staged_commit)
+        .expect("error merging commit");
+    assert_eq!(alice_group.epoch(), bob_group.epoch());
+
+    // Bob's messages can be decrypted again.
+    let message = bob_group
+        .create_message(provider, &bob_signer, b"Hello again")
+        .expect("error creating message");
+    alice_group
+        .process_message(
+            provider,
+            message
+                .into_protocol_message()
+                .expect("expected a protocol message"),
+        )
+        .expect("error processing message");
+}
@@ -0,0 +1,412 @@
+use openmls_traits::{types::Ciphersuite, OpenMlsProvider};
+use rstest::*;
+use rstest_reuse::{self, *};
+
+use super::*;
+use crate::{
+    group::{config::CryptoConfig, errors::DiscardEpochError, test_core_group::setup_client},
+    test_utils::*,
+    tree::{
+        secret_tree::SecretTreeError,
+        sender_ratchet::{SenderRatchetConfiguration, SenderSelector},
+    },
+};
+
+#[apply(ciphersuites_and_providers)]
+fn message_retention(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential, bob_kpb, bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+
+    let mls_group_config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .use_ratchet_tree_extension(true)
+        .max_past_epochs(2)
+        .build();
+
+    let mut alice_group = MlsGroup::new_with_group_id(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        GroupId::from_slice(b"Test Group"),
+        alice_credential_with_key,
+    )
+    .expect("error creating group");
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(provider, &alice_signer, &[bob_kpb.key_package().clone()])
+        .expect("error adding Bob")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    let welcome = welcome.into_welcome().expect("expected a welcome");
+    let mut bob_group = MlsGroup::new_from_welcome(provider, &mls_group_config, welcome, None)
+        .expect("error joining group");
+
+    let epoch = alice_group.epoch();
+    let bob_index = bob_group.own_leaf_index();
+    let alice_index = alice_group.own_leaf_index();
+    // Alice keeps the epoch in which she created the group.
+    assert_eq!(
+        alice_group.decryptable_epochs(),
+        vec![GroupEpoch::from(0), epoch]
+    );
+    assert_eq!(bob_group.decryptable_epochs(), vec![epoch]);
+
+    // Bob sends three messages and Alice receives the last one first.
+    let messages: Vec<MlsMessageOut> = (0..3)
+        .map(|_| {
+            bob_group
+                .create_message(provider, &bob_signer, b"Hello")
+                .expect("error creating message")
+        })
+        .collect();
+    for generation in 0..3 {
+        assert!(alice_group.can_decrypt_application_message(epoch, bob_index, generation));
+    }
+    alice_group
+        .process_message(
+            provider,
+            messages[2]
+                .clone()
+                .into_protocol_message()
+                .expect("expected a protocol message"),
+        )
+        .expect("error processing message");
+    assert!(alice_group.can_decrypt_application_message(epoch, bob_index, 0));
+    assert!(alice_group.can_decrypt_application_message(epoch, bob_index, 1));
+    assert!(!alice_group.can_decrypt_application_message(epoch, bob_index, 2));
+    assert!(alice_group.can_decrypt_application_message(epoch, bob_index, 3));
+
+    // Messages too far in the future, own messages and messages of unknown
+    // members can't be decrypted.
+    let maximum_forward_distance = mls_group_config
+        .sender_ratchet_configuration()
+        .maximum_forward_distance();
+    assert!(alice_group.can_decrypt_application_message(
+        epoch,
+        bob_index,
+        3 + maximum_forward_distance
+    ));
+    assert!(!alice_group.can_decrypt_application_message(
+        epoch,
+        bob_index,
+        4 + maximum_forward_distance
+    ));
+    assert!(!alice_group.can_decrypt_application_message(epoch, alice_index, 0));
+    assert!(!alice_group.can_decrypt_application_message(epoch, LeafNodeIndex::new(5), 0));
+
+    // === The epoch is retained for two more epochs ===
+    for _ in 0..3 {
+        alice_group
+            .self_update(provider, &alice_signer)
+            .expect("error updating");
+        alice_group
+            .merge_pending_commit(provider)
+            .expect("error merging commit");
+    }
+    let current_epoch = alice_group.epoch();
+    assert_eq!(
+        alice_group.decryptable_epochs(),
+        vec![
+            GroupEpoch::from(current_epoch.as_u64() - 2),
+            GroupEpoch::from(current_epoch.as_u64() - 1),
+            current_epoch,
+        ]
+    );
+    assert!(!alice_group.can_decrypt_application_message(epoch, bob_index, 0));
+    assert!(!alice_group.can_decrypt_application_message(
+        GroupEpoch::from(current_epoch.as_u64() + 1),
+        bob_index,
+        0
+    ));
+    assert!(alice_group.can_decrypt_application_message(
+        GroupEpoch::from(current_epoch.as_u64() - 1),
+        bob_index,
+        0
+    ));
+}
+
+#[apply(ciphersuites_and_providers)]
+fn sender_ratchet_overrides(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential, bob_kpb, bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+    let (_charlie_credential, charlie_kpb, charlie_signer, _charlie_pk) =
+        setup_client("Charlie", ciphersuite, provider);
+
+    let mls_group_config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .use_ratchet_tree_extension(true)
+        .build();
+    // Bob is a bot and may send many messages out of order.
+    let alice_config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .use_ratchet_tree_extension(true)
+        .sender_ratchet_configuration(SenderRatchetConfiguration::new(0, 2).with_sender_override(
+            SenderSelector::Identity(b"Bob".to_vec()),
+            5,
+            1000,
+        ))
+        .build();
+
+    let mut alice_group = MlsGroup::new(
+        provider,
+        &alice_signer,
+        &alice_config,
+        alice_credential_with_key,
+    )
+    .expect("error creating group");
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(
+            provider,
+            &alice_signer,
+            &[
+                bob_kpb.key_package().clone(),
+                charlie_kpb.key_package().clone(),
+            ],
+        )
+        .expect("error adding Bob and Charlie")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    let welcome = welcome.into_welcome().expect("expected a welcome");
+    let mut bob_group =
+        MlsGroup::new_from_welcome(provider, &mls_group_config, welcome.clone(), None)
+            .expect("error joining group");
+    let mut charlie_group = MlsGroup::new_from_welcome(provider, &mls_group_config, welcome, None)
+        .expect("error joining group");
+
+    let epoch = alice_group.epoch();
+    let bob_index = bob_group.own_leaf_index();
+    let charlie_index = charlie_group.own_leaf_index();
+    assert!(alice_group.can_decrypt_application_message(epoch, bob_index, 3));
+    assert!(!alice_group.can_decrypt_application_message(epoch, charlie_index, 3));
+
+    // === Bob's and Charlie's last message arrives first ===
+    for (group, signer) in [
+        (&mut bob_group, &bob_signer),
+        (&mut charlie_group, &charlie_signer),
+    ] {
+        let messages: Vec<MlsMessageOut> = (0..4)
+            .map(|_| {
+                group
+                    .create_message(provider, signer, b"Hello")
+                    .expect("error creating message")
+            })
+            .collect();
+        let result = alice_group.process_message(
+            provider,
+            messages[3]
+                .clone()
+                .into_protocol_message()
+                .expect("expected a protocol message"),
+        );
+        if group.own_leaf_index() == bob_index {
+            result.expect("error processing message");
+            // The earlier messages are within Bob's out-of-order window.
+            alice_group
+                .process_message(
+                    provider,
+                    messages[0]
+                        .clone()
+                        .into_protocol_message()
+                        .expect("expected a protocol message"),
+                )
+                .expect("error processing message");
+        } else {
+            assert_eq!(
+                result.expect_err("expected an error"),
+                ProcessMessageError::ValidationError(ValidationError::UnableToDecrypt(
+                    MessageDecryptionError::GenerationOutOfBound
+                ))
+            );
+        }
+    }
+}
+
+#[apply(ciphersuites_and_providers)]
+fn message_key_deletion(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential, bob_kpb, bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+
+    let mls_group_config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .use_ratchet_tree_extension(true)
+        .max_past_epochs(1)
+        .build();
+
+    let mut alice_group = MlsGroup::new_with_group_id(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        GroupId::from_slice(b"Test Group"),
+        alice_credential_with_key,
+    )
+    .expect("error creating group");
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(provider, &alice_signer, &[bob_kpb.key_package().clone()])
+        .expect("error adding Bob")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    let welcome = welcome.into_welcome().expect("expected a welcome");
+    let mut bob_group = MlsGroup::new_from_welcome(provider, &mls_group_config, welcome, None)
+        .expect("error joining group");
+    let epoch = alice_group.epoch();
+    let bob_index = bob_group.own_leaf_index();
+
+    // Bob sends three messages and Alice receives the last one first.
+    let messages: Vec<ProtocolMessage> = (0..3)
+        .map(|_| {
+            bob_group
+                .create_message(provider, &bob_signer, b"Hello")
+                .expect("error creating message")
+                .into_protocol_message()
+                .expect("expected a protocol message")
+        })
+        .collect();
+    assert!(alice_group.retained_message_keys().is_empty());
+    alice_group
+        .process_message(provider, messages[2].clone())
+        .expect("error processing message");
+    let generations = |group: &MlsGroup| -> Vec<u32> {
+        group
+            .retained_message_keys()
+            .iter()
+            .map(|retained_key| {
+                assert_eq!(retained_key.epoch(), epoch);
+                assert_eq!(retained_key.sender(), bob_index);
+                assert!(!retained_key.is_handshake());
+                retained_key.generation()
+            })
+            .collect()
+    };
+    assert_eq!(generations(&alice_group), vec![1, 0]);
+
+    // The key of a decrypted message is deleted, also from the stored state.
+    alice_group
+        .process_message(provider, messages[0].clone())
+        .expect("error processing message");
+    assert_eq!(generations(&alice_group), vec![1]);
+    alice_group
+        .save(provider.key_store())
+        .expect("error saving group");
+    let mut alice_group =
+        MlsGroup::load(alice_group.group_id(), provider.key_store()).expect("error loading group");
+    assert_eq!(generations(&alice_group), vec![1]);
+    assert!(alice_group.stale_message_keys().is_empty());
+    for message in [&messages[0], &messages[2]] {
+        assert_eq!(
+            alice_group
+                .process_message(provider, message.clone())
+                .expect_err("expected an error"),
+            ProcessMessageError::ValidationError(ValidationError::UnableToDecrypt(
+                MessageDecryptionError::SecretTreeError(SecretTreeError::SecretReuseError)
+            ))
+        );
+    }
+
+    // === The keys of an epoch are deleted with the epoch ===
+    for _ in 0..2 {
+        alice_group
+            .self_update(provider, &alice_signer)
+            .expect("error updating");
+        alice_group
+            .merge_pending_commit(provider)
+            .expect("error merging commit");
+        assert!(alice_group.stale_message_keys().is_empty());
+    }
+    assert!(alice_group.retained_message_keys().is_empty());
+}
+
+#[apply(ciphersuites_and_providers)]
+fn discard_epoch(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential, bob_kpb, bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+
+    let mls_group_config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .use_ratchet_tree_extension(true)
+        .max_past_epochs(2)
+        .build();
+
+    let mut alice_group = MlsGroup::new_with_group_id(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        GroupId::from_slice(b"Test Group"),
+        alice_credential_with_key,
+    )
+    .expect("error creating group");
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(provider, &alice_signer, &[bob_kpb.key_package().clone()])
+        .expect("error adding Bob")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    let welcome = welcome.into_welcome().expect("expected a welcome");
+    let mut bob_group = MlsGroup::new_from_welcome(provider, &mls_group_config, welcome, None)
+        .expect("error joining group");
+    let epoch = alice_group.epoch();
+    let bob_index = bob_group.own_leaf_index();
+
+    // Bob sends a message that is delivered to Alice after she advanced.
+    let message = bob_group
+        .create_message(provider, &bob_signer, b"Hello")
+        .expect("error creating message")
+        .into_protocol_message()
+        .expect("expected a protocol message");
+    alice_group
+        .self_update(provider, &alice_signer)
+        .expect("error updating");
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    assert_eq!(
+        alice_group.decryptable_epochs(),
+        vec![GroupEpoch::from(0), epoch, alice_group.epoch()]
+    );
+    assert!(alice_group.can_decrypt_application_message(epoch, bob_index, 0));
+
+    // === Alice discards the epoch of the message ===
+    assert_eq!(
+        alice_group.discard_epoch(alice_group.epoch()),
+        Err(DiscardEpochError::NotAPastEpoch)
+    );
+    alice_group
+        .discard_epoch(epoch)
+        .expect("error discarding epoch");
+    assert_eq!(
+        alice_group.discard_epoch(epoch),
+        Err(DiscardEpochError::UnknownEpoch)
+    );
+    assert_eq!(
+        alice_group.decryptable_epochs(),
+        vec![GroupEpoch::from(0), alice_group.epoch()]
+    );
+    assert!(!alice_group.can_decrypt_application_message(epoch, bob_index, 0));
+
+    // The epoch stays discarded in the stored state.
+    alice_group
+        .save(provider.key_store())
+        .expect("error saving group");
+    let mut alice_group =
+        MlsGroup::load(alice_group.group_id(), provider.key_store()).expect("error loading group");
+    assert_eq!(
+        alice_group.decryptable_epochs(),
+        vec![GroupEpoch::from(0), alice_group.epoch()]
+    );
+    alice_group
+        .process_message(provider, message)
+        .expect_err("message of a discarded epoch was decrypted");
+}
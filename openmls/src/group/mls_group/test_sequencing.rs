@@ -0,0 +1,135 @@
+use openmls_traits::{types::Ciphersuite, OpenMlsProvider};
+use rstest::*;
+use rstest_reuse::{self, *};
+
+use super::*;
+use crate::{
+    group::{config::CryptoConfig, errors::SequencingError, test_core_group::setup_client},
+    test_utils::*,
+};
+
+fn process_commit(
+    group: &mut MlsGroup,
+    provider: &impl OpenMlsProvider,
+    commit: MlsMessageOut,
+) -> Result<Vec<u8>, ProcessMessageError> {
+    let processed_message = group.process_message(
+        provider,
+        commit
+            .into_protocol_message()
+            .expect("expected a protocol message"),
+    )?;
+    let authenticated_data = processed_message.authenticated_data().to_vec();
+    match processed_message.into_content() {
+        ProcessedMessageContent::StagedCommitMessage(staged_commit) => group
+            .merge_staged_commit(provider, *staged_commit)
+            .expect("error merging commit"),
+        _ => panic!("Expected a staged commit."),
+    }
+    Ok(authenticated_data)
+}
+
+#[apply(ciphersuites_and_providers)]
+fn sequencing_tokens(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential, bob_kpb, bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+
+    let mls_group_config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .use_ratchet_tree_extension(true)
+        .sequencing_tokens(true)
+        .build();
+
+    let mut alice_group = MlsGroup::new_with_group_id(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        GroupId::from_slice(b"Test Group"),
+        alice_credential_with_key,
+    )
+    .expect("error creating group");
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(provider, &alice_signer, &[bob_kpb.key_package().clone()])
+        .expect("error adding Bob")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    assert_eq!(alice_group.last_sequence_number(), Some(0));
+    let welcome = welcome.into_welcome().expect("expected a welcome");
+    let mut bob_group = MlsGroup::new_from_welcome(provider, &mls_group_config, welcome, None)
+        .expect("error joining group");
+    assert_eq!(bob_group.last_sequence_number(), None);
+
+    // === Alice commits with the sequence number assigned by the DS ===
+    alice_group.set_aad(b"Alice's AAD");
+    alice_group
+        .set_sequence_number(5)
+        .expect("error setting sequence number");
+    let (commit, _welcome, _group_info) = alice_group
+        .self_update(provider, &alice_signer)
+        .expect("error updating")
+        .into_parts();
+    assert_eq!(
+        alice_group.set_sequence_number(6),
+        Err(MlsGroupStateError::PendingCommit)
+    );
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    assert_eq!(alice_group.last_sequence_number(), Some(5));
+
+    let authenticated_data =
+        process_commit(&mut bob_group, provider, commit).expect("error processing commit");
+    let sequenced_aad =
+        SequencedAad::decode(&authenticated_data).expect("error decoding sequenced AAD");
+    assert_eq!(sequenced_aad.sequence_number(), 5);
+    assert_eq!(sequenced_aad.aad(), b"Alice's AAD");
+    assert_eq!(bob_group.last_sequence_number(), Some(5));
+
+    // === Without a sequence number from the DS, the next one is used ===
+    let (commit, _welcome, _group_info) = bob_group
+        .self_update(provider, &bob_signer)
+        .expect("error updating")
+        .into_parts();
+    bob_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    process_commit(&mut alice_group, provider, commit).expect("error processing commit");
+    assert_eq!(alice_group.last_sequence_number(), Some(6));
+
+    // === Commits with an old sequence number are rejected ===
+    bob_group
+        .set_sequence_number(4)
+        .expect("error setting sequence number");
+    let (commit, _welcome, _group_info) = bob_group
+        .self_update(provider, &bob_signer)
+        .expect("error updating")
+        .into_parts();
+    assert_eq!(
+        process_commit(&mut alice_group, provider, commit)
+            .expect_err("processed a commit with an old sequence number"),
+        ProcessMessageError::SequencingError(SequencingError::NonMonotonicSequenceNumber)
+    );
+    bob_group.clear_pending_commit();
+
+    // === Commits without a sequence number are rejected ===
+    bob_group.set_configuration(
+        &MlsGroupConfig::builder()
+            .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+            .use_ratchet_tree_extension(true)
+            .build(),
+    );
+    let (commit, _welcome, _group_info) = bob_group
+        .self_update(provider, &bob_signer)
+        .expect("error updating")
+        .into_parts();
+    assert_eq!(
+        process_commit(&mut alice_group, provider, commit)
+            .expect_err("processed a commit without a sequence number"),
+        ProcessMessageError::SequencingError(SequencingError::MissingSequenceNumber)
+    );
+    assert_eq!(alice_group.last_sequence_number(), Some(6));
+}
@@ -0,0 +1,155 @@
+use std::thread;
+
+use openmls_traits::{types::Ciphersuite, OpenMlsProvider};
+use rstest::*;
+use rstest_reuse::{self, *};
+
+use super::*;
+use crate::{
+    group::{config::CryptoConfig, test_core_group::setup_client},
+    test_utils::*,
+};
+
+#[apply(ciphersuites_and_providers)]
+fn shared_group(ciphersuite: Ciphersuite, provider: &(impl OpenMlsProvider + Sync)) {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<SharedMlsGroup>();
+
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential, bob_kpb, bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+
+    let mls_group_config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .use_ratchet_tree_extension(true)
+        .build();
+
+    // === Alice creates a group and adds Bob ===
+    let mut alice_group = MlsGroup::new_with_group_id(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        GroupId::from_slice(b"Test Group"),
+        alice_credential_with_key,
+    )
+    .expect("error creating group");
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(provider, &alice_signer, &[bob_kpb.key_package().clone()])
+        .expect("error adding Bob")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+
+    let welcome = welcome.into_welcome().expect("expected a welcome");
+    let bob_group = MlsGroup::new_from_welcome(provider, &mls_group_config, welcome, None)
+        .expect("error joining group");
+    let bob_group = SharedMlsGroup::new(bob_group);
+
+    // === Bob sends messages while processing Alice's commit on another thread ===
+    let (commit, _welcome, _group_info) = alice_group
+        .self_update(provider, &alice_signer)
+        .expect("error updating")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+
+    let messages = thread::scope(|scope| {
+        let processing = scope.spawn(|| {
+            // Process and merge the commit while holding a single guard, so
+            // that no message is sent in between.
+            let mut group = bob_group.write().expect("error locking group");
+            let processed_message = group
+                .process_message(
+                    provider,
+                    commit
+                        .into_protocol_message()
+                        .expect("expected a protocol message"),
+                )
+                .expect("error processing commit");
+            match processed_message.into_content() {
+                ProcessedMessageContent::StagedCommitMessage(staged_commit) => group
+                    .merge_staged_commit(provider, *staged_commit)
+                    .expect("error merging commit"),
+                _ => panic!("Expected a staged commit."),
+            }
+        });
+        let sending = scope.spawn(|| {
+            (0..3)
+                .map(|_| {
+                    bob_group
+                        .write()
+                        .expect("error locking group")
+                        .create_message(provider, &bob_signer, b"Hi")
+                        .expect("error creating message")
+                })
+                .collect::<Vec<_>>()
+        });
+        processing.join().expect("processing panicked");
+        sending.join().expect("sending panicked")
+    });
+    assert_eq!(
+        bob_group.read().expect("error locking group").epoch(),
+        alice_group.epoch()
+    );
+
+    // Every message is in one of the two epochs. Alice can decrypt those that
+    // were sent after the commit was merged.
+    let mut decrypted = 0;
+    for message in messages {
+        let message = message
+            .into_protocol_message()
+            .expect("expected a protocol message");
+        if message.epoch() == alice_group.epoch() {
+            alice_group
+                .process_message(provider, message)
+                .expect("error processing message");
+            decrypted += 1;
+        } else {
+            assert_eq!(message.epoch().as_u64() + 1, alice_group.epoch().as_u64());
+        }
+    }
+    assert!(decrypted <= 3);
+
+    // === Non-blocking locking ===
+    {
+        let _reader = bob_group.read().expect("error locking group");
+        assert!(bob_group.try_read().is_ok());
+        assert_eq!(
+            bob_group.try_write().err(),
+            Some(SharedMlsGroupError::WouldBlock)
+        );
+    }
+    {
+        let _writer = bob_group.try_write().expect("error locking group");
+        assert_eq!(
+            bob_group.try_read().err(),
+            Some(SharedMlsGroupError::WouldBlock)
+        );
+    }
+
+    // === Only the last handle can unwrap the group ===
+    let handle = bob_group.clone();
+    let bob_group = bob_group
+        .try_into_inner()
+        .expect_err("there is another handle");
+    drop(handle);
+    let bob_group =
+        SharedMlsGroup::from(bob_group.try_into_inner().expect("this is the only handle"));
+
+    // === A panic while holding the lock poisons the group ===
+    let handle = bob_group.clone();
+    let result = thread::spawn(move || {
+        let _writer = handle.write().expect("error locking group");
+        panic!("panic while holding the lock");
+    })
+    .join();
+    assert!(result.is_err());
+    assert_eq!(bob_group.read().err(), Some(SharedMlsGroupError::Poisoned));
+    assert_eq!(
+        bob_group.try_write().err(),
+        Some(SharedMlsGroupError::Poisoned)
+    );
+}
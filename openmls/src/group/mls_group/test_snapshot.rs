@@ -0,0 +1,252 @@
+use openmls_traits::{types::Ciphersuite, OpenMlsProvider};
+use rstest::*;
+use rstest_reuse::{self, *};
+
+use super::*;
+use crate::{
+    group::{config::CryptoConfig, errors::SnapshotError, test_core_group::setup_client},
+    schedule::psk::{ExternalPsk, PreSharedKeyId, Psk, PskBundle},
+    test_utils::*,
+};
+
+/// Process the commit `message` in `group` and return the staged commit.
+fn stage(
+    group: &mut MlsGroup,
+    provider: &impl OpenMlsProvider,
+    message: &MlsMessageOut,
+) -> StagedCommit {
+    let processed = group
+        .process_message(
+            provider,
+            message
+                .clone()
+                .into_protocol_message()
+                .expect("expected a protocol message"),
+        )
+        .expect("error processing commit");
+    match processed.into_content() {
+        ProcessedMessageContent::StagedCommitMessage(staged_commit) => *staged_commit,
+        _ => panic!("expected a commit"),
+    }
+}
+
+#[apply(ciphersuites_and_providers)]
+fn snapshot_and_rollback(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential, bob_kpb, bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+
+    let config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .use_ratchet_tree_extension(true)
+        .build();
+
+    let mut alice_group =
+        MlsGroup::new(provider, &alice_signer, &config, alice_credential_with_key)
+            .expect("error creating group");
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(provider, &alice_signer, &[bob_kpb.key_package().clone()])
+        .expect("error adding Bob")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    let welcome = welcome.into_welcome().expect("expected a welcome");
+    let mut bob_group =
+        MlsGroup::new_from_welcome(provider, &config, welcome, None).expect("error joining group");
+
+    assert_eq!(
+        alice_group.release_snapshot(provider),
+        Err(SnapshotError::NoSnapshot)
+    );
+
+    // === Alice proposes an update and Bob commits it ===
+    let (proposal, _proposal_ref) = alice_group
+        .propose_self_update(provider, &alice_signer, None)
+        .expect("error proposing update");
+    let processed = bob_group
+        .process_message(provider, proposal.into_protocol_message().unwrap())
+        .expect("error processing proposal");
+    match processed.into_content() {
+        ProcessedMessageContent::ProposalMessage(proposal) => {
+            bob_group.store_pending_proposal(*proposal)
+        }
+        _ => panic!("expected a proposal"),
+    }
+    let (commit, _welcome, _group_info) = bob_group
+        .commit_to_pending_proposals(provider, &bob_signer)
+        .expect("error committing")
+        .into_parts();
+    bob_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+
+    // === Alice merges the commit tentatively and rolls it back ===
+    alice_group
+        .snapshot(provider)
+        .expect("error taking snapshot");
+    assert!(alice_group.has_snapshot());
+    assert_eq!(
+        alice_group.snapshot(provider),
+        Err(SnapshotError::SnapshotActive)
+    );
+    let epoch = alice_group.epoch();
+    let epoch_authenticator = alice_group.epoch_authenticator().clone();
+
+    let staged_commit = stage(&mut alice_group, provider, &commit);
+    alice_group
+        .merge_staged_commit(provider, staged_commit)
+        .expect("error merging commit");
+    assert_eq!(alice_group.epoch(), bob_group.epoch());
+
+    // Messages can't be sent while the snapshot is taken.
+    assert_eq!(
+        alice_group
+            .create_message(provider, &alice_signer, b"hello")
+            .expect_err("sent a message with a snapshot"),
+        CreateMessageError::GroupStateError(MlsGroupStateError::SnapshotActive)
+    );
+    assert!(matches!(
+        alice_group.self_update(provider, &alice_signer),
+        Err(SelfUpdateError::GroupStateError(
+            MlsGroupStateError::SnapshotActive
+        ))
+    ));
+
+    alice_group.rollback(provider).expect("error rolling back");
+    assert!(!alice_group.has_snapshot());
+    assert_eq!(alice_group.epoch(), epoch);
+    assert_eq!(
+        alice_group.epoch_authenticator().as_slice(),
+        epoch_authenticator.as_slice()
+    );
+    assert_eq!(
+        alice_group.rollback(provider),
+        Err(SnapshotError::NoSnapshot)
+    );
+
+    // === The rolled back commit can be merged again ===
+    alice_group
+        .snapshot(provider)
+        .expect("error taking snapshot");
+    let staged_commit = stage(&mut alice_group, provider, &commit);
+    alice_group
+        .merge_staged_commit(provider, staged_commit)
+        .expect("error merging commit");
+    alice_group
+        .release_snapshot(provider)
+        .expect("error releasing snapshot");
+    assert_eq!(
+        alice_group.epoch_authenticator().as_slice(),
+        bob_group.epoch_authenticator().as_slice()
+    );
+
+    // The group can send messages again.
+    let message = alice_group
+        .create_message(provider, &alice_signer, b"hello")
+        .expect("error creating message");
+    let processed = bob_group
+        .process_message(provider, message.into_protocol_message().unwrap())
+        .expect("error processing message");
+    assert!(matches!(
+        processed.into_content(),
+        ProcessedMessageContent::ApplicationMessage(_)
+    ));
+}
+
+#[apply(ciphersuites_and_providers)]
+fn rollback_restores_key_store(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential, bob_kpb, bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+
+    let config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .use_ratchet_tree_extension(true)
+        .build();
+
+    let mut alice_group =
+        MlsGroup::new(provider, &alice_signer, &config, alice_credential_with_key)
+            .expect("error creating group");
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(provider, &alice_signer, &[bob_kpb.key_package().clone()])
+        .expect("error adding Bob")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    let welcome = welcome.into_welcome().expect("expected a welcome");
+    let mut bob_group =
+        MlsGroup::new_from_welcome(provider, &config, welcome, None).expect("error joining group");
+
+    // === Bob commits to an external PSK ===
+    let psk_id = PreSharedKeyId::new(
+        ciphersuite,
+        provider.rand(),
+        Psk::External(ExternalPsk::new(b"psk".to_vec())),
+    )
+    .expect("error creating PSK ID");
+    psk_id
+        .write_to_key_store(provider, ciphersuite, b"secret")
+        .expect("error storing PSK");
+    let (proposal, _proposal_ref) = bob_group
+        .propose_external_psk(provider, &bob_signer, psk_id.clone())
+        .expect("error proposing PSK");
+    let (commit, _welcome, _group_info) = bob_group
+        .commit_to_pending_proposals(provider, &bob_signer)
+        .expect("error committing")
+        .into_parts();
+    bob_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    let processed = alice_group
+        .process_message(provider, proposal.into_protocol_message().unwrap())
+        .expect("error processing proposal");
+    match processed.into_content() {
+        ProcessedMessageContent::ProposalMessage(proposal) => {
+            alice_group.store_pending_proposal(*proposal)
+        }
+        _ => panic!("expected a proposal"),
+    }
+
+    // === Alice merges the commit with the PSK and rolls it back ===
+    let psk_in_key_store = || {
+        provider
+            .key_store()
+            .read::<PskBundle>(&psk_id.keystore_id().expect("error computing PSK ID"))
+            .is_some()
+    };
+    let epoch_keypairs = alice_group.group.read_epoch_keypairs(provider.key_store());
+    assert!(!epoch_keypairs.is_empty());
+    alice_group
+        .snapshot(provider)
+        .expect("error taking snapshot");
+    let staged_commit = stage(&mut alice_group, provider, &commit);
+    alice_group
+        .merge_staged_commit(provider, staged_commit)
+        .expect("error merging commit");
+    assert!(psk_in_key_store());
+    assert_ne!(
+        alice_group.group.read_epoch_keypairs(provider.key_store()),
+        epoch_keypairs
+    );
+
+    alice_group.rollback(provider).expect("error rolling back");
+    assert!(psk_in_key_store());
+    assert_eq!(
+        alice_group.group.read_epoch_keypairs(provider.key_store()),
+        epoch_keypairs
+    );
+
+    // === The commit is merged again with the PSK from the key store ===
+    let staged_commit = stage(&mut alice_group, provider, &commit);
+    alice_group
+        .merge_staged_commit(provider, staged_commit)
+        .expect("error merging commit");
+    assert_eq!(
+        alice_group.epoch_authenticator().as_slice(),
+        bob_group.epoch_authenticator().as_slice()
+    );
+}
@@ -0,0 +1,105 @@
+use openmls_traits::{types::Ciphersuite, OpenMlsProvider};
+use rstest::*;
+use rstest_reuse::{self, *};
+
+use super::*;
+use crate::{
+    group::{config::CryptoConfig, test_core_group::setup_client},
+    test_utils::*,
+};
+
+#[apply(ciphersuites_and_providers)]
+fn speculative_clone(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential, bob_kpb, _bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+    let (_charlie_credential, charlie_kpb, _charlie_signer, _charlie_pk) =
+        setup_client("Charlie", ciphersuite, provider);
+
+    let config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .use_ratchet_tree_extension(true)
+        .build();
+
+    let mut alice_group =
+        MlsGroup::new(provider, &alice_signer, &config, alice_credential_with_key)
+            .expect("error creating group");
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(provider, &alice_signer, &[bob_kpb.key_package().clone()])
+        .expect("error adding Bob")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    alice_group
+        .save(provider.key_store())
+        .expect("error saving group");
+    let welcome = welcome.into_welcome().expect("expected a welcome");
+    let mut bob_group =
+        MlsGroup::new_from_welcome(provider, &config, welcome, None).expect("error joining group");
+
+    // === Alice checks what adding Charlie and updating would look like ===
+    let mut speculative_group = alice_group
+        .speculative_clone()
+        .expect("error cloning group");
+    assert_eq!(speculative_group.epoch(), alice_group.epoch());
+    let epoch = speculative_group.apply(provider, |group, provider| {
+        group
+            .add_members(
+                provider,
+                &alice_signer,
+                &[charlie_kpb.key_package().clone()],
+            )
+            .expect("error adding Charlie");
+        group
+            .merge_pending_commit(provider)
+            .expect("error merging commit");
+        group
+            .self_update(provider, &alice_signer)
+            .expect("error updating");
+        group
+            .merge_pending_commit(provider)
+            .expect("error merging commit");
+        group
+            .save(provider.key_store())
+            .expect("error saving group");
+        group.epoch()
+    });
+    assert_eq!(epoch.as_u64(), alice_group.epoch().as_u64() + 2);
+    assert_eq!(speculative_group.epoch(), epoch);
+    assert_eq!(speculative_group.members().count(), 3);
+    assert_ne!(
+        speculative_group.export_group_context().tree_hash(),
+        alice_group.export_group_context().tree_hash()
+    );
+
+    // The group and the key store are unchanged.
+    assert_eq!(alice_group.members().count(), 2);
+    let loaded_group =
+        MlsGroup::load(alice_group.group_id(), provider.key_store()).expect("error loading group");
+    assert_eq!(loaded_group.epoch(), alice_group.epoch());
+    drop(speculative_group);
+
+    // === Alice actually updates and Bob follows ===
+    let (commit, _welcome, _group_info) = alice_group
+        .self_update(provider, &alice_signer)
+        .expect("error updating")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    let processed = bob_group
+        .process_message(provider, commit.into_protocol_message().unwrap())
+        .expect("error processing commit");
+    match processed.into_content() {
+        ProcessedMessageContent::StagedCommitMessage(staged_commit) => bob_group
+            .merge_staged_commit(provider, *staged_commit)
+            .expect("error merging commit"),
+        _ => panic!("expected a commit"),
+    }
+    assert_eq!(
+        alice_group.export_group_context().tree_hash(),
+        bob_group.export_group_context().tree_hash()
+    );
+}
@@ -0,0 +1,344 @@
+use openmls_traits::{types::Ciphersuite, OpenMlsProvider};
+use rstest::*;
+use rstest_reuse::{self, *};
+
+use super::*;
+use crate::{
+    group::{config::CryptoConfig, errors::WelcomeError, test_core_group::setup_client},
+    test_utils::*,
+};
+
+#[apply(ciphersuites_and_providers)]
+fn staged_welcome(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential, bob_kpb, _bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+
+    let mls_group_config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .use_ratchet_tree_extension(true)
+        .build();
+
+    // === Alice creates a group and invites Bob ===
+    let mut alice_group = MlsGroup::new(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        alice_credential_with_key.clone(),
+    )
+    .expect("error creating group");
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(provider, &alice_signer, &[bob_kpb.key_package().clone()])
+        .expect("error adding Bob")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    let welcome = welcome.into_welcome().expect("expected a welcome");
+
+    // === Bob inspects the invitation ===
+    let staged_welcome =
+        StagedWelcome::new_from_welcome(provider, &mls_group_config, welcome.clone(), None)
+            .expect("error staging welcome");
+    assert_eq!(staged_welcome.welcome_sender_index(), LeafNodeIndex::new(0));
+    assert_eq!(
+        staged_welcome
+            .welcome_sender()
+            .expect("error reading the welcome sender"),
+        &alice_credential_with_key.credential
+    );
+    assert_eq!(staged_welcome.ciphersuite(), ciphersuite);
+    assert_eq!(staged_welcome.group_id(), alice_group.group_id());
+    assert_eq!(staged_welcome.epoch(), alice_group.epoch());
+    assert_eq!(
+        staged_welcome.members().collect::<Vec<_>>(),
+        alice_group.members().collect::<Vec<_>>()
+    );
+    assert_eq!(staged_welcome.own_leaf_index(), LeafNodeIndex::new(1));
+    assert_eq!(
+        staged_welcome.group_context_extensions(),
+        alice_group.export_group_context().extensions()
+    );
+    // The GroupInfo extensions are those of the sender, here the ratchet
+    // tree and the external public key.
+    let group_info_extensions = staged_welcome.group_info_extensions();
+    assert!(group_info_extensions.ratchet_tree().is_some());
+    assert!(group_info_extensions.external_pub().is_some());
+
+    // Declining the invitation doesn't consume the key package, so the
+    // Welcome can be staged again.
+    drop(staged_welcome);
+    let staged_welcome =
+        StagedWelcome::new_from_welcome(provider, &mls_group_config, welcome.clone(), None)
+            .expect("error staging welcome");
+
+    // === Bob joins ===
+    let key_package_ref = bob_kpb
+        .key_package()
+        .hash_ref(provider.crypto())
+        .expect("error computing key package reference");
+    assert_eq!(
+        staged_welcome.consumed_key_package(),
+        Some(&key_package_ref)
+    );
+    assert!(!KeyPackage::is_consumed(
+        provider.key_store(),
+        &key_package_ref
+    ));
+    let mut bob_group = staged_welcome
+        .into_group(provider)
+        .expect("error joining group");
+    let message = alice_group
+        .create_message(provider, &alice_signer, b"Hello Bob")
+        .expect("error creating message");
+    let processed_message = bob_group
+        .process_message(
+            provider,
+            message
+                .into_protocol_message()
+                .expect("expected a protocol message"),
+        )
+        .expect("error processing message");
+    match processed_message.into_content() {
+        ProcessedMessageContent::ApplicationMessage(application_message) => {
+            assert_eq!(application_message.into_bytes(), b"Hello Bob")
+        }
+        _ => panic!("Expected an application message."),
+    }
+
+    // The key package was consumed when Bob joined.
+    assert!(KeyPackage::is_consumed(
+        provider.key_store(),
+        &key_package_ref
+    ));
+    assert!(provider
+        .key_store()
+        .read::<KeyPackage>(key_package_ref.as_slice())
+        .is_none());
+    assert_eq!(
+        StagedWelcome::new_from_welcome(provider, &mls_group_config, welcome, None)
+            .expect_err("expected an error"),
+        WelcomeError::KeyPackageConsumed
+    );
+}
+
+#[apply(ciphersuites_and_providers)]
+fn fetch_ratchet_tree(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential, bob_kpb, _bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+
+    let mls_group_config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .build();
+
+    // === Alice creates a group without the ratchet tree extension ===
+    let mut alice_group = MlsGroup::new(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        alice_credential_with_key,
+    )
+    .expect("error creating group");
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(provider, &alice_signer, &[bob_kpb.key_package().clone()])
+        .expect("error adding Bob")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    let welcome = welcome.into_welcome().expect("expected a welcome");
+    let tree_hash = alice_group.export_group_context().tree_hash().to_vec();
+
+    // === The tree isn't available ===
+    let mut fetched = Vec::new();
+    assert_eq!(
+        MlsGroup::new_from_welcome_fetching_tree(
+            provider,
+            &mls_group_config,
+            welcome.clone(),
+            |expected_tree_hash| {
+                fetched.push(expected_tree_hash.to_vec());
+                None
+            }
+        )
+        .expect_err("expected an error"),
+        WelcomeError::MissingRatchetTree
+    );
+    assert_eq!(fetched, vec![tree_hash.clone()]);
+
+    // === Bob fetches the tree and joins ===
+    let bob_group = MlsGroup::new_from_welcome_fetching_tree(
+        provider,
+        &mls_group_config,
+        welcome,
+        |expected_tree_hash| {
+            assert_eq!(expected_tree_hash, tree_hash);
+            Some(alice_group.export_ratchet_tree().into())
+        },
+    )
+    .expect("error joining group");
+    assert_eq!(bob_group.epoch(), alice_group.epoch());
+    assert_eq!(bob_group.export_group_context().tree_hash(), tree_hash);
+}
+
+#[apply(ciphersuites_and_providers)]
+fn welcome_expectations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential, bob_kpb, _bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+
+    let mls_group_config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .use_ratchet_tree_extension(true)
+        .build();
+
+    // === Alice creates a group and invites Bob ===
+    let mut alice_group = MlsGroup::new_with_group_id(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        GroupId::from_slice(b"Book club"),
+        alice_credential_with_key,
+    )
+    .expect("error creating group");
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(provider, &alice_signer, &[bob_kpb.key_package().clone()])
+        .expect("error adding Bob")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    let welcome = welcome.into_welcome().expect("expected a welcome");
+
+    let expectations = WelcomeExpectations::new()
+        .group_id(GroupId::from_slice(b"Book club"))
+        .ciphersuite(ciphersuite)
+        .inviter_identity(b"Alice".to_vec())
+        .max_members(2);
+    let other_ciphersuite = if ciphersuite == Ciphersuite::MLS_128_DHKEMP256_AES128GCM_SHA256_P256 {
+        Ciphersuite::MLS_128_DHKEMX25519_AES128GCM_SHA256_Ed25519
+    } else {
+        Ciphersuite::MLS_128_DHKEMP256_AES128GCM_SHA256_P256
+    };
+    for (expectations, error) in [
+        (
+            expectations
+                .clone()
+                .group_id(GroupId::from_slice(b"Chess club")),
+            WelcomeError::UnexpectedGroupId,
+        ),
+        (
+            expectations.clone().ciphersuite(other_ciphersuite),
+            WelcomeError::UnexpectedCiphersuite,
+        ),
+        (
+            expectations.clone().inviter_identity(b"Charlie".to_vec()),
+            WelcomeError::UnexpectedInviter,
+        ),
+        (
+            expectations.clone().max_members(1),
+            WelcomeError::TooManyMembers,
+        ),
+    ] {
+        assert_eq!(
+            StagedWelcome::new_from_welcome_with_expectations(
+                provider,
+                &mls_group_config,
+                welcome.clone(),
+                None,
+                &expectations,
+            )
+            .expect_err("expected an error"),
+            error
+        );
+    }
+
+    // === The Welcome message matches the expectations ===
+    let staged_welcome = StagedWelcome::new_from_welcome_with_expectations(
+        provider,
+        &mls_group_config,
+        welcome,
+        None,
+        &expectations,
+    )
+    .expect("error staging welcome");
+    assert_eq!(staged_welcome.group_id(), alice_group.group_id());
+}
+
+#[apply(ciphersuites_and_providers)]
+fn welcome_batch(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (bob_credential_with_key, _bob_kpb, bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+
+    let mls_group_config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .use_ratchet_tree_extension(true)
+        .build();
+    let config_without_tree = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .build();
+
+    // === Alice invites Bob to three groups with different key packages ===
+    let mut alice_groups = Vec::new();
+    let mut welcomes = Vec::new();
+    for config in [&mls_group_config, &mls_group_config, &config_without_tree] {
+        let bob_kpb = KeyPackageBundle::new(
+            provider,
+            &bob_signer,
+            ciphersuite,
+            bob_credential_with_key.clone(),
+        );
+        let mut alice_group = MlsGroup::new(
+            provider,
+            &alice_signer,
+            config,
+            alice_credential_with_key.clone(),
+        )
+        .expect("error creating group");
+        let (_commit, welcome, _group_info) = alice_group
+            .add_members(provider, &alice_signer, &[bob_kpb.key_package().clone()])
+            .expect("error adding Bob")
+            .into_parts();
+        alice_group
+            .merge_pending_commit(provider)
+            .expect("error merging commit");
+        welcomes.push((welcome.into_welcome().expect("expected a welcome"), None));
+        alice_groups.push(alice_group);
+    }
+
+    // === Bob joins all groups at once ===
+    // The last Welcome message lacks the ratchet tree, which only fails
+    // that entry.
+    let results = MlsGroup::new_from_welcomes(provider, &mls_group_config, welcomes.clone());
+    assert_eq!(results.len(), 3);
+    for (result, alice_group) in results.iter().zip(&alice_groups).take(2) {
+        let bob_group = result.as_ref().expect("error joining group");
+        assert_eq!(bob_group.group_id(), alice_group.group_id());
+        assert_eq!(bob_group.epoch(), alice_group.epoch());
+    }
+    assert_eq!(
+        results[2].as_ref().expect_err("expected an error"),
+        &WelcomeError::MissingRatchetTree
+    );
+
+    // The key packages of the joined groups were consumed.
+    let results = StagedWelcome::new_from_welcomes(provider, &mls_group_config, welcomes);
+    assert_eq!(
+        results[0].as_ref().expect_err("expected an error"),
+        &WelcomeError::KeyPackageConsumed
+    );
+    assert_eq!(
+        results[1].as_ref().expect_err("expected an error"),
+        &WelcomeError::KeyPackageConsumed
+    );
+    assert_eq!(
+        results[2].as_ref().expect_err("expected an error"),
+        &WelcomeError::MissingRatchetTree
+    );
+}
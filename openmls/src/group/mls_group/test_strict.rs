@@ -0,0 +1,101 @@
+use openmls_traits::{types::Ciphersuite, OpenMlsProvider};
+use rstest::*;
+use rstest_reuse::{self, *};
+
+use super::*;
+use crate::{
+    group::{config::CryptoConfig, test_core_group::setup_client},
+    test_utils::*,
+};
+
+#[apply(ciphersuites_and_providers)]
+fn strict_api(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential, bob_kpb, bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+
+    let config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .use_ratchet_tree_extension(true)
+        .build();
+
+    let mut alice_group =
+        MlsGroup::new(provider, &alice_signer, &config, alice_credential_with_key)
+            .expect("error creating group");
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(provider, &alice_signer, &[bob_kpb.key_package().clone()])
+        .expect("error adding Bob")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    let welcome = welcome.into_welcome().expect("expected a welcome");
+    let mut bob_group =
+        MlsGroup::new_from_welcome(provider, &config, welcome, None).expect("error joining group");
+
+    // === Alice sends a message while her commit is pending ===
+    let (commit, _welcome, _group_info) = alice_group
+        .self_update(provider, &alice_signer)
+        .expect("error updating")
+        .into_parts();
+    assert_eq!(
+        alice_group
+            .create_message(provider, &alice_signer, b"hello")
+            .expect_err("sent a message with a pending commit"),
+        CreateMessageError::GroupStateError(MlsGroupStateError::ApiMisuse(
+            ApiMisuseError::MessageWithPendingCommit
+        ))
+    );
+
+    // === Alice processes her own commit ===
+    let error = alice_group
+        .process_message(provider, commit.clone().into_protocol_message().unwrap())
+        .expect_err("processed an own commit");
+    assert_eq!(
+        error,
+        ProcessMessageError::GroupStateError(MlsGroupStateError::ApiMisuse(
+            ApiMisuseError::OwnMessage
+        ))
+    );
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+
+    // === Alice processes her own application message ===
+    let message = alice_group
+        .create_message(provider, &alice_signer, b"hello")
+        .expect("error creating message");
+    assert_eq!(
+        alice_group
+            .process_message(provider, message.clone().into_protocol_message().unwrap())
+            .expect_err("processed an own message"),
+        ProcessMessageError::GroupStateError(MlsGroupStateError::ApiMisuse(
+            ApiMisuseError::OwnMessage
+        ))
+    );
+
+    // === Bob merges a staged commit after the epoch changed ===
+    let staged_commit = match bob_group
+        .process_message(provider, commit.into_protocol_message().unwrap())
+        .expect("error processing commit")
+        .into_content()
+    {
+        ProcessedMessageContent::StagedCommitMessage(staged_commit) => *staged_commit,
+        _ => panic!("expected a commit"),
+    };
+    bob_group
+        .self_update(provider, &bob_signer)
+        .expect("error updating");
+    bob_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    assert!(matches!(
+        bob_group
+            .merge_staged_commit(provider, staged_commit)
+            .expect_err("merged a stale staged commit"),
+        MergeCommitError::GroupStateError(MlsGroupStateError::ApiMisuse(
+            ApiMisuseError::StaleStagedCommit
+        ))
+    ));
+}
@@ -0,0 +1,292 @@
+use openmls_traits::{types::Ciphersuite, OpenMlsProvider};
+use rstest::*;
+use rstest_reuse::{self, *};
+
+use super::*;
+use crate::{
+    extensions::ParentGroupExtension,
+    group::{
+        config::CryptoConfig,
+        errors::{SubgroupError, WelcomeError},
+        test_core_group::setup_client,
+    },
+    schedule::psk::{PreSharedKeyId, Psk, ResumptionPsk, ResumptionPskUsage},
+    test_utils::*,
+};
+
+#[apply(ciphersuites_and_providers)]
+fn subgroups(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (bob_credential_with_key, bob_kpb, bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+    let (_charlie_credential, charlie_kpb, _charlie_signer, _charlie_pk) =
+        setup_client("Charlie", ciphersuite, provider);
+    let (dave_credential_with_key, dave_kpb, dave_signer, _dave_pk) =
+        setup_client("Dave", ciphersuite, provider);
+
+    let mls_group_config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .use_ratchet_tree_extension(true)
+        .number_of_resumption_psks(4)
+        .build();
+
+    // === Alice creates a group with Bob and Charlie ===
+    let mut alice_group = MlsGroup::new_with_group_id(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        GroupId::from_slice(b"Parent group"),
+        alice_credential_with_key.clone(),
+    )
+    .expect("error creating group");
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(
+            provider,
+            &alice_signer,
+            &[
+                bob_kpb.key_package().clone(),
+                charlie_kpb.key_package().clone(),
+            ],
+        )
+        .expect("error adding Bob and Charlie")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    let welcome = welcome.into_welcome().expect("expected a welcome");
+    let mut bob_group = MlsGroup::new_from_welcome(provider, &mls_group_config, welcome, None)
+        .expect("error joining group");
+    assert!(alice_group.parent_group().is_none());
+
+    // === Only members of the parent group can be added to a subgroup ===
+    assert_eq!(
+        alice_group
+            .create_subgroup(
+                provider,
+                &alice_signer,
+                &mls_group_config,
+                GroupId::from_slice(b"Subgroup"),
+                alice_credential_with_key.clone(),
+                &[dave_kpb.key_package().clone()],
+            )
+            .expect_err("added Dave to a subgroup"),
+        SubgroupError::NotAParentMember
+    );
+
+    // === Alice creates a subgroup with Bob ===
+    let bob_key_package = KeyPackageBundle::new(
+        provider,
+        &bob_signer,
+        ciphersuite,
+        bob_credential_with_key.clone(),
+    )
+    .key_package()
+    .clone();
+    let (mut alice_subgroup, welcome, _group_info) = alice_group
+        .create_subgroup(
+            provider,
+            &alice_signer,
+            &mls_group_config,
+            GroupId::from_slice(b"Subgroup"),
+            alice_credential_with_key.clone(),
+            &[bob_key_package],
+        )
+        .expect("error creating subgroup");
+    assert_eq!(alice_subgroup.epoch(), GroupEpoch::from(1));
+    assert_eq!(alice_subgroup.members().count(), 2);
+    assert_eq!(
+        alice_subgroup.parent_group(),
+        Some(ParentGroupExtension::new(
+            alice_group.group_id().clone(),
+            alice_group.epoch()
+        ))
+    );
+
+    // The parent group moves on before Bob joins the subgroup.
+    let (commit, _welcome, _group_info) = alice_group
+        .self_update(provider, &alice_signer)
+        .expect("error updating")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    let processed_message = bob_group
+        .process_message(
+            provider,
+            commit
+                .into_protocol_message()
+                .expect("expected a protocol message"),
+        )
+        .expect("error processing commit");
+    match processed_message.into_content() {
+        ProcessedMessageContent::StagedCommitMessage(staged_commit) => bob_group
+            .merge_staged_commit(provider, *staged_commit)
+            .expect("error merging commit"),
+        _ => panic!("Expected a staged commit."),
+    }
+
+    let welcome = welcome.into_welcome().expect("expected a welcome");
+    let mut bob_subgroup = bob_group
+        .join_subgroup(provider, &mls_group_config, welcome, None)
+        .expect("error joining subgroup");
+    assert_eq!(bob_subgroup.parent_group(), alice_subgroup.parent_group());
+
+    // The members of the subgroup can exchange messages.
+    let message = alice_subgroup
+        .create_message(provider, &alice_signer, b"Hello Bob")
+        .expect("error creating message");
+    let processed_message = bob_subgroup
+        .process_message(
+            provider,
+            message
+                .into_protocol_message()
+                .expect("expected a protocol message"),
+        )
+        .expect("error processing message");
+    match processed_message.into_content() {
+        ProcessedMessageContent::ApplicationMessage(message) => {
+            assert_eq!(message.into_bytes(), b"Hello Bob")
+        }
+        _ => panic!("Expected an application message."),
+    }
+
+    // === Subgroups are listed ===
+    let other_group = MlsGroup::new(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        alice_credential_with_key,
+    )
+    .expect("error creating group");
+    let groups = [&alice_group, &alice_subgroup, &other_group];
+    let subgroups = alice_group.subgroups(groups);
+    assert_eq!(subgroups.len(), 1);
+    assert_eq!(subgroups[0].group_id(), alice_subgroup.group_id());
+    assert!(alice_subgroup.subgroups(groups).is_empty());
+
+    // === Groups that aren't subgroups can't be joined as subgroups ===
+    let bob_key_package = KeyPackageBundle::new(
+        provider,
+        &bob_signer,
+        ciphersuite,
+        bob_credential_with_key.clone(),
+    )
+    .key_package()
+    .clone();
+    let mut dave_group = MlsGroup::new(
+        provider,
+        &dave_signer,
+        &mls_group_config,
+        dave_credential_with_key.clone(),
+    )
+    .expect("error creating group");
+    let (_commit, welcome, _group_info) = dave_group
+        .add_members(provider, &dave_signer, &[bob_key_package])
+        .expect("error adding Bob")
+        .into_parts();
+    let welcome = welcome.into_welcome().expect("expected a welcome");
+    assert_eq!(
+        bob_group
+            .join_subgroup(provider, &mls_group_config, welcome, None)
+            .expect_err("joined a group that isn't a subgroup"),
+        SubgroupError::NotASubgroup
+    );
+
+    // === Outsiders can't forge the link to the parent group ===
+    let bob_key_package =
+        KeyPackageBundle::new(provider, &bob_signer, ciphersuite, bob_credential_with_key)
+            .key_package()
+            .clone();
+    let mut dave_group = MlsGroup::new_with_parent_group(
+        provider,
+        &dave_signer,
+        &mls_group_config,
+        GroupId::random(provider.rand()),
+        dave_credential_with_key,
+        Some(ParentGroupExtension::new(
+            alice_group.group_id().clone(),
+            alice_group.epoch(),
+        )),
+    )
+    .expect("error creating group");
+    let (_commit, welcome, _group_info) = dave_group
+        .add_members(provider, &dave_signer, &[bob_key_package])
+        .expect("error adding Bob")
+        .into_parts();
+    let welcome = welcome.into_welcome().expect("expected a welcome");
+    assert_eq!(
+        bob_group
+            .join_subgroup(provider, &mls_group_config, welcome, None)
+            .expect_err("joined a forged subgroup"),
+        SubgroupError::WelcomeError(WelcomeError::InvalidParentGroupLink)
+    );
+}
+
+#[apply(ciphersuites_and_providers)]
+fn subgroup_commit_contains_branch_psk(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential_with_key, bob_kpb, _bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+
+    let mls_group_config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .build();
+    let alice_group = MlsGroup::new(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        alice_credential_with_key.clone(),
+    )
+    .expect("error creating group");
+
+    let psk_id = PreSharedKeyId::new(
+        ciphersuite,
+        provider.rand(),
+        Psk::Resumption(ResumptionPsk::new(
+            ResumptionPskUsage::Branch,
+            alice_group.group_id().clone(),
+            alice_group.epoch(),
+        )),
+    )
+    .expect("error creating PSK ID");
+    psk_id
+        .write_to_key_store(
+            provider,
+            ciphersuite,
+            alice_group.resumption_psk_secret().as_slice(),
+        )
+        .expect("error storing PSK");
+
+    let mut alice_subgroup = MlsGroup::new_with_parent_group(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        GroupId::from_slice(b"Subgroup"),
+        alice_credential_with_key,
+        Some(ParentGroupExtension::new(
+            alice_group.group_id().clone(),
+            alice_group.epoch(),
+        )),
+    )
+    .expect("error creating subgroup");
+    alice_subgroup
+        .add_members_with_psks(
+            provider,
+            &alice_signer,
+            &[bob_kpb.key_package().clone()],
+            vec![psk_id.clone()],
+        )
+        .expect("error adding Bob");
+
+    // The branch PSK is committed with a PreSharedKey proposal.
+    let psk_ids = alice_subgroup
+        .pending_commit()
+        .expect("no pending commit")
+        .psk_proposals()
+        .map(|psk_proposal| psk_proposal.psk_proposal().clone().into_psk_id())
+        .collect::<Vec<_>>();
+    assert_eq!(psk_ids, vec![psk_id]);
+    assert!(alice_subgroup.pending_proposals().next().is_none());
+}
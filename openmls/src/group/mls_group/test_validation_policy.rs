@@ -0,0 +1,265 @@
+use openmls_traits::{clock::OpenMlsClock, types::Ciphersuite, OpenMlsProvider};
+use rstest::*;
+use rstest_reuse::{self, *};
+use tls_codec::{Deserialize as _, Serialize as _};
+
+use super::*;
+use crate::{
+    group::{
+        config::{CryptoConfig, ValidationPolicy, ValidationSeverity},
+        errors::ValidationError,
+        test_core_group::setup_client,
+    },
+    key_packages::{errors::KeyPackageVerifyError, Lifetime, SystemClock},
+    test_utils::*,
+};
+
+/// The time at which Charlie's key package was created.
+const KEY_PACKAGE_TIME: u64 = 1_000_000;
+
+struct FixedClock(u64);
+
+impl OpenMlsClock for FixedClock {
+    fn now(&self) -> u64 {
+        self.0
+    }
+}
+
+/// A provider that uses the crypto and the key store of `provider`, but
+/// takes the current time from `clock`.
+struct FixedClockProvider<'a, P> {
+    provider: &'a P,
+    clock: FixedClock,
+}
+
+impl<P: OpenMlsProvider> OpenMlsProvider for FixedClockProvider<'_, P> {
+    type CryptoProvider = P::CryptoProvider;
+    type RandProvider = P::RandProvider;
+    type KeyStoreProvider = P::KeyStoreProvider;
+    type ClockProvider = FixedClock;
+
+    fn crypto(&self) -> &Self::CryptoProvider {
+        self.provider.crypto()
+    }
+
+    fn rand(&self) -> &Self::RandProvider {
+        self.provider.rand()
+    }
+
+    fn key_store(&self) -> &Self::KeyStoreProvider {
+        self.provider.key_store()
+    }
+
+    fn clock(&self) -> &Self::ClockProvider {
+        &self.clock
+    }
+}
+
+#[test]
+fn validation_policy_profiles() {
+    let strict = ValidationPolicy::strict();
+    assert_eq!(strict, ValidationPolicy::default());
+    assert_eq!(strict.lifetime(), ValidationSeverity::Error);
+    assert_eq!(strict.capabilities(), ValidationSeverity::Error);
+    assert_eq!(strict.extensions(), ValidationSeverity::Error);
+    assert_eq!(strict.clock_skew(), 0);
+
+    let lenient = ValidationPolicy::lenient();
+    assert_eq!(lenient.lifetime(), ValidationSeverity::Warning);
+    assert_eq!(lenient.capabilities(), ValidationSeverity::Warning);
+    assert_eq!(lenient.extensions(), ValidationSeverity::Warning);
+
+    let policy = ValidationPolicy::strict()
+        .with_lifetime(ValidationSeverity::Warning)
+        .with_clock_skew(60);
+    assert_eq!(policy.lifetime(), ValidationSeverity::Warning);
+    assert_eq!(policy.capabilities(), ValidationSeverity::Error);
+    assert_eq!(policy.clock_skew(), 60);
+
+    let config = MlsGroupConfig::builder().validation_policy(policy).build();
+    assert_eq!(config.validation_policy(), policy);
+    assert_eq!(
+        MlsGroupConfig::default().validation_policy(),
+        ValidationPolicy::strict()
+    );
+}
+
+#[apply(ciphersuites_and_providers)]
+fn expired_key_package(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential, bob_kpb, _bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+    let (charlie_credential_with_key, _charlie_kpb, charlie_signer, _charlie_pk) =
+        setup_client("Charlie", ciphersuite, provider);
+
+    let config_with_policy = |policy: ValidationPolicy| {
+        MlsGroupConfig::builder()
+            .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+            .use_ratchet_tree_extension(true)
+            .validation_policy(policy)
+            .build()
+    };
+    let config = config_with_policy(ValidationPolicy::strict());
+
+    let mut alice_group =
+        MlsGroup::new(provider, &alice_signer, &config, alice_credential_with_key)
+            .expect("error creating group");
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(provider, &alice_signer, &[bob_kpb.key_package().clone()])
+        .expect("error adding Bob")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    let welcome = welcome.into_welcome().expect("expected a welcome");
+    let mut bob_group =
+        MlsGroup::new_from_welcome(provider, &config, welcome, None).expect("error joining group");
+
+    // Charlie's key package expired a long time ago.
+    let charlie_key_package = KeyPackage::builder()
+        .key_package_lifetime(Lifetime::new_with_clock(60, &FixedClock(KEY_PACKAGE_TIME)))
+        .build(
+            CryptoConfig::with_default_version(ciphersuite),
+            provider,
+            &charlie_signer,
+            charlie_credential_with_key,
+        )
+        .expect("error creating key package");
+
+    // Alice proposes to add Charlie and Bob processes the proposal with a
+    // clock at `time`.
+    let mut propose_charlie = |bob_group: &mut MlsGroup, time: u64| {
+        let (proposal, _proposal_ref) = alice_group
+            .propose_add_member(provider, &alice_signer, &charlie_key_package)
+            .expect("error proposing Charlie");
+        let bob_provider = FixedClockProvider {
+            provider,
+            clock: FixedClock(time),
+        };
+        bob_group.process_message(&bob_provider, proposal.into_protocol_message().unwrap())
+    };
+    let now = SystemClock.now();
+
+    // === Bob rejects the proposal with the strict policy ===
+    assert_eq!(
+        propose_charlie(&mut bob_group, now).expect_err("accepted an expired key package"),
+        ProcessMessageError::ValidationError(ValidationError::KeyPackageVerifyError(
+            KeyPackageVerifyError::InvalidLifetime
+        ))
+    );
+
+    // === Bob accepts the proposal if his clock is at the creation time ===
+    propose_charlie(&mut bob_group, KEY_PACKAGE_TIME).expect("rejected a valid key package");
+
+    // === Bob accepts the proposal if the clock skew is tolerated ===
+    bob_group.set_configuration(&config_with_policy(
+        ValidationPolicy::strict().with_clock_skew(now - KEY_PACKAGE_TIME),
+    ));
+    propose_charlie(&mut bob_group, now).expect("rejected a key package within the clock skew");
+
+    // === Bob only warns about or skips the lifetime with a relaxed policy ===
+    for severity in [ValidationSeverity::Warning, ValidationSeverity::Ignore] {
+        bob_group.set_configuration(&config_with_policy(
+            ValidationPolicy::strict().with_lifetime(severity),
+        ));
+        let processed =
+            propose_charlie(&mut bob_group, now).expect("rejected an expired key package");
+        assert!(matches!(
+            processed.into_content(),
+            ProcessedMessageContent::ProposalMessage(_)
+        ));
+    }
+}
+
+#[apply(ciphersuites_and_providers)]
+fn envelope_version(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential, bob_kpb, _bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+
+    let config_with_policy = |policy: ValidationPolicy| {
+        MlsGroupConfig::builder()
+            .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+            .use_ratchet_tree_extension(true)
+            .validation_policy(policy)
+            .build()
+    };
+    let config = config_with_policy(ValidationPolicy::lenient());
+
+    let mut alice_group =
+        MlsGroup::new(provider, &alice_signer, &config, alice_credential_with_key)
+            .expect("error creating group");
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(provider, &alice_signer, &[bob_kpb.key_package().clone()])
+        .expect("error adding Bob")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    let welcome = welcome.into_welcome().expect("expected a welcome");
+    let mut bob_group =
+        MlsGroup::new_from_welcome(provider, &config, welcome, None).expect("error joining group");
+    assert_eq!(bob_group.version(), ProtocolVersion::Mls10);
+
+    // Alice's messages are wrapped in an MLS 1.0 draft envelope and go over
+    // the wire.
+    let draft_envelope = |message: MlsMessageOut| {
+        let message = MlsMessageOut {
+            version: ProtocolVersion::Mls10Draft11,
+            body: message.body,
+        };
+        let encoded = message
+            .tls_serialize_detached()
+            .expect("error encoding message");
+        let message_in =
+            MlsMessageIn::tls_deserialize_exact(encoded).expect("error decoding message");
+        assert_eq!(message_in.version(), ProtocolVersion::Mls10Draft11);
+        match message_in.extract() {
+            MlsMessageInBody::PrivateMessage(message) => ProtocolMessage::from(message),
+            MlsMessageInBody::PublicMessage(message) => ProtocolMessage::from(message),
+            _ => panic!("expected a protocol message"),
+        }
+    };
+    let application_message = draft_envelope(
+        alice_group
+            .create_message(provider, &alice_signer, b"draft")
+            .expect("error creating message"),
+    );
+    let proposal = draft_envelope(
+        alice_group
+            .propose_self_update(provider, &alice_signer, None)
+            .expect("error proposing update")
+            .0,
+    );
+
+    // === Bob rejects the messages, even with the lenient profile ===
+    for message in [application_message.clone(), proposal.clone()] {
+        assert_eq!(
+            bob_group
+                .process_message(provider, message)
+                .expect_err("accepted a message in a draft envelope"),
+            ProcessMessageError::VersionMismatch
+        );
+    }
+
+    // === Bob accepts the messages if the check is relaxed explicitly ===
+    bob_group.set_configuration(&config_with_policy(
+        ValidationPolicy::strict().with_envelope_version(ValidationSeverity::Ignore),
+    ));
+    let processed = bob_group
+        .process_message(provider, application_message)
+        .expect("rejected a message with a relaxed envelope version check");
+    assert!(matches!(
+        processed.into_content(),
+        ProcessedMessageContent::ApplicationMessage(_)
+    ));
+    let processed = bob_group
+        .process_message(provider, proposal)
+        .expect("rejected a message with a relaxed envelope version check");
+    assert!(matches!(
+        processed.into_content(),
+        ProcessedMessageContent::ProposalMessage(_)
+    ));
+}
@@ -0,0 +1,89 @@
+use openmls_traits::{types::Ciphersuite, OpenMlsProvider};
+use rstest::*;
+use rstest_reuse::{self, *};
+
+use super::*;
+use crate::{
+    group::{config::CryptoConfig, test_core_group::setup_client},
+    test_utils::*,
+};
+
+#[apply(ciphersuites_and_providers)]
+fn verification_code(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential, bob_kpb, _bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+
+    let mls_group_config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .use_ratchet_tree_extension(true)
+        .build();
+
+    let mut alice_group = MlsGroup::new_with_group_id(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        GroupId::from_slice(b"Test Group"),
+        alice_credential_with_key,
+    )
+    .expect("error creating group");
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(provider, &alice_signer, &[bob_kpb.key_package().clone()])
+        .expect("error adding Bob")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    let welcome = welcome.into_welcome().expect("expected a welcome");
+    let bob_group = MlsGroup::new_from_welcome(provider, &mls_group_config, welcome, None)
+        .expect("error joining group");
+
+    // Alice and Bob get the same code.
+    let alice_code = alice_group
+        .verification_code(provider.crypto())
+        .expect("error deriving code");
+    let bob_code = bob_group
+        .verification_code(provider.crypto())
+        .expect("error deriving code");
+    assert_eq!(alice_code, bob_code);
+    assert_eq!(alice_code.epoch(), GroupEpoch::from(1));
+
+    // The digits are six groups of five digits.
+    let digits = alice_code.digits();
+    let groups: Vec<&str> = digits.split(' ').collect();
+    assert_eq!(groups.len(), 6);
+    assert!(groups
+        .iter()
+        .all(|group| group.len() == 5 && group.bytes().all(|b| b.is_ascii_digit())));
+    assert!(bob_code.matches_digits(&digits));
+    assert!(bob_code.matches_digits(&digits.replace(' ', "")));
+    assert!(!bob_code.matches_digits(&digits[..digits.len() - 1]));
+    let mut wrong_digits = digits.clone().into_bytes();
+    wrong_digits[0] = if wrong_digits[0] == b'0' { b'1' } else { b'0' };
+    assert!(!bob_code.matches_digits(&String::from_utf8(wrong_digits).unwrap()));
+
+    // The code can be transferred as bytes.
+    let bytes = alice_code.to_bytes().expect("error encoding code");
+    assert_eq!(
+        VerificationCode::from_bytes(&bytes).expect("error decoding code"),
+        alice_code
+    );
+    assert!(bob_code.matches_bytes(&bytes));
+    assert!(!bob_code.matches_bytes(&bytes[..bytes.len() - 1]));
+
+    // The code changes with the epoch.
+    alice_group
+        .self_update(provider, &alice_signer)
+        .expect("error updating");
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    let new_code = alice_group
+        .verification_code(provider.crypto())
+        .expect("error deriving code");
+    assert_eq!(new_code.epoch(), GroupEpoch::from(2));
+    assert_ne!(new_code.as_slice(), bob_code.as_slice());
+    assert_ne!(new_code, bob_code);
+    assert!(!bob_code.matches_bytes(&new_code.to_bytes().expect("error encoding code")));
+}
@@ -0,0 +1,77 @@
+use openmls_traits::{types::Ciphersuite, OpenMlsProvider};
+use rstest::*;
+use rstest_reuse::{self, *};
+
+use super::*;
+use crate::{
+    extensions::ExtensionType,
+    group::{config::CryptoConfig, test_core_group::setup_client},
+    test_utils::*,
+};
+
+#[apply(ciphersuites_and_providers)]
+fn public_group_view(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential, bob_kpb, _bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+    let (_charlie_credential, charlie_kpb, _charlie_signer, _charlie_pk) =
+        setup_client("Charlie", ciphersuite, provider);
+
+    let mls_group_config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .build();
+    let (mut alice_group, _commit_output) = MlsGroup::builder()
+        .with_config(&mls_group_config)
+        .with_initial_members(vec![bob_kpb.key_package().clone()])
+        .build(provider, &alice_signer, alice_credential_with_key)
+        .expect("error creating group");
+
+    let view = alice_group.view();
+    assert_eq!(view.group_id(), alice_group.group_id());
+    assert_eq!(view.epoch(), alice_group.epoch());
+    assert_eq!(view.ciphersuite(), ciphersuite);
+    assert_eq!(view.own_leaf_index(), alice_group.own_leaf_index());
+    assert_eq!(view.members(), alice_group.members().collect::<Vec<_>>());
+    assert_eq!(
+        view.member(LeafNodeIndex::new(1))
+            .map(|member| &member.credential),
+        alice_group.member(LeafNodeIndex::new(1))
+    );
+    assert_eq!(view.member(LeafNodeIndex::new(2)), None);
+    assert!(view
+        .extensions()
+        .contains(ExtensionType::RequiredCapabilities));
+    assert_eq!(
+        view.tree_hash(),
+        alice_group.export_group_context().tree_hash()
+    );
+
+    // Views can be handed to other threads.
+    let members = {
+        let view = view.clone();
+        std::thread::spawn(move || view.members().len())
+            .join()
+            .unwrap()
+    };
+    assert_eq!(members, 2);
+
+    // === Views don't change with the group ===
+    alice_group
+        .add_members(
+            provider,
+            &alice_signer,
+            &[charlie_kpb.key_package().clone()],
+        )
+        .expect("error adding Charlie");
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    assert_eq!(view.members().len(), 2);
+    assert_ne!(view.epoch(), alice_group.epoch());
+
+    let new_view = alice_group.view();
+    assert_ne!(new_view, view);
+    assert_eq!(new_view.members().len(), 3);
+    assert_eq!(new_view.epoch(), alice_group.epoch());
+}
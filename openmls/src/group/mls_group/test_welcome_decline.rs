@@ -0,0 +1,107 @@
+use openmls_traits::{types::Ciphersuite, OpenMlsProvider};
+use rstest::*;
+use rstest_reuse::{self, *};
+
+use super::*;
+use crate::{
+    ciphersuite::signable::SignatureError,
+    group::{config::CryptoConfig, errors::WelcomeDeclineError, test_core_group::setup_client},
+    test_utils::*,
+};
+
+#[apply(ciphersuites_and_providers)]
+fn welcome_decline(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential, bob_kpb, bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+    let (charlie_credential_with_key, _charlie_kpb, charlie_signer, _charlie_pk) =
+        setup_client("Charlie", ciphersuite, provider);
+
+    let mls_group_config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .use_ratchet_tree_extension(true)
+        .build();
+
+    // === Alice creates a group and invites Bob ===
+    let mut alice_group = MlsGroup::new(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        alice_credential_with_key,
+    )
+    .expect("error creating group");
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(provider, &alice_signer, &[bob_kpb.key_package().clone()])
+        .expect("error adding Bob")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    let welcome = welcome.into_welcome().expect("expected a welcome");
+
+    // === Bob declines the invitation ===
+    let decline =
+        StagedWelcome::new_from_welcome(provider, &mls_group_config, welcome.clone(), None)
+            .expect("error staging welcome")
+            .decline(&bob_signer)
+            .expect("error declining welcome");
+    assert_eq!(decline.group_id(), alice_group.group_id());
+    assert_eq!(decline.epoch(), alice_group.epoch());
+    assert_eq!(decline.leaf_index(), LeafNodeIndex::new(1));
+
+    // A decline that isn't signed by Bob is rejected.
+    let forged_decline =
+        StagedWelcome::new_from_welcome(provider, &mls_group_config, welcome, None)
+            .expect("error staging welcome")
+            .decline(&charlie_signer)
+            .expect("error declining welcome");
+    assert_eq!(
+        alice_group
+            .process_welcome_decline(provider.crypto(), forged_decline)
+            .expect_err("expected an error"),
+        WelcomeDeclineError::SignatureError(SignatureError::VerificationError)
+    );
+
+    // A decline for another group is rejected.
+    let mut charlie_group = MlsGroup::new(
+        provider,
+        &charlie_signer,
+        &mls_group_config,
+        charlie_credential_with_key,
+    )
+    .expect("error creating group");
+    assert_eq!(
+        charlie_group
+            .process_welcome_decline(provider.crypto(), decline.clone())
+            .expect_err("expected an error"),
+        WelcomeDeclineError::WrongGroup
+    );
+
+    // === Alice processes Bob's decline ===
+    assert!(alice_group.declined_members().is_empty());
+    for _ in 0..2 {
+        assert_eq!(
+            alice_group
+                .process_welcome_decline(provider.crypto(), decline.clone())
+                .expect("error processing decline"),
+            LeafNodeIndex::new(1)
+        );
+        assert_eq!(alice_group.declined_members(), vec![LeafNodeIndex::new(1)]);
+    }
+
+    // === Alice removes Bob ===
+    alice_group
+        .remove_members(provider, &alice_signer, &[LeafNodeIndex::new(1)])
+        .expect("error removing Bob");
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    assert!(alice_group.declined_members().is_empty());
+    assert_eq!(
+        alice_group
+            .process_welcome_decline(provider.crypto(), decline)
+            .expect_err("expected an error"),
+        WelcomeDeclineError::UnknownMember
+    );
+}
@@ -1,64 +1,51 @@
 use core_group::create_commit_params::CreateCommitParams;
 use openmls_traits::signatures::Signer;
 
-use crate::{messages::group_info::GroupInfo, treesync::LeafNode, versions::ProtocolVersion};
+use crate::{treesync::LeafNode, versions::ProtocolVersion};
 
 use super::*;
 
 impl MlsGroup {
     /// Updates the own leaf node.
     ///
-    /// If successful, it returns a tuple of [`MlsMessageOut`] (containing the
-    /// commit), an optional [`MlsMessageOut`] (containing the [`Welcome`]) and the [GroupInfo].
+    /// If successful, it returns a [`CommitOutput`] with the commit, an
+    /// optional [`Welcome`] and the `GroupInfo`.
     /// The [Welcome] is [Some] when the queue of pending proposals contained
     /// add proposals
-    /// The [GroupInfo] is [Some] if the group has the `use_ratchet_tree_extension` flag set.
+    /// The `GroupInfo` is [Some] if the group has the `use_ratchet_tree_extension` flag set.
     ///
     /// Returns an error if there is a pending commit.
     ///
     /// TODO #1208 : The caller should be able to optionally provide a
     /// [`LeafNode`] here, so that things like extensions can be changed via
     /// commit.
-    // FIXME: #1217
-    #[allow(clippy::type_complexity)]
     pub fn self_update<KeyStore: OpenMlsKeyStore>(
         &mut self,
         provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
         signer: &impl Signer,
-    ) -> Result<
-        (MlsMessageOut, Option<MlsMessageOut>, Option<GroupInfo>),
-        SelfUpdateError<KeyStore::Error>,
-    > {
+    ) -> Result<CommitOutput, SelfUpdateError<KeyStore::Error>> {
+        let result = self._self_update(provider, signer);
+        self.record_operation(provider.crypto(), OperationType::SelfUpdate, &[], &result);
+        result
+    }
+
+    fn _self_update<KeyStore: OpenMlsKeyStore>(
+        &mut self,
+        provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
+        signer: &impl Signer,
+    ) -> Result<CommitOutput, SelfUpdateError<KeyStore::Error>> {
         self.is_operational()?;
 
+        let aad = self.commit_aad()?;
         let params = CreateCommitParams::builder()
-            .framing_parameters(self.framing_parameters())
+            .framing_parameters(self.commit_framing_parameters(&aad))
             .proposal_store(&self.proposal_store)
             .build();
         // Create Commit over all proposals.
         // TODO #751
-        let create_commit_result = self.group.create_commit(params, provider, signer)?;
-
-        // Convert PublicMessage messages to MLSMessage and encrypt them if required by
-        // the configuration
-        let mls_message = self.content_to_mls_message(create_commit_result.commit, provider)?;
-
-        // Set the current group state to [`MlsGroupState::PendingCommit`],
-        // storing the current [`StagedCommit`] from the commit results
-        self.group_state = MlsGroupState::PendingCommit(Box::new(PendingCommitState::Member(
-            create_commit_result.staged_commit,
-        )));
-
-        // Since the state of the group might be changed, arm the state flag
-        self.flag_state_change();
+        let prepared_commit = self.prepare_commit(params, provider, signer)?;
 
-        Ok((
-            mls_message,
-            create_commit_result
-                .welcome_option
-                .map(|w| MlsMessageOut::from_welcome(w, self.group.version())),
-            create_commit_result.group_info,
-        ))
+        Ok(self.stage_prepared_commit(provider, prepared_commit)?)
     }
 
     /// Creates a proposal to update the own leaf node. Optionally, a
@@ -0,0 +1,143 @@
+//! # Verification codes
+//!
+//! Members of a group can verify out-of-band, e.g., by reading out numbers in
+//! person or by scanning a QR code, that they share the same view of the
+//! group. A [`VerificationCode`] is derived from the
+//! [`EpochAuthenticator`](crate::schedule::EpochAuthenticator) of the current
+//! epoch and the identities of all members, i.e., their leaf indices,
+//! credentials and signature keys. Two members get the same code if, and only
+//! if, they are in the same epoch of the same group with the same members.
+//!
+//! Because the epoch authenticator changes with every commit, so does the
+//! code. Members have to compare codes of the same epoch, which is included in
+//! the code.
+
+use openmls_traits::crypto::OpenMlsCrypto;
+use tls_codec::{
+    Deserialize as TlsDeserializeTrait, Serialize as TlsSerializeTrait, TlsDeserialize,
+    TlsSerialize, TlsSize, VLBytes,
+};
+
+use super::*;
+use crate::ciphersuite::equal_ct;
+
+/// The length of a verification code in bytes.
+const VERIFICATION_CODE_LENGTH: usize = 30;
+
+/// The number of bytes that are encoded as one group of digits.
+const BYTES_PER_GROUP: usize = 5;
+
+/// The identity of a member as it is bound to a [`VerificationCode`].
+#[derive(Debug, TlsSerialize, TlsSize)]
+struct VerificationMember {
+    leaf_index: LeafNodeIndex,
+    credential: Credential,
+    signature_key: VLBytes,
+}
+
+/// A short code that members of a group can compare out-of-band to verify
+/// that they share the same view of the group. See the
+/// [module documentation](self) for details.
+///
+/// Codes are compared in constant time.
+#[derive(Debug, Clone, TlsSerialize, TlsDeserialize, TlsSize)]
+pub struct VerificationCode {
+    epoch: GroupEpoch,
+    code: VLBytes,
+}
+
+impl VerificationCode {
+    /// Returns the epoch the code was derived in.
+    pub fn epoch(&self) -> GroupEpoch {
+        self.epoch
+    }
+
+    /// Returns the code as a slice.
+    pub fn as_slice(&self) -> &[u8] {
+        self.code.as_slice()
+    }
+
+    /// Returns the code as 30 decimal digits in six groups of five, separated
+    /// by spaces, e.g., `"05870 38142 91723 00465 72318 44096"`.
+    pub fn digits(&self) -> String {
+        self.code
+            .as_slice()
+            .chunks(BYTES_PER_GROUP)
+            .map(|chunk| {
+                let value = chunk
+                    .iter()
+                    .fold(0u64, |value, byte| (value << 8) | *byte as u64);
+                format!("{:05}", value % 100_000)
+            })
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+
+    /// Returns the code as bytes that can be encoded in a QR code and parsed
+    /// with [`VerificationCode::from_bytes()`].
+    pub fn to_bytes(&self) -> Result<Vec<u8>, tls_codec::Error> {
+        self.tls_serialize_detached()
+    }
+
+    /// Parse a code that was encoded with [`VerificationCode::to_bytes()`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, tls_codec::Error> {
+        Self::tls_deserialize_exact(bytes)
+    }
+
+    /// Returns `true` if the digits of this code match `digits`, e.g., as
+    /// entered by a user. Whitespace in `digits` is ignored.
+    pub fn matches_digits(&self, digits: &str) -> bool {
+        let expected: Vec<u8> = self.digits().bytes().filter(u8::is_ascii_digit).collect();
+        let actual: Vec<u8> = digits
+            .bytes()
+            .filter(|byte| !byte.is_ascii_whitespace())
+            .collect();
+        expected.len() == actual.len() && equal_ct(&expected, &actual)
+    }
+
+    /// Returns `true` if `bytes` encode a code that matches this code.
+    pub fn matches_bytes(&self, bytes: &[u8]) -> bool {
+        Self::from_bytes(bytes)
+            .map(|other| &other == self)
+            .unwrap_or(false)
+    }
+}
+
+impl PartialEq for VerificationCode {
+    fn eq(&self, other: &Self) -> bool {
+        self.epoch == other.epoch
+            && self.code.as_slice().len() == other.code.as_slice().len()
+            && equal_ct(self.code.as_slice(), other.code.as_slice())
+    }
+}
+
+impl Eq for VerificationCode {}
+
+impl MlsGroup {
+    /// Returns the [`VerificationCode`] of the current epoch.
+    pub fn verification_code(
+        &self,
+        crypto: &impl OpenMlsCrypto,
+    ) -> Result<VerificationCode, LibraryError> {
+        let members: Vec<VerificationMember> = self
+            .members()
+            .map(|member| VerificationMember {
+                leaf_index: member.index,
+                credential: member.credential,
+                signature_key: member.signature_key.into(),
+            })
+            .collect();
+        let context = members
+            .as_slice()
+            .tls_serialize_detached()
+            .map_err(LibraryError::missing_bound_check)?;
+        let code = self
+            .epoch_authenticator()
+            .derive_verification_code(crypto, &context, VERIFICATION_CODE_LENGTH)
+            .map_err(LibraryError::unexpected_crypto_error)?;
+        Ok(VerificationCode {
+            epoch: self.epoch(),
+            code: code.into(),
+        })
+    }
+}
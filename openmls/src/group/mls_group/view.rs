@@ -0,0 +1,100 @@
+//! # Read-only group views
+//!
+//! User interfaces and caches often only need to show the members or the
+//! extensions of a group, but can't hold a borrow of the [`MlsGroup`] while
+//! it processes messages on another thread. [`MlsGroup::view()`] returns a
+//! [`PublicGroupView`] instead: an owned, immutable copy of the public state
+//! of the current epoch.
+//!
+//! A view is `Send` and `Sync`, and cloning it only increments a reference
+//! count. It doesn't change with the group. Since the members and the
+//! extensions only change with the epoch, a view is up to date as long as
+//! its [`PublicGroupView::epoch()`] is the epoch of the group.
+
+use std::sync::Arc;
+
+use super::*;
+use crate::extensions::Extensions;
+
+/// An immutable view of the public state of an [`MlsGroup`] in one epoch.
+/// See the [module documentation](self) for details.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PublicGroupView {
+    inner: Arc<PublicGroupViewInner>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct PublicGroupViewInner {
+    group_id: GroupId,
+    epoch: GroupEpoch,
+    ciphersuite: Ciphersuite,
+    own_leaf_index: LeafNodeIndex,
+    members: Vec<Member>,
+    extensions: Extensions,
+    tree_hash: Vec<u8>,
+}
+
+impl PublicGroupView {
+    /// Returns the group ID.
+    pub fn group_id(&self) -> &GroupId {
+        &self.inner.group_id
+    }
+
+    /// Returns the epoch of the view.
+    pub fn epoch(&self) -> GroupEpoch {
+        self.inner.epoch
+    }
+
+    /// Returns the ciphersuite of the group.
+    pub fn ciphersuite(&self) -> Ciphersuite {
+        self.inner.ciphersuite
+    }
+
+    /// Returns the leaf index of the member the view was created by.
+    pub fn own_leaf_index(&self) -> LeafNodeIndex {
+        self.inner.own_leaf_index
+    }
+
+    /// Returns the members of the group, ordered by their leaf index.
+    pub fn members(&self) -> &[Member] {
+        &self.inner.members
+    }
+
+    /// Returns the member at the given leaf index, or `None` if the leaf is
+    /// blank.
+    pub fn member(&self, leaf_index: LeafNodeIndex) -> Option<&Member> {
+        self.inner
+            .members
+            .binary_search_by_key(&leaf_index, |member| member.index)
+            .ok()
+            .map(|position| &self.inner.members[position])
+    }
+
+    /// Returns the group context extensions.
+    pub fn extensions(&self) -> &Extensions {
+        &self.inner.extensions
+    }
+
+    /// Returns the tree hash of the ratchet tree.
+    pub fn tree_hash(&self) -> &[u8] {
+        &self.inner.tree_hash
+    }
+}
+
+impl MlsGroup {
+    /// Returns a [`PublicGroupView`] of the current epoch.
+    pub fn view(&self) -> PublicGroupView {
+        let group_context = self.export_group_context();
+        PublicGroupView {
+            inner: Arc::new(PublicGroupViewInner {
+                group_id: self.group_id().clone(),
+                epoch: self.epoch(),
+                ciphersuite: self.ciphersuite(),
+                own_leaf_index: self.own_leaf_index(),
+                members: self.members().collect(),
+                extensions: group_context.extensions().clone(),
+                tree_hash: group_context.tree_hash().to_vec(),
+            }),
+        }
+    }
+}
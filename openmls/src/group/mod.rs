@@ -3,6 +3,8 @@
 //! This module contains the API to interact with groups.
 
 mod group_context;
+#[cfg(feature = "check-invariants")]
+mod invariants;
 
 use std::fmt::Display;
 
@@ -5,7 +5,8 @@ use crate::{
     credentials::CredentialWithKey,
     error::LibraryError,
     extensions::{
-        errors::ExtensionError, Extension, Extensions, ExternalSendersExtension,
+        errors::ExtensionError, CustomExtension, Extension, Extensions,
+        ExternalSenderScopesExtension, ExternalSendersExtension, ParentGroupExtension,
         RequiredCapabilitiesExtension,
     },
     group::{config::CryptoConfig, GroupContext, GroupId},
@@ -25,6 +26,9 @@ pub(crate) struct TempBuilderPG1 {
     lifetime: Option<Lifetime>,
     required_capabilities: Option<RequiredCapabilitiesExtension>,
     external_senders: Option<ExternalSendersExtension>,
+    external_sender_scopes: Option<ExternalSenderScopesExtension>,
+    parent_group: Option<ParentGroupExtension>,
+    group_context_extensions: Option<Extensions>,
     leaf_extensions: Option<Extensions>,
 }
 
@@ -52,6 +56,26 @@ impl TempBuilderPG1 {
         self
     }
 
+    pub(crate) fn with_external_sender_scopes(
+        mut self,
+        external_sender_scopes: ExternalSenderScopesExtension,
+    ) -> Self {
+        if !external_sender_scopes.is_empty() {
+            self.external_sender_scopes = Some(external_sender_scopes);
+        }
+        self
+    }
+
+    pub(crate) fn with_parent_group(mut self, parent_group: ParentGroupExtension) -> Self {
+        self.parent_group = Some(parent_group);
+        self
+    }
+
+    pub(crate) fn with_group_context_extensions(mut self, extensions: Extensions) -> Self {
+        self.group_context_extensions = Some(extensions);
+        self
+    }
+
     pub(crate) fn get_secrets(
         self,
         provider: &impl OpenMlsProvider,
@@ -87,12 +111,29 @@ impl TempBuilderPG1 {
             _ => LibraryError::custom("Unexpected ExtensionError").into(),
         })?;
         let required_capabilities = Extension::RequiredCapabilities(required_capabilities);
-        let extensions =
+        let mut extensions =
             if let Some(ext_senders) = self.external_senders.map(Extension::ExternalSenders) {
                 vec![required_capabilities, ext_senders]
             } else {
                 vec![required_capabilities]
             };
+        if let Some(external_sender_scopes) = self.external_sender_scopes {
+            extensions.push(
+                external_sender_scopes
+                    .to_extension()
+                    .map_err(|_| LibraryError::custom("Error encoding external sender scopes"))?,
+            );
+        }
+        if let Some(parent_group) = self.parent_group {
+            extensions.push(
+                parent_group
+                    .to_extension()
+                    .map_err(|_| LibraryError::custom("Error encoding parent group"))?,
+            );
+        }
+        if let Some(group_context_extensions) = self.group_context_extensions {
+            extensions.extend(group_context_extensions.iter().cloned());
+        }
         let group_context = GroupContext::create_initial_group_context(
             self.crypto_config.ciphersuite,
             self.group_id,
@@ -171,6 +212,9 @@ impl PublicGroup {
             lifetime: None,
             required_capabilities: None,
             external_senders: None,
+            external_sender_scopes: None,
+            parent_group: None,
+            group_context_extensions: None,
             leaf_extensions: None,
         }
     }
@@ -2,6 +2,9 @@
 //!
 //! This module contains the [`PublicGroupDiff`] struct, as well as the
 //! [`StagedPublicGroupDiff`] and associated functions and types.
+
+#![deny(clippy::unwrap_used, clippy::expect_used)]
+
 use std::collections::HashSet;
 
 use openmls_traits::crypto::OpenMlsCrypto;
@@ -246,4 +249,9 @@ impl StagedPublicGroupDiff {
     pub(crate) fn group_context(&self) -> &GroupContext {
         &self.group_context
     }
+
+    /// Get the staged [`StagedTreeSyncDiff`].
+    pub(crate) fn tree_diff(&self) -> &StagedTreeSyncDiff {
+        &self.staged_diff
+    }
 }
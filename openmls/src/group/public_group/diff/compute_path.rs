@@ -1,6 +1,9 @@
 use std::collections::HashSet;
 
-use openmls_traits::{key_store::OpenMlsKeyStore, signatures::Signer, OpenMlsProvider};
+use openmls_traits::{
+    crypto::OpenMlsCrypto, key_store::OpenMlsKeyStore, signatures::Signer, types::Ciphersuite,
+    OpenMlsProvider,
+};
 use tls_codec::Serialize;
 
 use crate::{
@@ -15,10 +18,11 @@ use crate::{
     schedule::CommitSecret,
     treesync::{
         node::{
-            encryption_keys::EncryptionKeyPair, leaf_node::LeafNode,
+            encryption_keys::{EncryptionKey, EncryptionKeyPair},
+            leaf_node::LeafNode,
             parent_node::PlainUpdatePathNode,
         },
-        treekem::UpdatePath,
+        treekem::{encrypt_path_nodes, UpdatePath, UpdatePathNode},
     },
 };
 
@@ -34,8 +38,71 @@ pub(crate) struct PathComputationResult {
     pub(crate) new_keypairs: Vec<EncryptionKeyPair>,
 }
 
+/// A path that has been derived, but whose path secrets have not been
+/// encrypted yet. The path nodes are encrypted in steps with
+/// [`PathPreparation::encrypt_nodes()`].
+pub(crate) struct PathPreparation {
+    ciphersuite: Ciphersuite,
+    commit_secret: CommitSecret,
+    plain_path: Vec<PlainUpdatePathNode>,
+    new_keypairs: Vec<EncryptionKeyPair>,
+    leaf_node: LeafNode,
+    // The public keys in the copath resolution of each path node.
+    copath_keys: Vec<Vec<EncryptionKey>>,
+    serialized_group_context: Vec<u8>,
+    encrypted_nodes: Vec<UpdatePathNode>,
+}
+
+impl PathPreparation {
+    /// The number of path nodes that have been encrypted.
+    pub(crate) fn encrypted_nodes(&self) -> usize {
+        self.encrypted_nodes.len()
+    }
+
+    /// The number of path nodes.
+    pub(crate) fn nodes(&self) -> usize {
+        self.plain_path.len()
+    }
+
+    /// Encrypt up to `max_nodes` of the path nodes that have not been
+    /// encrypted yet.
+    pub(crate) fn encrypt_nodes(
+        &mut self,
+        crypto: &impl OpenMlsCrypto,
+        max_nodes: usize,
+    ) -> Result<(), LibraryError> {
+        let start = self.encrypted_nodes.len();
+        let end = start.saturating_add(max_nodes).min(self.plain_path.len());
+        let mut encrypted_nodes = encrypt_path_nodes(
+            crypto,
+            self.ciphersuite,
+            &self.plain_path[start..end],
+            &self.copath_keys[start..end],
+            &self.serialized_group_context,
+        )?;
+        self.encrypted_nodes.append(&mut encrypted_nodes);
+        Ok(())
+    }
+
+    /// Returns the [`PathComputationResult`] once all path nodes have been
+    /// encrypted and an error otherwise.
+    pub(crate) fn finish(self) -> Result<PathComputationResult, LibraryError> {
+        if self.encrypted_nodes.len() != self.plain_path.len() {
+            return Err(LibraryError::custom("The path has not been encrypted yet."));
+        }
+        Ok(PathComputationResult {
+            commit_secret: Some(self.commit_secret),
+            encrypted_path: Some(UpdatePath::new(self.leaf_node, self.encrypted_nodes)),
+            plain_path: Some(self.plain_path),
+            new_keypairs: self.new_keypairs,
+        })
+    }
+}
+
 impl<'a> PublicGroupDiff<'a> {
-    pub(crate) fn compute_path<KeyStore: OpenMlsKeyStore>(
+    /// Derive a new path for the leaf at `leaf_index` and apply it to the
+    /// diff, without encrypting the path secrets. See [`PathPreparation`].
+    pub(crate) fn prepare_path<KeyStore: OpenMlsKeyStore>(
         &mut self,
         provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
         leaf_index: LeafNodeIndex,
@@ -43,7 +110,7 @@ impl<'a> PublicGroupDiff<'a> {
         commit_type: CommitType,
         signer: &impl Signer,
         credential_with_key: Option<CredentialWithKey>,
-    ) -> Result<PathComputationResult, CreateCommitError<KeyStore::Error>> {
+    ) -> Result<PathPreparation, CreateCommitError<KeyStore::Error>> {
         let version = self.group_context().protocol_version();
         let ciphersuite = self.group_context().ciphersuite();
         let group_id = self.group_context().group_id().clone();
@@ -108,26 +175,25 @@ impl<'a> PublicGroupDiff<'a> {
             .tls_serialize_detached()
             .map_err(LibraryError::missing_bound_check)?;
 
-        // Encrypt the path to the correct recipient nodes.
-        let encrypted_path = self.diff.encrypt_path(
-            provider.crypto(),
-            ciphersuite,
-            &plain_path,
-            &serialized_group_context,
-            &exclusion_list,
-            leaf_index,
-        )?;
+        // The path is encrypted to the copath resolutions.
+        let copath_keys = self
+            .diff
+            .copath_encryption_keys(leaf_index, &exclusion_list);
+        debug_assert_eq!(copath_keys.len(), plain_path.len());
         let leaf_node = self
             .diff
             .leaf(leaf_index)
             .ok_or_else(|| LibraryError::custom("Couldn't find own leaf"))?
             .clone();
-        let encrypted_path = UpdatePath::new(leaf_node, encrypted_path);
-        Ok(PathComputationResult {
-            commit_secret: Some(commit_secret),
-            encrypted_path: Some(encrypted_path),
-            plain_path: Some(plain_path),
+        Ok(PathPreparation {
+            ciphersuite,
+            commit_secret,
+            plain_path,
             new_keypairs,
+            leaf_node,
+            copath_keys,
+            serialized_group_context,
+            encrypted_nodes: Vec::new(),
         })
     }
 }
@@ -44,3 +44,31 @@ pub enum PublicGroupBuildError {
     #[error("Invalid extensions set in configuration")]
     InvalidExtensions(#[from] InvalidExtensionError),
 }
+
+/// Membership policy error.
+#[derive(Error, Debug, PartialEq, Eq, Clone)]
+pub enum MembershipPolicyError {
+    /// The sender is not allowed to add the member.
+    #[error("The sender is not allowed to add the member.")]
+    UnauthorizedAdd,
+    /// The sender is not allowed to remove the member.
+    #[error("The sender is not allowed to remove the member.")]
+    UnauthorizedRemove,
+}
+
+/// Group info validation error.
+#[derive(Error, Debug, PartialEq, Eq, Clone)]
+pub enum GroupInfoValidationError {
+    /// The signer of the group info is not a member of the group.
+    #[error("The signer of the group info is not a member of the group.")]
+    UnknownSigner,
+    /// The signature on the group info is not valid.
+    #[error("The signature on the group info is not valid.")]
+    InvalidSignature,
+    /// The group context of the group info doesn't match the group.
+    #[error("The group context of the group info doesn't match the group.")]
+    GroupContextMismatch,
+    /// The confirmation tag of the group info doesn't match the group.
+    #[error("The confirmation tag of the group info doesn't match the group.")]
+    ConfirmationTagMismatch,
+}
@@ -8,6 +8,15 @@
 //! as associated helper structs the goal of which is to enable this
 //! functionality.
 //!
+//! A delivery service creates a [`PublicGroup`] from the published group info
+//! and ratchet tree with [`PublicGroup::from_external()`] and keeps it up to
+//! date by processing every proposal and commit with
+//! [`PublicGroup::process_message()`] and merging the commits with
+//! [`PublicGroup::merge_commit()`]. This tracks the tree, the group context and
+//! the membership across epochs without any secrets. On top of that, it can
+//! enforce a [`MembershipPolicy`] and validate the group infos published by
+//! the members with [`PublicGroup::validate_group_info()`].
+//!
 //! To avoid duplication of code and functionality, [`CoreGroup`] internally
 //! relies on a [`PublicGroup`] as well.
 
@@ -21,9 +30,12 @@ use serde::{Deserialize, Serialize};
 
 use self::{
     diff::{PublicGroupDiff, StagedPublicGroupDiff},
-    errors::CreationFromExternalError,
+    errors::{CreationFromExternalError, GroupInfoValidationError},
+};
+use super::{
+    config::{CommitLimits, ValidationPolicy},
+    GroupContext, GroupId, Member, ProposalStore, QueuedProposal, StagedCommit,
 };
-use super::{GroupContext, GroupId, Member, ProposalStore, QueuedProposal, StagedCommit};
 #[cfg(test)]
 use crate::treesync::{node::parent_node::PlainUpdatePathNode, treekem::UpdatePathNode};
 use crate::{
@@ -37,6 +49,7 @@ use crate::{
         proposals::{Proposal, ProposalOrRefType, ProposalType},
         ConfirmationTag, PathSecret,
     },
+    metrics::{self, Counter},
     schedule::CommitSecret,
     treesync::{
         errors::{DerivePathError, TreeSyncFromNodesError},
@@ -54,12 +67,15 @@ use crate::{framing::PublicMessage, group::CoreGroup};
 pub(crate) mod builder;
 pub(crate) mod diff;
 pub mod errors;
+mod policy;
 pub mod process;
 pub(crate) mod staged_commit;
 #[cfg(test)]
 mod tests;
 mod validation;
 
+pub use policy::MembershipPolicy;
+
 /// This struct holds all public values of an MLS group.
 #[derive(Debug, Serialize, Deserialize)]
 #[cfg_attr(test, derive(PartialEq, Clone))]
@@ -70,6 +86,12 @@ pub struct PublicGroup {
     interim_transcript_hash: Vec<u8>,
     // Most recent confirmation tag. Kept here for verification purposes.
     confirmation_tag: ConfirmationTag,
+    // The checks that only log a warning when they fail.
+    #[serde(default)]
+    validation_policy: ValidationPolicy,
+    // The bounds for incoming commits.
+    #[serde(default)]
+    commit_limits: CommitLimits,
 }
 
 impl PublicGroup {
@@ -97,6 +119,8 @@ impl PublicGroup {
             group_context,
             interim_transcript_hash,
             confirmation_tag: initial_confirmation_tag,
+            validation_policy: ValidationPolicy::default(),
+            commit_limits: CommitLimits::default(),
         })
     }
 
@@ -144,7 +168,7 @@ impl PublicGroup {
             return Err(CreationFromExternalError::TreeHashMismatch);
         }
 
-        if group_info.group_context().protocol_version() != ProtocolVersion::Mls10 {
+        if !group_info.group_context().protocol_version().is_supported() {
             return Err(CreationFromExternalError::UnsupportedMlsVersion);
         }
 
@@ -160,16 +184,53 @@ impl PublicGroup {
             )?
         };
 
-        Ok((
-            Self {
-                treesync,
-                group_context,
-                interim_transcript_hash,
-                confirmation_tag: group_info.confirmation_tag().clone(),
-                proposal_store,
-            },
-            group_info,
-        ))
+        let public_group = Self {
+            treesync,
+            group_context,
+            interim_transcript_hash,
+            confirmation_tag: group_info.confirmation_tag().clone(),
+            proposal_store,
+            validation_policy: ValidationPolicy::default(),
+            commit_limits: CommitLimits::default(),
+        };
+
+        #[cfg(feature = "check-invariants")]
+        public_group.check_invariants(crypto, "from_external");
+
+        Ok((public_group, group_info))
+    }
+
+    /// Validate a group info that a member published for the current epoch of
+    /// the group, e.g., alongside a commit.
+    ///
+    /// The group info must be signed by a current member and match the group
+    /// context and the confirmation tag of the group. A delivery service can
+    /// serve a validated group info together with the ratchet tree from
+    /// [`PublicGroup::export_ratchet_tree()`] to new members that join
+    /// through an external commit.
+    pub fn validate_group_info(
+        &self,
+        crypto: &impl OpenMlsCrypto,
+        verifiable_group_info: VerifiableGroupInfo,
+    ) -> Result<GroupInfo, GroupInfoValidationError> {
+        let signer_signature_key = self
+            .leaf(verifiable_group_info.signer())
+            .ok_or(GroupInfoValidationError::UnknownSigner)?
+            .signature_key()
+            .clone()
+            .into_signature_public_key_enriched(self.ciphersuite().signature_algorithm());
+        let group_info: GroupInfo = verifiable_group_info
+            .verify(crypto, &signer_signature_key)
+            .map_err(|_| GroupInfoValidationError::InvalidSignature)?;
+
+        if group_info.group_context() != self.group_context() {
+            return Err(GroupInfoValidationError::GroupContextMismatch);
+        }
+        if group_info.confirmation_tag() != self.confirmation_tag() {
+            return Err(GroupInfoValidationError::ConfirmationTagMismatch);
+        }
+
+        Ok(group_info)
     }
 
     /// Returns the index of the sender of a staged, external commit.
@@ -238,6 +299,7 @@ impl PublicGroup {
         self.group_context = diff.group_context;
         self.interim_transcript_hash = diff.interim_transcript_hash;
         self.confirmation_tag = diff.confirmation_tag;
+        metrics::increment_counter(Counter::EpochAdvanced);
     }
 
     /// Derives [`EncryptionKeyPair`]s for the nodes in the shared direct path
@@ -314,10 +376,15 @@ impl PublicGroup {
     }
 
     /// Get treesync.
-    fn treesync(&self) -> &TreeSync {
+    pub(crate) fn treesync(&self) -> &TreeSync {
         &self.treesync
     }
 
+    /// Get the [`ProposalStore`].
+    pub(crate) fn proposal_store(&self) -> &ProposalStore {
+        &self.proposal_store
+    }
+
     /// Get confirmation tag.
     pub fn confirmation_tag(&self) -> &ConfirmationTag {
         &self.confirmation_tag
@@ -334,10 +401,33 @@ impl PublicGroup {
         self.treesync().tree_size()
     }
 
-    fn interim_transcript_hash(&self) -> &[u8] {
+    /// Get the interim transcript hash.
+    pub fn interim_transcript_hash(&self) -> &[u8] {
         &self.interim_transcript_hash
     }
 
+    /// Get the [`ValidationPolicy`] that incoming messages are validated with.
+    pub fn validation_policy(&self) -> &ValidationPolicy {
+        &self.validation_policy
+    }
+
+    /// Set the [`ValidationPolicy`] that incoming messages are validated
+    /// with. The default policy is [`ValidationPolicy::strict()`].
+    pub fn set_validation_policy(&mut self, validation_policy: ValidationPolicy) {
+        self.validation_policy = validation_policy;
+    }
+
+    /// Get the [`CommitLimits`] that incoming commits are checked against.
+    pub fn commit_limits(&self) -> &CommitLimits {
+        &self.commit_limits
+    }
+
+    /// Set the [`CommitLimits`] that incoming commits are checked against.
+    /// By default, commits are not bounded.
+    pub fn set_commit_limits(&mut self, commit_limits: CommitLimits) {
+        self.commit_limits = commit_limits;
+    }
+
     /// Return a vector containing all [`EncryptionKey`]s for which the owner of
     /// the given `leaf_index` should have private key material.
     pub(crate) fn owned_encryption_keys(&self, leaf_index: LeafNodeIndex) -> Vec<EncryptionKey> {
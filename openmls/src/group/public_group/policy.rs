@@ -0,0 +1,88 @@
+//! # Membership policies
+//!
+//! A delivery service that tracks a group with a [`PublicGroup`] sees every
+//! proposal and commit before it is fanned out to the members. It can enforce
+//! its own rules for who may add or remove whom by checking the processed
+//! messages against a [`MembershipPolicy`] with
+//! [`PublicGroup::check_membership_policy()`] before forwarding them and
+//! merging the commits.
+
+use super::{errors::MembershipPolicyError, PublicGroup};
+use crate::{
+    framing::{ProcessedMessage, ProcessedMessageContent, Sender},
+    group::{Member, QueuedProposal},
+    key_packages::KeyPackage,
+    messages::proposals::Proposal,
+};
+
+/// A policy for changes of the membership of a group. By default, every
+/// change is allowed.
+pub trait MembershipPolicy {
+    /// Returns `true` if `sender` may add the owner of `key_package` to
+    /// `group`.
+    fn may_add(&self, group: &PublicGroup, sender: &Sender, key_package: &KeyPackage) -> bool {
+        let _ = (group, sender, key_package);
+        true
+    }
+
+    /// Returns `true` if `sender` may remove `removed` from `group`.
+    fn may_remove(&self, group: &PublicGroup, sender: &Sender, removed: &Member) -> bool {
+        let _ = (group, sender, removed);
+        true
+    }
+}
+
+impl PublicGroup {
+    /// Check that the proposal or the proposals covered by the commit in the
+    /// processed `message` are allowed by `policy`.
+    ///
+    /// The proposals of a commit are checked with the sender that proposed
+    /// them, i.e., the committer for proposals that are included by value.
+    /// Removals of members that are not in the tree are left to the
+    /// validation of the proposal.
+    pub fn check_membership_policy(
+        &self,
+        message: &ProcessedMessage,
+        policy: &impl MembershipPolicy,
+    ) -> Result<(), MembershipPolicyError> {
+        match message.content() {
+            ProcessedMessageContent::ProposalMessage(queued_proposal)
+            | ProcessedMessageContent::ExternalJoinProposalMessage(queued_proposal) => {
+                self.check_proposal(queued_proposal, policy)
+            }
+            ProcessedMessageContent::StagedCommitMessage(staged_commit) => staged_commit
+                .queued_proposals()
+                .try_for_each(|queued_proposal| self.check_proposal(queued_proposal, policy)),
+            ProcessedMessageContent::ApplicationMessage(_) => Ok(()),
+        }
+    }
+
+    fn check_proposal(
+        &self,
+        queued_proposal: &QueuedProposal,
+        policy: &impl MembershipPolicy,
+    ) -> Result<(), MembershipPolicyError> {
+        let sender = queued_proposal.sender();
+        match queued_proposal.proposal() {
+            Proposal::Add(add_proposal)
+                if !policy.may_add(self, sender, add_proposal.key_package()) =>
+            {
+                return Err(MembershipPolicyError::UnauthorizedAdd);
+            }
+            Proposal::Remove(remove_proposal) => {
+                let removed = match self
+                    .members()
+                    .find(|member| member.index == remove_proposal.removed())
+                {
+                    Some(removed) => removed,
+                    None => return Ok(()),
+                };
+                if !policy.may_remove(self, sender, &removed) {
+                    return Err(MembershipPolicyError::UnauthorizedRemove);
+                }
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+}
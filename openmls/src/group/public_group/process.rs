@@ -1,9 +1,11 @@
-use openmls_traits::crypto::OpenMlsCrypto;
+#![deny(clippy::unwrap_used, clippy::expect_used)]
+
+use openmls_traits::{clock::OpenMlsClock, crypto::OpenMlsCrypto};
 use tls_codec::Serialize;
 
 use crate::{
     ciphersuite::OpenMlsSignaturePublicKey,
-    credentials::CredentialWithKey,
+    credentials::{Credential, CredentialWithKey},
     error::LibraryError,
     framing::{
         mls_content::FramedContentBody, ApplicationMessage, DecryptedMessage, ProcessedMessage,
@@ -14,8 +16,10 @@ use crate::{
         errors::ValidationError,
         mls_group::errors::ProcessMessageError,
         past_secrets::MessageSecretsStore,
+        GroupEpoch, GroupId,
     },
-    messages::proposals::Proposal,
+    messages::proposals::{Proposal, ProposalOrRef, ProposalType},
+    metrics, security_events,
 };
 
 use super::PublicGroup;
@@ -68,6 +72,7 @@ impl PublicGroup {
                 .unwrap_or_default(),
             self.group_context().extensions().external_senders(),
         )?;
+        self.validate_external_sender(decrypted_message.sender())?;
         let signature_public_key = OpenMlsSignaturePublicKey::from_signature_key(
             signature_key,
             self.ciphersuite().signature_algorithm(),
@@ -97,7 +102,9 @@ impl PublicGroup {
 
     /// This function is used to parse messages from the DS. It checks for
     /// syntactic errors and does semantic validation as well. It returns a
-    /// [ProcessedMessage] enum. Checks the following semantic validation:
+    /// [ProcessedMessage] enum. Lifetimes of leaf nodes are validated against
+    /// the current time of the given `clock`. Checks the following semantic
+    /// validation:
     ///  - ValSem002
     ///  - ValSem003
     ///  - ValSem004
@@ -133,9 +140,36 @@ impl PublicGroup {
     pub fn process_message(
         &self,
         crypto: &impl OpenMlsCrypto,
+        clock: &impl OpenMlsClock,
         message: impl Into<ProtocolMessage>,
     ) -> Result<ProcessedMessage, ProcessMessageError> {
+        // Merging a commit has no access to a crypto provider, so the state it
+        // produced is checked here.
+        #[cfg(feature = "check-invariants")]
+        self.check_invariants(crypto, "process_message");
+
         let protocol_message = message.into();
+        let content_type = protocol_message.content_type();
+        self.process_protocol_message(crypto, clock, protocol_message)
+            .inspect(|processed_message| {
+                security_events::record_processed_message(self, processed_message)
+            })
+            .inspect_err(|e| {
+                metrics::record_process_message_error(content_type, e);
+                security_events::record_process_message_error(
+                    self.group_id(),
+                    self.group_context().epoch(),
+                    e,
+                );
+            })
+    }
+
+    fn process_protocol_message(
+        &self,
+        crypto: &impl OpenMlsCrypto,
+        clock: &impl OpenMlsClock,
+        protocol_message: ProtocolMessage,
+    ) -> Result<ProcessedMessage, ProcessMessageError> {
         // Checks the following semantic validation:
         //  - ValSem002
         //  - ValSem003
@@ -160,7 +194,187 @@ impl PublicGroup {
         let unverified_message = self
             .parse_message(decrypted_message, None)
             .map_err(ProcessMessageError::from)?;
-        self.process_unverified_message(crypto, unverified_message, &self.proposal_store)
+        self.process_unverified_message(crypto, clock, unverified_message, &self.proposal_store)
+    }
+}
+
+/// A [`PublicMessage`](crate::framing::PublicMessage) that was validated with
+/// [`PublicGroup::validate_message()`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidatedMessage {
+    group_id: GroupId,
+    epoch: GroupEpoch,
+    sender: Sender,
+    credential: Credential,
+    content: ValidatedMessageContent,
+}
+
+impl ValidatedMessage {
+    /// Returns the group ID of the message.
+    pub fn group_id(&self) -> &GroupId {
+        &self.group_id
+    }
+
+    /// Returns the epoch of the message.
+    pub fn epoch(&self) -> GroupEpoch {
+        self.epoch
+    }
+
+    /// Returns the authenticated sender of the message.
+    pub fn sender(&self) -> &Sender {
+        &self.sender
+    }
+
+    /// Returns the credential of the sender.
+    pub fn credential(&self) -> &Credential {
+        &self.credential
+    }
+
+    /// Returns the content of the message.
+    pub fn content(&self) -> &ValidatedMessageContent {
+        &self.content
+    }
+}
+
+/// The content of a [`ValidatedMessage`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidatedMessageContent {
+    /// A proposal of the given type.
+    Proposal(ProposalType),
+    /// A commit.
+    Commit(CommitSummary),
+}
+
+/// A summary of a commit in a [`ValidatedMessage`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitSummary {
+    proposal_types: Vec<ProposalType>,
+    proposal_references: usize,
+    has_path: bool,
+}
+
+impl CommitSummary {
+    /// Returns the types of the proposals the commit contains by value.
+    pub fn proposal_types(&self) -> &[ProposalType] {
+        &self.proposal_types
+    }
+
+    /// Returns the number of proposals the commit covers by reference. Their
+    /// types are only known to a group that tracks the proposals of the
+    /// epoch.
+    pub fn proposal_references(&self) -> usize {
+        self.proposal_references
+    }
+
+    /// Returns `true` if the commit contains an update path.
+    pub fn has_path(&self) -> bool {
+        self.has_path
+    }
+}
+
+impl PublicGroup {
+    /// Validate a [`PublicMessage`](crate::framing::PublicMessage) without
+    /// processing it.
+    ///
+    /// This function is meant for delivery services that only accept public
+    /// handshake messages. It only needs the public state of the group, e.g.,
+    /// a [`PublicGroup`] created from the published group info and ratchet
+    /// tree with [`PublicGroup::from_external()`], and doesn't change it. It
+    /// returns the authenticated sender and the type of the proposal or
+    /// commit. Lifetimes of leaf nodes are validated against the current time
+    /// of the given `clock`.
+    ///
+    /// The signature of the message is verified against the leaf of the
+    /// sender, or against the external sender for proposals by external
+    /// senders. The membership tag is authenticated with a key derived from
+    /// the epoch secrets of the group and can only be checked for presence.
+    /// Commits are not staged, so the proposals and the update path are not
+    /// validated. Use [`PublicGroup::process_message()`] for that.
+    ///
+    /// Checks the following semantic validation:
+    ///  - ValSem002
+    ///  - ValSem003
+    ///  - ValSem004
+    ///  - ValSem005
+    ///  - ValSem006
+    ///  - ValSem007
+    ///  - ValSem009
+    ///  - ValSem010
+    ///  - ValSem112
+    ///  - ValSem245
+    ///  - ValSem246 (as part of ValSem010)
+    pub fn validate_message(
+        &self,
+        crypto: &impl OpenMlsCrypto,
+        clock: &impl OpenMlsClock,
+        message: impl Into<ProtocolMessage>,
+    ) -> Result<ValidatedMessage, ProcessMessageError> {
+        let protocol_message = message.into();
+
+        // Checks the following semantic validation:
+        //  - ValSem002
+        //  - ValSem003
+        self.validate_framing(&protocol_message)?;
+
+        let public_message = match protocol_message {
+            ProtocolMessage::PrivateMessage(_) => {
+                return Err(ProcessMessageError::IncompatibleWireFormat)
+            }
+            ProtocolMessage::PublicMessage(public_message) => public_message,
+        };
+        let decrypted_message = DecryptedMessage::from_inbound_public_message(
+            public_message,
+            None,
+            self.group_context()
+                .tls_serialize_detached()
+                .map_err(LibraryError::missing_bound_check)?,
+            crypto,
+        )?;
+        let unverified_message = self.parse_message(decrypted_message, None)?;
+
+        // Checks the following semantic validation:
+        //  - ValSem010
+        //  - ValSem246 (as part of ValSem010)
+        let (content, credential) = unverified_message.verify(
+            self.ciphersuite(),
+            crypto,
+            self.version(),
+            clock,
+            self.validation_policy(),
+        )?;
+
+        let validated_content = match content.content() {
+            FramedContentBody::Proposal(proposal) => {
+                ValidatedMessageContent::Proposal(proposal.proposal_type())
+            }
+            FramedContentBody::Commit(commit) => {
+                let proposal_types = commit
+                    .proposals
+                    .iter()
+                    .filter_map(|proposal_or_ref| match proposal_or_ref {
+                        ProposalOrRef::Proposal(proposal) => Some(proposal.proposal_type()),
+                        ProposalOrRef::Reference(_) => None,
+                    })
+                    .collect::<Vec<_>>();
+                ValidatedMessageContent::Commit(CommitSummary {
+                    proposal_references: commit.proposals.len() - proposal_types.len(),
+                    proposal_types,
+                    has_path: commit.path().is_some(),
+                })
+            }
+            // Application messages are rejected as part of ValSem005.
+            FramedContentBody::Application(_) => {
+                return Err(LibraryError::custom("Unencrypted application message").into())
+            }
+        };
+
+        Ok(ValidatedMessage {
+            group_id: self.group_id().clone(),
+            epoch: self.group_context().epoch(),
+            sender: content.sender().clone(),
+            credential,
+            content: validated_content,
+        })
     }
 }
 
@@ -195,14 +409,20 @@ impl PublicGroup {
     pub(crate) fn process_unverified_message(
         &self,
         crypto: &impl OpenMlsCrypto,
+        clock: &impl OpenMlsClock,
         unverified_message: UnverifiedMessage,
         proposal_store: &ProposalStore,
     ) -> Result<ProcessedMessage, ProcessMessageError> {
         // Checks the following semantic validation:
         //  - ValSem010
         //  - ValSem246 (as part of ValSem010)
-        let (content, credential) =
-            unverified_message.verify(self.ciphersuite(), crypto, self.version())?;
+        let (content, credential) = unverified_message.verify(
+            self.ciphersuite(),
+            crypto,
+            self.version(),
+            clock,
+            self.validation_policy(),
+        )?;
 
         match content.sender() {
             Sender::Member(_) | Sender::NewMemberCommit | Sender::NewMemberProposal => {
@@ -213,6 +433,9 @@ impl PublicGroup {
                     FramedContentBody::Application(application_message) => {
                         ProcessedMessageContent::ApplicationMessage(ApplicationMessage::new(
                             application_message.as_slice().to_owned(),
+                            content.epoch(),
+                            content.wire_format(),
+                            content.signature().clone(),
                         ))
                     }
                     FramedContentBody::Proposal(_) => {
@@ -249,13 +472,15 @@ impl PublicGroup {
                     FramedContentBody::Application(_) => {
                         Err(ProcessMessageError::UnauthorizedExternalApplicationMessage)
                     }
-                    FramedContentBody::Proposal(Proposal::Remove(_)) => {
+                    FramedContentBody::Proposal(Proposal::Remove(remove_proposal)) => {
+                        self.validate_external_sender_scope(&sender, remove_proposal.removed())?;
                         let content = ProcessedMessageContent::ProposalMessage(Box::new(
                             QueuedProposal::from_authenticated_content_by_ref(
                                 self.ciphersuite(),
                                 crypto,
                                 content,
-                            )?,
+                            )?
+                            .with_external_sender(self.external_sender(&sender).cloned()),
                         ));
                         Ok(ProcessedMessage::new(
                             self.group_id().clone(),
@@ -270,7 +495,10 @@ impl PublicGroup {
                     FramedContentBody::Proposal(_) => {
                         Err(ProcessMessageError::UnsupportedProposalType)
                     }
-                    FramedContentBody::Commit(_) => unimplemented!(),
+                    // Rejected by ValSem112 when validating the content.
+                    FramedContentBody::Commit(_) => Err(ProcessMessageError::ValidationError(
+                        ValidationError::InvalidSenderType,
+                    )),
                 }
             }
         }
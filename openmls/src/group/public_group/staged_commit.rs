@@ -1,3 +1,5 @@
+#![deny(clippy::unwrap_used, clippy::expect_used)]
+
 use super::{super::errors::*, *};
 use crate::{
     framing::{mls_auth_content::AuthenticatedContent, mls_content::FramedContentBody, Sender},
@@ -36,6 +38,9 @@ impl PublicGroup {
             _ => return Err(StageCommitError::WrongPlaintextContentType),
         };
 
+        // Reject oversized commits before their proposals are processed.
+        self.validate_commit_limits(commit, proposal_store)?;
+
         let sender = mls_content.sender();
         // ValSem244: External Commit, There MUST NOT be any referenced proposals.
         if sender == &Sender::NewMemberCommit
@@ -87,10 +92,11 @@ impl PublicGroup {
         // ValSem107
         // ValSem108
         self.validate_remove_proposals(&proposal_queue)?;
+        self.validate_external_proposals(&proposal_queue)?;
         // ValSem401
         // ValSem402
         // ValSem403
-        self.validate_pre_shared_key_proposals(&proposal_queue)?;
+        self.validate_pre_shared_key_proposals(&proposal_queue, &[])?;
 
         match sender {
             Sender::Member(leaf_index) => {
@@ -195,7 +201,11 @@ impl PublicGroup {
 
         let staged_commit_state = StagedCommitState::PublicState(Box::new(staged_diff));
 
-        Ok(StagedCommit::new(proposal_queue, staged_commit_state))
+        Ok(StagedCommit::new(
+            proposal_queue,
+            staged_commit_state,
+            sender_index,
+        ))
     }
 
     fn stage_diff(
@@ -1,22 +1,31 @@
 use openmls_rust_crypto::OpenMlsRustCrypto;
-use openmls_traits::{types::Ciphersuite, OpenMlsProvider};
+use openmls_traits::{clock::OpenMlsClock, types::Ciphersuite, OpenMlsProvider};
 use rstest::*;
 use rstest_reuse::{self, *};
+use tls_codec::{Deserialize, Serialize};
 
 use crate::{
     binary_tree::LeafNodeIndex,
     framing::{
-        public_message_in::PublicMessageIn, MlsMessageOut, ProcessedMessage,
+        public_message_in::PublicMessageIn, MlsMessageIn, MlsMessageOut, ProcessedMessage,
         ProcessedMessageContent, ProtocolMessage, Sender,
     },
     group::{
-        config::CryptoConfig, test_core_group::setup_client, GroupId, MlsGroup,
-        MlsGroupConfigBuilder, ProposalStore, StagedCommit, PURE_PLAINTEXT_WIRE_FORMAT_POLICY,
+        config::CryptoConfig,
+        errors::{ProcessMessageError, ValidationError},
+        test_core_group::setup_client,
+        GroupId, Member, MlsGroup, MlsGroupConfigBuilder, ProposalStore, StagedCommit,
+        PURE_PLAINTEXT_WIRE_FORMAT_POLICY,
     },
-    messages::proposals::Proposal,
+    key_packages::errors::KeyPackageVerifyError,
+    messages::proposals::{Proposal, ProposalType},
 };
 
-use super::PublicGroup;
+use super::{
+    errors::{GroupInfoValidationError, MembershipPolicyError},
+    process::ValidatedMessageContent,
+    MembershipPolicy, PublicGroup,
+};
 
 #[apply(ciphersuites_and_providers)]
 fn public_group(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
@@ -48,7 +57,7 @@ fn public_group(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
 
     // === Create a public group that tracks the changes throughout this test ===
     let verifiable_group_info = alice_group
-        .export_group_info(provider.crypto(), &alice_signer, false)
+        .export_group_info(provider, &alice_signer, false)
         .unwrap()
         .into_verifiable_group_info()
         .unwrap();
@@ -64,7 +73,8 @@ fn public_group(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     // === Alice adds Bob ===
     let (message, welcome, _group_info) = alice_group
         .add_members(provider, &alice_signer, &[bob_kpb.key_package().clone()])
-        .expect("Could not add member to group.");
+        .expect("Could not add member to group.")
+        .into_parts();
 
     alice_group
         .merge_pending_commit(provider)
@@ -75,7 +85,7 @@ fn public_group(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
         ProtocolMessage::PublicMessage(public_message) => public_message,
     };
     let processed_message = public_group
-        .process_message(provider.crypto(), public_message)
+        .process_message(provider.crypto(), provider.clock(), public_message)
         .unwrap();
 
     // Further inspection of the message can take place here ...
@@ -104,7 +114,8 @@ fn public_group(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     // === Bob adds Charlie ===
     let (queued_messages, welcome, _group_info) = bob_group
         .add_members(provider, &bob_signer, &[charlie_kpb.key_package().clone()])
-        .unwrap();
+        .unwrap()
+        .into_parts();
 
     // Alice processes
     let alice_processed_message = alice_group
@@ -128,7 +139,11 @@ fn public_group(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
 
     // The public group processes
     let ppm = public_group
-        .process_message(provider.crypto(), into_public_message(queued_messages))
+        .process_message(
+            provider.crypto(),
+            provider.clock(),
+            into_public_message(queued_messages),
+        )
         .unwrap();
     public_group.merge_commit(extract_staged_commit(ppm));
 
@@ -163,7 +178,11 @@ fn public_group(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
 
     // The public group processes
     let ppm = public_group
-        .process_message(provider.crypto(), into_public_message(queued_messages))
+        .process_message(
+            provider.crypto(),
+            provider.clock(),
+            into_public_message(queued_messages),
+        )
         .unwrap();
     // We have to add the proposal to the public group's proposal store.
     match ppm.into_content() {
@@ -204,7 +223,8 @@ fn public_group(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     // Charlie commits
     let (queued_messages, _welcome, _group_info) = charlie_group
         .commit_to_pending_proposals(provider, &charlie_signer)
-        .expect("Could not commit proposal");
+        .expect("Could not commit proposal")
+        .into_parts();
 
     // The public group processes
     let ppm = public_group
@@ -271,7 +291,294 @@ fn public_group(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     );
 }
 
+#[apply(ciphersuites_and_providers)]
+fn validate_message(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential, bob_kpb, _bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+
+    let mls_group_config = MlsGroupConfigBuilder::new()
+        .wire_format_policy(PURE_PLAINTEXT_WIRE_FORMAT_POLICY)
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .build();
+    let mut alice_group = MlsGroup::new_with_group_id(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        GroupId::from_slice(b"Test Group"),
+        alice_credential_with_key.clone(),
+    )
+    .expect("An unexpected error occurred.");
+
+    // The delivery service only knows the published group info and tree.
+    let verifiable_group_info = alice_group
+        .export_group_info(provider, &alice_signer, false)
+        .unwrap()
+        .into_verifiable_group_info()
+        .unwrap();
+    let (public_group, _group_info) = PublicGroup::from_external(
+        provider.crypto(),
+        alice_group.export_ratchet_tree().into(),
+        verifiable_group_info,
+        ProposalStore::new(),
+    )
+    .unwrap();
+
+    // === Proposal ===
+    let (proposal, _proposal_ref) = alice_group
+        .propose_add_member(provider, &alice_signer, bob_kpb.key_package())
+        .unwrap();
+    let validated_message = public_group
+        .validate_message(
+            provider.crypto(),
+            provider.clock(),
+            into_public_message(proposal.clone()),
+        )
+        .expect("Error validating proposal.");
+    assert_eq!(
+        validated_message.sender(),
+        &Sender::build_member(LeafNodeIndex::new(0))
+    );
+    assert_eq!(
+        validated_message.credential(),
+        &alice_credential_with_key.credential
+    );
+    assert_eq!(
+        validated_message.epoch(),
+        public_group.group_context().epoch()
+    );
+    assert_eq!(
+        validated_message.content(),
+        &ValidatedMessageContent::Proposal(ProposalType::Add)
+    );
+
+    // A tampered signature is detected. The signature is followed by the
+    // membership tag.
+    let mut bytes = proposal.tls_serialize_detached().unwrap();
+    let signature_end = bytes.len() - ciphersuite.hash_length() - 1;
+    bytes[signature_end - 1] ^= 1;
+    let tampered_proposal = MlsMessageIn::tls_deserialize_exact(&bytes)
+        .unwrap()
+        .into_protocol_message()
+        .unwrap();
+    assert_eq!(
+        public_group.validate_message(provider.crypto(), provider.clock(), tampered_proposal),
+        Err(ProcessMessageError::InvalidSignature)
+    );
+
+    // === Commit ===
+    let (commit, _welcome, _group_info) = alice_group
+        .commit_to_pending_proposals(provider, &alice_signer)
+        .unwrap()
+        .into_parts();
+    let validated_message = public_group
+        .validate_message(
+            provider.crypto(),
+            provider.clock(),
+            into_public_message(commit),
+        )
+        .expect("Error validating commit.");
+    assert_eq!(
+        validated_message.sender(),
+        &Sender::build_member(LeafNodeIndex::new(0))
+    );
+    match validated_message.content() {
+        ValidatedMessageContent::Commit(commit_summary) => {
+            assert!(commit_summary.proposal_types().is_empty());
+            assert_eq!(commit_summary.proposal_references(), 1);
+            assert!(commit_summary.has_path());
+        }
+        ValidatedMessageContent::Proposal(_) => panic!("Expected a commit."),
+    }
+}
+
+// Only the member in the leftmost leaf may remove members.
+struct AdminPolicy;
+
+impl MembershipPolicy for AdminPolicy {
+    fn may_remove(&self, _group: &PublicGroup, sender: &Sender, _removed: &Member) -> bool {
+        sender == &Sender::build_member(LeafNodeIndex::new(0))
+    }
+}
+
+#[apply(ciphersuites_and_providers)]
+fn membership_policy(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential, bob_kpb, bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+    let (_charlie_credential, charlie_kpb, _charlie_signer, _charlie_pk) =
+        setup_client("Charlie", ciphersuite, provider);
+
+    let mls_group_config = MlsGroupConfigBuilder::new()
+        .wire_format_policy(PURE_PLAINTEXT_WIRE_FORMAT_POLICY)
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .build();
+    let mut alice_group = MlsGroup::new_with_group_id(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        GroupId::from_slice(b"Test Group"),
+        alice_credential_with_key,
+    )
+    .expect("An unexpected error occurred.");
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(
+            provider,
+            &alice_signer,
+            &[
+                bob_kpb.key_package().clone(),
+                charlie_kpb.key_package().clone(),
+            ],
+        )
+        .unwrap()
+        .into_parts();
+    alice_group.merge_pending_commit(provider).unwrap();
+    let mut bob_group = MlsGroup::new_from_welcome(
+        provider,
+        &mls_group_config,
+        welcome.into_welcome().unwrap(),
+        Some(alice_group.export_ratchet_tree().into()),
+    )
+    .unwrap();
+
+    let verifiable_group_info = alice_group
+        .export_group_info(provider, &alice_signer, false)
+        .unwrap()
+        .into_verifiable_group_info()
+        .unwrap();
+    let (mut public_group, _group_info) = PublicGroup::from_external(
+        provider.crypto(),
+        alice_group.export_ratchet_tree().into(),
+        verifiable_group_info.clone(),
+        ProposalStore::new(),
+    )
+    .unwrap();
+    public_group
+        .validate_group_info(provider.crypto(), verifiable_group_info.clone())
+        .expect("Error validating group info.");
+
+    // === Bob may not remove Charlie ===
+    let (proposal, _proposal_ref) = bob_group
+        .propose_remove_member(provider, &bob_signer, LeafNodeIndex::new(2))
+        .unwrap();
+    let processed_message = public_group
+        .process_message(
+            provider.crypto(),
+            provider.clock(),
+            into_public_message(proposal),
+        )
+        .unwrap();
+    assert_eq!(
+        public_group.check_membership_policy(&processed_message, &AdminPolicy),
+        Err(MembershipPolicyError::UnauthorizedRemove)
+    );
+
+    // === Alice may remove Charlie ===
+    let (commit, _welcome, _group_info) = alice_group
+        .remove_members(provider, &alice_signer, &[LeafNodeIndex::new(2)])
+        .unwrap()
+        .into_parts();
+    alice_group.merge_pending_commit(provider).unwrap();
+    let processed_message = public_group
+        .process_message(
+            provider.crypto(),
+            provider.clock(),
+            into_public_message(commit),
+        )
+        .unwrap();
+    public_group
+        .check_membership_policy(&processed_message, &AdminPolicy)
+        .expect("Alice may remove Charlie.");
+    public_group.merge_commit(extract_staged_commit(processed_message));
+    assert_eq!(public_group.members().count(), 2);
+
+    // === Group infos are validated against the tracked state ===
+    assert_eq!(
+        public_group.validate_group_info(provider.crypto(), verifiable_group_info),
+        Err(GroupInfoValidationError::GroupContextMismatch)
+    );
+    let verifiable_group_info = alice_group
+        .export_group_info(provider, &alice_signer, false)
+        .unwrap()
+        .into_verifiable_group_info()
+        .unwrap();
+    public_group
+        .validate_group_info(provider.crypto(), verifiable_group_info)
+        .expect("Error validating group info.");
+}
+
 // A helper function
+struct FixedClock(u64);
+
+impl OpenMlsClock for FixedClock {
+    fn now(&self) -> u64 {
+        self.0
+    }
+}
+
+#[apply(ciphersuites_and_providers)]
+fn lifetime_validation_clock(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential, bob_kpb, _bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+
+    let mls_group_config = MlsGroupConfigBuilder::new()
+        .wire_format_policy(PURE_PLAINTEXT_WIRE_FORMAT_POLICY)
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .build();
+    let mut alice_group = MlsGroup::new(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        alice_credential_with_key,
+    )
+    .unwrap();
+    let verifiable_group_info = alice_group
+        .export_group_info(provider, &alice_signer, false)
+        .unwrap()
+        .into_verifiable_group_info()
+        .unwrap();
+    let (public_group, _group_info) = PublicGroup::from_external(
+        provider.crypto(),
+        alice_group.export_ratchet_tree().into(),
+        verifiable_group_info,
+        ProposalStore::new(),
+    )
+    .unwrap();
+
+    let lifetime = *bob_kpb
+        .key_package()
+        .leaf_node()
+        .life_time()
+        .expect("missing lifetime");
+    let (commit, _welcome, _group_info) = alice_group
+        .add_members(provider, &alice_signer, &[bob_kpb.key_package().clone()])
+        .unwrap()
+        .into_parts();
+    let commit = into_public_message(commit);
+
+    // The key package of Bob is only accepted within its lifetime, according
+    // to the given clock and not to the system time.
+    for time in [lifetime.not_before() + 1, lifetime.not_after() - 1] {
+        public_group
+            .process_message(provider.crypto(), &FixedClock(time), commit.clone())
+            .expect("key package within its lifetime rejected");
+    }
+    for time in [lifetime.not_before(), lifetime.not_after()] {
+        assert_eq!(
+            public_group
+                .process_message(provider.crypto(), &FixedClock(time), commit.clone())
+                .expect_err("key package outside of its lifetime accepted"),
+            ProcessMessageError::ValidationError(ValidationError::KeyPackageVerifyError(
+                KeyPackageVerifyError::InvalidLifetime
+            ))
+        );
+    }
+}
+
 fn into_public_message(message: MlsMessageOut) -> PublicMessageIn {
     match message.into_protocol_message().unwrap() {
         ProtocolMessage::PrivateMessage(_) => panic!("Unexpected message type."),
@@ -1,30 +1,40 @@
 //! This module contains validation functions for incoming messages
 //! as defined in <https://github.com/openmls/openmls/wiki/Message-validation>
 
+#![deny(clippy::unwrap_used, clippy::expect_used)]
+
 use std::collections::{BTreeSet, HashSet};
 
 use openmls_traits::types::VerifiableCiphersuite;
+use tls_codec::Size;
 
 use super::PublicGroup;
 #[cfg(test)]
 use crate::treesync::errors::LeafNodeValidationError;
 use crate::{
     binary_tree::array_representation::LeafNodeIndex,
+    extensions::{ExternalSender, ExternalSenderScopesExtension},
     framing::{
         mls_auth_content_in::VerifiableAuthenticatedContentIn, ContentType, ProtocolMessage,
         Sender, WireFormat,
     },
     group::{
-        errors::{ExternalCommitValidationError, ProposalValidationError, ValidationError},
+        errors::{
+            CommitLimitError, ExternalCommitValidationError, ProposalValidationError,
+            ValidationError,
+        },
         past_secrets::MessageSecretsStore,
-        Member, ProposalQueue,
+        ProposalQueue, ProposalStore,
     },
     messages::{
-        proposals::{Proposal, ProposalOrRefType, ProposalType},
+        proposals::{Proposal, ProposalOrRef, ProposalOrRefType, ProposalType},
         Commit,
     },
-    schedule::errors::PskError,
-    treesync::node::leaf_node::LeafNode,
+    schedule::{
+        errors::PskError,
+        psk::{PreSharedKeyId, ResumptionPskUsage},
+    },
+    treesync::{node::leaf_node::LeafNode, KeyUniquenessViolation},
 };
 
 impl PublicGroup {
@@ -76,6 +86,7 @@ impl PublicGroup {
     ///  - ValSem004
     ///  - ValSem005
     ///  - ValSem009
+    ///  - ValSem112
     pub(super) fn validate_verifiable_content(
         &self,
         verifiable_content: &VerifiableAuthenticatedContentIn,
@@ -115,6 +126,52 @@ impl PublicGroup {
             return Err(ValidationError::MissingConfirmationTag);
         }
 
+        // ValSem112
+        // External senders can only send proposals
+        if matches!(sender, Sender::External(_))
+            && verifiable_content.content_type() == ContentType::Commit
+        {
+            return Err(ValidationError::InvalidSenderType);
+        }
+
+        Ok(())
+    }
+
+    /// Returns the entry of the `ExternalSendersExtension` of the group that
+    /// `sender` refers to, if `sender` is external.
+    pub(crate) fn external_sender(&self, sender: &Sender) -> Option<&ExternalSender> {
+        match sender {
+            Sender::External(index) => self
+                .group_context()
+                .extensions()
+                .external_senders()?
+                .get(index.index()),
+            _ => None,
+        }
+    }
+
+    /// Validate that the external `sender` can be attributed to exactly one
+    /// entry of the `ExternalSendersExtension`, if external senders are
+    /// validated strictly. Missing entries are left to ValSem245.
+    pub(super) fn validate_external_sender(&self, sender: &Sender) -> Result<(), ValidationError> {
+        if !self.validation_policy().strict_external_senders() {
+            return Ok(());
+        }
+        let external_sender = match self.external_sender(sender) {
+            Some(external_sender) => external_sender,
+            None => return Ok(()),
+        };
+        let occurrences = self
+            .group_context()
+            .extensions()
+            .external_senders()
+            .into_iter()
+            .flatten()
+            .filter(|entry| entry.signature_key() == external_sender.signature_key())
+            .count();
+        if occurrences > 1 {
+            return Err(ValidationError::AmbiguousExternalSender);
+        }
         Ok(())
     }
 
@@ -128,11 +185,17 @@ impl PublicGroup {
     ///  - ValSem110: Update Proposal: Encryption key must be unique among proposals & members
     ///  - ValSem206: Commit: Path leaf node encryption key must be unique among proposals & members
     ///  - ValSem207: Commit: Path encryption keys must be unique among proposals & members
+    ///
+    /// The keys of the members are looked up in the key index of the tree,
+    /// ignoring the members that are removed by the proposals. Collisions with
+    /// the key of a member are reported with the leaf index of the member.
+    /// Since RFC 9420 requires unique keys, violations are always errors.
     pub(crate) fn validate_key_uniqueness(
         &self,
         proposal_queue: &ProposalQueue,
         commit: Option<&Commit>,
     ) -> Result<(), ProposalValidationError> {
+        let key_index = self.treesync().key_index();
         let mut signature_key_set = HashSet::new();
         let mut init_key_set = HashSet::new();
         let mut encryption_key_set = HashSet::new();
@@ -142,21 +205,8 @@ impl PublicGroup {
                 .remove_proposals()
                 .map(|remove_proposal| remove_proposal.remove_proposal().removed),
         );
-
-        // Initialize the sets with the current members, filtered by the
-        // remove proposals.
-        for Member {
-            index,
-            encryption_key,
-            signature_key,
-            ..
-        } in self.treesync().full_leave_members()
-        {
-            if !remove_proposals.contains(&index) {
-                signature_key_set.insert(signature_key);
-                encryption_key_set.insert(encryption_key);
-            }
-        }
+        // Returns `true` if the member is not removed by the proposals.
+        let is_remaining = |leaf_index: &LeafNodeIndex| !remove_proposals.contains(leaf_index);
 
         // Collect signature keys from add proposals
         let signature_keys = proposal_queue.add_proposals().map(|add_proposal| {
@@ -224,8 +274,16 @@ impl PublicGroup {
         // Validate uniqueness of signature keys
         //  - ValSem101
         for signature_key in signature_keys {
+            if let Some(leaf_index) = key_index
+                .signature_key_leaves(&signature_key)
+                .find(is_remaining)
+            {
+                return Err(ProposalValidationError::DuplicateSignatureKey(Some(
+                    leaf_index,
+                )));
+            }
             if !signature_key_set.insert(signature_key) {
-                return Err(ProposalValidationError::DuplicateSignatureKey);
+                return Err(ProposalValidationError::DuplicateSignatureKey(None));
             }
         }
 
@@ -239,8 +297,16 @@ impl PublicGroup {
             if init_key_set.contains(&encryption_key) {
                 return Err(ProposalValidationError::InitEncryptionKeyCollision);
             }
+            if let Some(leaf_index) = key_index
+                .encryption_key_leaves(&encryption_key)
+                .find(is_remaining)
+            {
+                return Err(ProposalValidationError::DuplicateEncryptionKey(Some(
+                    leaf_index,
+                )));
+            }
             if !encryption_key_set.insert(encryption_key) {
-                return Err(ProposalValidationError::DuplicateEncryptionKey);
+                return Err(ProposalValidationError::DuplicateEncryptionKey(None));
             }
         }
 
@@ -248,7 +314,11 @@ impl PublicGroup {
         //  - ValSem102
         //  - ValSem104
         for init_key in init_keys {
-            if encryption_key_set.contains(&init_key) {
+            if encryption_key_set.contains(&init_key)
+                || key_index
+                    .encryption_key_leaves(&init_key)
+                    .any(|i| is_remaining(&i))
+            {
                 return Err(ProposalValidationError::InitEncryptionKeyCollision);
             }
             if !init_key_set.insert(init_key) {
@@ -259,6 +329,15 @@ impl PublicGroup {
         Ok(())
     }
 
+    /// Returns the signature and encryption keys that are used by more than
+    /// one leaf of the tree, together with the indices of the leaves.
+    ///
+    /// A valid tree has no such keys. Duplicate keys can only be part of the
+    /// tree if the tree was imported when joining the group.
+    pub fn key_uniqueness_violations(&self) -> Vec<KeyUniquenessViolation> {
+        self.treesync().key_index().violations()
+    }
+
     /// Validate capablities. This function implements the following checks:
     /// - ValSem106: Add Proposal: required capabilities
     /// - ValSem109: Update Proposal: required capabilities
@@ -290,6 +369,8 @@ impl PublicGroup {
             });
 
         let mut group_leaf_nodes = self.treesync().full_leaves();
+        let capabilities_check = self.validation_policy().capabilities();
+        let extensions_check = self.validation_policy().extensions();
 
         for leaf_node in leaf_nodes {
             // Check if the ciphersuite and the version of the group are
@@ -300,7 +381,7 @@ impl PublicGroup {
                 .contains(&VerifiableCiphersuite::from(self.ciphersuite()))
                 || !capabilities.versions().contains(&self.version())
             {
-                return Err(ProposalValidationError::InsufficientCapabilities);
+                capabilities_check.check(ProposalValidationError::InsufficientCapabilities)?;
             }
 
             // If there is a required capabilities extension, check if that one
@@ -309,19 +390,22 @@ impl PublicGroup {
                 self.group_context().extensions().required_capabilities()
             {
                 // Check if all required capabilities are supported.
-                capabilities
+                if capabilities
                     .supports_required_capabilities(required_capabilities)
-                    .map_err(|_| ProposalValidationError::InsufficientCapabilities)?;
+                    .is_err()
+                {
+                    capabilities_check.check(ProposalValidationError::InsufficientCapabilities)?;
+                }
             }
 
             // Check that all extensions are contained in the capabilities.
             if !capabilities.contain_extensions(leaf_node.extensions()) {
-                return Err(ProposalValidationError::InsufficientCapabilities);
+                extensions_check.check(ProposalValidationError::InsufficientCapabilities)?;
             }
 
             // Check that the capabilities contain the leaf node's credential type.
             if !capabilities.contains_credential(&leaf_node.credential().credential_type()) {
-                return Err(ProposalValidationError::InsufficientCapabilities);
+                capabilities_check.check(ProposalValidationError::InsufficientCapabilities)?;
             }
 
             // Check that the credential type is supported by all members of the group.
@@ -329,7 +413,7 @@ impl PublicGroup {
                 node.capabilities()
                     .contains_credential(&leaf_node.credential().credential_type())
             }) {
-                return Err(ProposalValidationError::InsufficientCapabilities);
+                capabilities_check.check(ProposalValidationError::InsufficientCapabilities)?;
             }
 
             // Check that the capabilities field of this LeafNode indicates
@@ -338,7 +422,52 @@ impl PublicGroup {
             if !group_leaf_nodes
                 .all(|node| capabilities.contains_credential(&node.credential().credential_type()))
             {
-                return Err(ProposalValidationError::InsufficientCapabilities);
+                capabilities_check.check(ProposalValidationError::InsufficientCapabilities)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Validate that `commit` doesn't exceed the [`CommitLimits`] of the
+    /// group. Proposals by reference are looked up in `proposal_store` to
+    /// count the Add proposals. Proposals that are missing from the store are
+    /// left to the construction of the proposal queue.
+    ///
+    /// [`CommitLimits`]: crate::group::config::CommitLimits
+    pub(crate) fn validate_commit_limits(
+        &self,
+        commit: &Commit,
+        proposal_store: &ProposalStore,
+    ) -> Result<(), CommitLimitError> {
+        let limits = self.commit_limits();
+
+        if matches!(limits.max_size(), Some(max) if commit.tls_serialized_len() > max) {
+            return Err(CommitLimitError::TooLarge);
+        }
+        if matches!(limits.max_proposals(), Some(max) if commit.proposals.len() > max) {
+            return Err(CommitLimitError::TooManyProposals);
+        }
+        if let (Some(max), Some(path)) = (limits.max_path_nodes(), commit.path()) {
+            if path.nodes().len() > max {
+                return Err(CommitLimitError::TooManyPathNodes);
+            }
+        }
+        if let Some(max) = limits.max_adds() {
+            let adds = commit
+                .proposals
+                .iter()
+                .filter(|proposal_or_ref| match proposal_or_ref {
+                    ProposalOrRef::Proposal(proposal) => proposal.is_type(ProposalType::Add),
+                    ProposalOrRef::Reference(reference) => {
+                        proposal_store.proposals().any(|queued_proposal| {
+                            &queued_proposal.proposal_reference() == reference
+                                && queued_proposal.proposal().is_type(ProposalType::Add)
+                        })
+                    }
+                })
+                .count();
+            if adds > max {
+                return Err(CommitLimitError::TooManyAdds);
             }
         }
         Ok(())
@@ -366,6 +495,7 @@ impl PublicGroup {
     /// Validate Remove proposals. This function implements the following checks:
     ///  - ValSem107: Remove Proposal: Removed member must be unique among proposals
     ///  - ValSem108: Remove Proposal: Removed member must be an existing group member
+    ///  - External senders may only remove members within their scope
     pub(crate) fn validate_remove_proposals(
         &self,
         proposal_queue: &ProposalQueue,
@@ -385,11 +515,65 @@ impl PublicGroup {
             if !self.treesync().is_leaf_in_tree(removed) {
                 return Err(ProposalValidationError::UnknownMemberRemoval);
             }
+
+            self.validate_external_sender_scope(remove_proposal.sender(), removed)?;
         }
 
         Ok(())
     }
 
+    /// Validate that an external `sender` may remove the member at `removed`
+    /// according to the [`ExternalSenderScopesExtension`] of the group.
+    /// Removals of members that are not in the tree are left to ValSem108.
+    pub(crate) fn validate_external_sender_scope(
+        &self,
+        sender: &Sender,
+        removed: LeafNodeIndex,
+    ) -> Result<(), ProposalValidationError> {
+        let sender_index = match sender {
+            Sender::External(sender_index) => *sender_index,
+            _ => return Ok(()),
+        };
+        let scopes = match self
+            .group_context()
+            .extensions()
+            .custom::<ExternalSenderScopesExtension>()
+        {
+            Ok(Some(scopes)) => scopes,
+            Ok(None) => return Ok(()),
+            // Malformed scopes don't allow any external removal.
+            Err(_) => return Err(ProposalValidationError::ExternalSenderOutOfScope),
+        };
+        match self.leaf(removed) {
+            Some(leaf) if !scopes.may_remove(sender_index, leaf.credential().identity()) => {
+                Err(ProposalValidationError::ExternalSenderOutOfScope)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Validate that the proposals of external senders in `proposal_queue`
+    /// were signed by the sender their index refers to in the current
+    /// `ExternalSendersExtension`, if external senders are validated strictly.
+    pub(crate) fn validate_external_proposals(
+        &self,
+        proposal_queue: &ProposalQueue,
+    ) -> Result<(), ProposalValidationError> {
+        if !self.validation_policy().strict_external_senders() {
+            return Ok(());
+        }
+        for queued_proposal in proposal_queue.queued_proposals() {
+            if !matches!(queued_proposal.sender(), Sender::External(_)) {
+                continue;
+            }
+            let current = self.external_sender(queued_proposal.sender());
+            if current.is_none() || current != queued_proposal.external_sender() {
+                return Err(ProposalValidationError::StaleExternalSender);
+            }
+        }
+        Ok(())
+    }
+
     /// Validate Update proposals. This function implements the following checks:
     ///  - ValSem111: Update Proposal: The sender of a full Commit must not include own update proposals
     ///  - ValSem112: Update Proposal: The sender of a standalone update proposal must be of type member
@@ -425,9 +609,14 @@ impl PublicGroup {
     /// * ValSem401: The nonce of a PreSharedKeyID must have length KDF.Nh.
     /// * ValSem402: PSK in proposal must be of type Resumption (with usage Application) or External.
     /// * ValSem403: Proposal list must not contain multiple PreSharedKey proposals that reference the same PreSharedKeyID.
+    ///
+    /// The PSKs in `branch_psk_ids` are the resumption PSKs of the subgroup
+    /// branching operation that creates the commit. They are the only PSKs
+    /// that may have usage Branch.
     pub(crate) fn validate_pre_shared_key_proposals(
         &self,
         proposal_queue: &ProposalQueue,
+        branch_psk_ids: &[PreSharedKeyId],
     ) -> Result<(), ProposalValidationError> {
         // ValSem403 (1/2)
         // TODO(#1335): Duplicate proposals are (likely) filtered.
@@ -439,7 +628,12 @@ impl PublicGroup {
 
             // ValSem401
             // ValSem402
-            let psk_id = psk_id.validate_in_proposal(self.ciphersuite())?;
+            let allowed_usages: &[ResumptionPskUsage] = if branch_psk_ids.contains(&psk_id) {
+                &[ResumptionPskUsage::Application, ResumptionPskUsage::Branch]
+            } else {
+                &[ResumptionPskUsage::Application]
+            };
+            let psk_id = psk_id.validate_in_proposal(self.ciphersuite(), allowed_usages)?;
 
             // ValSem403 (2/2)
             if !visited_psk_ids.contains(&psk_id) {
@@ -76,7 +76,8 @@ fn validation_test_setup(
 
     let (_message, welcome, _group_info) = alice_group
         .add_members(provider, &alice_signer_with_keys.signer, &[bob_key_package])
-        .expect("error adding Bob to group");
+        .expect("error adding Bob to group")
+        .into_parts();
 
     alice_group
         .merge_pending_commit(provider)
@@ -177,7 +178,8 @@ fn external_add_proposal_should_succeed(ciphersuite: Ciphersuite, provider: &imp
         // and Alice will commit it
         let (commit, welcome, _group_info) = alice_group
             .commit_to_pending_proposals(provider, &alice_signer)
-            .unwrap();
+            .unwrap()
+            .into_parts();
         alice_group.merge_pending_commit(provider).unwrap();
         assert_eq!(alice_group.members().count(), 3);
 
@@ -3,8 +3,12 @@ use rstest::*;
 use rstest_reuse::{self, *};
 
 use crate::{
+    binary_tree::LeafNodeIndex,
     framing::*,
-    group::{config::CryptoConfig, *},
+    group::{
+        config::{CryptoConfig, ValidationPolicy},
+        *,
+    },
     messages::external_proposals::*,
 };
 
@@ -339,3 +343,428 @@ fn external_remove_proposal_should_fail_when_no_external_senders(
         ProcessMessageError::ValidationError(ValidationError::NoExternalSendersExtension)
     );
 }
+
+#[apply(ciphersuites_and_providers)]
+fn external_remove_proposal_should_respect_scopes(
+    ciphersuite: Ciphersuite,
+    provider: &impl OpenMlsProvider,
+) {
+    // Each domain has a server that may send external proposals.
+    let example_ds = generate_credential_with_key(
+        "ds@example.com".into(),
+        ciphersuite.signature_algorithm(),
+        provider,
+    );
+    let other_ds = generate_credential_with_key(
+        "ds@other.org".into(),
+        ciphersuite.signature_algorithm(),
+        provider,
+    );
+    let external_senders = [&example_ds, &other_ds]
+        .iter()
+        .map(|ds| {
+            ExternalSender::new(
+                ds.credential_with_key.signature_key.clone(),
+                ds.credential_with_key.credential.clone(),
+            )
+        })
+        .collect();
+    let external_sender_scopes = ExternalSenderScopesExtension::new(vec![
+        ExternalSenderScope::new(SenderExtensionIndex::new(0), b"example.com"),
+        ExternalSenderScope::new(SenderExtensionIndex::new(1), b"other.org"),
+    ]);
+    assert!(external_sender_scopes.may_remove(SenderExtensionIndex::new(0), b"a@example.com"));
+    assert!(!external_sender_scopes.may_remove(SenderExtensionIndex::new(0), b"example.com"));
+    assert!(external_sender_scopes.may_remove(SenderExtensionIndex::new(2), b"Charlie"));
+
+    // === Alice creates a group with Bob ===
+    let alice_credential = generate_credential_with_key(
+        "alice@example.com".into(),
+        ciphersuite.signature_algorithm(),
+        provider,
+    );
+    let mls_group_config = MlsGroupConfig::builder()
+        .wire_format_policy(PURE_PLAINTEXT_WIRE_FORMAT_POLICY)
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .external_senders(external_senders)
+        .external_sender_scopes(external_sender_scopes.clone())
+        .build();
+    let mut alice_group = MlsGroup::new_with_group_id(
+        provider,
+        &alice_credential.signer,
+        &mls_group_config,
+        GroupId::from_slice(b"Test Group"),
+        alice_credential.credential_with_key.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        alice_group
+            .group()
+            .group_context_extensions()
+            .custom::<ExternalSenderScopesExtension>(),
+        Ok(Some(external_sender_scopes))
+    );
+
+    let bob_credential = generate_credential_with_key(
+        "bob@other.org".into(),
+        ciphersuite.signature_algorithm(),
+        provider,
+    );
+    let bob_key_package =
+        generate_key_package(ciphersuite, Extensions::empty(), provider, bob_credential);
+    alice_group
+        .add_members(provider, &alice_credential.signer, &[bob_key_package])
+        .unwrap();
+    alice_group.merge_pending_commit(provider).unwrap();
+    let bob_index = alice_group
+        .members()
+        .find(|member| member.credential.identity() == b"bob@other.org")
+        .map(|member| member.index)
+        .unwrap();
+
+    // The server of example.com can't remove Bob.
+    let remove_proposal: MlsMessageIn = ExternalProposal::new_remove(
+        bob_index,
+        alice_group.group_id().clone(),
+        alice_group.epoch(),
+        &example_ds.signer,
+        SenderExtensionIndex::new(0),
+    )
+    .unwrap()
+    .into();
+    assert_eq!(
+        alice_group
+            .process_message(provider, remove_proposal)
+            .unwrap_err(),
+        ProcessMessageError::InvalidProposal(ProposalValidationError::ExternalSenderOutOfScope)
+    );
+
+    // The server of other.org can.
+    let remove_proposal: MlsMessageIn = ExternalProposal::new_remove(
+        bob_index,
+        alice_group.group_id().clone(),
+        alice_group.epoch(),
+        &other_ds.signer,
+        SenderExtensionIndex::new(1),
+    )
+    .unwrap()
+    .into();
+    let processed_message = alice_group
+        .process_message(provider, remove_proposal)
+        .unwrap();
+    let ProcessedMessageContent::ProposalMessage(remove_proposal) =
+        processed_message.into_content()
+    else {
+        panic!("Not a remove proposal");
+    };
+    alice_group.store_pending_proposal(*remove_proposal);
+    alice_group
+        .commit_to_pending_proposals(provider, &alice_credential.signer)
+        .unwrap();
+    alice_group.merge_pending_commit(provider).unwrap();
+    assert_eq!(alice_group.members().count(), 1);
+}
+
+#[apply(ciphersuites_and_providers)]
+fn moderator_removal_should_be_auto_committed(
+    ciphersuite: Ciphersuite,
+    provider: &impl OpenMlsProvider,
+) {
+    // The moderator is the only external sender of the group.
+    let moderator = generate_credential_with_key(
+        "moderator".into(),
+        ciphersuite.signature_algorithm(),
+        provider,
+    );
+    let mls_group_config = MlsGroupConfig::builder()
+        .wire_format_policy(PURE_PLAINTEXT_WIRE_FORMAT_POLICY)
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .use_ratchet_tree_extension(true)
+        .external_senders(vec![ExternalSender::new(
+            moderator.credential_with_key.signature_key.clone(),
+            moderator.credential_with_key.credential.clone(),
+        )])
+        .moderator_removal_policy(ModeratorRemovalPolicy::AutoCommit)
+        .build();
+
+    // === Alice creates a group with Bob and Charlie ===
+    let alice_credential =
+        generate_credential_with_key("Alice".into(), ciphersuite.signature_algorithm(), provider);
+    let bob_credential =
+        generate_credential_with_key("Bob".into(), ciphersuite.signature_algorithm(), provider);
+    let charlie_credential = generate_credential_with_key(
+        "Charlie".into(),
+        ciphersuite.signature_algorithm(),
+        provider,
+    );
+    let mut alice_group = MlsGroup::new_with_group_id(
+        provider,
+        &alice_credential.signer,
+        &mls_group_config,
+        GroupId::from_slice(b"Test Group"),
+        alice_credential.credential_with_key.clone(),
+    )
+    .unwrap();
+    let key_packages = [&bob_credential, &charlie_credential].map(|credential| {
+        generate_key_package(
+            ciphersuite,
+            Extensions::empty(),
+            provider,
+            credential.clone(),
+        )
+    });
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(provider, &alice_credential.signer, &key_packages)
+        .unwrap()
+        .into_parts();
+    alice_group.merge_pending_commit(provider).unwrap();
+    let welcome = welcome.into_welcome().unwrap();
+    let mut bob_group =
+        MlsGroup::new_from_welcome(provider, &mls_group_config, welcome.clone(), None).unwrap();
+    let mut charlie_group =
+        MlsGroup::new_from_welcome(provider, &mls_group_config, welcome, None).unwrap();
+
+    // === The moderator tracks the group and removes Alice ===
+    let verifiable_group_info = alice_group
+        .export_group_info(provider, &alice_credential.signer, false)
+        .unwrap()
+        .into_verifiable_group_info()
+        .unwrap();
+    let (moderator_group, _group_info) = PublicGroup::from_external(
+        provider.crypto(),
+        alice_group.export_ratchet_tree().into(),
+        verifiable_group_info,
+        ProposalStore::new(),
+    )
+    .unwrap();
+    assert_eq!(
+        ExternalProposal::new_remove_member(
+            &alice_credential.credential_with_key.credential,
+            &moderator_group,
+            &moderator.signer,
+            SenderExtensionIndex::new(1),
+        )
+        .unwrap_err(),
+        ExternalRemoveProposalError::UnknownExternalSender
+    );
+    assert_eq!(
+        ExternalProposal::new_remove_member(
+            &moderator.credential_with_key.credential,
+            &moderator_group,
+            &moderator.signer,
+            SenderExtensionIndex::new(0),
+        )
+        .unwrap_err(),
+        ExternalRemoveProposalError::UnknownMember
+    );
+    let remove_proposal = ExternalProposal::new_remove_member(
+        &alice_credential.credential_with_key.credential,
+        &moderator_group,
+        &moderator.signer,
+        SenderExtensionIndex::new(0),
+    )
+    .unwrap();
+
+    // All members store the removal when they process it.
+    for group in [&mut alice_group, &mut bob_group, &mut charlie_group] {
+        let processed_message = group
+            .process_message(
+                provider,
+                remove_proposal.clone().into_protocol_message().unwrap(),
+            )
+            .unwrap();
+        assert_eq!(group.pending_moderator_removals().count(), 1);
+        // Storing the proposal again doesn't duplicate it.
+        let ProcessedMessageContent::ProposalMessage(queued_proposal) =
+            processed_message.into_content()
+        else {
+            panic!("Not a remove proposal");
+        };
+        group.store_pending_proposal(*queued_proposal);
+        assert_eq!(group.pending_proposals().count(), 1);
+    }
+
+    // Bob is the first member that isn't removed, so only Bob commits.
+    assert!(alice_group
+        .commit_moderator_removals(provider, &alice_credential.signer)
+        .unwrap()
+        .is_none());
+    assert!(charlie_group
+        .commit_moderator_removals(provider, &charlie_credential.signer)
+        .unwrap()
+        .is_none());
+    let (commit, _welcome, _group_info) = bob_group
+        .commit_moderator_removals(provider, &bob_credential.signer)
+        .unwrap()
+        .expect("Bob didn't commit the removal")
+        .into_parts();
+    bob_group.merge_pending_commit(provider).unwrap();
+
+    // Charlie sees that Alice was removed by the moderator.
+    let processed_message = charlie_group
+        .process_message(provider, commit.into_protocol_message().unwrap())
+        .unwrap();
+    let ProcessedMessageContent::StagedCommitMessage(staged_commit) =
+        processed_message.into_content()
+    else {
+        panic!("Not a commit");
+    };
+    let removal_events = charlie_group.removal_events(&staged_commit);
+    assert_eq!(removal_events.len(), 1);
+    assert_eq!(removal_events[0].removed(), LeafNodeIndex::new(0));
+    assert_eq!(
+        removal_events[0].credential(),
+        &alice_credential.credential_with_key.credential
+    );
+    assert_eq!(
+        removal_events[0].remover(),
+        &Remover::Moderator(SenderExtensionIndex::new(0))
+    );
+    charlie_group
+        .merge_staged_commit(provider, *staged_commit)
+        .unwrap();
+    assert_eq!(charlie_group.members().count(), 2);
+
+    // === Removals by members are peer removals ===
+    bob_group
+        .remove_members(
+            provider,
+            &bob_credential.signer,
+            &[charlie_group.own_leaf_index()],
+        )
+        .unwrap();
+    let removal_events = bob_group.removal_events(bob_group.pending_commit().unwrap());
+    assert_eq!(removal_events.len(), 1);
+    assert!(!removal_events[0].is_moderator_removal());
+    assert_eq!(
+        removal_events[0].remover(),
+        &Remover::Member(bob_group.own_leaf_index())
+    );
+}
+
+#[apply(ciphersuites_and_providers)]
+fn external_remove_proposal_with_strict_external_senders(
+    ciphersuite: Ciphersuite,
+    provider: &impl OpenMlsProvider,
+) {
+    let ds_credential_with_key = generate_credential_with_key(
+        "delivery-service".into(),
+        ciphersuite.signature_algorithm(),
+        provider,
+    );
+    let other_ds_credential_with_key = generate_credential_with_key(
+        "other-delivery-service".into(),
+        ciphersuite.signature_algorithm(),
+        provider,
+    );
+    let ds = ExternalSender::new(
+        ds_credential_with_key
+            .credential_with_key
+            .signature_key
+            .clone(),
+        ds_credential_with_key
+            .credential_with_key
+            .credential
+            .clone(),
+    );
+    // The same signature key is listed with a second credential.
+    let ds_alias = ExternalSender::new(
+        ds_credential_with_key
+            .credential_with_key
+            .signature_key
+            .clone(),
+        other_ds_credential_with_key
+            .credential_with_key
+            .credential
+            .clone(),
+    );
+
+    let (mut alice_group, alice_credential) = validation_test_setup(
+        PURE_PLAINTEXT_WIRE_FORMAT_POLICY,
+        ciphersuite,
+        provider,
+        vec![ds.clone(), ds_alias],
+    );
+    let mut strict_config = alice_group.configuration().clone();
+    strict_config.validation_policy = ValidationPolicy::strict().with_strict_external_senders(true);
+    alice_group.set_configuration(&strict_config);
+    let bob_index = alice_group
+        .members()
+        .find(|member| member.credential.identity() == b"Bob")
+        .map(|member| member.index)
+        .unwrap();
+    let remove_bob = |alice_group: &MlsGroup| -> MlsMessageIn {
+        ExternalProposal::new_remove(
+            bob_index,
+            alice_group.group_id().clone(),
+            alice_group.epoch(),
+            &ds_credential_with_key.signer,
+            SenderExtensionIndex::new(0),
+        )
+        .unwrap()
+        .into()
+    };
+
+    // === The proposal can't be attributed to exactly one sender ===
+    assert_eq!(
+        alice_group
+            .process_message(provider, remove_bob(&alice_group))
+            .unwrap_err(),
+        ProcessMessageError::ValidationError(ValidationError::AmbiguousExternalSender)
+    );
+
+    // === Alice removes the alias and learns which sender signed ===
+    let (_proposal, _proposal_ref) = alice_group
+        .propose_group_context_extensions(
+            provider,
+            Extensions::single(Extension::ExternalSenders(vec![ds.clone()])),
+            &alice_credential.signer,
+        )
+        .unwrap();
+    alice_group
+        .commit_to_pending_proposals(provider, &alice_credential.signer)
+        .unwrap();
+    alice_group.merge_pending_commit(provider).unwrap();
+    let processed_message = alice_group
+        .process_message(provider, remove_bob(&alice_group))
+        .unwrap();
+    let ProcessedMessageContent::ProposalMessage(remove_proposal) =
+        processed_message.into_content()
+    else {
+        panic!("Not a remove proposal");
+    };
+    assert_eq!(remove_proposal.external_sender(), Some(&ds));
+
+    // === The index is stale after the external senders changed ===
+    let (_proposal, _proposal_ref) = alice_group
+        .propose_group_context_extensions(
+            provider,
+            Extensions::single(Extension::ExternalSenders(vec![ExternalSender::new(
+                other_ds_credential_with_key
+                    .credential_with_key
+                    .signature_key
+                    .clone(),
+                other_ds_credential_with_key
+                    .credential_with_key
+                    .credential
+                    .clone(),
+            )])),
+            &alice_credential.signer,
+        )
+        .unwrap();
+    alice_group
+        .commit_to_pending_proposals(provider, &alice_credential.signer)
+        .unwrap();
+    alice_group.merge_pending_commit(provider).unwrap();
+    alice_group.store_pending_proposal(*remove_proposal);
+    assert_eq!(
+        alice_group
+            .commit_to_pending_proposals(provider, &alice_credential.signer)
+            .unwrap_err(),
+        CommitToPendingProposalsError::CreateCommitError(
+            CreateCommitError::ProposalValidationError(
+                ProposalValidationError::StaleExternalSender
+            )
+        )
+    );
+}
@@ -20,6 +20,8 @@ mod test_framing;
 mod test_framing_validation;
 #[cfg(test)]
 mod test_group;
+#[cfg(all(test, feature = "check-invariants"))]
+mod test_invariants;
 #[cfg(test)]
 mod test_past_secrets;
 #[cfg(test)]
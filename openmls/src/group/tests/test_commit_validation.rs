@@ -85,7 +85,8 @@ fn validation_test_setup(
             &alice_credential.signer,
             &[bob_key_package, charlie_key_package],
         )
-        .expect("error adding Bob to group");
+        .expect("error adding Bob to group")
+        .into_parts();
 
     alice_group
         .merge_pending_commit(provider)
@@ -162,6 +163,7 @@ fn test_valsem200(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     let serialized_message = alice_group
         .self_update(provider, &alice_credential.signer)
         .expect("Error creating self-update")
+        .commit()
         .tls_serialize_detached()
         .expect("Could not serialize message.");
 
@@ -440,6 +442,7 @@ fn test_valsem202(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     let serialized_update = alice_group
         .self_update(provider, &alice_credential.signer)
         .expect("Error creating self-update")
+        .commit()
         .tls_serialize_detached()
         .expect("Could not serialize message.");
 
@@ -511,6 +514,7 @@ fn test_valsem203(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     let serialized_update = alice_group
         .self_update(provider, &alice_credential.signer)
         .expect("Error creating self-update")
+        .commit()
         .tls_serialize_detached()
         .expect("Could not serialize message.");
 
@@ -584,6 +588,7 @@ fn test_valsem204(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     let serialized_update = alice_group
         .self_update(provider, &alice_credential.signer)
         .expect("Error creating self-update")
+        .commit()
         .tls_serialize_detached()
         .expect("Could not serialize message.");
 
@@ -703,6 +708,7 @@ fn test_valsem205(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     let serialized_update = alice_group
         .self_update(provider, &alice_credential.signer)
         .expect("Error creating self-update")
+        .commit()
         .tls_serialize_detached()
         .expect("Could not serialize message.");
 
@@ -801,7 +807,8 @@ fn test_partial_proposal_commit(ciphersuite: Ciphersuite, provider: &impl OpenMl
     alice_group.proposal_store.add(remaining_proposal);
     let (commit, _, _) = alice_group
         .commit_to_pending_proposals(provider, &alice_credential.signer)
-        .unwrap();
+        .unwrap()
+        .into_parts();
     // Alice herself should be able to merge the commit
     alice_group
         .merge_pending_commit(provider)
@@ -4,8 +4,15 @@ use tls_codec::{Deserialize, Serialize};
 
 use super::utils::*;
 use crate::{
-    binary_tree::LeafNodeIndex, framing::*, group::*, key_packages::*, messages::*,
-    schedule::psk::store::ResumptionPskStore, test_utils::*, *,
+    binary_tree::LeafNodeIndex,
+    framing::*,
+    group::*,
+    key_packages::*,
+    messages::{group_info::VerifiableGroupInfo, proposals_in::ProposalIn, *},
+    schedule::psk::store::ResumptionPskStore,
+    test_utils::*,
+    treesync::{node::leaf_node::LeafNodeIn, Node, ParentNode, RatchetTreeIn},
+    *,
 };
 
 /// Creates a simple test setup for various encoding tests.
@@ -432,3 +439,198 @@ fn test_welcome_message_encoding(provider: &impl OpenMlsProvider) {
         .is_ok());
     }
 }
+
+/// Decodes `bytes` as `T`, re-encodes the decoded value and checks that the
+/// encoding is byte-identical. Trailing data must be rejected.
+fn assert_canonical<T: Deserialize + Serialize>(bytes: &[u8]) -> T {
+    let decoded = T::tls_deserialize_exact(bytes).expect("error decoding");
+    assert_eq!(
+        decoded.tls_serialize_detached().expect("error re-encoding"),
+        bytes
+    );
+
+    let mut trailing_data = bytes.to_vec();
+    trailing_data.push(0);
+    assert!(T::tls_deserialize_exact(&trailing_data).is_err());
+
+    decoded
+}
+
+/// This test checks that re-encoding decoded tree and message types is
+/// byte-identical, i.e., that decoding doesn't lose or normalize any data.
+#[apply(ciphersuites_and_providers)]
+fn test_canonical_encoding(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        test_core_group::setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential, bob_kpb, _bob_signer, _bob_pk) =
+        test_core_group::setup_client("Bob", ciphersuite, provider);
+    let (_charlie_credential, charlie_kpb, _charlie_signer, _charlie_pk) =
+        test_core_group::setup_client("Charlie", ciphersuite, provider);
+
+    let mls_group_config = MlsGroupConfig::builder()
+        .crypto_config(config::CryptoConfig::with_default_version(ciphersuite))
+        .wire_format_policy(MIXED_PLAINTEXT_WIRE_FORMAT_POLICY)
+        .use_ratchet_tree_extension(true)
+        .build();
+    let mut alice_group = MlsGroup::new_with_group_id(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        GroupId::from_slice(b"Test Group"),
+        alice_credential_with_key,
+    )
+    .expect("error creating group");
+
+    // === Key packages ===
+    let key_package = bob_kpb.key_package().clone();
+    assert_canonical::<KeyPackageIn>(
+        &key_package
+            .tls_serialize_detached()
+            .expect("error encoding key package"),
+    );
+    assert_canonical::<MlsMessageIn>(
+        &MlsMessageOut::from(key_package)
+            .to_bytes()
+            .expect("error encoding key package"),
+    );
+
+    // === Commits and welcomes ===
+    let (commit, welcome, _group_info) = alice_group
+        .add_members(
+            provider,
+            &alice_signer,
+            &[
+                bob_kpb.key_package().clone(),
+                charlie_kpb.key_package().clone(),
+            ],
+        )
+        .expect("error adding members")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+
+    let commit = assert_canonical::<MlsMessageIn>(&commit.to_bytes().expect("error encoding"));
+    assert_eq!(commit.wire_format(), WireFormat::PublicMessage);
+    let welcome = assert_canonical::<MlsMessageIn>(&welcome.to_bytes().expect("error encoding"));
+    match welcome.extract() {
+        MlsMessageInBody::Welcome(welcome) => {
+            assert_canonical::<Welcome>(
+                &welcome
+                    .tls_serialize_detached()
+                    .expect("error encoding welcome"),
+            );
+        }
+        _ => panic!("Expected a welcome."),
+    }
+
+    let (commit, _welcome, _group_info) = alice_group
+        .self_update(provider, &alice_signer)
+        .expect("error updating")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    assert_canonical::<MlsMessageIn>(&commit.to_bytes().expect("error encoding"));
+
+    // === Application messages ===
+    let application_message = alice_group
+        .create_message(provider, &alice_signer, b"Hello, world!")
+        .expect("error creating message");
+    let application_message_bytes = application_message.to_bytes().expect("error encoding");
+    // The outer fields can be read without decoding the whole message.
+    let view = MlsMessageView::parse(&application_message_bytes).expect("error parsing view");
+    assert_eq!(view.wire_format(), WireFormat::PrivateMessage);
+    assert_eq!(view.group_id(), Some(alice_group.group_id().as_slice()));
+    assert_eq!(view.epoch(), Some(alice_group.epoch()));
+    assert_eq!(view.content_type(), Some(ContentType::Application));
+    assert_eq!(view.authenticated_data(), Some(&[][..]));
+    assert_eq!(view.sender(), None);
+    assert_eq!(
+        MlsMessageView::parse(&application_message_bytes[..10]),
+        Err(MlsMessageError::UnableToDecode)
+    );
+    let application_message = assert_canonical::<MlsMessageIn>(&application_message_bytes);
+    assert_eq!(
+        application_message.wire_format(),
+        WireFormat::PrivateMessage
+    );
+
+    // === Proposals ===
+    let (proposal, _proposal_ref) = alice_group
+        .propose_remove_member(provider, &alice_signer, LeafNodeIndex::new(2))
+        .expect("error creating proposal");
+    let proposal_bytes = proposal.to_bytes().expect("error encoding");
+    let view = MlsMessageView::parse(&proposal_bytes).expect("error parsing view");
+    assert_eq!(view.wire_format(), WireFormat::PublicMessage);
+    assert_eq!(view.epoch(), Some(alice_group.epoch()));
+    assert_eq!(
+        view.sender(),
+        Some(&Sender::build_member(alice_group.own_leaf_index()))
+    );
+    assert_eq!(view.content_type(), Some(ContentType::Proposal));
+    assert_canonical::<MlsMessageIn>(&proposal_bytes);
+    for queued_proposal in alice_group.pending_proposals() {
+        assert_canonical::<ProposalIn>(
+            &queued_proposal
+                .proposal()
+                .tls_serialize_detached()
+                .expect("error encoding proposal"),
+        );
+    }
+
+    // === Group state ===
+    let group_info = alice_group
+        .export_group_info(provider, &alice_signer, true)
+        .expect("error exporting group info");
+    // Streaming the group info produces the same bytes.
+    let mut streamed = Vec::new();
+    let written = group_info
+        .write_to(&mut streamed)
+        .expect("error streaming group info");
+    assert_eq!(written, streamed.len());
+    assert_eq!(group_info.serialized_len(), streamed.len());
+    assert_eq!(streamed, group_info.to_bytes().expect("error encoding"));
+    let group_info =
+        assert_canonical::<MlsMessageIn>(&group_info.to_bytes().expect("error encoding"));
+    match group_info.extract() {
+        MlsMessageInBody::GroupInfo(group_info) => {
+            assert_canonical::<VerifiableGroupInfo>(
+                &group_info
+                    .tls_serialize_detached()
+                    .expect("error encoding group info"),
+            );
+        }
+        _ => panic!("Expected a group info."),
+    }
+
+    assert_canonical::<GroupContext>(
+        &alice_group
+            .export_group_context()
+            .tls_serialize_detached()
+            .expect("error encoding group context"),
+    );
+
+    // === Ratchet tree ===
+    let ratchet_tree = alice_group.export_ratchet_tree();
+    assert!(ratchet_tree
+        .nodes()
+        .iter()
+        .any(|node| matches!(node, Some(Node::ParentNode(_)))));
+    assert_canonical::<RatchetTreeIn>(
+        &ratchet_tree
+            .tls_serialize_detached()
+            .expect("error encoding ratchet tree"),
+    );
+    for node in ratchet_tree.nodes().iter().flatten() {
+        let encoded = node.tls_serialize_detached().expect("error encoding node");
+        match node {
+            Node::LeafNode(_) => {
+                assert_canonical::<LeafNodeIn>(&encoded[1..]);
+            }
+            Node::ParentNode(_) => {
+                assert_canonical::<ParentNode>(&encoded[1..]);
+            }
+        }
+    }
+}
@@ -189,7 +189,7 @@ fn test_valsem242(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     alice_group.merge_pending_commit(provider).unwrap();
 
     let verifiable_group_info = alice_group
-        .export_group_info(provider.crypto(), &alice_credential.signer, true)
+        .export_group_info(provider, &alice_credential.signer, true)
         .unwrap()
         .into_verifiable_group_info()
         .unwrap();
@@ -340,7 +340,7 @@ fn test_valsem243(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
 
     // Have Alice export everything that bob needs.
     let verifiable_group_info = alice_group
-        .export_group_info(provider.crypto(), &alice_credential.signer, false)
+        .export_group_info(provider, &alice_credential.signer, false)
         .unwrap()
         .into_verifiable_group_info()
         .unwrap();
@@ -719,7 +719,7 @@ fn test_pure_ciphertest(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvide
 
     // Have Alice export everything that bob needs.
     let verifiable_group_info = alice_group
-        .export_group_info(provider.crypto(), &alice_credential.signer, true)
+        .export_group_info(provider, &alice_credential.signer, true)
         .unwrap()
         .into_verifiable_group_info()
         .unwrap();
@@ -801,7 +801,7 @@ mod utils {
 
         // Have Alice export everything that bob needs.
         let verifiable_group_info = alice_group
-            .export_group_info(provider.crypto(), &alice_credential.signer, false)
+            .export_group_info(provider, &alice_credential.signer, false)
             .unwrap()
             .into_verifiable_group_info()
             .unwrap();
@@ -82,7 +82,8 @@ fn validation_test_setup(
             &alice_credential.signer,
             &[bob_key_package.clone()],
         )
-        .expect("Could not add member.");
+        .expect("Could not add member.")
+        .into_parts();
 
     alice_group
         .merge_pending_commit(provider)
@@ -120,7 +121,8 @@ fn test_valsem002(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
 
     let (message, _welcome, _group_info) = alice_group
         .self_update(provider, &_alice_credential.signer)
-        .expect("Could not self-update.");
+        .expect("Could not self-update.")
+        .into_parts();
 
     let serialized_message = message
         .tls_serialize_detached()
@@ -167,7 +169,8 @@ fn test_valsem003(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     // Alice needs to create a new message that Bob can process.
     let (message, _welcome, _group_info) = alice_group
         .self_update(provider, &_alice_credential.signer)
-        .expect("Could not self update.");
+        .expect("Could not self update.")
+        .into_parts();
     alice_group.merge_pending_commit(provider).unwrap();
 
     alice_group
@@ -191,7 +194,8 @@ fn test_valsem003(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     // Do a second Commit to increase the epoch number
     let (message, _welcome, _group_info) = alice_group
         .self_update(provider, &_alice_credential.signer)
-        .expect("Could not add member.");
+        .expect("Could not add member.")
+        .into_parts();
 
     let current_epoch = alice_group.epoch();
 
@@ -260,7 +264,8 @@ fn test_valsem004(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
 
     let (message, _welcome, _group_info) = alice_group
         .self_update(provider, &_alice_credential.signer)
-        .expect("Could not self-update.");
+        .expect("Could not self-update.")
+        .into_parts();
 
     let serialized_message = message
         .tls_serialize_detached()
@@ -316,7 +321,8 @@ fn test_valsem005(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
 
     let (message, _welcome, _group_info) = alice_group
         .self_update(provider, &_alice_credential.signer)
-        .expect("Could not self-update.");
+        .expect("Could not self-update.")
+        .into_parts();
 
     let serialized_message = message
         .tls_serialize_detached()
@@ -419,7 +425,8 @@ fn test_valsem007(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
 
     let (message, _welcome, _group_info) = alice_group
         .self_update(provider, &_alice_credential.signer)
-        .expect("Could not self-update.");
+        .expect("Could not self-update.")
+        .into_parts();
 
     let serialized_message = message
         .tls_serialize_detached()
@@ -466,7 +473,8 @@ fn test_valsem008(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     // Alice needs to create a new message that Bob can process.
     let (message, _welcome, _group_info) = alice_group
         .self_update(provider, &_alice_credential.signer)
-        .expect("Could not self-update.");
+        .expect("Could not self-update.")
+        .into_parts();
 
     let serialized_message = message
         .tls_serialize_detached()
@@ -515,7 +523,8 @@ fn test_valsem009(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
 
     let (message, _welcome, _group_info) = alice_group
         .self_update(provider, &_alice_credential.signer)
-        .expect("Could not self-update.");
+        .expect("Could not self-update.")
+        .into_parts();
 
     let serialized_message = message
         .tls_serialize_detached()
@@ -571,7 +580,8 @@ fn test_valsem010(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     // Alice needs to create a new message that Bob can process.
     let (message, _welcome, _group_info) = alice_group
         .self_update(provider, &_alice_credential.signer)
-        .expect("Could not self update.");
+        .expect("Could not self update.")
+        .into_parts();
 
     let serialized_message = message
         .tls_serialize_detached()
@@ -0,0 +1,84 @@
+//! Tests for the invariant checks of the `check-invariants` feature.
+
+use openmls_rust_crypto::OpenMlsRustCrypto;
+use tests::utils::{generate_credential_with_key, generate_key_package};
+
+use crate::{
+    binary_tree::LeafNodeIndex,
+    framing::*,
+    group::{config::CryptoConfig, *},
+    test_utils::*,
+};
+
+#[test]
+#[should_panic(expected = "Violated invariants in `encrypt`")]
+fn corrupted_own_leaf_index() {
+    let provider = OpenMlsRustCrypto::default();
+    let ciphersuite = Ciphersuite::MLS_128_DHKEMX25519_AES128GCM_SHA256_Ed25519;
+    let framing_parameters = FramingParameters::new(&[], WireFormat::PublicMessage);
+
+    let alice_credential_with_keys = generate_credential_with_key(
+        b"Alice".to_vec(),
+        ciphersuite.signature_algorithm(),
+        &provider,
+    );
+    let bob_credential_with_keys = generate_credential_with_key(
+        b"Bob".to_vec(),
+        ciphersuite.signature_algorithm(),
+        &provider,
+    );
+    let bob_key_package = generate_key_package(
+        ciphersuite,
+        Extensions::empty(),
+        &provider,
+        bob_credential_with_keys,
+    );
+
+    // Alice creates a group and adds Bob. All checks pass.
+    let mut group_alice = CoreGroup::builder(
+        GroupId::random(provider.rand()),
+        CryptoConfig::with_default_version(ciphersuite),
+        alice_credential_with_keys.credential_with_key,
+    )
+    .build(&provider, &alice_credential_with_keys.signer)
+    .expect("Error creating CoreGroup.");
+    let bob_add_proposal = group_alice
+        .create_add_proposal(
+            framing_parameters,
+            bob_key_package,
+            &alice_credential_with_keys.signer,
+        )
+        .expect("Could not create proposal.");
+    let mut proposal_store = ProposalStore::from_queued_proposal(
+        QueuedProposal::from_authenticated_content_by_ref(
+            ciphersuite,
+            provider.crypto(),
+            bob_add_proposal,
+        )
+        .expect("Could not create QueuedProposal."),
+    );
+    let params = CreateCommitParams::builder()
+        .framing_parameters(framing_parameters)
+        .proposal_store(&proposal_store)
+        .build();
+    let create_commit_result = group_alice
+        .create_commit(params, &provider, &alice_credential_with_keys.signer)
+        .expect("Error creating commit");
+    group_alice
+        .merge_staged_commit(
+            &provider,
+            create_commit_result.staged_commit,
+            &mut proposal_store,
+        )
+        .expect("Error merging commit");
+
+    // The own leaf index no longer matches the secret tree.
+    group_alice.set_own_leaf_index(LeafNodeIndex::new(1));
+    let _ = group_alice.create_application_message(
+        &[],
+        b"Hello Bob",
+        0,
+        &provider,
+        &alice_credential_with_keys.signer,
+    );
+}
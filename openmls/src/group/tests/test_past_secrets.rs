@@ -62,7 +62,8 @@ fn test_past_secrets_in_group(ciphersuite: Ciphersuite, provider: &impl OpenMlsP
                 &alice_credential_with_keys.signer,
                 &[bob_key_package],
             )
-            .expect("An unexpected error occurred.");
+            .expect("An unexpected error occurred.")
+            .into_parts();
 
         alice_group
             .merge_pending_commit(provider)
@@ -90,7 +91,8 @@ fn test_past_secrets_in_group(ciphersuite: Ciphersuite, provider: &impl OpenMlsP
 
             let (message, _welcome, _group_info) = alice_group
                 .self_update(provider, &alice_credential_with_keys.signer)
-                .expect("An unexpected error occurred.");
+                .expect("An unexpected error occurred.")
+                .into_parts();
 
             update_commits.push(message.clone());
 
@@ -150,7 +150,8 @@ fn validation_test_setup(
             &alice_credential_with_key_and_signer.signer,
             &[bob_key_package],
         )
-        .unwrap();
+        .unwrap()
+        .into_parts();
 
     alice_group.merge_pending_commit(provider).unwrap();
 
@@ -297,7 +298,7 @@ fn test_valsem101a(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
                 assert_eq!(
                     err,
                     AddMembersError::CreateCommitError(CreateCommitError::ProposalValidationError(
-                        ProposalValidationError::DuplicateSignatureKey
+                        ProposalValidationError::DuplicateSignatureKey(None)
                     ))
                 );
             }
@@ -332,6 +333,7 @@ fn test_valsem101a(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
             &[charlie_key_package],
         )
         .expect("Error creating self-update")
+        .commit()
         .tls_serialize_detached()
         .expect("Could not serialize message.");
 
@@ -385,7 +387,7 @@ fn test_valsem101a(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     assert_eq!(
         err,
         ProcessMessageError::InvalidCommit(StageCommitError::ProposalValidationError(
-            ProposalValidationError::DuplicateSignatureKey
+            ProposalValidationError::DuplicateSignatureKey(None)
         ))
     );
 
@@ -490,6 +492,7 @@ fn test_valsem102(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
             &[charlie_key_package.clone()],
         )
         .expect("Error creating self-update")
+        .commit()
         .tls_serialize_detached()
         .expect("Could not serialize message.");
 
@@ -631,7 +634,7 @@ fn test_valsem101b(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
                 assert_eq!(
                     err,
                     AddMembersError::CreateCommitError(CreateCommitError::ProposalValidationError(
-                        ProposalValidationError::DuplicateSignatureKey
+                        ProposalValidationError::DuplicateSignatureKey(Some(LeafNodeIndex::new(0)))
                     ))
                 );
             }
@@ -694,6 +697,7 @@ fn test_valsem101b(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
         let serialized_update = alice_group
             .self_update(provider)
             .expect("Error creating self-update")
+            .commit()
             .tls_serialize_detached()
             .expect("Could not serialize message.");
 
@@ -887,6 +891,7 @@ fn test_valsem103_valsem104(ciphersuite: Ciphersuite, provider: &impl OpenMlsPro
     let serialized_update = alice_group
         .self_update(provider, &alice_credential_with_key_and_signer.signer)
         .expect("Error creating self-update")
+        .commit()
         .tls_serialize_detached()
         .expect("Could not serialize message.");
 
@@ -952,7 +957,7 @@ fn test_valsem103_valsem104(ciphersuite: Ciphersuite, provider: &impl OpenMlsPro
     assert_eq!(
         err,
         ProcessMessageError::InvalidCommit(StageCommitError::ProposalValidationError(
-            ProposalValidationError::DuplicateEncryptionKey
+            ProposalValidationError::DuplicateEncryptionKey(Some(LeafNodeIndex::new(1)))
         ))
     );
 
@@ -1032,7 +1037,7 @@ fn test_valsem105(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
             generate_credential_with_key_and_key_package("Charlie".into(), ciphersuite, provider);
 
         let kpi = KeyPackageIn::from(charlie_key_package.clone());
-        kpi.validate(provider.crypto(), ProtocolVersion::Mls10)
+        kpi.validate(provider.crypto(), ProtocolVersion::Mls10, provider.clock())
             .unwrap();
 
         // Let's just pick a ciphersuite that's not the one we're testing right now.
@@ -1173,6 +1178,7 @@ fn test_valsem105(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
         let serialized_update = alice_group
             .self_update(provider, &alice_credential_with_key_and_signer.signer)
             .unwrap()
+            .commit()
             .tls_serialize_detached()
             .unwrap();
 
@@ -1387,7 +1393,8 @@ fn test_valsem107(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     // that contains only one remove proposal.
     let (commit_ref_remove, _welcome, _group_info) = alice_group
         .commit_to_pending_proposals(provider, &alice_credential_with_key_and_signer.signer)
-        .expect("error while trying to commit to colliding remove proposals");
+        .expect("error while trying to commit to colliding remove proposals")
+        .into_parts();
 
     // Clear commit to try another way of committing two identical removes.
     alice_group.clear_pending_commit();
@@ -1399,7 +1406,8 @@ fn test_valsem107(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
             &alice_credential_with_key_and_signer.signer,
             &[bob_leaf_index, bob_leaf_index],
         )
-        .expect("error while trying to remove the same member twice");
+        .expect("error while trying to remove the same member twice")
+        .into_parts();
 
     // Check commit with referenced remove proposals.
     {
@@ -1536,6 +1544,7 @@ fn test_valsem108(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     let serialized_update = alice_group
         .self_update(provider, &alice_credential_with_key_and_signer.signer)
         .expect("Error creating self-update")
+        .commit()
         .tls_serialize_detached()
         .expect("Could not serialize message.");
 
@@ -1663,7 +1672,7 @@ fn test_valsem110(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
         err,
         CommitToPendingProposalsError::CreateCommitError(
             CreateCommitError::ProposalValidationError(
-                ProposalValidationError::DuplicateEncryptionKey
+                ProposalValidationError::DuplicateEncryptionKey(Some(LeafNodeIndex::new(0)))
             )
         )
     );
@@ -1680,6 +1689,7 @@ fn test_valsem110(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     let serialized_update = alice_group
         .self_update(provider, &alice_credential_with_key_and_signer.signer)
         .expect("Error creating self-update")
+        .commit()
         .tls_serialize_detached()
         .expect("Could not serialize message.");
 
@@ -1771,6 +1781,7 @@ fn test_valsem111(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
 
     // Check that there's no proposal in it.
     let serialized_message = commit
+        .commit()
         .tls_serialize_detached()
         .expect("error serializing plaintext");
 
@@ -1789,6 +1800,7 @@ fn test_valsem111(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     assert_eq!(commit_content.proposals.len(), 0);
 
     let serialized_update = commit
+        .commit()
         .tls_serialize_detached()
         .expect("Could not serialize message.");
 
@@ -1845,6 +1857,7 @@ fn test_valsem111(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
         .expect("Error creating self-update");
 
     let serialized_update = commit
+        .commit()
         .tls_serialize_detached()
         .expect("Could not serialize message.");
 
@@ -1920,6 +1933,7 @@ fn test_valsem112(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
 
     // Check that the sender type is indeed `member`.
     let serialized_update = commit
+        .commit()
         .tls_serialize_detached()
         .expect("error serializing plaintext");
 
@@ -2075,7 +2089,8 @@ fn test_valsem401_valsem402(ciphersuite: Ciphersuite, provider: &impl OpenMlsPro
                 &alice_provider,
                 &alice_credential_with_key_and_signer.signer,
             )
-            .unwrap();
+            .unwrap()
+            .into_parts();
 
         alice_group.clear_pending_proposals();
         alice_group.clear_pending_commit();
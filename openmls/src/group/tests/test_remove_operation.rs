@@ -83,7 +83,8 @@ fn test_remove_operation_variants(ciphersuite: Ciphersuite, provider: &impl Open
                 &alice_credential_with_key_and_signer.signer,
                 &[bob_key_package, charlie_key_package],
             )
-            .expect("An unexpected error occurred.");
+            .expect("An unexpected error occurred.")
+            .into_parts();
         alice_group
             .merge_pending_commit(&alice_provider)
             .expect("error merging pending commit");
@@ -153,7 +154,8 @@ fn test_remove_operation_variants(ciphersuite: Ciphersuite, provider: &impl Open
                     )
                     .expect("An unexpected error occurred.")
             }
-        };
+        }
+        .into_parts();
 
         // === Remove operation from Alice's perspective ===
 
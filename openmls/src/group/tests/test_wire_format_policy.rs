@@ -68,7 +68,8 @@ fn receive_message(
 
     let (_message, welcome, _group_info) = alice_group
         .add_members(provider, alice_signer, &[bob_key_package])
-        .expect("Could not add member.");
+        .expect("Could not add member.")
+        .into_parts();
 
     alice_group
         .merge_pending_commit(provider)
@@ -89,7 +90,8 @@ fn receive_message(
 
     let (message, _welcome, _group_info) = bob_group
         .self_update(provider, &bob_credential_with_key_and_signer.signer)
-        .expect("An unexpected error occurred.");
+        .expect("An unexpected error occurred.")
+        .into_parts();
     message.into()
 }
 
@@ -0,0 +1,267 @@
+//! # Comparison of public group state
+//!
+//! When two MLS implementations disagree about the state of a group, all that
+//! is usually visible is a failing confirmation tag or signature. This module
+//! compares the public state of a group as exported by OpenMLS with a
+//! [`PublicStateDump`] produced by another implementation and reports the first
+//! field in which they diverge.
+//!
+//! Fields are compared in the following order: the protocol version, the
+//! ciphersuite, the group ID, the epoch, the nodes of the ratchet tree (if both
+//! dumps contain one), the tree hash, the confirmed and interim transcript
+//! hashes and finally the group context extensions. The nodes of the tree are
+//! compared before the tree hash, so that a divergent tree hash is attributed
+//! to the node that causes it.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::{ExtensionDump, GroupContextDump, HexBytes, Inspect, NodeDump, RatchetTreeDump};
+use crate::{error::LibraryError, group::public_group::PublicGroup};
+
+/// The public state of a group that is compared across implementations.
+///
+/// Other implementations have to provide at least the group context and the
+/// interim transcript hash. The ratchet tree is optional.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublicStateDump {
+    /// The group context of the current epoch.
+    pub group_context: GroupContextDump,
+    /// The interim transcript hash of the current epoch.
+    pub interim_transcript_hash: HexBytes,
+    /// The ratchet tree of the current epoch.
+    #[serde(default)]
+    pub ratchet_tree: Option<RatchetTreeDump>,
+}
+
+impl Inspect for PublicGroup {
+    type Dump = PublicStateDump;
+
+    fn dump(&self) -> Result<Self::Dump, LibraryError> {
+        Ok(PublicStateDump {
+            group_context: self.group_context().dump()?,
+            interim_transcript_hash: self.interim_transcript_hash().into(),
+            ratchet_tree: Some(self.export_ratchet_tree().dump()?),
+        })
+    }
+}
+
+/// The first field in which two [`PublicStateDump`]s diverge.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    /// The path of the divergent field, e.g. `ratchet_tree.nodes[2].signature_key`.
+    pub field: String,
+    /// The value of the field in our state.
+    pub ours: String,
+    /// The value of the field in their state.
+    pub theirs: String,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} diverges: ours is {}, theirs is {}",
+            self.field, self.ours, self.theirs
+        )
+    }
+}
+
+impl PublicStateDump {
+    /// Compare this state (ours) with the state of another implementation
+    /// (theirs) and return the first field in which they diverge or `None` if
+    /// the states are equivalent.
+    pub fn first_divergence(&self, theirs: &Self) -> Option<Divergence> {
+        let (ours_context, theirs_context) = (&self.group_context, &theirs.group_context);
+        compare(
+            "group_context.protocol_version",
+            ours_context.protocol_version,
+            theirs_context.protocol_version,
+        )
+        .or_else(|| {
+            compare(
+                "group_context.ciphersuite",
+                ours_context.ciphersuite,
+                theirs_context.ciphersuite,
+            )
+        })
+        .or_else(|| {
+            compare(
+                "group_context.group_id",
+                &ours_context.group_id,
+                &theirs_context.group_id,
+            )
+        })
+        .or_else(|| {
+            compare(
+                "group_context.epoch",
+                ours_context.epoch,
+                theirs_context.epoch,
+            )
+        })
+        .or_else(|| match (&self.ratchet_tree, &theirs.ratchet_tree) {
+            (Some(ours_tree), Some(theirs_tree)) => compare_trees(ours_tree, theirs_tree),
+            _ => None,
+        })
+        .or_else(|| {
+            compare(
+                "group_context.tree_hash",
+                &ours_context.tree_hash,
+                &theirs_context.tree_hash,
+            )
+        })
+        .or_else(|| {
+            compare(
+                "group_context.confirmed_transcript_hash",
+                &ours_context.confirmed_transcript_hash,
+                &theirs_context.confirmed_transcript_hash,
+            )
+        })
+        .or_else(|| {
+            compare(
+                "interim_transcript_hash",
+                &self.interim_transcript_hash,
+                &theirs.interim_transcript_hash,
+            )
+        })
+        .or_else(|| {
+            compare_extensions(
+                "group_context.extensions",
+                &ours_context.extensions,
+                &theirs_context.extensions,
+            )
+        })
+    }
+}
+
+fn compare<T: PartialEq + fmt::Display>(field: &str, ours: T, theirs: T) -> Option<Divergence> {
+    (ours != theirs).then(|| Divergence {
+        field: field.to_string(),
+        ours: ours.to_string(),
+        theirs: theirs.to_string(),
+    })
+}
+
+/// Like [`compare()`], but for values without a [`fmt::Display`]
+/// implementation.
+fn compare_debug<T: PartialEq + fmt::Debug>(field: &str, ours: T, theirs: T) -> Option<Divergence> {
+    (ours != theirs).then(|| Divergence {
+        field: field.to_string(),
+        ours: format!("{ours:?}"),
+        theirs: format!("{theirs:?}"),
+    })
+}
+
+fn compare_extensions(
+    field: &str,
+    ours: &[ExtensionDump],
+    theirs: &[ExtensionDump],
+) -> Option<Divergence> {
+    ours.iter()
+        .zip(theirs)
+        .enumerate()
+        .find_map(|(i, (ours, theirs))| {
+            compare(
+                &format!("{field}[{i}].extension_type"),
+                ours.extension_type,
+                theirs.extension_type,
+            )
+            .or_else(|| {
+                compare(
+                    &format!("{field}[{i}].extension_data"),
+                    &ours.extension_data,
+                    &theirs.extension_data,
+                )
+            })
+        })
+        .or_else(|| compare(&format!("{field}.len"), ours.len(), theirs.len()))
+}
+
+fn compare_trees(ours: &RatchetTreeDump, theirs: &RatchetTreeDump) -> Option<Divergence> {
+    ours.nodes
+        .iter()
+        .zip(&theirs.nodes)
+        .enumerate()
+        .find_map(|(i, (ours, theirs))| {
+            let field = format!("ratchet_tree.nodes[{i}]");
+            match (ours, theirs) {
+                (None, None) => None,
+                (Some(NodeDump::Leaf(ours)), Some(NodeDump::Leaf(theirs))) => compare(
+                    &format!("{field}.encryption_key"),
+                    &ours.encryption_key,
+                    &theirs.encryption_key,
+                )
+                .or_else(|| {
+                    compare(
+                        &format!("{field}.signature_key"),
+                        &ours.signature_key,
+                        &theirs.signature_key,
+                    )
+                })
+                .or_else(|| {
+                    compare_debug(
+                        &format!("{field}.credential"),
+                        &ours.credential,
+                        &theirs.credential,
+                    )
+                })
+                .or_else(|| {
+                    compare_debug(
+                        &format!("{field}.capabilities"),
+                        &ours.capabilities,
+                        &theirs.capabilities,
+                    )
+                })
+                .or_else(|| {
+                    compare_debug(
+                        &format!("{field}.leaf_node_source"),
+                        &ours.leaf_node_source,
+                        &theirs.leaf_node_source,
+                    )
+                })
+                .or_else(|| {
+                    compare_extensions(
+                        &format!("{field}.extensions"),
+                        &ours.extensions,
+                        &theirs.extensions,
+                    )
+                })
+                .or_else(|| {
+                    compare(
+                        &format!("{field}.signature"),
+                        &ours.signature,
+                        &theirs.signature,
+                    )
+                }),
+                (Some(NodeDump::Parent(ours)), Some(NodeDump::Parent(theirs))) => compare(
+                    &format!("{field}.encryption_key"),
+                    &ours.encryption_key,
+                    &theirs.encryption_key,
+                )
+                .or_else(|| {
+                    compare(
+                        &format!("{field}.parent_hash"),
+                        &ours.parent_hash,
+                        &theirs.parent_hash,
+                    )
+                })
+                .or_else(|| {
+                    compare_debug(
+                        &format!("{field}.unmerged_leaves"),
+                        &ours.unmerged_leaves,
+                        &theirs.unmerged_leaves,
+                    )
+                }),
+                // One of the nodes is blank.
+                _ => compare_debug(&field, ours, theirs),
+            }
+        })
+        .or_else(|| {
+            compare(
+                "ratchet_tree.nodes.len",
+                ours.nodes.len(),
+                theirs.nodes.len(),
+            )
+        })
+}
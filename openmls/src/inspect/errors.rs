@@ -0,0 +1,22 @@
+//! # Inspection errors
+//!
+//! This module contains errors that can occur when encoding public group state
+//! for inspection or decoding it again.
+
+use thiserror::Error;
+
+use crate::error::LibraryError;
+
+/// Inspection error
+#[derive(Error, Debug, PartialEq, Clone)]
+pub enum InspectError {
+    /// See [`LibraryError`] for more details.
+    #[error(transparent)]
+    LibraryError(#[from] LibraryError),
+    /// The plain data representation could not be encoded.
+    #[error("The plain data representation could not be encoded: {0}")]
+    EncodingError(String),
+    /// The plain data representation could not be decoded.
+    #[error("The plain data representation could not be decoded: {0}")]
+    DecodingError(String),
+}
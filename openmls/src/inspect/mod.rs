@@ -0,0 +1,737 @@
+//! # Inspection of public group state
+//!
+//! This module contains plain data representations of the public MLS
+//! structures [`GroupContext`], [`RatchetTree`], [`GroupInfo`] and
+//! [`Proposal`]. They are meant for debugging tools, dashboards and services
+//! written in other languages that want to look at the state of a group without
+//! having to implement a TLS decoder.
+//!
+//! The representations are obtained through the [`Inspect`] trait. Field names
+//! are stable. Byte strings are encoded as lower-case hex strings and values
+//! from the MLS registries (protocol versions, ciphersuites, extension types,
+//! ...) as their numeric code points.
+//!
+//! The `inspect-json` feature enables a canonical JSON encoding via
+//! `Inspect::to_json()` and the `inspect-cbor` feature a CBOR encoding via
+//! `Inspect::to_cbor()`. Both encodings write fields in declaration order.
+//!
+//! [`MlsGroup::debug_dump()`](crate::group::MlsGroup::debug_dump()) describes the complete public state of a group,
+//! including pending proposals and commits, without any secrets. Such a
+//! [`GroupDump`] can be attached to bug reports and loaded into test harnesses
+//! with `GroupDump::from_json()` or `GroupDump::from_cbor()`.
+//!
+//! To debug interoperability issues, the public state of a group can be
+//! compared with the state of another implementation. See
+//! [`PublicStateDump::first_divergence()`].
+//!
+//! Note that the representations are for inspection only. They can't be
+//! converted back into the original structures.
+
+use std::fmt;
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use tls_codec::{Deserialize as TlsDeserializeTrait, Serialize as TlsSerializeTrait, VLBytes};
+
+use crate::{
+    credentials::Credential,
+    error::LibraryError,
+    extensions::{Extension, Extensions},
+    framing::Sender,
+    group::{GroupContext, QueuedProposal, StagedCommit},
+    messages::{group_info::GroupInfo, proposals::Proposal},
+    treesync::{
+        node::{
+            leaf_node::{Capabilities, LeafNodeSource},
+            parent_node::ParentNode,
+            Node,
+        },
+        LeafNode, RatchetTree,
+    },
+};
+
+mod compare;
+pub mod errors;
+
+#[cfg(test)]
+mod test_inspect;
+
+pub use compare::*;
+
+/// Types that have a plain data representation for inspection.
+pub trait Inspect {
+    /// The plain data representation of this type.
+    type Dump: Serialize;
+
+    /// Returns the plain data representation of `self`.
+    fn dump(&self) -> Result<Self::Dump, LibraryError>;
+
+    /// Returns the canonical JSON encoding of `self`.
+    #[cfg(feature = "inspect-json")]
+    fn to_json(&self) -> Result<String, errors::InspectError> {
+        serde_json::to_string(&self.dump()?)
+            .map_err(|e| errors::InspectError::EncodingError(e.to_string()))
+    }
+
+    /// Returns the CBOR encoding of `self`.
+    #[cfg(feature = "inspect-cbor")]
+    fn to_cbor(&self) -> Result<Vec<u8>, errors::InspectError> {
+        let mut buffer = Vec::new();
+        ciborium::ser::into_writer(&self.dump()?, &mut buffer)
+            .map_err(|e| errors::InspectError::EncodingError(e.to_string()))?;
+        Ok(buffer)
+    }
+}
+
+/// A byte string that is encoded as lower-case hex string.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct HexBytes(Vec<u8>);
+
+impl HexBytes {
+    /// Returns the bytes as slice.
+    pub fn as_slice(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl From<&[u8]> for HexBytes {
+    fn from(bytes: &[u8]) -> Self {
+        Self(bytes.to_vec())
+    }
+}
+
+impl From<Vec<u8>> for HexBytes {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self(bytes)
+    }
+}
+
+impl fmt::Display for HexBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in &self.0 {
+            write!(f, "{byte:02x}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Serialize for HexBytes {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for HexBytes {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let hex = String::deserialize(deserializer)?;
+        if hex.len() % 2 != 0 {
+            return Err(de::Error::custom("odd number of hex digits"));
+        }
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| {
+                hex.get(i..i + 2)
+                    .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+                    .ok_or_else(|| de::Error::custom("invalid hex digit"))
+            })
+            .collect::<Result<Vec<u8>, _>>()
+            .map(Self)
+    }
+}
+
+/// Plain data representation of an [`Extension`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExtensionDump {
+    /// The extension type code point.
+    pub extension_type: u16,
+    /// The TLS encoded extension data.
+    pub extension_data: HexBytes,
+}
+
+impl Inspect for Extension {
+    type Dump = ExtensionDump;
+
+    fn dump(&self) -> Result<Self::Dump, LibraryError> {
+        // The serialized extension is the extension type followed by the
+        // variable-length extension data.
+        let serialized = self
+            .tls_serialize_detached()
+            .map_err(LibraryError::missing_bound_check)?;
+        let extension_data = VLBytes::tls_deserialize(&mut serialized.get(2..).unwrap_or_default())
+            .map_err(LibraryError::missing_bound_check)?;
+        Ok(ExtensionDump {
+            extension_type: self.extension_type().into(),
+            extension_data: extension_data.as_slice().into(),
+        })
+    }
+}
+
+impl Inspect for Extensions {
+    type Dump = Vec<ExtensionDump>;
+
+    fn dump(&self) -> Result<Self::Dump, LibraryError> {
+        self.iter().map(Inspect::dump).collect()
+    }
+}
+
+/// Plain data representation of a [`GroupContext`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GroupContextDump {
+    /// The protocol version code point.
+    pub protocol_version: u16,
+    /// The ciphersuite code point.
+    pub ciphersuite: u16,
+    /// The group ID.
+    pub group_id: HexBytes,
+    /// The epoch.
+    pub epoch: u64,
+    /// The tree hash.
+    pub tree_hash: HexBytes,
+    /// The confirmed transcript hash.
+    pub confirmed_transcript_hash: HexBytes,
+    /// The group context extensions.
+    pub extensions: Vec<ExtensionDump>,
+}
+
+impl Inspect for GroupContext {
+    type Dump = GroupContextDump;
+
+    fn dump(&self) -> Result<Self::Dump, LibraryError> {
+        Ok(GroupContextDump {
+            protocol_version: self.protocol_version() as u16,
+            ciphersuite: self.ciphersuite().into(),
+            group_id: self.group_id().as_slice().into(),
+            epoch: self.epoch().as_u64(),
+            tree_hash: self.tree_hash().into(),
+            confirmed_transcript_hash: self.confirmed_transcript_hash().into(),
+            extensions: self.extensions().dump()?,
+        })
+    }
+}
+
+/// Plain data representation of a [`Credential`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CredentialDump {
+    /// The credential type code point.
+    pub credential_type: u16,
+    /// The identity.
+    pub identity: HexBytes,
+}
+
+impl From<&Credential> for CredentialDump {
+    fn from(credential: &Credential) -> Self {
+        Self {
+            credential_type: credential.credential_type().into(),
+            identity: credential.identity().into(),
+        }
+    }
+}
+
+/// Plain data representation of [`Capabilities`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CapabilitiesDump {
+    /// The supported protocol versions.
+    pub versions: Vec<u16>,
+    /// The supported ciphersuites.
+    pub ciphersuites: Vec<u16>,
+    /// The supported extension types.
+    pub extensions: Vec<u16>,
+    /// The supported proposal types.
+    pub proposals: Vec<u16>,
+    /// The supported credential types.
+    pub credentials: Vec<u16>,
+}
+
+impl From<&Capabilities> for CapabilitiesDump {
+    fn from(capabilities: &Capabilities) -> Self {
+        Self {
+            versions: capabilities
+                .versions()
+                .iter()
+                .map(|&version| version as u16)
+                .collect(),
+            ciphersuites: capabilities
+                .ciphersuites()
+                .iter()
+                .map(|&ciphersuite| ciphersuite.into())
+                .collect(),
+            extensions: capabilities
+                .extensions()
+                .iter()
+                .map(|&extension_type| extension_type.into())
+                .collect(),
+            proposals: capabilities
+                .proposals()
+                .iter()
+                .map(|&proposal_type| proposal_type.into())
+                .collect(),
+            credentials: capabilities
+                .credentials()
+                .iter()
+                .map(|&credential_type| credential_type.into())
+                .collect(),
+        }
+    }
+}
+
+/// Plain data representation of the source of a [`LeafNode`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LeafNodeSourceDump {
+    /// The leaf node is part of a key package.
+    KeyPackage {
+        /// Start of the lifetime in seconds since the Unix epoch.
+        not_before: u64,
+        /// End of the lifetime in seconds since the Unix epoch.
+        not_after: u64,
+    },
+    /// The leaf node was sent in an update proposal.
+    Update,
+    /// The leaf node was sent in a commit.
+    Commit {
+        /// The parent hash.
+        parent_hash: HexBytes,
+    },
+}
+
+impl From<&LeafNodeSource> for LeafNodeSourceDump {
+    fn from(leaf_node_source: &LeafNodeSource) -> Self {
+        match leaf_node_source {
+            LeafNodeSource::KeyPackage(lifetime) => Self::KeyPackage {
+                not_before: lifetime.not_before(),
+                not_after: lifetime.not_after(),
+            },
+            LeafNodeSource::Update => Self::Update,
+            LeafNodeSource::Commit(parent_hash) => Self::Commit {
+                parent_hash: parent_hash.as_slice().into(),
+            },
+        }
+    }
+}
+
+/// Plain data representation of a [`LeafNode`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LeafNodeDump {
+    /// The HPKE encryption key.
+    pub encryption_key: HexBytes,
+    /// The signature public key.
+    pub signature_key: HexBytes,
+    /// The credential.
+    pub credential: CredentialDump,
+    /// The capabilities.
+    pub capabilities: CapabilitiesDump,
+    /// The leaf node source.
+    pub leaf_node_source: LeafNodeSourceDump,
+    /// The leaf node extensions.
+    pub extensions: Vec<ExtensionDump>,
+    /// The signature.
+    pub signature: HexBytes,
+}
+
+impl Inspect for LeafNode {
+    type Dump = LeafNodeDump;
+
+    fn dump(&self) -> Result<Self::Dump, LibraryError> {
+        Ok(LeafNodeDump {
+            encryption_key: self.encryption_key().as_slice().into(),
+            signature_key: self.signature_key().as_slice().into(),
+            credential: self.credential().into(),
+            capabilities: self.capabilities().into(),
+            leaf_node_source: self.leaf_node_source().into(),
+            extensions: self.extensions().dump()?,
+            signature: self.signature().value().into(),
+        })
+    }
+}
+
+/// Plain data representation of a [`ParentNode`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParentNodeDump {
+    /// The HPKE encryption key.
+    pub encryption_key: HexBytes,
+    /// The parent hash.
+    pub parent_hash: HexBytes,
+    /// The indices of the unmerged leaves.
+    pub unmerged_leaves: Vec<u32>,
+}
+
+impl From<&ParentNode> for ParentNodeDump {
+    fn from(parent_node: &ParentNode) -> Self {
+        Self {
+            encryption_key: parent_node.encryption_key().as_slice().into(),
+            parent_hash: parent_node.parent_hash().into(),
+            unmerged_leaves: parent_node
+                .unmerged_leaves()
+                .iter()
+                .map(|leaf_index| leaf_index.u32())
+                .collect(),
+        }
+    }
+}
+
+/// Plain data representation of a [`Node`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeDump {
+    /// A leaf node.
+    Leaf(LeafNodeDump),
+    /// A parent node.
+    Parent(ParentNodeDump),
+}
+
+impl Inspect for Node {
+    type Dump = NodeDump;
+
+    fn dump(&self) -> Result<Self::Dump, LibraryError> {
+        Ok(match self {
+            Node::LeafNode(leaf_node) => NodeDump::Leaf(leaf_node.dump()?),
+            Node::ParentNode(parent_node) => NodeDump::Parent(parent_node.into()),
+        })
+    }
+}
+
+/// Plain data representation of a [`RatchetTree`].
+///
+/// The nodes are in array representation, i.e., leaves are at even and parent
+/// nodes at odd positions. Blank nodes are `None`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RatchetTreeDump {
+    /// The nodes of the tree.
+    pub nodes: Vec<Option<NodeDump>>,
+}
+
+impl Inspect for RatchetTree {
+    type Dump = RatchetTreeDump;
+
+    fn dump(&self) -> Result<Self::Dump, LibraryError> {
+        let nodes = self
+            .nodes()
+            .iter()
+            .map(|node| node.as_ref().map(Inspect::dump).transpose())
+            .collect::<Result<_, _>>()?;
+        Ok(RatchetTreeDump { nodes })
+    }
+}
+
+/// Plain data representation of a [`GroupInfo`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GroupInfoDump {
+    /// The group context.
+    pub group_context: GroupContextDump,
+    /// The group info extensions.
+    pub extensions: Vec<ExtensionDump>,
+    /// The confirmation tag.
+    pub confirmation_tag: HexBytes,
+    /// The leaf index of the signer.
+    pub signer: u32,
+    /// The signature.
+    pub signature: HexBytes,
+}
+
+impl Inspect for GroupInfo {
+    type Dump = GroupInfoDump;
+
+    fn dump(&self) -> Result<Self::Dump, LibraryError> {
+        Ok(GroupInfoDump {
+            group_context: self.group_context().dump()?,
+            extensions: self.extensions().dump()?,
+            confirmation_tag: self.confirmation_tag().0.mac_value.as_slice().into(),
+            signer: self.signer().u32(),
+            signature: self.signature().value().into(),
+        })
+    }
+}
+
+/// Plain data representation of a [`Proposal`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProposalDump {
+    /// An add proposal.
+    Add {
+        /// The protocol version code point of the key package.
+        protocol_version: u16,
+        /// The ciphersuite code point of the key package.
+        ciphersuite: u16,
+        /// The HPKE init key of the key package.
+        init_key: HexBytes,
+        /// The leaf node of the key package.
+        leaf_node: LeafNodeDump,
+        /// The key package extensions.
+        extensions: Vec<ExtensionDump>,
+    },
+    /// An update proposal.
+    Update {
+        /// The new leaf node.
+        leaf_node: LeafNodeDump,
+    },
+    /// A remove proposal.
+    Remove {
+        /// The leaf index of the removed member.
+        removed: u32,
+    },
+    /// A pre-shared key proposal.
+    PreSharedKey {
+        /// The TLS encoded pre-shared key ID.
+        psk_id: HexBytes,
+    },
+    /// A re-init proposal.
+    ReInit {
+        /// The ID of the new group.
+        group_id: HexBytes,
+        /// The protocol version code point of the new group.
+        protocol_version: u16,
+        /// The ciphersuite code point of the new group.
+        ciphersuite: u16,
+        /// The extensions of the new group.
+        extensions: Vec<ExtensionDump>,
+    },
+    /// An external init proposal.
+    ExternalInit {
+        /// The KEM output.
+        kem_output: HexBytes,
+    },
+    /// A group context extensions proposal.
+    GroupContextExtensions {
+        /// The new group context extensions.
+        extensions: Vec<ExtensionDump>,
+    },
+    /// An application acknowledgement proposal.
+    AppAck {
+        /// The TLS encoded proposal content.
+        content: HexBytes,
+    },
+}
+
+impl Inspect for Proposal {
+    type Dump = ProposalDump;
+
+    fn dump(&self) -> Result<Self::Dump, LibraryError> {
+        Ok(match self {
+            Proposal::Add(add_proposal) => {
+                let key_package = add_proposal.key_package();
+                ProposalDump::Add {
+                    protocol_version: key_package.protocol_version() as u16,
+                    ciphersuite: key_package.ciphersuite().into(),
+                    init_key: key_package.hpke_init_key().as_slice().into(),
+                    leaf_node: key_package.leaf_node().dump()?,
+                    extensions: key_package.extensions().dump()?,
+                }
+            }
+            Proposal::Update(update_proposal) => ProposalDump::Update {
+                leaf_node: update_proposal.leaf_node().dump()?,
+            },
+            Proposal::Remove(remove_proposal) => ProposalDump::Remove {
+                removed: remove_proposal.removed().u32(),
+            },
+            Proposal::PreSharedKey(psk_proposal) => ProposalDump::PreSharedKey {
+                psk_id: psk_proposal
+                    .tls_serialize_detached()
+                    .map_err(LibraryError::missing_bound_check)?
+                    .into(),
+            },
+            Proposal::ReInit(reinit_proposal) => ProposalDump::ReInit {
+                group_id: reinit_proposal.group_id.as_slice().into(),
+                protocol_version: reinit_proposal.version as u16,
+                ciphersuite: reinit_proposal.ciphersuite.into(),
+                extensions: reinit_proposal.extensions.dump()?,
+            },
+            Proposal::ExternalInit(external_init_proposal) => ProposalDump::ExternalInit {
+                kem_output: external_init_proposal.kem_output().into(),
+            },
+            Proposal::GroupContextExtensions(gce_proposal) => {
+                ProposalDump::GroupContextExtensions {
+                    extensions: gce_proposal.extensions().dump()?,
+                }
+            }
+            Proposal::AppAck(app_ack_proposal) => ProposalDump::AppAck {
+                content: app_ack_proposal
+                    .tls_serialize_detached()
+                    .map_err(LibraryError::missing_bound_check)?
+                    .into(),
+            },
+        })
+    }
+}
+
+/// Plain data representation of a [`Sender`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SenderDump {
+    /// A member of the group.
+    Member {
+        /// The leaf index of the member.
+        leaf_index: u32,
+    },
+    /// An external sender.
+    External {
+        /// The index in the external senders extension.
+        sender_index: u32,
+    },
+    /// A new member that joins through an external add proposal.
+    NewMemberProposal,
+    /// A new member that joins through an external commit.
+    NewMemberCommit,
+}
+
+impl From<&Sender> for SenderDump {
+    fn from(sender: &Sender) -> Self {
+        match sender {
+            Sender::Member(leaf_index) => SenderDump::Member {
+                leaf_index: leaf_index.u32(),
+            },
+            Sender::External(sender_index) => SenderDump::External {
+                sender_index: sender_index.index() as u32,
+            },
+            Sender::NewMemberProposal => SenderDump::NewMemberProposal,
+            Sender::NewMemberCommit => SenderDump::NewMemberCommit,
+        }
+    }
+}
+
+/// Plain data representation of a [`QueuedProposal`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueuedProposalDump {
+    /// The proposal reference.
+    pub proposal_ref: HexBytes,
+    /// The sender of the proposal.
+    pub sender: SenderDump,
+    /// The proposal.
+    pub proposal: ProposalDump,
+}
+
+impl Inspect for QueuedProposal {
+    type Dump = QueuedProposalDump;
+
+    fn dump(&self) -> Result<Self::Dump, LibraryError> {
+        Ok(QueuedProposalDump {
+            proposal_ref: self.proposal_reference().as_slice().into(),
+            sender: self.sender().into(),
+            proposal: self.proposal().dump()?,
+        })
+    }
+}
+
+/// Plain data representation of a [`StagedCommit`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StagedCommitDump {
+    /// The group context of the epoch the commit leads to.
+    pub group_context: GroupContextDump,
+    /// The proposals covered by the commit.
+    pub proposals: Vec<QueuedProposalDump>,
+    /// Whether the owner of the group is removed by the commit.
+    pub self_removed: bool,
+}
+
+impl Inspect for StagedCommit {
+    type Dump = StagedCommitDump;
+
+    fn dump(&self) -> Result<Self::Dump, LibraryError> {
+        Ok(StagedCommitDump {
+            group_context: self.group_context().dump()?,
+            proposals: self
+                .queued_proposals()
+                .map(Inspect::dump)
+                .collect::<Result<_, _>>()?,
+            self_removed: self.self_removed(),
+        })
+    }
+}
+
+/// The state of an [`MlsGroup`](crate::group::MlsGroup) as seen in a [`GroupDump`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GroupStateDump {
+    /// The group is operational.
+    Operational,
+    /// There is a pending commit that hasn't been merged yet.
+    PendingCommit,
+    /// The owner of the group has been removed.
+    Inactive,
+}
+
+/// The type of messages of a sender ratchet in a [`SenderRatchetDump`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RatchetTypeDump {
+    /// The ratchet of handshake messages.
+    Handshake,
+    /// The ratchet of application messages.
+    Application,
+}
+
+/// Telemetry of the ratchet in which this client decrypts the messages of
+/// another member in the current epoch.
+///
+/// Many kept keys of skipped messages indicate that the transport loses or
+/// reorders messages. They can be used to tune the `out_of_order_tolerance`
+/// of the `SenderRatchetConfiguration`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SenderRatchetDump {
+    /// The leaf index of the sender.
+    pub leaf_index: u32,
+    /// The type of messages of the ratchet.
+    pub ratchet_type: RatchetTypeDump,
+    /// The generation of the next message that the ratchet expects.
+    pub generation: u32,
+    /// The highest generation that was decrypted, if any.
+    pub highest_decrypted_generation: Option<u32>,
+    /// The number of kept keys of skipped messages.
+    pub skipped_keys: u32,
+}
+
+/// An entry of the operation journal of a group in a [`GroupDump`]. See
+/// [`JournalEntry`](crate::group::JournalEntry).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JournalEntryDump {
+    /// The name of the operation.
+    pub operation: String,
+    /// The hash of the parameters of the operation.
+    pub parameters_hash: HexBytes,
+    /// The epoch of the group after the operation.
+    pub epoch: u64,
+    /// The error code if the operation failed.
+    pub error: Option<u32>,
+}
+
+/// Redacted description of an [`MlsGroup`](crate::group::MlsGroup) for bug
+/// reports.
+///
+/// The dump contains the complete public state of the group, but no secrets.
+/// See [`MlsGroup::debug_dump()`](crate::group::MlsGroup::debug_dump()).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GroupDump {
+    /// The state of the group.
+    pub state: GroupStateDump,
+    /// The leaf index of the owner of the group.
+    pub own_leaf_index: u32,
+    /// The group context of the current epoch.
+    pub group_context: GroupContextDump,
+    /// The ratchet tree of the current epoch.
+    pub ratchet_tree: RatchetTreeDump,
+    /// The proposals that are pending in the current epoch.
+    pub pending_proposals: Vec<QueuedProposalDump>,
+    /// The commit that is pending in the current epoch, if any.
+    pub pending_commit: Option<StagedCommitDump>,
+    /// The ratchets in which the messages of other members are decrypted in
+    /// the current epoch, ordered by type and leaf index.
+    #[serde(default)]
+    pub sender_ratchets: Vec<SenderRatchetDump>,
+    /// The last operations on the group, oldest first, if the operation
+    /// journal is enabled.
+    #[serde(default)]
+    pub operation_journal: Vec<JournalEntryDump>,
+}
+
+impl GroupDump {
+    /// Loads a [`GroupDump`] from its JSON encoding.
+    #[cfg(feature = "inspect-json")]
+    pub fn from_json(json: &str) -> Result<Self, errors::InspectError> {
+        serde_json::from_str(json).map_err(|e| errors::InspectError::DecodingError(e.to_string()))
+    }
+
+    /// Loads a [`GroupDump`] from its CBOR encoding.
+    #[cfg(feature = "inspect-cbor")]
+    pub fn from_cbor(bytes: &[u8]) -> Result<Self, errors::InspectError> {
+        ciborium::de::from_reader(bytes)
+            .map_err(|e| errors::InspectError::DecodingError(e.to_string()))
+    }
+}
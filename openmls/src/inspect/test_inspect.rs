@@ -0,0 +1,428 @@
+use openmls_rust_crypto::OpenMlsRustCrypto;
+use openmls_traits::{types::Ciphersuite, OpenMlsProvider};
+use rstest::*;
+use rstest_reuse::{self, *};
+
+use super::*;
+use crate::{
+    binary_tree::LeafNodeIndex,
+    framing::ProcessedMessageContent,
+    group::{
+        config::CryptoConfig, test_core_group::setup_client, GroupId, MlsGroup,
+        MlsGroupConfigBuilder,
+    },
+    messages::proposals::{AddProposal, RemoveProposal},
+};
+
+#[test]
+fn hex_bytes() {
+    let bytes = HexBytes::from(vec![0x00, 0x0f, 0xa0, 0xff]);
+    let encoded = serde_json::to_string(&bytes).expect("error encoding hex bytes");
+    assert_eq!(encoded, "\"000fa0ff\"");
+
+    let decoded: HexBytes = serde_json::from_str(&encoded).expect("error decoding hex bytes");
+    assert_eq!(decoded, bytes);
+
+    // Odd number of digits and invalid digits must be rejected.
+    assert!(serde_json::from_str::<HexBytes>("\"000\"").is_err());
+    assert!(serde_json::from_str::<HexBytes>("\"0g\"").is_err());
+}
+
+#[apply(ciphersuites_and_providers)]
+fn inspect_public_group_state(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential, bob_kpb, _bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+
+    let mls_group_config = MlsGroupConfigBuilder::new()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .build();
+
+    let mut alice_group = MlsGroup::new_with_group_id(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        GroupId::from_slice(b"Test Group"),
+        alice_credential_with_key,
+    )
+    .expect("An unexpected error occurred.");
+
+    alice_group
+        .add_members(provider, &alice_signer, &[bob_kpb.key_package().clone()])
+        .expect("Could not add member to group.");
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging pending commit");
+
+    // === Group context ===
+    let group_context = alice_group.export_group_context();
+    let group_context_dump = group_context.dump().expect("error dumping group context");
+    assert_eq!(group_context_dump.ciphersuite, ciphersuite as u16);
+    assert_eq!(group_context_dump.group_id.as_slice(), b"Test Group");
+    assert_eq!(group_context_dump.epoch, 1);
+    assert_eq!(
+        group_context_dump.tree_hash.as_slice(),
+        group_context.tree_hash()
+    );
+    assert_eq!(
+        group_context_dump.extensions.len(),
+        group_context.extensions().iter().count()
+    );
+
+    // === Ratchet tree ===
+    let ratchet_tree_dump = alice_group
+        .export_ratchet_tree()
+        .dump()
+        .expect("error dumping ratchet tree");
+    assert_eq!(ratchet_tree_dump.nodes.len(), 3);
+    let identities: Vec<_> = ratchet_tree_dump
+        .nodes
+        .iter()
+        .filter_map(|node| match node {
+            Some(NodeDump::Leaf(leaf_node)) => Some(leaf_node.credential.identity.as_slice()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(identities, vec![b"Alice".as_slice(), b"Bob".as_slice()]);
+    // Bob's leaf still comes from his key package.
+    match &ratchet_tree_dump.nodes[2] {
+        Some(NodeDump::Leaf(leaf_node)) => assert!(matches!(
+            leaf_node.leaf_node_source,
+            LeafNodeSourceDump::KeyPackage { .. }
+        )),
+        _ => panic!("Expected a leaf node."),
+    }
+
+    // === Group info ===
+    let group_info: GroupInfo = alice_group
+        .export_group_info(provider, &alice_signer, true)
+        .expect("error exporting group info")
+        .into_verifiable_group_info()
+        .expect("not a group info")
+        .into();
+    let group_info_dump = group_info.dump().expect("error dumping group info");
+    assert_eq!(group_info_dump.group_context, group_context_dump);
+    assert_eq!(group_info_dump.signer, 0);
+    assert!(!group_info_dump.confirmation_tag.as_slice().is_empty());
+    assert_eq!(group_info_dump.extensions.len(), 2);
+
+    // === Proposals ===
+    let add_proposal = Proposal::Add(AddProposal {
+        key_package: bob_kpb.key_package().clone(),
+    });
+    match add_proposal.dump().expect("error dumping proposal") {
+        ProposalDump::Add {
+            ciphersuite: dumped_ciphersuite,
+            leaf_node,
+            ..
+        } => {
+            assert_eq!(dumped_ciphersuite, ciphersuite as u16);
+            assert_eq!(leaf_node.credential.identity.as_slice(), b"Bob");
+        }
+        _ => panic!("Expected an add proposal."),
+    }
+
+    let remove_proposal = Proposal::Remove(RemoveProposal {
+        removed: LeafNodeIndex::new(1),
+    });
+    assert_eq!(
+        remove_proposal.dump().expect("error dumping proposal"),
+        ProposalDump::Remove { removed: 1 }
+    );
+
+    // === Encodings ===
+    let json = serde_json::to_string(&group_info_dump).expect("error encoding group info");
+    let decoded: GroupInfoDump = serde_json::from_str(&json).expect("error decoding group info");
+    assert_eq!(decoded, group_info_dump);
+
+    #[cfg(feature = "inspect-json")]
+    assert_eq!(
+        group_info.to_json().expect("error encoding group info"),
+        json
+    );
+
+    #[cfg(feature = "inspect-cbor")]
+    {
+        let cbor = group_info.to_cbor().expect("error encoding group info");
+        let decoded: GroupInfoDump =
+            ciborium::de::from_reader(cbor.as_slice()).expect("error decoding group info");
+        assert_eq!(decoded, group_info_dump);
+    }
+}
+
+#[apply(ciphersuites_and_providers)]
+fn debug_dump(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential, bob_kpb, _bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+    let (_charlie_credential, charlie_kpb, _charlie_signer, _charlie_pk) =
+        setup_client("Charlie", ciphersuite, provider);
+
+    let mls_group_config = MlsGroupConfigBuilder::new()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .build();
+
+    let mut alice_group = MlsGroup::new_with_group_id(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        GroupId::from_slice(b"Test Group"),
+        alice_credential_with_key,
+    )
+    .expect("An unexpected error occurred.");
+
+    let dump = alice_group.debug_dump().expect("error dumping group");
+    assert_eq!(dump.state, GroupStateDump::Operational);
+    assert_eq!(dump.own_leaf_index, 0);
+    assert_eq!(dump.group_context.epoch, 0);
+    assert_eq!(dump.ratchet_tree.nodes.len(), 1);
+    assert!(dump.pending_proposals.is_empty());
+    assert!(dump.pending_commit.is_none());
+
+    // Propose to add Bob and Charlie and stage a commit that covers both.
+    alice_group
+        .propose_add_member(provider, &alice_signer, bob_kpb.key_package())
+        .expect("Could not create proposal.");
+    alice_group
+        .propose_add_member(provider, &alice_signer, charlie_kpb.key_package())
+        .expect("Could not create proposal.");
+    alice_group
+        .commit_to_pending_proposals(provider, &alice_signer)
+        .expect("Could not create commit.");
+
+    let dump = alice_group.debug_dump().expect("error dumping group");
+    assert_eq!(dump.state, GroupStateDump::PendingCommit);
+    assert_eq!(dump.pending_proposals.len(), 2);
+    assert!(dump
+        .pending_proposals
+        .iter()
+        .all(|queued_proposal| queued_proposal.sender == SenderDump::Member { leaf_index: 0 }));
+    let pending_commit = dump.pending_commit.as_ref().expect("no pending commit");
+    assert_eq!(pending_commit.group_context.epoch, 1);
+    assert!(!pending_commit.self_removed);
+    let identities: Vec<_> = pending_commit
+        .proposals
+        .iter()
+        .map(|queued_proposal| match &queued_proposal.proposal {
+            ProposalDump::Add { leaf_node, .. } => leaf_node.credential.identity.as_slice(),
+            _ => panic!("Expected an add proposal."),
+        })
+        .collect();
+    assert_eq!(identities, vec![b"Bob".as_slice(), b"Charlie".as_slice()]);
+
+    // The dump must not change the group and must be stable.
+    assert_eq!(alice_group.debug_dump().expect("error dumping group"), dump);
+
+    // === Parser ===
+    let json = serde_json::to_string(&dump).expect("error encoding group dump");
+    let decoded: GroupDump = serde_json::from_str(&json).expect("error decoding group dump");
+    assert_eq!(decoded, dump);
+
+    #[cfg(feature = "inspect-json")]
+    {
+        let json = alice_group.to_json().expect("error encoding group dump");
+        assert_eq!(
+            GroupDump::from_json(&json).expect("error decoding group dump"),
+            dump
+        );
+        assert!(matches!(
+            GroupDump::from_json(&json[1..]),
+            Err(errors::InspectError::DecodingError(_))
+        ));
+    }
+
+    #[cfg(feature = "inspect-cbor")]
+    {
+        let cbor = alice_group.to_cbor().expect("error encoding group dump");
+        assert_eq!(
+            GroupDump::from_cbor(&cbor).expect("error decoding group dump"),
+            dump
+        );
+    }
+}
+
+#[apply(ciphersuites_and_providers)]
+fn sender_ratchet_telemetry(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential, bob_kpb, bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+
+    let mls_group_config = MlsGroupConfigBuilder::new()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .use_ratchet_tree_extension(true)
+        .build();
+
+    let mut alice_group = MlsGroup::new_with_group_id(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        GroupId::from_slice(b"Test Group"),
+        alice_credential_with_key,
+    )
+    .expect("error creating group");
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(provider, &alice_signer, &[bob_kpb.key_package().clone()])
+        .expect("error adding Bob")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging commit");
+    let welcome = welcome.into_welcome().expect("expected a welcome");
+    let mut bob_group = MlsGroup::new_from_welcome(provider, &mls_group_config, welcome, None)
+        .expect("error joining group");
+    assert!(alice_group.sender_ratchets().is_empty());
+
+    // Bob sends four messages and Alice receives the last one first.
+    let messages: Vec<_> = (0..4)
+        .map(|_| {
+            bob_group
+                .create_message(provider, &bob_signer, b"Hello")
+                .expect("error creating message")
+                .into_protocol_message()
+                .expect("expected a protocol message")
+        })
+        .collect();
+    let mut expected = SenderRatchetDump {
+        leaf_index: 1,
+        ratchet_type: RatchetTypeDump::Application,
+        generation: 4,
+        highest_decrypted_generation: Some(3),
+        skipped_keys: 3,
+    };
+    for (message, skipped_keys) in [(&messages[3], 3), (&messages[1], 2)] {
+        let processed_message = alice_group
+            .process_message(provider, message.clone())
+            .expect("error processing message");
+        assert!(matches!(
+            processed_message.into_content(),
+            ProcessedMessageContent::ApplicationMessage(_)
+        ));
+        expected.skipped_keys = skipped_keys;
+        assert_eq!(alice_group.sender_ratchets(), vec![expected]);
+    }
+    assert_eq!(
+        alice_group
+            .debug_dump()
+            .expect("error dumping group")
+            .sender_ratchets,
+        vec![expected]
+    );
+
+    // Dumps without sender ratchets can still be loaded.
+    let mut json = serde_json::to_value(alice_group.debug_dump().expect("error dumping group"))
+        .expect("error encoding group dump");
+    json.as_object_mut()
+        .expect("expected an object")
+        .remove("sender_ratchets");
+    let decoded: GroupDump = serde_json::from_value(json).expect("error decoding group dump");
+    assert!(decoded.sender_ratchets.is_empty());
+}
+
+#[apply(ciphersuites_and_providers)]
+fn public_state_divergence(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential, bob_kpb, _bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+
+    let mls_group_config = MlsGroupConfigBuilder::new()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .use_ratchet_tree_extension(true)
+        .build();
+
+    let mut alice_group = MlsGroup::new_with_group_id(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        GroupId::from_slice(b"Test Group"),
+        alice_credential_with_key,
+    )
+    .expect("An unexpected error occurred.");
+
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(provider, &alice_signer, &[bob_kpb.key_package().clone()])
+        .expect("Could not add member to group.")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging pending commit");
+
+    let welcome = match welcome.into_welcome() {
+        Some(welcome) => welcome,
+        None => panic!("Expected a welcome."),
+    };
+    let bob_group = MlsGroup::new_from_welcome(provider, &mls_group_config, welcome, None)
+        .expect("error joining group");
+
+    // Both members agree on the public state.
+    let bob_state = bob_group
+        .export_public_state()
+        .expect("error exporting public state");
+    assert_eq!(
+        alice_group
+            .compare_public_state(&bob_state)
+            .expect("error comparing public state"),
+        None
+    );
+
+    // Another implementation doesn't have to provide a ratchet tree.
+    let mut theirs = bob_state.clone();
+    theirs.ratchet_tree = None;
+    assert_eq!(
+        alice_group
+            .compare_public_state(&theirs)
+            .expect("error comparing public state"),
+        None
+    );
+
+    // A divergent leaf is reported before the resulting divergent tree hash.
+    let mut theirs = bob_state.clone();
+    theirs.group_context.tree_hash = HexBytes::from(vec![0; 32]);
+    match theirs.ratchet_tree.as_mut().map(|tree| &mut tree.nodes[2]) {
+        Some(Some(NodeDump::Leaf(leaf_node))) => {
+            leaf_node.signature_key = HexBytes::from(vec![1, 2, 3])
+        }
+        _ => panic!("Expected a leaf node."),
+    }
+    let divergence = alice_group
+        .compare_public_state(&theirs)
+        .expect("error comparing public state")
+        .expect("no divergence");
+    assert_eq!(divergence.field, "ratchet_tree.nodes[2].signature_key");
+    assert_eq!(divergence.theirs, "010203");
+
+    theirs.ratchet_tree = None;
+    let divergence = alice_group
+        .compare_public_state(&theirs)
+        .expect("error comparing public state")
+        .expect("no divergence");
+    assert_eq!(divergence.field, "group_context.tree_hash");
+
+    // The epoch is compared before the transcript hashes.
+    let mut theirs = bob_state.clone();
+    theirs.group_context.epoch += 1;
+    theirs.interim_transcript_hash = HexBytes::default();
+    let divergence = alice_group
+        .compare_public_state(&theirs)
+        .expect("error comparing public state")
+        .expect("no divergence");
+    assert_eq!(
+        divergence.to_string(),
+        "group_context.epoch diverges: ours is 1, theirs is 2"
+    );
+
+    // Additional extensions are detected.
+    let mut theirs = bob_state;
+    theirs.group_context.extensions.push(ExtensionDump {
+        extension_type: 0xff00,
+        extension_data: HexBytes::default(),
+    });
+    let divergence = alice_group
+        .compare_public_state(&theirs)
+        .expect("error comparing public state")
+        .expect("no divergence");
+    assert_eq!(divergence.field, "group_context.extensions.len");
+}
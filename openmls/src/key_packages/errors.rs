@@ -62,3 +62,26 @@ pub enum KeyPackageNewError<KeyStoreError> {
     #[error(transparent)]
     SignatureError(#[from] SignatureError),
 }
+
+/// Key package pool error
+#[derive(Error, Debug, PartialEq, Clone)]
+pub enum KeyPackagePoolError {
+    /// See [`LibraryError`] for more details.
+    #[error(transparent)]
+    LibraryError(#[from] LibraryError),
+    /// See [`KeyPackageVerifyError`] for more details.
+    #[error(transparent)]
+    KeyPackageVerifyError(#[from] KeyPackageVerifyError),
+    /// The lifetime of the key package exceeds the maximum range.
+    #[error("The lifetime of the key package exceeds the maximum range.")]
+    LifetimeTooLong,
+    /// The lifetime of the key package ends too soon.
+    #[error("The lifetime of the key package ends too soon.")]
+    LifetimeTooShort,
+    /// The key package was already uploaded.
+    #[error("The key package was already uploaded.")]
+    DuplicateKeyPackage,
+    /// The init key of the key package was already used by another key package.
+    #[error("The init key of the key package was already used by another key package.")]
+    InitKeyReuse,
+}
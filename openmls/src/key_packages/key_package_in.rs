@@ -1,14 +1,17 @@
 //! Incoming KeyPackages. This modules contains deserialization and validation
 //! of KeyPackages.
 
+#![deny(clippy::unwrap_used, clippy::expect_used)]
+
 use crate::{
     ciphersuite::{signable::*, *},
     credentials::*,
     extensions::Extensions,
+    group::config::ValidationPolicy,
     treesync::node::leaf_node::{LeafNode, LeafNodeIn, VerifiableLeafNode},
     versions::ProtocolVersion,
 };
-use openmls_traits::{crypto::OpenMlsCrypto, types::Ciphersuite};
+use openmls_traits::{clock::OpenMlsClock, crypto::OpenMlsCrypto, types::Ciphersuite};
 use serde::{Deserialize, Serialize};
 use tls_codec::{Serialize as TlsSerializeTrait, TlsDeserialize, TlsSerialize, TlsSize};
 
@@ -104,7 +107,8 @@ impl KeyPackageIn {
     /// * verify that the signature on this key package is valid
     /// * verify that the signature on the leaf node is valid
     /// * verify that all extensions are supported by the leaf node
-    /// * make sure that the lifetime is valid
+    /// * make sure that the lifetime is valid at the current time of the given
+    ///   `clock`
     /// * make sure that the init key and the encryption key are different
     /// * make sure that the protocol version is valid
     ///
@@ -114,6 +118,20 @@ impl KeyPackageIn {
         self,
         crypto: &impl OpenMlsCrypto,
         protocol_version: ProtocolVersion,
+        clock: &impl OpenMlsClock,
+    ) -> Result<KeyPackage, KeyPackageVerifyError> {
+        self.validate_with_policy(crypto, protocol_version, clock, &ValidationPolicy::strict())
+    }
+
+    /// Verify that this key package is valid as in
+    /// [`KeyPackageIn::validate()`], where the lifetime and the
+    /// extensions are only checked as required by the `policy`.
+    pub(crate) fn validate_with_policy(
+        self,
+        crypto: &impl OpenMlsCrypto,
+        protocol_version: ProtocolVersion,
+        clock: &impl OpenMlsClock,
+        policy: &ValidationPolicy,
     ) -> Result<KeyPackage, KeyPackageVerifyError> {
         // We first need to verify the LeafNode inside the KeyPackage
         let leaf_node = self.payload.leaf_node.clone().into_verifiable_leaf_node();
@@ -161,19 +179,25 @@ impl KeyPackageIn {
                 .leaf_node
                 .supports_extension(&extension.extension_type())
             {
-                return Err(KeyPackageVerifyError::UnsupportedExtension);
+                policy
+                    .extensions()
+                    .check(KeyPackageVerifyError::UnsupportedExtension)?;
             }
         }
 
         // Ensure validity of the life time extension in the leaf node.
         if let Some(life_time) = key_package.payload.leaf_node.life_time() {
-            if !life_time.is_valid() {
-                return Err(KeyPackageVerifyError::InvalidLifetime);
+            if !life_time.is_valid_with_skew(clock, policy.clock_skew()) {
+                policy
+                    .lifetime()
+                    .check(KeyPackageVerifyError::InvalidLifetime)?;
             }
         } else {
             // This assumes that we only verify key packages with leaf nodes
             // that were created for the key package.
-            return Err(KeyPackageVerifyError::MissingLifetime);
+            policy
+                .lifetime()
+                .check(KeyPackageVerifyError::MissingLifetime)?;
         }
 
         Ok(key_package)
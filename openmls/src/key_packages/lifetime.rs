@@ -1,5 +1,10 @@
+#[cfg(not(all(target_arch = "wasm32", feature = "js")))]
 use std::time::{SystemTime, UNIX_EPOCH};
 
+#[cfg(all(target_arch = "wasm32", feature = "js"))]
+use fluvio_wasm_timer::{SystemTime, UNIX_EPOCH};
+
+use openmls_traits::clock::OpenMlsClock;
 use serde::{Deserialize, Serialize};
 use tls_codec::{TlsDeserialize, TlsSerialize, TlsSize};
 
@@ -47,31 +52,52 @@ impl Lifetime {
     /// Note that the lifetime is extended 1h into the past to adapt to skewed
     /// clocks, i.e. `not_before` is set to now - 1h.
     pub fn new(t: u64) -> Self {
+        Self::new_with_clock(t, &SystemClock)
+    }
+
+    /// Create a new lifetime with lifetime `t` (in seconds), where the current
+    /// time is taken from the given `clock`.
+    /// As with [`Lifetime::new()`], the lifetime is extended 1h into the past.
+    pub fn new_with_clock(t: u64, clock: &impl OpenMlsClock) -> Self {
         let lifetime_margin: u64 = DEFAULT_KEY_PACKAGE_LIFETIME_MARGIN_SECONDS;
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .expect("SystemTime before UNIX EPOCH!")
-            .as_secs();
-        let not_before = now - lifetime_margin;
-        let not_after = now + t;
+        let now = clock.now();
+        let not_before = now.saturating_sub(lifetime_margin);
+        let not_after = now.saturating_add(t);
         Self {
             not_before,
             not_after,
         }
     }
 
+    /// Returns the start of the lifetime in seconds since the Unix epoch.
+    pub fn not_before(&self) -> u64 {
+        self.not_before
+    }
+
+    /// Returns the end of the lifetime in seconds since the Unix epoch.
+    pub fn not_after(&self) -> u64 {
+        self.not_after
+    }
+
     /// Returns true if this lifetime is valid.
+    #[cfg(test)]
     pub(crate) fn is_valid(&self) -> bool {
-        match SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .map(|duration| duration.as_secs())
-        {
-            Ok(elapsed) => self.not_before < elapsed && elapsed < self.not_after,
-            Err(_) => {
-                log::error!("SystemTime before UNIX EPOCH.");
-                false
-            }
-        }
+        self.is_valid_with_clock(&SystemClock)
+    }
+
+    /// Returns true if this lifetime is valid at the current time of the
+    /// given `clock`.
+    pub fn is_valid_with_clock(&self, clock: &impl OpenMlsClock) -> bool {
+        self.is_valid_with_skew(clock, 0)
+    }
+
+    /// Returns true if this lifetime is valid at the current time of the
+    /// given `clock`, where the lifetime is extended by `clock_skew` seconds
+    /// into the past and into the future.
+    pub fn is_valid_with_skew(&self, clock: &impl OpenMlsClock, clock_skew: u64) -> bool {
+        let now = clock.now();
+        self.not_before < now.saturating_add(clock_skew)
+            && now.saturating_sub(clock_skew) < self.not_after
     }
 
     /// ValSem(openmls/annotations#32):
@@ -88,12 +114,44 @@ impl Default for Lifetime {
     }
 }
 
+/// The [`OpenMlsClock`] based on the system time.
+///
+/// This is the clock used by [`Lifetime::new()`]. Key packages and leaf nodes
+/// are validated against the clock of the provider instead.
+/// On `wasm32-unknown-unknown`, the time is taken from the JavaScript host if
+/// the `js` feature is enabled.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl OpenMlsClock for SystemClock {
+    /// Returns the system time in seconds since the Unix epoch, or 0 if the
+    /// system time is before the Unix epoch.
+    fn now(&self) -> u64 {
+        match SystemTime::now().duration_since(UNIX_EPOCH) {
+            Ok(duration) => duration.as_secs(),
+            Err(_) => {
+                log::error!("SystemTime before UNIX EPOCH.");
+                0
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use openmls_traits::clock::OpenMlsClock;
     use tls_codec::{Deserialize, Serialize};
 
     use super::Lifetime;
 
+    struct FixedClock(u64);
+
+    impl OpenMlsClock for FixedClock {
+        fn now(&self) -> u64 {
+            self.0
+        }
+    }
+
     #[test]
     fn lifetime() {
         // A freshly created extensions must be valid.
@@ -113,4 +171,31 @@ mod tests {
             .expect("Error deserializing lifetime");
         assert!(!ext_deserialized.is_valid());
     }
+
+    #[test]
+    fn lifetime_with_clock() {
+        let now = 1_700_000_000;
+        let lifetime = Lifetime::new_with_clock(60, &FixedClock(now));
+        assert_eq!(lifetime.not_before(), now - 60 * 60);
+        assert_eq!(lifetime.not_after(), now + 60);
+
+        assert!(lifetime.is_valid_with_clock(&FixedClock(now)));
+        assert!(!lifetime.is_valid_with_clock(&FixedClock(now - 60 * 60)));
+        assert!(!lifetime.is_valid_with_clock(&FixedClock(now + 60)));
+
+        // Skewed clocks are tolerated.
+        assert!(lifetime.is_valid_with_skew(&FixedClock(now + 60), 1));
+        assert!(lifetime.is_valid_with_skew(&FixedClock(now - 60 * 60), 1));
+        assert!(!lifetime.is_valid_with_skew(&FixedClock(now + 120), 60));
+        assert!(lifetime.is_valid_with_skew(&FixedClock(u64::MAX), u64::MAX));
+
+        // The clock may be too far in the past for the margin.
+        let lifetime = Lifetime::new_with_clock(60, &FixedClock(0));
+        assert_eq!(lifetime.not_before(), 0);
+
+        // Lifetimes that end after `u64::MAX` end at `u64::MAX`.
+        let lifetime = Lifetime::new_with_clock(u64::MAX, &FixedClock(now));
+        assert_eq!(lifetime.not_after(), u64::MAX);
+        assert!(lifetime.is_valid_with_skew(&FixedClock(u64::MAX - 1), 0));
+    }
 }
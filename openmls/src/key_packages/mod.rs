@@ -87,7 +87,7 @@
 //!     .expect("Could not deserialize KeyPackage");
 //!
 //! let key_package = key_package_in
-//!     .validate(provider.crypto(), ProtocolVersion::Mls10)
+//!     .validate(provider.crypto(), ProtocolVersion::Mls10, provider.clock())
 //!     .expect("Invalid KeyPackage");
 //! ```
 //!
@@ -133,6 +133,7 @@ pub mod errors;
 pub mod key_package_in;
 
 mod lifetime;
+mod pool;
 
 // Tests
 #[cfg(test)]
@@ -140,7 +141,8 @@ pub(crate) mod test_key_packages;
 
 // Public types
 pub use key_package_in::KeyPackageIn;
-pub use lifetime::Lifetime;
+pub use lifetime::{Lifetime, SystemClock};
+pub use pool::KeyPackagePool;
 
 /// The unsigned payload of a key package.
 /// Any modification must happen on this unsigned struct. Use `sign` to get a
@@ -209,6 +211,37 @@ impl MlsEntity for KeyPackage {
     const ID: MlsEntityId = MlsEntityId::KeyPackage;
 }
 
+const CONSUMED_KEY_PACKAGE_LABEL: &[u8; 20] = b"consumed_key_package";
+
+/// The marker that is stored in the key store when a key package was used to
+/// join a group and its private key was deleted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct ConsumedKeyPackage(KeyPackageRef);
+
+impl MlsEntity for ConsumedKeyPackage {
+    const ID: MlsEntityId = MlsEntityId::ConsumedKeyPackage;
+}
+
+impl ConsumedKeyPackage {
+    /// Returns the key store index of the marker for `key_package_ref`.
+    fn key_store_index(key_package_ref: &KeyPackageRef) -> Vec<u8> {
+        let mut key_store_index = CONSUMED_KEY_PACKAGE_LABEL.to_vec();
+        key_store_index.extend_from_slice(key_package_ref.as_slice());
+        key_store_index
+    }
+
+    /// Marks the key package with the given reference as consumed.
+    pub(crate) fn store<KeyStore: OpenMlsKeyStore>(
+        key_store: &KeyStore,
+        key_package_ref: KeyPackageRef,
+    ) -> Result<(), KeyStore::Error> {
+        key_store.store(
+            &Self::key_store_index(&key_package_ref),
+            &Self(key_package_ref),
+        )
+    }
+}
+
 /// Helper struct containing the results of building a new [`KeyPackage`].
 pub(crate) struct KeyPackageCreationResult {
     pub key_package: KeyPackage,
@@ -315,6 +348,15 @@ impl KeyPackage {
         Ok((key_package, encryption_key_pair))
     }
 
+    /// Returns `true` if the key package with the given reference was used to
+    /// join a group, i.e., a Welcome message for it was processed and the
+    /// key package was deleted from the key store.
+    pub fn is_consumed(key_store: &impl OpenMlsKeyStore, key_package_ref: &KeyPackageRef) -> bool {
+        key_store
+            .read::<ConsumedKeyPackage>(&ConsumedKeyPackage::key_store_index(key_package_ref))
+            .is_some()
+    }
+
     /// Delete this key package and its private key from the key store.
     pub fn delete<KeyStore: OpenMlsKeyStore>(
         &self,
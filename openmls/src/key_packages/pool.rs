@@ -0,0 +1,157 @@
+//! # Key package pool
+//!
+//! A key package directory service stores the key packages that clients
+//! upload and hands them out to clients that want to add the uploader to a
+//! group. The [`KeyPackagePool`] implements the checks such a service should
+//! perform without requiring any group state:
+//!
+//! * Uploaded key packages are validated with
+//!   [`KeyPackageIn::validate()`].
+//! * The lifetime of an uploaded key package must not exceed the maximum
+//!   range (see [`Lifetime::has_acceptable_range()`](super::Lifetime::has_acceptable_range())) and must not end within
+//!   the configured minimum remaining lifetime.
+//! * A key package can only be uploaded once and its init key must not have
+//!   been used by any other key package uploaded to the pool, including key
+//!   packages that were already handed out.
+//!
+//! Key packages are handed out only once, except for last resort key
+//! packages, which stay in the pool until they expire.
+
+use std::collections::{BTreeMap, HashSet};
+
+use openmls_traits::{clock::OpenMlsClock, crypto::OpenMlsCrypto, types::Ciphersuite};
+
+use super::{errors::KeyPackagePoolError, KeyPackage, KeyPackageIn};
+use crate::{ciphersuite::hash_ref::KeyPackageRef, error::LibraryError, versions::ProtocolVersion};
+
+/// A pool of validated key packages. See the [module documentation](self)
+/// for details.
+#[derive(Debug, Default)]
+pub struct KeyPackagePool {
+    min_remaining_lifetime: u64,
+    key_packages: BTreeMap<KeyPackageRef, KeyPackage>,
+    init_keys: HashSet<Vec<u8>>,
+}
+
+impl KeyPackagePool {
+    /// Create a new, empty pool that rejects key packages whose lifetime ends
+    /// within `min_remaining_lifetime` seconds of their upload.
+    pub fn new(min_remaining_lifetime: u64) -> Self {
+        Self {
+            min_remaining_lifetime,
+            ..Default::default()
+        }
+    }
+
+    /// Validate the uploaded `key_package` and add it to the pool.
+    ///
+    /// Returns the [`KeyPackageRef`] of the key package, or an error if the
+    /// key package is invalid, its lifetime is not acceptable, or it or its
+    /// init key was already uploaded.
+    pub fn upload(
+        &mut self,
+        crypto: &impl OpenMlsCrypto,
+        key_package: KeyPackageIn,
+        protocol_version: ProtocolVersion,
+        clock: &impl OpenMlsClock,
+    ) -> Result<KeyPackageRef, KeyPackagePoolError> {
+        let key_package = key_package.validate(crypto, protocol_version, clock)?;
+        let life_time = key_package
+            .leaf_node()
+            .life_time()
+            .ok_or_else(|| LibraryError::custom("Validated key package without lifetime"))?;
+        if !life_time.has_acceptable_range() {
+            return Err(KeyPackagePoolError::LifetimeTooLong);
+        }
+        if life_time.not_after() < clock.now().saturating_add(self.min_remaining_lifetime) {
+            return Err(KeyPackagePoolError::LifetimeTooShort);
+        }
+
+        let key_package_ref = key_package.hash_ref(crypto)?;
+        if self.key_packages.contains_key(&key_package_ref) {
+            return Err(KeyPackagePoolError::DuplicateKeyPackage);
+        }
+        if !self
+            .init_keys
+            .insert(key_package.hpke_init_key().as_slice().to_vec())
+        {
+            return Err(KeyPackagePoolError::InitKeyReuse);
+        }
+
+        self.key_packages
+            .insert(key_package_ref.clone(), key_package);
+        Ok(key_package_ref)
+    }
+
+    /// Returns the key package with the given reference, if it is in the
+    /// pool.
+    pub fn get(&self, key_package_ref: &KeyPackageRef) -> Option<&KeyPackage> {
+        self.key_packages.get(key_package_ref)
+    }
+
+    /// Hand out a key package of the client with the given credential
+    /// `identity` for the given `ciphersuite` that is valid at the current
+    /// time of `clock`.
+    ///
+    /// Key packages that are not last resort key packages are preferred and
+    /// removed from the pool. A last resort key package is only handed out
+    /// if there is no other key package and stays in the pool.
+    pub fn take(
+        &mut self,
+        identity: &[u8],
+        ciphersuite: Ciphersuite,
+        clock: &impl OpenMlsClock,
+    ) -> Option<KeyPackage> {
+        let mut last_resort = None;
+        for (key_package_ref, key_package) in self.key_packages.iter() {
+            if key_package.leaf_node().credential().identity() != identity
+                || key_package.ciphersuite() != ciphersuite
+                || !key_package
+                    .leaf_node()
+                    .life_time()
+                    .is_some_and(|life_time| life_time.is_valid_with_clock(clock))
+            {
+                continue;
+            }
+            if !key_package.last_resort() {
+                let key_package_ref = key_package_ref.clone();
+                return self.key_packages.remove(&key_package_ref);
+            }
+            if last_resort.is_none() {
+                last_resort = Some(key_package.clone());
+            }
+        }
+        last_resort
+    }
+
+    /// Remove the key package with the given reference from the pool, e.g.,
+    /// because it was used in a Welcome message. Its init key stays blocked.
+    pub fn remove(&mut self, key_package_ref: &KeyPackageRef) -> Option<KeyPackage> {
+        self.key_packages.remove(key_package_ref)
+    }
+
+    /// Remove all key packages whose lifetime ended before the current time
+    /// of `clock` and return how many were removed. Their init keys stay
+    /// blocked.
+    pub fn remove_expired(&mut self, clock: &impl OpenMlsClock) -> usize {
+        let now = clock.now();
+        let len = self.key_packages.len();
+        self.key_packages.retain(|_, key_package| {
+            key_package
+                .leaf_node()
+                .life_time()
+                .is_some_and(|life_time| life_time.not_after() > now)
+        });
+        len - self.key_packages.len()
+    }
+
+    /// Returns the number of key packages in the pool.
+    pub fn len(&self) -> usize {
+        self.key_packages.len()
+    }
+
+    /// Returns `true` if the pool doesn't contain any key packages.
+    pub fn is_empty(&self) -> bool {
+        self.key_packages.is_empty()
+    }
+}
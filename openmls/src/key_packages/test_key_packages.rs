@@ -1,6 +1,7 @@
 use crate::test_utils::*;
 use openmls_basic_credential::SignatureKeyPair;
 use openmls_rust_crypto::OpenMlsRustCrypto;
+use openmls_traits::clock::OpenMlsClock;
 use tls_codec::Deserialize;
 
 use crate::{extensions::*, key_packages::*};
@@ -38,7 +39,7 @@ fn generate_key_package(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvide
 
     let kpi = KeyPackageIn::from(key_package);
     assert!(kpi
-        .validate(provider.crypto(), ProtocolVersion::Mls10)
+        .validate(provider.crypto(), ProtocolVersion::Mls10, provider.clock())
         .is_ok());
 }
 
@@ -85,7 +86,7 @@ fn application_id_extension(ciphersuite: Ciphersuite, provider: &impl OpenMlsPro
 
     let kpi = KeyPackageIn::from(key_package.clone());
     assert!(kpi
-        .validate(provider.crypto(), ProtocolVersion::Mls10)
+        .validate(provider.crypto(), ProtocolVersion::Mls10, provider.clock())
         .is_ok());
 
     // Check ID
@@ -119,7 +120,7 @@ fn key_package_validation(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvi
 
     let key_package_in = KeyPackageIn::tls_deserialize(&mut encoded.as_slice()).unwrap();
     let err = key_package_in
-        .validate(provider.crypto(), ProtocolVersion::Mls10)
+        .validate(provider.crypto(), ProtocolVersion::Mls10, provider.clock())
         .unwrap_err();
 
     // Expect an invalid protocol version error
@@ -138,9 +139,160 @@ fn key_package_validation(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvi
 
     let key_package_in = KeyPackageIn::tls_deserialize(&mut encoded.as_slice()).unwrap();
     let err = key_package_in
-        .validate(provider.crypto(), ProtocolVersion::Mls10)
+        .validate(provider.crypto(), ProtocolVersion::Mls10, provider.clock())
         .unwrap_err();
 
     // Expect an invalid init/encryption key error
     assert_eq!(err, KeyPackageVerifyError::InitKeyEqualsEncryptionKey);
 }
+
+#[cfg(not(feature = "crypto-debug"))]
+#[apply(ciphersuites_and_providers)]
+fn redacted_debug_output(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let credential = Credential::new(b"Sasha".to_vec(), CredentialType::Basic).unwrap();
+    let signer = SignatureKeyPair::new(ciphersuite.signature_algorithm()).unwrap();
+    let key_package_bundle = KeyPackageBundle::new(
+        provider,
+        &signer,
+        ciphersuite,
+        CredentialWithKey {
+            credential,
+            signature_key: signer.to_public_vec().into(),
+        },
+    );
+
+    let private_key = hex::encode(&**key_package_bundle.private_key());
+    let debug_output = format!("{key_package_bundle:?}");
+    assert!(!debug_output.contains(&private_key));
+    assert!(debug_output.contains("***"));
+}
+
+#[apply(ciphersuites_and_providers)]
+fn key_package_pool(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    struct FixedClock(u64);
+
+    impl OpenMlsClock for FixedClock {
+        fn now(&self) -> u64 {
+            self.0
+        }
+    }
+
+    let (key_package, credential, signer) = key_package(ciphersuite, provider);
+    let credential_with_key = CredentialWithKey {
+        credential: credential.clone(),
+        signature_key: signer.to_public_vec().into(),
+    };
+    let config = CryptoConfig::with_default_version(ciphersuite);
+    let mut pool = KeyPackagePool::default();
+
+    let key_package_ref = pool
+        .upload(
+            provider.crypto(),
+            key_package.clone().into(),
+            ProtocolVersion::Mls10,
+            &SystemClock,
+        )
+        .expect("Error uploading key package.");
+    assert_eq!(
+        key_package_ref,
+        key_package.hash_ref(provider.crypto()).unwrap()
+    );
+    assert_eq!(pool.get(&key_package_ref), Some(&key_package));
+
+    // The same key package or another one with the same init key are rejected.
+    assert_eq!(
+        pool.upload(
+            provider.crypto(),
+            key_package.clone().into(),
+            ProtocolVersion::Mls10,
+            &SystemClock,
+        ),
+        Err(KeyPackagePoolError::DuplicateKeyPackage)
+    );
+    let reused_init_key = KeyPackage::builder()
+        .build(config, provider, &signer, credential_with_key.clone())
+        .unwrap()
+        .into_with_init_key(
+            config,
+            &signer,
+            key_package.hpke_init_key().as_slice().to_vec(),
+        )
+        .unwrap();
+    assert_eq!(
+        pool.upload(
+            provider.crypto(),
+            reused_init_key.into(),
+            ProtocolVersion::Mls10,
+            &SystemClock,
+        ),
+        Err(KeyPackagePoolError::InitKeyReuse)
+    );
+
+    // A last resort key package is only handed out if there is no other one
+    // and stays in the pool.
+    let last_resort = KeyPackage::builder()
+        .key_package_extensions(Extensions::single(Extension::LastResort(
+            LastResortExtension::default(),
+        )))
+        .leaf_node_capabilities(Capabilities::new(
+            None,
+            None,
+            Some(&[ExtensionType::LastResort]),
+            None,
+            None,
+        ))
+        .build(config, provider, &signer, credential_with_key.clone())
+        .unwrap();
+    pool.upload(
+        provider.crypto(),
+        last_resort.clone().into(),
+        ProtocolVersion::Mls10,
+        &SystemClock,
+    )
+    .expect("Error uploading key package.");
+    let identity = credential.identity();
+    assert_eq!(
+        pool.take(identity, ciphersuite, &SystemClock),
+        Some(key_package.clone())
+    );
+    assert_eq!(
+        pool.take(identity, ciphersuite, &SystemClock),
+        Some(last_resort.clone())
+    );
+    assert_eq!(
+        pool.take(identity, ciphersuite, &SystemClock),
+        Some(last_resort)
+    );
+    assert_eq!(pool.take(b"Unknown", ciphersuite, &SystemClock), None);
+    assert_eq!(pool.len(), 1);
+
+    // Taken key packages can't be uploaded again.
+    assert_eq!(
+        pool.upload(
+            provider.crypto(),
+            key_package.into(),
+            ProtocolVersion::Mls10,
+            &SystemClock,
+        ),
+        Err(KeyPackagePoolError::InitKeyReuse)
+    );
+
+    // Expired key packages are removed.
+    assert_eq!(pool.remove_expired(&FixedClock(u64::MAX)), 1);
+    assert!(pool.is_empty());
+
+    // Key packages that expire too soon are rejected.
+    let mut pool = KeyPackagePool::new(u64::MAX);
+    let key_package = KeyPackage::builder()
+        .build(config, provider, &signer, credential_with_key)
+        .unwrap();
+    assert_eq!(
+        pool.upload(
+            provider.crypto(),
+            key_package.into(),
+            ProtocolVersion::Mls10,
+            &SystemClock,
+        ),
+        Err(KeyPackagePoolError::LifetimeTooShort)
+    );
+}
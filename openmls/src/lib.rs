@@ -98,7 +98,8 @@
 //! // via a server storing key packages for users.
 //! let (mls_message_out, welcome_out, group_info) = sasha_group
 //!     .add_members(provider, &sasha_signer, &[maxim_key_package])
-//!     .expect("Could not add members.");
+//!     .expect("Could not add members.")
+//!     .into_parts();
 //!
 //! // Sasha merges the pending commit that adds Maxim.
 //! sasha_group
@@ -133,6 +134,18 @@
 //!  .expect("Error joining group from Welcome");
 //! ```
 //!
+//! ## Debug output
+//! The [`Debug`](std::fmt::Debug) output of OpenMLS types never contains
+//! private keys, secrets, or the content of application messages, so that
+//! groups, key package bundles, and processed messages can be logged safely.
+//! These values are replaced with `***`. This is part of the API and applies
+//! to all types of OpenMLS and the [`openmls_traits`] crate.
+//!
+//! For debugging, the full output can be re-enabled with the ☣️
+//! `crypto-debug` feature for keys and secrets and the ☣️ `content-debug`
+//! feature for message content. These features must never be enabled in
+//! production.
+//!
 //! [//]: # "links and badges"
 //! [user Manual]: https://openmls.tech/book
 #![cfg_attr(docsrs, feature(doc_cfg))]
@@ -168,19 +181,35 @@ pub mod error;
 
 // Public
 pub mod ciphersuite;
+pub mod codec;
 pub mod credentials;
 pub mod extensions;
 pub mod framing;
 pub mod group;
+pub mod inspect;
 pub mod key_packages;
 pub mod messages;
+pub mod metrics;
 pub mod schedule;
+pub mod security_events;
 pub mod treesync;
 pub mod versions;
 
+#[cfg(feature = "fuzz")]
+pub mod fuzz;
+
+#[cfg(feature = "proptest")]
+pub mod arbitrary;
+
 // Private
 mod binary_tree;
 mod tree;
 
 /// Single place, re-exporting the most used public functions.
 pub mod prelude;
+
+// === Re-exports ===
+
+/// The [`tls_codec`] crate, e.g., to derive the encoding of
+/// [`CustomExtension`](extensions::CustomExtension)s.
+pub use tls_codec;
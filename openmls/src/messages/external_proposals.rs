@@ -6,11 +6,13 @@
 
 use crate::{
     binary_tree::LeafNodeIndex,
-    extensions::SenderExtensionIndex,
+    credentials::Credential,
+    extensions::{ExternalSenderScopesExtension, SenderExtensionIndex},
     framing::{mls_auth_content::AuthenticatedContent, MlsMessageOut, PublicMessage},
     group::{
-        errors::ProposeRemoveMemberError, mls_group::errors::ProposeAddMemberError, GroupEpoch,
-        GroupId,
+        errors::{ExternalRemoveProposalError, ProposeRemoveMemberError},
+        mls_group::errors::ProposeAddMemberError,
+        GroupEpoch, GroupId, PublicGroup,
     },
     key_packages::KeyPackage,
     messages::{AddProposal, Proposal},
@@ -87,4 +89,62 @@ impl ExternalProposal {
         .map(MlsMessageOut::from)
         .map_err(ProposeRemoveMemberError::from)
     }
+
+    /// Creates an external Remove proposal for the member with the given
+    /// `removed` credential in the current epoch of `public_group`. This is
+    /// meant for moderators and delivery services that track the group with a
+    /// [`PublicGroup`] and don't want to keep track of leaf indices and epochs
+    /// themselves.
+    ///
+    /// Unlike [`ExternalProposal::new_remove()`], it checks that
+    /// `sender_index` refers to an external sender of the group and that the
+    /// removal is within the [`ExternalSenderScopesExtension`] of the group,
+    /// so that the proposal isn't rejected by the members.
+    ///
+    /// # Arguments
+    /// * `removed` - credential of the member to remove
+    /// * `public_group` - public state of the group in its current epoch
+    /// * `signer` - of the sender to sign the message
+    /// * `sender_index` - index of the sender of the proposal (in the [crate::extensions::ExternalSendersExtension] array
+    /// from the Group Context)
+    pub fn new_remove_member(
+        removed: &Credential,
+        public_group: &PublicGroup,
+        signer: &impl Signer,
+        sender_index: SenderExtensionIndex,
+    ) -> Result<MlsMessageOut, ExternalRemoveProposalError> {
+        let extensions = public_group.group_context().extensions();
+        let is_external_sender = extensions
+            .external_senders()
+            .map(|external_senders| sender_index.index() < external_senders.len())
+            .unwrap_or(false);
+        if !is_external_sender {
+            return Err(ExternalRemoveProposalError::UnknownExternalSender);
+        }
+        let member = public_group
+            .members()
+            .find(|member| &member.credential == removed)
+            .ok_or(ExternalRemoveProposalError::UnknownMember)?;
+        let may_remove = match extensions.custom::<ExternalSenderScopesExtension>() {
+            Ok(Some(scopes)) => scopes.may_remove(sender_index, removed.identity()),
+            Ok(None) => true,
+            Err(_) => false,
+        };
+        if !may_remove {
+            return Err(ExternalRemoveProposalError::ExternalSenderOutOfScope);
+        }
+
+        AuthenticatedContent::new_external_proposal(
+            Proposal::Remove(RemoveProposal {
+                removed: member.index,
+            }),
+            public_group.group_id().clone(),
+            public_group.group_context().epoch(),
+            signer,
+            sender_index,
+        )
+        .map(PublicMessage::from)
+        .map(MlsMessageOut::from)
+        .map_err(ExternalRemoveProposalError::from)
+    }
 }
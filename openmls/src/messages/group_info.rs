@@ -15,6 +15,7 @@ use crate::{
     extensions::Extensions,
     group::{GroupContext, GroupId},
     messages::ConfirmationTag,
+    versions::ProtocolVersion,
 };
 
 const SIGNATURE_GROUP_INFO_LABEL: &str = "GroupInfoTBS";
@@ -91,6 +92,13 @@ impl VerifiableGroupInfo {
         self.payload.group_context.ciphersuite()
     }
 
+    /// Get (unverified) protocol version of the verifiable group info.
+    ///
+    /// Note: This method should only be used when necessary to verify the group info signature.
+    pub fn protocol_version(&self) -> ProtocolVersion {
+        self.payload.group_context.protocol_version()
+    }
+
     /// Get (unverified) signer of the verifiable group info.
     ///
     /// Note: This method should only be used when necessary to verify the group info signature.
@@ -112,6 +120,14 @@ impl VerifiableGroupInfo {
     pub(crate) fn group_id(&self) -> &GroupId {
         self.payload.group_context.group_id()
     }
+
+    /// Get (unverified) tree hash of the verifiable group info.
+    ///
+    /// Note: This method should only be used when necessary to verify the group
+    /// info signature.
+    pub(crate) fn tree_hash(&self) -> &[u8] {
+        self.payload.group_context.tree_hash()
+    }
 }
 
 #[cfg(test)]
@@ -175,6 +191,16 @@ impl GroupInfo {
         &self.payload.confirmation_tag
     }
 
+    /// Returns the leaf index of the signer.
+    pub(crate) fn signer(&self) -> LeafNodeIndex {
+        self.payload.signer
+    }
+
+    /// Returns the signature.
+    pub(crate) fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
     #[cfg(any(feature = "test-utils", test))]
     pub(crate) fn into_verifiable_group_info(self) -> VerifiableGroupInfo {
         VerifiableGroupInfo {
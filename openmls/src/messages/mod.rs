@@ -4,6 +4,7 @@
 //! as well as Proposals & the group info used for External Commits.
 
 use openmls_traits::{
+    clock::OpenMlsClock,
     crypto::OpenMlsCrypto,
     types::{Ciphersuite, HpkeCiphertext, HpkeKeyPair},
 };
@@ -18,7 +19,7 @@ use crate::{
     credentials::CredentialWithKey,
     error::LibraryError,
     framing::SenderContext,
-    group::errors::ValidationError,
+    group::{config::ValidationPolicy, errors::ValidationError},
     schedule::{psk::PreSharedKeyId, JoinerSecret},
     treesync::{
         node::{
@@ -56,7 +57,9 @@ use self::{proposals::*, proposals_in::ProposalOrRefIn};
 ///   opaque encrypted_group_info<V>;
 /// } Welcome;
 /// ```
-#[derive(Clone, Debug, Eq, PartialEq, TlsDeserialize, TlsSerialize, TlsSize)]
+#[derive(
+    Clone, Debug, Eq, PartialEq, Serialize, Deserialize, TlsDeserialize, TlsSerialize, TlsSize,
+)]
 pub struct Welcome {
     cipher_suite: Ciphersuite,
     secrets: Vec<EncryptedGroupSecrets>,
@@ -93,6 +96,40 @@ impl Welcome {
         self.encrypted_group_info.as_slice()
     }
 
+    /// Split the Welcome message into one Welcome message per new member,
+    /// keyed by the [`KeyPackageRef`] of the new member.
+    ///
+    /// Each of the Welcome messages only contains the [`EncryptedGroupSecrets`]
+    /// of its new member and the shared encrypted group info. The delivery
+    /// service or the sender can use this to deliver only the part of the
+    /// Welcome message a new member needs. The new members can join with the
+    /// split Welcome messages as with the original one.
+    pub fn split(&self) -> Vec<(KeyPackageRef, Welcome)> {
+        self.secrets
+            .iter()
+            .map(|secrets| (secrets.new_member(), self.with_secrets(secrets)))
+            .collect()
+    }
+
+    /// Returns a Welcome message that only contains the
+    /// [`EncryptedGroupSecrets`] of the new member with the given
+    /// [`KeyPackageRef`], or `None` if the Welcome message doesn't contain
+    /// secrets for that member. See [`Welcome::split()`].
+    pub fn for_new_member(&self, new_member: &KeyPackageRef) -> Option<Welcome> {
+        self.secrets
+            .iter()
+            .find(|secrets| &secrets.new_member == new_member)
+            .map(|secrets| self.with_secrets(secrets))
+    }
+
+    fn with_secrets(&self, secrets: &EncryptedGroupSecrets) -> Welcome {
+        Self {
+            cipher_suite: self.cipher_suite,
+            secrets: vec![secrets.clone()],
+            encrypted_group_info: self.encrypted_group_info.clone(),
+        }
+    }
+
     /// Set the welcome's encrypted group info.
     #[cfg(test)]
     pub fn set_encrypted_group_info(&mut self, encrypted_group_info: Vec<u8>) {
@@ -103,7 +140,9 @@ impl Welcome {
 /// EncryptedGroupSecrets
 ///
 /// This is part of a [`Welcome`] message. It can be used to correlate the correct secrets with each new member.
-#[derive(Clone, Debug, Eq, PartialEq, TlsDeserialize, TlsSerialize, TlsSize)]
+#[derive(
+    Clone, Debug, Eq, PartialEq, Serialize, Deserialize, TlsDeserialize, TlsSerialize, TlsSize,
+)]
 pub struct EncryptedGroupSecrets {
     /// Key package reference of the new member
     new_member: KeyPackageRef,
@@ -195,11 +234,13 @@ impl CommitIn {
         crypto: &impl OpenMlsCrypto,
         sender_context: SenderContext,
         protocol_version: ProtocolVersion,
+        clock: &impl OpenMlsClock,
+        policy: &ValidationPolicy,
     ) -> Result<Commit, ValidationError> {
         let proposals = self
             .proposals
             .into_iter()
-            .map(|p| p.validate(crypto, ciphersuite, protocol_version))
+            .map(|p| p.validate(crypto, ciphersuite, protocol_version, clock, policy))
             .collect::<Result<Vec<_>, _>>()?;
 
         let path = if let Some(path) = self.path {
@@ -269,6 +310,13 @@ impl From<Commit> for CommitIn {
 )]
 pub struct ConfirmationTag(pub(crate) Mac);
 
+impl ConfirmationTag {
+    /// Returns the confirmation tag as a slice.
+    pub fn as_slice(&self) -> &[u8] {
+        self.0.mac_value.as_slice()
+    }
+}
+
 /// PathSecret
 ///
 /// > 11.2.2. Welcoming New Members
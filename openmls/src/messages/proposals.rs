@@ -430,6 +430,11 @@ impl GroupContextExtensionProposal {
     pub(crate) fn new(extensions: Extensions) -> Self {
         Self { extensions }
     }
+
+    /// Returns the [`Extensions`] of this proposal.
+    pub(crate) fn extensions(&self) -> &Extensions {
+        &self.extensions
+    }
 }
 
 // Crate-only types
@@ -5,17 +5,19 @@
 //! To find out if a specific proposal type is supported,
 //! [`ProposalType::is_supported()`] can be used.
 
+#![deny(clippy::unwrap_used, clippy::expect_used)]
+
 use crate::{
     ciphersuite::{hash_ref::ProposalRef, signable::Verifiable},
     credentials::CredentialWithKey,
     framing::SenderContext,
-    group::errors::ValidationError,
+    group::{config::ValidationPolicy, errors::ValidationError},
     key_packages::*,
     treesync::node::leaf_node::{LeafNodeIn, TreePosition, VerifiableLeafNode},
     versions::ProtocolVersion,
 };
 
-use openmls_traits::{crypto::OpenMlsCrypto, types::Ciphersuite};
+use openmls_traits::{clock::OpenMlsClock, crypto::OpenMlsCrypto, types::Ciphersuite};
 use serde::{Deserialize, Serialize};
 use tls_codec::{TlsDeserialize, TlsSerialize, TlsSize};
 
@@ -99,10 +101,12 @@ impl ProposalIn {
         ciphersuite: Ciphersuite,
         sender_context: Option<SenderContext>,
         protocol_version: ProtocolVersion,
+        clock: &impl OpenMlsClock,
+        policy: &ValidationPolicy,
     ) -> Result<Proposal, ValidationError> {
         Ok(match self {
             ProposalIn::Add(add) => {
-                Proposal::Add(add.validate(crypto, protocol_version, ciphersuite)?)
+                Proposal::Add(add.validate(crypto, protocol_version, ciphersuite, clock, policy)?)
             }
             ProposalIn::Update(update) => {
                 let sender_context =
@@ -149,8 +153,12 @@ impl AddProposalIn {
         crypto: &impl OpenMlsCrypto,
         protocol_version: ProtocolVersion,
         ciphersuite: Ciphersuite,
+        clock: &impl OpenMlsClock,
+        policy: &ValidationPolicy,
     ) -> Result<AddProposal, ValidationError> {
-        let key_package = self.key_package.validate(crypto, protocol_version)?;
+        let key_package =
+            self.key_package
+                .validate_with_policy(crypto, protocol_version, clock, policy)?;
         // Verify that the ciphersuite is valid
         if key_package.ciphersuite() != ciphersuite {
             return Err(ValidationError::InvalidAddProposalCiphersuite);
@@ -232,10 +240,12 @@ impl ProposalOrRefIn {
         crypto: &impl OpenMlsCrypto,
         ciphersuite: Ciphersuite,
         protocol_version: ProtocolVersion,
+        clock: &impl OpenMlsClock,
+        policy: &ValidationPolicy,
     ) -> Result<ProposalOrRef, ValidationError> {
         Ok(match self {
             ProposalOrRefIn::Proposal(proposal_in) => ProposalOrRef::Proposal(
-                proposal_in.validate(crypto, ciphersuite, None, protocol_version)?,
+                proposal_in.validate(crypto, ciphersuite, None, protocol_version, clock, policy)?,
             ),
             ProposalOrRefIn::Reference(reference) => ProposalOrRef::Reference(reference),
         })
@@ -17,7 +17,7 @@ fn export_group_info(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider)
     let (group_alice, _, signer, pk) = setup_alice_group(ciphersuite, provider);
 
     let group_info: GroupInfo = group_alice
-        .export_group_info(provider.crypto(), &signer, true)
+        .export_group_info(provider, &signer, true)
         .unwrap();
 
     let verifiable_group_info = {
@@ -14,8 +14,8 @@ use crate::{
     },
     extensions::Extensions,
     group::{
-        config::CryptoConfig, errors::WelcomeError, GroupContext, GroupId, MlsGroup,
-        MlsGroupConfigBuilder,
+        config::CryptoConfig, errors::WelcomeError, test_core_group::setup_client, GroupContext,
+        GroupId, MlsGroup, MlsGroupConfigBuilder,
     },
     messages::{
         group_info::{GroupInfoTBS, VerifiableGroupInfo},
@@ -72,7 +72,8 @@ fn test_welcome_context_mismatch(ciphersuite: Ciphersuite, provider: &impl OpenM
 
     let (_queued_message, welcome, _group_info) = alice_group
         .add_members(provider, &alice_signer, &[bob_kp.clone()])
-        .expect("Could not add member to group.");
+        .expect("Could not add member to group.")
+        .into_parts();
 
     alice_group
         .merge_pending_commit(provider)
@@ -325,3 +326,74 @@ fn invalid_welcomes() {
     let msg = Welcome::tls_deserialize(&mut bytes);
     assert!(msg.is_err());
 }
+
+#[apply(ciphersuites_and_providers)]
+fn split_welcome(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential, bob_kpb, _bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+    let (_charlie_credential, charlie_kpb, _charlie_signer, _charlie_pk) =
+        setup_client("Charlie", ciphersuite, provider);
+    let bob_ref = bob_kpb.key_package().hash_ref(provider.crypto()).unwrap();
+    let charlie_ref = charlie_kpb
+        .key_package()
+        .hash_ref(provider.crypto())
+        .unwrap();
+
+    let mls_group_config = MlsGroupConfigBuilder::new()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .use_ratchet_tree_extension(true)
+        .build();
+    let mut alice_group = MlsGroup::new_with_group_id(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        GroupId::from_slice(b"Test Group"),
+        alice_credential_with_key,
+    )
+    .expect("An unexpected error occurred.");
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(
+            provider,
+            &alice_signer,
+            &[
+                bob_kpb.key_package().clone(),
+                charlie_kpb.key_package().clone(),
+            ],
+        )
+        .expect("Could not add members.")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging pending commit");
+    let welcome = welcome.into_welcome().expect("Unexpected message type.");
+
+    let split_welcomes = welcome.split();
+    assert_eq!(split_welcomes.len(), 2);
+    for (new_member, split_welcome) in &split_welcomes {
+        assert_eq!(split_welcome.secrets().len(), 1);
+        assert_eq!(&split_welcome.secrets()[0].new_member(), new_member);
+        assert_eq!(
+            split_welcome.encrypted_group_info(),
+            welcome.encrypted_group_info()
+        );
+        assert_eq!(
+            welcome.for_new_member(new_member).as_ref(),
+            Some(split_welcome)
+        );
+    }
+    assert!(split_welcomes
+        .iter()
+        .any(|(new_member, _)| new_member == &charlie_ref));
+
+    // Bob can join with his part of the Welcome message, which doesn't
+    // contain the secrets of Charlie.
+    let bob_welcome = welcome
+        .for_new_member(&bob_ref)
+        .expect("No secrets for Bob.");
+    assert!(bob_welcome.for_new_member(&charlie_ref).is_none());
+    let bob_group = MlsGroup::new_from_welcome(provider, &mls_group_config, bob_welcome, None)
+        .expect("Error joining from the split Welcome.");
+    assert_eq!(bob_group.epoch(), alice_group.epoch());
+}
@@ -0,0 +1,319 @@
+//! # Metrics
+//!
+//! OpenMLS reports protocol-level events through a global [`Metrics`]
+//! recorder, so that operators can monitor the health of the protocol across
+//! all groups of a client or a delivery service, e.g., alert on a rising rate
+//! of rejected commits or decryption failures.
+//!
+//! The recorder is installed per process with [`set_metrics()`], similar to a
+//! logger of the `log` crate, since metrics are also reported deep inside the
+//! protocol where no group configuration is at hand, e.g., by the sender
+//! ratchets. The recorder can be removed with [`take_metrics()`], e.g., to
+//! install another one or in tests. Without a recorder, no metrics are
+//! collected. The recorder is called synchronously on the thread that
+//! executes the operation and must therefore be cheap, e.g., increment an
+//! atomic counter or forward the event to a metrics library.
+//!
+//! The following events are reported:
+//!
+//! * [`Counter::EpochAdvanced`] whenever a group, including a
+//!   [`PublicGroup`](crate::group::PublicGroup), merges a commit.
+//! * [`Counter::CommitRejected`] whenever processing a commit fails, with a
+//!   [`CommitRejectionReason`].
+//! * [`Counter::DecryptionFailed`] whenever an incoming message can't be
+//!   decrypted.
+//! * [`Counter::KeyPackageConsumed`] whenever a key package is used to join a
+//!   group from a Welcome message.
+//! * [`Histogram::OutOfOrderDistance`] whenever a message is decrypted that
+//!   was not the next expected message of its sender.
+//!
+//! No metric carries information that identifies a group or a member.
+
+use std::sync::{Arc, PoisonError, RwLock};
+
+use thiserror::Error;
+
+use crate::{
+    framing::ContentType,
+    group::errors::{ProcessMessageError, StageCommitError, ValidationError},
+};
+
+/// A recorder for the metrics reported by OpenMLS.
+pub trait Metrics: Send + Sync {
+    /// Increment `counter` by one.
+    fn increment_counter(&self, counter: Counter);
+
+    /// Record `value` in `histogram`.
+    fn record_histogram(&self, histogram: Histogram, value: u64);
+}
+
+/// The counters reported by OpenMLS.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Counter {
+    /// A group advanced to a new epoch by merging a commit.
+    EpochAdvanced,
+    /// An incoming commit was rejected.
+    CommitRejected(CommitRejectionReason),
+    /// An incoming message could not be decrypted.
+    DecryptionFailed,
+    /// A key package was consumed by joining a group from a Welcome message.
+    KeyPackageConsumed,
+}
+
+impl Counter {
+    /// Returns the name of the counter, e.g., for use as a metric name.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Counter::EpochAdvanced => "openmls_epochs_advanced",
+            Counter::CommitRejected(_) => "openmls_commits_rejected",
+            Counter::DecryptionFailed => "openmls_decryption_failures",
+            Counter::KeyPackageConsumed => "openmls_key_packages_consumed",
+        }
+    }
+}
+
+/// The reason why an incoming commit was rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum CommitRejectionReason {
+    /// The commit is for a different epoch.
+    WrongEpoch,
+    /// The signature or the membership tag of the commit is invalid, or the
+    /// sender is not authorized.
+    Authentication,
+    /// The commit could not be decrypted.
+    Decryption,
+    /// The confirmation tag is missing or doesn't match.
+    ConfirmationTag,
+    /// The proposals covered by the commit are missing or invalid.
+    Proposals,
+    /// The update path is missing or invalid.
+    UpdatePath,
+    /// The commit was rejected for another reason.
+    Other,
+}
+
+impl CommitRejectionReason {
+    /// Returns the reason as a string, e.g., for use as a metric label.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CommitRejectionReason::WrongEpoch => "wrong_epoch",
+            CommitRejectionReason::Authentication => "authentication",
+            CommitRejectionReason::Decryption => "decryption",
+            CommitRejectionReason::ConfirmationTag => "confirmation_tag",
+            CommitRejectionReason::Proposals => "proposals",
+            CommitRejectionReason::UpdatePath => "update_path",
+            CommitRejectionReason::Other => "other",
+        }
+    }
+
+    fn from_validation_error(error: &ValidationError) -> Self {
+        match error {
+            ValidationError::WrongEpoch | ValidationError::NoPastEpochData => Self::WrongEpoch,
+            ValidationError::UnknownMember
+            | ValidationError::MissingMembershipTag
+            | ValidationError::InvalidMembershipTag
+            | ValidationError::InvalidSignature
+            | ValidationError::UnauthorizedExternalSender
+            | ValidationError::AmbiguousExternalSender
+            | ValidationError::InvalidSenderType => Self::Authentication,
+            ValidationError::UnableToDecrypt(_) => Self::Decryption,
+            ValidationError::MissingConfirmationTag => Self::ConfirmationTag,
+            ValidationError::NoPath
+            | ValidationError::UpdatePathError(_)
+            | ValidationError::InvalidLeafNodeSignature
+            | ValidationError::InvalidLeafNodeSourceType => Self::UpdatePath,
+            ValidationError::CommitterIncludedOwnUpdate
+            | ValidationError::InvalidAddProposalCiphersuite
+            | ValidationError::KeyPackageVerifyError(_) => Self::Proposals,
+            _ => Self::Other,
+        }
+    }
+
+    fn from_stage_commit_error(error: &StageCommitError) -> Self {
+        match error {
+            StageCommitError::EpochMismatch => Self::WrongEpoch,
+            StageCommitError::InconsistentSenderIndex
+            | StageCommitError::SenderTypeExternal
+            | StageCommitError::SenderTypeNewMemberProposal => Self::Authentication,
+            StageCommitError::ConfirmationTagMissing
+            | StageCommitError::ConfirmationTagMismatch => Self::ConfirmationTag,
+            StageCommitError::AttemptedSelfRemoval
+            | StageCommitError::MissingProposal
+            | StageCommitError::TooManyNewMembers
+            | StageCommitError::ProposalValidationError(_)
+            | StageCommitError::PskError(_) => Self::Proposals,
+            StageCommitError::PathLeafNodeVerificationFailure
+            | StageCommitError::RequiredPathNotFound
+            | StageCommitError::UpdatePathError(_)
+            | StageCommitError::MissingDecryptionKey => Self::UpdatePath,
+            _ => Self::Other,
+        }
+    }
+}
+
+/// The histograms reported by OpenMLS.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Histogram {
+    /// The distance in generations between a decrypted message and the next
+    /// message that was expected from its sender. Messages that arrive in
+    /// order are not recorded.
+    OutOfOrderDistance,
+}
+
+impl Histogram {
+    /// Returns the name of the histogram, e.g., for use as a metric name.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Histogram::OutOfOrderDistance => "openmls_out_of_order_distance",
+        }
+    }
+}
+
+/// Error setting the metrics recorder.
+#[derive(Error, Debug, PartialEq, Eq, Clone)]
+pub enum SetMetricsError {
+    /// A metrics recorder has already been set.
+    #[error("A metrics recorder has already been set.")]
+    AlreadySet,
+}
+
+static METRICS: RwLock<Option<Arc<dyn Metrics>>> = RwLock::new(None);
+
+/// Set the global metrics recorder.
+///
+/// Returns an error if a recorder has already been set. To replace the
+/// recorder, remove it with [`take_metrics()`] first.
+pub fn set_metrics(metrics: Arc<dyn Metrics>) -> Result<(), SetMetricsError> {
+    let mut installed = METRICS.write().unwrap_or_else(PoisonError::into_inner);
+    if installed.is_some() {
+        return Err(SetMetricsError::AlreadySet);
+    }
+    *installed = Some(metrics);
+    Ok(())
+}
+
+/// Remove the global metrics recorder and return it, if one was set. No
+/// metrics are collected until a new recorder is set.
+pub fn take_metrics() -> Option<Arc<dyn Metrics>> {
+    METRICS
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .take()
+}
+
+// The recorder is cloned out of the lock, so that it is not held while the
+// recorder runs.
+fn metrics() -> Option<Arc<dyn Metrics>> {
+    METRICS
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+}
+
+fn has_metrics() -> bool {
+    METRICS
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .is_some()
+}
+
+/// A metric event.
+#[derive(Debug, Clone, Copy)]
+enum Event {
+    Counter(Counter),
+    Histogram(Histogram, u64),
+}
+
+#[cfg(feature = "async")]
+thread_local! {
+    // Events of the operation that is executed on this thread are buffered
+    // here while they are deferred.
+    static DEFERRED: std::cell::RefCell<Option<Vec<Event>>> = const { std::cell::RefCell::new(None) };
+}
+
+fn record(event: Event) {
+    if !has_metrics() {
+        return;
+    }
+    #[cfg(feature = "async")]
+    {
+        let deferred = DEFERRED.with(|deferred| match deferred.borrow_mut().as_mut() {
+            Some(events) => {
+                events.push(event);
+                true
+            }
+            None => false,
+        });
+        if deferred {
+            return;
+        }
+    }
+    report(event);
+}
+
+fn report(event: Event) {
+    if let Some(metrics) = metrics() {
+        match event {
+            Event::Counter(counter) => metrics.increment_counter(counter),
+            Event::Histogram(histogram, value) => metrics.record_histogram(histogram, value),
+        }
+    }
+}
+
+pub(crate) fn increment_counter(counter: Counter) {
+    record(Event::Counter(counter));
+}
+
+pub(crate) fn record_histogram(histogram: Histogram, value: u64) {
+    record(Event::Histogram(histogram, value));
+}
+
+/// Metrics that were reported by an operation executed with [`deferred()`].
+#[cfg(feature = "async")]
+#[must_use]
+pub(crate) struct DeferredMetrics(Vec<Event>);
+
+#[cfg(feature = "async")]
+impl DeferredMetrics {
+    /// Report the metrics to the recorder. Metrics that are dropped instead
+    /// are discarded.
+    pub(crate) fn report(self) {
+        self.0.into_iter().for_each(report);
+    }
+}
+
+/// Execute `operation` and return the metrics it reported instead of reporting
+/// them, e.g., because the operation may be executed again.
+#[cfg(feature = "async")]
+pub(crate) fn deferred<T>(operation: impl FnOnce() -> T) -> (T, DeferredMetrics) {
+    let previous = DEFERRED.with(|deferred| deferred.replace(Some(Vec::new())));
+    let result = operation();
+    let events = DEFERRED.with(|deferred| deferred.replace(previous));
+    (result, DeferredMetrics(events.unwrap_or_default()))
+}
+
+/// Report the failure to process a message with the given `content_type`.
+pub(crate) fn record_process_message_error(content_type: ContentType, error: &ProcessMessageError) {
+    if !has_metrics() {
+        return;
+    }
+    if let ProcessMessageError::ValidationError(ValidationError::UnableToDecrypt(_)) = error {
+        increment_counter(Counter::DecryptionFailed);
+    }
+    if content_type == ContentType::Commit {
+        let reason = match error {
+            ProcessMessageError::ValidationError(e) => {
+                CommitRejectionReason::from_validation_error(e)
+            }
+            ProcessMessageError::InvalidCommit(e) => {
+                CommitRejectionReason::from_stage_commit_error(e)
+            }
+            ProcessMessageError::InvalidSignature => CommitRejectionReason::Authentication,
+            _ => CommitRejectionReason::Other,
+        };
+        increment_counter(Counter::CommitRejected(reason));
+    }
+}
@@ -2,12 +2,25 @@
 //! Include this to get access to all the public functions of OpenMLS.
 
 // MlsGroup
-pub use crate::group::{config::CryptoConfig, core_group::Member, ser::*, *};
+pub use crate::group::{
+    config::{CommitLimits, CryptoConfig, ValidationPolicy, ValidationSeverity},
+    core_group::Member,
+    ser::*,
+    *,
+};
 
 pub use crate::group::public_group::{errors::*, process::*, *};
 
 // Ciphersuite
-pub use crate::ciphersuite::{hash_ref::KeyPackageRef, signable::*, signature::*, *};
+pub use crate::ciphersuite::{
+    hash_ref::{KeyPackageRef, MessageRef},
+    signable::*,
+    signature::*,
+    *,
+};
+
+// Codec
+pub use crate::codec::*;
 
 // Messages
 pub use crate::messages::{external_proposals::*, proposals::*, proposals_in::*, *};
@@ -24,11 +37,14 @@ pub use crate::extensions::{errors::*, *};
 // Framing
 pub use crate::framing::{message_in::*, message_out::*, sender::*, validation::*, *};
 
+// Inspection
+pub use crate::inspect::{errors::*, *};
+
 // Key packages
 pub use crate::key_packages::{errors::*, *};
 
 // Tree
-pub use crate::tree::sender_ratchet::SenderRatchetConfiguration;
+pub use crate::tree::sender_ratchet::{SenderRatchetConfiguration, SenderSelector};
 
 // Binary tree
 pub use crate::binary_tree::LeafNodeIndex;
@@ -46,6 +46,15 @@ impl MessageSecrets {
         }
     }
 
+    /// Returns the approximate number of bytes used by the message secrets.
+    pub(crate) fn memory_usage(&self, ciphersuite: Ciphersuite) -> usize {
+        // The sender data secret, the membership key and the confirmation key
+        // all have the length of the hash output.
+        3 * ciphersuite.hash_length()
+            + self.serialized_context.len()
+            + self.secret_tree.memory_usage(ciphersuite)
+    }
+
     /// Get a reference to the message secrets's sender data secret.
     pub(crate) fn sender_data_secret(&self) -> &SenderDataSecret {
         &self.sender_data_secret
@@ -66,6 +75,11 @@ impl MessageSecrets {
         self.serialized_context.as_ref()
     }
 
+    /// Get a reference to the message secrets's secret tree.
+    pub(crate) fn secret_tree(&self) -> &SecretTree {
+        &self.secret_tree
+    }
+
     /// Get a mutable reference to the message secrets's secret tree.
     pub(crate) fn secret_tree_mut(&mut self) -> &mut SecretTree {
         &mut self.secret_tree
@@ -183,8 +183,8 @@ impl ResumptionPskSecret {
 
 /// A secret that can be used among members to make sure everyone has the same
 /// group state.
-#[derive(Debug, Serialize, Deserialize)]
-#[cfg_attr(test, derive(Eq, PartialEq, Clone))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(Eq, PartialEq))]
 pub struct EpochAuthenticator {
     secret: Secret,
 }
@@ -202,13 +202,25 @@ impl EpochAuthenticator {
     pub fn as_slice(&self) -> &[u8] {
         self.secret.as_slice()
     }
+
+    /// Derive a verification code of `length` bytes that is bound to the
+    /// given `context`.
+    pub(crate) fn derive_verification_code(
+        &self,
+        crypto: &impl OpenMlsCrypto,
+        context: &[u8],
+        length: usize,
+    ) -> Result<Vec<u8>, CryptoError> {
+        self.secret
+            .kdf_expand_label(crypto, "verification code", context, length)
+            .map(|secret| secret.as_slice().to_vec())
+    }
 }
 
 // Crate-only types
 
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 #[cfg_attr(test, derive(PartialEq))]
-#[cfg_attr(any(feature = "test-utils", test), derive(Clone))]
 pub(crate) struct CommitSecret {
     secret: Secret,
 }
@@ -681,6 +693,10 @@ impl EncryptionSecret {
     }
 }
 
+/// The label that the secrets of sub-exporters are exported with. See
+/// [`ExporterSecret::derive_sub_exporter_secret()`].
+pub(crate) const SUB_EXPORTER_LABEL: &str = "sub-exporter";
+
 /// A secret that we can derive secrets from, that are used outside of OpenMLS.
 #[derive(Debug, Serialize, Deserialize)]
 #[cfg_attr(test, derive(PartialEq, Clone))]
@@ -719,6 +735,24 @@ impl ExporterSecret {
             .as_slice()
             .to_vec())
     }
+
+    /// Derive the `ExporterSecret` of the sub-exporter with the given
+    /// `namespace`. Its value is `MLS-Exporter(SUB_EXPORTER_LABEL, namespace,
+    /// Nh)`, so exporting from it is domain-separated from exporting with any
+    /// other label.
+    pub(crate) fn derive_sub_exporter_secret(
+        &self,
+        ciphersuite: Ciphersuite,
+        crypto: &impl OpenMlsCrypto,
+        namespace: &[u8],
+    ) -> Result<Self, CryptoError> {
+        let context_hash = &crypto.hash(ciphersuite.hash_algorithm(), namespace)?;
+        let secret = self
+            .secret
+            .derive_secret(crypto, SUB_EXPORTER_LABEL)?
+            .kdf_expand_label(crypto, "exported", context_hash, ciphersuite.hash_length())?;
+        Ok(Self { secret })
+    }
 }
 
 /// A secret used when joining a group with an external Commit.
@@ -744,6 +778,23 @@ impl ExternalSecret {
         crypto.derive_hpke_keypair(ciphersuite.hpke_config(), self.secret.as_slice())
     }
 
+    /// Derive the external keypair of the given rotation `period` for
+    /// External Commits.
+    pub(crate) fn derive_rotated_external_keypair(
+        &self,
+        crypto: &impl OpenMlsCrypto,
+        ciphersuite: Ciphersuite,
+        period: u64,
+    ) -> Result<HpkeKeyPair, CryptoError> {
+        let secret = self.secret.kdf_expand_label(
+            crypto,
+            "external rotation",
+            &period.to_be_bytes(),
+            ciphersuite.hash_length(),
+        )?;
+        Ok(crypto.derive_hpke_keypair(ciphersuite.hpke_config(), secret.as_slice()))
+    }
+
     #[cfg(any(feature = "test-utils", test))]
     pub(crate) fn as_slice(&self) -> &[u8] {
         self.secret.as_slice()
@@ -947,12 +998,10 @@ impl SenderDataSecret {
         }
     }
 
-    #[cfg(any(feature = "test-utils", test))]
     pub(crate) fn as_slice(&self) -> &[u8] {
         self.secret.as_slice()
     }
 
-    #[cfg(any(feature = "test-utils", test))]
     /// Create a new secret from a byte vector.
     pub(crate) fn from_slice(
         bytes: &[u8],
@@ -1,5 +1,7 @@
 //! # Preshared keys.
 
+#![deny(clippy::unwrap_used, clippy::expect_used)]
+
 use openmls_traits::{
     key_store::{MlsEntity, MlsEntityId, OpenMlsKeyStore},
     random::OpenMlsRand,
@@ -317,13 +319,17 @@ impl PreSharedKeyId {
 
     // ----- Validation ----------------------------------------------------------------------------
 
-    pub(crate) fn validate_in_proposal(self, ciphersuite: Ciphersuite) -> Result<Self, PskError> {
+    pub(crate) fn validate_in_proposal(
+        self,
+        ciphersuite: Ciphersuite,
+        allowed_usages: &[ResumptionPskUsage],
+    ) -> Result<Self, PskError> {
         // ValSem402
         match self.psk() {
             Psk::Resumption(resumption_psk) => {
-                if resumption_psk.usage != ResumptionPskUsage::Application {
+                if !allowed_usages.contains(&resumption_psk.usage) {
                     return Err(PskError::UsageMismatch {
-                        allowed: vec![ResumptionPskUsage::Application],
+                        allowed: allowed_usages.to_vec(),
                         got: resumption_psk.usage,
                     });
                 }
@@ -474,14 +480,17 @@ pub(crate) fn load_psks<'p>(
         log_crypto!(trace, "PSK store {:?}", resumption_psk_store);
 
         match &psk_id.psk {
-            Psk::Resumption(resumption) => {
+            Psk::Resumption(resumption) if resumption.usage == ResumptionPskUsage::Application => {
                 if let Some(psk_bundle) = resumption_psk_store.get(resumption.psk_epoch()) {
                     psk_bundles.push((psk_id, psk_bundle.secret.clone()));
                 } else {
                     return Err(PskError::KeyNotFound);
                 }
             }
-            Psk::External(_) => {
+            // Resumption PSKs for branching and reinitialization belong to
+            // another group and are provided through the key store, like
+            // external PSKs.
+            Psk::Resumption(_) | Psk::External(_) => {
                 if let Some(psk_bundle) = key_store.read::<PskBundle>(&psk_id.keystore_id()?) {
                     psk_bundles.push((psk_id, psk_bundle.secret));
                 } else {
@@ -545,5 +554,13 @@ pub mod store {
                 .find(|&(e, _s)| e == &epoch)
                 .map(|(_e, s)| s)
         }
+
+        /// Returns an iterator over the epochs and resumption PSKs in the
+        /// store.
+        pub(crate) fn iter(&self) -> impl Iterator<Item = (GroupEpoch, &ResumptionPskSecret)> {
+            self.resumption_psk
+                .iter()
+                .map(|(epoch, secret)| (*epoch, secret))
+        }
     }
 }
@@ -0,0 +1,367 @@
+//! # Security events
+//!
+//! OpenMLS reports notable events that may indicate an attack or a
+//! misbehaving client to a global [`SecurityEventHandler`], so that
+//! applications can centralize their incident response instead of
+//! pattern-matching the errors of every operation.
+//!
+//! The handler is installed per process with
+//! [`set_security_event_handler()`], like the recorder of the
+//! [`metrics`](crate::metrics), since events are also reported where no group
+//! configuration is at hand, e.g., when a Welcome message or a message of a
+//! [`PublicGroup`] is rejected. The events carry the group ID, so that a
+//! handler can dispatch them per group. The handler can be removed with
+//! [`take_security_event_handler()`], e.g., to install another one or in
+//! tests. Without a handler, no events are reported.
+//! The handler is called synchronously on the thread that executes the
+//! operation and should therefore return quickly, e.g., by forwarding the
+//! event to a queue.
+//!
+//! The following events are reported:
+//!
+//! * [`SecurityEventKind::SignatureVerificationFailed`] when the signature or
+//!   the membership tag of an incoming message, the signature of a leaf node
+//!   or key package it contains, or the signature of the group info in a
+//!   Welcome message is invalid.
+//! * [`SecurityEventKind::ReplayDetected`] when an incoming message is
+//!   encrypted with key material that was already used to decrypt another
+//!   message.
+//! * [`SecurityEventKind::DowngradeAttempt`] when an incoming proposal or
+//!   commit adds a key package with a different protocol version or
+//!   ciphersuite than the group, or when the key package used to join a
+//!   group from a Welcome message doesn't match the version or ciphersuite of
+//!   the group.
+//! * [`SecurityEventKind::ExternalCommitByUnknownIdentity`] when an incoming
+//!   external commit is valid, but the identity of the joiner doesn't match
+//!   the identity of any current member.
+//! * [`SecurityEventKind::LifetimeViolation`] when an incoming proposal or
+//!   commit adds a key package whose lifetime doesn't cover the current time.
+//! * [`SecurityEventKind::MessageKeyEvicted`] when the key of a skipped
+//!   message is evicted to stay within the limits on skipped keys of the
+//!   [`SenderRatchetConfiguration`](crate::tree::sender_ratchet::SenderRatchetConfiguration).
+//!   A sender that skips many generations may try to exhaust the memory of
+//!   the receivers, and the late message with that key can't be decrypted
+//!   anymore.
+//!
+//! The events are reported in addition to the errors that are returned, the
+//! behaviour of the operations doesn't change.
+
+use std::sync::{Arc, PoisonError, RwLock};
+
+use thiserror::Error;
+
+use crate::{
+    binary_tree::LeafNodeIndex,
+    credentials::Credential,
+    framing::{errors::MessageDecryptionError, ProcessedMessage, ProcessedMessageContent, Sender},
+    group::{
+        errors::{
+            ProcessMessageError, ProposalValidationError, StageCommitError, ValidationError,
+            WelcomeError,
+        },
+        GroupEpoch, GroupId, PublicGroup,
+    },
+    key_packages::errors::KeyPackageVerifyError,
+    tree::secret_tree::SecretTreeError,
+};
+
+/// A handler for the security events reported by OpenMLS.
+pub trait SecurityEventHandler: Send + Sync {
+    /// Handle `event`.
+    fn handle_event(&self, event: &SecurityEvent);
+}
+
+/// A security event reported by OpenMLS. See the
+/// [module documentation](self) for details.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SecurityEvent {
+    kind: SecurityEventKind,
+    group_id: Option<GroupId>,
+    epoch: Option<GroupEpoch>,
+}
+
+impl SecurityEvent {
+    /// Returns the kind of the event.
+    pub fn kind(&self) -> &SecurityEventKind {
+        &self.kind
+    }
+
+    /// Returns the ID of the group the event occurred in, or `None` if the
+    /// group is not known, e.g., because a Welcome message couldn't be
+    /// decrypted.
+    pub fn group_id(&self) -> Option<&GroupId> {
+        self.group_id.as_ref()
+    }
+
+    /// Returns the epoch of the group the event occurred in, or `None` if the
+    /// group is not known.
+    pub fn epoch(&self) -> Option<GroupEpoch> {
+        self.epoch
+    }
+}
+
+/// The kinds of security events reported by OpenMLS.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum SecurityEventKind {
+    /// A signature or membership tag could not be verified.
+    SignatureVerificationFailed,
+    /// A message was encrypted with key material that was already used.
+    ReplayDetected,
+    /// A protocol version or ciphersuite doesn't match the group.
+    DowngradeAttempt,
+    /// A new member with the given credential joined through an external
+    /// commit, but its identity doesn't match the identity of any current
+    /// member.
+    ExternalCommitByUnknownIdentity(Credential),
+    /// A lifetime doesn't cover the current time.
+    LifetimeViolation,
+    /// The key of a skipped message of the sender at the given leaf index
+    /// and generation was evicted.
+    MessageKeyEvicted {
+        /// The leaf index of the sender.
+        sender: LeafNodeIndex,
+        /// The generation of the evicted key.
+        generation: u32,
+    },
+}
+
+/// Error setting the security event handler.
+#[derive(Error, Debug, PartialEq, Eq, Clone)]
+pub enum SetSecurityEventHandlerError {
+    /// A security event handler has already been set.
+    #[error("A security event handler has already been set.")]
+    AlreadySet,
+}
+
+static HANDLER: RwLock<Option<Arc<dyn SecurityEventHandler>>> = RwLock::new(None);
+
+/// Set the global security event handler.
+///
+/// Returns an error if a handler has already been set. To replace the
+/// handler, remove it with [`take_security_event_handler()`] first.
+pub fn set_security_event_handler(
+    handler: Arc<dyn SecurityEventHandler>,
+) -> Result<(), SetSecurityEventHandlerError> {
+    let mut installed = HANDLER.write().unwrap_or_else(PoisonError::into_inner);
+    if installed.is_some() {
+        return Err(SetSecurityEventHandlerError::AlreadySet);
+    }
+    *installed = Some(handler);
+    Ok(())
+}
+
+/// Remove the global security event handler and return it, if one was set.
+/// No events are reported until a new handler is set.
+pub fn take_security_event_handler() -> Option<Arc<dyn SecurityEventHandler>> {
+    HANDLER
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .take()
+}
+
+// The handler is cloned out of the lock, so that it is not held while the
+// handler runs.
+fn handler() -> Option<Arc<dyn SecurityEventHandler>> {
+    HANDLER
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+}
+
+fn has_handler() -> bool {
+    HANDLER
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .is_some()
+}
+
+#[cfg(feature = "async")]
+thread_local! {
+    // Events of the operation that is executed on this thread are buffered
+    // here while they are deferred.
+    static DEFERRED: std::cell::RefCell<Option<Vec<SecurityEvent>>> = const { std::cell::RefCell::new(None) };
+}
+
+fn record(kind: SecurityEventKind, group_id: Option<&GroupId>, epoch: Option<GroupEpoch>) {
+    if !has_handler() {
+        return;
+    }
+    let event = SecurityEvent {
+        kind,
+        group_id: group_id.cloned(),
+        epoch,
+    };
+    #[cfg(feature = "async")]
+    let event = match DEFERRED.with(|deferred| match deferred.borrow_mut().as_mut() {
+        Some(events) => {
+            events.push(event);
+            None
+        }
+        None => Some(event),
+    }) {
+        Some(event) => event,
+        None => return,
+    };
+    report(&event);
+}
+
+fn report(event: &SecurityEvent) {
+    if let Some(handler) = handler() {
+        handler.handle_event(event);
+    }
+}
+
+/// Security events that were reported by an operation executed with
+/// [`deferred()`].
+#[cfg(feature = "async")]
+#[must_use]
+pub(crate) struct DeferredSecurityEvents(Vec<SecurityEvent>);
+
+#[cfg(feature = "async")]
+impl DeferredSecurityEvents {
+    /// Report the events to the handler. Events that are dropped instead are
+    /// discarded.
+    pub(crate) fn report(self) {
+        self.0.iter().for_each(report);
+    }
+}
+
+/// Execute `operation` and return the security events it reported instead of
+/// reporting them, e.g., because the operation may be executed again.
+#[cfg(feature = "async")]
+pub(crate) fn deferred<T>(operation: impl FnOnce() -> T) -> (T, DeferredSecurityEvents) {
+    let previous = DEFERRED.with(|deferred| deferred.replace(Some(Vec::new())));
+    let result = operation();
+    let events = DEFERRED.with(|deferred| deferred.replace(previous));
+    (result, DeferredSecurityEvents(events.unwrap_or_default()))
+}
+
+/// Report the security event that caused the failure to process a message in
+/// the given group, if any.
+pub(crate) fn record_process_message_error(
+    group_id: &GroupId,
+    epoch: GroupEpoch,
+    error: &ProcessMessageError,
+) {
+    if !has_handler() {
+        return;
+    }
+    let kind = match error {
+        ProcessMessageError::InvalidSignature => SecurityEventKind::SignatureVerificationFailed,
+        ProcessMessageError::ValidationError(e) => match validation_error_kind(e) {
+            Some(kind) => kind,
+            None => return,
+        },
+        ProcessMessageError::InvalidCommit(e) => match stage_commit_error_kind(e) {
+            Some(kind) => kind,
+            None => return,
+        },
+        _ => return,
+    };
+    record(kind, Some(group_id), Some(epoch));
+}
+
+fn validation_error_kind(error: &ValidationError) -> Option<SecurityEventKind> {
+    match error {
+        ValidationError::InvalidSignature
+        | ValidationError::InvalidMembershipTag
+        | ValidationError::InvalidLeafNodeSignature => {
+            Some(SecurityEventKind::SignatureVerificationFailed)
+        }
+        ValidationError::UnableToDecrypt(MessageDecryptionError::SecretTreeError(
+            SecretTreeError::SecretReuseError,
+        )) => Some(SecurityEventKind::ReplayDetected),
+        ValidationError::InvalidAddProposalCiphersuite => Some(SecurityEventKind::DowngradeAttempt),
+        ValidationError::KeyPackageVerifyError(e) => key_package_verify_error_kind(e),
+        _ => None,
+    }
+}
+
+fn stage_commit_error_kind(error: &StageCommitError) -> Option<SecurityEventKind> {
+    match error {
+        StageCommitError::PathLeafNodeVerificationFailure => {
+            Some(SecurityEventKind::SignatureVerificationFailed)
+        }
+        StageCommitError::ProposalValidationError(
+            ProposalValidationError::InvalidAddProposalCiphersuiteOrVersion,
+        ) => Some(SecurityEventKind::DowngradeAttempt),
+        _ => None,
+    }
+}
+
+fn key_package_verify_error_kind(error: &KeyPackageVerifyError) -> Option<SecurityEventKind> {
+    match error {
+        KeyPackageVerifyError::InvalidSignature
+        | KeyPackageVerifyError::InvalidLeafNodeSignature => {
+            Some(SecurityEventKind::SignatureVerificationFailed)
+        }
+        KeyPackageVerifyError::InvalidProtocolVersion => Some(SecurityEventKind::DowngradeAttempt),
+        KeyPackageVerifyError::InvalidLifetime => Some(SecurityEventKind::LifetimeViolation),
+        _ => None,
+    }
+}
+
+/// Report the security event that caused the failure to join a group from a
+/// Welcome message, if any.
+pub(crate) fn record_welcome_error<KeyStoreError>(error: &WelcomeError<KeyStoreError>) {
+    if !has_handler() {
+        return;
+    }
+    let kind = match error {
+        WelcomeError::InvalidGroupInfoSignature => SecurityEventKind::SignatureVerificationFailed,
+        WelcomeError::CiphersuiteMismatch | WelcomeError::VersionMismatch => {
+            SecurityEventKind::DowngradeAttempt
+        }
+        _ => return,
+    };
+    record(kind, None, None);
+}
+
+/// Report the keys of skipped messages that were evicted while decrypting a
+/// message of the given group and epoch, as pairs of the sender and the
+/// generation.
+pub(crate) fn record_evicted_keys(
+    group_id: &GroupId,
+    epoch: GroupEpoch,
+    evicted_keys: Vec<(LeafNodeIndex, u32)>,
+) {
+    for (sender, generation) in evicted_keys {
+        record(
+            SecurityEventKind::MessageKeyEvicted { sender, generation },
+            Some(group_id),
+            Some(epoch),
+        );
+    }
+}
+
+/// Report an external commit in `processed_message` whose joiner doesn't have
+/// the identity of any current member of `public_group`.
+pub(crate) fn record_processed_message(
+    public_group: &PublicGroup,
+    processed_message: &ProcessedMessage,
+) {
+    if !has_handler() {
+        return;
+    }
+    if !matches!(processed_message.sender(), Sender::NewMemberCommit)
+        || !matches!(
+            processed_message.content(),
+            ProcessedMessageContent::StagedCommitMessage(_)
+        )
+    {
+        return;
+    }
+    let identity = processed_message.credential().identity();
+    if public_group
+        .members()
+        .any(|member| member.credential.identity() == identity)
+    {
+        return;
+    }
+    record(
+        SecurityEventKind::ExternalCommitByUnknownIdentity(processed_message.credential().clone()),
+        Some(public_group.group_id()),
+        Some(public_group.group_context().epoch()),
+    );
+}
@@ -0,0 +1,205 @@
+//! This module provides a [`FaultyDeliveryService`], an in-memory delivery
+//! service that injects network faults, to test how clients cope with messages
+//! that arrive late, twice, out of order or not at all.
+//!
+//! Unlike the delivery service of the [`Simulation`](super::simulation), the
+//! faulty delivery service doesn't know anything about groups or epochs. It
+//! queues every message that is sent to a recipient and delivers it according
+//! to the [`Faults`] of that recipient. Commits may therefore overtake each
+//! other and arrive before the commit of the previous epoch, so clients have to
+//! buffer messages of future epochs themselves.
+//!
+//! Time is a logical clock that only advances with
+//! [`FaultyDeliveryService::tick()`]. A message that is delayed by `n` ticks
+//! can be delivered after the clock advanced `n` times.
+//! [`FaultyDeliveryService::flush()`] advances the clock until all queued
+//! messages can be delivered.
+//!
+//! All faults are derived from a single seed, so that a test delivers the same
+//! messages in the same order whenever it sends the same messages in the same
+//! order.
+//!
+//! ```ignore
+//! let mut delivery_service = FaultyDeliveryService::new(7, 2, Faults::default());
+//! delivery_service.set_faults(
+//!     1,
+//!     Faults {
+//!         duplicate_rate: 0.2,
+//!         max_delay: 3,
+//!         reorder: true,
+//!         ..Default::default()
+//!     },
+//! );
+//! delivery_service.send(&[0, 1], &message)?;
+//! delivery_service.tick();
+//! while let Some(message) = delivery_service.deliver(1) {
+//!     // Process the message or buffer it until its epoch is reached.
+//! }
+//! ```
+
+use ::rand::{rngs::StdRng, Rng, SeedableRng};
+use tls_codec::{Deserialize, Serialize};
+
+use crate::framing::*;
+
+/// The faults that a [`FaultyDeliveryService`] injects into the messages for
+/// a recipient. The default injects no faults.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Faults {
+    /// The probability, between `0.0` and `1.0`, that a message is dropped.
+    pub drop_rate: f64,
+    /// The probability, between `0.0` and `1.0`, that a message is delivered
+    /// twice. Both copies are delayed independently.
+    pub duplicate_rate: f64,
+    /// The maximum number of ticks by which a message is delayed. Messages
+    /// that are delayed less may overtake it.
+    pub max_delay: u64,
+    /// Deliver the messages that can be delivered in a random order, instead
+    /// of the order in which they were sent.
+    pub reorder: bool,
+}
+
+/// Counters of what happened in a [`FaultyDeliveryService`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DeliveryStats {
+    /// Messages that were sent to a recipient.
+    pub sent: usize,
+    /// Messages that were dropped on their way to a recipient.
+    pub dropped: usize,
+    /// Messages that were duplicated on their way to a recipient.
+    pub duplicated: usize,
+    /// Messages, including duplicates, that were delivered to a recipient.
+    pub delivered: usize,
+}
+
+/// A message waiting in the queue of a recipient.
+struct Envelope {
+    /// The tick from which on the message can be delivered.
+    due: u64,
+    message: Vec<u8>,
+}
+
+/// An in-memory delivery service that drops, duplicates, delays and reorders
+/// messages. Recipients are identified by their index.
+pub struct FaultyDeliveryService {
+    rng: StdRng,
+    now: u64,
+    faults: Vec<Faults>,
+    queues: Vec<Vec<Envelope>>,
+    stats: DeliveryStats,
+}
+
+impl FaultyDeliveryService {
+    /// Creates a delivery service for `number_of_recipients` recipients that
+    /// all suffer from the same `faults`. The faults are derived from `seed`.
+    pub fn new(seed: u64, number_of_recipients: usize, faults: Faults) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+            now: 0,
+            faults: vec![faults; number_of_recipients],
+            queues: (0..number_of_recipients).map(|_| Vec::new()).collect(),
+            stats: DeliveryStats::default(),
+        }
+    }
+
+    /// Sets the faults for the messages that are sent to `recipient` from now
+    /// on.
+    pub fn set_faults(&mut self, recipient: usize, faults: Faults) {
+        self.faults[recipient] = faults;
+    }
+
+    /// Returns the current tick of the logical clock.
+    pub fn now(&self) -> u64 {
+        self.now
+    }
+
+    /// Returns the counters of the delivery service.
+    pub fn stats(&self) -> DeliveryStats {
+        self.stats
+    }
+
+    /// Sends `message` to each of the `recipients`, subject to their
+    /// [`Faults`].
+    pub fn send(
+        &mut self,
+        recipients: &[usize],
+        message: &MlsMessageOut,
+    ) -> Result<(), tls_codec::Error> {
+        let message = message.tls_serialize_detached()?;
+        for &recipient in recipients {
+            let faults = self.faults[recipient];
+            self.stats.sent += 1;
+            if self.rng.gen_bool(faults.drop_rate) {
+                self.stats.dropped += 1;
+                continue;
+            }
+            let copies = if self.rng.gen_bool(faults.duplicate_rate) {
+                self.stats.duplicated += 1;
+                2
+            } else {
+                1
+            };
+            for _ in 0..copies {
+                let due = self.now + self.rng.gen_range(0..=faults.max_delay);
+                self.queues[recipient].push(Envelope {
+                    due,
+                    message: message.clone(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Advances the logical clock by one tick.
+    pub fn tick(&mut self) {
+        self.now += 1;
+    }
+
+    /// Advances the logical clock until all queued messages can be delivered.
+    pub fn flush(&mut self) {
+        let last_due = self
+            .queues
+            .iter()
+            .flatten()
+            .map(|envelope| envelope.due)
+            .max();
+        if let Some(last_due) = last_due {
+            self.now = self.now.max(last_due);
+        }
+    }
+
+    /// Returns the number of messages, including the delayed ones, that are
+    /// queued for `recipient`.
+    pub fn pending(&self, recipient: usize) -> usize {
+        self.queues[recipient].len()
+    }
+
+    /// Delivers the next message to `recipient`, or returns `None` if no
+    /// queued message can be delivered at the current tick.
+    pub fn deliver(&mut self, recipient: usize) -> Option<MlsMessageIn> {
+        let queue = &mut self.queues[recipient];
+        let due: Vec<usize> = queue
+            .iter()
+            .enumerate()
+            .filter(|(_, envelope)| envelope.due <= self.now)
+            .map(|(index, _)| index)
+            .collect();
+        let index = match due.len() {
+            0 => return None,
+            n if self.faults[recipient].reorder => due[self.rng.gen_range(0..n)],
+            _ => due[0],
+        };
+        let envelope = queue.remove(index);
+        self.stats.delivered += 1;
+        Some(
+            MlsMessageIn::tls_deserialize(&mut envelope.message.as_slice())
+                .expect("error deserializing a message that was serialized before"),
+        )
+    }
+
+    /// Delivers all messages to `recipient` that can be delivered at the
+    /// current tick.
+    pub fn deliver_all(&mut self, recipient: usize) -> Vec<MlsMessageIn> {
+        std::iter::from_fn(|| self.deliver(recipient)).collect()
+    }
+}
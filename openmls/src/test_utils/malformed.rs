@@ -0,0 +1,363 @@
+//! This module generates malformed variants of valid messages for negative
+//! testing.
+//!
+//! [`malformed_messages()`] takes a valid commit, proposal, application
+//! message or welcome and returns one [`MalformedMessage`] per applicable
+//! [`Corruption`]. Each variant is serialized and annotated with the
+//! [`ExpectedError`] with which a recipient rejects it, so that applications
+//! can check that their error handling sees and reports the right error:
+//!
+//! ```ignore
+//! for malformed_message in malformed_messages(provider, &group, &commit)? {
+//!     let result = MlsMessageIn::tls_deserialize(&mut malformed_message.message())
+//!         .map(|message| group.process_message(provider, message.into_protocol_message().unwrap()));
+//!     match result {
+//!         Err(_) => assert_eq!(malformed_message.expected_error(), ExpectedError::Decoding),
+//!         Ok(result) => assert!(malformed_message.is_expected(&result.unwrap_err())),
+//!     }
+//! }
+//! ```
+//!
+//! Every variant only breaks a single check, and the checks are ordered as the
+//! recipient performs them. E.g., the membership tag of a [`PublicMessage`] is
+//! recomputed after its signature was corrupted, so that the recipient
+//! rejects the signature and not the membership tag.
+//!
+//! Recipients reject all variants before they use any key material, so the
+//! variants can be processed in any order and the original message is still
+//! accepted afterwards.
+//!
+//! [`PublicMessage`]: crate::framing::PublicMessage
+
+use std::convert::Infallible;
+
+use openmls_traits::{types::HpkeCiphertext, OpenMlsProvider};
+use tls_codec::{Deserialize, Serialize};
+
+use crate::{
+    ciphersuite::hash_ref::KeyPackageRef,
+    error::{ErrorCode, LibraryError, StableErrorCode},
+    framing::{errors::MessageDecryptionError, *},
+    group::{errors::*, GroupId, MlsGroup},
+    messages::{EncryptedGroupSecrets, GroupSecretsError, Welcome},
+};
+
+/// A way in which a [`MalformedMessage`] is corrupted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Corruption {
+    /// The last byte of the serialized message is cut off, so that the last
+    /// vector of the message is shorter than its length.
+    Truncated,
+    /// The group id of a commit, proposal or application message is changed.
+    WrongGroupId,
+    /// The epoch of a commit, proposal or application message is set to the
+    /// next epoch.
+    WrongEpoch,
+    /// A bit of the signature of a public message is flipped.
+    FlippedSignatureBit,
+    /// A bit of the membership tag of a public message is flipped.
+    BadMembershipTag,
+    /// A bit of the encrypted sender data of a private message is flipped.
+    FlippedSenderDataBit,
+    /// A bit of the ciphertext of a private message is flipped. The start of
+    /// the ciphertext is the sample from which the key for the sender data is
+    /// derived, so the recipient fails to decrypt the sender data.
+    FlippedCiphertextBit,
+    /// The key package references of the new members of a welcome are
+    /// changed.
+    UnknownKeyPackage,
+    /// A bit of the encrypted group secrets of a welcome is flipped.
+    FlippedGroupSecretsBit,
+    /// A bit of the encrypted group info of a welcome is flipped. The
+    /// encrypted group info is bound to the encrypted group secrets, so the
+    /// recipient fails to decrypt the group secrets.
+    FlippedGroupInfoBit,
+}
+
+/// The error with which a recipient rejects a [`MalformedMessage`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExpectedError {
+    /// Deserializing the message with
+    /// [`MlsMessageIn::tls_deserialize()`](MlsMessageIn) fails.
+    Decoding,
+    /// Processing the message with [`MlsGroup::process_message()`], or joining
+    /// with [`MlsGroup::new_from_welcome()`], fails with an error with this
+    /// [`ErrorCode`].
+    Processing(ErrorCode),
+}
+
+/// A serialized malformed variant of a valid message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MalformedMessage {
+    corruption: Corruption,
+    message: Vec<u8>,
+    expected_error: ExpectedError,
+}
+
+impl MalformedMessage {
+    fn new(corruption: Corruption, message: Vec<u8>, expected_error: ExpectedError) -> Self {
+        Self {
+            corruption,
+            message,
+            expected_error,
+        }
+    }
+
+    fn processing(
+        corruption: Corruption,
+        message: &MlsMessageIn,
+        expected_error: impl StableErrorCode,
+    ) -> Result<Self, LibraryError> {
+        let message = message
+            .tls_serialize_detached()
+            .map_err(LibraryError::missing_bound_check)?;
+        Ok(Self::new(
+            corruption,
+            message,
+            ExpectedError::Processing(expected_error.error_code()),
+        ))
+    }
+
+    /// Returns how the message is corrupted.
+    pub fn corruption(&self) -> Corruption {
+        self.corruption
+    }
+
+    /// Returns the serialized message.
+    pub fn message(&self) -> &[u8] {
+        &self.message
+    }
+
+    /// Returns the error with which a recipient rejects the message.
+    pub fn expected_error(&self) -> ExpectedError {
+        self.expected_error
+    }
+
+    /// Returns `true` if `error` is the error with which a recipient is
+    /// expected to reject the message.
+    pub fn is_expected(&self, error: &impl StableErrorCode) -> bool {
+        self.expected_error == ExpectedError::Processing(error.error_code())
+    }
+}
+
+/// Returns the malformed variants of the valid `message`.
+///
+/// Commits, proposals and application messages have to be from the current
+/// epoch of `group`, which is the group of any member of the group, e.g., the
+/// sender before it merges its commit. The `group` is only used to recompute
+/// the membership tag of public messages. Other messages than welcomes, e.g.,
+/// key packages, only have the [`Corruption::Truncated`] variant.
+pub fn malformed_messages(
+    provider: &impl OpenMlsProvider,
+    group: &MlsGroup,
+    message: &MlsMessageOut,
+) -> Result<Vec<MalformedMessage>, LibraryError> {
+    let serialized_message = message
+        .tls_serialize_detached()
+        .map_err(LibraryError::missing_bound_check)?;
+    let message = MlsMessageIn::tls_deserialize(&mut serialized_message.as_slice())
+        .map_err(LibraryError::missing_bound_check)?;
+
+    let mut malformed_messages = vec![MalformedMessage::new(
+        Corruption::Truncated,
+        serialized_message[..serialized_message.len() - 1].to_vec(),
+        ExpectedError::Decoding,
+    )];
+    match &message.body {
+        MlsMessageInBody::PublicMessage(public_message) => {
+            malformed_public_messages(
+                provider,
+                group,
+                &message,
+                public_message,
+                &mut malformed_messages,
+            )?;
+        }
+        MlsMessageInBody::PrivateMessage(private_message) => {
+            malformed_private_messages(&message, private_message, &mut malformed_messages)?;
+        }
+        MlsMessageInBody::Welcome(welcome) => {
+            malformed_welcomes(&message, welcome, &mut malformed_messages)?;
+        }
+        MlsMessageInBody::GroupInfo(_) | MlsMessageInBody::KeyPackage(_) => {}
+    }
+    Ok(malformed_messages)
+}
+
+fn malformed_public_messages(
+    provider: &impl OpenMlsProvider,
+    group: &MlsGroup,
+    message: &MlsMessageIn,
+    public_message: &PublicMessageIn,
+    malformed_messages: &mut Vec<MalformedMessage>,
+) -> Result<(), LibraryError> {
+    let with_body = |public_message: PublicMessageIn| MlsMessageIn {
+        version: message.version,
+        body: MlsMessageInBody::PublicMessage(public_message),
+    };
+
+    let mut corrupted = public_message.clone();
+    corrupted.set_group_id(other_group_id(public_message.group_id()));
+    malformed_messages.push(MalformedMessage::processing(
+        Corruption::WrongGroupId,
+        &with_body(corrupted),
+        ValidationError::WrongGroupId,
+    )?);
+
+    let mut corrupted = public_message.clone();
+    corrupted.set_epoch(public_message.epoch().as_u64() + 1);
+    malformed_messages.push(MalformedMessage::processing(
+        Corruption::WrongEpoch,
+        &with_body(corrupted),
+        ValidationError::WrongEpoch,
+    )?);
+
+    let mut corrupted = public_message.clone();
+    corrupted.flip_signature_bit();
+    if corrupted.membership_tag().is_some() {
+        let message_secrets = group.group().message_secrets();
+        corrupted.set_membership_tag(
+            provider,
+            message_secrets.membership_key(),
+            message_secrets.serialized_context(),
+        )?;
+    }
+    malformed_messages.push(MalformedMessage::processing(
+        Corruption::FlippedSignatureBit,
+        &with_body(corrupted),
+        ProcessMessageError::InvalidSignature,
+    )?);
+
+    if public_message.membership_tag().is_some() {
+        let mut corrupted = public_message.clone();
+        corrupted.flip_membership_tag_bit();
+        malformed_messages.push(MalformedMessage::processing(
+            Corruption::BadMembershipTag,
+            &with_body(corrupted),
+            ValidationError::InvalidMembershipTag,
+        )?);
+    }
+    Ok(())
+}
+
+fn malformed_private_messages(
+    message: &MlsMessageIn,
+    private_message: &PrivateMessageIn,
+    malformed_messages: &mut Vec<MalformedMessage>,
+) -> Result<(), LibraryError> {
+    let with_body = |private_message: PrivateMessageIn| MlsMessageIn {
+        version: message.version,
+        body: MlsMessageInBody::PrivateMessage(private_message),
+    };
+
+    let mut corrupted = private_message.clone();
+    corrupted.set_group_id(other_group_id(private_message.group_id()));
+    malformed_messages.push(MalformedMessage::processing(
+        Corruption::WrongGroupId,
+        &with_body(corrupted),
+        ValidationError::WrongGroupId,
+    )?);
+
+    let mut corrupted = private_message.clone();
+    corrupted.set_epoch((private_message.epoch().as_u64() + 1).into());
+    malformed_messages.push(MalformedMessage::processing(
+        Corruption::WrongEpoch,
+        &with_body(corrupted),
+        ValidationError::WrongEpoch,
+    )?);
+
+    let mut corrupted = private_message.clone();
+    corrupted.flip_sender_data_bit();
+    malformed_messages.push(MalformedMessage::processing(
+        Corruption::FlippedSenderDataBit,
+        &with_body(corrupted),
+        MessageDecryptionError::AeadError,
+    )?);
+
+    let mut corrupted = private_message.clone();
+    corrupted.flip_ciphertext_bit();
+    malformed_messages.push(MalformedMessage::processing(
+        Corruption::FlippedCiphertextBit,
+        &with_body(corrupted),
+        MessageDecryptionError::AeadError,
+    )?);
+    Ok(())
+}
+
+fn malformed_welcomes(
+    message: &MlsMessageIn,
+    welcome: &Welcome,
+    malformed_messages: &mut Vec<MalformedMessage>,
+) -> Result<(), LibraryError> {
+    let with_parts =
+        |secrets: Vec<EncryptedGroupSecrets>, encrypted_group_info: Vec<u8>| MlsMessageIn {
+            version: message.version,
+            body: MlsMessageInBody::Welcome(Welcome::new(
+                welcome.ciphersuite(),
+                secrets,
+                encrypted_group_info,
+            )),
+        };
+
+    let secrets = welcome
+        .secrets()
+        .iter()
+        .map(|secrets| {
+            let mut new_member = secrets.new_member().as_slice().to_vec();
+            new_member[0] ^= 0x01;
+            EncryptedGroupSecrets::new(
+                KeyPackageRef::from_slice(&new_member),
+                secrets.encrypted_group_secrets().clone(),
+            )
+        })
+        .collect();
+    malformed_messages.push(MalformedMessage::processing(
+        Corruption::UnknownKeyPackage,
+        &with_parts(secrets, welcome.encrypted_group_info().to_vec()),
+        WelcomeError::<Infallible>::NoMatchingKeyPackage,
+    )?);
+
+    let secrets = welcome
+        .secrets()
+        .iter()
+        .map(|secrets| {
+            let encrypted_group_secrets = secrets.encrypted_group_secrets();
+            EncryptedGroupSecrets::new(
+                secrets.new_member(),
+                HpkeCiphertext {
+                    kem_output: encrypted_group_secrets.kem_output.clone(),
+                    ciphertext: flip_first_bit(encrypted_group_secrets.ciphertext.as_slice())
+                        .into(),
+                },
+            )
+        })
+        .collect();
+    malformed_messages.push(MalformedMessage::processing(
+        Corruption::FlippedGroupSecretsBit,
+        &with_parts(secrets, welcome.encrypted_group_info().to_vec()),
+        WelcomeError::<Infallible>::GroupSecrets(GroupSecretsError::DecryptionFailed),
+    )?);
+
+    malformed_messages.push(MalformedMessage::processing(
+        Corruption::FlippedGroupInfoBit,
+        &with_parts(
+            welcome.secrets().to_vec(),
+            flip_first_bit(welcome.encrypted_group_info()),
+        ),
+        WelcomeError::<Infallible>::GroupSecrets(GroupSecretsError::DecryptionFailed),
+    )?);
+    Ok(())
+}
+
+/// Returns a group id that differs from `group_id`.
+fn other_group_id(group_id: &GroupId) -> GroupId {
+    let mut other_group_id = group_id.as_slice().to_vec();
+    other_group_id.push(0);
+    GroupId::from_slice(&other_group_id)
+}
+
+fn flip_first_bit(bytes: &[u8]) -> Vec<u8> {
+    let mut bytes = bytes.to_vec();
+    bytes[0] ^= 0x01;
+    bytes
+}
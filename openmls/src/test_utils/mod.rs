@@ -26,6 +26,9 @@ use crate::{
     treesync::node::encryption_keys::{EncryptionKeyPair, EncryptionPrivateKey},
 };
 
+pub mod faulty_delivery_service;
+pub mod malformed;
+pub mod simulation;
 pub mod test_framework;
 
 pub(crate) fn write(file_name: &str, obj: impl Serialize) {
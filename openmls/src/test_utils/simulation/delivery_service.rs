@@ -0,0 +1,150 @@
+//! The in-memory delivery service of the [`Simulation`](super::Simulation).
+//!
+//! The delivery service keeps one queue of messages per client. It delivers
+//! the commits of a group in the order in which it accepted them, as required
+//! by the MLS protocol. Application messages are subject to the
+//! [`NetworkConditions`]: they may be lost, and those waiting for a client
+//! between two commits may be delivered in any order.
+
+use std::collections::VecDeque;
+
+use ::rand::{rngs::StdRng, Rng};
+use tls_codec::{Deserialize, Serialize};
+
+use super::NetworkConditions;
+use crate::{framing::*, messages::Welcome, treesync::RatchetTree};
+
+/// A message waiting in the queue of a client.
+pub(super) enum Envelope {
+    /// A commit or an application message of the group.
+    Message(ProtocolMessage),
+    /// A [`Welcome`] to the group and the ratchet tree of the group.
+    Welcome(Welcome, RatchetTree),
+}
+
+impl Envelope {
+    fn is_application_message(&self) -> bool {
+        matches!(
+            self,
+            Envelope::Message(message) if message.content_type() == ContentType::Application
+        )
+    }
+}
+
+pub(super) struct DeliveryService {
+    conditions: NetworkConditions,
+    epoch: u64,
+    members: Vec<usize>,
+    queues: Vec<VecDeque<Envelope>>,
+}
+
+impl DeliveryService {
+    /// Creates a delivery service for `number_of_clients` clients and a
+    /// group that was created by the client `creator`.
+    pub(super) fn new(
+        number_of_clients: usize,
+        creator: usize,
+        conditions: NetworkConditions,
+    ) -> Self {
+        Self {
+            conditions,
+            epoch: 0,
+            members: vec![creator],
+            queues: (0..number_of_clients).map(|_| VecDeque::new()).collect(),
+        }
+    }
+
+    /// Returns the epoch in which the delivery service accepts messages.
+    pub(super) fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Returns the clients that are members of the group in the current
+    /// epoch.
+    pub(super) fn members(&self) -> &[usize] {
+        &self.members
+    }
+
+    /// Queues an accepted `commit` of the client `sender` for the other
+    /// members of the group and the `welcome` for the clients that the commit
+    /// adds. `members` are the members of the group in the new epoch.
+    pub(super) fn submit_commit(
+        &mut self,
+        sender: usize,
+        commit: &MlsMessageOut,
+        welcome: Option<(&MlsMessageOut, RatchetTree)>,
+        members: Vec<usize>,
+    ) -> Result<(), tls_codec::Error> {
+        let commit = transmit(commit)?
+            .into_protocol_message()
+            .expect("expected a commit");
+        for &member in self.members.iter().filter(|&&member| member != sender) {
+            self.queues[member].push_back(Envelope::Message(commit.clone()));
+        }
+        if let Some((welcome, ratchet_tree)) = welcome {
+            let welcome = transmit(welcome)?
+                .into_welcome()
+                .expect("expected a welcome");
+            for &member in members
+                .iter()
+                .filter(|member| !self.members.contains(member))
+            {
+                self.queues[member]
+                    .push_back(Envelope::Welcome(welcome.clone(), ratchet_tree.clone()));
+            }
+        }
+        self.members = members;
+        self.epoch += 1;
+        Ok(())
+    }
+
+    /// Queues an application `message` of the client `sender` for the other
+    /// members of the group. Returns the number of members for which the
+    /// message was lost.
+    pub(super) fn submit_application_message(
+        &mut self,
+        sender: usize,
+        message: &MlsMessageOut,
+        rng: &mut StdRng,
+    ) -> Result<usize, tls_codec::Error> {
+        let message = transmit(message)?
+            .into_protocol_message()
+            .expect("expected an application message");
+        let mut lost = 0;
+        for &member in self.members.iter().filter(|&&member| member != sender) {
+            if rng.gen_bool(self.conditions.loss_rate) {
+                lost += 1;
+            } else {
+                self.queues[member].push_back(Envelope::Message(message.clone()));
+            }
+        }
+        Ok(lost)
+    }
+
+    /// Takes the next message from the queue of the client `recipient`.
+    pub(super) fn next_envelope(&mut self, recipient: usize, rng: &mut StdRng) -> Option<Envelope> {
+        let queue = &mut self.queues[recipient];
+        // Application messages are only reordered up to the next commit, so
+        // that they are still processed in the epoch they were sent in.
+        let window = queue
+            .iter()
+            .take_while(|envelope| envelope.is_application_message())
+            .count();
+        if self.conditions.reorder && window > 1 {
+            queue.remove(rng.gen_range(0..window))
+        } else {
+            queue.pop_front()
+        }
+    }
+
+    /// Returns a client with a non-empty queue, if there is one.
+    pub(super) fn next_recipient(&self) -> Option<usize> {
+        self.queues.iter().position(|queue| !queue.is_empty())
+    }
+}
+
+/// Sends `message` over the wire.
+fn transmit(message: &MlsMessageOut) -> Result<MlsMessageIn, tls_codec::Error> {
+    let serialized_message = message.tls_serialize_detached()?;
+    MlsMessageIn::tls_deserialize(&mut serialized_message.as_slice())
+}
@@ -0,0 +1,49 @@
+use openmls_rust_crypto::MemoryKeyStoreError;
+use thiserror::Error;
+
+use crate::{group::errors::*, key_packages::errors::KeyPackageNewError};
+
+/// An error that stopped a [`Simulation`](super::Simulation).
+///
+/// Rejected or lost messages are part of the simulated network and don't
+/// cause an error. An error means that a client failed to perform an
+/// operation or to process a message that the delivery service delivered.
+#[derive(Error, Debug, PartialEq, Clone)]
+pub enum SimulationError {
+    /// See [`tls_codec::Error`] for more details.
+    #[error(transparent)]
+    TlsCodecError(#[from] tls_codec::Error),
+    /// See [`KeyPackageNewError`] for more details.
+    #[error(transparent)]
+    KeyPackageNewError(#[from] KeyPackageNewError<MemoryKeyStoreError>),
+    /// See [`NewGroupError`] for more details.
+    #[error(transparent)]
+    NewGroupError(#[from] NewGroupError<MemoryKeyStoreError>),
+    /// See [`WelcomeError`] for more details.
+    #[error(transparent)]
+    WelcomeError(#[from] WelcomeError<MemoryKeyStoreError>),
+    /// See [`CreateMessageError`] for more details.
+    #[error(transparent)]
+    CreateMessageError(#[from] CreateMessageError),
+    /// See [`SelfUpdateError`] for more details.
+    #[error(transparent)]
+    SelfUpdateError(#[from] SelfUpdateError<MemoryKeyStoreError>),
+    /// See [`AddMembersError`] for more details.
+    #[error(transparent)]
+    AddMembersError(#[from] AddMembersError<MemoryKeyStoreError>),
+    /// See [`RemoveMembersError`] for more details.
+    #[error(transparent)]
+    RemoveMembersError(#[from] RemoveMembersError<MemoryKeyStoreError>),
+    /// See [`MergePendingCommitError`] for more details.
+    #[error(transparent)]
+    MergePendingCommitError(#[from] MergePendingCommitError<MemoryKeyStoreError>),
+    /// See [`ProcessMessageError`] for more details.
+    #[error(transparent)]
+    ProcessMessageError(#[from] ProcessMessageError),
+    /// See [`MergeCommitError`] for more details.
+    #[error(transparent)]
+    MergeCommitError(#[from] MergeCommitError<MemoryKeyStoreError>),
+    /// See [`ExportSecretError`] for more details.
+    #[error(transparent)]
+    ExportSecretError(#[from] ExportSecretError),
+}
@@ -0,0 +1,541 @@
+//! This module provides a [`Simulation`] of a group of clients that
+//! communicate through an in-memory delivery service, to test that the clients
+//! converge on the same group state after an arbitrary schedule of operations.
+//!
+//! A simulation starts with a group that only contains the first client. It is
+//! then driven by a schedule of [`Step`]s, which is either given explicitly to
+//! [`Simulation::run()`] or generated from the seed of the
+//! [`NetworkConditions`] by [`Simulation::run_random()`]. Steps that can't be
+//! performed in the current state, e.g., a client that isn't a member of the
+//! group sending a message, are skipped, so that any schedule is valid.
+//!
+//! The delivery service behaves like a typical MLS delivery service:
+//!
+//! * It only accepts commits and application messages for the current epoch of
+//!   the group. A client that commits in an older epoch clears its pending
+//!   commit, and has to process the messages waiting for it before its
+//!   operations are accepted again.
+//! * It delivers the commits of the group to all members in the order in which
+//!   it accepted them, and the welcomes to the clients that they add.
+//! * Application messages may be lost, and may be delivered in any order
+//!   between two commits, depending on the [`NetworkConditions`].
+//!
+//! Messages are only delivered to a client by a [`Step::Deliver`] or a
+//! [`Step::DeliverAll`], so that clients can fall behind the group.
+//! [`Simulation::assert_converged()`] delivers all waiting messages and checks
+//! that all members of the group agree on the group state.
+//!
+//! Applications can register a handler with [`Simulation::on_message()`] to
+//! run their own logic on the messages the clients process, and can inspect the
+//! state of each client with [`Simulation::client()`].
+
+use ::rand::{rngs::StdRng, Rng, SeedableRng};
+use openmls_basic_credential::SignatureKeyPair;
+use openmls_rust_crypto::OpenMlsRustCrypto;
+use openmls_traits::{types::Ciphersuite, OpenMlsProvider};
+
+use crate::{
+    binary_tree::LeafNodeIndex,
+    credentials::*,
+    framing::*,
+    group::{config::CryptoConfig, *},
+    key_packages::KeyPackage,
+};
+
+mod delivery_service;
+pub mod errors;
+
+use self::delivery_service::*;
+use self::errors::*;
+
+/// The behavior of the network between the clients and the delivery service.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NetworkConditions {
+    /// The seed of the randomness of the network and of the schedules
+    /// generated by [`Simulation::run_random()`].
+    pub seed: u64,
+    /// Deliver the application messages that wait for a client between two
+    /// commits in a random order. Messages of a sender that are reordered
+    /// further than the
+    /// [`SenderRatchetConfiguration`](crate::prelude::SenderRatchetConfiguration)
+    /// of the group tolerates fail to decrypt.
+    pub reorder: bool,
+    /// The probability, between `0.0` and `1.0`, that an application message
+    /// is lost on its way to a member.
+    pub loss_rate: f64,
+}
+
+/// A step of the schedule of a [`Simulation`]. Clients are identified by their
+/// index in the simulation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    /// The client sends an application message.
+    Send(usize),
+    /// The client commits an update of its leaf.
+    Update(usize),
+    /// The `committer` commits adding the `client`.
+    Add { committer: usize, client: usize },
+    /// The `committer` commits removing the `client`.
+    Remove { committer: usize, client: usize },
+    /// The next message waiting for the client is delivered.
+    Deliver(usize),
+    /// All waiting messages are delivered.
+    DeliverAll,
+}
+
+/// Counters of what happened in a [`Simulation`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SimulationStats {
+    /// Messages that were delivered to a client.
+    pub delivered: usize,
+    /// Application messages that were lost on their way to a member.
+    pub lost: usize,
+    /// Commits and application messages that the delivery service rejected
+    /// because they were sent in an older epoch.
+    pub rejected: usize,
+    /// Steps that couldn't be performed and were skipped.
+    pub skipped: usize,
+}
+
+/// A handler for the messages that the clients of a [`Simulation`] process.
+/// It is called with the index of the client, the group of the client before
+/// the message is applied, and the content of the processed message.
+pub type MessageHandler = Box<dyn FnMut(usize, &MlsGroup, &ProcessedMessageContent)>;
+
+/// A client of a [`Simulation`].
+pub struct SimulatedClient {
+    identity: Vec<u8>,
+    provider: OpenMlsRustCrypto,
+    signer: SignatureKeyPair,
+    credential_with_key: CredentialWithKey,
+    group: Option<MlsGroup>,
+}
+
+impl SimulatedClient {
+    fn new(index: usize, ciphersuite: Ciphersuite) -> Self {
+        let identity = format!("client {index}").into_bytes();
+        let signer = SignatureKeyPair::new(ciphersuite.signature_algorithm())
+            .expect("error creating the signature keys");
+        let credential_with_key = CredentialWithKey {
+            credential: Credential::new(identity.clone(), CredentialType::Basic)
+                .expect("error creating the credential"),
+            signature_key: signer.public().into(),
+        };
+        Self {
+            identity,
+            provider: OpenMlsRustCrypto::default(),
+            signer,
+            credential_with_key,
+            group: None,
+        }
+    }
+
+    /// Returns the identity in the credential of the client.
+    pub fn identity(&self) -> &[u8] {
+        &self.identity
+    }
+
+    /// Returns the provider of the client.
+    pub fn provider(&self) -> &OpenMlsRustCrypto {
+        &self.provider
+    }
+
+    /// Returns the signer of the client.
+    pub fn signer(&self) -> &SignatureKeyPair {
+        &self.signer
+    }
+
+    /// Returns the group of the client, if it joined the group. The group is
+    /// inactive if the client was removed from it.
+    pub fn group(&self) -> Option<&MlsGroup> {
+        self.group.as_ref()
+    }
+
+    fn active_group(&self) -> Option<&MlsGroup> {
+        self.group.as_ref().filter(|group| group.is_active())
+    }
+
+    fn key_package(&self, crypto_config: CryptoConfig) -> Result<KeyPackage, SimulationError> {
+        Ok(KeyPackage::builder().build(
+            crypto_config,
+            &self.provider,
+            &self.signer,
+            self.credential_with_key.clone(),
+        )?)
+    }
+}
+
+/// A simulation of a group of clients. See the [module documentation](self)
+/// for details.
+pub struct Simulation {
+    group_config: MlsGroupConfig,
+    clients: Vec<SimulatedClient>,
+    delivery_service: DeliveryService,
+    rng: StdRng,
+    stats: SimulationStats,
+    handler: Option<MessageHandler>,
+}
+
+impl Simulation {
+    /// Creates a simulation with `number_of_clients` clients, in which the
+    /// first client creates a group with the given `group_config`.
+    ///
+    /// Panics if `number_of_clients` is zero.
+    pub fn new(
+        group_config: MlsGroupConfig,
+        number_of_clients: usize,
+        conditions: NetworkConditions,
+    ) -> Result<Self, SimulationError> {
+        assert!(number_of_clients > 0, "a simulation needs a client");
+        let ciphersuite = group_config.crypto_config().ciphersuite;
+        let mut clients: Vec<_> = (0..number_of_clients)
+            .map(|index| SimulatedClient::new(index, ciphersuite))
+            .collect();
+        let creator = &mut clients[0];
+        creator.group = Some(MlsGroup::new(
+            &creator.provider,
+            &creator.signer,
+            &group_config,
+            creator.credential_with_key.clone(),
+        )?);
+        Ok(Self {
+            group_config,
+            clients,
+            delivery_service: DeliveryService::new(number_of_clients, 0, conditions),
+            rng: StdRng::seed_from_u64(conditions.seed),
+            stats: SimulationStats::default(),
+            handler: None,
+        })
+    }
+
+    /// Sets the `handler` that is called for every message a client
+    /// processes.
+    pub fn on_message(
+        &mut self,
+        handler: impl FnMut(usize, &MlsGroup, &ProcessedMessageContent) + 'static,
+    ) {
+        self.handler = Some(Box::new(handler));
+    }
+
+    /// Returns the client with the given index.
+    pub fn client(&self, index: usize) -> &SimulatedClient {
+        &self.clients[index]
+    }
+
+    /// Returns the indices of the clients that are members of the group in
+    /// the current epoch of the delivery service.
+    pub fn members(&self) -> &[usize] {
+        self.delivery_service.members()
+    }
+
+    /// Returns the current epoch of the group at the delivery service.
+    pub fn epoch(&self) -> u64 {
+        self.delivery_service.epoch()
+    }
+
+    /// Returns the counters of the simulation.
+    pub fn stats(&self) -> SimulationStats {
+        self.stats
+    }
+
+    /// Performs the steps of the `schedule` in order.
+    pub fn run(&mut self, schedule: impl IntoIterator<Item = Step>) -> Result<(), SimulationError> {
+        for step in schedule {
+            self.step(step)?;
+        }
+        Ok(())
+    }
+
+    /// Performs `number_of_steps` random steps.
+    pub fn run_random(&mut self, number_of_steps: usize) -> Result<(), SimulationError> {
+        for _ in 0..number_of_steps {
+            let step = self.random_step();
+            self.step(step)?;
+        }
+        Ok(())
+    }
+
+    /// Returns a random step.
+    pub fn random_step(&mut self) -> Step {
+        let client = self.rng.gen_range(0..self.clients.len());
+        let other = self.rng.gen_range(0..self.clients.len());
+        match self.rng.gen_range(0..100) {
+            0..=29 => Step::Send(client),
+            30..=59 => Step::Deliver(client),
+            60..=74 => Step::Update(client),
+            75..=84 => Step::Add {
+                committer: client,
+                client: other,
+            },
+            85..=94 => Step::Remove {
+                committer: client,
+                client: other,
+            },
+            _ => Step::DeliverAll,
+        }
+    }
+
+    /// Performs a single `step`.
+    pub fn step(&mut self, step: Step) -> Result<(), SimulationError> {
+        log::debug!("Simulation step: {step:?}");
+        let performed = match step {
+            Step::Send(client) => self.send(client)?,
+            Step::Update(client) => self.update(client)?,
+            Step::Add { committer, client } => self.add(committer, client)?,
+            Step::Remove { committer, client } => self.remove(committer, client)?,
+            Step::Deliver(client) => self.deliver(client)?,
+            Step::DeliverAll => {
+                self.deliver_all()?;
+                true
+            }
+        };
+        if !performed {
+            self.stats.skipped += 1;
+        }
+        Ok(())
+    }
+
+    /// Delivers all waiting messages and checks that the members of the group
+    /// agree on the epoch, the members, the ratchet tree and an exported
+    /// secret, and that no other client is still active in the group.
+    ///
+    /// Panics if the clients didn't converge.
+    pub fn assert_converged(&mut self) {
+        self.deliver_all()
+            .expect("error delivering the waiting messages");
+        let epoch = self.delivery_service.epoch();
+        let members = self.delivery_service.members();
+        let mut expected_identities: Vec<_> = members
+            .iter()
+            .map(|&member| self.clients[member].identity.clone())
+            .collect();
+        expected_identities.sort();
+
+        let mut reference_state = None;
+        for (index, client) in self.clients.iter().enumerate() {
+            let group = client.active_group();
+            if !members.contains(&index) {
+                assert!(
+                    group.is_none(),
+                    "client {index} isn't a member but is active in the group"
+                );
+                continue;
+            }
+            let group = group.unwrap_or_else(|| {
+                panic!("client {index} is a member but not active in the group")
+            });
+            assert_eq!(
+                group.epoch().as_u64(),
+                epoch,
+                "client {index} is in the wrong epoch"
+            );
+            let mut identities: Vec<_> = group
+                .members()
+                .map(|member| member.credential.identity().to_vec())
+                .collect();
+            identities.sort();
+            assert_eq!(
+                identities, expected_identities,
+                "client {index} has the wrong members"
+            );
+            let state = (
+                group.export_ratchet_tree(),
+                group
+                    .export_secret(client.provider.crypto(), "simulation", &[], 32)
+                    .expect("error exporting a secret"),
+            );
+            match &reference_state {
+                None => reference_state = Some(state),
+                Some(reference_state) => assert!(
+                    reference_state == &state,
+                    "client {index} diverged from the other members"
+                ),
+            }
+        }
+    }
+
+    fn send(&mut self, sender: usize) -> Result<bool, SimulationError> {
+        let Some(client) = self.clients.get_mut(sender) else {
+            return Ok(false);
+        };
+        let Some(group) = client.group.as_mut().filter(|group| group.is_active()) else {
+            return Ok(false);
+        };
+        let epoch = group.epoch().as_u64();
+        let message = group.create_message(&client.provider, &client.signer, &client.identity)?;
+        if epoch != self.delivery_service.epoch() {
+            self.stats.rejected += 1;
+            return Ok(true);
+        }
+        self.stats.lost +=
+            self.delivery_service
+                .submit_application_message(sender, &message, &mut self.rng)?;
+        Ok(true)
+    }
+
+    fn update(&mut self, committer: usize) -> Result<bool, SimulationError> {
+        if self.active_group(committer).is_none() {
+            return Ok(false);
+        }
+        self.commit(committer, |group, provider, signer| {
+            let (commit, welcome, _group_info) = group.self_update(provider, signer)?.into_parts();
+            Ok((commit, welcome))
+        })?;
+        Ok(true)
+    }
+
+    fn add(&mut self, committer: usize, client: usize) -> Result<bool, SimulationError> {
+        let Some(group) = self.active_group(committer) else {
+            return Ok(false);
+        };
+        // The client must not be a member, neither in the current epoch nor
+        // in the epoch of the committer.
+        if client >= self.clients.len()
+            || self.delivery_service.members().contains(&client)
+            || leaf_index_of(group, &self.clients[client].identity).is_some()
+        {
+            return Ok(false);
+        }
+        let key_package = self.clients[client].key_package(*self.group_config.crypto_config())?;
+        self.commit(committer, |group, provider, signer| {
+            let (commit, welcome, _group_info) = group
+                .add_members(provider, signer, &[key_package])?
+                .into_parts();
+            Ok((commit, Some(welcome)))
+        })?;
+        Ok(true)
+    }
+
+    fn remove(&mut self, committer: usize, client: usize) -> Result<bool, SimulationError> {
+        let Some(group) = self.active_group(committer) else {
+            return Ok(false);
+        };
+        let Some(target) = self
+            .clients
+            .get(client)
+            .filter(|_| client != committer)
+            .and_then(|client| leaf_index_of(group, &client.identity))
+        else {
+            return Ok(false);
+        };
+        self.commit(committer, |group, provider, signer| {
+            let (commit, welcome, _group_info) = group
+                .remove_members(provider, signer, &[target])?
+                .into_parts();
+            Ok((commit, welcome))
+        })?;
+        Ok(true)
+    }
+
+    /// Has the `committer` create a commit with `create_commit` and submits
+    /// it to the delivery service. The committer merges the commit if the
+    /// delivery service accepts it and clears it otherwise.
+    fn commit(
+        &mut self,
+        committer: usize,
+        create_commit: impl FnOnce(
+            &mut MlsGroup,
+            &OpenMlsRustCrypto,
+            &SignatureKeyPair,
+        )
+            -> Result<(MlsMessageOut, Option<MlsMessageOut>), SimulationError>,
+    ) -> Result<(), SimulationError> {
+        let client = &mut self.clients[committer];
+        let group = client.group.as_mut().expect("the committer has no group");
+        let epoch = group.epoch().as_u64();
+        let (commit, welcome) = create_commit(group, &client.provider, &client.signer)?;
+        if epoch != self.delivery_service.epoch() {
+            group.clear_pending_commit();
+            self.stats.rejected += 1;
+            return Ok(());
+        }
+        group.merge_pending_commit(&client.provider)?;
+        let ratchet_tree = group.export_ratchet_tree();
+        let identities: Vec<_> = group
+            .members()
+            .map(|member| member.credential.identity().to_vec())
+            .collect();
+        let members = identities
+            .iter()
+            .map(|identity| {
+                self.clients
+                    .iter()
+                    .position(|client| &client.identity == identity)
+                    .expect("the group has a member that isn't a client")
+            })
+            .collect();
+        self.delivery_service.submit_commit(
+            committer,
+            &commit,
+            welcome.as_ref().map(|welcome| (welcome, ratchet_tree)),
+            members,
+        )?;
+        Ok(())
+    }
+
+    /// Delivers the next message waiting for the `recipient`.
+    fn deliver(&mut self, recipient: usize) -> Result<bool, SimulationError> {
+        if recipient >= self.clients.len() {
+            return Ok(false);
+        }
+        let Some(envelope) = self
+            .delivery_service
+            .next_envelope(recipient, &mut self.rng)
+        else {
+            return Ok(false);
+        };
+        let client = &mut self.clients[recipient];
+        match envelope {
+            Envelope::Welcome(welcome, ratchet_tree) => {
+                client.group = Some(MlsGroup::new_from_welcome(
+                    &client.provider,
+                    &self.group_config,
+                    welcome,
+                    Some(ratchet_tree.into()),
+                )?);
+            }
+            Envelope::Message(message) => {
+                let group = client
+                    .group
+                    .as_mut()
+                    .expect("the delivery service only delivers messages to members");
+                let processed_message = group.process_message(&client.provider, message)?;
+                if let Some(handler) = self.handler.as_mut() {
+                    handler(recipient, group, processed_message.content());
+                }
+                match processed_message.into_content() {
+                    ProcessedMessageContent::StagedCommitMessage(staged_commit) => {
+                        group.merge_staged_commit(&client.provider, *staged_commit)?;
+                    }
+                    ProcessedMessageContent::ProposalMessage(queued_proposal)
+                    | ProcessedMessageContent::ExternalJoinProposalMessage(queued_proposal) => {
+                        group.store_pending_proposal(*queued_proposal);
+                    }
+                    ProcessedMessageContent::ApplicationMessage(_) => {}
+                }
+            }
+        }
+        self.stats.delivered += 1;
+        Ok(true)
+    }
+
+    /// Delivers all waiting messages.
+    fn deliver_all(&mut self) -> Result<(), SimulationError> {
+        while let Some(recipient) = self.delivery_service.next_recipient() {
+            self.deliver(recipient)?;
+        }
+        Ok(())
+    }
+
+    fn active_group(&self, index: usize) -> Option<&MlsGroup> {
+        self.clients.get(index)?.active_group()
+    }
+}
+
+/// Returns the leaf index of the member of `group` with the given `identity`.
+fn leaf_index_of(group: &MlsGroup, identity: &[u8]) -> Option<LeafNodeIndex> {
+    group
+        .members()
+        .find(|member| member.credential.identity() == identity)
+        .map(|member| member.index)
+}
@@ -207,7 +207,7 @@ impl Client {
         )
         .unwrap();
         let (msg, welcome_option, group_info) = match action_type {
-            ActionType::Commit => group.self_update(&self.crypto, &signer)?,
+            ActionType::Commit => group.self_update(&self.crypto, &signer)?.into_parts(),
             ActionType::Proposal => (
                 group
                     .propose_self_update(&self.crypto, &signer, leaf_node)
@@ -250,8 +250,9 @@ impl Client {
         .unwrap();
         let action_results = match action_type {
             ActionType::Commit => {
-                let (messages, welcome_message, group_info) =
-                    group.add_members(&self.crypto, &signer, key_packages)?;
+                let (messages, welcome_message, group_info) = group
+                    .add_members(&self.crypto, &signer, key_packages)?
+                    .into_parts();
                 (
                     vec![messages],
                     Some(
@@ -303,8 +304,9 @@ impl Client {
         .unwrap();
         let action_results = match action_type {
             ActionType::Commit => {
-                let (message, welcome_option, group_info) =
-                    group.remove_members(&self.crypto, &signer, targets)?;
+                let (message, welcome_option, group_info) = group
+                    .remove_members(&self.crypto, &signer, targets)?
+                    .into_parts();
                 (
                     vec![message],
                     welcome_option.map(|w| w.into_welcome().expect("Unexpected message type.")),
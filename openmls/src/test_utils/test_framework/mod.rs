@@ -40,6 +40,7 @@ use openmls_traits::{
     types::{Ciphersuite, HpkeKeyPair, SignatureScheme},
     OpenMlsProvider,
 };
+#[cfg(not(target_arch = "wasm32"))]
 use rayon::prelude::*;
 use std::{collections::HashMap, sync::RwLock};
 use tls_codec::*;
@@ -299,10 +300,13 @@ impl MlsGroupTestSetup {
         .into_protocol_message()
         .expect("Unexptected message type.");
         let clients = self.clients.read().expect("An unexpected error occurred.");
+        #[cfg(not(target_arch = "wasm32"))]
+        let members = group.members.par_iter();
+        #[cfg(target_arch = "wasm32")]
+        let members = group.members.iter();
+
         // Distribute message to all members, except to the sender in the case of application messages
-        let results: Result<Vec<_>, _> = group
-            .members
-            .par_iter()
+        let results: Result<Vec<_>, _> = members
             .filter_map(|(_index, member_id)| {
                 if message.content_type() == ContentType::Application && member_id == sender_id {
                     None
@@ -354,9 +358,13 @@ impl MlsGroupTestSetup {
     /// above tests fail.
     pub fn check_group_states(&self, group: &mut Group) {
         let clients = self.clients.read().expect("An unexpected error occurred.");
-        let messages = group
-            .members
-            .par_iter()
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let members = group.members.par_iter();
+        #[cfg(target_arch = "wasm32")]
+        let members = group.members.iter();
+
+        let messages = members
             .filter_map(|(_, m_id)| {
                 let m = clients
                     .get(m_id)
@@ -531,6 +539,48 @@ impl MlsGroupTestSetup {
         Ok(())
     }
 
+    /// Advance the group `group` by `number_of_epochs` epochs. The clients
+    /// with the identities in `committer_ids` take turns to commit a self
+    /// update without any other proposals, which is distributed to all members
+    /// of the group. If `committer_ids` is empty, all members of the group
+    /// take turns.
+    ///
+    /// Returns the commits in the order in which they were sent, e.g., for
+    /// replaying them later. Returns an error if one of the committers is not
+    /// a member of the group.
+    pub fn fast_forward(
+        &self,
+        group: &mut Group,
+        committer_ids: &[Vec<u8>],
+        number_of_epochs: usize,
+    ) -> Result<Vec<MlsMessageOut>, SetupError> {
+        let committer_ids: Vec<Vec<u8>> = if committer_ids.is_empty() {
+            group.members().map(|(_, identity)| identity).collect()
+        } else {
+            committer_ids.to_vec()
+        };
+        if committer_ids
+            .iter()
+            .any(|committer_id| !group.members.iter().any(|(_, id)| id == committer_id))
+        {
+            return Err(SetupError::ClientNotInGroup);
+        }
+        let clients = self.clients.read().expect("An unexpected error occurred.");
+        let mut commits = Vec::with_capacity(number_of_epochs);
+        for committer_id in committer_ids.iter().cycle().take(number_of_epochs) {
+            let committer = clients
+                .get(committer_id)
+                .ok_or(SetupError::UnknownClientId)?
+                .read()
+                .expect("An unexpected error occurred.");
+            let (commit, _, _) =
+                committer.self_update(ActionType::Commit, &group.group_id, None)?;
+            self.distribute_to_members(committer_id, group, &commit.clone().into())?;
+            commits.push(commit);
+        }
+        Ok(commits)
+    }
+
     /// Has the `adder` either propose or commit (depending on the
     /// `action_type`) an add of the `addee` to the Group `group`. Returns an
     /// error if
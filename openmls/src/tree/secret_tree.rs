@@ -1,3 +1,5 @@
+use std::collections::{BTreeMap, BTreeSet};
+
 use openmls_traits::crypto::OpenMlsCrypto;
 use openmls_traits::types::{Ciphersuite, CryptoError};
 use thiserror::Error;
@@ -16,6 +18,56 @@ use crate::{
     tree::sender_ratchet::*,
 };
 
+/// Deserializes the nodes and sender ratchets of a [`SecretTree`], which are
+/// stored by index, also from the vectors with one entry per leaf of earlier
+/// versions.
+mod sparse {
+    use std::{collections::BTreeMap, fmt, marker::PhantomData};
+
+    use serde::{
+        de::{MapAccess, SeqAccess, Visitor},
+        Deserialize, Deserializer,
+    };
+
+    struct SparseVisitor<T>(PhantomData<T>);
+
+    impl<'de, T: Deserialize<'de>> Visitor<'de> for SparseVisitor<T> {
+        type Value = BTreeMap<u32, T>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a map or a sequence of optional entries")
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+            let mut entries = BTreeMap::new();
+            while let Some((index, entry)) = map.next_entry()? {
+                entries.insert(index, entry);
+            }
+            Ok(entries)
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let mut entries = BTreeMap::new();
+            let mut index = 0;
+            while let Some(entry) = seq.next_element::<Option<T>>()? {
+                if let Some(entry) = entry {
+                    entries.insert(index, entry);
+                }
+                index += 1;
+            }
+            Ok(entries)
+        }
+    }
+
+    pub(super) fn deserialize<'de, D, T>(deserializer: D) -> Result<BTreeMap<u32, T>, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: Deserialize<'de>,
+    {
+        deserializer.deserialize_any(SparseVisitor(PhantomData))
+    }
+}
+
 /// Secret tree error
 #[derive(Error, Debug, Eq, PartialEq, Clone)]
 pub enum SecretTreeError {
@@ -46,8 +98,19 @@ pub enum SecretTreeError {
     /// See [`CryptoError`] for more details.
     #[error(transparent)]
     CryptoError(#[from] CryptoError),
+    /// The key of the generation was evicted to stay within the limits on
+    /// skipped message keys.
+    #[error(
+        "The key of the generation was evicted to stay within the limits on skipped message keys."
+    )]
+    KeyEvicted,
 }
 
+/// The maximum number of generations a sender may be ahead of its ratchet to
+/// be detected as desynchronized. Authenticating the message of a sender that
+/// is further ahead would take too many derivations.
+pub(crate) const MAX_DESYNCHRONIZATION_DISTANCE: u32 = 1 << 16;
+
 #[derive(Debug, Copy, Clone)]
 pub(crate) enum SecretType {
     HandshakeSecret,
@@ -105,42 +168,53 @@ pub(crate) struct SecretTreeNode {
     pub(crate) secret: Secret,
 }
 
+/// The secret tree of an epoch. Secrets and sender ratchets are only derived
+/// when a member sends or receives a message, and only the nodes and sender
+/// ratchets that hold a secret are stored, so that members who never send
+/// don't use any memory.
 #[derive(Serialize, Deserialize)]
 #[cfg_attr(any(feature = "test-utils", test), derive(PartialEq, Clone))]
 #[cfg_attr(any(feature = "crypto-debug", test), derive(Debug))]
 pub(crate) struct SecretTree {
     own_index: LeafNodeIndex,
-    leaf_nodes: Vec<Option<SecretTreeNode>>,
-    parent_nodes: Vec<Option<SecretTreeNode>>,
-    handshake_sender_ratchets: Vec<Option<SenderRatchet>>,
-    application_sender_ratchets: Vec<Option<SenderRatchet>>,
+    #[serde(deserialize_with = "sparse::deserialize")]
+    leaf_nodes: BTreeMap<u32, SecretTreeNode>,
+    #[serde(deserialize_with = "sparse::deserialize")]
+    parent_nodes: BTreeMap<u32, SecretTreeNode>,
+    #[serde(deserialize_with = "sparse::deserialize")]
+    handshake_sender_ratchets: BTreeMap<u32, SenderRatchet>,
+    #[serde(deserialize_with = "sparse::deserialize")]
+    application_sender_ratchets: BTreeMap<u32, SenderRatchet>,
     size: TreeSize,
+    // Keys that were evicted to stay within the limits on skipped keys and
+    // have not been reported yet.
+    #[serde(skip)]
+    evicted_keys: Vec<(LeafNodeIndex, Generation)>,
+    // Senders that sent an authentic message beyond the maximum forward
+    // distance of their ratchet.
+    #[serde(default)]
+    desynchronized_senders: BTreeSet<u32>,
 }
 
 impl SecretTree {
     /// Creates a new SecretTree based on an `encryption_secret` and group size
     /// `size`. The inner nodes of the tree and the SenderRatchets only get
-    /// initialized when secrets are requested either through `secret()`
-    /// or `next_secret()`.
+    /// initialized when secrets are requested either through
+    /// `secret_for_encryption()` or `secret_for_decryption()`.
     pub(crate) fn new(
         encryption_secret: EncryptionSecret,
         size: TreeSize,
         own_index: LeafNodeIndex,
     ) -> Self {
-        let leaf_count = size.leaf_count() as usize;
-        let leaf_nodes = std::iter::repeat_with(|| None).take(leaf_count).collect();
-        let parent_nodes = std::iter::repeat_with(|| None).take(leaf_count).collect();
-        let handshake_sender_ratchets = std::iter::repeat_with(|| None).take(leaf_count).collect();
-        let application_sender_ratchets =
-            std::iter::repeat_with(|| None).take(leaf_count).collect();
-
         let mut secret_tree = SecretTree {
             own_index,
-            leaf_nodes,
-            parent_nodes,
-            handshake_sender_ratchets,
-            application_sender_ratchets,
+            leaf_nodes: BTreeMap::new(),
+            parent_nodes: BTreeMap::new(),
+            handshake_sender_ratchets: BTreeMap::new(),
+            application_sender_ratchets: BTreeMap::new(),
             size,
+            evicted_keys: Vec::new(),
+            desynchronized_senders: BTreeSet::new(),
         };
 
         // Set the encryption secret in the root node. We ignore the Result
@@ -168,8 +242,10 @@ impl SecretTree {
         }
     }
 
-    /// Initializes a specific SenderRatchet pair for a given index by
-    /// calculating and deleting the appropriate values in the SecretTree
+    /// Initializes both SenderRatchets for a given index by calculating and
+    /// deleting the appropriate values in the SecretTree. The secret of the
+    /// leaf is deleted as soon as it is used, as required by Section 9.2 of
+    /// the MLS specification, so both SenderRatchets are initialized at once.
     fn initialize_sender_ratchets(
         &mut self,
         ciphersuite: Ciphersuite,
@@ -181,14 +257,16 @@ impl SecretTree {
             log::error!("Index is larger than the tree size.");
             return Err(SecretTreeError::IndexOutOfBounds);
         }
-        // Check if SenderRatchets are already initialized
-        if self
-            .ratchet_opt(index, SecretType::HandshakeSecret)?
-            .is_some()
-            && self
-                .ratchet_opt(index, SecretType::ApplicationSecret)?
-                .is_some()
-        {
+        // Only initialize the SenderRatchets that don't exist yet. Trees
+        // stored by earlier versions may have one SenderRatchet of a leaf
+        // and its secret.
+        let mut missing_secret_types = Vec::new();
+        for secret_type in [SecretType::HandshakeSecret, SecretType::ApplicationSecret] {
+            if self.ratchet_opt(index, secret_type)?.is_none() {
+                missing_secret_types.push(secret_type);
+            }
+        }
+        if missing_secret_types.is_empty() {
             log::trace!("The sender ratchets are initialized already.");
             return Ok(());
         }
@@ -220,61 +298,41 @@ impl SecretTree {
 
         // Calculate node secret and initialize SenderRatchets
         let node_secret = match self.get_node(index.into())? {
-            Some(node) => &node.secret,
+            Some(node) => node.secret.clone(),
             // We just derived all necessary nodes so this should not happen
             None => {
                 return Err(SecretTreeError::LibraryError);
             }
         };
 
-        log::trace!("Deriving leaf node secrets for leaf {index:?}");
-
-        let handshake_ratchet_secret =
-            node_secret.kdf_expand_label(crypto, "handshake", b"", ciphersuite.hash_length())?;
-        let application_ratchet_secret =
-            node_secret.kdf_expand_label(crypto, "application", b"", ciphersuite.hash_length())?;
-
-        log_crypto!(
-            trace,
-            "handshake ratchet secret {handshake_ratchet_secret:x?}"
-        );
-        log_crypto!(
-            trace,
-            "application ratchet secret {application_ratchet_secret:x?}"
-        );
-
-        // Initialize SenderRatchets, we differentiate between the own SenderRatchets
-        // and the SenderRatchets of other members
-        let (handshake_sender_ratchet, application_sender_ratchet) = if index == self.own_index {
-            let handshake_sender_ratchet = SenderRatchet::EncryptionRatchet(
-                RatchetSecret::initial_ratchet_secret(handshake_ratchet_secret),
-            );
-            let application_sender_ratchet = SenderRatchet::EncryptionRatchet(
-                RatchetSecret::initial_ratchet_secret(application_ratchet_secret),
-            );
-
-            (handshake_sender_ratchet, application_sender_ratchet)
-        } else {
-            let handshake_sender_ratchet =
-                SenderRatchet::DecryptionRatchet(DecryptionRatchet::new(handshake_ratchet_secret));
-            let application_sender_ratchet = SenderRatchet::DecryptionRatchet(
-                DecryptionRatchet::new(application_ratchet_secret),
-            );
-
-            (handshake_sender_ratchet, application_sender_ratchet)
-        };
+        for secret_type in missing_secret_types {
+            log::trace!("Deriving {secret_type:?} leaf node secret for leaf {index:?}");
+
+            let label = match secret_type {
+                SecretType::HandshakeSecret => "handshake",
+                SecretType::ApplicationSecret => "application",
+            };
+            let ratchet_secret =
+                node_secret.kdf_expand_label(crypto, label, b"", ciphersuite.hash_length())?;
+
+            log_crypto!(trace, "{label} ratchet secret {ratchet_secret:x?}");
+
+            // Initialize the SenderRatchet, we differentiate between the own
+            // SenderRatchets and the SenderRatchets of other members
+            let sender_ratchet = if index == self.own_index {
+                SenderRatchet::EncryptionRatchet(RatchetSecret::initial_ratchet_secret(
+                    ratchet_secret,
+                ))
+            } else {
+                SenderRatchet::DecryptionRatchet(DecryptionRatchet::new(ratchet_secret))
+            };
+            self.sender_ratchets_mut(secret_type)
+                .insert(index.u32(), sender_ratchet);
+        }
 
-        *self
-            .handshake_sender_ratchets
-            .get_mut(index.usize())
-            .ok_or(SecretTreeError::IndexOutOfBounds)? = Some(handshake_sender_ratchet);
-        *self
-            .application_sender_ratchets
-            .get_mut(index.usize())
-            .ok_or(SecretTreeError::IndexOutOfBounds)? = Some(application_sender_ratchet);
-
-        // Delete leaf node
-        self.set_node(index.into(), None)
+        // Delete the leaf node now that both SenderRatchets are initialized
+        self.set_node(index.into(), None)?;
+        Ok(())
     }
 
     /// Return RatchetSecrets for a given index and generation. This should be
@@ -305,16 +363,264 @@ impl SecretTree {
             log::trace!("   initialize sender ratchets");
             self.initialize_sender_ratchets(ciphersuite, crypto, index)?;
         }
-        match self.ratchet_mut(index, secret_type)? {
-            SenderRatchet::EncryptionRatchet(_) => {
+        // The ratchets are borrowed without the rest of the tree, so that
+        // evictions can be recorded right away.
+        let sender_ratchets = match secret_type {
+            SecretType::HandshakeSecret => &mut self.handshake_sender_ratchets,
+            SecretType::ApplicationSecret => &mut self.application_sender_ratchets,
+        };
+        let dec_ratchet = match sender_ratchets.get_mut(&index.u32()) {
+            Some(SenderRatchet::DecryptionRatchet(dec_ratchet)) => dec_ratchet,
+            Some(SenderRatchet::EncryptionRatchet(_)) => {
                 log::error!("This is the wrong ratchet type.");
-                Err(SecretTreeError::RatchetTypeError)
+                return Err(SecretTreeError::RatchetTypeError);
+            }
+            None => return Err(SecretTreeError::IndexOutOfBounds),
+        };
+        log::trace!("   getting secret for decryption");
+        let ratchet_key_material =
+            dec_ratchet.secret_for_decryption(ciphersuite, crypto, generation, configuration);
+        // Evictions are recorded even if no key material is returned, since
+        // the keys are gone either way.
+        if let Some(max_skipped_keys) = configuration.max_skipped_keys_per_sender() {
+            while dec_ratchet.skipped_keys() > max_skipped_keys as usize {
+                let Some(generation) = dec_ratchet.evict_oldest_skipped_key() else {
+                    break;
+                };
+                self.evicted_keys.push((index, generation));
+            }
+        }
+        if let Some(max_skipped_keys) = configuration.max_skipped_keys() {
+            self.evict_skipped_keys(max_skipped_keys as usize);
+        }
+        ratchet_key_material
+    }
+
+    /// Evicts the keys of skipped messages with the oldest generations, across
+    /// all senders, until at most `max_skipped_keys` are kept.
+    fn evict_skipped_keys(&mut self, max_skipped_keys: usize) {
+        loop {
+            let decryption_ratchets = self
+                .handshake_sender_ratchets
+                .iter_mut()
+                .chain(self.application_sender_ratchets.iter_mut())
+                .filter_map(|(&index, sender_ratchet)| match sender_ratchet {
+                    SenderRatchet::DecryptionRatchet(dec_ratchet) => Some((index, dec_ratchet)),
+                    SenderRatchet::EncryptionRatchet(_) => None,
+                });
+            let mut skipped_keys = 0;
+            let mut oldest: Option<(Generation, u32, &mut DecryptionRatchet)> = None;
+            for (index, dec_ratchet) in decryption_ratchets {
+                skipped_keys += dec_ratchet.skipped_keys();
+                if let Some(generation) = dec_ratchet.oldest_skipped_key() {
+                    let is_oldest = match &oldest {
+                        Some((oldest_generation, _, _)) => generation < *oldest_generation,
+                        None => true,
+                    };
+                    if is_oldest {
+                        oldest = Some((generation, index, dec_ratchet));
+                    }
+                }
+            }
+            if skipped_keys <= max_skipped_keys {
+                return;
+            }
+            let Some((_, index, dec_ratchet)) = oldest else {
+                return;
+            };
+            if let Some(generation) = dec_ratchet.evict_oldest_skipped_key() {
+                self.evicted_keys
+                    .push((LeafNodeIndex::new(index), generation));
+            }
+        }
+    }
+
+    /// Returns the keys of skipped messages that are kept by the sender
+    /// ratchets as `(sender, secret type, generation, ratchet generation)`.
+    pub(crate) fn skipped_keys(&self) -> Vec<(LeafNodeIndex, SecretType, Generation, Generation)> {
+        [
+            (SecretType::HandshakeSecret, &self.handshake_sender_ratchets),
+            (
+                SecretType::ApplicationSecret,
+                &self.application_sender_ratchets,
+            ),
+        ]
+        .into_iter()
+        .flat_map(|(secret_type, sender_ratchets)| {
+            sender_ratchets
+                .iter()
+                .filter_map(|(&index, sender_ratchet)| match sender_ratchet {
+                    SenderRatchet::DecryptionRatchet(dec_ratchet) => Some((index, dec_ratchet)),
+                    SenderRatchet::EncryptionRatchet(_) => None,
+                })
+                .flat_map(move |(index, dec_ratchet)| {
+                    dec_ratchet.skipped_generations().map(move |generation| {
+                        (
+                            LeafNodeIndex::new(index),
+                            secret_type,
+                            generation,
+                            dec_ratchet.generation(),
+                        )
+                    })
+                })
+        })
+        .collect()
+    }
+
+    /// Returns the state of the decryption ratchets as `(sender, secret type,
+    /// ratchet generation, number of kept keys of skipped messages)`.
+    pub(crate) fn decryption_ratchets(
+        &self,
+    ) -> Vec<(LeafNodeIndex, SecretType, Generation, usize)> {
+        [
+            (SecretType::HandshakeSecret, &self.handshake_sender_ratchets),
+            (
+                SecretType::ApplicationSecret,
+                &self.application_sender_ratchets,
+            ),
+        ]
+        .into_iter()
+        .flat_map(|(secret_type, sender_ratchets)| {
+            sender_ratchets.iter().filter_map(
+                move |(&index, sender_ratchet)| match sender_ratchet {
+                    SenderRatchet::DecryptionRatchet(dec_ratchet) => Some((
+                        LeafNodeIndex::new(index),
+                        secret_type,
+                        dec_ratchet.generation(),
+                        dec_ratchet.skipped_keys(),
+                    )),
+                    SenderRatchet::EncryptionRatchet(_) => None,
+                },
+            )
+        })
+        .collect()
+    }
+
+    /// Returns the keys that were evicted to stay within the limits on
+    /// skipped keys since the last call, as pairs of the sender and the
+    /// generation.
+    pub(crate) fn take_evicted_keys(&mut self) -> Vec<(LeafNodeIndex, Generation)> {
+        std::mem::take(&mut self.evicted_keys)
+    }
+
+    /// Returns the senders that sent a message in a generation beyond the
+    /// maximum forward distance of their ratchet. Their messages can't be
+    /// decrypted until the next epoch.
+    pub(crate) fn desynchronized_senders(&self) -> Vec<LeafNodeIndex> {
+        self.desynchronized_senders
+            .iter()
+            .map(|&index| LeafNodeIndex::new(index))
+            .collect()
+    }
+
+    /// Marks the member at `index` as desynchronized. This must only be done
+    /// once a message of the member beyond the maximum forward distance of its
+    /// ratchet was authenticated, see
+    /// [`SecretTree::secret_beyond_forward_distance()`].
+    pub(crate) fn flag_desynchronized_sender(&mut self, index: LeafNodeIndex) {
+        log::warn!("Sender {index:?} is too far ahead of its ratchet.");
+        self.desynchronized_senders.insert(index.u32());
+    }
+
+    /// Derives the key material of the member at `index` for a `generation`
+    /// beyond the maximum forward distance of its ratchet, without changing
+    /// the tree, so that the message can be authenticated before the member
+    /// is marked as desynchronized. Returns `None` if the ratchet of the
+    /// member is not initialized, or if the generation is within the forward
+    /// distance or more than [`MAX_DESYNCHRONIZATION_DISTANCE`] ahead.
+    pub(crate) fn secret_beyond_forward_distance(
+        &self,
+        ciphersuite: Ciphersuite,
+        crypto: &impl OpenMlsCrypto,
+        index: LeafNodeIndex,
+        secret_type: SecretType,
+        generation: u32,
+        configuration: &SenderRatchetConfiguration,
+    ) -> Result<Option<RatchetKeyMaterial>, SecretTreeError> {
+        match self.ratchet_opt(index, secret_type)? {
+            Some(SenderRatchet::DecryptionRatchet(dec_ratchet)) => dec_ratchet
+                .secret_beyond_forward_distance(
+                    ciphersuite,
+                    crypto,
+                    generation,
+                    configuration,
+                    MAX_DESYNCHRONIZATION_DISTANCE,
+                ),
+            Some(SenderRatchet::EncryptionRatchet(_)) | None => Ok(None),
+        }
+    }
+
+    /// Returns `true` if a message of the member at `index` in the given
+    /// `generation` can still be decrypted, without changing the tree.
+    pub(crate) fn can_decrypt(
+        &self,
+        index: LeafNodeIndex,
+        secret_type: SecretType,
+        generation: u32,
+        configuration: &SenderRatchetConfiguration,
+    ) -> bool {
+        if index == self.own_index {
+            return false;
+        }
+        match self.ratchet_opt(index, secret_type) {
+            Ok(Some(SenderRatchet::DecryptionRatchet(dec_ratchet))) => {
+                dec_ratchet.can_decrypt(generation, configuration)
             }
-            SenderRatchet::DecryptionRatchet(dec_ratchet) => {
-                log::trace!("   getting secret for decryption");
-                dec_ratchet.secret_for_decryption(ciphersuite, crypto, generation, configuration)
+            Ok(Some(SenderRatchet::EncryptionRatchet(_))) | Err(_) => false,
+            // The ratchet will be initialized with generation 0.
+            Ok(None) => generation <= configuration.maximum_forward_distance(),
+        }
+    }
+
+    /// Derives the application key material of the member at `index` for the
+    /// next `count` generations without changing the tree. Keys of skipped
+    /// generations that are kept by the tree are not included.
+    pub(crate) fn upcoming_application_keys(
+        &self,
+        ciphersuite: Ciphersuite,
+        crypto: &impl OpenMlsCrypto,
+        index: LeafNodeIndex,
+        count: u32,
+    ) -> Result<Vec<(Generation, RatchetKeyMaterial)>, SecretTreeError> {
+        if index == self.own_index {
+            return Err(SecretTreeError::RatchetTypeError);
+        }
+        match self.ratchet_opt(index, SecretType::ApplicationSecret)? {
+            Some(SenderRatchet::DecryptionRatchet(dec_ratchet)) => {
+                return dec_ratchet.upcoming_keys(ciphersuite, crypto, count)
             }
+            Some(SenderRatchet::EncryptionRatchet(_)) => {
+                return Err(SecretTreeError::RatchetTypeError)
+            }
+            None => (),
         }
+
+        // Derive the secret of the leaf from the closest node on the direct
+        // path that holds a secret, without storing the derived secrets.
+        let direct_path = direct_path(index, self.size);
+        let nodes: Vec<TreeNodeIndex> = std::iter::once(index.into())
+            .chain(direct_path.iter().map(|&parent| parent.into()))
+            .collect();
+        let mut position = 0;
+        let mut secret = loop {
+            let node = nodes.get(position).ok_or(SecretTreeError::LibraryError)?;
+            if let Some(node) = self.get_node(*node)? {
+                break node.secret.clone();
+            }
+            position += 1;
+        };
+        while position > 0 {
+            position -= 1;
+            let label: &[u8] = if left(direct_path[position]) == nodes[position] {
+                b"left"
+            } else {
+                b"right"
+            };
+            secret = secret.kdf_expand_label(crypto, "tree", label, ciphersuite.hash_length())?;
+        }
+        let ratchet_secret =
+            secret.kdf_expand_label(crypto, "application", b"", ciphersuite.hash_length())?;
+        DecryptionRatchet::new(ratchet_secret).upcoming_keys(ciphersuite, crypto, count)
     }
 
     /// Return the next RatchetSecrets that should be used for encryption and
@@ -347,13 +653,8 @@ impl SecretTree {
         index: LeafNodeIndex,
         secret_type: SecretType,
     ) -> Result<&mut SenderRatchet, SecretTreeError> {
-        let sender_ratchets = match secret_type {
-            SecretType::HandshakeSecret => &mut self.handshake_sender_ratchets,
-            SecretType::ApplicationSecret => &mut self.application_sender_ratchets,
-        };
-        sender_ratchets
-            .get_mut(index.usize())
-            .and_then(|r| r.as_mut())
+        self.sender_ratchets_mut(secret_type)
+            .get_mut(&index.u32())
             .ok_or(SecretTreeError::IndexOutOfBounds)
     }
 
@@ -363,13 +664,24 @@ impl SecretTree {
         index: LeafNodeIndex,
         secret_type: SecretType,
     ) -> Result<Option<&SenderRatchet>, SecretTreeError> {
+        if index.u32() >= self.size.leaf_count() {
+            return Err(SecretTreeError::IndexOutOfBounds);
+        }
         let sender_ratchets = match secret_type {
             SecretType::HandshakeSecret => &self.handshake_sender_ratchets,
             SecretType::ApplicationSecret => &self.application_sender_ratchets,
         };
-        match sender_ratchets.get(index.usize()) {
-            Some(sender_ratchet_option) => Ok(sender_ratchet_option.as_ref()),
-            None => Err(SecretTreeError::IndexOutOfBounds),
+        Ok(sender_ratchets.get(&index.u32()))
+    }
+
+    /// Returns the SenderRatchets of the given type.
+    fn sender_ratchets_mut(
+        &mut self,
+        secret_type: SecretType,
+    ) -> &mut BTreeMap<u32, SenderRatchet> {
+        match secret_type {
+            SecretType::HandshakeSecret => &mut self.handshake_sender_ratchets,
+            SecretType::ApplicationSecret => &mut self.application_sender_ratchets,
         }
     }
 
@@ -432,19 +744,31 @@ impl SecretTree {
         self.set_node(index_in_tree.into(), None)
     }
 
+    /// Returns the nodes of the given kind and the index of the node among
+    /// them, or an error if the index is out of the bounds of the tree.
+    fn nodes_mut(
+        &mut self,
+        index: TreeNodeIndex,
+    ) -> Result<(&mut BTreeMap<u32, SecretTreeNode>, u32), SecretTreeError> {
+        let (nodes, index) = match index {
+            TreeNodeIndex::Leaf(leaf_index) => (&mut self.leaf_nodes, leaf_index.u32()),
+            TreeNodeIndex::Parent(parent_index) => (&mut self.parent_nodes, parent_index.u32()),
+        };
+        if index >= self.size.leaf_count() {
+            return Err(SecretTreeError::IndexOutOfBounds);
+        }
+        Ok((nodes, index))
+    }
+
     fn get_node(&self, index: TreeNodeIndex) -> Result<Option<&SecretTreeNode>, SecretTreeError> {
-        match index {
-            TreeNodeIndex::Leaf(leaf_index) => Ok(self
-                .leaf_nodes
-                .get(leaf_index.usize())
-                .ok_or(SecretTreeError::IndexOutOfBounds)?
-                .as_ref()),
-            TreeNodeIndex::Parent(parent_index) => Ok(self
-                .parent_nodes
-                .get(parent_index.usize())
-                .ok_or(SecretTreeError::IndexOutOfBounds)?
-                .as_ref()),
+        let (nodes, index) = match index {
+            TreeNodeIndex::Leaf(leaf_index) => (&self.leaf_nodes, leaf_index.u32()),
+            TreeNodeIndex::Parent(parent_index) => (&self.parent_nodes, parent_index.u32()),
+        };
+        if index >= self.size.leaf_count() {
+            return Err(SecretTreeError::IndexOutOfBounds);
         }
+        Ok(nodes.get(&index))
     }
 
     fn set_node(
@@ -452,20 +776,124 @@ impl SecretTree {
         index: TreeNodeIndex,
         node: Option<SecretTreeNode>,
     ) -> Result<(), SecretTreeError> {
-        match index {
-            TreeNodeIndex::Leaf(leaf_index) => {
-                *self
-                    .leaf_nodes
-                    .get_mut(leaf_index.usize())
-                    .ok_or(SecretTreeError::IndexOutOfBounds)? = node;
+        let (nodes, index) = self.nodes_mut(index)?;
+        match node {
+            Some(node) => nodes.insert(index, node),
+            None => nodes.remove(&index),
+        };
+        Ok(())
+    }
+
+    /// Returns the approximate number of bytes of secret material held by the
+    /// tree, i.e., the secrets of its nodes and the sender ratchets, including
+    /// the entries that hold them. Leaves of members that never sent a
+    /// message don't use any memory.
+    pub(crate) fn memory_usage(&self, ciphersuite: Ciphersuite) -> usize {
+        let nodes: usize = self
+            .leaf_nodes
+            .values()
+            .chain(self.parent_nodes.values())
+            .map(|node| std::mem::size_of::<(u32, SecretTreeNode)>() + node.secret.as_slice().len())
+            .sum();
+        let ratchets: usize = self
+            .handshake_sender_ratchets
+            .values()
+            .chain(self.application_sender_ratchets.values())
+            .map(|ratchet| {
+                std::mem::size_of::<(u32, SenderRatchet)>() + ratchet.memory_usage(ciphersuite)
+            })
+            .sum();
+        nodes + ratchets
+    }
+
+    /// Check the invariants of the tree and return a description of every
+    /// violation. The following invariants are checked:
+    ///
+    /// * The tree has the given `size` and `own_index`, and no nodes or
+    ///   sender ratchets outside of it.
+    /// * Only the own leaf has encryption ratchets.
+    /// * Every secret in the tree is still needed, i.e., on the path from a
+    ///   leaf to the root there is at most one secret and none for a leaf
+    ///   whose sender ratchets are initialized.
+    /// * The sender ratchets of a leaf are initialized together.
+    #[cfg(feature = "check-invariants")]
+    pub(crate) fn invariant_violations(
+        &self,
+        size: TreeSize,
+        own_index: LeafNodeIndex,
+    ) -> Vec<String> {
+        let mut violations = Vec::new();
+        if self.size != size {
+            violations.push(format!(
+                "the secret tree has {} leaves instead of {}",
+                self.size.leaf_count(),
+                size.leaf_count()
+            ));
+        }
+        if self.own_index != own_index {
+            violations.push(format!(
+                "the own index of the secret tree is {} instead of {}",
+                self.own_index.u32(),
+                own_index.u32()
+            ));
+        }
+        let leaf_count = self.size.leaf_count();
+        if let Some(&index) = self
+            .leaf_nodes
+            .keys()
+            .chain(self.parent_nodes.keys())
+            .chain(self.handshake_sender_ratchets.keys())
+            .chain(self.application_sender_ratchets.keys())
+            .find(|&&index| index >= leaf_count)
+        {
+            violations.push(format!(
+                "the secret tree has a node or sender ratchet at index {index} for {leaf_count} leaves",
+            ));
+            return violations;
+        }
+
+        for index in (0..self.size.leaf_count()).map(LeafNodeIndex::new) {
+            let handshake_ratchet = self.handshake_sender_ratchets.get(&index.u32());
+            let application_ratchet = self.application_sender_ratchets.get(&index.u32());
+            for ratchet in [handshake_ratchet, application_ratchet]
+                .into_iter()
+                .flatten()
+            {
+                let is_encryption_ratchet = matches!(ratchet, SenderRatchet::EncryptionRatchet(_));
+                if is_encryption_ratchet != (index == self.own_index) {
+                    violations.push(format!(
+                        "leaf {} has the wrong type of sender ratchet",
+                        index.u32()
+                    ));
+                }
             }
-            TreeNodeIndex::Parent(parent_index) => {
-                *self
-                    .parent_nodes
-                    .get_mut(parent_index.usize())
-                    .ok_or(SecretTreeError::IndexOutOfBounds)? = node;
+
+            let secrets = std::iter::once(TreeNodeIndex::from(index))
+                .chain(direct_path(index, self.size).into_iter().map(Into::into))
+                .filter(|&node_index| matches!(self.get_node(node_index), Ok(Some(_))))
+                .count();
+            if secrets > 1 {
+                violations.push(format!(
+                    "there are {} secrets on the path of leaf {}",
+                    secrets,
+                    index.u32()
+                ));
+            }
+            let initialized =
+                handshake_ratchet.is_some() as usize + application_ratchet.is_some() as usize;
+            if secrets > 0 && initialized > 0 {
+                violations.push(format!(
+                    "there is a secret on the path of leaf {}, whose sender ratchets are initialized",
+                    index.u32()
+                ));
+            }
+            if initialized == 1 {
+                violations.push(format!(
+                    "only one sender ratchet of leaf {} is initialized",
+                    index.u32()
+                ));
             }
         }
-        Ok(())
+        violations
     }
 }
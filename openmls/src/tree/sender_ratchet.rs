@@ -10,7 +10,9 @@ use std::collections::VecDeque;
 
 use openmls_traits::types::Ciphersuite;
 
+use crate::binary_tree::LeafNodeIndex;
 use crate::ciphersuite::{AeadNonce, *};
+use crate::metrics::{self, Histogram};
 use crate::tree::secret_tree::*;
 
 use super::*;
@@ -29,10 +31,50 @@ pub(crate) type Generation = u32;
 ///  - maximum_forward_distance:
 /// This parameter defines how many incoming messages can be skipped. This is useful if the DS
 /// drops application messages. The default value is 1000.
+///  - max_skipped_keys_per_sender:
+/// The maximum number of keys of skipped messages that are kept for a sender
+/// within the out-of-order window. The default is no limit.
+///  - max_skipped_keys:
+/// The maximum number of keys of skipped messages that are kept for all
+/// senders of an epoch together. The default is no limit.
+///
+/// If a limit is exceeded, the keys of the oldest generations are evicted
+/// first and a
+/// [`SecurityEventKind::MessageKeyEvicted`](crate::security_events::SecurityEventKind::MessageKeyEvicted)
+/// is reported for each of them. Messages whose key was evicted can't be decrypted anymore.
+///
+/// Both parameters can be overridden for individual senders with
+/// [`SenderRatchetConfiguration::with_sender_override()`], e.g., to tolerate
+/// a larger out-of-order window for bots that send many messages.
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SenderRatchetConfiguration {
     out_of_order_tolerance: Generation,
     maximum_forward_distance: Generation,
+    #[serde(default)]
+    max_skipped_keys_per_sender: Option<u32>,
+    #[serde(default)]
+    max_skipped_keys: Option<u32>,
+    #[serde(default)]
+    sender_overrides: Vec<SenderOverride>,
+}
+
+/// Selects the senders that an override of the [`SenderRatchetConfiguration`]
+/// applies to.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SenderSelector {
+    /// The member at the given leaf index.
+    LeafIndex(LeafNodeIndex),
+    /// The members whose credential has the given identity.
+    Identity(Vec<u8>),
+}
+
+/// The parameters of the [`SenderRatchetConfiguration`] for the senders
+/// selected by `sender`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct SenderOverride {
+    sender: SenderSelector,
+    out_of_order_tolerance: Generation,
+    maximum_forward_distance: Generation,
 }
 
 impl SenderRatchetConfiguration {
@@ -41,8 +83,79 @@ impl SenderRatchetConfiguration {
         Self {
             out_of_order_tolerance,
             maximum_forward_distance,
+            max_skipped_keys_per_sender: None,
+            max_skipped_keys: None,
+            sender_overrides: Vec::new(),
         }
     }
+
+    /// Returns the configuration with the given limit on the number of keys
+    /// of skipped messages that are kept for each sender.
+    pub fn with_max_skipped_keys_per_sender(mut self, max_skipped_keys_per_sender: u32) -> Self {
+        self.max_skipped_keys_per_sender = Some(max_skipped_keys_per_sender);
+        self
+    }
+
+    /// Returns the configuration with the given limit on the number of keys
+    /// of skipped messages that are kept for all senders of an epoch.
+    pub fn with_max_skipped_keys(mut self, max_skipped_keys: u32) -> Self {
+        self.max_skipped_keys = Some(max_skipped_keys);
+        self
+    }
+
+    /// Returns the configuration with the given parameters for the senders
+    /// selected by `sender`. If several overrides select a sender, the one
+    /// that was added first applies.
+    pub fn with_sender_override(
+        mut self,
+        sender: SenderSelector,
+        out_of_order_tolerance: Generation,
+        maximum_forward_distance: Generation,
+    ) -> Self {
+        self.sender_overrides.push(SenderOverride {
+            sender,
+            out_of_order_tolerance,
+            maximum_forward_distance,
+        });
+        self
+    }
+
+    /// Returns the configuration for the sender at `leaf_index` with the
+    /// given `identity`, i.e., the first matching override or otherwise this
+    /// configuration without overrides. The limits on skipped keys apply to
+    /// all senders.
+    pub(crate) fn for_sender(
+        &self,
+        leaf_index: LeafNodeIndex,
+        identity: Option<&[u8]>,
+    ) -> SenderRatchetConfiguration {
+        self.sender_overrides
+            .iter()
+            .find(|sender_override| match &sender_override.sender {
+                SenderSelector::LeafIndex(index) => *index == leaf_index,
+                SenderSelector::Identity(expected) => Some(expected.as_slice()) == identity,
+            })
+            .map(|sender_override| {
+                (
+                    sender_override.out_of_order_tolerance,
+                    sender_override.maximum_forward_distance,
+                )
+            })
+            .map_or_else(
+                || Self {
+                    sender_overrides: Vec::new(),
+                    ..self.clone()
+                },
+                |(out_of_order_tolerance, maximum_forward_distance)| Self {
+                    out_of_order_tolerance,
+                    maximum_forward_distance,
+                    max_skipped_keys_per_sender: self.max_skipped_keys_per_sender,
+                    max_skipped_keys: self.max_skipped_keys,
+                    sender_overrides: Vec::new(),
+                },
+            )
+    }
+
     /// Get a reference to the sender ratchet configuration's out of order tolerance.
     pub fn out_of_order_tolerance(&self) -> Generation {
         self.out_of_order_tolerance
@@ -52,6 +165,18 @@ impl SenderRatchetConfiguration {
     pub fn maximum_forward_distance(&self) -> Generation {
         self.maximum_forward_distance
     }
+
+    /// Returns the maximum number of keys of skipped messages that are kept
+    /// for each sender, or `None` if there is no limit.
+    pub fn max_skipped_keys_per_sender(&self) -> Option<u32> {
+        self.max_skipped_keys_per_sender
+    }
+
+    /// Returns the maximum number of keys of skipped messages that are kept
+    /// for all senders of an epoch, or `None` if there is no limit.
+    pub fn max_skipped_keys(&self) -> Option<u32> {
+        self.max_skipped_keys
+    }
 }
 
 impl Default for SenderRatchetConfiguration {
@@ -86,6 +211,15 @@ impl SenderRatchet {
             SenderRatchet::DecryptionRatchet(dec_ratchet) => dec_ratchet.generation(),
         }
     }
+
+    /// Returns the approximate number of bytes of secret material held by the
+    /// ratchet.
+    pub(crate) fn memory_usage(&self, ciphersuite: Ciphersuite) -> usize {
+        match self {
+            SenderRatchet::EncryptionRatchet(enc_ratchet) => enc_ratchet.memory_usage(),
+            SenderRatchet::DecryptionRatchet(dec_ratchet) => dec_ratchet.memory_usage(ciphersuite),
+        }
+    }
 }
 
 /// The core of both types of [`SenderRatchet`]. It contains the current head of
@@ -114,6 +248,11 @@ impl RatchetSecret {
         self.generation
     }
 
+    /// Returns the number of bytes of the ratchet secret.
+    pub(crate) fn memory_usage(&self) -> usize {
+        self.secret.as_slice().len()
+    }
+
     /// Consume this [`RatchetSecret`] to derive a pair of [`RatchetSecrets`],
     /// as well as the [`RatchetSecret`] of the next generation and return both.
     pub(crate) fn ratchet_forward(
@@ -173,6 +312,10 @@ impl RatchetSecret {
 pub struct DecryptionRatchet {
     past_secrets: VecDeque<Option<RatchetKeyMaterial>>,
     ratchet_head: RatchetSecret,
+    // Generations within the window whose key was evicted to stay within the
+    // limits on skipped keys.
+    #[serde(default)]
+    evicted: Vec<Generation>,
 }
 
 impl DecryptionRatchet {
@@ -181,6 +324,7 @@ impl DecryptionRatchet {
         Self {
             past_secrets: VecDeque::new(),
             ratchet_head: RatchetSecret::initial_ratchet_secret(secret),
+            evicted: Vec::new(),
         }
     }
 
@@ -188,7 +332,43 @@ impl DecryptionRatchet {
     /// bounds determined by the [`SenderRatchetConfiguration`].
     fn prune_past_secrets(&mut self, configuration: &SenderRatchetConfiguration) {
         self.past_secrets
-            .truncate(configuration.out_of_order_tolerance() as usize)
+            .truncate(configuration.out_of_order_tolerance() as usize);
+        let generation = self.generation();
+        let window = self.past_secrets.len() as u32;
+        self.evicted
+            .retain(|evicted| generation - evicted <= window);
+    }
+
+    /// Returns the number of keys of skipped messages that are kept.
+    pub(crate) fn skipped_keys(&self) -> usize {
+        self.past_secrets.iter().flatten().count()
+    }
+
+    /// Returns the generations whose keys are kept, newest first.
+    pub(crate) fn skipped_generations(&self) -> impl Iterator<Item = Generation> + '_ {
+        self.past_secrets
+            .iter()
+            .enumerate()
+            .filter(|(_, key_material)| key_material.is_some())
+            .map(|(index, _)| self.generation() - index as Generation - 1)
+    }
+
+    /// Returns the oldest generation whose key is kept, if any.
+    pub(crate) fn oldest_skipped_key(&self) -> Option<Generation> {
+        self.past_secrets
+            .iter()
+            .rposition(Option::is_some)
+            .map(|index| self.generation() - index as Generation - 1)
+    }
+
+    /// Deletes the key of the oldest generation that is kept and returns the
+    /// generation, if any.
+    pub(crate) fn evict_oldest_skipped_key(&mut self) -> Option<Generation> {
+        let generation = self.oldest_skipped_key()?;
+        let index = (self.generation() - generation - 1) as usize;
+        self.past_secrets.get_mut(index)?.take();
+        self.evicted.push(generation);
+        Some(generation)
     }
 
     /// Get the generation of the ratchet head.
@@ -196,6 +376,77 @@ impl DecryptionRatchet {
         self.ratchet_head.generation()
     }
 
+    /// Returns the approximate number of bytes of secret material held by the
+    /// ratchet, i.e., the ratchet head and the key material that is kept for
+    /// out-of-order messages.
+    pub(crate) fn memory_usage(&self, ciphersuite: Ciphersuite) -> usize {
+        let key_material_len = ciphersuite.aead_key_length() + ciphersuite.aead_nonce_length();
+        self.ratchet_head.memory_usage()
+            + self.past_secrets.iter().flatten().count() * key_material_len
+    }
+
+    /// Returns `true` if the key material for `generation` is still available
+    /// or can be derived within the bounds of the `configuration`.
+    pub(crate) fn can_decrypt(
+        &self,
+        generation: Generation,
+        configuration: &SenderRatchetConfiguration,
+    ) -> bool {
+        if generation >= self.generation() {
+            generation - self.generation() <= configuration.maximum_forward_distance()
+        } else {
+            let index = (self.generation() - generation - 1) as usize;
+            matches!(self.past_secrets.get(index), Some(Some(_)))
+        }
+    }
+
+    /// Derives the key material of the next `count` generations from a copy
+    /// of the ratchet head, without changing the ratchet. Keys of skipped
+    /// generations are not included.
+    pub(crate) fn upcoming_keys(
+        &self,
+        ciphersuite: Ciphersuite,
+        crypto: &impl OpenMlsCrypto,
+        count: u32,
+    ) -> Result<Vec<(Generation, RatchetKeyMaterial)>, SecretTreeError> {
+        let mut ratchet_head = RatchetSecret {
+            secret: self.ratchet_head.secret.clone(),
+            generation: self.ratchet_head.generation,
+        };
+        (0..count)
+            .map(|_| ratchet_head.ratchet_forward(crypto, ciphersuite))
+            .collect()
+    }
+
+    /// Derives the key material for a `generation` beyond the
+    /// `maximum_forward_distance` of the `configuration` from a copy of the
+    /// ratchet head, without changing the ratchet. Returns `None` if the
+    /// generation is within the forward distance or more than
+    /// `max_distance` ahead of the ratchet.
+    pub(crate) fn secret_beyond_forward_distance(
+        &self,
+        ciphersuite: Ciphersuite,
+        crypto: &impl OpenMlsCrypto,
+        generation: Generation,
+        configuration: &SenderRatchetConfiguration,
+        max_distance: Generation,
+    ) -> Result<Option<RatchetKeyMaterial>, SecretTreeError> {
+        let distance = generation.saturating_sub(self.generation());
+        if distance <= configuration.maximum_forward_distance() || distance > max_distance {
+            return Ok(None);
+        }
+        let mut ratchet_head = RatchetSecret {
+            secret: self.ratchet_head.secret.clone(),
+            generation: self.ratchet_head.generation,
+        };
+        for _ in 0..distance {
+            ratchet_head.ratchet_forward(crypto, ciphersuite)?;
+        }
+        ratchet_head
+            .ratchet_forward(crypto, ciphersuite)
+            .map(|(_, key_material)| Some(key_material))
+    }
+
     #[cfg(test)]
     pub(crate) fn ratchet_secret_mut(&mut self) -> &mut RatchetSecret {
         &mut self.ratchet_head
@@ -224,6 +475,12 @@ impl DecryptionRatchet {
             log::error!("  Generation is too far in the past (broke out of order tolerance ({}) {generation} < {}).", configuration.out_of_order_tolerance(), self.generation());
             return Err(SecretTreeError::TooDistantInThePast);
         }
+        if generation != self.generation() {
+            metrics::record_histogram(
+                Histogram::OutOfOrderDistance,
+                generation.abs_diff(self.generation()).into(),
+            );
+        }
         // If generation is the one the ratchet is currently at or in the future
         if generation >= self.generation() {
             // Ratchet the chain forward as far as necessary
@@ -268,7 +525,13 @@ impl DecryptionRatchet {
                 .take()
                 // If the requested generation was used to decrypt a message
                 // earlier, throw an error.
-                .ok_or(SecretTreeError::SecretReuseError)
+                .ok_or_else(|| {
+                    if self.evicted.contains(&generation) {
+                        SecretTreeError::KeyEvicted
+                    } else {
+                        SecretTreeError::SecretReuseError
+                    }
+                })
         }
     }
 }
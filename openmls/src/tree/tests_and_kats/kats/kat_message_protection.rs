@@ -62,6 +62,7 @@
 //!     * When protecting the Commit message, add the supplied confirmation tag
 
 use openmls_basic_credential::SignatureKeyPair;
+#[cfg(not(feature = "check-invariants"))]
 use openmls_rust_crypto::OpenMlsRustCrypto;
 use openmls_traits::{types::SignatureScheme, OpenMlsProvider};
 use serde::{self, Deserialize, Serialize};
@@ -70,7 +71,7 @@ use crate::{
     binary_tree::array_representation::LeafNodeIndex,
     credentials::{Credential, CredentialType, CredentialWithKey},
     framing::{mls_auth_content::AuthenticatedContent, mls_content::FramedContentBody, *},
-    group::*,
+    group::{config::ValidationPolicy, *},
     schedule::{EncryptionSecret, SenderDataSecret},
     test_utils::*,
     tree::{secret_tree::SecretTree, sender_ratchet::SenderRatchetConfiguration},
@@ -367,7 +368,7 @@ pub fn run_test_vector(
             // check that the proposal in proposal_pub == proposal
             let decrypted_message = group
                 .decrypt_message(
-                    provider.crypto(),
+                    provider,
                     proposal_pub.into_protocol_message().unwrap(),
                     &sender_ratchet_config,
                 )
@@ -378,7 +379,13 @@ pub fn run_test_vector(
                 .parse_message(decrypted_message, group.message_secrets_store())
                 .unwrap();
             let processed_message: AuthenticatedContent = processed_unverified_message
-                .verify(ciphersuite, provider.crypto(), ProtocolVersion::Mls10)
+                .verify(
+                    ciphersuite,
+                    provider.crypto(),
+                    ProtocolVersion::Mls10,
+                    provider.clock(),
+                    &ValidationPolicy::strict(),
+                )
                 .unwrap()
                 .0;
             match processed_message.content().to_owned() {
@@ -503,7 +510,7 @@ pub fn run_test_vector(
             // check that the proposal in proposal_pub == proposal
             let decrypted_message = group
                 .decrypt_message(
-                    provider.crypto(),
+                    provider,
                     commit_pub.into_protocol_message().unwrap(),
                     &sender_ratchet_config,
                 )
@@ -514,7 +521,13 @@ pub fn run_test_vector(
                 .parse_message(decrypted_message, group.message_secrets_store())
                 .unwrap();
             let processed_message: AuthenticatedContent = processed_unverified_message
-                .verify(ciphersuite, provider.crypto(), ProtocolVersion::Mls10)
+                .verify(
+                    ciphersuite,
+                    provider.crypto(),
+                    ProtocolVersion::Mls10,
+                    provider.clock(),
+                    &ValidationPolicy::strict(),
+                )
                 .unwrap()
                 .0;
             match processed_message.content().to_owned() {
@@ -546,7 +559,7 @@ pub fn run_test_vector(
             // check that the proposal in proposal_priv == proposal
             let decrypted_message = group
                 .decrypt_message(
-                    provider.crypto(),
+                    provider,
                     commit_priv.into_protocol_message().unwrap(),
                     &sender_ratchet_config,
                 )
@@ -557,7 +570,13 @@ pub fn run_test_vector(
                 .parse_message(decrypted_message, group.message_secrets_store())
                 .unwrap();
             let processed_message: AuthenticatedContent = processed_unverified_message
-                .verify(ciphersuite, provider.crypto(), ProtocolVersion::Mls10)
+                .verify(
+                    ciphersuite,
+                    provider.crypto(),
+                    ProtocolVersion::Mls10,
+                    provider.clock(),
+                    &ValidationPolicy::strict(),
+                )
                 .unwrap()
                 .0;
             match processed_message.content().to_owned() {
@@ -699,6 +718,9 @@ pub fn run_test_vector(
     Ok(())
 }
 
+// The test vectors set a group context that doesn't match the ratchet tree of
+// the group, which violates the invariants checked by `check-invariants`.
+#[cfg(not(feature = "check-invariants"))]
 #[apply(providers)]
 fn read_test_vectors_mp(provider: &impl OpenMlsProvider) {
     let _ = pretty_env_logger::try_init();
@@ -48,12 +48,37 @@
 //!     * `handshake_nonce = handshake_ratchet_nonce_[i]_[generation]`
 //!     * `application_key = application_ratchet_key_[i]_[generation]`
 //!     * `application_nonce = application_ratchet_nonce_[i]_[generation]`
+//!
+//! OpenMLS extends the format with optional fields for the reuse guard:
+//!
+//! ```text
+//!       {
+//!         ...
+//!         "reuse_guard": /* hex-encoded binary data */,
+//!         "handshake_guarded_nonce": /* hex-encoded binary data */,
+//!         "application_guarded_nonce": /* hex-encoded binary data */,
+//!       }
+//! ```
+//!
+//! * If `reuse_guard` is present, verify that:
+//!   * `handshake_guarded_nonce = handshake_nonce XOR reuse_guard`
+//!   * `application_guarded_nonce = application_nonce XOR reuse_guard`
+//!
+//!   where the `reuse_guard` is XORed into the first four bytes of the nonce.
+//!
+//! In addition, if the tree has more than one leaf, every entry is derived
+//! by another member for decryption, which must fail for a generation that
+//! was already used.
+//!
+//! The OpenMLS test vectors are generated deterministically from the
+//! ciphersuite, the number of leaves and the generations, so that any change
+//! of the derivation shows up as a mismatch.
 
 use serde::{Deserialize, Serialize};
 
 use crate::test_utils::*;
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SenderData {
     sender_data_secret: String,
     ciphertext: String,
@@ -61,16 +86,22 @@ pub struct SenderData {
     nonce: String,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Leaf {
     generation: u32,
     application_key: String,
     application_nonce: String,
     handshake_key: String,
     handshake_nonce: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    reuse_guard: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    application_guarded_nonce: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    handshake_guarded_nonce: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SecretTree {
     cipher_suite: u16,
 
@@ -79,14 +110,231 @@ pub struct SecretTree {
     leaves: Vec<Vec<Leaf>>,
 }
 
+/// Derives an input of `length` bytes for the OpenMLS test vectors from a
+/// `label` and the parameters of the test vector.
+#[cfg(test)]
+fn test_input(
+    crypto: &impl openmls_traits::crypto::OpenMlsCrypto,
+    ciphersuite: Ciphersuite,
+    label: &str,
+    context: &[u32],
+    length: usize,
+) -> Vec<u8> {
+    let mut data = label.as_bytes().to_vec();
+    data.extend((ciphersuite as u16).to_be_bytes());
+    for value in context {
+        data.extend(value.to_be_bytes());
+    }
+    let mut input = crypto
+        .hash(ciphersuite.hash_algorithm(), &data)
+        .expect("Error hashing the test input.");
+    input.truncate(length);
+    input
+}
+
+/// Ratchets the sender ratchet of `leaf_index` in `secret_tree` forward to
+/// `generation` and returns the key and nonce of that generation.
+#[cfg(test)]
+fn encryption_secrets(
+    secret_tree: &mut crate::tree::secret_tree::SecretTree,
+    crypto: &impl openmls_traits::crypto::OpenMlsCrypto,
+    ciphersuite: Ciphersuite,
+    leaf_index: crate::binary_tree::LeafNodeIndex,
+    secret_type: crate::tree::secret_tree::SecretType,
+    generation: u32,
+) -> crate::tree::sender_ratchet::RatchetKeyMaterial {
+    loop {
+        let (current_generation, ratchet_key_material) = secret_tree
+            .secret_for_encryption(ciphersuite, crypto, leaf_index, secret_type)
+            .expect("Error getting encryption secret.");
+        if current_generation == generation {
+            return ratchet_key_material;
+        }
+    }
+}
+
+#[cfg(test)]
+pub fn generate_test_vector(
+    n_leaves: u32,
+    generations: &[u32],
+    ciphersuite: Ciphersuite,
+) -> SecretTree {
+    use openmls_rust_crypto::OpenMlsRustCrypto;
+    use tls_codec::Deserialize;
+
+    use crate::{
+        binary_tree::{array_representation::TreeSize, LeafNodeIndex},
+        ciphersuite::{ReuseGuard, REUSE_GUARD_BYTES},
+        schedule::{EncryptionSecret, SenderDataSecret},
+        tree::secret_tree::{self, SecretType},
+        versions::ProtocolVersion,
+    };
+
+    assert!(
+        generations.windows(2).all(|pair| pair[0] < pair[1]),
+        "The generations must be in ascending order."
+    );
+
+    let provider = OpenMlsRustCrypto::default();
+    let crypto = provider.crypto();
+    let hash_length = ciphersuite.hash_length();
+
+    let sender_data_secret_bytes = test_input(
+        crypto,
+        ciphersuite,
+        "sender data secret",
+        &[n_leaves],
+        hash_length,
+    );
+    let sender_data_secret = SenderDataSecret::from_slice(
+        &sender_data_secret_bytes,
+        ProtocolVersion::Mls10,
+        ciphersuite,
+    );
+    let ciphertext = test_input(crypto, ciphersuite, "ciphertext", &[n_leaves], hash_length);
+    let sender_data_key = sender_data_secret
+        .derive_aead_key(crypto, &ciphertext)
+        .expect("Could not derive AEAD key.");
+    let sender_data_nonce = sender_data_secret
+        .derive_aead_nonce(ciphersuite, crypto, &ciphertext)
+        .expect("Could not derive nonce.");
+    let sender_data = SenderData {
+        sender_data_secret: bytes_to_hex(&sender_data_secret_bytes),
+        ciphertext: bytes_to_hex(&ciphertext),
+        key: bytes_to_hex(sender_data_key.as_slice()),
+        nonce: bytes_to_hex(sender_data_nonce.as_slice()),
+    };
+
+    let encryption_secret = test_input(
+        crypto,
+        ciphersuite,
+        "encryption secret",
+        &[n_leaves],
+        hash_length,
+    );
+    let leaves = (0..n_leaves)
+        .map(|leaf_index| {
+            let leaf_index = LeafNodeIndex::new(leaf_index);
+            let mut secret_tree = secret_tree::SecretTree::new(
+                EncryptionSecret::from_slice(
+                    &encryption_secret,
+                    ProtocolVersion::Mls10,
+                    ciphersuite,
+                ),
+                TreeSize::new(n_leaves),
+                leaf_index,
+            );
+            generations
+                .iter()
+                .map(|&generation| {
+                    let (handshake_key, handshake_nonce) = encryption_secrets(
+                        &mut secret_tree,
+                        crypto,
+                        ciphersuite,
+                        leaf_index,
+                        SecretType::HandshakeSecret,
+                        generation,
+                    );
+                    let (application_key, application_nonce) = encryption_secrets(
+                        &mut secret_tree,
+                        crypto,
+                        ciphersuite,
+                        leaf_index,
+                        SecretType::ApplicationSecret,
+                        generation,
+                    );
+                    let reuse_guard = test_input(
+                        crypto,
+                        ciphersuite,
+                        "reuse guard",
+                        &[n_leaves, leaf_index.u32(), generation],
+                        REUSE_GUARD_BYTES,
+                    );
+                    let guard = ReuseGuard::tls_deserialize_exact(&reuse_guard)
+                        .expect("Error deserializing reuse guard.");
+                    Leaf {
+                        generation,
+                        application_key: bytes_to_hex(application_key.as_slice()),
+                        application_nonce: bytes_to_hex(application_nonce.as_slice()),
+                        handshake_key: bytes_to_hex(handshake_key.as_slice()),
+                        handshake_nonce: bytes_to_hex(handshake_nonce.as_slice()),
+                        reuse_guard: Some(bytes_to_hex(&reuse_guard)),
+                        application_guarded_nonce: Some(bytes_to_hex(
+                            application_nonce.xor_with_reuse_guard(&guard).as_slice(),
+                        )),
+                        handshake_guarded_nonce: Some(bytes_to_hex(
+                            handshake_nonce.xor_with_reuse_guard(&guard).as_slice(),
+                        )),
+                    }
+                })
+                .collect()
+        })
+        .collect();
+
+    SecretTree {
+        cipher_suite: ciphersuite as u16,
+        encryption_secret: bytes_to_hex(&encryption_secret),
+        sender_data,
+        leaves,
+    }
+}
+
+#[test]
+fn write_test_vectors() {
+    use openmls_rust_crypto::OpenMlsRustCrypto;
+    use openmls_traits::crypto::OpenMlsCrypto;
+
+    let _ = pretty_env_logger::try_init();
+    log::debug!("Generating new test vectors ...");
+
+    let mut tests = Vec::new();
+    for &ciphersuite in OpenMlsRustCrypto::default()
+        .crypto()
+        .supported_ciphersuites()
+        .iter()
+    {
+        for n_leaves in [1, 2, 8, 32] {
+            tests.push(generate_test_vector(
+                n_leaves,
+                &[0, 1, 2, 15, 255],
+                ciphersuite,
+            ));
+        }
+    }
+
+    write("test_vectors/secret-tree-openmls-new.json", &tests);
+}
+
+#[test]
+fn deterministic_test_vectors() {
+    let tests: Vec<SecretTree> = read("test_vectors/secret-tree-openmls.json");
+
+    for test_vector in tests {
+        let ciphersuite = Ciphersuite::try_from(test_vector.cipher_suite).unwrap();
+        let generations: Vec<u32> = test_vector.leaves[0]
+            .iter()
+            .map(|leaf| leaf.generation)
+            .collect();
+        let generated =
+            generate_test_vector(test_vector.leaves.len() as u32, &generations, ciphersuite);
+        assert_eq!(generated, test_vector);
+    }
+}
+
 #[cfg(test)]
 pub fn run_test_vector(test: SecretTree, provider: &impl OpenMlsProvider) -> Result<(), String> {
     use openmls_traits::crypto::OpenMlsCrypto;
 
+    use tls_codec::Deserialize;
+
     use crate::{
         binary_tree::{array_representation::TreeSize, LeafNodeIndex},
+        ciphersuite::ReuseGuard,
         schedule::{EncryptionSecret, SenderDataSecret},
-        tree::secret_tree::{SecretTree, SecretType},
+        tree::{
+            secret_tree::{SecretTree, SecretTreeError, SecretType},
+            sender_ratchet::SenderRatchetConfiguration,
+        },
         versions::ProtocolVersion,
     };
 
@@ -181,6 +429,79 @@ pub fn run_test_vector(test: SecretTree, provider: &impl OpenMlsProvider) -> Res
                 handshake.1.as_slice(),
                 &hex_to_bytes(&leaf_generation.handshake_nonce)
             );
+
+            // Check the nonces with the reuse guard
+            if let Some(reuse_guard) = &leaf_generation.reuse_guard {
+                let reuse_guard = ReuseGuard::tls_deserialize_exact(hex_to_bytes(reuse_guard))
+                    .map_err(|e| format!("Invalid reuse guard: {e:?}"))?;
+                let application_guarded_nonce = leaf_generation
+                    .application_guarded_nonce
+                    .as_ref()
+                    .ok_or("Missing application guarded nonce.")?;
+                assert_eq!(
+                    application.1.xor_with_reuse_guard(&reuse_guard).as_slice(),
+                    &hex_to_bytes(application_guarded_nonce)
+                );
+                let handshake_guarded_nonce = leaf_generation
+                    .handshake_guarded_nonce
+                    .as_ref()
+                    .ok_or("Missing handshake guarded nonce.")?;
+                assert_eq!(
+                    handshake.1.xor_with_reuse_guard(&reuse_guard).as_slice(),
+                    &hex_to_bytes(handshake_guarded_nonce)
+                );
+            }
+        }
+
+        // Another member derives the same secrets for decryption, but only
+        // once per generation.
+        if num_leaves == 1 {
+            continue;
+        }
+        let sender = LeafNodeIndex::new(leaf_index as u32);
+        let mut secret_tree = SecretTree::new(
+            EncryptionSecret::from_slice(&encryption_secret, ProtocolVersion::Mls10, ciphersuite),
+            TreeSize::new(num_leaves as u32),
+            LeafNodeIndex::new(u32::from(leaf_index == 0)),
+        );
+        for leaf_generation in leaf {
+            let generation = leaf_generation.generation;
+            for (secret_type, key, nonce) in [
+                (
+                    SecretType::HandshakeSecret,
+                    &leaf_generation.handshake_key,
+                    &leaf_generation.handshake_nonce,
+                ),
+                (
+                    SecretType::ApplicationSecret,
+                    &leaf_generation.application_key,
+                    &leaf_generation.application_nonce,
+                ),
+            ] {
+                let (my_key, my_nonce) = secret_tree
+                    .secret_for_decryption(
+                        ciphersuite,
+                        provider.crypto(),
+                        sender,
+                        secret_type,
+                        generation,
+                        &SenderRatchetConfiguration::default(),
+                    )
+                    .map_err(|e| format!("Error getting decryption secret: {e:?}"))?;
+                assert_eq!(my_key.as_slice(), &hex_to_bytes(key));
+                assert_eq!(my_nonce.as_slice(), &hex_to_bytes(nonce));
+                assert!(matches!(
+                    secret_tree.secret_for_decryption(
+                        ciphersuite,
+                        provider.crypto(),
+                        sender,
+                        secret_type,
+                        generation,
+                        &SenderRatchetConfiguration::default(),
+                    ),
+                    Err(SecretTreeError::SecretReuseError)
+                ));
+            }
         }
     }
 
@@ -192,7 +513,10 @@ fn read_test_vectors_st(provider: &impl OpenMlsProvider) {
     let _ = pretty_env_logger::try_init();
     log::debug!("Reading test vectors ...");
 
-    let tests: Vec<SecretTree> = read("test_vectors/secret-tree.json");
+    let mut tests: Vec<SecretTree> = read("test_vectors/secret-tree.json");
+    tests.extend(read::<Vec<SecretTree>>(
+        "test_vectors/secret-tree-openmls.json",
+    ));
 
     for test_vector in tests {
         match run_test_vector(test_vector, provider) {
@@ -260,3 +260,233 @@ fn secret_tree(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
         application_secret_nonce.as_slice()
     );
 }
+
+// Test that the keys of skipped messages are evicted, oldest generation first,
+// once a limit is exceeded.
+#[apply(ciphersuites_and_providers)]
+fn skipped_key_limits(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let configuration = &SenderRatchetConfiguration::new(10, 1000)
+        .with_max_skipped_keys_per_sender(3)
+        .with_max_skipped_keys(4);
+    let encryption_secret = EncryptionSecret::random(ciphersuite, provider.rand());
+    let mut secret_tree = SecretTree::new(
+        encryption_secret,
+        TreeSize::from_leaf_count(4u32),
+        LeafNodeIndex::new(3u32),
+    );
+    let secret_type = SecretType::ApplicationSecret;
+    let decrypt = |secret_tree: &mut SecretTree, sender: u32, generation: u32| {
+        secret_tree
+            .secret_for_decryption(
+                ciphersuite,
+                provider.crypto(),
+                LeafNodeIndex::new(sender),
+                secret_type,
+                generation,
+                configuration,
+            )
+            .map(|_| ())
+    };
+
+    // Sender 0 skips five messages, two of which exceed the per-sender limit.
+    decrypt(&mut secret_tree, 0, 5).expect("error decrypting");
+    // Sender 1 skips two messages, which exceeds the global limit. The key of
+    // the oldest generation is evicted, which is one of sender 1.
+    decrypt(&mut secret_tree, 1, 2).expect("error decrypting");
+    assert_eq!(
+        secret_tree.take_evicted_keys(),
+        vec![
+            (LeafNodeIndex::new(0), 0),
+            (LeafNodeIndex::new(0), 1),
+            (LeafNodeIndex::new(1), 0)
+        ]
+    );
+    assert!(secret_tree.take_evicted_keys().is_empty());
+
+    // Evicted keys are distinguishable from used keys.
+    assert_eq!(
+        decrypt(&mut secret_tree, 0, 0),
+        Err(SecretTreeError::KeyEvicted)
+    );
+    decrypt(&mut secret_tree, 0, 3).expect("error decrypting");
+    assert_eq!(
+        decrypt(&mut secret_tree, 0, 3),
+        Err(SecretTreeError::SecretReuseError)
+    );
+    decrypt(&mut secret_tree, 1, 1).expect("error decrypting");
+    assert!(secret_tree.take_evicted_keys().is_empty());
+}
+
+// Test that keys which are evicted while a decryption fails are still
+// reported, e.g., after the limits were lowered.
+#[apply(ciphersuites_and_providers)]
+fn skipped_key_evictions_on_error(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let encryption_secret = EncryptionSecret::random(ciphersuite, provider.rand());
+    let mut secret_tree = SecretTree::new(
+        encryption_secret,
+        TreeSize::from_leaf_count(4u32),
+        LeafNodeIndex::new(3u32),
+    );
+    let secret_type = SecretType::ApplicationSecret;
+    let decrypt = |secret_tree: &mut SecretTree,
+                   sender: u32,
+                   generation: u32,
+                   configuration: &SenderRatchetConfiguration| {
+        secret_tree
+            .secret_for_decryption(
+                ciphersuite,
+                provider.crypto(),
+                LeafNodeIndex::new(sender),
+                secret_type,
+                generation,
+                configuration,
+            )
+            .map(|_| ())
+    };
+
+    // Senders 0 and 1 skip three messages each.
+    let configuration = SenderRatchetConfiguration::new(10, 1000);
+    decrypt(&mut secret_tree, 0, 3, &configuration).expect("error decrypting");
+    decrypt(&mut secret_tree, 1, 3, &configuration).expect("error decrypting");
+    assert!(secret_tree.take_evicted_keys().is_empty());
+
+    // A replay of sender 0 fails, but the keys evicted under the lowered
+    // limits are reported.
+    let configuration = SenderRatchetConfiguration::new(10, 1000)
+        .with_max_skipped_keys_per_sender(2)
+        .with_max_skipped_keys(3);
+    assert_eq!(
+        decrypt(&mut secret_tree, 0, 3, &configuration),
+        Err(SecretTreeError::SecretReuseError)
+    );
+    assert_eq!(
+        secret_tree.take_evicted_keys(),
+        vec![
+            (LeafNodeIndex::new(0), 0),
+            (LeafNodeIndex::new(1), 0),
+            (LeafNodeIndex::new(0), 1)
+        ]
+    );
+}
+
+// Test that the secret tree of a large group in which only a few members send
+// uses about as much memory as that of a small group.
+#[apply(ciphersuites_and_providers)]
+fn silent_members_are_cheap(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let configuration = &SenderRatchetConfiguration::default();
+    let memory_usage = |leaf_count: u32| {
+        let encryption_secret = EncryptionSecret::random(ciphersuite, provider.rand());
+        let mut secret_tree = SecretTree::new(
+            encryption_secret,
+            TreeSize::from_leaf_count(leaf_count),
+            LeafNodeIndex::new(0),
+        );
+        for sender in [1, 2] {
+            secret_tree
+                .secret_for_decryption(
+                    ciphersuite,
+                    provider.crypto(),
+                    LeafNodeIndex::new(sender),
+                    SecretType::ApplicationSecret,
+                    0,
+                    configuration,
+                )
+                .expect("error decrypting");
+        }
+        secret_tree.memory_usage(ciphersuite)
+    };
+
+    // The large tree only stores the secrets of the additional levels on the
+    // path of the senders.
+    let node_size = std::mem::size_of::<(u32, SecretTreeNode)>() + ciphersuite.hash_length();
+    let additional_levels = 16 - 4;
+    assert!(memory_usage(1 << 16) <= memory_usage(1 << 4) + additional_levels * node_size);
+}
+
+// Test that secret trees stored with one entry per leaf can still be loaded.
+#[apply(ciphersuites_and_providers)]
+fn load_dense_secret_tree(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let encryption_secret = EncryptionSecret::random(ciphersuite, provider.rand());
+    let mut secret_tree = SecretTree::new(
+        encryption_secret,
+        TreeSize::from_leaf_count(8),
+        LeafNodeIndex::new(0),
+    );
+    secret_tree
+        .secret_for_decryption(
+            ciphersuite,
+            provider.crypto(),
+            LeafNodeIndex::new(5),
+            SecretType::HandshakeSecret,
+            0,
+            &SenderRatchetConfiguration::default(),
+        )
+        .expect("error decrypting");
+
+    let mut value = serde_json::to_value(&secret_tree).expect("error serializing");
+    for field in [
+        "leaf_nodes",
+        "parent_nodes",
+        "handshake_sender_ratchets",
+        "application_sender_ratchets",
+    ] {
+        let entries = value[field].as_object().expect("expected a map").clone();
+        let length = entries
+            .keys()
+            .map(|index| index.parse::<usize>().expect("expected an index") + 1)
+            .max()
+            .unwrap_or(0);
+        let dense: Vec<serde_json::Value> = (0..length)
+            .map(|index| {
+                entries
+                    .get(&index.to_string())
+                    .cloned()
+                    .unwrap_or(serde_json::Value::Null)
+            })
+            .collect();
+        value[field] = dense.into();
+    }
+    let loaded: SecretTree = serde_json::from_value(value).expect("error deserializing");
+    assert_eq!(loaded, secret_tree);
+}
+
+// Test that the secret of a leaf is deleted as soon as one of its sender
+// ratchets is used.
+#[apply(ciphersuites_and_providers)]
+fn leaf_secret_is_deleted_on_first_use(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let encryption_secret = EncryptionSecret::random(ciphersuite, provider.rand());
+    let mut secret_tree = SecretTree::new(
+        encryption_secret,
+        TreeSize::from_leaf_count(8),
+        LeafNodeIndex::new(0),
+    );
+    // Leaf 5 only sends application messages and the own leaf only sends
+    // handshake messages.
+    secret_tree
+        .secret_for_decryption(
+            ciphersuite,
+            provider.crypto(),
+            LeafNodeIndex::new(5),
+            SecretType::ApplicationSecret,
+            0,
+            &SenderRatchetConfiguration::default(),
+        )
+        .expect("error decrypting");
+    secret_tree
+        .secret_for_encryption(
+            ciphersuite,
+            provider.crypto(),
+            LeafNodeIndex::new(0),
+            SecretType::HandshakeSecret,
+        )
+        .expect("error encrypting");
+
+    let value = serde_json::to_value(&secret_tree).expect("error serializing");
+    for leaf in ["0", "5"] {
+        assert!(value["leaf_nodes"].get(leaf).is_none());
+        assert!(value["handshake_sender_ratchets"].get(leaf).is_some());
+        assert!(value["application_sender_ratchets"].get(leaf).is_some());
+    }
+    // The secret of the sibling of leaf 5 is kept until it is used.
+    assert!(value["leaf_nodes"].get("4").is_some());
+}
@@ -1,8 +1,8 @@
 use openmls_rust_crypto::OpenMlsRustCrypto;
 
 use crate::{
-    ciphersuite::Secret, test_utils::*, tree::secret_tree::SecretTreeError,
-    tree::sender_ratchet::*, versions::ProtocolVersion,
+    binary_tree::LeafNodeIndex, ciphersuite::Secret, test_utils::*,
+    tree::secret_tree::SecretTreeError, tree::sender_ratchet::*, versions::ProtocolVersion,
 };
 
 // Test the maximum forward ratcheting
@@ -150,3 +150,34 @@ fn sender_ratchet_generation_overflow() {
         .expect_err("no error exceeding generation u32::MAX");
     assert_eq!(err, SecretTreeError::RatchetTooLong)
 }
+
+#[test]
+fn sender_overrides() {
+    let configuration = SenderRatchetConfiguration::new(1, 10)
+        .with_sender_override(SenderSelector::LeafIndex(LeafNodeIndex::new(2)), 2, 20)
+        .with_sender_override(SenderSelector::Identity(b"Bot".to_vec()), 3, 30)
+        .with_sender_override(SenderSelector::LeafIndex(LeafNodeIndex::new(3)), 4, 40);
+
+    // Senders without an override get the default configuration.
+    assert_eq!(
+        configuration.for_sender(LeafNodeIndex::new(0), Some(b"Alice")),
+        SenderRatchetConfiguration::new(1, 10)
+    );
+    assert_eq!(
+        configuration.for_sender(LeafNodeIndex::new(2), Some(b"Alice")),
+        SenderRatchetConfiguration::new(2, 20)
+    );
+    assert_eq!(
+        configuration.for_sender(LeafNodeIndex::new(1), Some(b"Bot")),
+        SenderRatchetConfiguration::new(3, 30)
+    );
+    assert_eq!(
+        configuration.for_sender(LeafNodeIndex::new(1), None),
+        SenderRatchetConfiguration::new(1, 10)
+    );
+    // The first matching override applies.
+    assert_eq!(
+        configuration.for_sender(LeafNodeIndex::new(3), Some(b"Bot")),
+        SenderRatchetConfiguration::new(3, 30)
+    );
+}
@@ -26,6 +26,7 @@ use serde::{Deserialize, Serialize};
 
 use super::{
     errors::*,
+    hashes::{MembershipProof, MembershipProofStep},
     node::{
         encryption_keys::{EncryptionKey, EncryptionKeyPair, EncryptionPrivateKey},
         parent_node::{ParentNode, PathDerivationResult, PlainUpdatePathNode},
@@ -73,6 +74,10 @@ impl StagedTreeSyncDiff {
     ) {
         (self.diff, self.new_tree_hash)
     }
+
+    pub(super) fn diff(&self) -> &StagedMlsBinaryTreeDiff<TreeSyncLeafNode, TreeSyncParentNode> {
+        &self.diff
+    }
 }
 
 /// A [`TreeSyncDiff`] serves as a way to perform changes on an otherwise
@@ -715,6 +720,30 @@ impl<'a> TreeSyncDiff<'a> {
         }
     }
 
+    /// Returns a [`MembershipProof`] for the leaf with the given index.
+    pub(super) fn membership_proof(
+        &self,
+        crypto: &impl OpenMlsCrypto,
+        ciphersuite: Ciphersuite,
+        leaf_index: LeafNodeIndex,
+    ) -> Result<MembershipProof, LibraryError> {
+        let path = self
+            .diff
+            .direct_path(leaf_index)
+            .into_iter()
+            .zip(self.diff.copath(leaf_index))
+            .map(|(parent_index, copath_index)| {
+                let copath_hash =
+                    self.compute_tree_hash(crypto, ciphersuite, copath_index, &HashSet::new())?;
+                Ok(MembershipProofStep::new(
+                    self.diff.parent(parent_index).node().clone(),
+                    copath_hash,
+                ))
+            })
+            .collect::<Result<Vec<_>, LibraryError>>()?;
+        Ok(MembershipProof::new(leaf_index, path))
+    }
+
     /// Return a reference to the leaf with the given index.
     pub(crate) fn leaf(&self, index: LeafNodeIndex) -> Option<&LeafNode> {
         self.diff.leaf(index).node().as_ref()
@@ -1,7 +1,10 @@
 //! This module contains helper structs and functions related to parent hashing
 //! and tree hashing.
 use openmls_traits::{crypto::OpenMlsCrypto, types::Ciphersuite};
-use tls_codec::{Serialize, TlsSerialize, TlsSize, VLByteSlice};
+use serde::{Deserialize, Serialize};
+use tls_codec::{
+    Serialize as TlsSerializeTrait, TlsDeserialize, TlsSerialize, TlsSize, VLByteSlice, VLBytes,
+};
 
 use crate::{
     binary_tree::array_representation::LeafNodeIndex, ciphersuite::HpkePublicKey,
@@ -166,3 +169,95 @@ struct ParentNodeHashInput<'a> {
     left_hash: VLByteSlice<'a>,
     right_hash: VLByteSlice<'a>,
 }
+
+/// A proof that a leaf is part of a ratchet tree with a given tree hash. The
+/// proof contains the parent nodes on the direct path of the leaf and the tree
+/// hashes of the nodes on its copath, but no other leaves of the tree.
+///
+/// ```c
+/// struct {
+///     optional<ParentNode> parent_node;
+///     opaque copath_hash<V>;
+/// } MembershipProofStep;
+///
+/// struct {
+///     uint32 leaf_index;
+///     MembershipProofStep path<V>;
+/// } MembershipProof;
+/// ```
+#[derive(
+    Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TlsSerialize, TlsDeserialize, TlsSize,
+)]
+pub struct MembershipProof {
+    leaf_index: LeafNodeIndex,
+    // From the parent of the leaf to the root.
+    path: Vec<MembershipProofStep>,
+}
+
+#[derive(
+    Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TlsSerialize, TlsDeserialize, TlsSize,
+)]
+pub(super) struct MembershipProofStep {
+    parent_node: Option<ParentNode>,
+    copath_hash: VLBytes,
+}
+
+impl MembershipProofStep {
+    pub(super) fn new(parent_node: Option<ParentNode>, copath_hash: Vec<u8>) -> Self {
+        Self {
+            parent_node,
+            copath_hash: copath_hash.into(),
+        }
+    }
+}
+
+impl MembershipProof {
+    pub(super) fn new(leaf_index: LeafNodeIndex, path: Vec<MembershipProofStep>) -> Self {
+        Self { leaf_index, path }
+    }
+
+    /// Returns the index of the leaf.
+    pub fn leaf_index(&self) -> LeafNodeIndex {
+        self.leaf_index
+    }
+
+    /// Returns `true` if the proof shows that `leaf_node` is at the leaf index
+    /// of the proof in a tree with the given `tree_hash`.
+    pub(crate) fn verify(
+        &self,
+        crypto: &impl OpenMlsCrypto,
+        ciphersuite: Ciphersuite,
+        leaf_node: &LeafNode,
+        tree_hash: &[u8],
+    ) -> Result<bool, LibraryError> {
+        // The leaf index must fit into a tree of the height of the proof.
+        if self
+            .leaf_index
+            .u32()
+            .checked_shr(self.path.len() as u32)
+            .is_some_and(|rest| rest != 0)
+        {
+            return Ok(false);
+        }
+
+        let mut hash =
+            TreeHashInput::new_leaf(&self.leaf_index, Some(leaf_node)).hash(crypto, ciphersuite)?;
+        for (level, step) in self.path.iter().enumerate() {
+            // The leaf is in the left subtree of the parent at this level if
+            // the corresponding bit of its index is not set.
+            let (left_hash, right_hash) = if (self.leaf_index.u32() >> level) & 1 == 0 {
+                (hash.as_slice(), step.copath_hash.as_slice())
+            } else {
+                (step.copath_hash.as_slice(), hash.as_slice())
+            };
+            hash = TreeHashInput::new_parent(
+                step.parent_node.as_ref(),
+                VLByteSlice(left_hash),
+                VLByteSlice(right_hash),
+            )
+            .hash(crypto, ciphersuite)?;
+        }
+
+        Ok(hash == tree_hash)
+    }
+}
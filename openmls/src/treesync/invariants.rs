@@ -0,0 +1,90 @@
+//! Invariant checks for the [`TreeSync`] state. Only available with the
+//! `check-invariants` feature.
+
+use openmls_traits::{crypto::OpenMlsCrypto, types::Ciphersuite};
+
+use super::TreeSync;
+use crate::binary_tree::array_representation::direct_path;
+
+impl TreeSync {
+    /// Check the invariants of the tree and return a description of every
+    /// violation. The following invariants are checked:
+    ///
+    /// * The parent hashes of all parent nodes are valid.
+    /// * The cached tree hash matches the tree hash computed from the nodes.
+    /// * Every unmerged leaf of a parent node is a non-blank leaf below the
+    ///   parent node and is also an unmerged leaf of all non-blank parent
+    ///   nodes between the leaf and the parent node.
+    pub(crate) fn invariant_violations(
+        &self,
+        crypto: &impl OpenMlsCrypto,
+        ciphersuite: Ciphersuite,
+    ) -> Vec<String> {
+        let mut violations = Vec::new();
+
+        if self.verify_parent_hashes(crypto, ciphersuite).is_err() {
+            violations.push("the tree contains an invalid parent hash".to_string());
+        }
+
+        match self.empty_diff().compute_tree_hashes(crypto, ciphersuite) {
+            Ok(tree_hash) if tree_hash != self.tree_hash => violations.push(format!(
+                "the cached tree hash {:x?} doesn't match the computed tree hash {:x?}",
+                self.tree_hash, tree_hash
+            )),
+            Ok(_) => (),
+            Err(e) => violations.push(format!("the tree hash can't be computed: {e}")),
+        }
+
+        let tree_size = self.tree.tree_size();
+        for (parent_index, parent) in self.tree.parents() {
+            let parent_node = match parent.node() {
+                Some(parent_node) => parent_node,
+                None => continue,
+            };
+            for &leaf_index in parent_node.unmerged_leaves() {
+                if leaf_index.u32() >= self.tree.leaf_count() {
+                    violations.push(format!(
+                        "unmerged leaf {} of parent node {} is outside the tree",
+                        leaf_index.u32(),
+                        parent_index.u32()
+                    ));
+                    continue;
+                }
+                if self.tree.leaf(leaf_index).node().is_none() {
+                    violations.push(format!(
+                        "unmerged leaf {} of parent node {} is blank",
+                        leaf_index.u32(),
+                        parent_index.u32()
+                    ));
+                }
+                let path = direct_path(leaf_index, tree_size);
+                let position = match path.iter().position(|&index| index == parent_index) {
+                    Some(position) => position,
+                    None => {
+                        violations.push(format!(
+                            "unmerged leaf {} of parent node {} is not below the parent node",
+                            leaf_index.u32(),
+                            parent_index.u32()
+                        ));
+                        continue;
+                    }
+                };
+                for &intermediate_index in &path[..position] {
+                    if let Some(intermediate) = self.tree.parent_by_index(intermediate_index).node()
+                    {
+                        if !intermediate.unmerged_leaves().contains(&leaf_index) {
+                            violations.push(format!(
+                                "unmerged leaf {} of parent node {} is missing in parent node {}",
+                                leaf_index.u32(),
+                                parent_index.u32(),
+                                intermediate_index.u32()
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+
+        violations
+    }
+}
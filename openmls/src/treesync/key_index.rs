@@ -0,0 +1,173 @@
+//! An index of the signature and encryption keys of the leaves of a
+//! [`TreeSync`] instance.
+//!
+//! RFC 9420 requires that the signature keys and the encryption keys of all
+//! leaves of the tree are unique. Instead of collecting the keys of all
+//! members whenever proposals are validated, the [`KeyIndex`] maps every key
+//! to the leaves that use it. It is built once when a tree is created or
+//! loaded and is updated with the changed leaves whenever a diff is merged.
+
+use std::collections::{BTreeSet, HashMap};
+
+use super::{diff::StagedTreeSyncDiff, node::leaf_node::LeafNode, TreeSync};
+use crate::binary_tree::array_representation::LeafNodeIndex;
+
+/// A signature or encryption key that is used by more than one leaf of the
+/// tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyUniquenessViolation {
+    /// The signature key is used by all of the given leaves.
+    SignatureKey {
+        /// The signature key.
+        key: Vec<u8>,
+        /// The leaves that use the key, in ascending order.
+        leaves: Vec<LeafNodeIndex>,
+    },
+    /// The encryption key is used by all of the given leaves.
+    EncryptionKey {
+        /// The encryption key.
+        key: Vec<u8>,
+        /// The leaves that use the key, in ascending order.
+        leaves: Vec<LeafNodeIndex>,
+    },
+}
+
+impl KeyUniquenessViolation {
+    /// Returns the leaves that use the same key.
+    pub fn leaves(&self) -> &[LeafNodeIndex] {
+        match self {
+            KeyUniquenessViolation::SignatureKey { leaves, .. }
+            | KeyUniquenessViolation::EncryptionKey { leaves, .. } => leaves,
+        }
+    }
+}
+
+/// Maps the signature and encryption keys of the leaves of a tree to the
+/// indices of the leaves that use them. Keys map to more than one leaf only if
+/// the tree violates the uniqueness requirements, e.g., if duplicate keys were
+/// accepted with a relaxed validation policy.
+#[derive(Debug, Default)]
+#[cfg_attr(test, derive(PartialEq, Clone))]
+pub(crate) struct KeyIndex {
+    signature_keys: HashMap<Vec<u8>, BTreeSet<LeafNodeIndex>>,
+    encryption_keys: HashMap<Vec<u8>, BTreeSet<LeafNodeIndex>>,
+}
+
+impl KeyIndex {
+    /// Builds the index of the given leaves.
+    fn new<'a>(leaves: impl Iterator<Item = (LeafNodeIndex, &'a LeafNode)>) -> Self {
+        let mut key_index = Self::default();
+        for (leaf_index, leaf_node) in leaves {
+            key_index.insert(leaf_index, leaf_node);
+        }
+        key_index
+    }
+
+    /// Adds the keys of `leaf_node` at `leaf_index` to the index.
+    fn insert(&mut self, leaf_index: LeafNodeIndex, leaf_node: &LeafNode) {
+        self.signature_keys
+            .entry(leaf_node.signature_key().as_slice().to_vec())
+            .or_default()
+            .insert(leaf_index);
+        self.encryption_keys
+            .entry(leaf_node.encryption_key().as_slice().to_vec())
+            .or_default()
+            .insert(leaf_index);
+    }
+
+    /// Removes the keys of `leaf_node` at `leaf_index` from the index.
+    fn remove(&mut self, leaf_index: LeafNodeIndex, leaf_node: &LeafNode) {
+        for (keys, key) in [
+            (
+                &mut self.signature_keys,
+                leaf_node.signature_key().as_slice(),
+            ),
+            (
+                &mut self.encryption_keys,
+                leaf_node.encryption_key().as_slice(),
+            ),
+        ] {
+            if let Some(leaves) = keys.get_mut(key) {
+                leaves.remove(&leaf_index);
+                if leaves.is_empty() {
+                    keys.remove(key);
+                }
+            }
+        }
+    }
+
+    /// Returns the leaves that use the signature key `key`.
+    pub(crate) fn signature_key_leaves(
+        &self,
+        key: &[u8],
+    ) -> impl Iterator<Item = LeafNodeIndex> + '_ {
+        self.signature_keys.get(key).into_iter().flatten().copied()
+    }
+
+    /// Returns the leaves that use the encryption key `key`.
+    pub(crate) fn encryption_key_leaves(
+        &self,
+        key: &[u8],
+    ) -> impl Iterator<Item = LeafNodeIndex> + '_ {
+        self.encryption_keys.get(key).into_iter().flatten().copied()
+    }
+
+    /// Returns all keys that are used by more than one leaf, ordered by the
+    /// index of the first leaf that uses them.
+    pub(crate) fn violations(&self) -> Vec<KeyUniquenessViolation> {
+        let duplicates = |keys: &HashMap<Vec<u8>, BTreeSet<LeafNodeIndex>>| {
+            keys.iter()
+                .filter(|(_, leaves)| leaves.len() > 1)
+                .map(|(key, leaves)| (key.clone(), leaves.iter().copied().collect::<Vec<_>>()))
+                .collect::<Vec<_>>()
+        };
+        let mut violations = duplicates(&self.signature_keys)
+            .into_iter()
+            .map(|(key, leaves)| KeyUniquenessViolation::SignatureKey { key, leaves })
+            .chain(
+                duplicates(&self.encryption_keys)
+                    .into_iter()
+                    .map(|(key, leaves)| KeyUniquenessViolation::EncryptionKey { key, leaves }),
+            )
+            .collect::<Vec<_>>();
+        violations.sort_by(|a, b| a.leaves().cmp(b.leaves()));
+        violations
+    }
+}
+
+impl TreeSync {
+    /// Returns the index of the keys of the leaves of the tree.
+    pub(crate) fn key_index(&self) -> &KeyIndex {
+        &self.key_index
+    }
+
+    /// Rebuilds the key index from all leaves of the tree.
+    pub(super) fn rebuild_key_index(&mut self) {
+        self.key_index = KeyIndex::new(
+            self.tree
+                .leaves()
+                .filter_map(|(index, leaf)| leaf.node().as_ref().map(|node| (index, node))),
+        );
+    }
+
+    /// Updates the key index with the leaves changed by `diff`. This has to
+    /// be called before the diff is merged into the tree.
+    pub(super) fn update_key_index(&mut self, diff: &StagedTreeSyncDiff) {
+        let diff = diff.diff();
+        for (leaf_index, leaf) in diff.changed_leaves() {
+            if let Some(old_leaf_node) = self.tree.leaf(leaf_index).node() {
+                self.key_index.remove(leaf_index, old_leaf_node);
+            }
+            if let Some(new_leaf_node) = leaf.node() {
+                self.key_index.insert(leaf_index, new_leaf_node);
+            }
+        }
+        // Leaves outside of the new tree are removed by the diff.
+        let leaf_count = diff.tree_size().leaf_count() as usize;
+        for (leaf_index, leaf) in self.tree.leaves().skip(leaf_count) {
+            if let Some(old_leaf_node) = leaf.node() {
+                self.key_index.remove(leaf_index, old_leaf_node);
+            }
+        }
+    }
+}
@@ -0,0 +1,54 @@
+//! Memory accounting for the [`TreeSync`] state.
+
+use tls_codec::Size;
+
+use super::{
+    diff::StagedTreeSyncDiff,
+    treesync_node::{TreeSyncLeafNode, TreeSyncParentNode},
+    TreeSync,
+};
+use crate::binary_tree::array_representation::{LeafNodeIndex, ParentNodeIndex, TreeSize};
+
+impl TreeSync {
+    /// Returns the approximate number of bytes used by the nodes of the tree.
+    pub(crate) fn memory_usage(&self) -> usize {
+        memory_usage(
+            self.tree.tree_size(),
+            |index| self.tree.leaf(index),
+            |index| self.tree.parent_by_index(index),
+        )
+    }
+
+    /// Returns the approximate number of bytes the nodes of the tree would use
+    /// after merging the given `diff`.
+    pub(crate) fn memory_usage_after(&self, diff: &StagedTreeSyncDiff) -> usize {
+        let diff = diff.diff();
+        memory_usage(
+            diff.tree_size(),
+            |index| {
+                diff.changed_leaf(index)
+                    .unwrap_or_else(|| self.tree.leaf(index))
+            },
+            |index| {
+                diff.changed_parent(index)
+                    .unwrap_or_else(|| self.tree.parent_by_index(index))
+            },
+        )
+    }
+}
+
+fn memory_usage<'a>(
+    tree_size: TreeSize,
+    leaf: impl Fn(LeafNodeIndex) -> &'a TreeSyncLeafNode,
+    parent: impl Fn(ParentNodeIndex) -> &'a TreeSyncParentNode,
+) -> usize {
+    let leaves: usize = (0..tree_size.leaf_count())
+        .filter_map(|index| leaf(LeafNodeIndex::new(index)).node().as_ref())
+        .map(|leaf_node| leaf_node.tls_serialized_len())
+        .sum();
+    let parents: usize = (0..tree_size.parent_count())
+        .filter_map(|index| parent(ParentNodeIndex::new(index)).node().as_ref())
+        .map(|parent_node| parent_node.tls_serialized_len())
+        .sum();
+    leaves + parents
+}
@@ -39,6 +39,7 @@ use tls_codec::{TlsDeserialize, TlsSerialize, TlsSize};
 
 use self::{
     diff::{StagedTreeSyncDiff, TreeSyncDiff},
+    key_index::KeyIndex,
     node::{
         leaf_node::{
             Capabilities, LeafNodeSource, NewLeafNodeParams, TreeInfoTbs, TreePosition,
@@ -73,6 +74,10 @@ use crate::{
 
 // Private
 mod hashes;
+#[cfg(feature = "check-invariants")]
+mod invariants;
+mod key_index;
+mod memory;
 use errors::*;
 
 // Crate
@@ -90,6 +95,8 @@ pub use node::encryption_keys::test_utils;
 pub use node::encryption_keys::EncryptionKey;
 
 // Public re-exports
+pub use hashes::MembershipProof;
+pub use key_index::KeyUniquenessViolation;
 pub use node::{leaf_node::LeafNode, parent_node::ParentNode, Node};
 
 // Tests
@@ -212,6 +219,11 @@ impl RatchetTree {
             }
         }
     }
+
+    /// Returns the nodes of this [`RatchetTree`] in array representation.
+    pub(crate) fn nodes(&self) -> &[Option<Node>] {
+        &self.0
+    }
 }
 
 /// A ratchet tree made of unverified nodes. This is used for deserialization
@@ -351,11 +363,38 @@ impl fmt::Display for RatchetTree {
 /// [`TreeSync`] instance guarantee a few invariants that are checked upon
 /// creating a new instance from an imported set of nodes, as well as when
 /// merging a diff.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize)]
 #[cfg_attr(test, derive(PartialEq, Clone))]
 pub(crate) struct TreeSync {
     tree: MlsBinaryTree<TreeSyncLeafNode, TreeSyncParentNode>,
     tree_hash: Vec<u8>,
+    // The index of the keys of the leaves. It is rebuilt when the tree is
+    // deserialized.
+    #[serde(skip)]
+    key_index: KeyIndex,
+}
+
+/// Helper struct that contains the serialized values of a [`TreeSync`].
+#[derive(Deserialize)]
+struct SerializedTreeSync {
+    tree: MlsBinaryTree<TreeSyncLeafNode, TreeSyncParentNode>,
+    tree_hash: Vec<u8>,
+}
+
+impl<'de> Deserialize<'de> for TreeSync {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let SerializedTreeSync { tree, tree_hash } = SerializedTreeSync::deserialize(deserializer)?;
+        let mut tree_sync = Self {
+            tree,
+            tree_hash,
+            key_index: KeyIndex::default(),
+        };
+        tree_sync.rebuild_key_index();
+        Ok(tree_sync)
+    }
 }
 
 impl TreeSync {
@@ -396,7 +435,9 @@ impl TreeSync {
         let mut tree_sync = Self {
             tree,
             tree_hash: vec![],
+            key_index: KeyIndex::default(),
         };
+        tree_sync.rebuild_key_index();
         // Populate tree hash caches.
         tree_sync.populate_parent_hashes(provider.crypto(), config.ciphersuite)?;
 
@@ -408,9 +449,22 @@ impl TreeSync {
         self.tree_hash.as_slice()
     }
 
+    /// Returns a [`MembershipProof`] for the leaf with the given index, which
+    /// shows that the leaf is part of a tree with the tree hash of this tree.
+    pub(crate) fn membership_proof(
+        &self,
+        crypto: &impl OpenMlsCrypto,
+        ciphersuite: Ciphersuite,
+        leaf_index: LeafNodeIndex,
+    ) -> Result<MembershipProof, LibraryError> {
+        self.empty_diff()
+            .membership_proof(crypto, ciphersuite, leaf_index)
+    }
+
     /// Merge the given diff into this `TreeSync` instance, refreshing the
     /// `tree_hash` value in the process.
     pub(crate) fn merge_diff(&mut self, tree_sync_diff: StagedTreeSyncDiff) {
+        self.update_key_index(&tree_sync_diff);
         let (diff, new_tree_hash) = tree_sync_diff.into_parts();
         self.tree_hash = new_tree_hash;
         self.tree.merge_diff(diff);
@@ -455,7 +509,9 @@ impl TreeSync {
         let mut tree_sync = Self {
             tree,
             tree_hash: vec![],
+            key_index: KeyIndex::default(),
         };
+        tree_sync.rebuild_key_index();
         // Verify all parent hashes.
         tree_sync
             .verify_parent_hashes(crypto, ciphersuite)
@@ -362,6 +362,11 @@ impl LeafNode {
         }
     }
 
+    /// Returns the [`LeafNodeSource`].
+    pub(crate) fn leaf_node_source(&self) -> &LeafNodeSource {
+        &self.payload.leaf_node_source
+    }
+
     /// Returns a reference to the [`Signature`] of this leaf.
     pub fn signature(&self) -> &Signature {
         &self.signature
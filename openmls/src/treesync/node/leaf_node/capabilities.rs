@@ -9,7 +9,7 @@ use crate::{
     extensions::{Extension, ExtensionType, Extensions, RequiredCapabilitiesExtension},
     messages::proposals::ProposalType,
     treesync::errors::LeafNodeValidationError,
-    versions::ProtocolVersion,
+    versions::{ProtocolVersion, SUPPORTED_PROTOCOL_VERSIONS},
 };
 
 /// Capabilities of [`LeafNode`]s.
@@ -194,7 +194,7 @@ impl Default for Capabilities {
 }
 
 pub(super) fn default_versions() -> Vec<ProtocolVersion> {
-    vec![ProtocolVersion::Mls10]
+    SUPPORTED_PROTOCOL_VERSIONS.to_vec()
 }
 
 pub(super) fn default_ciphersuites() -> Vec<Ciphersuite> {
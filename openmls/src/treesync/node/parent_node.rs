@@ -3,6 +3,7 @@
 //! [`UpdatePathNode`] instances.
 use openmls_traits::crypto::OpenMlsCrypto;
 use openmls_traits::types::{Ciphersuite, HpkeCiphertext};
+#[cfg(not(target_arch = "wasm32"))]
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use thiserror::*;
@@ -57,8 +58,12 @@ impl PlainUpdatePathNode {
         public_keys: &[EncryptionKey],
         group_context: &[u8],
     ) -> Result<UpdatePathNode, LibraryError> {
+        #[cfg(not(target_arch = "wasm32"))]
+        let public_keys = public_keys.par_iter();
+        #[cfg(target_arch = "wasm32")]
+        let public_keys = public_keys.iter();
+
         public_keys
-            .par_iter()
             .map(|pk| {
                 self.path_secret
                     .encrypt(crypto, ciphersuite, pk, group_context)
@@ -121,9 +126,13 @@ impl ParentNode {
             Vec<PlainUpdatePathNode>,
         );
 
+        #[cfg(not(target_arch = "wasm32"))]
+        let path_secrets = path_secrets.into_par_iter();
+        #[cfg(target_arch = "wasm32")]
+        let path_secrets = path_secrets.into_iter();
+
         // Iterate over the path secrets and derive a key pair
         let (path_with_keypairs, update_path_nodes): PathDerivationResults = path_secrets
-            .into_par_iter()
             .zip(path_indices)
             .map(|(path_secret, index)| {
                 // Derive a key pair from the path secret. This includes the
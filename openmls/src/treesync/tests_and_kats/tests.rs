@@ -109,7 +109,8 @@ fn that_commit_secret_is_derived_from_end_of_update_path_not_root(
             &alice.credential_with_key_and_signer.signer,
             &[bob.key_package, charlie.key_package, dave.key_package],
         )
-        .expect("Adding members failed.");
+        .expect("Adding members failed.")
+        .into_parts();
 
     alice_group.merge_pending_commit(&alice.provider).unwrap();
     alice_group.print_ratchet_tree("Alice (after add_members)");
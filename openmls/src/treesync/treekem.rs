@@ -4,12 +4,16 @@
 //!
 //! This module contains structs and functions to encrypt and decrypt path
 //! updates for a [`TreeSyncDiff`] instance.
+
+#![deny(clippy::unwrap_used, clippy::expect_used)]
+
 use std::collections::HashSet;
 
 use openmls_traits::{
     crypto::OpenMlsCrypto,
     types::{Ciphersuite, HpkeCiphertext},
 };
+#[cfg(not(target_arch = "wasm32"))]
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use tls_codec::{TlsDeserialize, TlsSerialize, TlsSize};
@@ -42,6 +46,7 @@ impl<'a> TreeSyncDiff<'a> {
     /// included in the resulting [`UpdatePath`].
     ///
     /// Returns the encrypted path (i.e. an [`UpdatePath`] instance).
+    #[cfg(test)]
     pub(crate) fn encrypt_path(
         &self,
         crypto: &impl OpenMlsCrypto,
@@ -51,9 +56,23 @@ impl<'a> TreeSyncDiff<'a> {
         exclusion_list: &HashSet<&LeafNodeIndex>,
         own_leaf_index: LeafNodeIndex,
     ) -> Result<Vec<UpdatePathNode>, LibraryError> {
-        // Copath resolutions with the corresponding public keys.
-        let copath_resolutions = self
-            .filtered_copath_resolutions(own_leaf_index, exclusion_list)
+        let copath_keys = self.copath_encryption_keys(own_leaf_index, exclusion_list);
+
+        // There should be as many copath resolutions.
+        debug_assert_eq!(copath_keys.len(), path.len());
+
+        encrypt_path_nodes(crypto, ciphersuite, path, &copath_keys, group_context)
+    }
+
+    /// Return the public keys in the copath resolutions of the direct path of
+    /// `own_leaf_index`, in the order of the path. The `exclusion_list` is
+    /// used to filter target leaves from the resolutions.
+    pub(crate) fn copath_encryption_keys(
+        &self,
+        own_leaf_index: LeafNodeIndex,
+        exclusion_list: &HashSet<&LeafNodeIndex>,
+    ) -> Vec<Vec<EncryptionKey>> {
+        self.filtered_copath_resolutions(own_leaf_index, exclusion_list)
             .into_iter()
             .map(|resolution| {
                 resolution
@@ -64,16 +83,7 @@ impl<'a> TreeSyncDiff<'a> {
                     })
                     .collect::<Vec<EncryptionKey>>()
             })
-            .collect::<Vec<Vec<EncryptionKey>>>();
-
-        // There should be as many copath resolutions.
-        debug_assert_eq!(copath_resolutions.len(), path.len());
-
-        // Encrypt the secrets
-        path.par_iter()
-            .zip(copath_resolutions.par_iter())
-            .map(|(node, resolution)| node.encrypt(crypto, ciphersuite, resolution, group_context))
-            .collect::<Result<Vec<UpdatePathNode>, LibraryError>>()
+            .collect()
     }
 
     /// Decrypt an [`UpdatePath`] originating from the given
@@ -223,6 +233,28 @@ impl<'a> TreeSyncDiff<'a> {
     }
 }
 
+/// Encrypt the nodes in `path` to the public keys of the corresponding copath
+/// resolutions in `copath_keys`. The `group_context` is used in the encryption
+/// of the nodes.
+///
+/// Returns the encrypted nodes in the order of the path.
+pub(crate) fn encrypt_path_nodes(
+    crypto: &impl OpenMlsCrypto,
+    ciphersuite: Ciphersuite,
+    path: &[PlainUpdatePathNode],
+    copath_keys: &[Vec<EncryptionKey>],
+    group_context: &[u8],
+) -> Result<Vec<UpdatePathNode>, LibraryError> {
+    #[cfg(not(target_arch = "wasm32"))]
+    let path = path.par_iter().zip(copath_keys.par_iter());
+    #[cfg(target_arch = "wasm32")]
+    let path = path.iter().zip(copath_keys.iter());
+
+    // Encrypt the secrets
+    path.map(|(node, resolution)| node.encrypt(crypto, ciphersuite, resolution, group_context))
+        .collect::<Result<Vec<UpdatePathNode>, LibraryError>>()
+}
+
 pub(crate) struct DecryptPathParams<'a> {
     pub(crate) version: ProtocolVersion,
     pub(crate) update_path: &'a [UpdatePathNode],
@@ -264,6 +296,8 @@ impl UpdatePathNode {
 
     /// Flip the last byte of every `encrypted_path_secret` in this node.
     #[cfg(test)]
+    // Only used by tests to tamper with well-formed update paths.
+    #[allow(clippy::expect_used)]
     fn flip_last_byte(&mut self) {
         let mut new_eps_vec = Vec::new();
         for eps in self.encrypted_path_secrets.as_slice() {
@@ -281,6 +315,7 @@ impl UpdatePathNode {
 
     /// Flip the last byte of the public key in this node.
     #[cfg(test)]
+    #[allow(clippy::expect_used)]
     fn flip_last_pk_byte(&mut self) {
         use tls_codec::{Deserialize, Serialize};
 
@@ -353,8 +388,9 @@ impl UpdatePath {
         self.nodes.pop()
     }
 
-    #[cfg(test)]
     /// Flip the last bytes of the public key in the last node in the path.
+    #[cfg(test)]
+    #[allow(clippy::expect_used)]
     pub fn flip_node_bytes(&mut self) {
         let mut last_node = self.nodes.pop().expect("path empty");
         last_node.flip_last_pk_byte();
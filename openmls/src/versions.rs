@@ -1,6 +1,14 @@
 //! # MLS versions
 //!
 //! Only MLS 1.0 is currently supported.
+//!
+//! The versions supported by OpenMLS are listed in
+//! [`SUPPORTED_PROTOCOL_VERSIONS`] and advertised in the capabilities of the
+//! client's leaf nodes. Structures that carry a protocol version (key packages,
+//! group infos and messages) must agree with the version of the group they
+//! belong to. A mismatch is never resolved silently by falling back to another
+//! version, but is reported as [`VersionError::VersionMismatch`] or one of the
+//! dedicated errors of the respective operation.
 
 use serde::{Deserialize, Serialize};
 use std::fmt;
@@ -39,6 +47,44 @@ pub enum ProtocolVersion {
     Mls10Draft11 = 200, // pre RFC version
 }
 
+/// The protocol versions supported by OpenMLS, in order of preference.
+pub const SUPPORTED_PROTOCOL_VERSIONS: &[ProtocolVersion] = &[ProtocolVersion::Mls10];
+
+impl ProtocolVersion {
+    /// Returns `true` if OpenMLS supports this protocol version and `false`
+    /// otherwise.
+    pub fn is_supported(&self) -> bool {
+        SUPPORTED_PROTOCOL_VERSIONS.contains(self)
+    }
+
+    /// Negotiate the protocol version to use with a peer that supports the
+    /// given `versions`, e.g., the versions advertised in the capabilities of
+    /// its leaf node.
+    ///
+    /// Returns the most preferred version in [`SUPPORTED_PROTOCOL_VERSIONS`]
+    /// that is supported by the peer or a [`VersionError::NoCommonVersion`]
+    /// if there is none.
+    pub fn negotiate(versions: &[ProtocolVersion]) -> Result<ProtocolVersion, VersionError> {
+        SUPPORTED_PROTOCOL_VERSIONS
+            .iter()
+            .find(|version| versions.contains(version))
+            .copied()
+            .ok_or(VersionError::NoCommonVersion)
+    }
+
+    /// Check that the `received` protocol version matches this (expected)
+    /// version.
+    ///
+    /// Returns a [`VersionError::VersionMismatch`] otherwise.
+    pub fn check_matches(&self, received: ProtocolVersion) -> Result<(), VersionError> {
+        if *self != received {
+            log::error!("Protocol version mismatch: expected {self}, got {received}.");
+            return Err(VersionError::VersionMismatch);
+        }
+        Ok(())
+    }
+}
+
 /// There's only one version right now, which is the default.
 impl Default for ProtocolVersion {
     fn default() -> Self {
@@ -76,4 +122,46 @@ pub enum VersionError {
     /// Unsupported MLS version.
     #[error("Unsupported MLS version.")]
     UnsupportedMlsVersion,
+    /// The protocol version doesn't match the expected protocol version.
+    #[error("The protocol version doesn't match the expected protocol version.")]
+    VersionMismatch,
+    /// There is no protocol version that is supported by both parties.
+    #[error("There is no protocol version that is supported by both parties.")]
+    NoCommonVersion,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn version_negotiation() {
+        assert!(ProtocolVersion::Mls10.is_supported());
+        assert!(!ProtocolVersion::Mls10Draft11.is_supported());
+
+        assert_eq!(
+            ProtocolVersion::negotiate(&[ProtocolVersion::Mls10Draft11, ProtocolVersion::Mls10]),
+            Ok(ProtocolVersion::Mls10)
+        );
+        assert_eq!(
+            ProtocolVersion::negotiate(&[ProtocolVersion::Mls10Draft11]),
+            Err(VersionError::NoCommonVersion)
+        );
+        assert_eq!(
+            ProtocolVersion::negotiate(&[]),
+            Err(VersionError::NoCommonVersion)
+        );
+    }
+
+    #[test]
+    fn version_mismatch() {
+        assert_eq!(
+            ProtocolVersion::Mls10.check_matches(ProtocolVersion::Mls10),
+            Ok(())
+        );
+        assert_eq!(
+            ProtocolVersion::Mls10.check_matches(ProtocolVersion::Mls10Draft11),
+            Err(VersionError::VersionMismatch)
+        );
+    }
 }
@@ -177,7 +177,8 @@ fn book_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     // ANCHOR: alice_adds_bob
     let (mls_message_out, welcome, group_info) = alice_group
         .add_members(provider, &alice_signature_keys, &[bob_key_package])
-        .expect("Could not add members.");
+        .expect("Could not add members.")
+        .into_parts();
     // ANCHOR_END: alice_adds_bob
 
     // Suppress warning
@@ -228,7 +229,7 @@ fn book_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
 
     // ANCHOR: alice_exports_group_info
     let verifiable_group_info = alice_group
-        .export_group_info(provider.crypto(), &alice_signature_keys, true)
+        .export_group_info(provider, &alice_signature_keys, true)
         .expect("Cannot export group info")
         .into_verifiable_group_info()
         .expect("Could not get group info");
@@ -302,7 +303,8 @@ fn book_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     // ANCHOR: self_update
     let (mls_message_out, welcome_option, _group_info) = bob_group
         .self_update(provider, &bob_signature_keys)
-        .expect("Could not update own key package.");
+        .expect("Could not update own key package.")
+        .into_parts();
     // ANCHOR_END: self_update
 
     let alice_processed_message = alice_group
@@ -394,7 +396,8 @@ fn book_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     // ANCHOR: commit_to_proposals
     let (mls_message_out, welcome_option, _group_info) = alice_group
         .commit_to_pending_proposals(provider, &alice_signature_keys)
-        .expect("Could not commit to pending proposals.");
+        .expect("Could not commit to pending proposals.")
+        .into_parts();
     // ANCHOR_END: commit_to_proposals
 
     // Suppress warning
@@ -452,7 +455,8 @@ fn book_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
 
     let (queued_message, welcome, _group_info) = bob_group
         .add_members(provider, &bob_signature_keys, &[charlie_key_package])
-        .unwrap();
+        .unwrap()
+        .into_parts();
 
     let alice_processed_message = alice_group
         .process_message(
@@ -529,7 +533,8 @@ fn book_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     // === Charlie updates and commits ===
     let (queued_message, welcome_option, _group_info) = charlie_group
         .self_update(provider, &charlie_signature_keys)
-        .unwrap();
+        .unwrap()
+        .into_parts();
 
     let alice_processed_message = alice_group
         .process_message(
@@ -624,7 +629,8 @@ fn book_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     // ANCHOR: charlie_removes_bob
     let (mls_message_out, welcome_option, _group_info) = charlie_group
         .remove_members(provider, &charlie_signature_keys, &[bob_member.index])
-        .expect("Could not remove Bob from group.");
+        .expect("Could not remove Bob from group.")
+        .into_parts();
     // ANCHOR_END: charlie_removes_bob
 
     // Check that Bob's group is still active
@@ -873,7 +879,8 @@ fn book_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     // Commit to the proposals and process it
     let (queued_message, welcome_option, _group_info) = alice_group
         .commit_to_pending_proposals(provider, &alice_signature_keys)
-        .expect("Could not flush proposals");
+        .expect("Could not flush proposals")
+        .into_parts();
 
     let charlie_processed_message = charlie_group
         .process_message(
@@ -1021,7 +1028,8 @@ fn book_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
 
     let (queued_message, _welcome_option, _group_info) = alice_group
         .commit_to_pending_proposals(provider, &alice_signature_keys)
-        .expect("Could not commit to proposals.");
+        .expect("Could not commit to proposals.")
+        .into_parts();
 
     // Check that Bob's group is still active
     assert!(bob_group.is_active());
@@ -1132,7 +1140,8 @@ fn book_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
             alice_group.store_pending_proposal(*proposal);
             let (_commit, welcome, _group_info) = alice_group
                 .commit_to_pending_proposals(provider, &alice_signature_keys)
-                .expect("Could not commit");
+                .expect("Could not commit")
+                .into_parts();
             assert_eq!(alice_group.members().count(), 1);
             alice_group
                 .merge_pending_commit(provider)
@@ -1217,7 +1226,8 @@ fn book_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     // Add Bob to the group
     let (_queued_message, welcome, _group_info) = alice_group
         .add_members(provider, &alice_signature_keys, &[bob_key_package])
-        .expect("Could not add Bob");
+        .expect("Could not add Bob")
+        .into_parts();
 
     // Merge Commit
     alice_group
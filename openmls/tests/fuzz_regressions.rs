@@ -0,0 +1,300 @@
+//! Tests for the fuzzing entry points.
+//!
+//! Replays the regression corpus in `fuzz/regressions` and makes sure that the
+//! entry points accept valid inputs. Mutations of valid messages are fed to
+//! the entry points and to the public functions that process messages from
+//! the network, which must reject them without panicking.
+#![cfg(feature = "fuzz")]
+
+use std::{fs, path::Path};
+
+use openmls::{
+    fuzz,
+    prelude::{test_utils::new_credential, *},
+    test_utils::*,
+};
+
+/// Feed every file in `fuzz/regressions/<entry_point>` to the entry point.
+/// None of them may cause a panic. Every entry point must have a corpus.
+fn replay(entry_point: &str, f: impl Fn(&[u8]) -> bool) {
+    let directory = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../fuzz/regressions")
+        .join(entry_point);
+    let entries = fs::read_dir(&directory)
+        .unwrap_or_else(|e| panic!("error reading corpus {}: {e}", directory.display()));
+    let mut replayed = 0;
+    for entry in entries {
+        let path = entry.expect("error reading corpus entry").path();
+        let data = fs::read(&path).expect("error reading corpus file");
+        f(&data);
+        replayed += 1;
+    }
+    assert!(replayed > 0, "empty corpus {}", directory.display());
+}
+
+#[test]
+fn fuzz_regressions() {
+    let provider = OpenMlsRustCrypto::default();
+    let crypto = provider.crypto();
+
+    replay("mls_message_in", |data| fuzz::mls_message_in(crypto, data));
+    replay("key_package_in", |data| fuzz::key_package_in(crypto, data));
+    replay("group_info", |data| fuzz::group_info(crypto, data));
+    replay("ratchet_tree_in", |data| {
+        fuzz::ratchet_tree_in(crypto, data)
+    });
+    replay("welcome", fuzz::welcome);
+    replay("update_path_in", fuzz::update_path_in);
+}
+
+#[test]
+fn fuzz_entry_points() {
+    let ciphersuite = fuzz::RATCHET_TREE_CIPHERSUITE;
+    let provider = &OpenMlsRustCrypto::default();
+
+    let (alice_credential_with_key, alice_signer) = new_credential(
+        provider,
+        b"Alice",
+        CredentialType::Basic,
+        ciphersuite.signature_algorithm(),
+    );
+    let (bob_credential_with_key, bob_signer) = new_credential(
+        provider,
+        b"Bob",
+        CredentialType::Basic,
+        ciphersuite.signature_algorithm(),
+    );
+    let bob_key_package = KeyPackage::builder()
+        .build(
+            CryptoConfig::with_default_version(ciphersuite),
+            provider,
+            &bob_signer,
+            bob_credential_with_key,
+        )
+        .unwrap();
+
+    // Key packages
+    let key_package = MlsMessageOut::from(bob_key_package.clone())
+        .to_bytes()
+        .unwrap();
+    assert!(fuzz::mls_message_in(provider.crypto(), &key_package));
+    // Strip the MLSMessage header (version and wire format).
+    assert!(fuzz::key_package_in(provider.crypto(), &key_package[4..]));
+    assert!(!fuzz::key_package_in(
+        provider.crypto(),
+        &key_package[4..key_package.len() - 1]
+    ));
+
+    let mls_group_config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .build();
+    let mut alice_group = MlsGroup::new_with_group_id(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        GroupId::from_slice(&[]),
+        alice_credential_with_key,
+    )
+    .unwrap();
+
+    // Update paths
+    let mut update_path = alice_group
+        .own_leaf()
+        .unwrap()
+        .tls_serialize_detached()
+        .unwrap();
+    // An update path without nodes.
+    update_path.push(0);
+    assert!(fuzz::update_path_in(&update_path));
+    assert!(!fuzz::update_path_in(&update_path[..update_path.len() - 1]));
+
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(provider, &alice_signer, &[bob_key_package])
+        .unwrap()
+        .into_parts();
+    alice_group.merge_pending_commit(provider).unwrap();
+
+    // Group infos
+    let group_info = alice_group
+        .export_group_info(provider, &alice_signer, true)
+        .unwrap()
+        .to_bytes()
+        .unwrap();
+    assert!(fuzz::mls_message_in(provider.crypto(), &group_info));
+    assert!(fuzz::group_info(provider.crypto(), &group_info[4..]));
+    let group_info_without_tree = alice_group
+        .export_group_info(provider, &alice_signer, false)
+        .unwrap()
+        .to_bytes()
+        .unwrap();
+    assert!(!fuzz::group_info(
+        provider.crypto(),
+        &group_info_without_tree[4..]
+    ));
+
+    // Ratchet trees
+    let ratchet_tree = alice_group
+        .export_ratchet_tree()
+        .tls_serialize_detached()
+        .unwrap();
+    assert!(fuzz::ratchet_tree_in(provider.crypto(), &ratchet_tree));
+    assert!(!fuzz::ratchet_tree_in(provider.crypto(), &[]));
+    assert!(!fuzz::ratchet_tree_in(
+        provider.crypto(),
+        &ratchet_tree[..ratchet_tree.len() - 1]
+    ));
+
+    // Welcomes
+    let welcome = welcome.to_bytes().unwrap();
+    assert!(fuzz::mls_message_in(provider.crypto(), &welcome));
+    assert!(fuzz::welcome(&welcome[4..]));
+    assert!(!fuzz::welcome(&welcome[4..welcome.len() - 1]));
+}
+
+/// Every truncation of a message and every message with a flipped byte.
+fn mutations(message: &[u8]) -> impl Iterator<Item = Vec<u8>> + '_ {
+    let truncations = (0..message.len()).map(|length| message[..length].to_vec());
+    let flips = (0..message.len()).map(|index| {
+        let mut mutated = message.to_vec();
+        mutated[index] ^= 0xff;
+        mutated
+    });
+    truncations.chain(flips)
+}
+
+/// Malformed inputs must be rejected with an error by the public entry points
+/// that receive data from the network: `KeyPackageIn::validate`,
+/// `MlsGroup::new_from_welcome`, `MlsGroup::join_by_external_commit` and
+/// `MlsGroup::process_message`. None of them may cause a panic.
+#[test]
+fn malformed_inputs() {
+    let ciphersuite = fuzz::RATCHET_TREE_CIPHERSUITE;
+    let provider = &OpenMlsRustCrypto::default();
+
+    let (alice_credential_with_key, alice_signer) = new_credential(
+        provider,
+        b"Alice",
+        CredentialType::Basic,
+        ciphersuite.signature_algorithm(),
+    );
+    let (bob_credential_with_key, bob_signer) = new_credential(
+        provider,
+        b"Bob",
+        CredentialType::Basic,
+        ciphersuite.signature_algorithm(),
+    );
+    let bob_key_package = KeyPackage::builder()
+        .build(
+            CryptoConfig::with_default_version(ciphersuite),
+            provider,
+            &bob_signer,
+            bob_credential_with_key,
+        )
+        .unwrap();
+
+    let mls_group_config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .wire_format_policy(MIXED_PLAINTEXT_WIRE_FORMAT_POLICY)
+        .use_ratchet_tree_extension(true)
+        .build();
+    let mut alice_group = MlsGroup::new(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        alice_credential_with_key,
+    )
+    .unwrap();
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(provider, &alice_signer, &[bob_key_package.clone()])
+        .unwrap()
+        .into_parts();
+    alice_group.merge_pending_commit(provider).unwrap();
+    let welcome = welcome.into_welcome().unwrap();
+
+    // Key packages are validated.
+    let key_package = MlsMessageOut::from(bob_key_package).to_bytes().unwrap();
+    for mutated in mutations(&key_package) {
+        fuzz::mls_message_in(provider.crypto(), &mutated);
+        // Strip the MLSMessage header (version and wire format).
+        let Some(mutated) = mutated.get(4..) else {
+            continue;
+        };
+        if let Ok(key_package_in) = KeyPackageIn::tls_deserialize_exact(mutated) {
+            let _ = key_package_in.validate(
+                provider.crypto(),
+                ProtocolVersion::Mls10,
+                provider.clock(),
+            );
+        }
+    }
+
+    // Welcomes are joined by Bob. This happens before Bob joins with the valid
+    // Welcome, which consumes his key package.
+    let welcome_message = MlsMessageOut::from_welcome(welcome.clone(), ProtocolVersion::Mls10)
+        .to_bytes()
+        .unwrap();
+    for mutated in mutations(&welcome_message) {
+        fuzz::mls_message_in(provider.crypto(), &mutated);
+        let welcome = MlsMessageIn::tls_deserialize_exact(&mutated)
+            .ok()
+            .and_then(|message| message.into_welcome());
+        if let Some(welcome) = welcome {
+            let _ = MlsGroup::new_from_welcome(provider, &mls_group_config, welcome, None);
+        }
+    }
+    let mut bob_group =
+        MlsGroup::new_from_welcome(provider, &mls_group_config, welcome, None).unwrap();
+
+    // Group infos are joined by Charlie with an external commit.
+    let group_info = alice_group
+        .export_group_info(provider, &alice_signer, true)
+        .unwrap()
+        .to_bytes()
+        .unwrap();
+    let (charlie_credential_with_key, charlie_signer) = new_credential(
+        provider,
+        b"Charlie",
+        CredentialType::Basic,
+        ciphersuite.signature_algorithm(),
+    );
+    for mutated in mutations(&group_info) {
+        fuzz::mls_message_in(provider.crypto(), &mutated);
+        let verifiable_group_info = MlsMessageIn::tls_deserialize_exact(&mutated)
+            .ok()
+            .and_then(|message| message.into_verifiable_group_info());
+        if let Some(verifiable_group_info) = verifiable_group_info {
+            let _ = MlsGroup::join_by_external_commit(
+                provider,
+                &charlie_signer,
+                None,
+                verifiable_group_info,
+                &mls_group_config,
+                b"",
+                charlie_credential_with_key.clone(),
+            );
+        }
+    }
+
+    // Messages that are processed by Bob.
+    let (proposal, _proposal_ref) = alice_group
+        .propose_self_update(provider, &alice_signer, None)
+        .unwrap();
+    let application_message = alice_group
+        .create_message(provider, &alice_signer, b"Hello, Bob!")
+        .unwrap();
+    let (commit, _welcome, _group_info) = alice_group
+        .commit_to_pending_proposals(provider, &alice_signer)
+        .unwrap()
+        .into_parts();
+    for message in [proposal, application_message, commit] {
+        let message = message.to_bytes().unwrap();
+        for mutated in mutations(&message) {
+            let protocol_message = MlsMessageIn::tls_deserialize_exact(&mutated)
+                .ok()
+                .and_then(|message| message.into_protocol_message());
+            if let Some(protocol_message) = protocol_message {
+                let _ = bob_group.process_message(provider, protocol_message);
+            }
+        }
+    }
+}
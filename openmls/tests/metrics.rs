@@ -0,0 +1,183 @@
+//! Test the metrics reported by OpenMLS.
+//!
+//! The metrics recorder is global, so this file contains a single test.
+use std::sync::{Arc, Mutex};
+
+use openmls::{
+    metrics::*,
+    prelude::{config::CryptoConfig, test_utils::new_credential, *},
+};
+use openmls_rust_crypto::OpenMlsRustCrypto;
+
+#[derive(Default)]
+struct Recorder {
+    counters: Mutex<Vec<Counter>>,
+    histograms: Mutex<Vec<(Histogram, u64)>>,
+}
+
+impl Metrics for Recorder {
+    fn increment_counter(&self, counter: Counter) {
+        self.counters.lock().unwrap().push(counter);
+    }
+
+    fn record_histogram(&self, histogram: Histogram, value: u64) {
+        self.histograms.lock().unwrap().push((histogram, value));
+    }
+}
+
+impl Recorder {
+    fn take(&self) -> (Vec<Counter>, Vec<(Histogram, u64)>) {
+        (
+            std::mem::take(&mut self.counters.lock().unwrap()),
+            std::mem::take(&mut self.histograms.lock().unwrap()),
+        )
+    }
+}
+
+#[test]
+fn metrics() {
+    let recorder = Arc::new(Recorder::default());
+    set_metrics(recorder.clone()).expect("error setting the recorder");
+    assert_eq!(
+        set_metrics(recorder.clone()),
+        Err(SetMetricsError::AlreadySet)
+    );
+
+    let ciphersuite = Ciphersuite::MLS_128_DHKEMX25519_AES128GCM_SHA256_Ed25519;
+    let provider = OpenMlsRustCrypto::default();
+    let (alice_credential_with_key, alice_signer) = new_credential(
+        &provider,
+        b"Alice",
+        CredentialType::Basic,
+        ciphersuite.signature_algorithm(),
+    );
+    let (bob_credential_with_key, bob_signer) = new_credential(
+        &provider,
+        b"Bob",
+        CredentialType::Basic,
+        ciphersuite.signature_algorithm(),
+    );
+    let bob_key_package = KeyPackage::builder()
+        .build(
+            CryptoConfig::with_default_version(ciphersuite),
+            &provider,
+            &bob_signer,
+            bob_credential_with_key,
+        )
+        .expect("error creating key package");
+
+    let mls_group_config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .use_ratchet_tree_extension(true)
+        .build();
+
+    // === Alice adds Bob ===
+    let mut alice_group = MlsGroup::new(
+        &provider,
+        &alice_signer,
+        &mls_group_config,
+        alice_credential_with_key,
+    )
+    .expect("error creating group");
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(&provider, &alice_signer, &[bob_key_package])
+        .expect("error adding Bob")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(&provider)
+        .expect("error merging commit");
+    let mut bob_group = MlsGroup::new_from_welcome(
+        &provider,
+        &mls_group_config,
+        welcome.into_welcome().expect("expected a welcome"),
+        None,
+    )
+    .expect("error joining group");
+    assert_eq!(
+        recorder.take(),
+        (
+            vec![Counter::EpochAdvanced, Counter::KeyPackageConsumed],
+            vec![]
+        )
+    );
+
+    // === Messages out of order ===
+    let messages: Vec<ProtocolMessage> = (0..3)
+        .map(|_| {
+            alice_group
+                .create_message(&provider, &alice_signer, b"Hi")
+                .expect("error creating message")
+                .into_protocol_message()
+                .expect("expected a protocol message")
+        })
+        .collect();
+    for i in [2, 0, 1] {
+        bob_group
+            .process_message(&provider, messages[i].clone())
+            .expect("error processing message");
+    }
+    assert_eq!(
+        recorder.take(),
+        (
+            vec![],
+            vec![
+                (Histogram::OutOfOrderDistance, 2),
+                (Histogram::OutOfOrderDistance, 3),
+                (Histogram::OutOfOrderDistance, 2)
+            ]
+        )
+    );
+
+    // A message can only be decrypted once.
+    bob_group
+        .process_message(&provider, messages[0].clone())
+        .expect_err("processed a message twice");
+    let (counters, _) = recorder.take();
+    assert_eq!(counters, vec![Counter::DecryptionFailed]);
+
+    // === Rejected commits ===
+    let (commit, _welcome, _group_info) = alice_group
+        .self_update(&provider, &alice_signer)
+        .expect("error updating")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(&provider)
+        .expect("error merging commit");
+    let commit = commit
+        .into_protocol_message()
+        .expect("expected a protocol message");
+    match bob_group
+        .process_message(&provider, commit.clone())
+        .expect("error processing commit")
+        .into_content()
+    {
+        ProcessedMessageContent::StagedCommitMessage(staged_commit) => bob_group
+            .merge_staged_commit(&provider, *staged_commit)
+            .expect("error merging commit"),
+        _ => panic!("Expected a staged commit."),
+    }
+    assert_eq!(
+        recorder.take(),
+        (vec![Counter::EpochAdvanced, Counter::EpochAdvanced], vec![])
+    );
+
+    bob_group
+        .process_message(&provider, commit.clone())
+        .expect_err("processed a commit twice");
+    let (counters, _) = recorder.take();
+    assert_eq!(
+        counters,
+        vec![Counter::CommitRejected(CommitRejectionReason::WrongEpoch)]
+    );
+    assert_eq!(counters[0].name(), "openmls_commits_rejected");
+    assert_eq!(CommitRejectionReason::WrongEpoch.as_str(), "wrong_epoch");
+
+    // === Nothing is recorded after the recorder was removed ===
+    assert!(take_metrics().is_some());
+    assert!(take_metrics().is_none());
+    bob_group
+        .process_message(&provider, commit)
+        .expect_err("processed a commit twice");
+    assert_eq!(recorder.take(), (vec![], vec![]));
+    set_metrics(recorder.clone()).expect("error setting the recorder");
+}
@@ -0,0 +1,216 @@
+//! Test the security events reported by OpenMLS.
+//!
+//! The security event handler is global, so this file contains a single test.
+use std::sync::{Arc, Mutex};
+
+use openmls::{
+    prelude::{config::CryptoConfig, test_utils::new_credential, *},
+    security_events::*,
+};
+use openmls_rust_crypto::OpenMlsRustCrypto;
+use tls_codec::Deserialize;
+
+#[derive(Default)]
+struct Handler {
+    events: Mutex<Vec<SecurityEvent>>,
+}
+
+impl SecurityEventHandler for Handler {
+    fn handle_event(&self, event: &SecurityEvent) {
+        self.events.lock().unwrap().push(event.clone());
+    }
+}
+
+impl Handler {
+    fn take(&self) -> Vec<SecurityEvent> {
+        std::mem::take(&mut self.events.lock().unwrap())
+    }
+}
+
+#[test]
+fn security_events() {
+    let handler = Arc::new(Handler::default());
+    set_security_event_handler(handler.clone()).expect("error setting the handler");
+    assert_eq!(
+        set_security_event_handler(handler.clone()),
+        Err(SetSecurityEventHandlerError::AlreadySet)
+    );
+
+    let ciphersuite = Ciphersuite::MLS_128_DHKEMX25519_AES128GCM_SHA256_Ed25519;
+    let provider = OpenMlsRustCrypto::default();
+    let (alice_credential_with_key, alice_signer) = new_credential(
+        &provider,
+        b"Alice",
+        CredentialType::Basic,
+        ciphersuite.signature_algorithm(),
+    );
+    let (bob_credential_with_key, bob_signer) = new_credential(
+        &provider,
+        b"Bob",
+        CredentialType::Basic,
+        ciphersuite.signature_algorithm(),
+    );
+    let (charlie_credential_with_key, charlie_signer) = new_credential(
+        &provider,
+        b"Charlie",
+        CredentialType::Basic,
+        ciphersuite.signature_algorithm(),
+    );
+    let bob_key_package = KeyPackage::builder()
+        .build(
+            CryptoConfig::with_default_version(ciphersuite),
+            &provider,
+            &bob_signer,
+            bob_credential_with_key,
+        )
+        .expect("error creating key package");
+
+    let mls_group_config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .wire_format_policy(PURE_PLAINTEXT_WIRE_FORMAT_POLICY)
+        .use_ratchet_tree_extension(true)
+        .build();
+
+    // === Alice adds Bob ===
+    let mut alice_group = MlsGroup::new(
+        &provider,
+        &alice_signer,
+        &mls_group_config,
+        alice_credential_with_key,
+    )
+    .expect("error creating group");
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(&provider, &alice_signer, &[bob_key_package])
+        .expect("error adding Bob")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(&provider)
+        .expect("error merging commit");
+    let mut bob_group = MlsGroup::new_from_welcome(
+        &provider,
+        &mls_group_config,
+        welcome.into_welcome().expect("expected a welcome"),
+        None,
+    )
+    .expect("error joining group");
+    assert!(handler.take().is_empty());
+
+    // === Replayed message ===
+    let message = alice_group
+        .create_message(&provider, &alice_signer, b"Hi")
+        .expect("error creating message")
+        .into_protocol_message()
+        .expect("expected a protocol message");
+    bob_group
+        .process_message(&provider, message.clone())
+        .expect("error processing message");
+    assert!(handler.take().is_empty());
+    bob_group
+        .process_message(&provider, message)
+        .expect_err("processed a message twice");
+    let events = handler.take();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].kind(), &SecurityEventKind::ReplayDetected);
+    assert_eq!(events[0].group_id(), Some(bob_group.group_id()));
+    assert_eq!(events[0].epoch(), Some(bob_group.epoch()));
+
+    // === Skipped message key evicted ===
+    bob_group.set_configuration(
+        &MlsGroupConfig::builder()
+            .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+            .wire_format_policy(PURE_PLAINTEXT_WIRE_FORMAT_POLICY)
+            .use_ratchet_tree_extension(true)
+            .sender_ratchet_configuration(
+                SenderRatchetConfiguration::default().with_max_skipped_keys_per_sender(1),
+            )
+            .build(),
+    );
+    let messages: Vec<ProtocolMessage> = (0..3)
+        .map(|_| {
+            alice_group
+                .create_message(&provider, &alice_signer, b"Hi")
+                .expect("error creating message")
+                .into_protocol_message()
+                .expect("expected a protocol message")
+        })
+        .collect();
+    bob_group
+        .process_message(&provider, messages[2].clone())
+        .expect("error processing message");
+    let events = handler.take();
+    assert_eq!(events.len(), 1);
+    assert_eq!(
+        events[0].kind(),
+        &SecurityEventKind::MessageKeyEvicted {
+            sender: alice_group.own_leaf_index(),
+            generation: 1
+        }
+    );
+    assert_eq!(events[0].group_id(), Some(bob_group.group_id()));
+    bob_group
+        .process_message(&provider, messages[0].clone())
+        .expect_err("processed a message with an evicted key");
+    assert!(handler.take().is_empty());
+    bob_group
+        .process_message(&provider, messages[1].clone())
+        .expect("error processing message");
+
+    // === Tampered message ===
+    let (proposal, _proposal_ref) = alice_group
+        .propose_self_update(&provider, &alice_signer, None)
+        .expect("error proposing update");
+    let mut bytes = proposal.to_bytes().expect("error encoding proposal");
+    // Flip a bit of the membership tag at the end of the message.
+    *bytes.last_mut().unwrap() ^= 1;
+    let proposal = MlsMessageIn::tls_deserialize_exact(&bytes)
+        .expect("error decoding proposal")
+        .into_protocol_message()
+        .expect("expected a protocol message");
+    bob_group
+        .process_message(&provider, proposal)
+        .expect_err("processed a tampered proposal");
+    let events = handler.take();
+    assert_eq!(events.len(), 1);
+    assert_eq!(
+        events[0].kind(),
+        &SecurityEventKind::SignatureVerificationFailed
+    );
+
+    // === Charlie joins through an external commit ===
+    let verifiable_group_info = alice_group
+        .export_group_info(&provider, &alice_signer, true)
+        .expect("error exporting group info")
+        .into_verifiable_group_info()
+        .expect("expected a group info");
+    let (_charlie_group, commit, _group_info) = MlsGroup::join_by_external_commit(
+        &provider,
+        &charlie_signer,
+        None,
+        verifiable_group_info,
+        &mls_group_config,
+        &[],
+        charlie_credential_with_key.clone(),
+    )
+    .expect("error joining group");
+    bob_group
+        .process_message(
+            &provider,
+            commit
+                .into_protocol_message()
+                .expect("expected a protocol message"),
+        )
+        .expect("error processing commit");
+    let events = handler.take();
+    assert_eq!(events.len(), 1);
+    assert_eq!(
+        events[0].kind(),
+        &SecurityEventKind::ExternalCommitByUnknownIdentity(charlie_credential_with_key.credential)
+    );
+
+    // === The handler is replaced ===
+    assert!(take_security_event_handler().is_some());
+    assert!(take_security_event_handler().is_none());
+    let other_handler = Arc::new(Handler::default());
+    set_security_event_handler(other_handler.clone()).expect("error setting the handler");
+    assert!(take_security_event_handler().is_some());
+}
@@ -0,0 +1,101 @@
+//! Compile-time checks that groups, messages, and providers can be moved
+//! between and shared across threads.
+
+use std::{sync::Arc, thread};
+
+use openmls::{
+    prelude::{config::CryptoConfig, test_utils::new_credential, *},
+    test_utils::*,
+    *,
+};
+use openmls_basic_credential::SignatureKeyPair;
+use openmls_rust_crypto::{MemoryKeyStore, MemoryKeyStoreError, OpenMlsRustCrypto, RustCrypto};
+use openmls_traits::{crypto::OpenMlsCrypto, signatures::Signer};
+
+fn assert_send_sync<T: Send + Sync + ?Sized>() {}
+
+#[test]
+fn auto_traits() {
+    // Groups
+    assert_send_sync::<MlsGroup>();
+    assert_send_sync::<SharedMlsGroup>();
+    assert_send_sync::<PublicGroup>();
+    assert_send_sync::<MlsGroupConfig>();
+    assert_send_sync::<StagedCommit>();
+    assert_send_sync::<QueuedProposal>();
+
+    // Messages
+    assert_send_sync::<MlsMessageIn>();
+    assert_send_sync::<MlsMessageOut>();
+    assert_send_sync::<ProtocolMessage>();
+    assert_send_sync::<ProcessedMessage>();
+    assert_send_sync::<Welcome>();
+    assert_send_sync::<KeyPackage>();
+    assert_send_sync::<CredentialWithKey>();
+
+    // Errors
+    assert_send_sync::<LibraryError>();
+    assert_send_sync::<ProcessMessageError>();
+    assert_send_sync::<NewGroupError<MemoryKeyStoreError>>();
+    assert_send_sync::<WelcomeError<MemoryKeyStoreError>>();
+    assert_send_sync::<AddMembersError<MemoryKeyStoreError>>();
+
+    // Providers
+    assert_send_sync::<OpenMlsRustCrypto>();
+    assert_send_sync::<RustCrypto>();
+    assert_send_sync::<MemoryKeyStore>();
+    assert_send_sync::<SignatureKeyPair>();
+    assert_send_sync::<dyn OpenMlsCrypto>();
+
+    // The crypto and randomness providers of every provider are thread-safe.
+    fn provider<P: OpenMlsProvider>() {
+        assert_send_sync::<P::CryptoProvider>();
+        assert_send_sync::<P::RandProvider>();
+    }
+    provider::<OpenMlsRustCrypto>();
+}
+
+#[apply(ciphersuites)]
+fn groups_move_between_threads(ciphersuite: Ciphersuite) {
+    let provider = Arc::new(OpenMlsRustCrypto::default());
+    let (alice_credential_with_key, alice_signer) = new_credential(
+        provider.as_ref(),
+        b"Alice",
+        CredentialType::Basic,
+        ciphersuite.signature_algorithm(),
+    );
+    // Signers can be used as trait objects.
+    let alice_signer: Arc<dyn Signer + Send + Sync> = Arc::new(alice_signer);
+
+    let mls_group_config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .build();
+
+    // The group is created on one thread ...
+    let alice_group = {
+        let provider = provider.clone();
+        let alice_signer = alice_signer.clone();
+        thread::spawn(move || {
+            MlsGroup::new(
+                provider.as_ref(),
+                &alice_signer,
+                &mls_group_config,
+                alice_credential_with_key,
+            )
+            .expect("error creating group")
+        })
+        .join()
+        .expect("thread panicked")
+    };
+
+    // ... and used on another one.
+    let message = thread::spawn(move || {
+        let mut alice_group = alice_group;
+        alice_group
+            .create_message(provider.as_ref(), &alice_signer, b"Hi")
+            .expect("error creating message")
+    })
+    .join()
+    .expect("thread panicked");
+    assert!(message.into_protocol_message().is_some());
+}
@@ -0,0 +1,58 @@
+//! Property-based tests with arbitrary protocol values.
+use openmls::{arbitrary::ArbitraryRatchetTree, prelude::*, test_utils::OpenMlsRustCrypto};
+use proptest::prelude::*;
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    #[test]
+    fn key_packages_round_trip(key_package in any::<KeyPackage>()) {
+        let provider = OpenMlsRustCrypto::default();
+        let bytes = key_package.tls_serialize_detached().unwrap();
+        let decoded = KeyPackageIn::tls_deserialize_exact(bytes)
+            .unwrap()
+            .validate(provider.crypto(), ProtocolVersion::Mls10, provider.clock())
+            .unwrap();
+        prop_assert_eq!(decoded, key_package);
+    }
+
+    #[test]
+    fn proposals_round_trip(proposal in any::<Proposal>()) {
+        let bytes = proposal.tls_serialize_detached().unwrap();
+        let decoded = ProposalIn::tls_deserialize_exact(bytes).unwrap();
+        prop_assert_eq!(Proposal::from(decoded), proposal);
+    }
+
+    #[test]
+    fn ratchet_trees_round_trip(tree in any::<ArbitraryRatchetTree>()) {
+        let provider = OpenMlsRustCrypto::default();
+        let bytes = tree.ratchet_tree().tls_serialize_detached().unwrap();
+        let decoded = RatchetTreeIn::tls_deserialize_exact(bytes)
+            .unwrap()
+            .into_verified(tree.ciphersuite(), provider.crypto(), tree.group_id())
+            .unwrap();
+        prop_assert_eq!(&decoded, tree.ratchet_tree());
+    }
+}
+
+proptest! {
+    #[test]
+    fn extensions_round_trip(extensions in any::<Extensions>()) {
+        let bytes = extensions.tls_serialize_detached().unwrap();
+        let decoded = Extensions::tls_deserialize_exact(bytes).unwrap();
+        prop_assert_eq!(decoded, extensions);
+    }
+
+    #[test]
+    fn extension_merges_are_idempotent(
+        extensions in any::<Extensions>(),
+        extension in any::<Extension>(),
+    ) {
+        let mut merged_once = extensions;
+        merged_once.add_or_replace(extension.clone());
+        let mut merged_twice = merged_once.clone();
+        let replaced = merged_twice.add_or_replace(extension.clone());
+        prop_assert_eq!(replaced, Some(extension));
+        prop_assert_eq!(merged_twice, merged_once);
+    }
+}
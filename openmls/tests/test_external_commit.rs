@@ -40,7 +40,7 @@ fn test_external_commit(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvide
     // ... and exports a group info (with ratchet_tree).
     let verifiable_group_info = {
         let group_info = alice_group
-            .export_group_info(provider.crypto(), &alice_signer, true)
+            .export_group_info(provider, &alice_signer, true)
             .unwrap();
 
         let serialized_group_info = group_info.tls_serialize_detached().unwrap();
@@ -53,7 +53,7 @@ fn test_external_commit(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvide
 
     let verifiable_group_info_broken = {
         let group_info = alice_group
-            .export_group_info(provider.crypto(), &alice_signer, true)
+            .export_group_info(provider, &alice_signer, true)
             .unwrap();
 
         let serialized_group_info = {
@@ -134,7 +134,10 @@ fn test_group_info(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     let (mut alice_group, _, alice_signer) = create_alice_group(ciphersuite, provider, true);
 
     // Self update Alice's to get a group info from a commit
-    let (.., group_info) = alice_group.self_update(provider, &alice_signer).unwrap();
+    let (.., group_info) = alice_group
+        .self_update(provider, &alice_signer)
+        .unwrap()
+        .into_parts();
     alice_group.merge_pending_commit(provider).unwrap();
 
     // Bob wants to join
@@ -224,7 +227,10 @@ fn test_not_present_group_info(ciphersuite: Ciphersuite, provider: &impl OpenMls
     let (mut alice_group, _, alice_signer) = create_alice_group(ciphersuite, provider, false);
 
     // Self update Alice's to get a group info from a commit
-    let (.., group_info) = alice_group.self_update(provider, &alice_signer).unwrap();
+    let (.., group_info) = alice_group
+        .self_update(provider, &alice_signer)
+        .unwrap()
+        .into_parts();
     alice_group.merge_pending_commit(provider).unwrap();
 
     assert!(group_info.is_none());
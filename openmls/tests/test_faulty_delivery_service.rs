@@ -0,0 +1,265 @@
+//! Test that clients tolerate the faults of an unreliable network.
+use openmls::{
+    prelude::{config::CryptoConfig, test_utils::new_credential, *},
+    test_utils::{
+        faulty_delivery_service::{DeliveryStats, Faults, FaultyDeliveryService},
+        *,
+    },
+    *,
+};
+use openmls_traits::signatures::Signer;
+
+const FAULTS: Faults = Faults {
+    drop_rate: 0.2,
+    duplicate_rate: 0.3,
+    max_delay: 3,
+    reorder: true,
+};
+
+/// Sends application messages of a one-member group through a delivery
+/// service with the given `seed` and returns the delivered messages.
+fn deliveries(
+    provider: &impl OpenMlsProvider,
+    signer: &impl Signer,
+    group: &mut MlsGroup,
+    seed: u64,
+) -> (Vec<Vec<Vec<u8>>>, DeliveryStats) {
+    let mut delivery_service = FaultyDeliveryService::new(seed, 2, FAULTS);
+    delivery_service.set_faults(0, Faults::default());
+    let mut deliveries = vec![Vec::new(), Vec::new()];
+    for i in 0..20 {
+        let message = group
+            .create_message(provider, signer, format!("message {i}").as_bytes())
+            .expect("error creating message");
+        delivery_service
+            .send(&[0, 1], &message)
+            .expect("error sending message");
+        delivery_service.tick();
+        for (recipient, delivered) in deliveries.iter_mut().enumerate() {
+            delivered.extend(
+                delivery_service
+                    .deliver_all(recipient)
+                    .into_iter()
+                    .map(|message| message.tls_serialize_detached().unwrap()),
+            );
+        }
+    }
+    delivery_service.flush();
+    for (recipient, delivered) in deliveries.iter_mut().enumerate() {
+        delivered.extend(
+            delivery_service
+                .deliver_all(recipient)
+                .into_iter()
+                .map(|message| message.tls_serialize_detached().unwrap()),
+        );
+        assert_eq!(delivery_service.pending(recipient), 0);
+    }
+    (deliveries, delivery_service.stats())
+}
+
+#[apply(ciphersuites)]
+fn faults_are_deterministic(ciphersuite: Ciphersuite) {
+    let provider = OpenMlsRustCrypto::default();
+    let (credential_with_key, signer) = new_credential(
+        &provider,
+        b"Alice",
+        CredentialType::Basic,
+        ciphersuite.signature_algorithm(),
+    );
+    let mut group = MlsGroup::new(
+        &provider,
+        &signer,
+        &MlsGroupConfig::test_default(ciphersuite),
+        credential_with_key,
+    )
+    .expect("error creating group");
+
+    let (first_deliveries, first_stats) = deliveries(&provider, &signer, &mut group, 7);
+    assert_eq!(first_stats.sent, 40);
+    assert_eq!(
+        first_stats.delivered,
+        first_stats.sent - first_stats.dropped + first_stats.duplicated
+    );
+    // The first recipient doesn't suffer from any faults.
+    assert_eq!(first_deliveries[0].len(), 20);
+    assert_ne!(first_deliveries[0], first_deliveries[1]);
+
+    // The messages of the second run are different, but they are dropped,
+    // duplicated and reordered in the same way.
+    let (second_deliveries, second_stats) = deliveries(&provider, &signer, &mut group, 7);
+    assert_eq!(second_stats, first_stats);
+    for (first, second) in first_deliveries.iter().zip(second_deliveries.iter()) {
+        assert_eq!(first.len(), second.len());
+    }
+}
+
+/// A client that buffers messages of future epochs.
+struct BufferingClient {
+    provider: OpenMlsRustCrypto,
+    group: MlsGroup,
+    buffer: Vec<ProtocolMessage>,
+    received: Vec<Vec<u8>>,
+    rejected: usize,
+}
+
+impl BufferingClient {
+    /// Processes `message` and all buffered messages that can be processed
+    /// afterwards.
+    fn receive(&mut self, message: MlsMessageIn) {
+        self.buffer
+            .push(message.into_protocol_message().expect("expected a message"));
+        loop {
+            let epoch = self.group.epoch();
+            let (mut ready, future): (Vec<_>, Vec<_>) = self
+                .buffer
+                .drain(..)
+                .partition(|message| message.epoch() <= epoch);
+            self.buffer = future;
+            if ready.is_empty() {
+                break;
+            }
+            // Application messages of the current epoch have to be processed
+            // before the commit that ends it.
+            ready.sort_by_key(|message| message.content_type() == ContentType::Commit);
+            for message in ready {
+                // Duplicates of processed messages are rejected.
+                let Ok(processed_message) = self.group.process_message(&self.provider, message)
+                else {
+                    self.rejected += 1;
+                    continue;
+                };
+                match processed_message.into_content() {
+                    ProcessedMessageContent::ApplicationMessage(application_message) => {
+                        self.received.push(application_message.into_bytes())
+                    }
+                    ProcessedMessageContent::StagedCommitMessage(staged_commit) => self
+                        .group
+                        .merge_staged_commit(&self.provider, *staged_commit)
+                        .expect("error merging commit"),
+                    _ => panic!("unexpected message"),
+                }
+            }
+        }
+    }
+}
+
+#[apply(ciphersuites)]
+fn future_epochs_are_buffered(ciphersuite: Ciphersuite) {
+    let alice_provider = OpenMlsRustCrypto::default();
+    let bob_provider = OpenMlsRustCrypto::default();
+    let (alice_credential_with_key, alice_signer) = new_credential(
+        &alice_provider,
+        b"Alice",
+        CredentialType::Basic,
+        ciphersuite.signature_algorithm(),
+    );
+    let (bob_credential_with_key, bob_signer) = new_credential(
+        &bob_provider,
+        b"Bob",
+        CredentialType::Basic,
+        ciphersuite.signature_algorithm(),
+    );
+    let bob_key_package = KeyPackage::builder()
+        .build(
+            CryptoConfig::with_default_version(ciphersuite),
+            &bob_provider,
+            &bob_signer,
+            bob_credential_with_key,
+        )
+        .expect("error creating key package");
+
+    // Application messages may be delayed past the commits of the next
+    // epochs.
+    let mls_group_config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .max_past_epochs(FAULTS.max_delay as usize + 1)
+        .use_ratchet_tree_extension(true)
+        .build();
+    let mut alice_group = MlsGroup::new(
+        &alice_provider,
+        &alice_signer,
+        &mls_group_config,
+        alice_credential_with_key,
+    )
+    .expect("error creating group");
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(&alice_provider, &alice_signer, &[bob_key_package])
+        .expect("error adding Bob")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(&alice_provider)
+        .expect("error merging commit");
+    let bob_group = MlsGroup::new_from_welcome(
+        &bob_provider,
+        &mls_group_config,
+        welcome.into_welcome().expect("expected a welcome"),
+        None,
+    )
+    .expect("error joining group");
+    let mut bob = BufferingClient {
+        provider: bob_provider,
+        group: bob_group,
+        buffer: Vec::new(),
+        received: Vec::new(),
+        rejected: 0,
+    };
+
+    // Commits must not be dropped, so that Bob can catch up.
+    let mut delivery_service = FaultyDeliveryService::new(
+        3,
+        1,
+        Faults {
+            drop_rate: 0.0,
+            ..FAULTS
+        },
+    );
+    let mut sent = Vec::new();
+    for epoch in 0..10 {
+        for i in 0..2 {
+            let content = format!("message {i} in epoch {epoch}").into_bytes();
+            let message = alice_group
+                .create_message(&alice_provider, &alice_signer, &content)
+                .expect("error creating message");
+            delivery_service
+                .send(&[0], &message)
+                .expect("error sending message");
+            sent.push(content);
+        }
+        let (commit, _welcome, _group_info) = alice_group
+            .self_update(&alice_provider, &alice_signer)
+            .expect("error updating")
+            .into_parts();
+        alice_group
+            .merge_pending_commit(&alice_provider)
+            .expect("error merging commit");
+        delivery_service
+            .send(&[0], &commit)
+            .expect("error sending commit");
+
+        delivery_service.tick();
+        for message in delivery_service.deliver_all(0) {
+            bob.receive(message);
+        }
+    }
+    delivery_service.flush();
+    for message in delivery_service.deliver_all(0) {
+        bob.receive(message);
+    }
+
+    assert!(bob.buffer.is_empty());
+    assert_eq!(bob.group.epoch(), alice_group.epoch());
+    assert_eq!(
+        bob.group
+            .export_secret(bob.provider.crypto(), "test", &[], 32)
+            .unwrap(),
+        alice_group
+            .export_secret(alice_provider.crypto(), "test", &[], 32)
+            .unwrap()
+    );
+    // Every message was received exactly once, and only the duplicates were
+    // rejected.
+    bob.received.sort();
+    sent.sort();
+    assert_eq!(bob.received, sent);
+    assert_eq!(bob.rejected, delivery_service.stats().duplicated);
+}
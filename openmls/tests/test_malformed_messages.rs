@@ -0,0 +1,179 @@
+//! Test that recipients reject malformed messages with the expected errors.
+use openmls::{
+    prelude::{config::CryptoConfig, test_utils::new_credential, *},
+    test_utils::{
+        malformed::{malformed_messages, Corruption, ExpectedError, MalformedMessage},
+        *,
+    },
+    *,
+};
+
+/// Deserializes and processes the `malformed_message` and checks that it is
+/// rejected with the expected error.
+fn check_rejected(
+    provider: &impl OpenMlsProvider,
+    group: &mut MlsGroup,
+    malformed_message: &MalformedMessage,
+) {
+    let message = match MlsMessageIn::tls_deserialize(&mut malformed_message.message()) {
+        Ok(message) => message,
+        Err(_) => {
+            assert_eq!(malformed_message.expected_error(), ExpectedError::Decoding);
+            return;
+        }
+    };
+    let error = group
+        .process_message(provider, message.into_protocol_message().unwrap())
+        .expect_err("processed a malformed message");
+    assert!(
+        malformed_message.is_expected(&error),
+        "{:?} was rejected with {error:?}",
+        malformed_message.corruption()
+    );
+}
+
+fn corruptions(malformed_messages: &[MalformedMessage]) -> Vec<Corruption> {
+    malformed_messages
+        .iter()
+        .map(MalformedMessage::corruption)
+        .collect()
+}
+
+#[apply(ciphersuites)]
+fn malformed_messages_are_rejected(ciphersuite: Ciphersuite) {
+    let provider = OpenMlsRustCrypto::default();
+    let (alice_credential_with_key, alice_signer) = new_credential(
+        &provider,
+        b"Alice",
+        CredentialType::Basic,
+        ciphersuite.signature_algorithm(),
+    );
+    let (bob_credential_with_key, bob_signer) = new_credential(
+        &provider,
+        b"Bob",
+        CredentialType::Basic,
+        ciphersuite.signature_algorithm(),
+    );
+    let bob_key_package = KeyPackage::builder()
+        .build(
+            CryptoConfig::with_default_version(ciphersuite),
+            &provider,
+            &bob_signer,
+            bob_credential_with_key,
+        )
+        .expect("error creating key package");
+
+    let mls_group_config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .wire_format_policy(PURE_PLAINTEXT_WIRE_FORMAT_POLICY)
+        .use_ratchet_tree_extension(true)
+        .build();
+    let mut alice_group = MlsGroup::new(
+        &provider,
+        &alice_signer,
+        &mls_group_config,
+        alice_credential_with_key,
+    )
+    .expect("error creating group");
+
+    // === Welcome ===
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(&provider, &alice_signer, &[bob_key_package])
+        .expect("error adding Bob")
+        .into_parts();
+    let malformed_welcomes =
+        malformed_messages(&provider, &alice_group, &welcome).expect("error corrupting welcome");
+    assert_eq!(
+        corruptions(&malformed_welcomes),
+        vec![
+            Corruption::Truncated,
+            Corruption::UnknownKeyPackage,
+            Corruption::FlippedGroupSecretsBit,
+            Corruption::FlippedGroupInfoBit,
+        ]
+    );
+    alice_group
+        .merge_pending_commit(&provider)
+        .expect("error merging commit");
+    for malformed_welcome in &malformed_welcomes {
+        let welcome = match MlsMessageIn::tls_deserialize(&mut malformed_welcome.message()) {
+            Ok(welcome) => welcome.into_welcome().expect("expected a welcome"),
+            Err(_) => {
+                assert_eq!(malformed_welcome.expected_error(), ExpectedError::Decoding);
+                continue;
+            }
+        };
+        let error = MlsGroup::new_from_welcome(&provider, &mls_group_config, welcome, None)
+            .expect_err("joined with a malformed welcome");
+        assert!(
+            malformed_welcome.is_expected(&error),
+            "{:?} was rejected with {error:?}",
+            malformed_welcome.corruption()
+        );
+    }
+    let mut bob_group = MlsGroup::new_from_welcome(
+        &provider,
+        &mls_group_config,
+        welcome.into_welcome().expect("expected a welcome"),
+        None,
+    )
+    .expect("error joining group");
+
+    // === Commit ===
+    let (commit, _welcome, _group_info) = alice_group
+        .self_update(&provider, &alice_signer)
+        .expect("error updating")
+        .into_parts();
+    let malformed_commits =
+        malformed_messages(&provider, &alice_group, &commit).expect("error corrupting commit");
+    assert_eq!(
+        corruptions(&malformed_commits),
+        vec![
+            Corruption::Truncated,
+            Corruption::WrongGroupId,
+            Corruption::WrongEpoch,
+            Corruption::FlippedSignatureBit,
+            Corruption::BadMembershipTag,
+        ]
+    );
+    for malformed_commit in &malformed_commits {
+        check_rejected(&provider, &mut bob_group, malformed_commit);
+    }
+    alice_group
+        .merge_pending_commit(&provider)
+        .expect("error merging commit");
+    let processed_message = bob_group
+        .process_message(&provider, commit.into_protocol_message().unwrap())
+        .expect("error processing commit");
+    let ProcessedMessageContent::StagedCommitMessage(staged_commit) =
+        processed_message.into_content()
+    else {
+        panic!("expected a commit");
+    };
+    bob_group
+        .merge_staged_commit(&provider, *staged_commit)
+        .expect("error merging commit");
+
+    // === Application message ===
+    let message = alice_group
+        .create_message(&provider, &alice_signer, b"Hello Bob")
+        .expect("error creating message");
+    let malformed_application_messages = malformed_messages(&provider, &alice_group, &message)
+        .expect("error corrupting application message");
+    assert_eq!(
+        corruptions(&malformed_application_messages),
+        vec![
+            Corruption::Truncated,
+            Corruption::WrongGroupId,
+            Corruption::WrongEpoch,
+            Corruption::FlippedSenderDataBit,
+            Corruption::FlippedCiphertextBit,
+        ]
+    );
+    for malformed_application_message in &malformed_application_messages {
+        check_rejected(&provider, &mut bob_group, malformed_application_message);
+    }
+    bob_group
+        .process_message(&provider, message.into_protocol_message().unwrap())
+        .expect("error processing message");
+}
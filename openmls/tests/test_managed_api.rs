@@ -1,6 +1,9 @@
 use openmls::{
     prelude::*,
-    test_utils::test_framework::{ActionType, CodecUse, MlsGroupTestSetup},
+    test_utils::test_framework::{
+        errors::{ClientError, SetupError},
+        ActionType, CodecUse, MlsGroupTestSetup,
+    },
     test_utils::*,
     *,
 };
@@ -48,3 +51,76 @@ fn test_mls_group_api(ciphersuite: Ciphersuite) {
     // Check that all group members agree on the same group state.
     setup.check_group_states(group);
 }
+
+#[apply(ciphersuites)]
+fn test_fast_forward(ciphersuite: Ciphersuite) {
+    let mls_group_config = MlsGroupConfig::test_default(ciphersuite);
+    let setup = MlsGroupTestSetup::new(mls_group_config, 3, CodecUse::SerializedMessages);
+
+    let group_id = setup
+        .create_random_group(3, ciphersuite)
+        .expect("An unexpected error occurred.");
+    let mut groups = setup.groups.write().expect("An unexpected error occurred.");
+    let group = groups
+        .get_mut(&group_id)
+        .expect("An unexpected error occurred.");
+    let (_, member_id) = group.members().next().unwrap();
+    let epoch = || {
+        let clients = setup.clients.read().expect("An unexpected error occurred.");
+        let member = clients
+            .get(&member_id)
+            .expect("An unexpected error occurred.")
+            .read()
+            .expect("An unexpected error occurred.");
+        let groups = member.groups.read().expect("An unexpected error occurred.");
+        groups
+            .get(&group_id)
+            .expect("An unexpected error occurred.")
+            .epoch()
+            .as_u64()
+    };
+    let initial_epoch = epoch();
+
+    // All members take turns.
+    let commits = setup
+        .fast_forward(group, &[], 5)
+        .expect("An unexpected error occurred.");
+    assert_eq!(commits.len(), 5);
+    assert_eq!(epoch(), initial_epoch + 5);
+
+    // A single member commits.
+    let (_, committer_id) = group.members().nth(1).unwrap();
+    let commits = setup
+        .fast_forward(group, &[committer_id.clone()], 3)
+        .expect("An unexpected error occurred.");
+    assert_eq!(commits.len(), 3);
+    assert_eq!(epoch(), initial_epoch + 8);
+    setup.check_group_states(group);
+
+    // Replayed commits are rejected.
+    let commit = MlsMessageIn::from(commits[0].clone())
+        .into_protocol_message()
+        .unwrap();
+    let clients = setup.clients.read().expect("An unexpected error occurred.");
+    let member = clients
+        .get(&member_id)
+        .expect("An unexpected error occurred.")
+        .read()
+        .expect("An unexpected error occurred.");
+    assert_eq!(
+        member.receive_messages_for_group(&commit, &committer_id),
+        Err(ClientError::ProcessMessageError(
+            ProcessMessageError::ValidationError(ValidationError::WrongEpoch)
+        ))
+    );
+    drop(member);
+    drop(clients);
+
+    // Only members can commit.
+    assert_eq!(
+        setup
+            .fast_forward(group, &[b"not a member".to_vec()], 1)
+            .expect_err("A client that is not a member committed."),
+        SetupError::ClientNotInGroup
+    );
+}
@@ -96,7 +96,7 @@ fn mls_group_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvide
 
         // === Alice adds Bob ===
         let welcome = match alice_group.add_members(provider, &alice_signer, &[bob_key_package]) {
-            Ok((_, welcome, _)) => welcome,
+            Ok(commit_output) => commit_output.welcome().clone(),
             Err(e) => panic!("Could not add member to group: {e:?}"),
         };
 
@@ -183,8 +183,10 @@ fn mls_group_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvide
         }
 
         // === Bob updates and commits ===
-        let (queued_message, welcome_option, _group_info) =
-            bob_group.self_update(provider, &bob_signer).unwrap();
+        let (queued_message, welcome_option, _group_info) = bob_group
+            .self_update(provider, &bob_signer)
+            .unwrap()
+            .into_parts();
 
         let alice_processed_message = alice_group
             .process_message(
@@ -271,7 +273,8 @@ fn mls_group_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvide
 
         let (queued_message, _welcome_option, _group_info) = alice_group
             .commit_to_pending_proposals(provider, &alice_signer)
-            .unwrap();
+            .unwrap()
+            .into_parts();
 
         let bob_processed_message = bob_group
             .process_message(
@@ -321,7 +324,8 @@ fn mls_group_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvide
 
         let (queued_message, welcome, _group_info) = bob_group
             .add_members(provider, &bob_signer, &[charlie_key_package])
-            .unwrap();
+            .unwrap()
+            .into_parts();
 
         let alice_processed_message = alice_group
             .process_message(
@@ -399,7 +403,8 @@ fn mls_group_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvide
         // === Charlie updates and commits ===
         let (queued_message, welcome_option, _group_info) = charlie_group
             .self_update(provider, &charlie_signer)
-            .unwrap();
+            .unwrap()
+            .into_parts();
 
         let alice_processed_message = alice_group
             .process_message(
@@ -472,7 +477,8 @@ fn mls_group_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvide
         println!(" >>> Charlie is removing bob");
         let (queued_message, welcome_option, _group_info) = charlie_group
             .remove_members(provider, &charlie_signer, &[bob_group.own_leaf_index()])
-            .expect("Could not remove member from group.");
+            .expect("Could not remove member from group.")
+            .into_parts();
 
         // Check that Bob's group is still active
         assert!(bob_group.is_active());
@@ -661,7 +667,8 @@ fn mls_group_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvide
         // Commit to the proposals and process it
         let (queued_message, welcome_option, _group_info) = alice_group
             .commit_to_pending_proposals(provider, &alice_signer)
-            .expect("Could not flush proposals");
+            .expect("Could not flush proposals")
+            .into_parts();
 
         let charlie_processed_message = charlie_group
             .process_message(
@@ -793,7 +800,8 @@ fn mls_group_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvide
 
         let (queued_message, _welcome_option, _group_info) = alice_group
             .commit_to_pending_proposals(provider, &alice_signer)
-            .expect("Could not commit to proposals.");
+            .expect("Could not commit to proposals.")
+            .into_parts();
 
         // Check that Bob's group is still active
         assert!(bob_group.is_active());
@@ -875,7 +883,8 @@ fn mls_group_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvide
         // Add Bob to the group
         let (_queued_message, welcome, _group_info) = alice_group
             .add_members(provider, &alice_signer, &[bob_key_package])
-            .expect("Could not add Bob");
+            .expect("Could not add Bob")
+            .into_parts();
 
         // Test saving & loading the group state when there is a pending commit
         alice_group
@@ -989,7 +998,7 @@ fn addition_order(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
             &alice_signer,
             &[bob_key_package, charlie_key_package],
         ) {
-            Ok((_, welcome, _)) => welcome,
+            Ok(commit_output) => commit_output.welcome().clone(),
             Err(e) => panic!("Could not add member to group: {e:?}"),
         };
 
@@ -1127,7 +1136,8 @@ fn mls_group_ratchet_tree_extension(ciphersuite: Ciphersuite, provider: &impl Op
         // === Alice adds Bob ===
         let (_queued_message, welcome, _group_info) = alice_group
             .add_members(provider, &alice_signer, &[bob_key_package.clone()])
-            .unwrap();
+            .unwrap()
+            .into_parts();
 
         // === Bob joins using the ratchet tree extension ===
         let _bob_group = MlsGroup::new_from_welcome(
@@ -1179,7 +1189,8 @@ fn mls_group_ratchet_tree_extension(ciphersuite: Ciphersuite, provider: &impl Op
         // === Alice adds Bob ===
         let (_queued_message, welcome, _group_info) = alice_group
             .add_members(provider, &alice_signer, &[bob_key_package])
-            .unwrap();
+            .unwrap()
+            .into_parts();
 
         // === Bob tries to join without the ratchet tree extension ===
         let error = MlsGroup::new_from_welcome(
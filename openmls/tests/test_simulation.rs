@@ -0,0 +1,101 @@
+//! Test that clients converge in simulated networks.
+use std::{cell::Cell, rc::Rc};
+
+use openmls::{
+    prelude::*,
+    test_utils::{
+        simulation::{NetworkConditions, Simulation, SimulationStats, Step},
+        *,
+    },
+    *,
+};
+
+#[apply(ciphersuites)]
+fn scripted_schedule(ciphersuite: Ciphersuite) {
+    let mut simulation = Simulation::new(
+        MlsGroupConfig::test_default(ciphersuite),
+        4,
+        NetworkConditions::default(),
+    )
+    .expect("error creating the simulation");
+    let application_messages = Rc::new(Cell::new(0));
+    let counter = application_messages.clone();
+    simulation.on_message(move |_, _, content| {
+        if let ProcessedMessageContent::ApplicationMessage(_) = content {
+            counter.set(counter.get() + 1);
+        }
+    });
+
+    simulation
+        .run([
+            Step::Add {
+                committer: 0,
+                client: 1,
+            },
+            Step::Add {
+                committer: 0,
+                client: 2,
+            },
+            Step::Deliver(1),
+            // Client 1 didn't process the second add yet.
+            Step::Update(1),
+            Step::DeliverAll,
+            Step::Update(1),
+            // Clients 0 and 2 didn't process the update yet.
+            Step::Send(2),
+            Step::Remove {
+                committer: 0,
+                client: 2,
+            },
+            Step::DeliverAll,
+            Step::Remove {
+                committer: 0,
+                client: 2,
+            },
+            Step::Send(0),
+            // Client 3 isn't a member.
+            Step::Send(3),
+        ])
+        .expect("error running the simulation");
+    simulation.assert_converged();
+
+    assert_eq!(simulation.members(), &[0, 1]);
+    assert_eq!(simulation.epoch(), 4);
+    assert!(!simulation.client(2).group().unwrap().is_active());
+    assert!(simulation.client(3).group().is_none());
+    assert_eq!(
+        simulation.stats(),
+        SimulationStats {
+            delivered: 8,
+            lost: 0,
+            rejected: 3,
+            skipped: 1,
+        }
+    );
+    assert_eq!(application_messages.get(), 1);
+}
+
+#[apply(ciphersuites)]
+fn random_schedules(ciphersuite: Ciphersuite) {
+    let mls_group_config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .sender_ratchet_configuration(SenderRatchetConfiguration::new(100, 1000))
+        .build();
+
+    for seed in 0..3 {
+        let mut simulation = Simulation::new(
+            mls_group_config.clone(),
+            8,
+            NetworkConditions {
+                seed,
+                reorder: true,
+                loss_rate: 0.1,
+            },
+        )
+        .expect("error creating the simulation");
+        simulation
+            .run_random(200)
+            .expect("error running the simulation");
+        simulation.assert_converged();
+    }
+}
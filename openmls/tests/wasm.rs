@@ -0,0 +1,121 @@
+//! Tests for `wasm32-unknown-unknown` that run in the browser.
+//!
+//! Run them with `wasm-pack test --headless --chrome openmls -- --features js --test wasm`.
+#![cfg(target_arch = "wasm32")]
+
+use openmls::prelude::{config::CryptoConfig, test_utils::new_credential, *};
+use openmls_rust_crypto::OpenMlsRustCrypto;
+use openmls_traits::OpenMlsProvider;
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+/// Create a group, add a member and exchange an application message.
+#[wasm_bindgen_test]
+fn create_and_join_group() {
+    let ciphersuite = Ciphersuite::MLS_128_DHKEMX25519_AES128GCM_SHA256_Ed25519;
+    let alice_provider = &OpenMlsRustCrypto::default();
+    let bob_provider = &OpenMlsRustCrypto::default();
+
+    let (alice_credential, alice_signer) = new_credential(
+        alice_provider,
+        b"Alice",
+        CredentialType::Basic,
+        ciphersuite.signature_algorithm(),
+    );
+    let (bob_credential, bob_signer) = new_credential(
+        bob_provider,
+        b"Bob",
+        CredentialType::Basic,
+        ciphersuite.signature_algorithm(),
+    );
+
+    // The lifetime of the key package requires the current time, which is
+    // provided by the browser.
+    let bob_key_package = KeyPackage::builder()
+        .build(
+            CryptoConfig::with_default_version(ciphersuite),
+            bob_provider,
+            &bob_signer,
+            bob_credential,
+        )
+        .expect("error creating key package");
+
+    let mls_group_config = MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .use_ratchet_tree_extension(true)
+        .build();
+
+    // === Alice creates a group and adds Bob ===
+    let mut alice_group = MlsGroup::new(
+        alice_provider,
+        &alice_signer,
+        &mls_group_config,
+        alice_credential,
+    )
+    .expect("error creating group");
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(alice_provider, &alice_signer, &[bob_key_package])
+        .expect("error adding Bob")
+        .into_parts();
+    alice_group
+        .merge_pending_commit(alice_provider)
+        .expect("error merging commit");
+
+    // === Bob joins the group ===
+    let welcome = welcome.into_welcome().expect("expected a welcome");
+    let mut bob_group = MlsGroup::new_from_welcome(bob_provider, &mls_group_config, welcome, None)
+        .expect("error joining group");
+    assert_eq!(alice_group.members().count(), 2);
+    assert_eq!(bob_group.members().count(), 2);
+
+    // === Alice sends a message to Bob ===
+    let message = alice_group
+        .create_message(alice_provider, &alice_signer, b"Hello from the browser")
+        .expect("error creating message");
+    let processed_message = bob_group
+        .process_message(
+            bob_provider,
+            message
+                .into_protocol_message()
+                .expect("expected a protocol message"),
+        )
+        .expect("error processing message");
+    match processed_message.into_content() {
+        ProcessedMessageContent::ApplicationMessage(message) => {
+            assert_eq!(message.into_bytes(), b"Hello from the browser")
+        }
+        _ => panic!("Expected an application message."),
+    }
+
+    // === Bob updates, which encrypts to Alice's leaf ===
+    let (commit, _welcome, _group_info) = bob_group
+        .self_update(bob_provider, &bob_signer)
+        .expect("error updating")
+        .into_parts();
+    bob_group
+        .merge_pending_commit(bob_provider)
+        .expect("error merging commit");
+    let processed_message = alice_group
+        .process_message(
+            alice_provider,
+            commit
+                .into_protocol_message()
+                .expect("expected a protocol message"),
+        )
+        .expect("error processing commit");
+    match processed_message.into_content() {
+        ProcessedMessageContent::StagedCommitMessage(staged_commit) => alice_group
+            .merge_staged_commit(alice_provider, *staged_commit)
+            .expect("error merging commit"),
+        _ => panic!("Expected a staged commit."),
+    }
+    assert_eq!(
+        alice_group
+            .export_secret(alice_provider.crypto(), "label", b"context", 32)
+            .expect("error exporting secret"),
+        bob_group
+            .export_secret(bob_provider.crypto(), "label", b"context", 32)
+            .expect("error exporting secret")
+    );
+}
@@ -19,6 +19,7 @@ impl OpenMlsProvider for OpenMlsRustCrypto {
     type CryptoProvider = RustCrypto;
     type RandProvider = RustCrypto;
     type KeyStoreProvider = MemoryKeyStore;
+    type ClockProvider = RustCrypto;
 
     fn crypto(&self) -> &Self::CryptoProvider {
         &self.crypto
@@ -31,4 +32,8 @@ impl OpenMlsProvider for OpenMlsRustCrypto {
     fn key_store(&self) -> &Self::KeyStoreProvider {
         &self.key_store
     }
+
+    fn clock(&self) -> &Self::ClockProvider {
+        &self.crypto
+    }
 }
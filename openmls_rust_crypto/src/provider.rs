@@ -1,4 +1,7 @@
-use std::sync::RwLock;
+use std::{
+    sync::RwLock,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
 use aes_gcm::{
     aead::{Aead, Payload},
@@ -11,6 +14,7 @@ use hpke::Hpke;
 use hpke_rs_crypto::types as hpke_types;
 use hpke_rs_rust_crypto::HpkeRustCrypto;
 use openmls_traits::{
+    clock::OpenMlsClock,
     crypto::OpenMlsCrypto,
     random::OpenMlsRand,
     types::{
@@ -414,6 +418,19 @@ impl OpenMlsRand for RustCrypto {
     }
 }
 
+impl OpenMlsClock for RustCrypto {
+    /// Returns the system time in seconds since the Unix epoch, or 0 if the
+    /// system time is before the Unix epoch.
+    ///
+    /// Note that the system time isn't available on `wasm32-unknown-unknown`.
+    fn now(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default()
+    }
+}
+
 #[derive(thiserror::Error, Debug, Copy, Clone, PartialEq, Eq)]
 pub enum RandError {
     #[error("Rng lock is poisoned.")]
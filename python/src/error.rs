@@ -0,0 +1,318 @@
+//! Errors and the Python exceptions they are raised as.
+
+use openmls::{error::LibraryError, framing::errors::MlsMessageError, prelude::*};
+use pyo3::prelude::*;
+use thiserror::Error;
+
+use crate::ProviderKeyStoreError;
+
+/// The Python exceptions. `OpenMlsError` is the base class of all of them.
+pub(crate) mod exceptions {
+    use pyo3::{create_exception, exceptions::PyException};
+
+    create_exception!(
+        openmls,
+        OpenMlsError,
+        PyException,
+        "The base class of all errors raised by OpenMLS."
+    );
+    create_exception!(
+        openmls,
+        InvalidArgumentError,
+        OpenMlsError,
+        "An argument is invalid, e.g. an unsupported ciphersuite or an unknown member."
+    );
+    create_exception!(
+        openmls,
+        DecodingError,
+        OpenMlsError,
+        "The input bytes could not be decoded."
+    );
+    create_exception!(
+        openmls,
+        EncodingError,
+        OpenMlsError,
+        "A value could not be encoded."
+    );
+    create_exception!(
+        openmls,
+        NotFoundError,
+        OpenMlsError,
+        "The requested value was not found."
+    );
+    create_exception!(
+        openmls,
+        InvalidMessageError,
+        OpenMlsError,
+        "A message, key package or welcome was rejected by the validation."
+    );
+    create_exception!(
+        openmls,
+        GroupStateError,
+        OpenMlsError,
+        "The operation is not possible in the current state of the group."
+    );
+    create_exception!(
+        openmls,
+        KeyStoreError,
+        OpenMlsError,
+        "Accessing the key store failed."
+    );
+    create_exception!(
+        openmls,
+        CryptoError,
+        OpenMlsError,
+        "A cryptographic operation failed."
+    );
+    create_exception!(
+        openmls,
+        LibraryError,
+        OpenMlsError,
+        "An internal error occurred. This is a bug in OpenMLS."
+    );
+}
+
+/// Add the exceptions to the module.
+pub(crate) fn add_exceptions(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = module.py();
+    module.add("OpenMlsError", py.get_type::<exceptions::OpenMlsError>())?;
+    module.add(
+        "InvalidArgumentError",
+        py.get_type::<exceptions::InvalidArgumentError>(),
+    )?;
+    module.add("DecodingError", py.get_type::<exceptions::DecodingError>())?;
+    module.add("EncodingError", py.get_type::<exceptions::EncodingError>())?;
+    module.add("NotFoundError", py.get_type::<exceptions::NotFoundError>())?;
+    module.add(
+        "InvalidMessageError",
+        py.get_type::<exceptions::InvalidMessageError>(),
+    )?;
+    module.add(
+        "GroupStateError",
+        py.get_type::<exceptions::GroupStateError>(),
+    )?;
+    module.add("KeyStoreError", py.get_type::<exceptions::KeyStoreError>())?;
+    module.add("CryptoError", py.get_type::<exceptions::CryptoError>())?;
+    module.add("LibraryError", py.get_type::<exceptions::LibraryError>())?;
+    Ok(())
+}
+
+/// The errors of the bindings. They are raised as the Python exception of the
+/// same name.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub(crate) enum BindingError {
+    #[error("{0}")]
+    InvalidArgument(String),
+    #[error("{0}")]
+    DecodingError(String),
+    #[error("{0}")]
+    EncodingError(String),
+    #[error("{0}")]
+    NotFound(String),
+    #[error("{0}")]
+    InvalidMessage(String),
+    #[error("{0}")]
+    GroupStateError(String),
+    #[error("{0}")]
+    KeyStoreError(String),
+    #[error("{0}")]
+    CryptoError(String),
+    #[error("{0}")]
+    LibraryError(String),
+}
+
+impl From<BindingError> for PyErr {
+    fn from(error: BindingError) -> Self {
+        match error {
+            BindingError::InvalidArgument(message) => {
+                exceptions::InvalidArgumentError::new_err(message)
+            }
+            BindingError::DecodingError(message) => exceptions::DecodingError::new_err(message),
+            BindingError::EncodingError(message) => exceptions::EncodingError::new_err(message),
+            BindingError::NotFound(message) => exceptions::NotFoundError::new_err(message),
+            BindingError::InvalidMessage(message) => {
+                exceptions::InvalidMessageError::new_err(message)
+            }
+            BindingError::GroupStateError(message) => exceptions::GroupStateError::new_err(message),
+            BindingError::KeyStoreError(message) => exceptions::KeyStoreError::new_err(message),
+            BindingError::CryptoError(message) => exceptions::CryptoError::new_err(message),
+            BindingError::LibraryError(message) => exceptions::LibraryError::new_err(message),
+        }
+    }
+}
+
+impl From<LibraryError> for BindingError {
+    fn from(error: LibraryError) -> Self {
+        Self::LibraryError(error.to_string())
+    }
+}
+
+impl From<ProviderKeyStoreError> for BindingError {
+    fn from(error: ProviderKeyStoreError) -> Self {
+        Self::KeyStoreError(error.to_string())
+    }
+}
+
+impl From<tls_codec::Error> for BindingError {
+    fn from(error: tls_codec::Error) -> Self {
+        Self::DecodingError(error.to_string())
+    }
+}
+
+impl From<MlsMessageError> for BindingError {
+    fn from(error: MlsMessageError) -> Self {
+        Self::EncodingError(error.to_string())
+    }
+}
+
+impl From<MlsGroupStateError> for BindingError {
+    fn from(error: MlsGroupStateError) -> Self {
+        match error {
+            MlsGroupStateError::LibraryError(error) => error.into(),
+            _ => Self::GroupStateError(error.to_string()),
+        }
+    }
+}
+
+impl From<KeyPackageVerifyError> for BindingError {
+    fn from(error: KeyPackageVerifyError) -> Self {
+        match error {
+            KeyPackageVerifyError::LibraryError(error) => error.into(),
+            _ => Self::InvalidMessage(error.to_string()),
+        }
+    }
+}
+
+impl From<KeyPackageNewError<ProviderKeyStoreError>> for BindingError {
+    fn from(error: KeyPackageNewError<ProviderKeyStoreError>) -> Self {
+        match error {
+            KeyPackageNewError::LibraryError(error) => error.into(),
+            KeyPackageNewError::KeyStoreError(error) => error.into(),
+            KeyPackageNewError::SignatureError(_) => Self::CryptoError(error.to_string()),
+            KeyPackageNewError::CiphersuiteSignatureSchemeMismatch => {
+                Self::InvalidArgument(error.to_string())
+            }
+        }
+    }
+}
+
+impl From<NewGroupError<ProviderKeyStoreError>> for BindingError {
+    fn from(error: NewGroupError<ProviderKeyStoreError>) -> Self {
+        match error {
+            NewGroupError::LibraryError(error) => error.into(),
+            NewGroupError::KeyStoreError(error) => error.into(),
+            _ => Self::InvalidArgument(error.to_string()),
+        }
+    }
+}
+
+impl From<WelcomeError<ProviderKeyStoreError>> for BindingError {
+    fn from(error: WelcomeError<ProviderKeyStoreError>) -> Self {
+        match error {
+            WelcomeError::LibraryError(error) => error.into(),
+            WelcomeError::KeyStoreError(error) => error.into(),
+            // The welcome is not for any of our key packages.
+            WelcomeError::NoMatchingKeyPackage | WelcomeError::NoMatchingEncryptionKey => {
+                Self::NotFound(error.to_string())
+            }
+            _ => Self::InvalidMessage(error.to_string()),
+        }
+    }
+}
+
+impl From<CreateCommitError<ProviderKeyStoreError>> for BindingError {
+    fn from(error: CreateCommitError<ProviderKeyStoreError>) -> Self {
+        match error {
+            CreateCommitError::LibraryError(error) => error.into(),
+            CreateCommitError::KeyStoreError(error) => error.into(),
+            CreateCommitError::KeyPackageGenerationError(error) => error.into(),
+            CreateCommitError::SignatureError(_) => Self::CryptoError(error.to_string()),
+            _ => Self::InvalidArgument(error.to_string()),
+        }
+    }
+}
+
+impl From<AddMembersError<ProviderKeyStoreError>> for BindingError {
+    fn from(error: AddMembersError<ProviderKeyStoreError>) -> Self {
+        match error {
+            AddMembersError::LibraryError(error) => error.into(),
+            AddMembersError::EmptyInput(_) => Self::InvalidArgument(error.to_string()),
+            AddMembersError::CreateCommitError(error) => error.into(),
+            AddMembersError::GroupStateError(error) => error.into(),
+        }
+    }
+}
+
+impl From<RemoveMembersError<ProviderKeyStoreError>> for BindingError {
+    fn from(error: RemoveMembersError<ProviderKeyStoreError>) -> Self {
+        match error {
+            RemoveMembersError::LibraryError(error) => error.into(),
+            RemoveMembersError::EmptyInput(_) | RemoveMembersError::UnknownMember => {
+                Self::InvalidArgument(error.to_string())
+            }
+            RemoveMembersError::CreateCommitError(error) => error.into(),
+            RemoveMembersError::GroupStateError(error) => error.into(),
+        }
+    }
+}
+
+impl From<SelfUpdateError<ProviderKeyStoreError>> for BindingError {
+    fn from(error: SelfUpdateError<ProviderKeyStoreError>) -> Self {
+        match error {
+            SelfUpdateError::LibraryError(error) => error.into(),
+            SelfUpdateError::CreateCommitError(error) => error.into(),
+            SelfUpdateError::GroupStateError(error) => error.into(),
+            SelfUpdateError::KeyStoreError => Self::KeyStoreError(error.to_string()),
+        }
+    }
+}
+
+impl From<MergeCommitError<ProviderKeyStoreError>> for BindingError {
+    fn from(error: MergeCommitError<ProviderKeyStoreError>) -> Self {
+        match error {
+            MergeCommitError::LibraryError(error) => error.into(),
+            MergeCommitError::KeyStoreError(error) => error.into(),
+            MergeCommitError::GroupStateError(error) => error.into(),
+        }
+    }
+}
+
+impl From<MergePendingCommitError<ProviderKeyStoreError>> for BindingError {
+    fn from(error: MergePendingCommitError<ProviderKeyStoreError>) -> Self {
+        match error {
+            MergePendingCommitError::MlsGroupStateError(error) => error.into(),
+            MergePendingCommitError::MergeCommitError(error) => error.into(),
+        }
+    }
+}
+
+impl From<CreateMessageError> for BindingError {
+    fn from(error: CreateMessageError) -> Self {
+        match error {
+            CreateMessageError::LibraryError(error) => error.into(),
+            CreateMessageError::GroupStateError(error) => error.into(),
+        }
+    }
+}
+
+impl From<ProcessMessageError> for BindingError {
+    fn from(error: ProcessMessageError) -> Self {
+        match error {
+            ProcessMessageError::LibraryError(error) => error.into(),
+            ProcessMessageError::GroupStateError(error) => error.into(),
+            _ => Self::InvalidMessage(error.to_string()),
+        }
+    }
+}
+
+impl From<ExportSecretError> for BindingError {
+    fn from(error: ExportSecretError) -> Self {
+        match error {
+            ExportSecretError::LibraryError(error) => error.into(),
+            ExportSecretError::KeyLengthTooLong | ExportSecretError::ReservedLabel => {
+                Self::InvalidArgument(error.to_string())
+            }
+            ExportSecretError::GroupStateError(error) => error.into(),
+        }
+    }
+}
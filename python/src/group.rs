@@ -0,0 +1,323 @@
+//! Groups.
+
+use openmls::prelude::{config::CryptoConfig, *};
+use openmls_traits::OpenMlsProvider;
+use pyo3::prelude::*;
+use tls_codec::Deserialize;
+
+use crate::{py_bytes, BindingError, Identity, Provider};
+
+/// A message processed with `Group.process_message()`.
+#[pyclass(module = "openmls", get_all)]
+#[derive(Debug)]
+pub(crate) struct ProcessedMessage {
+    /// The kind of the message: `"application"`, `"proposal"` or `"commit"`.
+    kind: &'static str,
+    /// The identity of the sender's credential.
+    sender: PyObject,
+    /// The plaintext of an application message, `None` otherwise.
+    plaintext: Option<PyObject>,
+}
+
+#[pymethods]
+impl ProcessedMessage {
+    fn __repr__(&self) -> String {
+        format!("ProcessedMessage(kind={:?})", self.kind)
+    }
+}
+
+/// An MLS group.
+#[pyclass(module = "openmls")]
+#[derive(Debug)]
+pub(crate) struct Group {
+    group: MlsGroup,
+}
+
+/// The configuration of groups created through the bindings. The ratchet tree
+/// is included in welcome messages, so that they can be processed on their
+/// own.
+fn group_config(ciphersuite: Ciphersuite) -> MlsGroupConfig {
+    MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .use_ratchet_tree_extension(true)
+        .build()
+}
+
+/// Decode an `MLSMessage`.
+fn decode_message(message: &[u8]) -> Result<MlsMessageInBody, BindingError> {
+    Ok(MlsMessageIn::tls_deserialize_exact(message)?.extract())
+}
+
+/// Encode an outgoing `MLSMessage`.
+fn encode_message(py: Python<'_>, message: MlsMessageOut) -> PyResult<PyObject> {
+    let message = message.to_bytes().map_err(BindingError::from)?;
+    Ok(py_bytes(py, &message))
+}
+
+#[pymethods]
+impl Group {
+    /// Create a new group with the ID `group_id` and `identity` as the only
+    /// member.
+    #[staticmethod]
+    fn create(provider: &Provider, identity: &Identity, group_id: &[u8]) -> PyResult<Self> {
+        let group = MlsGroup::new_with_group_id(
+            provider,
+            &identity.signer,
+            &group_config(identity.ciphersuite),
+            GroupId::from_slice(group_id),
+            identity.credential_with_key.clone(),
+        )
+        .map_err(BindingError::from)?;
+        Ok(Self { group })
+    }
+
+    /// Join a group with the `welcome` message. The welcome must be for a key
+    /// package created with the same provider.
+    #[staticmethod]
+    fn join(provider: &Provider, welcome: &[u8]) -> PyResult<Self> {
+        let welcome = match decode_message(welcome)? {
+            MlsMessageInBody::Welcome(welcome) => welcome,
+            _ => {
+                return Err(BindingError::InvalidArgument(
+                    "The message is not a welcome.".to_string(),
+                )
+                .into())
+            }
+        };
+        // The ciphersuite is taken from the welcome.
+        let config = MlsGroupConfig::builder()
+            .use_ratchet_tree_extension(true)
+            .build();
+        let group = MlsGroup::new_from_welcome(provider, &config, welcome, None)
+            .map_err(BindingError::from)?;
+        Ok(Self { group })
+    }
+
+    /// Load the group with the ID `group_id`, which has been saved with
+    /// `save()`.
+    #[staticmethod]
+    fn load(provider: &Provider, group_id: &[u8]) -> PyResult<Self> {
+        let group = MlsGroup::load(&GroupId::from_slice(group_id), provider.key_store())
+            .ok_or_else(|| BindingError::NotFound("No group with this ID.".to_string()))?;
+        Ok(Self { group })
+    }
+
+    /// Save the group in the provider.
+    fn save(&mut self, provider: &Provider) -> PyResult<()> {
+        self.group
+            .save(provider.key_store())
+            .map_err(BindingError::from)?;
+        Ok(())
+    }
+
+    /// The ID of the group.
+    #[getter]
+    fn group_id(&self, py: Python<'_>) -> PyObject {
+        py_bytes(py, self.group.group_id().as_slice())
+    }
+
+    /// The current epoch.
+    #[getter]
+    fn epoch(&self) -> u64 {
+        self.group.epoch().as_u64()
+    }
+
+    /// The leaf index of the own member.
+    #[getter]
+    fn own_leaf_index(&self) -> u32 {
+        self.group.own_leaf_index().u32()
+    }
+
+    /// Whether the own member is still part of the group.
+    #[getter]
+    fn is_active(&self) -> bool {
+        self.group.is_active()
+    }
+
+    /// The members of the group as list of `(leaf_index, identity,
+    /// signature_key)` tuples.
+    fn members(&self, py: Python<'_>) -> Vec<(u32, PyObject, PyObject)> {
+        self.group
+            .members()
+            .map(|member| {
+                (
+                    member.index.u32(),
+                    py_bytes(py, member.credential.identity()),
+                    py_bytes(py, &member.signature_key),
+                )
+            })
+            .collect()
+    }
+
+    /// Add the members with the given `key_packages` to the group and return
+    /// the commit and the welcome.
+    ///
+    /// The commit is pending until it is merged with `merge_pending_commit()`.
+    fn add_members(
+        &mut self,
+        py: Python<'_>,
+        provider: &Provider,
+        identity: &Identity,
+        key_packages: Vec<Vec<u8>>,
+    ) -> PyResult<(PyObject, PyObject)> {
+        let key_packages = key_packages
+            .into_iter()
+            .map(|key_package| match decode_message(&key_package)? {
+                MlsMessageInBody::KeyPackage(key_package) => Ok(key_package.validate(
+                    provider.crypto(),
+                    ProtocolVersion::Mls10,
+                    provider.clock(),
+                )?),
+                _ => Err(BindingError::InvalidArgument(
+                    "The message is not a key package.".to_string(),
+                )),
+            })
+            .collect::<Result<Vec<_>, BindingError>>()?;
+        let (commit, welcome, _group_info) = self
+            .group
+            .add_members(provider, &identity.signer, &key_packages)
+            .map_err(BindingError::from)?
+            .into_parts();
+        Ok((encode_message(py, commit)?, encode_message(py, welcome)?))
+    }
+
+    /// Remove the members at `leaf_indices` from the group and return the
+    /// commit.
+    ///
+    /// The commit is pending until it is merged with `merge_pending_commit()`.
+    fn remove_members(
+        &mut self,
+        py: Python<'_>,
+        provider: &Provider,
+        identity: &Identity,
+        leaf_indices: Vec<u32>,
+    ) -> PyResult<PyObject> {
+        let leaf_indices: Vec<LeafNodeIndex> =
+            leaf_indices.into_iter().map(LeafNodeIndex::new).collect();
+        let (commit, _welcome, _group_info) = self
+            .group
+            .remove_members(provider, &identity.signer, &leaf_indices)
+            .map_err(BindingError::from)?
+            .into_parts();
+        encode_message(py, commit)
+    }
+
+    /// Update the own leaf and return the commit.
+    ///
+    /// The commit is pending until it is merged with `merge_pending_commit()`.
+    fn self_update(
+        &mut self,
+        py: Python<'_>,
+        provider: &Provider,
+        identity: &Identity,
+    ) -> PyResult<PyObject> {
+        let (commit, _welcome, _group_info) = self
+            .group
+            .self_update(provider, &identity.signer)
+            .map_err(BindingError::from)?
+            .into_parts();
+        encode_message(py, commit)
+    }
+
+    /// Merge the pending commit.
+    fn merge_pending_commit(&mut self, provider: &Provider) -> PyResult<()> {
+        self.group
+            .merge_pending_commit(provider)
+            .map_err(BindingError::from)?;
+        Ok(())
+    }
+
+    /// Discard the pending commit.
+    fn clear_pending_commit(&mut self) {
+        self.group.clear_pending_commit()
+    }
+
+    /// Encrypt the application message `plaintext`.
+    fn encrypt(
+        &mut self,
+        py: Python<'_>,
+        provider: &Provider,
+        identity: &Identity,
+        plaintext: &[u8],
+    ) -> PyResult<PyObject> {
+        let message = self
+            .group
+            .create_message(provider, &identity.signer, plaintext)
+            .map_err(BindingError::from)?;
+        encode_message(py, message)
+    }
+
+    /// Process the `MLSMessage` `message`. Application messages are decrypted,
+    /// proposals are stored and commits are merged.
+    fn process_message(
+        &mut self,
+        py: Python<'_>,
+        provider: &Provider,
+        message: &[u8],
+    ) -> PyResult<ProcessedMessage> {
+        let message = match decode_message(message)? {
+            MlsMessageInBody::PublicMessage(message) => ProtocolMessage::from(message),
+            MlsMessageInBody::PrivateMessage(message) => ProtocolMessage::from(message),
+            _ => {
+                return Err(BindingError::InvalidArgument(
+                    "The message is not a protocol message.".to_string(),
+                )
+                .into())
+            }
+        };
+        let processed_message = self
+            .group
+            .process_message(provider, message)
+            .map_err(BindingError::from)?;
+        let sender = py_bytes(py, processed_message.credential().identity());
+        let (kind, plaintext) = match processed_message.into_content() {
+            ProcessedMessageContent::ApplicationMessage(message) => {
+                ("application", Some(py_bytes(py, &message.into_bytes())))
+            }
+            ProcessedMessageContent::ProposalMessage(proposal) => {
+                self.group.store_pending_proposal(*proposal);
+                ("proposal", None)
+            }
+            ProcessedMessageContent::ExternalJoinProposalMessage(proposal) => {
+                self.group.store_pending_proposal(*proposal);
+                ("proposal", None)
+            }
+            ProcessedMessageContent::StagedCommitMessage(staged_commit) => {
+                self.group
+                    .merge_staged_commit(provider, *staged_commit)
+                    .map_err(BindingError::from)?;
+                ("commit", None)
+            }
+        };
+        Ok(ProcessedMessage {
+            kind,
+            sender,
+            plaintext,
+        })
+    }
+
+    /// Export a secret of `length` bytes from the current epoch.
+    #[pyo3(signature = (provider, label, context = b"".as_slice(), length = 32))]
+    fn export_secret(
+        &self,
+        py: Python<'_>,
+        provider: &Provider,
+        label: &str,
+        context: &[u8],
+        length: usize,
+    ) -> PyResult<PyObject> {
+        let secret = self
+            .group
+            .export_secret(provider.crypto(), label, context, length)
+            .map_err(BindingError::from)?;
+        Ok(py_bytes(py, &secret))
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Group(group_id={:?}, epoch={})",
+            String::from_utf8_lossy(self.group.group_id().as_slice()),
+            self.group.epoch().as_u64()
+        )
+    }
+}
@@ -0,0 +1,116 @@
+//! Identities and key packages.
+
+use openmls::prelude::{config::CryptoConfig, *};
+use openmls_basic_credential::SignatureKeyPair;
+use openmls_traits::{crypto::OpenMlsCrypto, OpenMlsProvider};
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{py_bytes, BindingError, Provider};
+
+/// A basic credential with its signature key pair.
+///
+/// The identity is bound to a ciphersuite, which is used for its key packages
+/// and the groups it creates.
+#[pyclass(module = "openmls")]
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct Identity {
+    pub(crate) ciphersuite: Ciphersuite,
+    pub(crate) credential_with_key: CredentialWithKey,
+    pub(crate) signer: SignatureKeyPair,
+}
+
+#[pymethods]
+impl Identity {
+    /// Create a new identity with a basic credential for `identity`.
+    /// `ciphersuite` is the IANA value of the ciphersuite and defaults to
+    /// `MLS_128_DHKEMX25519_AES128GCM_SHA256_Ed25519`.
+    #[new]
+    #[pyo3(signature = (provider, identity, ciphersuite = 1))]
+    fn new(provider: &Provider, identity: &[u8], ciphersuite: u16) -> PyResult<Self> {
+        let ciphersuite = Ciphersuite::try_from(ciphersuite)
+            .map_err(|e| BindingError::InvalidArgument(e.to_string()))?;
+        if !provider
+            .crypto()
+            .supported_ciphersuites()
+            .contains(&ciphersuite)
+        {
+            return Err(BindingError::InvalidArgument(format!(
+                "Unsupported ciphersuite {ciphersuite}."
+            ))
+            .into());
+        }
+
+        let credential = Credential::new(identity.to_vec(), CredentialType::Basic)
+            .map_err(|e| BindingError::InvalidArgument(e.to_string()))?;
+        let signer = SignatureKeyPair::new(ciphersuite.signature_algorithm())
+            .map_err(|e| BindingError::CryptoError(format!("{e:?}")))?;
+        let credential_with_key = CredentialWithKey {
+            credential,
+            signature_key: signer.to_public_vec().into(),
+        };
+
+        Ok(Self {
+            ciphersuite,
+            credential_with_key,
+            signer,
+        })
+    }
+
+    /// Restore an identity exported with `to_bytes()`.
+    #[staticmethod]
+    fn from_bytes(identity: &[u8]) -> PyResult<Self> {
+        Ok(serde_json::from_slice(identity)
+            .map_err(|e| BindingError::DecodingError(e.to_string()))?)
+    }
+
+    /// Export the identity, including its private signature key.
+    fn to_bytes(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let identity =
+            serde_json::to_vec(self).map_err(|e| BindingError::EncodingError(e.to_string()))?;
+        Ok(py_bytes(py, &identity))
+    }
+
+    /// The identity of the credential.
+    #[getter]
+    fn identity(&self, py: Python<'_>) -> PyObject {
+        py_bytes(py, self.credential_with_key.credential.identity())
+    }
+
+    /// The public signature key.
+    #[getter]
+    fn signature_key(&self, py: Python<'_>) -> PyObject {
+        py_bytes(py, self.credential_with_key.signature_key.as_slice())
+    }
+
+    /// The IANA value of the ciphersuite.
+    #[getter]
+    fn ciphersuite(&self) -> u16 {
+        self.ciphersuite.into()
+    }
+
+    /// Create a new key package and return it as `MLSMessage`. Its private
+    /// keys are stored in the provider.
+    fn key_package(&self, py: Python<'_>, provider: &Provider) -> PyResult<PyObject> {
+        let key_package = KeyPackage::builder()
+            .build(
+                CryptoConfig::with_default_version(self.ciphersuite),
+                provider,
+                &self.signer,
+                self.credential_with_key.clone(),
+            )
+            .map_err(BindingError::from)?;
+        let key_package = MlsMessageOut::from(key_package)
+            .to_bytes()
+            .map_err(BindingError::from)?;
+        Ok(py_bytes(py, &key_package))
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Identity({:?}, ciphersuite={})",
+            String::from_utf8_lossy(self.credential_with_key.credential.identity()),
+            u16::from(self.ciphersuite)
+        )
+    }
+}
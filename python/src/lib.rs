@@ -0,0 +1,47 @@
+//! # OpenMLS Python bindings
+//!
+//! A Python module for scripting MLS flows, e.g. in research and server-side
+//! tooling, against the same implementation that clients use. The module is
+//! built with [maturin](https://www.maturin.rs/) and requires the `pyo3`
+//! feature (enabled by default).
+//!
+//! The module exposes three classes:
+//!
+//! * `Provider`: the crypto provider and an in-memory key store, which can be
+//!   exported with `Provider.to_bytes()`.
+//! * `Identity`: a basic credential with its signature key pair and
+//!   ciphersuite. It creates key packages.
+//! * `Group`: an MLS group.
+//!
+//! MLS messages (key packages, commits, welcomes, application messages) are
+//! exchanged as TLS encoded `MLSMessage`s in `bytes` objects. Errors are raised
+//! as subclasses of `openmls.OpenMlsError`.
+#![cfg(feature = "pyo3")]
+
+use pyo3::{prelude::*, types::PyBytes};
+
+mod error;
+mod group;
+mod identity;
+mod provider;
+
+use error::*;
+use group::*;
+use identity::*;
+use provider::*;
+
+/// Convert `bytes` into a Python `bytes` object.
+fn py_bytes(py: Python<'_>, bytes: &[u8]) -> PyObject {
+    PyBytes::new(py, bytes).into()
+}
+
+/// The `openmls` Python module.
+#[pymodule]
+fn openmls(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<Provider>()?;
+    module.add_class::<Identity>()?;
+    module.add_class::<Group>()?;
+    module.add_class::<ProcessedMessage>()?;
+    add_exceptions(module)?;
+    Ok(())
+}
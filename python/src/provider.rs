@@ -0,0 +1,131 @@
+//! The provider and its key store.
+
+use std::{collections::HashMap, sync::RwLock};
+
+use openmls_rust_crypto::RustCrypto;
+use openmls_traits::{
+    key_store::{unwrap_blob, BlobHeader, MlsEntity, OpenMlsKeyStore},
+    OpenMlsProvider,
+};
+use pyo3::prelude::*;
+use thiserror::Error;
+
+use crate::{py_bytes, BindingError};
+
+/// An in-memory key store whose entire state can be exported and imported.
+#[derive(Debug, Default)]
+pub(crate) struct ProviderKeyStore {
+    values: RwLock<HashMap<Vec<u8>, Vec<u8>>>,
+}
+
+impl OpenMlsKeyStore for ProviderKeyStore {
+    /// The error type returned by the [`OpenMlsKeyStore`].
+    type Error = ProviderKeyStoreError;
+
+    /// Store a value `v` that implements the [`MlsEntity`] trait for
+    /// serialization for ID `k`.
+    ///
+    /// Returns an error if storing fails.
+    fn store<V: MlsEntity>(&self, k: &[u8], v: &V) -> Result<(), Self::Error> {
+        let value = serde_json::to_vec(v).map_err(|_| ProviderKeyStoreError::SerializationError)?;
+        let value = BlobHeader::for_entity::<V>().wrap(&value);
+        // We unwrap here, because the functions claiming a write lock only
+        // hold the lock very briefly and should not panic during that period.
+        self.values.write().unwrap().insert(k.to_vec(), value);
+        Ok(())
+    }
+
+    /// Read and return a value stored for ID `k` that implements the
+    /// [`MlsEntity`] trait for deserialization.
+    ///
+    /// Returns [`None`] if no value is stored for `k` or reading fails.
+    fn read<V: MlsEntity>(&self, k: &[u8]) -> Option<V> {
+        let values = self.values.read().unwrap();
+        values
+            .get(k)
+            .and_then(|value| serde_json::from_slice(unwrap_blob::<V>(value)?).ok())
+    }
+
+    /// Delete a value stored for ID `k`.
+    ///
+    /// Returns an error if deleting fails.
+    fn delete<V: MlsEntity>(&self, k: &[u8]) -> Result<(), Self::Error> {
+        self.values.write().unwrap().remove(k);
+        Ok(())
+    }
+}
+
+/// Errors thrown by the key store.
+#[derive(Error, Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum ProviderKeyStoreError {
+    /// Error serializing a value.
+    #[error("Error serializing value.")]
+    SerializationError,
+}
+
+/// The crypto provider and an in-memory key store.
+///
+/// The key store holds all key material and saved groups. Its state is
+/// exported with `to_bytes()` and restored with `Provider.from_bytes()`.
+#[pyclass(module = "openmls")]
+#[derive(Debug, Default)]
+pub(crate) struct Provider {
+    crypto: RustCrypto,
+    key_store: ProviderKeyStore,
+}
+
+#[pymethods]
+impl Provider {
+    /// Create a provider with an empty key store.
+    #[new]
+    fn new() -> Self {
+        Self::default()
+    }
+
+    /// Restore a provider from a state exported with `to_bytes()`.
+    #[staticmethod]
+    fn from_bytes(state: &[u8]) -> PyResult<Self> {
+        // JSON only supports string keys. The values are therefore exported as
+        // a list of key-value pairs.
+        let values: Vec<(Vec<u8>, Vec<u8>)> = serde_json::from_slice(state)
+            .map_err(|e| BindingError::DecodingError(e.to_string()))?;
+        Ok(Self {
+            crypto: RustCrypto::default(),
+            key_store: ProviderKeyStore {
+                values: RwLock::new(values.into_iter().collect()),
+            },
+        })
+    }
+
+    /// Export the state of the key store, including all private keys.
+    fn to_bytes(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let values = self.key_store.values.read().unwrap();
+        let values: Vec<(&Vec<u8>, &Vec<u8>)> = values.iter().collect();
+        let state =
+            serde_json::to_vec(&values).map_err(|e| BindingError::EncodingError(e.to_string()))?;
+        Ok(py_bytes(py, &state))
+    }
+}
+
+impl OpenMlsProvider for Provider {
+    type CryptoProvider = RustCrypto;
+    type RandProvider = RustCrypto;
+    type KeyStoreProvider = ProviderKeyStore;
+    type ClockProvider = RustCrypto;
+
+    fn crypto(&self) -> &Self::CryptoProvider {
+        &self.crypto
+    }
+
+    fn rand(&self) -> &Self::RandProvider {
+        &self.crypto
+    }
+
+    fn key_store(&self) -> &Self::KeyStoreProvider {
+        &self.key_store
+    }
+
+    fn clock(&self) -> &Self::ClockProvider {
+        &self.crypto
+    }
+}
@@ -0,0 +1,87 @@
+"""Tests for the OpenMLS Python bindings. Run with `pytest` after
+`maturin develop`."""
+
+import pytest
+
+import openmls
+
+
+def create_member(name):
+    provider = openmls.Provider()
+    identity = openmls.Identity(provider, name)
+    return provider, identity
+
+
+def test_core_flows():
+    alice_provider, alice = create_member(b"Alice")
+    bob_provider, bob = create_member(b"Bob")
+
+    # Alice creates a group and adds Bob.
+    alice_group = openmls.Group.create(alice_provider, alice, b"group")
+    commit, welcome = alice_group.add_members(
+        alice_provider, alice, [bob.key_package(bob_provider)]
+    )
+    assert isinstance(commit, bytes)
+    alice_group.merge_pending_commit(alice_provider)
+    assert alice_group.epoch == 1
+
+    bob_group = openmls.Group.join(bob_provider, welcome)
+    assert bob_group.group_id == b"group"
+    assert bob_group.epoch == 1
+    assert bob_group.own_leaf_index == 1
+    assert [identity for _, identity, _ in bob_group.members()] == [b"Alice", b"Bob"]
+
+    # Application messages
+    message = alice_group.encrypt(alice_provider, alice, b"Hi Bob")
+    processed = bob_group.process_message(bob_provider, message)
+    assert processed.kind == "application"
+    assert processed.sender == b"Alice"
+    assert processed.plaintext == b"Hi Bob"
+
+    # Bob updates his leaf.
+    commit = bob_group.self_update(bob_provider, bob)
+    bob_group.merge_pending_commit(bob_provider)
+    processed = alice_group.process_message(alice_provider, commit)
+    assert processed.kind == "commit"
+    assert processed.plaintext is None
+    assert alice_group.epoch == bob_group.epoch == 2
+    assert alice_group.export_secret(
+        alice_provider, "label"
+    ) == bob_group.export_secret(bob_provider, "label")
+
+    # Alice removes Bob.
+    commit = alice_group.remove_members(alice_provider, alice, [1])
+    alice_group.merge_pending_commit(alice_provider)
+    bob_group.process_message(bob_provider, commit)
+    assert not bob_group.is_active
+    assert len(alice_group.members()) == 1
+
+
+def test_persistence():
+    provider, alice = create_member(b"Alice")
+    group = openmls.Group.create(provider, alice, b"group")
+    group.save(provider)
+
+    provider = openmls.Provider.from_bytes(provider.to_bytes())
+    alice = openmls.Identity.from_bytes(alice.to_bytes())
+    group = openmls.Group.load(provider, b"group")
+    assert group.epoch == 0
+    group.self_update(provider, alice)
+    group.merge_pending_commit(provider)
+    assert group.epoch == 1
+
+
+def test_errors():
+    provider, alice = create_member(b"Alice")
+
+    with pytest.raises(openmls.NotFoundError):
+        openmls.Group.load(provider, b"unknown")
+    with pytest.raises(openmls.InvalidArgumentError):
+        openmls.Identity(provider, b"Alice", ciphersuite=0xFFFF)
+    with pytest.raises(openmls.DecodingError):
+        openmls.Group.join(provider, b"\x00")
+
+    # All exceptions derive from `OpenMlsError`.
+    group = openmls.Group.create(provider, alice, b"group")
+    with pytest.raises(openmls.OpenMlsError):
+        group.process_message(provider, alice.key_package(provider))
@@ -0,0 +1,13 @@
+//! # Clock for OpenMLS
+//!
+//! The [`OpenMlsClock`] trait defines the functionality required by OpenMLS to
+//! get the current time, e.g. to validate the lifetime of leaf nodes.
+//! Applications that don't want to rely on the system time, e.g. because they
+//! have a trusted time source or need a fixed time in tests, provide their own
+//! implementation.
+
+pub trait OpenMlsClock {
+    /// Returns the current time in seconds since the Unix epoch
+    /// (1970-01-01T00:00:00Z).
+    fn now(&self) -> u64;
+}
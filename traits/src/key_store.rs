@@ -1,6 +1,7 @@
 //! # OpenMLS Key Store Trait
 
 /// Sealed list of struct openmls manages (create/read/delete) through [OpenMlsKeyStore]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MlsEntityId {
     SignatureKeyPair,
     HpkePrivateKey,
@@ -8,6 +9,36 @@ pub enum MlsEntityId {
     PskBundle,
     EncryptionKeyPair,
     GroupState,
+    ConsumedKeyPackage,
+}
+
+impl MlsEntityId {
+    /// Returns the content type of the entity in a [`BlobHeader`].
+    pub fn content_type(&self) -> u8 {
+        match self {
+            MlsEntityId::SignatureKeyPair => 1,
+            MlsEntityId::HpkePrivateKey => 2,
+            MlsEntityId::KeyPackage => 3,
+            MlsEntityId::PskBundle => 4,
+            MlsEntityId::EncryptionKeyPair => 5,
+            MlsEntityId::GroupState => 6,
+            MlsEntityId::ConsumedKeyPackage => 7,
+        }
+    }
+
+    /// Returns the entity with the given content type of a [`BlobHeader`].
+    pub fn from_content_type(content_type: u8) -> Option<Self> {
+        Some(match content_type {
+            1 => MlsEntityId::SignatureKeyPair,
+            2 => MlsEntityId::HpkePrivateKey,
+            3 => MlsEntityId::KeyPackage,
+            4 => MlsEntityId::PskBundle,
+            5 => MlsEntityId::EncryptionKeyPair,
+            6 => MlsEntityId::GroupState,
+            7 => MlsEntityId::ConsumedKeyPackage,
+            _ => return None,
+        })
+    }
 }
 
 /// To implement by any struct owned by openmls aiming to be persisted in [OpenMlsKeyStore]
@@ -15,6 +46,10 @@ pub trait MlsEntity: serde::Serialize + serde::de::DeserializeOwned {
     /// Identifier used to downcast the actual entity within an [OpenMlsKeyStore] method.
     /// In case for example you need to select a SQL table depending on the entity type
     const ID: MlsEntityId;
+
+    /// Version of the schema in which the entity is persisted. It is written
+    /// to the [`BlobHeader`] of the persisted blob.
+    const VERSION: u16 = 1;
 }
 
 /// Blanket impl for when you have to lookup a list of entities from the keystore
@@ -23,6 +58,82 @@ where
     T: MlsEntity + std::fmt::Debug,
 {
     const ID: MlsEntityId = T::ID;
+    const VERSION: u16 = T::VERSION;
+}
+
+/// The magic bytes at the start of every blob written with a [`BlobHeader`].
+pub const BLOB_MAGIC: [u8; 4] = *b"OMLS";
+
+/// The header of a persisted blob. It makes persisted entities
+/// self-describing, so that tooling can identify and migrate them.
+///
+/// A header is encoded as the [`BLOB_MAGIC`], the schema version as a
+/// big-endian `u16` and the content type of the [`MlsEntityId`] as a `u8`,
+/// followed by the serialized entity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlobHeader {
+    entity: MlsEntityId,
+    version: u16,
+}
+
+impl BlobHeader {
+    /// The length of an encoded header in bytes.
+    pub const LEN: usize = 7;
+
+    /// Create the header for blobs of `V`.
+    pub fn for_entity<V: MlsEntity>() -> Self {
+        Self {
+            entity: V::ID,
+            version: V::VERSION,
+        }
+    }
+
+    /// Returns the entity that is contained in the blob.
+    pub fn entity(&self) -> MlsEntityId {
+        self.entity
+    }
+
+    /// Returns the schema version of the contained entity.
+    pub fn version(&self) -> u16 {
+        self.version
+    }
+
+    /// Returns the blob of the header followed by `content`.
+    pub fn wrap(&self, content: &[u8]) -> Vec<u8> {
+        let mut blob = Vec::with_capacity(Self::LEN + content.len());
+        blob.extend_from_slice(&BLOB_MAGIC);
+        blob.extend_from_slice(&self.version.to_be_bytes());
+        blob.push(self.entity.content_type());
+        blob.extend_from_slice(content);
+        blob
+    }
+}
+
+/// Detect the [`BlobHeader`] of a persisted `blob`. Returns the header and
+/// the content that follows it, or [`None`] if the blob doesn't start with a
+/// valid header.
+pub fn detect(blob: &[u8]) -> Option<(BlobHeader, &[u8])> {
+    if blob.len() < BlobHeader::LEN || blob[..4] != BLOB_MAGIC {
+        return None;
+    }
+    let header = BlobHeader {
+        version: u16::from_be_bytes([blob[4], blob[5]]),
+        entity: MlsEntityId::from_content_type(blob[6])?,
+    };
+    Some((header, &blob[BlobHeader::LEN..]))
+}
+
+/// Returns the content of a blob of `V` that was written with
+/// [`BlobHeader::wrap()`]. Blobs without a header, which were persisted before
+/// headers were introduced, are returned as they are. Returns [`None`] if the
+/// blob contains a different entity or a newer schema version.
+pub fn unwrap_blob<V: MlsEntity>(blob: &[u8]) -> Option<&[u8]> {
+    match detect(blob) {
+        Some((header, content)) => {
+            (header.entity() == V::ID && header.version() <= V::VERSION).then_some(content)
+        }
+        None => Some(blob),
+    }
 }
 
 /// The Key Store trait
@@ -5,7 +5,12 @@
 
 use std::fmt::Debug;
 
-pub trait OpenMlsRand {
+/// The randomness source.
+///
+/// Like the [`OpenMlsCrypto`](crate::crypto::OpenMlsCrypto) provider, it has
+/// to be [`Send`] and [`Sync`] so that providers can be shared between
+/// threads.
+pub trait OpenMlsRand: Send + Sync {
     type Error: std::error::Error + Debug + Clone + PartialEq;
 
     /// Fill an array with random bytes.
@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use crate::types::{Error, SignatureScheme};
 
 /// Sign the provided payload and return a signature.
@@ -10,3 +12,37 @@ pub trait Signer {
     /// The [`SignatureScheme`] of this signer.
     fn signature_scheme(&self) -> SignatureScheme;
 }
+
+// The trait is object safe. The following implementations allow passing
+// trait objects, e.g. a `Box<dyn Signer + Send + Sync>`, to functions that
+// take an `&impl Signer`.
+
+impl<S: Signer + ?Sized> Signer for &S {
+    fn sign(&self, payload: &[u8]) -> Result<Vec<u8>, Error> {
+        (**self).sign(payload)
+    }
+
+    fn signature_scheme(&self) -> SignatureScheme {
+        (**self).signature_scheme()
+    }
+}
+
+impl<S: Signer + ?Sized> Signer for Box<S> {
+    fn sign(&self, payload: &[u8]) -> Result<Vec<u8>, Error> {
+        (**self).sign(payload)
+    }
+
+    fn signature_scheme(&self) -> SignatureScheme {
+        (**self).signature_scheme()
+    }
+}
+
+impl<S: Signer + ?Sized> Signer for Arc<S> {
+    fn sign(&self, payload: &[u8]) -> Result<Vec<u8>, Error> {
+        (**self).sign(payload)
+    }
+
+    fn signature_scheme(&self) -> SignatureScheme {
+        (**self).signature_scheme()
+    }
+}
@@ -3,6 +3,7 @@
 //! This module defines a number of traits that are used by the public
 //! API of OpenMLS.
 
+pub mod clock;
 pub mod crypto;
 pub mod key_store;
 pub mod random;
@@ -12,11 +13,13 @@ pub mod types;
 /// The OpenMLS Crypto Provider Trait
 ///
 /// An implementation of this trait must be passed in to the public OpenMLS API
-/// to perform randomness generation, cryptographic operations, and key storage.
+/// to perform randomness generation, cryptographic operations, key storage,
+/// and to get the current time.
 pub trait OpenMlsProvider {
     type CryptoProvider: crypto::OpenMlsCrypto;
     type RandProvider: random::OpenMlsRand;
     type KeyStoreProvider: key_store::OpenMlsKeyStore;
+    type ClockProvider: clock::OpenMlsClock;
 
     /// Get the crypto provider.
     fn crypto(&self) -> &Self::CryptoProvider;
@@ -26,4 +29,7 @@ pub trait OpenMlsProvider {
 
     /// Get the key store provider.
     fn key_store(&self) -> &Self::KeyStoreProvider;
+
+    /// Get the clock provider.
+    fn clock(&self) -> &Self::ClockProvider;
 }
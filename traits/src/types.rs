@@ -232,13 +232,22 @@ pub struct HpkeCiphertext {
 }
 
 /// A simple type for HPKE private keys.
-#[derive(
-    Debug, Clone, serde::Serialize, serde::Deserialize, TlsSerialize, TlsDeserialize, TlsSize,
-)]
+///
+/// The [`Debug`](std::fmt::Debug) output doesn't contain the key unless the
+/// `crypto-debug` feature is enabled.
+#[derive(Clone, serde::Serialize, serde::Deserialize, TlsSerialize, TlsDeserialize, TlsSize)]
 #[cfg_attr(feature = "test-utils", derive(PartialEq, Eq))]
+#[cfg_attr(feature = "crypto-debug", derive(Debug))]
 #[serde(transparent)]
 pub struct HpkePrivateKey(SecretVLBytes);
 
+#[cfg(not(feature = "crypto-debug"))]
+impl std::fmt::Debug for HpkePrivateKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("HpkePrivateKey").field(&"***").finish()
+    }
+}
+
 impl From<Vec<u8>> for HpkePrivateKey {
     fn from(bytes: Vec<u8>) -> Self {
         Self(bytes.into())
@@ -271,9 +280,22 @@ pub struct HpkeKeyPair {
 }
 
 pub type KemOutput = Vec<u8>;
-#[derive(Clone, Debug)]
+
+/// A secret exported from the key schedule.
+///
+/// The [`Debug`](std::fmt::Debug) output doesn't contain the secret unless the
+/// `crypto-debug` feature is enabled.
+#[derive(Clone)]
+#[cfg_attr(feature = "crypto-debug", derive(Debug))]
 pub struct ExporterSecret(SecretVLBytes);
 
+#[cfg(not(feature = "crypto-debug"))]
+impl std::fmt::Debug for ExporterSecret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("ExporterSecret").field(&"***").finish()
+    }
+}
+
 impl Deref for ExporterSecret {
     type Target = [u8];
 
@@ -308,6 +330,12 @@ impl From<Ciphersuite> for VerifiableCiphersuite {
     }
 }
 
+impl From<VerifiableCiphersuite> for u16 {
+    fn from(value: VerifiableCiphersuite) -> Self {
+        value.0
+    }
+}
+
 /// MLS ciphersuites.
 #[allow(non_camel_case_types)]
 #[allow(clippy::upper_case_acronyms)]
@@ -0,0 +1,219 @@
+//! Errors of the bindings.
+
+use openmls::{error::LibraryError, framing::errors::MlsMessageError, prelude::*};
+use thiserror::Error;
+
+use crate::StorageError;
+
+/// The error thrown by the bindings.
+///
+/// Each variant carries a description of the underlying error.
+#[derive(Error, Debug, Clone, PartialEq, Eq, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum OpenMlsError {
+    /// An argument is invalid, e.g. an unsupported ciphersuite or an unknown
+    /// member.
+    #[error("Invalid argument: {0}")]
+    InvalidArgument(String),
+    /// The input bytes could not be decoded.
+    #[error("Decoding error: {0}")]
+    DecodingError(String),
+    /// A value could not be encoded.
+    #[error("Encoding error: {0}")]
+    EncodingError(String),
+    /// The requested value was not found.
+    #[error("Not found: {0}")]
+    NotFound(String),
+    /// A message, key package or welcome was rejected by the validation.
+    #[error("Invalid message: {0}")]
+    InvalidMessage(String),
+    /// The operation is not possible in the current state of the group, e.g.
+    /// because a commit is pending or the group is inactive.
+    #[error("Group state error: {0}")]
+    GroupStateError(String),
+    /// The [`StorageProvider`](crate::StorageProvider) returned an error.
+    #[error("Storage error: {0}")]
+    StorageError(String),
+    /// A cryptographic operation failed.
+    #[error("Crypto error: {0}")]
+    CryptoError(String),
+    /// An internal error occurred. This is a bug in OpenMLS.
+    #[error("Library error: {0}")]
+    LibraryError(String),
+}
+
+impl From<LibraryError> for OpenMlsError {
+    fn from(error: LibraryError) -> Self {
+        Self::LibraryError(error.to_string())
+    }
+}
+
+impl From<StorageError> for OpenMlsError {
+    fn from(error: StorageError) -> Self {
+        Self::StorageError(error.to_string())
+    }
+}
+
+impl From<tls_codec::Error> for OpenMlsError {
+    fn from(error: tls_codec::Error) -> Self {
+        Self::DecodingError(error.to_string())
+    }
+}
+
+impl From<MlsMessageError> for OpenMlsError {
+    fn from(error: MlsMessageError) -> Self {
+        Self::EncodingError(error.to_string())
+    }
+}
+
+impl From<MlsGroupStateError> for OpenMlsError {
+    fn from(error: MlsGroupStateError) -> Self {
+        match error {
+            MlsGroupStateError::LibraryError(error) => error.into(),
+            _ => Self::GroupStateError(error.to_string()),
+        }
+    }
+}
+
+impl From<KeyPackageVerifyError> for OpenMlsError {
+    fn from(error: KeyPackageVerifyError) -> Self {
+        match error {
+            KeyPackageVerifyError::LibraryError(error) => error.into(),
+            _ => Self::InvalidMessage(error.to_string()),
+        }
+    }
+}
+
+impl From<KeyPackageNewError<StorageError>> for OpenMlsError {
+    fn from(error: KeyPackageNewError<StorageError>) -> Self {
+        match error {
+            KeyPackageNewError::LibraryError(error) => error.into(),
+            KeyPackageNewError::KeyStoreError(error) => error.into(),
+            KeyPackageNewError::SignatureError(_) => Self::CryptoError(error.to_string()),
+            KeyPackageNewError::CiphersuiteSignatureSchemeMismatch => {
+                Self::InvalidArgument(error.to_string())
+            }
+        }
+    }
+}
+
+impl From<NewGroupError<StorageError>> for OpenMlsError {
+    fn from(error: NewGroupError<StorageError>) -> Self {
+        match error {
+            NewGroupError::LibraryError(error) => error.into(),
+            NewGroupError::KeyStoreError(error) => error.into(),
+            _ => Self::InvalidArgument(error.to_string()),
+        }
+    }
+}
+
+impl From<WelcomeError<StorageError>> for OpenMlsError {
+    fn from(error: WelcomeError<StorageError>) -> Self {
+        match error {
+            WelcomeError::LibraryError(error) => error.into(),
+            WelcomeError::KeyStoreError(error) => error.into(),
+            // The welcome is not for any of our key packages.
+            WelcomeError::NoMatchingKeyPackage | WelcomeError::NoMatchingEncryptionKey => {
+                Self::NotFound(error.to_string())
+            }
+            _ => Self::InvalidMessage(error.to_string()),
+        }
+    }
+}
+
+impl From<CreateCommitError<StorageError>> for OpenMlsError {
+    fn from(error: CreateCommitError<StorageError>) -> Self {
+        match error {
+            CreateCommitError::LibraryError(error) => error.into(),
+            CreateCommitError::KeyStoreError(error) => error.into(),
+            CreateCommitError::KeyPackageGenerationError(error) => error.into(),
+            CreateCommitError::SignatureError(_) => Self::CryptoError(error.to_string()),
+            _ => Self::InvalidArgument(error.to_string()),
+        }
+    }
+}
+
+impl From<AddMembersError<StorageError>> for OpenMlsError {
+    fn from(error: AddMembersError<StorageError>) -> Self {
+        match error {
+            AddMembersError::LibraryError(error) => error.into(),
+            AddMembersError::EmptyInput(_) => Self::InvalidArgument(error.to_string()),
+            AddMembersError::CreateCommitError(error) => error.into(),
+            AddMembersError::GroupStateError(error) => error.into(),
+        }
+    }
+}
+
+impl From<RemoveMembersError<StorageError>> for OpenMlsError {
+    fn from(error: RemoveMembersError<StorageError>) -> Self {
+        match error {
+            RemoveMembersError::LibraryError(error) => error.into(),
+            RemoveMembersError::EmptyInput(_) | RemoveMembersError::UnknownMember => {
+                Self::InvalidArgument(error.to_string())
+            }
+            RemoveMembersError::CreateCommitError(error) => error.into(),
+            RemoveMembersError::GroupStateError(error) => error.into(),
+        }
+    }
+}
+
+impl From<SelfUpdateError<StorageError>> for OpenMlsError {
+    fn from(error: SelfUpdateError<StorageError>) -> Self {
+        match error {
+            SelfUpdateError::LibraryError(error) => error.into(),
+            SelfUpdateError::CreateCommitError(error) => error.into(),
+            SelfUpdateError::GroupStateError(error) => error.into(),
+            SelfUpdateError::KeyStoreError => Self::StorageError(error.to_string()),
+        }
+    }
+}
+
+impl From<MergeCommitError<StorageError>> for OpenMlsError {
+    fn from(error: MergeCommitError<StorageError>) -> Self {
+        match error {
+            MergeCommitError::LibraryError(error) => error.into(),
+            MergeCommitError::KeyStoreError(error) => error.into(),
+            MergeCommitError::GroupStateError(error) => error.into(),
+        }
+    }
+}
+
+impl From<MergePendingCommitError<StorageError>> for OpenMlsError {
+    fn from(error: MergePendingCommitError<StorageError>) -> Self {
+        match error {
+            MergePendingCommitError::MlsGroupStateError(error) => error.into(),
+            MergePendingCommitError::MergeCommitError(error) => error.into(),
+        }
+    }
+}
+
+impl From<CreateMessageError> for OpenMlsError {
+    fn from(error: CreateMessageError) -> Self {
+        match error {
+            CreateMessageError::LibraryError(error) => error.into(),
+            CreateMessageError::GroupStateError(error) => error.into(),
+        }
+    }
+}
+
+impl From<ProcessMessageError> for OpenMlsError {
+    fn from(error: ProcessMessageError) -> Self {
+        match error {
+            ProcessMessageError::LibraryError(error) => error.into(),
+            ProcessMessageError::GroupStateError(error) => error.into(),
+            _ => Self::InvalidMessage(error.to_string()),
+        }
+    }
+}
+
+impl From<ExportSecretError> for OpenMlsError {
+    fn from(error: ExportSecretError) -> Self {
+        match error {
+            ExportSecretError::LibraryError(error) => error.into(),
+            ExportSecretError::KeyLengthTooLong | ExportSecretError::ReservedLabel => {
+                Self::InvalidArgument(error.to_string())
+            }
+            ExportSecretError::GroupStateError(error) => error.into(),
+        }
+    }
+}
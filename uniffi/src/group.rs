@@ -0,0 +1,324 @@
+//! Groups.
+
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use openmls::prelude::{config::CryptoConfig, *};
+use openmls_traits::OpenMlsProvider;
+use tls_codec::Deserialize;
+
+use crate::{Identity, OpenMlsError, Provider};
+
+/// The result of [`Group::add_members()`].
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct AddMembersResult {
+    /// The commit for the existing members.
+    pub commit: Vec<u8>,
+    /// The welcome for the new members.
+    pub welcome: Vec<u8>,
+}
+
+/// A member of a group.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct GroupMember {
+    /// The leaf index of the member, used to remove it.
+    pub index: u32,
+    /// The identity of the member's credential.
+    pub identity: Vec<u8>,
+    /// The public signature key of the member.
+    pub signature_key: Vec<u8>,
+}
+
+/// A message processed with [`Group::process_message()`].
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Enum)]
+pub enum ProcessedMessage {
+    /// An application message.
+    Application {
+        /// The identity of the sender's credential.
+        sender: Vec<u8>,
+        /// The decrypted message.
+        plaintext: Vec<u8>,
+    },
+    /// A proposal. It has been stored and is committed by the next commit.
+    Proposal,
+    /// A commit. It has been merged and the group is in the next epoch. If the
+    /// commit removed the own member, the group is inactive afterwards (see
+    /// [`Group::is_active()`]).
+    Commit,
+}
+
+/// An MLS group.
+///
+/// The group is internally synchronized and can be used from multiple threads.
+#[derive(Debug, uniffi::Object)]
+pub struct Group {
+    group: Mutex<MlsGroup>,
+}
+
+/// The configuration of groups created through the bindings. The ratchet tree
+/// is included in welcome messages, so that they can be processed on their
+/// own.
+fn group_config(ciphersuite: Ciphersuite) -> MlsGroupConfig {
+    MlsGroupConfig::builder()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .use_ratchet_tree_extension(true)
+        .build()
+}
+
+/// Decode an `MLSMessage`.
+fn decode_message(message: &[u8]) -> Result<MlsMessageInBody, OpenMlsError> {
+    Ok(MlsMessageIn::tls_deserialize_exact(message)?.extract())
+}
+
+impl Group {
+    fn from_group(group: MlsGroup) -> Arc<Self> {
+        Arc::new(Self {
+            group: Mutex::new(group),
+        })
+    }
+
+    fn lock(&self) -> MutexGuard<'_, MlsGroup> {
+        // A poisoned lock means that a previous call panicked while modifying
+        // the group. The state of the group is unknown in this case.
+        self.group.lock().unwrap()
+    }
+}
+
+#[uniffi::export]
+impl Group {
+    /// Create a new group with the ID `group_id` and `identity` as the only
+    /// member.
+    #[uniffi::constructor]
+    pub fn new(
+        provider: &Provider,
+        identity: &Identity,
+        group_id: Vec<u8>,
+    ) -> Result<Arc<Self>, OpenMlsError> {
+        let group = MlsGroup::new_with_group_id(
+            provider,
+            &identity.signer,
+            &group_config(identity.ciphersuite),
+            GroupId::from_slice(&group_id),
+            identity.credential_with_key.clone(),
+        )?;
+        Ok(Self::from_group(group))
+    }
+
+    /// Join a group with the `welcome` message. The welcome must have been
+    /// created for a key package created with
+    /// [`Identity::create_key_package()`] and the same provider.
+    #[uniffi::constructor]
+    pub fn join(provider: &Provider, welcome: Vec<u8>) -> Result<Arc<Self>, OpenMlsError> {
+        let welcome = match decode_message(&welcome)? {
+            MlsMessageInBody::Welcome(welcome) => welcome,
+            _ => {
+                return Err(OpenMlsError::InvalidArgument(
+                    "The message is not a welcome.".to_string(),
+                ))
+            }
+        };
+        // The ciphersuite is taken from the welcome.
+        let config = MlsGroupConfig::builder()
+            .use_ratchet_tree_extension(true)
+            .build();
+        let group = MlsGroup::new_from_welcome(provider, &config, welcome, None)?;
+        Ok(Self::from_group(group))
+    }
+
+    /// Load the group with the ID `group_id`, which has been saved with
+    /// [`Group::save()`].
+    #[uniffi::constructor]
+    pub fn load(provider: &Provider, group_id: Vec<u8>) -> Result<Arc<Self>, OpenMlsError> {
+        let group = MlsGroup::load(&GroupId::from_slice(&group_id), provider.key_store())
+            .ok_or_else(|| OpenMlsError::NotFound("No group with this ID.".to_string()))?;
+        Ok(Self::from_group(group))
+    }
+
+    /// Save the group in the provider's storage.
+    pub fn save(&self, provider: &Provider) -> Result<(), OpenMlsError> {
+        self.lock().save(provider.key_store())?;
+        Ok(())
+    }
+
+    /// The ID of the group.
+    pub fn group_id(&self) -> Vec<u8> {
+        self.lock().group_id().as_slice().to_vec()
+    }
+
+    /// The current epoch.
+    pub fn epoch(&self) -> u64 {
+        self.lock().epoch().as_u64()
+    }
+
+    /// The leaf index of the own member.
+    pub fn own_leaf_index(&self) -> u32 {
+        self.lock().own_leaf_index().u32()
+    }
+
+    /// Whether the own member is still part of the group.
+    pub fn is_active(&self) -> bool {
+        self.lock().is_active()
+    }
+
+    /// The members of the group.
+    pub fn members(&self) -> Vec<GroupMember> {
+        self.lock()
+            .members()
+            .map(|member| GroupMember {
+                index: member.index.u32(),
+                identity: member.credential.identity().to_vec(),
+                signature_key: member.signature_key,
+            })
+            .collect()
+    }
+
+    /// Add the members with the given `key_packages` to the group.
+    ///
+    /// The commit is pending until it is merged with
+    /// [`Group::merge_pending_commit()`], which should happen once the
+    /// delivery service accepted it.
+    pub fn add_members(
+        &self,
+        provider: &Provider,
+        identity: &Identity,
+        key_packages: Vec<Vec<u8>>,
+    ) -> Result<AddMembersResult, OpenMlsError> {
+        let key_packages = key_packages
+            .iter()
+            .map(|key_package| match decode_message(key_package)? {
+                MlsMessageInBody::KeyPackage(key_package) => Ok(key_package.validate(
+                    provider.crypto(),
+                    ProtocolVersion::Mls10,
+                    provider.clock(),
+                )?),
+                _ => Err(OpenMlsError::InvalidArgument(
+                    "The message is not a key package.".to_string(),
+                )),
+            })
+            .collect::<Result<Vec<_>, OpenMlsError>>()?;
+        let (commit, welcome, _group_info) = self
+            .lock()
+            .add_members(provider, &identity.signer, &key_packages)?
+            .into_parts();
+        Ok(AddMembersResult {
+            commit: commit.to_bytes()?,
+            welcome: welcome.to_bytes()?,
+        })
+    }
+
+    /// Remove the members at `leaf_indices` from the group and return the
+    /// commit.
+    ///
+    /// The commit is pending until it is merged with
+    /// [`Group::merge_pending_commit()`].
+    pub fn remove_members(
+        &self,
+        provider: &Provider,
+        identity: &Identity,
+        leaf_indices: Vec<u32>,
+    ) -> Result<Vec<u8>, OpenMlsError> {
+        let leaf_indices: Vec<LeafNodeIndex> =
+            leaf_indices.into_iter().map(LeafNodeIndex::new).collect();
+        let (commit, _welcome, _group_info) = self
+            .lock()
+            .remove_members(provider, &identity.signer, &leaf_indices)?
+            .into_parts();
+        Ok(commit.to_bytes()?)
+    }
+
+    /// Update the own leaf and return the commit.
+    ///
+    /// The commit is pending until it is merged with
+    /// [`Group::merge_pending_commit()`].
+    pub fn self_update(
+        &self,
+        provider: &Provider,
+        identity: &Identity,
+    ) -> Result<Vec<u8>, OpenMlsError> {
+        let (commit, _welcome, _group_info) = self
+            .lock()
+            .self_update(provider, &identity.signer)?
+            .into_parts();
+        Ok(commit.to_bytes()?)
+    }
+
+    /// Merge the pending commit.
+    pub fn merge_pending_commit(&self, provider: &Provider) -> Result<(), OpenMlsError> {
+        self.lock().merge_pending_commit(provider)?;
+        Ok(())
+    }
+
+    /// Discard the pending commit, e.g. because the delivery service rejected
+    /// it.
+    pub fn clear_pending_commit(&self) {
+        self.lock().clear_pending_commit()
+    }
+
+    /// Encrypt the application message `plaintext` and return the resulting
+    /// `MLSMessage`.
+    pub fn encrypt(
+        &self,
+        provider: &Provider,
+        identity: &Identity,
+        plaintext: Vec<u8>,
+    ) -> Result<Vec<u8>, OpenMlsError> {
+        let message = self
+            .lock()
+            .create_message(provider, &identity.signer, &plaintext)?;
+        Ok(message.to_bytes()?)
+    }
+
+    /// Process the `MLSMessage` `message`.
+    ///
+    /// * Application messages are decrypted.
+    /// * Proposals are stored in the group.
+    /// * Commits are merged.
+    pub fn process_message(
+        &self,
+        provider: &Provider,
+        message: Vec<u8>,
+    ) -> Result<ProcessedMessage, OpenMlsError> {
+        let message = match decode_message(&message)? {
+            MlsMessageInBody::PublicMessage(message) => ProtocolMessage::from(message),
+            MlsMessageInBody::PrivateMessage(message) => ProtocolMessage::from(message),
+            _ => {
+                return Err(OpenMlsError::InvalidArgument(
+                    "The message is not a protocol message.".to_string(),
+                ))
+            }
+        };
+        let mut group = self.lock();
+        let processed_message = group.process_message(provider, message)?;
+        let sender = processed_message.credential().identity().to_vec();
+        Ok(match processed_message.into_content() {
+            ProcessedMessageContent::ApplicationMessage(message) => ProcessedMessage::Application {
+                sender,
+                plaintext: message.into_bytes(),
+            },
+            ProcessedMessageContent::ProposalMessage(proposal) => {
+                group.store_pending_proposal(*proposal);
+                ProcessedMessage::Proposal
+            }
+            ProcessedMessageContent::ExternalJoinProposalMessage(proposal) => {
+                group.store_pending_proposal(*proposal);
+                ProcessedMessage::Proposal
+            }
+            ProcessedMessageContent::StagedCommitMessage(staged_commit) => {
+                group.merge_staged_commit(provider, *staged_commit)?;
+                ProcessedMessage::Commit
+            }
+        })
+    }
+
+    /// Export a secret of `length` bytes from the current epoch.
+    pub fn export_secret(
+        &self,
+        provider: &Provider,
+        label: String,
+        context: Vec<u8>,
+        length: u32,
+    ) -> Result<Vec<u8>, OpenMlsError> {
+        Ok(self
+            .lock()
+            .export_secret(provider.crypto(), label, &context, length as usize)?)
+    }
+}
@@ -0,0 +1,114 @@
+//! Identities and key packages.
+
+use std::sync::Arc;
+
+use openmls::prelude::{config::CryptoConfig, *};
+use openmls_basic_credential::SignatureKeyPair;
+use openmls_traits::{crypto::OpenMlsCrypto, OpenMlsProvider};
+use serde::{Deserialize, Serialize};
+
+use crate::{OpenMlsError, Provider};
+
+/// A new key package, created with [`Identity::create_key_package()`].
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct NewKeyPackage {
+    /// The key package as TLS encoded `MLSMessage`, to be uploaded to the
+    /// delivery service.
+    pub key_package: Vec<u8>,
+    /// The hash reference of the key package. It identifies the key package,
+    /// e.g. to delete it with [`Provider::delete_key_package()`].
+    pub reference: Vec<u8>,
+}
+
+/// An identity, consisting of a basic credential with its signature key pair.
+///
+/// The identity is bound to a ciphersuite, which is used for its key packages
+/// and the groups it creates.
+#[derive(Debug, Serialize, Deserialize, uniffi::Object)]
+pub struct Identity {
+    pub(crate) ciphersuite: Ciphersuite,
+    pub(crate) credential_with_key: CredentialWithKey,
+    pub(crate) signer: SignatureKeyPair,
+}
+
+#[uniffi::export]
+impl Identity {
+    /// Create a new identity with a basic credential for `identity`.
+    /// `ciphersuite` is the IANA value of the ciphersuite, e.g. `1` for
+    /// `MLS_128_DHKEMX25519_AES128GCM_SHA256_Ed25519`.
+    #[uniffi::constructor]
+    pub fn new(
+        provider: &Provider,
+        ciphersuite: u16,
+        identity: Vec<u8>,
+    ) -> Result<Arc<Self>, OpenMlsError> {
+        let ciphersuite = Ciphersuite::try_from(ciphersuite)
+            .map_err(|e| OpenMlsError::InvalidArgument(e.to_string()))?;
+        if !provider
+            .crypto()
+            .supported_ciphersuites()
+            .contains(&ciphersuite)
+        {
+            return Err(OpenMlsError::InvalidArgument(format!(
+                "Unsupported ciphersuite {ciphersuite}."
+            )));
+        }
+
+        let credential = Credential::new(identity, CredentialType::Basic)
+            .map_err(|e| OpenMlsError::InvalidArgument(e.to_string()))?;
+        let signer = SignatureKeyPair::new(ciphersuite.signature_algorithm())
+            .map_err(|e| OpenMlsError::CryptoError(format!("{e:?}")))?;
+        let credential_with_key = CredentialWithKey {
+            credential,
+            signature_key: signer.to_public_vec().into(),
+        };
+
+        Ok(Arc::new(Self {
+            ciphersuite,
+            credential_with_key,
+            signer,
+        }))
+    }
+
+    /// Load an identity exported with [`Identity::to_bytes()`].
+    #[uniffi::constructor]
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Arc<Self>, OpenMlsError> {
+        let identity: Self = serde_json::from_slice(&bytes)
+            .map_err(|e| OpenMlsError::DecodingError(e.to_string()))?;
+        Ok(Arc::new(identity))
+    }
+
+    /// Export the identity, including its private signature key. The app has
+    /// to store it securely.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, OpenMlsError> {
+        serde_json::to_vec(self).map_err(|e| OpenMlsError::EncodingError(e.to_string()))
+    }
+
+    /// The identity of the credential.
+    pub fn identity(&self) -> Vec<u8> {
+        self.credential_with_key.credential.identity().to_vec()
+    }
+
+    /// The public signature key.
+    pub fn signature_key(&self) -> Vec<u8> {
+        self.credential_with_key.signature_key.as_slice().to_vec()
+    }
+
+    /// Create a new key package. Its private keys are stored in the provider
+    /// until the key package is used to join a group or deleted with
+    /// [`Provider::delete_key_package()`].
+    pub fn create_key_package(&self, provider: &Provider) -> Result<NewKeyPackage, OpenMlsError> {
+        let key_package = KeyPackage::builder().build(
+            CryptoConfig::with_default_version(self.ciphersuite),
+            provider,
+            &self.signer,
+            self.credential_with_key.clone(),
+        )?;
+        let reference = key_package.hash_ref(provider.crypto())?.as_slice().to_vec();
+        let key_package = MlsMessageOut::from(key_package).to_bytes()?;
+        Ok(NewKeyPackage {
+            key_package,
+            reference,
+        })
+    }
+}
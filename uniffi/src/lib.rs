@@ -0,0 +1,34 @@
+//! # OpenMLS UniFFI
+//!
+//! [UniFFI](https://mozilla.github.io/uniffi-rs/) bindings for OpenMLS, used to
+//! generate Kotlin and Swift APIs for mobile apps.
+//!
+//! The bindings expose three objects:
+//!
+//! * [`Provider`]: the crypto provider and the key store. The key store is
+//!   backed by a [`StorageProvider`], which is implemented by the app, e.g. on
+//!   top of a database.
+//! * [`Identity`]: a basic credential with its signature key pair and
+//!   ciphersuite. It creates key packages for the identity.
+//! * [`Group`]: an MLS group.
+//!
+//! Objects are reference counted and freed when the last reference in the
+//! foreign language is dropped (or `destroy()`/`close()` is called). They can
+//! be shared between threads. Errors are thrown as [`OpenMlsError`] (an
+//! `OpenMlsException` in Kotlin). Errors returned by the [`StorageProvider`]
+//! are passed through as [`OpenMlsError::StorageError`].
+//!
+//! MLS messages (key packages, commits, welcomes, application messages) are
+//! exchanged as TLS encoded `MLSMessage`s.
+
+mod error;
+mod group;
+mod identity;
+mod provider;
+
+pub use error::*;
+pub use group::*;
+pub use identity::*;
+pub use provider::*;
+
+uniffi::setup_scaffolding!();
@@ -0,0 +1,194 @@
+//! The provider and the storage callbacks.
+
+use std::{collections::HashMap, sync::Arc, sync::RwLock};
+
+use openmls::prelude::KeyPackage;
+use openmls_rust_crypto::RustCrypto;
+use openmls_traits::{
+    key_store::{unwrap_blob, BlobHeader, MlsEntity, OpenMlsKeyStore},
+    OpenMlsProvider,
+};
+use thiserror::Error;
+
+use crate::OpenMlsError;
+
+/// The storage of the key store, implemented by the app.
+///
+/// All key material and saved groups are written through this interface.
+/// Values are opaque byte strings that are stored under opaque keys.
+/// Implementations have to be thread-safe.
+#[uniffi::export(with_foreign)]
+pub trait StorageProvider: Send + Sync {
+    /// Read the value stored for `key`. Returns `null`/`nil` if no value is
+    /// stored for `key`.
+    fn read(&self, key: Vec<u8>) -> Option<Vec<u8>>;
+
+    /// Store `value` for `key`, replacing any existing value.
+    fn write(&self, key: Vec<u8>, value: Vec<u8>) -> Result<(), StorageError>;
+
+    /// Delete the value stored for `key`. Deleting a key that doesn't exist is
+    /// not an error.
+    fn delete(&self, key: Vec<u8>) -> Result<(), StorageError>;
+}
+
+/// Errors thrown by a [`StorageProvider`].
+#[derive(Error, Debug, Clone, PartialEq, Eq, uniffi::Error)]
+pub enum StorageError {
+    /// The storage backend failed.
+    #[error("The storage backend failed: {message}")]
+    Backend {
+        /// A description of the failure.
+        message: String,
+    },
+    /// Error serializing or deserializing a value.
+    #[error("Error serializing value.")]
+    SerializationError,
+    /// The storage callback failed unexpectedly, e.g. with an exception that
+    /// is not a `StorageError`.
+    #[error("Unexpected error in the storage provider: {message}")]
+    Unexpected {
+        /// A description of the failure.
+        message: String,
+    },
+}
+
+impl From<uniffi::UnexpectedUniFFICallbackError> for StorageError {
+    fn from(error: uniffi::UnexpectedUniFFICallbackError) -> Self {
+        Self::Unexpected {
+            message: error.reason,
+        }
+    }
+}
+
+/// An in-memory [`StorageProvider`], used by [`Provider::in_memory()`].
+#[derive(Debug, Default)]
+struct MemoryStorage {
+    values: RwLock<HashMap<Vec<u8>, Vec<u8>>>,
+}
+
+impl StorageProvider for MemoryStorage {
+    fn read(&self, key: Vec<u8>) -> Option<Vec<u8>> {
+        // We unwrap here, because the functions claiming a write lock only
+        // hold the lock very briefly and should not panic during that period.
+        self.values.read().unwrap().get(&key).cloned()
+    }
+
+    fn write(&self, key: Vec<u8>, value: Vec<u8>) -> Result<(), StorageError> {
+        self.values.write().unwrap().insert(key, value);
+        Ok(())
+    }
+
+    fn delete(&self, key: Vec<u8>) -> Result<(), StorageError> {
+        self.values.write().unwrap().remove(&key);
+        Ok(())
+    }
+}
+
+/// The key store on top of a [`StorageProvider`]. Values are serialized as
+/// JSON.
+pub struct ForeignKeyStore {
+    storage: Arc<dyn StorageProvider>,
+}
+
+impl std::fmt::Debug for ForeignKeyStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ForeignKeyStore").finish_non_exhaustive()
+    }
+}
+
+impl OpenMlsKeyStore for ForeignKeyStore {
+    /// The error type returned by the [`OpenMlsKeyStore`].
+    type Error = StorageError;
+
+    /// Store a value `v` that implements the [`MlsEntity`] trait for
+    /// serialization for ID `k`.
+    ///
+    /// Returns an error if storing fails.
+    fn store<V: MlsEntity>(&self, k: &[u8], v: &V) -> Result<(), Self::Error> {
+        let value = serde_json::to_vec(v).map_err(|_| StorageError::SerializationError)?;
+        let value = BlobHeader::for_entity::<V>().wrap(&value);
+        self.storage.write(k.to_vec(), value)
+    }
+
+    /// Read and return a value stored for ID `k` that implements the
+    /// [`MlsEntity`] trait for deserialization.
+    ///
+    /// Returns [`None`] if no value is stored for `k` or reading fails.
+    fn read<V: MlsEntity>(&self, k: &[u8]) -> Option<V> {
+        self.storage
+            .read(k.to_vec())
+            .and_then(|value| serde_json::from_slice(unwrap_blob::<V>(&value)?).ok())
+    }
+
+    /// Delete a value stored for ID `k`.
+    ///
+    /// Returns an error if deleting fails.
+    fn delete<V: MlsEntity>(&self, k: &[u8]) -> Result<(), Self::Error> {
+        self.storage.delete(k.to_vec())
+    }
+}
+
+/// The provider, holding the crypto provider and the key store.
+///
+/// It uses the RustCrypto crypto provider and stores all key material and
+/// saved groups in a [`StorageProvider`].
+#[derive(Debug, uniffi::Object)]
+pub struct Provider {
+    crypto: RustCrypto,
+    key_store: ForeignKeyStore,
+}
+
+#[uniffi::export]
+impl Provider {
+    /// Create a provider that stores its state in `storage`.
+    #[uniffi::constructor]
+    pub fn new(storage: Arc<dyn StorageProvider>) -> Arc<Self> {
+        Arc::new(Self {
+            crypto: RustCrypto::default(),
+            key_store: ForeignKeyStore { storage },
+        })
+    }
+
+    /// Create a provider that keeps its state in memory. The state is lost
+    /// when the provider is dropped.
+    #[uniffi::constructor]
+    pub fn in_memory() -> Arc<Self> {
+        Self::new(Arc::<MemoryStorage>::default())
+    }
+
+    /// Delete the key package with the hash reference `reference` (see
+    /// [`NewKeyPackage`](crate::NewKeyPackage)) and its private keys, e.g.
+    /// because it expired before it was used.
+    ///
+    /// Key packages used to join a group are deleted automatically.
+    pub fn delete_key_package(&self, reference: Vec<u8>) -> Result<(), OpenMlsError> {
+        let key_package: KeyPackage = self.key_store.read(&reference).ok_or_else(|| {
+            OpenMlsError::NotFound("No key package with this reference.".to_string())
+        })?;
+        key_package.delete(self)?;
+        Ok(())
+    }
+}
+
+impl OpenMlsProvider for Provider {
+    type CryptoProvider = RustCrypto;
+    type RandProvider = RustCrypto;
+    type KeyStoreProvider = ForeignKeyStore;
+    type ClockProvider = RustCrypto;
+
+    fn crypto(&self) -> &Self::CryptoProvider {
+        &self.crypto
+    }
+
+    fn rand(&self) -> &Self::RandProvider {
+        &self.crypto
+    }
+
+    fn key_store(&self) -> &Self::KeyStoreProvider {
+        &self.key_store
+    }
+
+    fn clock(&self) -> &Self::ClockProvider {
+        &self.crypto
+    }
+}
@@ -0,0 +1,167 @@
+//! Test the core flows through the exported API.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use openmls_uniffi::*;
+
+const CIPHERSUITE: u16 = 1;
+
+/// A storage provider as it would be implemented by an app.
+#[derive(Default)]
+struct TestStorage {
+    values: Mutex<HashMap<Vec<u8>, Vec<u8>>>,
+    fail: Mutex<bool>,
+}
+
+impl StorageProvider for TestStorage {
+    fn read(&self, key: Vec<u8>) -> Option<Vec<u8>> {
+        self.values.lock().unwrap().get(&key).cloned()
+    }
+
+    fn write(&self, key: Vec<u8>, value: Vec<u8>) -> Result<(), StorageError> {
+        if *self.fail.lock().unwrap() {
+            return Err(StorageError::Backend {
+                message: "disk full".to_string(),
+            });
+        }
+        self.values.lock().unwrap().insert(key, value);
+        Ok(())
+    }
+
+    fn delete(&self, key: Vec<u8>) -> Result<(), StorageError> {
+        self.values.lock().unwrap().remove(&key);
+        Ok(())
+    }
+}
+
+#[test]
+fn core_flows() {
+    let alice_provider = Provider::in_memory();
+    let bob_storage = Arc::new(TestStorage::default());
+    let bob_provider = Provider::new(bob_storage.clone());
+    let alice = Identity::new(&alice_provider, CIPHERSUITE, b"Alice".to_vec()).unwrap();
+    let bob = Identity::new(&bob_provider, CIPHERSUITE, b"Bob".to_vec()).unwrap();
+
+    // === Alice creates a group and adds Bob ===
+    let alice_group = Group::new(&alice_provider, &alice, b"Test Group".to_vec()).unwrap();
+    let bob_key_package = bob.create_key_package(&bob_provider).unwrap();
+    let AddMembersResult { welcome, .. } = alice_group
+        .add_members(&alice_provider, &alice, vec![bob_key_package.key_package])
+        .unwrap();
+    alice_group.merge_pending_commit(&alice_provider).unwrap();
+
+    // === Bob joins ===
+    let bob_group = Group::join(&bob_provider, welcome).unwrap();
+    assert_eq!(bob_group.epoch(), 1);
+    assert_eq!(bob_group.group_id(), b"Test Group");
+    let identities: Vec<Vec<u8>> = bob_group
+        .members()
+        .into_iter()
+        .map(|member| member.identity)
+        .collect();
+    assert_eq!(identities, vec![b"Alice".to_vec(), b"Bob".to_vec()]);
+
+    // The key package has been consumed.
+    assert!(matches!(
+        bob_provider.delete_key_package(bob_key_package.reference),
+        Err(OpenMlsError::NotFound(_))
+    ));
+
+    // === Alice sends a message to Bob ===
+    let message = alice_group
+        .encrypt(&alice_provider, &alice, b"Hi".to_vec())
+        .unwrap();
+    assert_eq!(
+        bob_group.process_message(&bob_provider, message).unwrap(),
+        ProcessedMessage::Application {
+            sender: b"Alice".to_vec(),
+            plaintext: b"Hi".to_vec()
+        }
+    );
+
+    // === Bob persists his state and restarts ===
+    bob_group.save(&bob_provider).unwrap();
+    let bob = Identity::from_bytes(bob.to_bytes().unwrap()).unwrap();
+    drop(bob_group);
+    let bob_provider = Provider::new(bob_storage.clone());
+    let bob_group = Group::load(&bob_provider, b"Test Group".to_vec()).unwrap();
+    assert!(matches!(
+        Group::load(&bob_provider, b"Unknown".to_vec()),
+        Err(OpenMlsError::NotFound(_))
+    ));
+
+    // === Bob updates ===
+    let commit = bob_group.self_update(&bob_provider, &bob).unwrap();
+    bob_group.merge_pending_commit(&bob_provider).unwrap();
+    assert_eq!(
+        alice_group
+            .process_message(&alice_provider, commit)
+            .unwrap(),
+        ProcessedMessage::Commit
+    );
+    assert_eq!(
+        alice_group
+            .export_secret(&alice_provider, "label".to_string(), vec![], 32)
+            .unwrap(),
+        bob_group
+            .export_secret(&bob_provider, "label".to_string(), vec![], 32)
+            .unwrap()
+    );
+
+    // === Storage errors are passed through ===
+    *bob_storage.fail.lock().unwrap() = true;
+    assert!(matches!(
+        bob_group.save(&bob_provider),
+        Err(OpenMlsError::StorageError(_))
+    ));
+    *bob_storage.fail.lock().unwrap() = false;
+
+    // === Alice removes Bob ===
+    let commit = alice_group
+        .remove_members(&alice_provider, &alice, vec![bob_group.own_leaf_index()])
+        .unwrap();
+    alice_group.merge_pending_commit(&alice_provider).unwrap();
+    assert_eq!(
+        bob_group.process_message(&bob_provider, commit).unwrap(),
+        ProcessedMessage::Commit
+    );
+    assert!(!bob_group.is_active());
+    assert_eq!(alice_group.epoch(), 3);
+}
+
+#[test]
+fn errors() {
+    let provider = Provider::in_memory();
+
+    // Unknown ciphersuites
+    assert!(matches!(
+        Identity::new(&provider, 0xffff, b"Alice".to_vec()),
+        Err(OpenMlsError::InvalidArgument(_))
+    ));
+
+    // Malformed messages
+    assert!(matches!(
+        Group::join(&provider, vec![1, 2, 3]),
+        Err(OpenMlsError::DecodingError(_))
+    ));
+
+    // A key package is not a welcome
+    let alice = Identity::new(&provider, CIPHERSUITE, b"Alice".to_vec()).unwrap();
+    let key_package = alice.create_key_package(&provider).unwrap();
+    assert!(matches!(
+        Group::join(&provider, key_package.key_package),
+        Err(OpenMlsError::InvalidArgument(_))
+    ));
+
+    // Unused key packages can be deleted
+    provider
+        .delete_key_package(key_package.reference.clone())
+        .unwrap();
+    assert!(matches!(
+        provider.delete_key_package(key_package.reference),
+        Err(OpenMlsError::NotFound(_))
+    ));
+}
@@ -0,0 +1,287 @@
+//! Errors of the bindings.
+
+use std::convert::Infallible;
+
+use openmls::{error::LibraryError, framing::errors::MlsMessageError, prelude::*};
+use thiserror::Error;
+use wasm_bindgen::prelude::*;
+
+use crate::StorageError;
+
+#[wasm_bindgen(typescript_custom_section)]
+const OPEN_MLS_ERROR_NAME: &'static str = r#"
+/**
+ * The `name` of the errors thrown by OpenMLS. The `message` describes the
+ * underlying error.
+ */
+export type OpenMlsErrorName =
+  | "InvalidArgumentError"
+  | "DecodingError"
+  | "EncodingError"
+  | "NotFoundError"
+  | "InvalidMessageError"
+  | "GroupStateError"
+  | "StorageError"
+  | "CryptoError"
+  | "LibraryError";
+"#;
+
+/// The error thrown by the bindings. It is thrown to JavaScript as an `Error`
+/// whose `name` is the name of the variant (see [`OpenMlsError::name()`]).
+///
+/// Each variant carries a description of the underlying error.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum OpenMlsError {
+    /// An argument is invalid, e.g. an unsupported ciphersuite or an unknown
+    /// member.
+    #[error("Invalid argument: {0}")]
+    InvalidArgument(String),
+    /// The input bytes could not be decoded.
+    #[error("Decoding error: {0}")]
+    DecodingError(String),
+    /// A value could not be encoded.
+    #[error("Encoding error: {0}")]
+    EncodingError(String),
+    /// The requested value was not found.
+    #[error("Not found: {0}")]
+    NotFound(String),
+    /// A message, key package or welcome was rejected by the validation.
+    #[error("Invalid message: {0}")]
+    InvalidMessage(String),
+    /// The operation is not possible in the current state of the group, e.g.
+    /// because a commit is pending or the group is inactive.
+    #[error("Group state error: {0}")]
+    GroupStateError(String),
+    /// The `Storage` returned an error.
+    #[error("Storage error: {0}")]
+    StorageError(String),
+    /// A cryptographic operation failed.
+    #[error("Crypto error: {0}")]
+    CryptoError(String),
+    /// An internal error occurred. This is a bug in OpenMLS.
+    #[error("Library error: {0}")]
+    LibraryError(String),
+}
+
+impl OpenMlsError {
+    /// The `name` of the JavaScript `Error`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::InvalidArgument(_) => "InvalidArgumentError",
+            Self::DecodingError(_) => "DecodingError",
+            Self::EncodingError(_) => "EncodingError",
+            Self::NotFound(_) => "NotFoundError",
+            Self::InvalidMessage(_) => "InvalidMessageError",
+            Self::GroupStateError(_) => "GroupStateError",
+            Self::StorageError(_) => "StorageError",
+            Self::CryptoError(_) => "CryptoError",
+            Self::LibraryError(_) => "LibraryError",
+        }
+    }
+}
+
+impl From<OpenMlsError> for JsValue {
+    fn from(error: OpenMlsError) -> Self {
+        let js_error = js_sys::Error::new(&error.to_string());
+        js_error.set_name(error.name());
+        js_error.into()
+    }
+}
+
+impl From<Infallible> for OpenMlsError {
+    fn from(error: Infallible) -> Self {
+        match error {}
+    }
+}
+
+impl From<LibraryError> for OpenMlsError {
+    fn from(error: LibraryError) -> Self {
+        Self::LibraryError(error.to_string())
+    }
+}
+
+impl From<StorageError> for OpenMlsError {
+    fn from(error: StorageError) -> Self {
+        Self::StorageError(error.to_string())
+    }
+}
+
+impl From<KeyStoreCacheError> for OpenMlsError {
+    fn from(error: KeyStoreCacheError) -> Self {
+        Self::EncodingError(error.to_string())
+    }
+}
+
+impl<E: Into<OpenMlsError>> From<AsyncGroupError<E, StorageError>> for OpenMlsError {
+    fn from(error: AsyncGroupError<E, StorageError>) -> Self {
+        match error {
+            AsyncGroupError::LibraryError(error) => error.into(),
+            AsyncGroupError::Operation(error) => error.into(),
+            AsyncGroupError::KeyStoreError(error) => error.into(),
+        }
+    }
+}
+
+impl From<tls_codec::Error> for OpenMlsError {
+    fn from(error: tls_codec::Error) -> Self {
+        Self::DecodingError(error.to_string())
+    }
+}
+
+impl From<MlsMessageError> for OpenMlsError {
+    fn from(error: MlsMessageError) -> Self {
+        Self::EncodingError(error.to_string())
+    }
+}
+
+impl From<MlsGroupStateError> for OpenMlsError {
+    fn from(error: MlsGroupStateError) -> Self {
+        match error {
+            MlsGroupStateError::LibraryError(error) => error.into(),
+            _ => Self::GroupStateError(error.to_string()),
+        }
+    }
+}
+
+impl From<KeyPackageVerifyError> for OpenMlsError {
+    fn from(error: KeyPackageVerifyError) -> Self {
+        match error {
+            KeyPackageVerifyError::LibraryError(error) => error.into(),
+            _ => Self::InvalidMessage(error.to_string()),
+        }
+    }
+}
+
+impl From<KeyPackageNewError<KeyStoreCacheError>> for OpenMlsError {
+    fn from(error: KeyPackageNewError<KeyStoreCacheError>) -> Self {
+        match error {
+            KeyPackageNewError::LibraryError(error) => error.into(),
+            KeyPackageNewError::KeyStoreError(error) => error.into(),
+            KeyPackageNewError::SignatureError(_) => Self::CryptoError(error.to_string()),
+            KeyPackageNewError::CiphersuiteSignatureSchemeMismatch => {
+                Self::InvalidArgument(error.to_string())
+            }
+        }
+    }
+}
+
+impl From<NewGroupError<KeyStoreCacheError>> for OpenMlsError {
+    fn from(error: NewGroupError<KeyStoreCacheError>) -> Self {
+        match error {
+            NewGroupError::LibraryError(error) => error.into(),
+            NewGroupError::KeyStoreError(error) => error.into(),
+            _ => Self::InvalidArgument(error.to_string()),
+        }
+    }
+}
+
+impl From<WelcomeError<KeyStoreCacheError>> for OpenMlsError {
+    fn from(error: WelcomeError<KeyStoreCacheError>) -> Self {
+        match error {
+            WelcomeError::LibraryError(error) => error.into(),
+            WelcomeError::KeyStoreError(error) => error.into(),
+            // The welcome is not for any of our key packages.
+            WelcomeError::NoMatchingKeyPackage | WelcomeError::NoMatchingEncryptionKey => {
+                Self::NotFound(error.to_string())
+            }
+            _ => Self::InvalidMessage(error.to_string()),
+        }
+    }
+}
+
+impl From<CreateCommitError<KeyStoreCacheError>> for OpenMlsError {
+    fn from(error: CreateCommitError<KeyStoreCacheError>) -> Self {
+        match error {
+            CreateCommitError::LibraryError(error) => error.into(),
+            CreateCommitError::KeyStoreError(error) => error.into(),
+            CreateCommitError::KeyPackageGenerationError(error) => error.into(),
+            CreateCommitError::SignatureError(_) => Self::CryptoError(error.to_string()),
+            _ => Self::InvalidArgument(error.to_string()),
+        }
+    }
+}
+
+impl From<AddMembersError<KeyStoreCacheError>> for OpenMlsError {
+    fn from(error: AddMembersError<KeyStoreCacheError>) -> Self {
+        match error {
+            AddMembersError::LibraryError(error) => error.into(),
+            AddMembersError::EmptyInput(_) => Self::InvalidArgument(error.to_string()),
+            AddMembersError::CreateCommitError(error) => error.into(),
+            AddMembersError::GroupStateError(error) => error.into(),
+        }
+    }
+}
+
+impl From<RemoveMembersError<KeyStoreCacheError>> for OpenMlsError {
+    fn from(error: RemoveMembersError<KeyStoreCacheError>) -> Self {
+        match error {
+            RemoveMembersError::LibraryError(error) => error.into(),
+            RemoveMembersError::EmptyInput(_) | RemoveMembersError::UnknownMember => {
+                Self::InvalidArgument(error.to_string())
+            }
+            RemoveMembersError::CreateCommitError(error) => error.into(),
+            RemoveMembersError::GroupStateError(error) => error.into(),
+        }
+    }
+}
+
+impl From<SelfUpdateError<KeyStoreCacheError>> for OpenMlsError {
+    fn from(error: SelfUpdateError<KeyStoreCacheError>) -> Self {
+        match error {
+            SelfUpdateError::LibraryError(error) => error.into(),
+            SelfUpdateError::CreateCommitError(error) => error.into(),
+            SelfUpdateError::GroupStateError(error) => error.into(),
+            SelfUpdateError::KeyStoreError => Self::StorageError(error.to_string()),
+        }
+    }
+}
+
+impl From<MergeCommitError<KeyStoreCacheError>> for OpenMlsError {
+    fn from(error: MergeCommitError<KeyStoreCacheError>) -> Self {
+        match error {
+            MergeCommitError::LibraryError(error) => error.into(),
+            MergeCommitError::KeyStoreError(error) => error.into(),
+            MergeCommitError::GroupStateError(error) => error.into(),
+        }
+    }
+}
+
+impl From<MergePendingCommitError<KeyStoreCacheError>> for OpenMlsError {
+    fn from(error: MergePendingCommitError<KeyStoreCacheError>) -> Self {
+        match error {
+            MergePendingCommitError::MlsGroupStateError(error) => error.into(),
+            MergePendingCommitError::MergeCommitError(error) => error.into(),
+        }
+    }
+}
+
+impl From<CreateMessageError> for OpenMlsError {
+    fn from(error: CreateMessageError) -> Self {
+        match error {
+            CreateMessageError::LibraryError(error) => error.into(),
+            CreateMessageError::GroupStateError(error) => error.into(),
+        }
+    }
+}
+
+impl From<ProcessMessageError> for OpenMlsError {
+    fn from(error: ProcessMessageError) -> Self {
+        match error {
+            ProcessMessageError::LibraryError(error) => error.into(),
+            ProcessMessageError::GroupStateError(error) => error.into(),
+            _ => Self::InvalidMessage(error.to_string()),
+        }
+    }
+}
+
+impl From<ExportSecretError> for OpenMlsError {
+    fn from(error: ExportSecretError) -> Self {
+        match error {
+            ExportSecretError::LibraryError(error) => error.into(),
+            ExportSecretError::KeyLengthTooLong | ExportSecretError::ReservedLabel => {
+                Self::InvalidArgument(error.to_string())
+            }
+            ExportSecretError::GroupStateError(error) => error.into(),
+        }
+    }
+}
@@ -0,0 +1,445 @@
+//! Groups.
+
+use std::{
+    cell::RefCell,
+    ops::{Deref, DerefMut},
+    rc::Rc,
+};
+
+use js_sys::{Array, Promise, Uint8Array};
+use openmls::prelude::{config::CryptoConfig, *};
+use tls_codec::Deserialize;
+use wasm_bindgen::{prelude::*, JsCast};
+
+use crate::{promise, uint8_array, Identity, OpenMlsError, Provider};
+
+/// A member of a group.
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Debug, Clone)]
+pub struct Member {
+    /// The leaf index of the member.
+    #[wasm_bindgen(js_name = leafIndex)]
+    pub leaf_index: u32,
+    /// The identity of the member's credential.
+    pub identity: Vec<u8>,
+    /// The public signature key of the member.
+    #[wasm_bindgen(js_name = signatureKey)]
+    pub signature_key: Vec<u8>,
+}
+
+/// The result of `MlsGroup.addMembers()`.
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Debug, Clone)]
+pub struct AddMembersResult {
+    /// The commit for the existing members.
+    pub commit: Vec<u8>,
+    /// The welcome for the new members.
+    pub welcome: Vec<u8>,
+}
+
+/// The kind of a processed message.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageKind {
+    /// An application message.
+    Application = "application",
+    /// A proposal, which is stored until it is committed.
+    Proposal = "proposal",
+    /// A commit, which has been merged.
+    Commit = "commit",
+}
+
+/// A message processed with `MlsGroup.processMessage()`.
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Debug, Clone)]
+pub struct ProcessedMessage {
+    /// The kind of the message.
+    pub kind: MessageKind,
+    /// The identity of the sender's credential.
+    pub sender: Vec<u8>,
+    /// The plaintext of an application message, `undefined` otherwise.
+    pub plaintext: Option<Vec<u8>>,
+}
+
+/// The configuration of groups created through the bindings. The ratchet tree
+/// is included in welcome messages, so that they can be processed on their
+/// own.
+fn group_config(ciphersuite: Option<Ciphersuite>) -> MlsGroupConfig {
+    let builder = MlsGroupConfig::builder().use_ratchet_tree_extension(true);
+    match ciphersuite {
+        Some(ciphersuite) => builder
+            .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+            .build(),
+        None => builder.build(),
+    }
+}
+
+/// Decode an `MLSMessage`.
+fn decode_message(message: &[u8]) -> Result<MlsMessageInBody, OpenMlsError> {
+    Ok(MlsMessageIn::tls_deserialize_exact(message)?.extract())
+}
+
+/// Encode an outgoing `MLSMessage` as `Uint8Array`.
+fn encode_message(message: MlsMessageOut) -> Result<JsValue, OpenMlsError> {
+    Ok(uint8_array(&message.to_bytes()?))
+}
+
+/// The group of a [`Group`] while an operation is running. The group is
+/// returned to the handle when the guard is dropped.
+struct GroupGuard {
+    handle: Rc<RefCell<Option<AsyncMlsGroup>>>,
+    group: Option<AsyncMlsGroup>,
+}
+
+impl Deref for GroupGuard {
+    type Target = AsyncMlsGroup;
+
+    fn deref(&self) -> &Self::Target {
+        // The group is only taken when the guard is dropped.
+        self.group.as_ref().unwrap()
+    }
+}
+
+impl DerefMut for GroupGuard {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.group.as_mut().unwrap()
+    }
+}
+
+impl Drop for GroupGuard {
+    fn drop(&mut self) {
+        *self.handle.borrow_mut() = self.group.take();
+    }
+}
+
+/// An MLS group.
+///
+/// Operations that access the storage are asynchronous. Only one operation
+/// can run on a group at a time; other calls throw a `GroupStateError` until
+/// the running operation completes.
+#[wasm_bindgen(js_name = MlsGroup)]
+#[derive(Debug, Clone)]
+pub struct Group {
+    group: Rc<RefCell<Option<AsyncMlsGroup>>>,
+}
+
+impl From<AsyncMlsGroup> for Group {
+    fn from(group: AsyncMlsGroup) -> Self {
+        Self {
+            group: Rc::new(RefCell::new(Some(group))),
+        }
+    }
+}
+
+impl Group {
+    /// Take the group out of the handle until the returned guard is dropped.
+    ///
+    /// Returns an error if another operation is running on the group.
+    fn lock(&self) -> Result<GroupGuard, OpenMlsError> {
+        let group = self.group.borrow_mut().take().ok_or_else(|| {
+            OpenMlsError::GroupStateError("Another operation is running on the group.".to_string())
+        })?;
+        Ok(GroupGuard {
+            handle: self.group.clone(),
+            group: Some(group),
+        })
+    }
+}
+
+#[wasm_bindgen(js_class = MlsGroup)]
+impl Group {
+    /// Create a new group with the ID `groupId` and `identity` as the only
+    /// member.
+    #[wasm_bindgen(unchecked_return_type = "Promise<MlsGroup>")]
+    pub fn create(provider: &Provider, identity: &Identity, group_id: &[u8]) -> Promise {
+        let provider = provider.provider.clone();
+        let identity = identity.identity.clone();
+        let group_id = GroupId::from_slice(group_id);
+        promise(async move {
+            let group = AsyncMlsGroup::new_with_group_id(
+                provider.as_ref(),
+                &identity.signer,
+                &group_config(Some(identity.ciphersuite)),
+                group_id,
+                identity.credential_with_key.clone(),
+            )
+            .await?;
+            Ok(Group::from(group).into())
+        })
+    }
+
+    /// Join a group with the `welcome` message. The welcome must be for a key
+    /// package created with the same storage.
+    #[wasm_bindgen(unchecked_return_type = "Promise<MlsGroup>")]
+    pub fn join(provider: &Provider, welcome: &[u8]) -> Promise {
+        let provider = provider.provider.clone();
+        let welcome = decode_message(welcome);
+        promise(async move {
+            let welcome = match welcome? {
+                MlsMessageInBody::Welcome(welcome) => welcome,
+                _ => {
+                    return Err(OpenMlsError::InvalidArgument(
+                        "The message is not a welcome.".to_string(),
+                    ))
+                }
+            };
+            // The ciphersuite is taken from the welcome.
+            let group = AsyncMlsGroup::new_from_welcome(
+                provider.as_ref(),
+                &group_config(None),
+                welcome,
+                None,
+            )
+            .await?;
+            Ok(Group::from(group).into())
+        })
+    }
+
+    /// Load the group with the ID `groupId`, which has been saved with
+    /// `save()`.
+    #[wasm_bindgen(unchecked_return_type = "Promise<MlsGroup>")]
+    pub fn load(provider: &Provider, group_id: &[u8]) -> Promise {
+        let provider = provider.provider.clone();
+        let group_id = GroupId::from_slice(group_id);
+        promise(async move {
+            let group = AsyncMlsGroup::load(provider.as_ref(), &group_id)
+                .await?
+                .ok_or_else(|| OpenMlsError::NotFound("No group with this ID.".to_string()))?;
+            Ok(Group::from(group).into())
+        })
+    }
+
+    /// Save the group in the storage of `provider`.
+    #[wasm_bindgen(unchecked_return_type = "Promise<void>")]
+    pub fn save(&self, provider: &Provider) -> Promise {
+        let provider = provider.provider.clone();
+        let group = self.lock();
+        promise(async move {
+            group?.save(provider.as_ref()).await?;
+            Ok(JsValue::UNDEFINED)
+        })
+    }
+
+    /// The ID of the group.
+    #[wasm_bindgen(getter, js_name = groupId)]
+    pub fn group_id(&self) -> Result<Vec<u8>, OpenMlsError> {
+        Ok(self.lock()?.group_id().as_slice().to_vec())
+    }
+
+    /// The current epoch.
+    #[wasm_bindgen(getter)]
+    pub fn epoch(&self) -> Result<u64, OpenMlsError> {
+        Ok(self.lock()?.epoch().as_u64())
+    }
+
+    /// The leaf index of the own member.
+    #[wasm_bindgen(getter, js_name = ownLeafIndex)]
+    pub fn own_leaf_index(&self) -> Result<u32, OpenMlsError> {
+        Ok(self.lock()?.own_leaf_index().u32())
+    }
+
+    /// Whether the own member is still part of the group.
+    #[wasm_bindgen(getter, js_name = isActive)]
+    pub fn is_active(&self) -> Result<bool, OpenMlsError> {
+        Ok(self.lock()?.is_active())
+    }
+
+    /// The members of the group.
+    pub fn members(&self) -> Result<Vec<Member>, OpenMlsError> {
+        Ok(self
+            .lock()?
+            .members()
+            .map(|member| Member {
+                leaf_index: member.index.u32(),
+                identity: member.credential.identity().to_vec(),
+                signature_key: member.signature_key,
+            })
+            .collect())
+    }
+
+    /// Add the members with the given `keyPackages` to the group and return
+    /// the commit and the welcome.
+    ///
+    /// The commit is pending until it is merged with `mergePendingCommit()`.
+    #[wasm_bindgen(js_name = addMembers, unchecked_return_type = "Promise<AddMembersResult>")]
+    pub fn add_members(
+        &self,
+        provider: &Provider,
+        identity: &Identity,
+        #[wasm_bindgen(js_name = keyPackages, unchecked_param_type = "Uint8Array[]")]
+        key_packages: Array,
+    ) -> Promise {
+        let provider = provider.provider.clone();
+        let identity = identity.identity.clone();
+        let group = self.lock();
+        promise(async move {
+            let mut group = group?;
+            let key_packages = key_packages
+                .iter()
+                .map(|key_package| {
+                    let key_package = key_package.dyn_into::<Uint8Array>().map_err(|_| {
+                        OpenMlsError::InvalidArgument(
+                            "The key packages must be Uint8Arrays.".to_string(),
+                        )
+                    })?;
+                    match decode_message(&key_package.to_vec())? {
+                        MlsMessageInBody::KeyPackage(key_package) => Ok(key_package.validate(
+                            provider.crypto(),
+                            ProtocolVersion::Mls10,
+                            provider.clock(),
+                        )?),
+                        _ => Err(OpenMlsError::InvalidArgument(
+                            "The message is not a key package.".to_string(),
+                        )),
+                    }
+                })
+                .collect::<Result<Vec<_>, OpenMlsError>>()?;
+            let (commit, welcome, _group_info) = group
+                .add_members(provider.as_ref(), &identity.signer, &key_packages)
+                .await?
+                .into_parts();
+            Ok(AddMembersResult {
+                commit: commit.to_bytes()?,
+                welcome: welcome.to_bytes()?,
+            }
+            .into())
+        })
+    }
+
+    /// Remove the members at `leafIndices` from the group and return the
+    /// commit.
+    ///
+    /// The commit is pending until it is merged with `mergePendingCommit()`.
+    #[wasm_bindgen(js_name = removeMembers, unchecked_return_type = "Promise<Uint8Array>")]
+    pub fn remove_members(
+        &self,
+        provider: &Provider,
+        identity: &Identity,
+        #[wasm_bindgen(js_name = leafIndices)] leaf_indices: Vec<u32>,
+    ) -> Promise {
+        let provider = provider.provider.clone();
+        let identity = identity.identity.clone();
+        let group = self.lock();
+        let leaf_indices: Vec<LeafNodeIndex> =
+            leaf_indices.into_iter().map(LeafNodeIndex::new).collect();
+        promise(async move {
+            let (commit, _welcome, _group_info) = group?
+                .remove_members(provider.as_ref(), &identity.signer, &leaf_indices)
+                .await?
+                .into_parts();
+            encode_message(commit)
+        })
+    }
+
+    /// Update the own leaf and return the commit.
+    ///
+    /// The commit is pending until it is merged with `mergePendingCommit()`.
+    #[wasm_bindgen(js_name = selfUpdate, unchecked_return_type = "Promise<Uint8Array>")]
+    pub fn self_update(&self, provider: &Provider, identity: &Identity) -> Promise {
+        let provider = provider.provider.clone();
+        let identity = identity.identity.clone();
+        let group = self.lock();
+        promise(async move {
+            let (commit, _welcome, _group_info) = group?
+                .self_update(provider.as_ref(), &identity.signer)
+                .await?
+                .into_parts();
+            encode_message(commit)
+        })
+    }
+
+    /// Merge the pending commit.
+    #[wasm_bindgen(js_name = mergePendingCommit, unchecked_return_type = "Promise<void>")]
+    pub fn merge_pending_commit(&self, provider: &Provider) -> Promise {
+        let provider = provider.provider.clone();
+        let group = self.lock();
+        promise(async move {
+            group?.merge_pending_commit(provider.as_ref()).await?;
+            Ok(JsValue::UNDEFINED)
+        })
+    }
+
+    /// Discard the pending commit.
+    #[wasm_bindgen(js_name = clearPendingCommit)]
+    pub fn clear_pending_commit(&self) -> Result<(), OpenMlsError> {
+        self.lock()?.clear_pending_commit();
+        Ok(())
+    }
+
+    /// Encrypt the application message `plaintext`.
+    #[wasm_bindgen(unchecked_return_type = "Promise<Uint8Array>")]
+    pub fn encrypt(&self, provider: &Provider, identity: &Identity, plaintext: &[u8]) -> Promise {
+        let provider = provider.provider.clone();
+        let identity = identity.identity.clone();
+        let group = self.lock();
+        let plaintext = plaintext.to_vec();
+        promise(async move {
+            let message = group?
+                .create_message(provider.as_ref(), &identity.signer, &plaintext)
+                .await?;
+            encode_message(message)
+        })
+    }
+
+    /// Process the `MLSMessage` `message`. Application messages are decrypted,
+    /// proposals are stored and commits are merged.
+    #[wasm_bindgen(js_name = processMessage, unchecked_return_type = "Promise<ProcessedMessage>")]
+    pub fn process_message(&self, provider: &Provider, message: &[u8]) -> Promise {
+        let provider = provider.provider.clone();
+        let group = self.lock();
+        let message = decode_message(message);
+        promise(async move {
+            let mut group = group?;
+            let message = match message? {
+                MlsMessageInBody::PublicMessage(message) => ProtocolMessage::from(message),
+                MlsMessageInBody::PrivateMessage(message) => ProtocolMessage::from(message),
+                _ => {
+                    return Err(OpenMlsError::InvalidArgument(
+                        "The message is not a protocol message.".to_string(),
+                    ))
+                }
+            };
+            let processed_message = group.process_message(provider.as_ref(), message).await?;
+            let sender = processed_message.credential().identity().to_vec();
+            let (kind, plaintext) = match processed_message.into_content() {
+                ProcessedMessageContent::ApplicationMessage(message) => {
+                    (MessageKind::Application, Some(message.into_bytes()))
+                }
+                ProcessedMessageContent::ProposalMessage(proposal) => {
+                    group.store_pending_proposal(*proposal);
+                    (MessageKind::Proposal, None)
+                }
+                ProcessedMessageContent::ExternalJoinProposalMessage(proposal) => {
+                    group.store_pending_proposal(*proposal);
+                    (MessageKind::Proposal, None)
+                }
+                ProcessedMessageContent::StagedCommitMessage(staged_commit) => {
+                    group
+                        .merge_staged_commit(provider.as_ref(), *staged_commit)
+                        .await?;
+                    (MessageKind::Commit, None)
+                }
+            };
+            Ok(ProcessedMessage {
+                kind,
+                sender,
+                plaintext,
+            }
+            .into())
+        })
+    }
+
+    /// Export a secret of `length` bytes from the current epoch.
+    #[wasm_bindgen(js_name = exportSecret)]
+    pub fn export_secret(
+        &self,
+        provider: &Provider,
+        label: &str,
+        context: &[u8],
+        length: usize,
+    ) -> Result<Vec<u8>, OpenMlsError> {
+        Ok(self
+            .lock()?
+            .export_secret(provider.provider.crypto(), label, context, length)?)
+    }
+}
@@ -0,0 +1,206 @@
+//! Identities and key packages.
+
+use std::{cell::RefCell, rc::Rc};
+
+use js_sys::Promise;
+use openmls::prelude::{config::CryptoConfig, *};
+use openmls_basic_credential::SignatureKeyPair;
+use openmls_rust_crypto::RustCrypto;
+use openmls_traits::{
+    crypto::OpenMlsCrypto,
+    key_store::{BlobHeader, MlsEntity, OpenMlsKeyStore},
+    OpenMlsProvider,
+};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use crate::{promise, uint8_array, OpenMlsError, Provider};
+
+/// A synchronous key store that records the values written by an operation,
+/// so that they can be written to the `Storage` afterwards.
+#[derive(Debug, Default)]
+struct WriteBuffer {
+    values: RefCell<Vec<(Vec<u8>, Vec<u8>)>>,
+}
+
+impl OpenMlsKeyStore for WriteBuffer {
+    type Error = KeyStoreCacheError;
+
+    fn store<V: MlsEntity>(&self, k: &[u8], v: &V) -> Result<(), Self::Error> {
+        // Values are serialized in the same way as by the `AsyncMlsGroup`.
+        let value = serde_json::to_vec(v)
+            .map_err(|e| KeyStoreCacheError::SerializationError(e.to_string()))?;
+        let value = BlobHeader::for_entity::<V>().wrap(&value);
+        self.values.borrow_mut().push((k.to_vec(), value));
+        Ok(())
+    }
+
+    fn read<V: MlsEntity>(&self, _k: &[u8]) -> Option<V> {
+        None
+    }
+
+    fn delete<V: MlsEntity>(&self, k: &[u8]) -> Result<(), Self::Error> {
+        self.values.borrow_mut().retain(|(key, _)| key != k);
+        Ok(())
+    }
+}
+
+/// The synchronous provider that key packages are created with.
+struct KeyPackageProvider<'a> {
+    crypto: &'a RustCrypto,
+    key_store: WriteBuffer,
+}
+
+impl OpenMlsProvider for KeyPackageProvider<'_> {
+    type CryptoProvider = RustCrypto;
+    type RandProvider = RustCrypto;
+    type KeyStoreProvider = WriteBuffer;
+    type ClockProvider = SystemClock;
+
+    fn crypto(&self) -> &Self::CryptoProvider {
+        self.crypto
+    }
+
+    fn rand(&self) -> &Self::RandProvider {
+        self.crypto
+    }
+
+    fn key_store(&self) -> &Self::KeyStoreProvider {
+        &self.key_store
+    }
+
+    fn clock(&self) -> &Self::ClockProvider {
+        &SystemClock
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct IdentityInner {
+    pub(crate) ciphersuite: Ciphersuite,
+    pub(crate) credential_with_key: CredentialWithKey,
+    pub(crate) signer: SignatureKeyPair,
+}
+
+/// A basic credential with its signature key pair.
+///
+/// The identity is bound to a ciphersuite, which is used for its key packages
+/// and the groups it creates.
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct Identity {
+    // Operations hold on to the identity until they complete.
+    pub(crate) identity: Rc<IdentityInner>,
+}
+
+#[wasm_bindgen]
+impl Identity {
+    /// Create a new identity with a basic credential for `identity`.
+    /// `ciphersuite` is the IANA value of the ciphersuite and defaults to
+    /// `MLS_128_DHKEMX25519_AES128GCM_SHA256_Ed25519`.
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        provider: &Provider,
+        identity: &[u8],
+        ciphersuite: Option<u16>,
+    ) -> Result<Identity, OpenMlsError> {
+        let ciphersuite = Ciphersuite::try_from(ciphersuite.unwrap_or(1))
+            .map_err(|e| OpenMlsError::InvalidArgument(e.to_string()))?;
+        if !provider
+            .provider
+            .crypto()
+            .supported_ciphersuites()
+            .contains(&ciphersuite)
+        {
+            return Err(OpenMlsError::InvalidArgument(format!(
+                "Unsupported ciphersuite {ciphersuite}."
+            )));
+        }
+
+        let credential = Credential::new(identity.to_vec(), CredentialType::Basic)
+            .map_err(|e| OpenMlsError::InvalidArgument(e.to_string()))?;
+        let signer = SignatureKeyPair::new(ciphersuite.signature_algorithm())
+            .map_err(|e| OpenMlsError::CryptoError(format!("{e:?}")))?;
+        let credential_with_key = CredentialWithKey {
+            credential,
+            signature_key: signer.to_public_vec().into(),
+        };
+
+        Ok(Self {
+            identity: Rc::new(IdentityInner {
+                ciphersuite,
+                credential_with_key,
+                signer,
+            }),
+        })
+    }
+
+    /// Restore an identity exported with `toBytes()`.
+    #[wasm_bindgen(js_name = fromBytes)]
+    pub fn from_bytes(identity: &[u8]) -> Result<Identity, OpenMlsError> {
+        let identity = serde_json::from_slice(identity)
+            .map_err(|e| OpenMlsError::DecodingError(e.to_string()))?;
+        Ok(Self {
+            identity: Rc::new(identity),
+        })
+    }
+
+    /// Export the identity, including its private signature key. The app has
+    /// to store it securely.
+    #[wasm_bindgen(js_name = toBytes)]
+    pub fn to_bytes(&self) -> Result<Vec<u8>, OpenMlsError> {
+        serde_json::to_vec(self.identity.as_ref())
+            .map_err(|e| OpenMlsError::EncodingError(e.to_string()))
+    }
+
+    /// The identity of the credential.
+    #[wasm_bindgen(getter)]
+    pub fn identity(&self) -> Vec<u8> {
+        self.identity
+            .credential_with_key
+            .credential
+            .identity()
+            .to_vec()
+    }
+
+    /// The public signature key.
+    #[wasm_bindgen(getter, js_name = signatureKey)]
+    pub fn signature_key(&self) -> Vec<u8> {
+        self.identity
+            .credential_with_key
+            .signature_key
+            .as_slice()
+            .to_vec()
+    }
+
+    /// The IANA value of the ciphersuite.
+    #[wasm_bindgen(getter)]
+    pub fn ciphersuite(&self) -> u16 {
+        self.identity.ciphersuite.into()
+    }
+
+    /// Create a new key package and return it as `MLSMessage`. Its private
+    /// keys are written to the storage of `provider`.
+    #[wasm_bindgen(js_name = keyPackage, unchecked_return_type = "Promise<Uint8Array>")]
+    pub fn key_package(&self, provider: &Provider) -> Promise {
+        let identity = self.identity.clone();
+        let provider = provider.provider.clone();
+        promise(async move {
+            let key_package_provider = KeyPackageProvider {
+                crypto: provider.crypto(),
+                key_store: WriteBuffer::default(),
+            };
+            let key_package = KeyPackage::builder().build(
+                CryptoConfig::with_default_version(identity.ciphersuite),
+                &key_package_provider,
+                &identity.signer,
+                identity.credential_with_key.clone(),
+            )?;
+            let key_package = MlsMessageOut::from(key_package).to_bytes()?;
+
+            for (k, v) in key_package_provider.key_store.values.into_inner() {
+                provider.key_store().store(&k, &v).await?;
+            }
+            Ok(uint8_array(&key_package))
+        })
+    }
+}
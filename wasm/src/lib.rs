@@ -0,0 +1,50 @@
+//! # OpenMLS JavaScript bindings
+//!
+//! A [wasm-bindgen](https://rustwasm.github.io/docs/wasm-bindgen/) API for
+//! web clients, published to npm with
+//! [wasm-pack](https://rustwasm.github.io/docs/wasm-pack/). TypeScript
+//! definitions are generated along with the JavaScript glue code.
+//!
+//! The module exposes three classes:
+//!
+//! * `Provider`: the crypto provider and the key store on top of a `Storage`
+//!   implemented in JavaScript, e.g. over IndexedDB.
+//! * `Identity`: a basic credential with its signature key pair and
+//!   ciphersuite. It creates key packages.
+//! * `MlsGroup`: an MLS group, backed by the [`AsyncMlsGroup`] of OpenMLS.
+//!
+//! MLS messages (key packages, commits, welcomes, application messages) are
+//! exchanged as TLS encoded `MLSMessage`s in `Uint8Array`s. All operations
+//! that access the storage return a `Promise`. Errors are thrown as `Error`s
+//! whose `name` is one of `OpenMlsErrorName`.
+//!
+//! This crate is only available on `wasm32-unknown-unknown`.
+//!
+//! [`AsyncMlsGroup`]: openmls::prelude::AsyncMlsGroup
+#![cfg(target_arch = "wasm32")]
+
+use std::future::Future;
+
+use js_sys::{Promise, Uint8Array};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::future_to_promise;
+
+mod error;
+mod group;
+mod identity;
+mod provider;
+
+pub use error::*;
+pub use group::*;
+pub use identity::*;
+pub use provider::*;
+
+/// Run `future` as a JavaScript `Promise`. Errors reject the promise.
+fn promise(future: impl Future<Output = Result<JsValue, OpenMlsError>> + 'static) -> Promise {
+    future_to_promise(async move { future.await.map_err(JsValue::from) })
+}
+
+/// Copy `bytes` into a new `Uint8Array`.
+fn uint8_array(bytes: &[u8]) -> JsValue {
+    Uint8Array::from(bytes).into()
+}
@@ -0,0 +1,178 @@
+//! The provider and the storage callbacks.
+
+use std::rc::Rc;
+
+use async_trait::async_trait;
+use js_sys::{Promise, Uint8Array};
+use openmls::prelude::{AsyncOpenMlsKeyStore, AsyncOpenMlsProvider, SystemClock};
+use openmls_rust_crypto::RustCrypto;
+use thiserror::Error;
+use wasm_bindgen::{prelude::*, JsCast};
+use wasm_bindgen_futures::JsFuture;
+
+#[wasm_bindgen(typescript_custom_section)]
+const STORAGE: &'static str = r#"
+/**
+ * The storage of the key store, implemented by the app, e.g. over IndexedDB.
+ *
+ * All key material and saved groups are written through this interface.
+ * Values are opaque byte strings that are stored under opaque keys. The
+ * methods may also return their result directly instead of a promise.
+ */
+export interface Storage {
+  /** Read the value stored for `key`, or `undefined` if there is none. */
+  read(key: Uint8Array): Promise<Uint8Array | undefined>;
+  /** Store `value` for `key`, replacing any existing value. */
+  write(key: Uint8Array, value: Uint8Array): Promise<void>;
+  /**
+   * Delete the value stored for `key`. Deleting a key that doesn't exist is
+   * not an error.
+   */
+  delete(key: Uint8Array): Promise<void>;
+}
+"#;
+
+#[wasm_bindgen]
+extern "C" {
+    /// The JavaScript `Storage` interface.
+    #[wasm_bindgen(typescript_type = "Storage")]
+    pub type Storage;
+
+    #[wasm_bindgen(method, catch)]
+    fn read(this: &Storage, key: Uint8Array) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(method, catch)]
+    fn write(this: &Storage, key: Uint8Array, value: Uint8Array) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(method, catch)]
+    fn delete(this: &Storage, key: Uint8Array) -> Result<JsValue, JsValue>;
+}
+
+/// Errors thrown by the [`Storage`].
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum StorageError {
+    /// The storage threw an error or rejected the promise.
+    #[error("The storage failed: {0}")]
+    Backend(String),
+    /// The storage returned a value that is not a `Uint8Array`.
+    #[error("The storage returned a value that is not a Uint8Array.")]
+    InvalidValue,
+}
+
+impl From<JsValue> for StorageError {
+    fn from(value: JsValue) -> Self {
+        match value.dyn_ref::<js_sys::Error>() {
+            Some(error) => Self::Backend(error.message().into()),
+            None => Self::Backend(format!("{value:?}")),
+        }
+    }
+}
+
+/// Wait for the result of a storage callback, which is either a promise or
+/// the value itself.
+async fn resolve(result: Result<JsValue, JsValue>) -> Result<JsValue, StorageError> {
+    Ok(JsFuture::from(Promise::resolve(&result?)).await?)
+}
+
+/// The asynchronous key store on top of a [`Storage`].
+pub struct JsKeyStore {
+    storage: Storage,
+}
+
+impl std::fmt::Debug for JsKeyStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JsKeyStore").finish_non_exhaustive()
+    }
+}
+
+#[async_trait(?Send)]
+impl AsyncOpenMlsKeyStore for JsKeyStore {
+    /// The error type returned by the [`JsKeyStore`].
+    type Error = StorageError;
+
+    /// Store the value `v` for ID `k`. An existing value is overwritten.
+    ///
+    /// Returns an error if storing fails.
+    async fn store(&self, k: &[u8], v: &[u8]) -> Result<(), Self::Error> {
+        resolve(self.storage.write(k.into(), v.into())).await?;
+        Ok(())
+    }
+
+    /// Read the value stored for ID `k`.
+    ///
+    /// Returns [`None`] if no value is stored for `k` and an error if reading
+    /// fails.
+    async fn read(&self, k: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+        let value = resolve(self.storage.read(k.into())).await?;
+        if value.is_undefined() || value.is_null() {
+            return Ok(None);
+        }
+        match value.dyn_into::<Uint8Array>() {
+            Ok(value) => Ok(Some(value.to_vec())),
+            Err(_) => Err(StorageError::InvalidValue),
+        }
+    }
+
+    /// Delete the value stored for ID `k`.
+    ///
+    /// Returns an error if deleting fails.
+    async fn delete(&self, k: &[u8]) -> Result<(), Self::Error> {
+        resolve(self.storage.delete(k.into())).await?;
+        Ok(())
+    }
+}
+
+/// The [`AsyncOpenMlsProvider`] behind a [`Provider`].
+#[derive(Debug)]
+pub struct JsProvider {
+    crypto: RustCrypto,
+    key_store: JsKeyStore,
+}
+
+impl AsyncOpenMlsProvider for JsProvider {
+    type CryptoProvider = RustCrypto;
+    type RandProvider = RustCrypto;
+    type KeyStoreProvider = JsKeyStore;
+    type ClockProvider = SystemClock;
+
+    fn crypto(&self) -> &Self::CryptoProvider {
+        &self.crypto
+    }
+
+    fn rand(&self) -> &Self::RandProvider {
+        &self.crypto
+    }
+
+    fn key_store(&self) -> &Self::KeyStoreProvider {
+        &self.key_store
+    }
+
+    fn clock(&self) -> &Self::ClockProvider {
+        &SystemClock
+    }
+}
+
+/// The provider, holding the crypto provider and the key store.
+///
+/// It uses the RustCrypto crypto provider and stores all key material and
+/// saved groups in a `Storage`.
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct Provider {
+    // Operations hold on to the provider until they complete.
+    pub(crate) provider: Rc<JsProvider>,
+}
+
+#[wasm_bindgen]
+impl Provider {
+    /// Create a provider that stores its state in `storage`.
+    #[wasm_bindgen(constructor)]
+    pub fn new(storage: Storage) -> Self {
+        Self {
+            provider: Rc::new(JsProvider {
+                crypto: RustCrypto::default(),
+                key_store: JsKeyStore { storage },
+            }),
+        }
+    }
+}
@@ -0,0 +1,118 @@
+// Tests for the JavaScript API. Build the package for Node.js first:
+//
+//   wasm-pack build --target nodejs wasm
+//   node --test wasm/tests
+import assert from "node:assert/strict";
+import { test } from "node:test";
+
+import { Identity, MlsGroup, Provider } from "../pkg/openmls_wasm.js";
+
+/** A `Storage` that keeps the values in memory. */
+class MemoryStorage {
+  values = new Map();
+
+  async read(key) {
+    return this.values.get(key.toString());
+  }
+
+  async write(key, value) {
+    this.values.set(key.toString(), value);
+  }
+
+  async delete(key) {
+    this.values.delete(key.toString());
+  }
+}
+
+const encoder = new TextEncoder();
+const bytes = (string) => encoder.encode(string);
+
+test("core flows", async () => {
+  const aliceProvider = new Provider(new MemoryStorage());
+  const alice = new Identity(aliceProvider, bytes("Alice"));
+  const bobProvider = new Provider(new MemoryStorage());
+  const bob = new Identity(bobProvider, bytes("Bob"));
+
+  // Alice creates a group and adds Bob.
+  const aliceGroup = await MlsGroup.create(aliceProvider, alice, bytes("group"));
+  const { commit, welcome } = await aliceGroup.addMembers(aliceProvider, alice, [
+    await bob.keyPackage(bobProvider),
+  ]);
+  assert.ok(commit instanceof Uint8Array);
+  await aliceGroup.mergePendingCommit(aliceProvider);
+  assert.equal(aliceGroup.epoch, 1n);
+
+  const bobGroup = await MlsGroup.join(bobProvider, welcome);
+  assert.deepEqual(bobGroup.groupId, bytes("group"));
+  assert.equal(bobGroup.ownLeafIndex, 1);
+  assert.deepEqual(
+    bobGroup.members().map((member) => member.identity),
+    [bytes("Alice"), bytes("Bob")],
+  );
+
+  // Application messages
+  const message = await aliceGroup.encrypt(aliceProvider, alice, bytes("Hi Bob"));
+  const processed = await bobGroup.processMessage(bobProvider, message);
+  assert.equal(processed.kind, "application");
+  assert.deepEqual(processed.sender, bytes("Alice"));
+  assert.deepEqual(processed.plaintext, bytes("Hi Bob"));
+
+  // Bob updates his leaf.
+  const update = await bobGroup.selfUpdate(bobProvider, bob);
+  await bobGroup.mergePendingCommit(bobProvider);
+  assert.equal((await aliceGroup.processMessage(aliceProvider, update)).kind, "commit");
+  assert.deepEqual(
+    aliceGroup.exportSecret(aliceProvider, "label", new Uint8Array(), 32),
+    bobGroup.exportSecret(bobProvider, "label", new Uint8Array(), 32),
+  );
+
+  // Alice removes Bob.
+  const remove = await aliceGroup.removeMembers(aliceProvider, alice, [1]);
+  await aliceGroup.mergePendingCommit(aliceProvider);
+  await bobGroup.processMessage(bobProvider, remove);
+  assert.equal(bobGroup.isActive, false);
+});
+
+test("persistence", async () => {
+  const storage = new MemoryStorage();
+  let provider = new Provider(storage);
+  let alice = new Identity(provider, bytes("Alice"));
+  const group = await MlsGroup.create(provider, alice, bytes("group"));
+  await group.save(provider);
+
+  provider = new Provider(storage);
+  alice = Identity.fromBytes(alice.toBytes());
+  const loaded = await MlsGroup.load(provider, bytes("group"));
+  await loaded.selfUpdate(provider, alice);
+  await loaded.mergePendingCommit(provider);
+  assert.equal(loaded.epoch, 1n);
+});
+
+test("errors", async () => {
+  const provider = new Provider(new MemoryStorage());
+  const alice = new Identity(provider, bytes("Alice"));
+
+  await assert.rejects(MlsGroup.load(provider, bytes("unknown")), { name: "NotFoundError" });
+  assert.throws(() => new Identity(provider, bytes("Alice"), 0xffff), {
+    name: "InvalidArgumentError",
+  });
+
+  // Only one operation can run on a group at a time.
+  const group = await MlsGroup.create(provider, alice, bytes("group"));
+  const update = group.selfUpdate(provider, alice);
+  assert.throws(() => group.epoch, { name: "GroupStateError" });
+  await update;
+
+  // Errors of the storage are passed through.
+  const failing = new Provider({
+    read: async () => undefined,
+    write: async () => {
+      throw new Error("disk full");
+    },
+    delete: async () => {},
+  });
+  await assert.rejects(new Identity(failing, bytes("Alice")).keyPackage(failing), {
+    name: "StorageError",
+    message: /disk full/,
+  });
+});